@@ -0,0 +1,8 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // 使用随依赖发布的 protoc，构建环境不需要另外安装
+    unsafe {
+        std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+    }
+    tonic_build::configure().compile_protos(&["proto/order_book.proto", "proto/feed.proto"], &["proto"])?;
+    Ok(())
+}
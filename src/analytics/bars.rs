@@ -0,0 +1,205 @@
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use rust_decimal::Decimal;
+
+use crate::kline::Candle;
+use crate::trade::Trade;
+
+/// 每个交易对保留的已完成 bar 数量
+const MAX_CLOSED_BARS: usize = 500;
+
+/// bar 的划分方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarKind {
+    /// 按固定时长（毫秒）
+    Time(u64),
+    /// 按固定成交量，仅适用于成交
+    Volume(Decimal),
+    /// 按固定成交笔数，仅适用于成交
+    Tick(u64),
+}
+
+/// bar 的数据来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarSource {
+    /// 采样的中间价，只能生成时间 bar，成交量为 0
+    Mid,
+    /// 归集成交
+    Trade,
+}
+
+impl BarKind {
+    /// 从命令行参数解析，例如 "time:60000"、"volume:10"、"tick:100"
+    pub fn parse(spec: &str) -> Option<Self> {
+        let (kind, size) = spec.split_once(':')?;
+        match kind.to_ascii_lowercase().as_str() {
+            "time" => size.parse::<u64>().ok().filter(|ms| *ms > 0).map(BarKind::Time),
+            "volume" => size.parse::<Decimal>().ok().filter(|volume| *volume > Decimal::ZERO).map(BarKind::Volume),
+            "tick" => size.parse::<u64>().ok().filter(|ticks| *ticks > 0).map(BarKind::Tick),
+            _ => None,
+        }
+    }
+}
+
+impl BarSource {
+    /// 从命令行参数解析
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "mid" => Some(BarSource::Mid),
+            "trade" => Some(BarSource::Trade),
+            _ => None,
+        }
+    }
+}
+
+/// 单个交易对的 bar 序列
+#[derive(Debug, Clone, Default)]
+struct BarSeries {
+    current: Option<Candle>,
+    closed: VecDeque<Candle>,
+}
+
+impl BarSeries {
+    /// 收盘当前 bar 并保存
+    fn close(&mut self, close_time: u64) -> Option<Candle> {
+        let mut bar = self.current.take()?;
+        bar.close_time = close_time;
+        bar.closed = true;
+        if self.closed.len() >= MAX_CLOSED_BARS {
+            self.closed.pop_front();
+        }
+        self.closed.push_back(bar);
+        Some(bar)
+    }
+
+    /// 把价格和成交量计入当前 bar，没有当前 bar 时以 `open_time` 开盘
+    fn add(&mut self, open_time: u64, close_time: u64, price: Decimal, quantity: Decimal, trades: u64) {
+        let bar = self.current.get_or_insert(Candle {
+            open_time,
+            close_time,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: Decimal::ZERO,
+            quote_volume: Decimal::ZERO,
+            trades: 0,
+            closed: false,
+        });
+        bar.high = bar.high.max(price);
+        bar.low = bar.low.min(price);
+        bar.close = price;
+        bar.close_time = bar.close_time.max(close_time);
+        bar.volume += quantity;
+        bar.quote_volume += price * quantity;
+        bar.trades += trades;
+    }
+}
+
+/// OHLCV bar 生成器
+///
+/// 时间 bar 在收到下一个时段的数据时收盘，没有数据的时段不生成 bar；
+/// 成交量 bar 会把跨越边界的成交拆到相邻的 bar 中
+#[derive(Debug, Clone)]
+pub struct BarBuilder {
+    kind: BarKind,
+    source: BarSource,
+    /// (交易所, 交易对) -> bar 序列
+    series: HashMap<(String, String), BarSeries>,
+}
+
+impl BarBuilder {
+    /// 创建生成器，中间价只支持时间 bar
+    pub fn new(kind: BarKind, source: BarSource) -> Result<Self, Box<dyn Error>> {
+        if source == BarSource::Mid && !matches!(kind, BarKind::Time(_)) {
+            return Err(format!("中间价只能生成时间 bar: {:?}", kind).into());
+        }
+        Ok(BarBuilder {
+            kind,
+            source,
+            series: HashMap::new(),
+        })
+    }
+
+    /// bar 的划分方式
+    pub fn kind(&self) -> BarKind {
+        self.kind
+    }
+
+    /// bar 的数据来源
+    pub fn source(&self) -> BarSource {
+        self.source
+    }
+
+    /// 更新采样的中间价，返回收盘的 bar
+    pub fn on_mid(&mut self, venue: &str, symbol: &str, mid: Decimal, local_time: u64) -> Vec<Candle> {
+        if self.source != BarSource::Mid {
+            return Vec::new();
+        }
+        self.add(venue, symbol, mid, Decimal::ZERO, 0, local_time)
+    }
+
+    /// 累计一笔成交，返回收盘的 bar
+    pub fn on_trade(&mut self, venue: &str, symbol: &str, trade: &Trade) -> Vec<Candle> {
+        if self.source != BarSource::Trade {
+            return Vec::new();
+        }
+        self.add(venue, symbol, trade.price, trade.quantity, 1, trade.trade_time)
+    }
+
+    /// 当前未收盘的 bar
+    pub fn current(&self, venue: &str, symbol: &str) -> Option<&Candle> {
+        self.series.get(&(venue.to_string(), symbol.to_string()))?.current.as_ref()
+    }
+
+    /// 最近 `n` 根已收盘的 bar，从旧到新
+    pub fn closed(&self, venue: &str, symbol: &str, n: usize) -> Vec<Candle> {
+        let Some(series) = self.series.get(&(venue.to_string(), symbol.to_string())) else {
+            return Vec::new();
+        };
+        let skip = series.closed.len().saturating_sub(n);
+        series.closed.iter().skip(skip).copied().collect()
+    }
+
+    fn add(&mut self, venue: &str, symbol: &str, price: Decimal, quantity: Decimal, trades: u64, time: u64) -> Vec<Candle> {
+        let series = self.series.entry((venue.to_string(), symbol.to_string())).or_default();
+        let mut closed = Vec::new();
+        match self.kind {
+            BarKind::Time(interval_ms) => {
+                let open_time = time - time % interval_ms;
+                if let Some(bar) = series.current
+                    && bar.open_time != open_time
+                {
+                    closed.extend(series.close(bar.close_time));
+                }
+                series.add(open_time, open_time + interval_ms - 1, price, quantity, trades);
+            }
+            BarKind::Volume(bar_volume) => {
+                let mut remaining = quantity;
+                let mut trades = trades;
+                loop {
+                    let space = bar_volume - series.current.map_or(Decimal::ZERO, |bar| bar.volume);
+                    let filled = remaining.min(space);
+                    series.add(time, time, price, filled, trades);
+                    // 拆分的成交只在第一根 bar 中计一笔
+                    trades = 0;
+                    remaining -= filled;
+                    if filled < space {
+                        break;
+                    }
+                    closed.extend(series.close(time));
+                    if remaining.is_zero() {
+                        break;
+                    }
+                }
+            }
+            BarKind::Tick(ticks) => {
+                series.add(time, time, price, quantity, trades);
+                if series.current.is_some_and(|bar| bar.trades >= ticks) {
+                    closed.extend(series.close(time));
+                }
+            }
+        }
+        closed
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use rust_decimal::Decimal;
+use serde_json::json;
+
+use crate::metrics;
+use crate::order_book::OrderBook;
+
+/// 默认统计区间（毫秒）
+pub const DEFAULT_CHURN_INTERVAL_MS: u64 = 10_000;
+
+/// 一条增量更新相对本地订单薄的档位变化
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LevelChanges {
+    /// 原来没有的价格
+    pub added: u64,
+    /// 删除已有的价格
+    pub removed: u64,
+    /// 已有价格的数量改变
+    pub modified: u64,
+}
+
+impl LevelChanges {
+    /// 在应用之前与订单薄比较；数量不变的档位和删除不存在的价格不计入
+    pub fn between(book: &OrderBook, bids: &[(Decimal, Decimal)], asks: &[(Decimal, Decimal)]) -> Self {
+        let mut changes = LevelChanges::default();
+        for (side, levels) in [(&book.bids, bids), (&book.asks, asks)] {
+            for (price, quantity) in levels {
+                match (side.get(price), quantity.is_zero()) {
+                    (None, false) => changes.added += 1,
+                    (Some(_), true) => changes.removed += 1,
+                    (Some(old), false) if old != quantity => changes.modified += 1,
+                    _ => {}
+                }
+            }
+        }
+        changes
+    }
+}
+
+/// 一段时间内的更新计数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChurnCounts {
+    /// 增量更新消息数
+    pub messages: u64,
+    pub added: u64,
+    pub removed: u64,
+    pub modified: u64,
+    /// 消息携带的档位总数
+    pub levels: u64,
+    /// 单条消息携带的最多档位数
+    pub max_levels: u64,
+}
+
+impl ChurnCounts {
+    fn add(&mut self, changes: LevelChanges, levels: u64) {
+        self.messages += 1;
+        self.added += changes.added;
+        self.removed += changes.removed;
+        self.modified += changes.modified;
+        self.levels += levels;
+        self.max_levels = self.max_levels.max(levels);
+    }
+
+    /// 平均每条消息携带的档位数
+    pub fn mean_levels(&self) -> f64 {
+        if self.messages == 0 {
+            return 0.0;
+        }
+        self.levels as f64 / self.messages as f64
+    }
+
+    fn to_json(self) -> serde_json::Value {
+        json!({
+            "messages": self.messages,
+            "added": self.added,
+            "removed": self.removed,
+            "modified": self.modified,
+            "meanLevels": self.mean_levels(),
+            "maxLevels": self.max_levels,
+        })
+    }
+}
+
+/// 单个交易对的状态
+#[derive(Debug, Clone, Copy, Default)]
+struct ChurnState {
+    /// 当前区间开始时间（毫秒）
+    start: u64,
+    current: ChurnCounts,
+    /// 最近结束的区间 (开始时间, 计数)
+    last: Option<(u64, ChurnCounts)>,
+    total: ChurnCounts,
+}
+
+/// 一个交易对的更新统计
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChurnSummary {
+    pub venue: String,
+    pub symbol: String,
+    pub interval_ms: u64,
+    /// 最近结束的区间 (开始时间, 计数)，第一个区间尚未结束时为 None
+    pub last_interval: Option<(u64, ChurnCounts)>,
+    /// 启动以来的累计
+    pub total: ChurnCounts,
+}
+
+impl ChurnSummary {
+    /// 最近结束区间的每秒更新数
+    pub fn updates_per_second(&self) -> Option<f64> {
+        self.last_interval.map(|(_, counts)| counts.messages as f64 * 1000.0 / self.interval_ms as f64)
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "venue": self.venue,
+            "symbol": self.symbol,
+            "intervalMs": self.interval_ms,
+            "updatesPerSecond": self.updates_per_second(),
+            "lastInterval": self.last_interval.map(|(start, counts)| {
+                let mut value = counts.to_json();
+                value["start"] = json!(start);
+                value
+            }),
+            "total": self.total.to_json(),
+        })
+    }
+}
+
+/// 按交易对统计增量更新频率、档位变化（新增、删除、修改）和消息大小，用于容量规划
+///
+/// 按固定区间统计，区间边界对齐到区间长度的整数倍；消息大小以携带的档位数计。
+/// 在主循环和指标服务之间共享，记录时同时写入指标：档位变化计数器、消息档位数直方图，
+/// 以及区间结束时的每秒更新数
+#[derive(Debug)]
+pub struct ChurnMonitor {
+    interval_ms: u64,
+    states: Mutex<HashMap<(String, String), ChurnState>>,
+}
+
+impl Default for ChurnMonitor {
+    fn default() -> Self {
+        ChurnMonitor::new(DEFAULT_CHURN_INTERVAL_MS)
+    }
+}
+
+impl ChurnMonitor {
+    /// 创建统计，`interval_ms` 为统计区间（毫秒）
+    pub fn new(interval_ms: u64) -> Self {
+        ChurnMonitor {
+            interval_ms: interval_ms.max(1),
+            states: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 记录一条已应用的增量更新，`levels` 为消息携带的档位数
+    pub fn record(&self, venue: &str, symbol: &str, changes: LevelChanges, levels: usize, local_time: u64) {
+        let start = local_time - local_time % self.interval_ms;
+        let mut states = self.lock();
+        let state = states.entry((venue.to_string(), symbol.to_string()))
+            .or_insert_with(|| ChurnState { start, ..ChurnState::default() });
+        if state.start != start {
+            // 中间没有消息的区间计数为 0，最近结束的区间是紧挨当前区间的那个
+            let previous = start.saturating_sub(self.interval_ms);
+            let last = if state.start == previous { state.current } else { ChurnCounts::default() };
+            state.last = Some((previous, last));
+            state.start = start;
+            state.current = ChurnCounts::default();
+            metrics::updates_per_second(venue, symbol, last.messages as f64 * 1000.0 / self.interval_ms as f64);
+        }
+        state.current.add(changes, levels as u64);
+        state.total.add(changes, levels as u64);
+        metrics::level_changes(venue, symbol, changes.added, changes.removed, changes.modified, levels);
+    }
+
+    /// 交易对的更新统计
+    pub fn summary(&self, venue: &str, symbol: &str) -> Option<ChurnSummary> {
+        self.lock().get(&(venue.to_string(), symbol.to_string())).map(|state| self.summarize(venue, symbol, state))
+    }
+
+    /// 所有交易对的更新统计，按交易所和交易对排序
+    pub fn summaries(&self) -> Vec<ChurnSummary> {
+        let mut summaries: Vec<_> = self.lock().iter()
+            .map(|((venue, symbol), state)| self.summarize(venue, symbol, state))
+            .collect();
+        summaries.sort_by(|a, b| (&a.venue, &a.symbol).cmp(&(&b.venue, &b.symbol)));
+        summaries
+    }
+
+    fn summarize(&self, venue: &str, symbol: &str, state: &ChurnState) -> ChurnSummary {
+        ChurnSummary {
+            venue: venue.to_string(),
+            symbol: symbol.to_string(),
+            interval_ms: self.interval_ms,
+            last_interval: state.last,
+            total: state.total,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<(String, String), ChurnState>> {
+        // 持锁的代码不会 panic，锁中毒时数据仍然可用
+        self.states.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
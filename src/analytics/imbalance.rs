@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+use rust_decimal::Decimal;
+
+use crate::order_book::OrderBook;
+
+/// 不平衡度穿越阈值的方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrossDirection {
+    /// 向上穿越
+    Up,
+    /// 向下穿越
+    Down,
+}
+
+/// 一次不平衡度穿越阈值
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImbalanceCross {
+    /// 交易所名称
+    pub venue: String,
+    /// 交易对
+    pub symbol: String,
+    /// 当前不平衡度
+    pub imbalance: Decimal,
+    /// 被穿越的阈值
+    pub threshold: Decimal,
+    /// 穿越方向
+    pub direction: CrossDirection,
+}
+
+/// 不平衡度监控，订单薄更新后计算前N档不平衡度，穿越阈值时产生事件
+#[derive(Debug, Clone)]
+pub struct ImbalanceMonitor {
+    n_levels: usize,
+    /// 升序排列的阈值，例如 [-0.6, 0.6]
+    thresholds: Vec<Decimal>,
+    /// (交易所, 交易对) -> 当前所在区间（低于几个阈值）
+    zones: HashMap<(String, String), usize>,
+}
+
+impl ImbalanceMonitor {
+    /// 创建监控
+    ///
+    /// # 参数
+    ///
+    /// * `n_levels` - 计算不平衡度的档位数
+    /// * `thresholds` - 阈值，取值 -1 到 1
+    pub fn new(n_levels: usize, thresholds: &[Decimal]) -> Self {
+        let mut thresholds = thresholds.to_vec();
+        thresholds.sort();
+        thresholds.dedup();
+        ImbalanceMonitor {
+            n_levels,
+            thresholds,
+            zones: HashMap::new(),
+        }
+    }
+
+    /// 计算不平衡度的档位数
+    pub fn n_levels(&self) -> usize {
+        self.n_levels
+    }
+
+    /// 更新交易对的不平衡度，返回本次穿越的阈值（一次跳变可能穿越多个阈值）
+    ///
+    /// 第一次计算只记录所在区间，不产生穿越
+    pub fn update(&mut self, venue: &str, symbol: &str, book: &OrderBook) -> Vec<ImbalanceCross> {
+        let Some(imbalance) = book.imbalance(self.n_levels) else {
+            return Vec::new();
+        };
+        let zone = self.thresholds.iter().filter(|threshold| imbalance >= **threshold).count();
+        let previous = self.zones.insert((venue.to_string(), symbol.to_string()), zone);
+        let Some(previous) = previous else {
+            return Vec::new();
+        };
+
+        let cross = |threshold: &Decimal, direction| ImbalanceCross {
+            venue: venue.to_string(),
+            symbol: symbol.to_string(),
+            imbalance,
+            threshold: *threshold,
+            direction,
+        };
+        if zone > previous {
+            self.thresholds[previous..zone].iter().map(|threshold| cross(threshold, CrossDirection::Up)).collect()
+        } else {
+            self.thresholds[zone..previous].iter().rev().map(|threshold| cross(threshold, CrossDirection::Down)).collect()
+        }
+    }
+}
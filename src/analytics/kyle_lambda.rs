@@ -0,0 +1,126 @@
+use std::collections::{HashMap, VecDeque};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+/// 单个交易对的状态
+#[derive(Debug, Clone, Default)]
+struct LambdaState {
+    /// 当前区间开始时间（毫秒）
+    interval_start: u64,
+    /// 当前区间开始时的中间价
+    start_mid: Option<f64>,
+    /// 最新中间价
+    last_mid: Option<f64>,
+    /// 当前区间的带方向成交量，主动买入为正
+    flow: f64,
+    /// 最近区间的 (带方向成交量, 中间价变化)
+    samples: VecDeque<(f64, f64)>,
+}
+
+impl LambdaState {
+    /// 到达新区间时结束当前区间并记录样本
+    fn roll(&mut self, interval_ms: u64, window: usize, time: u64) {
+        if time < self.interval_start + interval_ms {
+            return;
+        }
+        if let (Some(start_mid), Some(last_mid)) = (self.start_mid, self.last_mid) {
+            if self.samples.len() >= window {
+                self.samples.pop_front();
+            }
+            self.samples.push_back((self.flow, last_mid - start_mid));
+        }
+        self.interval_start = time - time % interval_ms;
+        self.start_mid = self.last_mid;
+        self.flow = 0.0;
+    }
+}
+
+/// Kyle's lambda 价格冲击估计
+///
+/// 按固定区间汇总带方向成交量和中间价变化，在最近 `window` 个区间上做最小二乘回归，
+/// 斜率即单位成交量引起的价格变化
+#[derive(Debug, Clone)]
+pub struct KyleLambdaEstimator {
+    interval_ms: u64,
+    window: usize,
+    /// (交易所, 交易对) -> 状态
+    states: HashMap<(String, String), LambdaState>,
+}
+
+impl KyleLambdaEstimator {
+    /// 创建估计器
+    ///
+    /// # 参数
+    ///
+    /// * `interval_ms` - 汇总区间（毫秒）
+    /// * `window` - 参与回归的区间数
+    pub fn new(interval_ms: u64, window: usize) -> Self {
+        KyleLambdaEstimator {
+            interval_ms: interval_ms.max(1),
+            window: window.max(2),
+            states: HashMap::new(),
+        }
+    }
+
+    /// 更新中间价
+    pub fn update_mid(&mut self, venue: &str, symbol: &str, mid: Decimal, local_time: u64) {
+        let Some(mid) = mid.to_f64() else {
+            return;
+        };
+        let state = self.states.entry((venue.to_string(), symbol.to_string())).or_default();
+        state.roll(self.interval_ms, self.window, local_time);
+        state.last_mid = Some(mid);
+        if state.start_mid.is_none() {
+            state.start_mid = Some(mid);
+        }
+    }
+
+    /// 累计一笔成交，`local_time` 应与中间价使用同一时钟
+    ///
+    /// # 参数
+    ///
+    /// * `buyer_is_maker` - 买方是否为挂单方，是则为主动卖出
+    pub fn add_trade(&mut self, venue: &str, symbol: &str, quantity: Decimal, buyer_is_maker: bool, local_time: u64) {
+        let Some(quantity) = quantity.to_f64() else {
+            return;
+        };
+        let state = self.states.entry((venue.to_string(), symbol.to_string())).or_default();
+        state.roll(self.interval_ms, self.window, local_time);
+        state.flow += if buyer_is_maker { -quantity } else { quantity };
+    }
+
+    /// 已跟踪的 (交易所, 交易对)
+    pub fn symbols(&self) -> Vec<(String, String)> {
+        let mut symbols: Vec<(String, String)> = self.states.keys().cloned().collect();
+        symbols.sort();
+        symbols
+    }
+
+    /// 参与回归的区间数
+    pub fn sample_count(&self, venue: &str, symbol: &str) -> usize {
+        self.state(venue, symbol).map_or(0, |state| state.samples.len())
+    }
+
+    /// 估计的 lambda（每单位带方向成交量对应的中间价变化）
+    ///
+    /// 样本少于两个或成交量没有波动时返回 None
+    pub fn lambda(&self, venue: &str, symbol: &str) -> Option<f64> {
+        let samples = &self.state(venue, symbol)?.samples;
+        if samples.len() < 2 {
+            return None;
+        }
+        let n = samples.len() as f64;
+        let mean_flow = samples.iter().map(|(flow, _)| flow).sum::<f64>() / n;
+        let mean_change = samples.iter().map(|(_, change)| change).sum::<f64>() / n;
+        let covariance: f64 = samples.iter().map(|(flow, change)| (flow - mean_flow) * (change - mean_change)).sum();
+        let variance: f64 = samples.iter().map(|(flow, _)| (flow - mean_flow).powi(2)).sum();
+        if variance == 0.0 {
+            return None;
+        }
+        Some(covariance / variance)
+    }
+
+    fn state(&self, venue: &str, symbol: &str) -> Option<&LambdaState> {
+        self.states.get(&(venue.to_string(), symbol.to_string()))
+    }
+}
@@ -0,0 +1,15 @@
+//! 基于本地订单薄和成交流的行情分析指标
+
+pub mod bars;
+pub mod churn;
+pub mod imbalance;
+pub mod kyle_lambda;
+pub mod ofi;
+pub mod queue;
+pub mod spread_stats;
+pub mod trade_spread;
+pub mod update_rate;
+pub mod volatility;
+pub mod volume_profile;
+pub mod vpin;
+pub mod wall;
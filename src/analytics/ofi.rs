@@ -0,0 +1,153 @@
+use std::collections::{HashMap, VecDeque};
+use rust_decimal::Decimal;
+
+use crate::order_book::OrderBook;
+
+/// 一个统计区间内的订单流不平衡
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OfiInterval {
+    /// 交易所名称
+    pub venue: String,
+    /// 交易对
+    pub symbol: String,
+    /// 区间开始时间（毫秒）
+    pub start: u64,
+    /// 区间结束时间（毫秒，不含）
+    pub end: u64,
+    /// 区间内 OFI 之和
+    pub ofi: Decimal,
+    /// 区间内的订单薄更新次数
+    pub updates: usize,
+}
+
+/// 单个交易对的 OFI 状态
+#[derive(Debug, Clone)]
+struct OfiState {
+    /// 上一次的 (买一价, 买一量, 卖一价, 卖一量)
+    previous: Option<(Decimal, Decimal, Decimal, Decimal)>,
+    /// 滚动窗口内的 (本地时间, OFI)
+    window: VecDeque<(u64, Decimal)>,
+    /// 滚动窗口内 OFI 之和
+    window_sum: Decimal,
+    /// 当前未结束的区间
+    interval: Option<OfiInterval>,
+}
+
+/// 订单流不平衡（Order Flow Imbalance）计算器
+///
+/// 每次订单薄更新按买一卖一的变化计算 OFI：
+/// 买一价上升或不变时计入新的买一量，下降或不变时减去旧的买一量，
+/// 卖一价一侧方向相反，结果为买方贡献减去卖方贡献
+#[derive(Debug, Clone)]
+pub struct OfiCalculator {
+    window_ms: u64,
+    interval_ms: u64,
+    /// (交易所, 交易对) -> 状态
+    states: HashMap<(String, String), OfiState>,
+}
+
+impl OfiCalculator {
+    /// 创建计算器
+    ///
+    /// # 参数
+    ///
+    /// * `window_ms` - 滚动窗口长度（毫秒）
+    /// * `interval_ms` - 区间汇总长度（毫秒），按本地时间对齐
+    pub fn new(window_ms: u64, interval_ms: u64) -> Self {
+        OfiCalculator {
+            window_ms,
+            interval_ms: interval_ms.max(1),
+            states: HashMap::new(),
+        }
+    }
+
+    /// 滚动窗口长度（毫秒）
+    pub fn window_ms(&self) -> u64 {
+        self.window_ms
+    }
+
+    /// 区间汇总长度（毫秒）
+    pub fn interval_ms(&self) -> u64 {
+        self.interval_ms
+    }
+
+    /// 订单薄更新后计算本次 OFI，进入新区间时返回上一个已结束的区间
+    ///
+    /// 第一次更新只记录买一卖一，不产生 OFI
+    pub fn update(&mut self, venue: &str, symbol: &str, book: &OrderBook, local_time: u64) -> Option<OfiInterval> {
+        let (Some((bid_price, bid_quantity)), Some((ask_price, ask_quantity))) = (book.best_bid(), book.best_ask()) else {
+            return None;
+        };
+        let state = self.states.entry((venue.to_string(), symbol.to_string())).or_insert_with(|| OfiState {
+            previous: None,
+            window: VecDeque::new(),
+            window_sum: Decimal::ZERO,
+            interval: None,
+        });
+        let previous = state.previous.replace((bid_price, bid_quantity, ask_price, ask_quantity));
+        let (prev_bid_price, prev_bid_quantity, prev_ask_price, prev_ask_quantity) = previous?;
+
+        let mut ofi = Decimal::ZERO;
+        if bid_price >= prev_bid_price {
+            ofi += bid_quantity;
+        }
+        if bid_price <= prev_bid_price {
+            ofi -= prev_bid_quantity;
+        }
+        if ask_price <= prev_ask_price {
+            ofi -= ask_quantity;
+        }
+        if ask_price >= prev_ask_price {
+            ofi += prev_ask_quantity;
+        }
+
+        state.window.push_back((local_time, ofi));
+        state.window_sum += ofi;
+        while let Some((time, value)) = state.window.front().copied() {
+            if time + self.window_ms > local_time {
+                break;
+            }
+            state.window_sum -= value;
+            state.window.pop_front();
+        }
+
+        let start = local_time - local_time % self.interval_ms;
+        let finished = match &state.interval {
+            Some(interval) if interval.start == start => None,
+            _ => state.interval.replace(OfiInterval {
+                venue: venue.to_string(),
+                symbol: symbol.to_string(),
+                start,
+                end: start + self.interval_ms,
+                ofi: Decimal::ZERO,
+                updates: 0,
+            }),
+        };
+        if let Some(interval) = state.interval.as_mut() {
+            interval.ofi += ofi;
+            interval.updates += 1;
+        }
+        finished
+    }
+
+    /// 滚动窗口内的 OFI 之和
+    pub fn rolling(&self, venue: &str, symbol: &str) -> Option<Decimal> {
+        self.state(venue, symbol).map(|state| state.window_sum)
+    }
+
+    /// 滚动窗口内逐次更新的 OFI 时间序列 (本地时间, OFI)
+    pub fn series(&self, venue: &str, symbol: &str) -> Vec<(u64, Decimal)> {
+        self.state(venue, symbol)
+            .map(|state| state.window.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// 当前尚未结束的区间
+    pub fn current_interval(&self, venue: &str, symbol: &str) -> Option<&OfiInterval> {
+        self.state(venue, symbol).and_then(|state| state.interval.as_ref())
+    }
+
+    fn state(&self, venue: &str, symbol: &str) -> Option<&OfiState> {
+        self.states.get(&(venue.to_string(), symbol.to_string()))
+    }
+}
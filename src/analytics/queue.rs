@@ -0,0 +1,224 @@
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+use crate::order_book::{OrderBook, Side};
+use crate::trade::Trade;
+
+/// 假想的挂单
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestingOrder {
+    /// 交易所名称
+    pub venue: String,
+    /// 交易对
+    pub symbol: String,
+    /// 挂单所在盘口方向，买单为 Side::Bid
+    pub side: Side,
+    /// 挂单价格
+    pub price: Decimal,
+    /// 挂单数量
+    pub quantity: Decimal,
+}
+
+/// 挂单的排队估计
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueEstimate {
+    /// 排在前面的数量
+    pub queue_ahead: Decimal,
+    /// 已成交数量
+    pub filled: Decimal,
+    /// 剩余数量
+    pub remaining: Decimal,
+    /// 当前价位的挂单总量（不含假想挂单）
+    pub level_quantity: Decimal,
+    /// 挂单以来经过的时间（毫秒）
+    pub elapsed_ms: u64,
+    /// 队列前方的消耗速度（成交和撤单，每秒数量）
+    pub depletion_rate: Decimal,
+    /// 按当前消耗速度预计完全成交还需的时间（毫秒），速度为 0 时为 None
+    pub expected_fill_ms: Option<u64>,
+}
+
+/// 单个假想挂单的跟踪状态
+#[derive(Debug, Clone)]
+struct TrackedOrder {
+    order: RestingOrder,
+    placed_at: u64,
+    last_update: u64,
+    queue_ahead: Decimal,
+    filled: Decimal,
+    level_quantity: Decimal,
+    /// 已成交但尚未体现在深度中的数量
+    unreflected_trades: Decimal,
+    /// 挂单以来前方被成交和撤单消耗的数量
+    depleted: Decimal,
+}
+
+impl TrackedOrder {
+    fn remaining(&self) -> Decimal {
+        self.order.quantity - self.filled
+    }
+
+    /// 挂单被对手价穿越，视为全部成交
+    fn fill_all(&mut self) {
+        self.depleted += self.queue_ahead;
+        self.queue_ahead = Decimal::ZERO;
+        self.filled = self.order.quantity;
+    }
+
+    /// 前方消耗 `quantity`，超出部分成交自己的挂单
+    fn consume(&mut self, quantity: Decimal) {
+        let ahead = quantity.min(self.queue_ahead);
+        self.queue_ahead -= ahead;
+        self.depleted += ahead;
+        self.filled += (quantity - ahead).min(self.remaining());
+    }
+}
+
+/// 挂单排队位置估计
+///
+/// 挂单时排在当前价位所有挂单之后。之后在该价位上的成交先消耗前方数量，
+/// 超出部分成交自己的挂单；深度减少中无法由成交解释的部分视为撤单，
+/// 按前方数量占价位总量的比例从前方扣除；深度增加视为排在后面的新挂单
+#[derive(Debug, Clone, Default)]
+pub struct QueuePositionEstimator {
+    orders: Vec<Option<TrackedOrder>>,
+}
+
+impl QueuePositionEstimator {
+    /// 创建估计器
+    pub fn new() -> Self {
+        QueuePositionEstimator::default()
+    }
+
+    /// 放置假想挂单，返回编号
+    pub fn place(&mut self, order: RestingOrder, book: &OrderBook, local_time: u64) -> usize {
+        let level_quantity = book.level_quantity(order.side, order.price);
+        self.orders.push(Some(TrackedOrder {
+            order,
+            placed_at: local_time,
+            last_update: local_time,
+            queue_ahead: level_quantity,
+            filled: Decimal::ZERO,
+            level_quantity,
+            unreflected_trades: Decimal::ZERO,
+            depleted: Decimal::ZERO,
+        }));
+        self.orders.len() - 1
+    }
+
+    /// 撤销假想挂单
+    pub fn cancel(&mut self, id: usize) -> Option<RestingOrder> {
+        self.orders.get_mut(id)?.take().map(|tracked| tracked.order)
+    }
+
+    /// 未撤销的假想挂单 (编号, 挂单)
+    pub fn orders(&self) -> Vec<(usize, &RestingOrder)> {
+        self.orders.iter()
+            .enumerate()
+            .filter_map(|(id, tracked)| tracked.as_ref().map(|tracked| (id, &tracked.order)))
+            .collect()
+    }
+
+    /// 是否有挂在该交易对上的假想挂单
+    pub fn contains(&self, venue: &str, symbol: &str) -> bool {
+        self.tracked(venue, symbol).next().is_some()
+    }
+
+    /// 订单薄更新后根据挂单价位数量的变化更新排队位置
+    pub fn on_book_update(&mut self, venue: &str, symbol: &str, book: &OrderBook, local_time: u64) {
+        for tracked in self.tracked_mut(venue, symbol) {
+            tracked.last_update = local_time;
+            let order = &tracked.order;
+            let crossed = match order.side {
+                Side::Bid => book.best_ask().is_some_and(|(ask_price, _)| ask_price <= order.price),
+                Side::Ask => book.best_bid().is_some_and(|(bid_price, _)| bid_price >= order.price),
+            };
+            if crossed {
+                tracked.fill_all();
+                continue;
+            }
+
+            let level_quantity = book.level_quantity(order.side, order.price);
+            let decrease = tracked.level_quantity - level_quantity;
+            if decrease > Decimal::ZERO {
+                // 先用尚未体现的成交解释减少量，其余视为撤单
+                let traded = decrease.min(tracked.unreflected_trades);
+                tracked.unreflected_trades -= traded;
+                let cancelled = decrease - traded;
+                if !tracked.level_quantity.is_zero() {
+                    let cancelled_ahead = (cancelled * tracked.queue_ahead / tracked.level_quantity).min(tracked.queue_ahead);
+                    tracked.queue_ahead -= cancelled_ahead;
+                    tracked.depleted += cancelled_ahead;
+                }
+            }
+            tracked.level_quantity = level_quantity;
+            if tracked.queue_ahead > level_quantity {
+                tracked.depleted += tracked.queue_ahead - level_quantity;
+                tracked.queue_ahead = level_quantity;
+            }
+        }
+    }
+
+    /// 成交后更新排队位置，只处理在挂单价位或穿越挂单价位的被动方成交
+    pub fn on_trade(&mut self, venue: &str, symbol: &str, trade: &Trade, local_time: u64) {
+        for tracked in self.tracked_mut(venue, symbol) {
+            tracked.last_update = local_time;
+            let order = &tracked.order;
+            // 主动卖出吃买盘，主动买入吃卖盘
+            let passive_side = if trade.buyer_is_maker { Side::Bid } else { Side::Ask };
+            if passive_side != order.side {
+                continue;
+            }
+            let through = match order.side {
+                Side::Bid => trade.price < order.price,
+                Side::Ask => trade.price > order.price,
+            };
+            if through {
+                tracked.fill_all();
+            } else if trade.price == order.price {
+                tracked.unreflected_trades += trade.quantity;
+                tracked.consume(trade.quantity);
+            }
+        }
+    }
+
+    /// 假想挂单的排队估计，编号不存在或已撤销时返回 None
+    pub fn estimate(&self, id: usize) -> Option<QueueEstimate> {
+        let tracked = self.orders.get(id)?.as_ref()?;
+        let elapsed_ms = tracked.last_update.saturating_sub(tracked.placed_at);
+        let depletion_rate = if elapsed_ms == 0 {
+            Decimal::ZERO
+        } else {
+            tracked.depleted * Decimal::from(1000) / Decimal::from(elapsed_ms)
+        };
+        let remaining = tracked.remaining();
+        let expected_fill_ms = if remaining.is_zero() {
+            Some(0)
+        } else if depletion_rate.is_zero() {
+            None
+        } else {
+            ((tracked.queue_ahead + remaining) * Decimal::from(1000) / depletion_rate).to_u64()
+        };
+        Some(QueueEstimate {
+            queue_ahead: tracked.queue_ahead,
+            filled: tracked.filled,
+            remaining,
+            level_quantity: tracked.level_quantity,
+            elapsed_ms,
+            depletion_rate,
+            expected_fill_ms,
+        })
+    }
+
+    fn tracked<'a>(&'a self, venue: &'a str, symbol: &'a str) -> impl Iterator<Item = &'a TrackedOrder> {
+        self.orders.iter()
+            .flatten()
+            .filter(move |tracked| tracked.order.venue == venue && tracked.order.symbol == symbol)
+    }
+
+    fn tracked_mut<'a>(&'a mut self, venue: &'a str, symbol: &'a str) -> impl Iterator<Item = &'a mut TrackedOrder> {
+        self.orders.iter_mut()
+            .flatten()
+            .filter(move |tracked| tracked.order.venue == venue && tracked.order.symbol == symbol && !tracked.remaining().is_zero())
+    }
+}
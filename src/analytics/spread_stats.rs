@@ -0,0 +1,123 @@
+use std::collections::{HashMap, VecDeque};
+use rust_decimal::Decimal;
+
+use crate::order_book::OrderBook;
+
+/// 滚动窗口内的价差统计
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpreadSummary {
+    /// 当前价差
+    pub current: Decimal,
+    /// 时间加权平均价差
+    pub twap: Decimal,
+    /// 最小价差
+    pub min: Decimal,
+    /// 最大价差
+    pub max: Decimal,
+    /// 时间加权中位数
+    pub p50: Decimal,
+    /// 时间加权 90 分位
+    pub p90: Decimal,
+    /// 时间加权 99 分位
+    pub p99: Decimal,
+}
+
+/// 买卖价差随时间的统计
+///
+/// 价差变化时记录一个样本，每个样本持续到下一个样本为止，
+/// 平均值和分位数都按持续时间加权
+#[derive(Debug, Clone)]
+pub struct SpreadTracker {
+    window_ms: u64,
+    /// (交易所, 交易对) -> 按时间排列的 (本地时间, 价差)
+    samples: HashMap<(String, String), VecDeque<(u64, Decimal)>>,
+}
+
+impl SpreadTracker {
+    /// 创建统计，保留最近 `window_ms` 毫秒的价差
+    pub fn new(window_ms: u64) -> Self {
+        SpreadTracker {
+            window_ms: window_ms.max(1),
+            samples: HashMap::new(),
+        }
+    }
+
+    /// 滚动窗口长度（毫秒）
+    pub fn window_ms(&self) -> u64 {
+        self.window_ms
+    }
+
+    /// 订单薄更新后记录价差，价差未变化时不记录
+    pub fn update(&mut self, venue: &str, symbol: &str, book: &OrderBook, local_time: u64) {
+        let Some(spread) = book.spread() else {
+            return;
+        };
+        let samples = self.samples.entry((venue.to_string(), symbol.to_string())).or_default();
+        if samples.back().is_none_or(|(_, last)| *last != spread) {
+            samples.push_back((local_time, spread));
+        }
+        // 保留窗口开始时仍然有效的那个样本
+        let start = local_time.saturating_sub(self.window_ms);
+        while samples.get(1).is_some_and(|(time, _)| *time <= start) {
+            samples.pop_front();
+        }
+    }
+
+    /// 已跟踪的 (交易所, 交易对)
+    pub fn symbols(&self) -> Vec<(String, String)> {
+        let mut symbols: Vec<(String, String)> = self.samples.keys().cloned().collect();
+        symbols.sort();
+        symbols
+    }
+
+    /// 截至 `now` 的滚动窗口统计，尚无样本时返回 None
+    pub fn summary(&self, venue: &str, symbol: &str, now: u64) -> Option<SpreadSummary> {
+        let weighted = self.weighted(venue, symbol, now)?;
+        let current = self.samples.get(&(venue.to_string(), symbol.to_string()))?.back()?.1;
+        let total: u64 = weighted.iter().map(|(_, duration)| duration).sum();
+
+        let twap = if total == 0 {
+            current
+        } else {
+            weighted.iter().map(|(spread, duration)| spread * Decimal::from(*duration)).sum::<Decimal>() / Decimal::from(total)
+        };
+        // 窗口开始前已被替换的样本持续时间为 0，不参与最小值和分位数
+        let mut sorted: Vec<(Decimal, u64)> = weighted.into_iter()
+            .filter(|(_, duration)| total == 0 || *duration > 0)
+            .collect();
+        sorted.sort();
+        let percentile = |percentile: u64| {
+            let target = total * percentile / 100;
+            let mut elapsed = 0;
+            for (spread, duration) in &sorted {
+                elapsed += duration;
+                if elapsed > target || elapsed == total {
+                    return *spread;
+                }
+            }
+            current
+        };
+        Some(SpreadSummary {
+            current,
+            twap,
+            min: sorted.first()?.0,
+            max: sorted.last()?.0,
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+        })
+    }
+
+    /// 窗口内各价差及其持续时间（毫秒），最新的价差持续到 `now`
+    fn weighted(&self, venue: &str, symbol: &str, now: u64) -> Option<Vec<(Decimal, u64)>> {
+        let samples = self.samples.get(&(venue.to_string(), symbol.to_string()))?;
+        if samples.is_empty() {
+            return None;
+        }
+        let start = now.saturating_sub(self.window_ms);
+        let ends = samples.iter().skip(1).map(|(time, _)| *time).chain(std::iter::once(now.max(samples.back()?.0)));
+        Some(samples.iter().zip(ends)
+            .map(|((time, spread), end)| (*spread, end.saturating_sub((*time).max(start))))
+            .collect())
+    }
+}
@@ -0,0 +1,162 @@
+use std::collections::{HashMap, VecDeque};
+use rust_decimal::Decimal;
+
+use crate::order_book::OrderBook;
+use crate::trade::Trade;
+
+/// 一笔成交的价差记录，价差均以基点表示
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TradeSpreadRecord {
+    /// 成交时的本地时间（毫秒）
+    pub time: u64,
+    /// 成交价格
+    pub price: Decimal,
+    /// 成交数量
+    pub quantity: Decimal,
+    /// 是否为主动买入
+    pub buy: bool,
+    /// 成交时的中间价
+    pub mid: Decimal,
+    /// 有效价差 `2 × 方向 × (成交价 - 成交时中间价) / 成交时中间价`
+    pub effective_bps: Decimal,
+    /// 实现价差 `2 × 方向 × (成交价 - N 秒后中间价) / 成交时中间价`，尚未到期时为 None
+    pub realized_bps: Option<Decimal>,
+    /// 相对成交时对手最优价的价格改善（基点），为正表示优于报价
+    pub improvement_bps: Decimal,
+}
+
+/// 滚动窗口内的成交价差统计，均按成交量加权
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TradeSpreadStats {
+    /// 参与统计的成交笔数
+    pub trades: usize,
+    /// 平均有效价差（基点）
+    pub effective_bps: Decimal,
+    /// 平均实现价差（基点），仅统计已到期的成交
+    pub realized_bps: Option<Decimal>,
+    /// 平均价格冲击（有效价差 - 实现价差，基点）
+    pub price_impact_bps: Option<Decimal>,
+    /// 获得价格改善的成交占比，取值 0 到 1
+    pub improved_share: Decimal,
+    /// 平均价格改善（基点）
+    pub improvement_bps: Decimal,
+}
+
+/// 用成交和中间价计算有效价差、实现价差和价格改善
+///
+/// 成交时记录当时的中间价和对手最优价，`horizon_ms` 之后用最新中间价计算实现价差
+#[derive(Debug, Clone)]
+pub struct TradeSpreadAnalyzer {
+    horizon_ms: u64,
+    window: usize,
+    /// (交易所, 交易对) -> 最近的成交记录，从旧到新
+    records: HashMap<(String, String), VecDeque<TradeSpreadRecord>>,
+}
+
+impl TradeSpreadAnalyzer {
+    /// 创建统计
+    ///
+    /// # 参数
+    ///
+    /// * `horizon_ms` - 计算实现价差的时间间隔（毫秒），例如 5000
+    /// * `window` - 保留的成交笔数
+    pub fn new(horizon_ms: u64, window: usize) -> Self {
+        TradeSpreadAnalyzer {
+            horizon_ms,
+            window: window.max(1),
+            records: HashMap::new(),
+        }
+    }
+
+    /// 记录一笔成交，订单薄缺少任意一侧时忽略
+    ///
+    /// `local_time` 为本地时间，应与 [`update_mid`](Self::update_mid) 使用同一时钟
+    pub fn add_trade(&mut self, venue: &str, symbol: &str, book: &OrderBook, trade: &Trade, local_time: u64) {
+        let (Some((bid_price, _)), Some((ask_price, _)), Some(mid)) = (book.best_bid(), book.best_ask(), book.mid_price()) else {
+            return;
+        };
+        if mid.is_zero() {
+            return;
+        }
+        let (price, buy) = (trade.price, trade.is_buy());
+        let direction = if buy { Decimal::ONE } else { Decimal::NEGATIVE_ONE };
+        let improvement = if buy { ask_price - price } else { price - bid_price };
+        let records = self.records.entry((venue.to_string(), symbol.to_string())).or_default();
+        if records.len() >= self.window {
+            records.pop_front();
+        }
+        records.push_back(TradeSpreadRecord {
+            time: local_time,
+            price,
+            quantity: trade.quantity,
+            buy,
+            mid,
+            effective_bps: Decimal::TWO * direction * (price - mid) / mid * Decimal::from(10_000),
+            realized_bps: None,
+            improvement_bps: improvement / mid * Decimal::from(10_000),
+        });
+    }
+
+    /// 更新中间价，为已到期的成交计算实现价差
+    pub fn update_mid(&mut self, venue: &str, symbol: &str, mid: Decimal, local_time: u64) {
+        let Some(records) = self.records.get_mut(&(venue.to_string(), symbol.to_string())) else {
+            return;
+        };
+        // 未到期的成交都在队尾，从后往前找到第一个已计算的记录为止
+        for record in records.iter_mut().rev() {
+            if record.realized_bps.is_some() {
+                break;
+            }
+            if record.time + self.horizon_ms > local_time {
+                continue;
+            }
+            let direction = if record.buy { Decimal::ONE } else { Decimal::NEGATIVE_ONE };
+            record.realized_bps = Some(Decimal::TWO * direction * (record.price - mid) / record.mid * Decimal::from(10_000));
+        }
+    }
+
+    /// 已跟踪的 (交易所, 交易对)
+    pub fn symbols(&self) -> Vec<(String, String)> {
+        let mut symbols: Vec<(String, String)> = self.records.keys().cloned().collect();
+        symbols.sort();
+        symbols
+    }
+
+    /// 最近的成交记录，从旧到新
+    pub fn records(&self, venue: &str, symbol: &str) -> Vec<TradeSpreadRecord> {
+        self.records.get(&(venue.to_string(), symbol.to_string()))
+            .map(|records| records.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// 滚动窗口统计，没有成交时返回 None
+    pub fn stats(&self, venue: &str, symbol: &str) -> Option<TradeSpreadStats> {
+        let records = self.records.get(&(venue.to_string(), symbol.to_string()))?;
+        let volume: Decimal = records.iter().map(|record| record.quantity).sum();
+        if volume.is_zero() {
+            return None;
+        }
+        let weighted = |value: fn(&TradeSpreadRecord) -> Decimal| {
+            records.iter().map(|record| value(record) * record.quantity).sum::<Decimal>() / volume
+        };
+
+        let realized: Vec<&TradeSpreadRecord> = records.iter().filter(|record| record.realized_bps.is_some()).collect();
+        let realized_volume: Decimal = realized.iter().map(|record| record.quantity).sum();
+        let (realized_bps, price_impact_bps) = if realized_volume.is_zero() {
+            (None, None)
+        } else {
+            let realized_bps = realized.iter().map(|record| record.realized_bps.unwrap_or_default() * record.quantity).sum::<Decimal>() / realized_volume;
+            let effective_bps = realized.iter().map(|record| record.effective_bps * record.quantity).sum::<Decimal>() / realized_volume;
+            (Some(realized_bps), Some(effective_bps - realized_bps))
+        };
+        let improved = records.iter().filter(|record| record.improvement_bps > Decimal::ZERO).count();
+        Some(TradeSpreadStats {
+            trades: records.len(),
+            effective_bps: weighted(|record| record.effective_bps),
+            realized_bps,
+            price_impact_bps,
+            improved_share: Decimal::from(improved) / Decimal::from(records.len()),
+            improvement_bps: weighted(|record| record.improvement_bps),
+        })
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::{HashMap, VecDeque};
+
+/// 每个交易对保留的异常时段数
+const MAX_ANOMALIES: usize = 100;
+/// 开始判断异常前至少需要的正常区间数
+const MIN_HISTORY: usize = 30;
+
+/// 一段更新频率异常的时段，相邻的异常区间合并为一段
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpdateRateAnomaly {
+    /// 交易所名称
+    pub venue: String,
+    /// 交易对
+    pub symbol: String,
+    /// 开始时间（毫秒）
+    pub start: u64,
+    /// 结束时间（毫秒，不含）
+    pub end: u64,
+    /// 时段内的消息数
+    pub messages: u64,
+    /// 时段内变化的档位数（新增、修改和撤销）
+    pub level_changes: u64,
+    /// 时段内单个区间偏离正常水平的最大标准差倍数
+    pub max_z_score: f64,
+}
+
+/// 单个区间的计数
+#[derive(Debug, Clone, Copy, Default)]
+struct IntervalCount {
+    start: u64,
+    messages: u64,
+    level_changes: u64,
+}
+
+/// 检测参数
+#[derive(Debug, Clone, Copy)]
+struct RateSettings {
+    interval_ms: u64,
+    history_len: usize,
+    z_threshold: f64,
+}
+
+/// 单个交易对的状态
+#[derive(Debug, Clone, Default)]
+struct RateState {
+    current: Option<IntervalCount>,
+    /// 最近正常区间的 (消息数, 变化档位数)
+    history: VecDeque<(u64, u64)>,
+    anomalies: VecDeque<UpdateRateAnomaly>,
+}
+
+impl RateState {
+    /// 结束一个区间，判断是否异常并更新基准
+    fn close_interval(&mut self, settings: &RateSettings, venue: &str, symbol: &str, interval: IntervalCount) -> Option<UpdateRateAnomaly> {
+        let z_score = if self.history.len() >= MIN_HISTORY {
+            let messages = z_score(self.history.iter().map(|(messages, _)| *messages), interval.messages);
+            let level_changes = z_score(self.history.iter().map(|(_, changes)| *changes), interval.level_changes);
+            messages.max(level_changes)
+        } else {
+            0.0
+        };
+        if z_score < settings.z_threshold {
+            if self.history.len() >= settings.history_len {
+                self.history.pop_front();
+            }
+            self.history.push_back((interval.messages, interval.level_changes));
+            return None;
+        }
+
+        // 异常区间不计入基准，紧接上一个异常时段时合并
+        let end = interval.start + settings.interval_ms;
+        if let Some(last) = self.anomalies.back_mut().filter(|last| last.end == interval.start) {
+            last.end = end;
+            last.messages += interval.messages;
+            last.level_changes += interval.level_changes;
+            last.max_z_score = last.max_z_score.max(z_score);
+            return None;
+        }
+        let anomaly = UpdateRateAnomaly {
+            venue: venue.to_string(),
+            symbol: symbol.to_string(),
+            start: interval.start,
+            end,
+            messages: interval.messages,
+            level_changes: interval.level_changes,
+            max_z_score: z_score,
+        };
+        if self.anomalies.len() >= MAX_ANOMALIES {
+            self.anomalies.pop_front();
+        }
+        self.anomalies.push_back(anomaly.clone());
+        Some(anomaly)
+    }
+}
+
+/// 更新频率异常检测（疑似刷单 / quote stuffing）
+///
+/// 按固定区间统计每个交易对的深度消息数和变化档位数，
+/// 区间结束时任一计数超过最近正常区间均值 `z_threshold` 个标准差即视为异常
+#[derive(Debug, Clone)]
+pub struct UpdateRateMonitor {
+    settings: RateSettings,
+    /// (交易所, 交易对) -> 状态
+    states: HashMap<(String, String), RateState>,
+}
+
+impl UpdateRateMonitor {
+    /// 创建检测器
+    ///
+    /// # 参数
+    ///
+    /// * `interval_ms` - 统计区间（毫秒）
+    /// * `history_len` - 作为基准的正常区间数
+    /// * `z_threshold` - 判定异常的标准差倍数，例如 4
+    pub fn new(interval_ms: u64, history_len: usize, z_threshold: f64) -> Self {
+        UpdateRateMonitor {
+            settings: RateSettings {
+                interval_ms: interval_ms.max(1),
+                history_len: history_len.max(MIN_HISTORY),
+                z_threshold,
+            },
+            states: HashMap::new(),
+        }
+    }
+
+    /// 记录一条深度消息，上一个区间结束且异常时返回新开始的异常时段
+    ///
+    /// 异常延续到相邻区间时只延长已有时段，不再返回
+    pub fn record(&mut self, venue: &str, symbol: &str, level_changes: usize, local_time: u64) -> Option<UpdateRateAnomaly> {
+        let start = local_time - local_time % self.settings.interval_ms;
+        let state = self.states.entry((venue.to_string(), symbol.to_string())).or_default();
+        let started = state.current.take_if(|current| current.start != start)
+            .and_then(|finished| state.close_interval(&self.settings, venue, symbol, finished));
+        let current = state.current.get_or_insert(IntervalCount { start, ..IntervalCount::default() });
+        current.messages += 1;
+        current.level_changes += level_changes as u64;
+        started
+    }
+
+    /// 最近的异常时段，从旧到新
+    pub fn anomalies(&self, venue: &str, symbol: &str) -> Vec<UpdateRateAnomaly> {
+        self.states.get(&(venue.to_string(), symbol.to_string()))
+            .map(|state| state.anomalies.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+}
+
+/// `value` 偏离样本均值的标准差倍数，样本没有波动时按 1 的标准差计算
+fn z_score(samples: impl Iterator<Item = u64>, value: u64) -> f64 {
+    let samples: Vec<f64> = samples.map(|sample| sample as f64).collect();
+    let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+    let variance = samples.iter().map(|sample| (sample - mean).powi(2)).sum::<f64>() / samples.len() as f64;
+    (value as f64 - mean) / variance.sqrt().max(1.0)
+}
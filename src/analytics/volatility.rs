@@ -0,0 +1,107 @@
+use std::collections::{HashMap, VecDeque};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+/// 一年的毫秒数，用于年化波动率
+const MILLIS_PER_YEAR: f64 = 365.0 * 24.0 * 3600.0 * 1000.0;
+
+/// 单个交易对的采样状态
+#[derive(Debug, Clone, Default)]
+struct VolatilityState {
+    /// 上一次采样的中间价
+    last_mid: Option<f64>,
+    /// 下一次采样的时间（毫秒）
+    next_sample: u64,
+    /// 滚动窗口内的对数收益率
+    returns: VecDeque<f64>,
+}
+
+/// 中间价滚动已实现波动率
+///
+/// 按固定间隔对中间价采样，保留最近 `window` 个对数收益率，
+/// 已实现波动率为收益率平方和的平方根
+#[derive(Debug, Clone)]
+pub struct VolatilityTracker {
+    sample_interval_ms: u64,
+    window: usize,
+    /// (交易所, 交易对) -> 采样状态
+    states: HashMap<(String, String), VolatilityState>,
+}
+
+impl VolatilityTracker {
+    /// 创建统计
+    ///
+    /// # 参数
+    ///
+    /// * `sample_interval_ms` - 中间价采样间隔（毫秒）
+    /// * `window` - 滚动窗口内的收益率个数
+    pub fn new(sample_interval_ms: u64, window: usize) -> Self {
+        VolatilityTracker {
+            sample_interval_ms: sample_interval_ms.max(1),
+            window: window.max(1),
+            states: HashMap::new(),
+        }
+    }
+
+    /// 采样间隔（毫秒）
+    pub fn sample_interval_ms(&self) -> u64 {
+        self.sample_interval_ms
+    }
+
+    /// 更新中间价，到达采样时间时记录一个收益率
+    ///
+    /// 两次更新之间跨过多个采样点时只记录一次，收益率覆盖整个空档
+    pub fn update(&mut self, venue: &str, symbol: &str, mid: Decimal, local_time: u64) {
+        let Some(mid) = mid.to_f64().filter(|mid| *mid > 0.0) else {
+            return;
+        };
+        let state = self.states.entry((venue.to_string(), symbol.to_string())).or_default();
+        if local_time < state.next_sample {
+            return;
+        }
+        state.next_sample = local_time - local_time % self.sample_interval_ms + self.sample_interval_ms;
+        if let Some(last_mid) = state.last_mid.replace(mid) {
+            if state.returns.len() >= self.window {
+                state.returns.pop_front();
+            }
+            state.returns.push_back((mid / last_mid).ln());
+        }
+    }
+
+    /// 已跟踪的 (交易所, 交易对)
+    pub fn symbols(&self) -> Vec<(String, String)> {
+        let mut symbols: Vec<(String, String)> = self.states.keys().cloned().collect();
+        symbols.sort();
+        symbols
+    }
+
+    /// 滚动窗口内的对数收益率，从旧到新
+    pub fn returns(&self, venue: &str, symbol: &str) -> Vec<f64> {
+        self.state(venue, symbol)
+            .map(|state| state.returns.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// 滚动窗口内的已实现波动率（未年化），尚无收益率时返回 None
+    pub fn realized_volatility(&self, venue: &str, symbol: &str) -> Option<f64> {
+        let state = self.state(venue, symbol)?;
+        if state.returns.is_empty() {
+            return None;
+        }
+        Some(state.returns.iter().map(|r| r * r).sum::<f64>().sqrt())
+    }
+
+    /// 年化波动率，按单个采样间隔的平均方差推算到一年
+    pub fn annualized_volatility(&self, venue: &str, symbol: &str) -> Option<f64> {
+        let state = self.state(venue, symbol)?;
+        if state.returns.is_empty() {
+            return None;
+        }
+        let variance = state.returns.iter().map(|r| r * r).sum::<f64>() / state.returns.len() as f64;
+        Some((variance * MILLIS_PER_YEAR / self.sample_interval_ms as f64).sqrt())
+    }
+
+    fn state(&self, venue: &str, symbol: &str) -> Option<&VolatilityState> {
+        self.states.get(&(venue.to_string(), symbol.to_string()))
+    }
+}
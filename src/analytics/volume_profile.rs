@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+use rust_decimal::Decimal;
+
+/// 默认价值区域占总成交量的比例
+pub const DEFAULT_VALUE_AREA: Decimal = Decimal::from_parts(70, 0, 0, false, 2);
+
+/// 按价格分桶的成交量分布
+///
+/// 成交按 `bucket_size` 向下取整归入价格桶，会话按 UTC 对齐的 `session_ms` 划分，
+/// 进入新会话时清空重新累计
+#[derive(Debug, Clone)]
+pub struct VolumeProfile {
+    bucket_size: Decimal,
+    session_ms: u64,
+    /// 当前会话开始时间（毫秒）
+    session_start: u64,
+    /// 价格桶下沿 -> 成交量
+    volumes: BTreeMap<Decimal, Decimal>,
+    total_volume: Decimal,
+}
+
+impl VolumeProfile {
+    /// 创建成交量分布
+    ///
+    /// # 参数
+    ///
+    /// * `bucket_size` - 价格桶宽度，例如 10 表示每 10 USDT 一个桶
+    /// * `session_ms` - 会话长度（毫秒），例如 86400000 表示按天
+    pub fn new(bucket_size: Decimal, session_ms: u64) -> Self {
+        VolumeProfile {
+            bucket_size,
+            session_ms: session_ms.max(1),
+            session_start: 0,
+            volumes: BTreeMap::new(),
+            total_volume: Decimal::ZERO,
+        }
+    }
+
+    /// 累计一笔成交，成交属于新会话时返回上一个会话的分布
+    pub fn add_trade(&mut self, price: Decimal, quantity: Decimal, trade_time: u64) -> Option<VolumeProfile> {
+        let session_start = trade_time - trade_time % self.session_ms;
+        let mut finished = None;
+        if session_start > self.session_start {
+            let previous = std::mem::replace(self, VolumeProfile::new(self.bucket_size, self.session_ms));
+            self.session_start = session_start;
+            if !previous.volumes.is_empty() {
+                finished = Some(previous);
+            }
+        }
+
+        let bucket = if self.bucket_size > Decimal::ZERO {
+            (price / self.bucket_size).floor() * self.bucket_size
+        } else {
+            price
+        };
+        *self.volumes.entry(bucket).or_default() += quantity;
+        self.total_volume += quantity;
+        finished
+    }
+
+    /// 会话开始时间（毫秒）
+    pub fn session_start(&self) -> u64 {
+        self.session_start
+    }
+
+    /// 会话结束时间（毫秒，不含）
+    pub fn session_end(&self) -> u64 {
+        self.session_start + self.session_ms
+    }
+
+    /// 价格桶宽度
+    pub fn bucket_size(&self) -> Decimal {
+        self.bucket_size
+    }
+
+    /// 会话内总成交量
+    pub fn total_volume(&self) -> Decimal {
+        self.total_volume
+    }
+
+    /// 各价格桶 (下沿, 成交量)，按价格升序
+    pub fn buckets(&self) -> Vec<(Decimal, Decimal)> {
+        self.volumes.iter().map(|(price, volume)| (*price, *volume)).collect()
+    }
+
+    /// 成交量最大的价格桶 (POC)，返回 (下沿, 成交量)，成交量相同时取较低价格
+    pub fn poc(&self) -> Option<(Decimal, Decimal)> {
+        self.volumes.iter()
+            .fold(None, |best: Option<(&Decimal, &Decimal)>, level| match best {
+                Some(best) if best.1 >= level.1 => Some(best),
+                _ => Some(level),
+            })
+            .map(|(price, volume)| (*price, *volume))
+    }
+
+    /// 价值区域，返回 (下沿, 上沿)
+    ///
+    /// 从 POC 开始，每次向成交量较大的相邻价格桶扩展，
+    /// 直到区域内成交量达到总成交量的 `fraction`（例如 0.7）
+    pub fn value_area(&self, fraction: Decimal) -> Option<(Decimal, Decimal)> {
+        let (poc, _) = self.poc()?;
+        let buckets = self.buckets();
+        let index = buckets.iter().position(|(price, _)| *price == poc)?;
+        let target = self.total_volume * fraction;
+
+        let (mut low, mut high) = (index, index);
+        let mut volume = buckets[index].1;
+        while volume < target {
+            let below = low.checked_sub(1).map(|i| buckets[i].1);
+            let above = buckets.get(high + 1).map(|(_, volume)| *volume);
+            match (below, above) {
+                (Some(below), Some(above)) if above > below => {
+                    high += 1;
+                    volume += above;
+                }
+                (Some(below), _) => {
+                    low -= 1;
+                    volume += below;
+                }
+                (None, Some(above)) => {
+                    high += 1;
+                    volume += above;
+                }
+                (None, None) => break,
+            }
+        }
+        Some((buckets[low].0, buckets[high].0 + self.bucket_size))
+    }
+}
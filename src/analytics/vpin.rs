@@ -0,0 +1,152 @@
+use std::collections::{HashMap, VecDeque};
+use rust_decimal::Decimal;
+
+/// 每个交易对保留的 VPIN 历史数
+const MAX_HISTORY: usize = 1000;
+
+/// 一个成交量桶结束时的 VPIN
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VpinPoint {
+    /// 桶结束时的成交时间（毫秒）
+    pub time: u64,
+    /// 当前 VPIN，取值 0 到 1
+    pub vpin: Decimal,
+}
+
+/// 单个交易对的状态
+#[derive(Debug, Clone, Default)]
+struct VpinState {
+    /// 当前桶的主动买入量和主动卖出量
+    buy_volume: Decimal,
+    sell_volume: Decimal,
+    /// 最近已完成桶的买卖不平衡 |买 - 卖|
+    imbalances: VecDeque<Decimal>,
+    history: VecDeque<VpinPoint>,
+}
+
+/// VPIN（成交量同步的知情交易概率）
+///
+/// 成交按主动方向分为买入和卖出，依次填入固定成交量的桶，一笔成交可能跨越多个桶。
+/// VPIN 为最近 `window` 个桶的买卖不平衡之和除以这些桶的总成交量
+#[derive(Debug, Clone)]
+pub struct VpinCalculator {
+    bucket_volume: Decimal,
+    window: usize,
+    /// (交易所, 交易对) -> 状态
+    states: HashMap<(String, String), VpinState>,
+}
+
+impl VpinCalculator {
+    /// 创建计算器
+    ///
+    /// # 参数
+    ///
+    /// * `bucket_volume` - 每个桶的成交量（基础资产）
+    /// * `window` - 计算 VPIN 的桶数，例如 50
+    pub fn new(bucket_volume: Decimal, window: usize) -> Self {
+        VpinCalculator {
+            bucket_volume,
+            window: window.max(1),
+            states: HashMap::new(),
+        }
+    }
+
+    /// 每个桶的成交量
+    pub fn bucket_volume(&self) -> Decimal {
+        self.bucket_volume
+    }
+
+    /// 累计一笔成交，返回本次完成的桶对应的 VPIN（桶数不足窗口时不返回）
+    ///
+    /// # 参数
+    ///
+    /// * `buyer_is_maker` - 买方是否为挂单方，是则为主动卖出
+    pub fn add_trade(&mut self, venue: &str, symbol: &str, quantity: Decimal, buyer_is_maker: bool, trade_time: u64) -> Vec<VpinPoint> {
+        let mut points = Vec::new();
+        if self.bucket_volume <= Decimal::ZERO {
+            return points;
+        }
+        let state = self.states.entry((venue.to_string(), symbol.to_string())).or_default();
+        let mut remaining = quantity;
+        while remaining > Decimal::ZERO {
+            let space = self.bucket_volume - state.buy_volume - state.sell_volume;
+            let filled = remaining.min(space);
+            if buyer_is_maker {
+                state.sell_volume += filled;
+            } else {
+                state.buy_volume += filled;
+            }
+            remaining -= filled;
+            if filled < space {
+                break;
+            }
+
+            if state.imbalances.len() >= self.window {
+                state.imbalances.pop_front();
+            }
+            state.imbalances.push_back((state.buy_volume - state.sell_volume).abs());
+            state.buy_volume = Decimal::ZERO;
+            state.sell_volume = Decimal::ZERO;
+            if state.imbalances.len() == self.window {
+                let vpin = state.imbalances.iter().sum::<Decimal>() / (self.bucket_volume * Decimal::from(self.window));
+                let point = VpinPoint { time: trade_time, vpin };
+                if state.history.len() >= MAX_HISTORY {
+                    state.history.pop_front();
+                }
+                state.history.push_back(point);
+                points.push(point);
+            }
+        }
+        points
+    }
+
+    /// 已跟踪的 (交易所, 交易对)
+    pub fn symbols(&self) -> Vec<(String, String)> {
+        let mut symbols: Vec<(String, String)> = self.states.keys().cloned().collect();
+        symbols.sort();
+        symbols
+    }
+
+    /// 最新的 VPIN，完成的桶数不足窗口时返回 None
+    pub fn vpin(&self, venue: &str, symbol: &str) -> Option<Decimal> {
+        self.state(venue, symbol)?.history.back().map(|point| point.vpin)
+    }
+
+    /// VPIN 历史，从旧到新
+    pub fn history(&self, venue: &str, symbol: &str) -> Vec<VpinPoint> {
+        self.state(venue, symbol)
+            .map(|state| state.history.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    fn state(&self, venue: &str, symbol: &str) -> Option<&VpinState> {
+        self.states.get(&(venue.to_string(), symbol.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    #[test]
+    fn buckets_close_at_exact_volume_and_across_trades() {
+        let mut calculator = VpinCalculator::new(dec!(10), 2);
+        assert!(calculator.add_trade("binance", "BTCUSDT", dec!(4), false, 1).is_empty());
+        // 正好填满第一个桶，桶数不足窗口
+        assert!(calculator.add_trade("binance", "BTCUSDT", dec!(6), true, 2).is_empty());
+        assert_eq!(calculator.vpin("binance", "BTCUSDT"), None);
+
+        // 一笔成交填满两个桶，剩余 5 留在下一个桶
+        let points = calculator.add_trade("binance", "BTCUSDT", dec!(25), false, 3);
+        assert_eq!(points, vec![VpinPoint { time: 3, vpin: dec!(0.6) }, VpinPoint { time: 3, vpin: dec!(1) }]);
+
+        // 卖出 5 正好填满，买卖相等
+        let points = calculator.add_trade("binance", "BTCUSDT", dec!(5), true, 4);
+        assert_eq!(points, vec![VpinPoint { time: 4, vpin: dec!(0.5) }]);
+        assert_eq!(calculator.vpin("binance", "BTCUSDT"), Some(dec!(0.5)));
+        assert_eq!(calculator.history("binance", "BTCUSDT").len(), 3);
+        assert!(calculator.history("okx", "BTCUSDT").is_empty());
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use rust_decimal::Decimal;
+
+use crate::order_book::{OrderBook, Side};
+
+/// 默认参与比较的相邻档位数（每侧）
+const DEFAULT_NEIGHBOURS: usize = 5;
+
+/// 挂单墙的变化
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WallChange {
+    /// 出现新的挂单墙
+    Appeared,
+    /// 挂单墙移动到新价格，`from` 为原价格
+    Moved { from: Decimal },
+    /// 挂单墙消失，价格和数量为消失前的值
+    Removed,
+}
+
+/// 挂单墙事件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WallEvent {
+    /// 交易所名称
+    pub venue: String,
+    /// 交易对
+    pub symbol: String,
+    /// 挂单墙所在盘口方向
+    pub side: Side,
+    /// 变化类型
+    pub change: WallChange,
+    /// 价格
+    pub price: Decimal,
+    /// 数量
+    pub quantity: Decimal,
+    /// 距中间价的距离（基点），始终为非负数
+    pub distance_bps: Option<Decimal>,
+}
+
+/// 挂单墙检测
+///
+/// 在每侧前 `depth` 档中寻找数量超过相邻档位平均数量 `multiple` 倍的档位，
+/// 与上一次的结果比较后产生出现、移动、消失事件
+#[derive(Debug, Clone)]
+pub struct WallDetector {
+    multiple: Decimal,
+    depth: usize,
+    neighbours: usize,
+    /// (交易所, 交易对, 方向) -> 当前挂单墙 (价格, 数量)
+    walls: HashMap<(String, String, Side), Vec<(Decimal, Decimal)>>,
+}
+
+impl WallDetector {
+    /// 创建检测器
+    ///
+    /// # 参数
+    ///
+    /// * `multiple` - 数量超过相邻档位平均数量的倍数，例如 5
+    /// * `depth` - 每侧扫描的档位数
+    pub fn new(multiple: Decimal, depth: usize) -> Self {
+        WallDetector {
+            multiple,
+            depth,
+            neighbours: DEFAULT_NEIGHBOURS,
+            walls: HashMap::new(),
+        }
+    }
+
+    /// 设置参与比较的相邻档位数（每侧），默认 5
+    pub fn set_neighbours(&mut self, neighbours: usize) {
+        self.neighbours = neighbours.max(1);
+    }
+
+    /// 当前的挂单墙 (价格, 数量)，从最优价开始
+    pub fn walls(&self, venue: &str, symbol: &str, side: Side) -> &[(Decimal, Decimal)] {
+        self.walls.get(&(venue.to_string(), symbol.to_string(), side))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// 订单薄更新后重新扫描，返回挂单墙的变化
+    pub fn update(&mut self, venue: &str, symbol: &str, book: &OrderBook) -> Vec<WallEvent> {
+        let mid = book.mid_price();
+        let mut events = Vec::new();
+        for side in [Side::Bid, Side::Ask] {
+            let levels: Vec<(Decimal, Decimal)> = book.notional_levels(side, self.depth).iter()
+                .map(|level| (level.price, level.quantity))
+                .collect();
+            let current = self.scan(&levels);
+            let previous = self.walls.insert((venue.to_string(), symbol.to_string(), side), current.clone()).unwrap_or_default();
+
+            let event = |change, (price, quantity): (Decimal, Decimal)| WallEvent {
+                venue: venue.to_string(),
+                symbol: symbol.to_string(),
+                side,
+                change,
+                price,
+                quantity,
+                distance_bps: mid.filter(|mid| !mid.is_zero())
+                    .map(|mid| (price - mid).abs() / mid * Decimal::from(10_000)),
+            };
+            let mut removed: Vec<(Decimal, Decimal)> = previous.iter()
+                .filter(|(price, _)| !current.iter().any(|(current_price, _)| current_price == price))
+                .copied()
+                .collect();
+            for wall in current.iter().filter(|(price, _)| !previous.iter().any(|(previous_price, _)| previous_price == price)) {
+                // 数量相近的消失墙中取价格最近的一个，视为移动
+                let moved = removed.iter()
+                    .enumerate()
+                    .filter(|(_, (_, quantity))| *quantity * Decimal::TWO >= wall.1 && wall.1 * Decimal::TWO >= *quantity)
+                    .min_by_key(|(_, (price, _))| (*price - wall.0).abs())
+                    .map(|(index, _)| index);
+                match moved {
+                    Some(index) => {
+                        let (from, _) = removed.remove(index);
+                        events.push(event(WallChange::Moved { from }, *wall));
+                    }
+                    None => events.push(event(WallChange::Appeared, *wall)),
+                }
+            }
+            events.extend(removed.into_iter().map(|wall| event(WallChange::Removed, wall)));
+        }
+        events
+    }
+
+    /// 找出挂单墙，`levels` 从最优价开始
+    fn scan(&self, levels: &[(Decimal, Decimal)]) -> Vec<(Decimal, Decimal)> {
+        let mut walls = Vec::new();
+        for (i, (price, quantity)) in levels.iter().enumerate() {
+            let start = i.saturating_sub(self.neighbours);
+            let end = (i + self.neighbours + 1).min(levels.len());
+            let neighbours: Vec<Decimal> = levels[start..end].iter()
+                .enumerate()
+                .filter(|(j, _)| start + j != i)
+                .map(|(_, (_, quantity))| *quantity)
+                .collect();
+            if neighbours.is_empty() {
+                continue;
+            }
+            let average = neighbours.iter().sum::<Decimal>() / Decimal::from(neighbours.len());
+            if *quantity > average * self.multiple {
+                walls.push((*price, *quantity));
+            }
+        }
+        walls
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use rust_decimal::Decimal;
+
+use crate::fees::FeeSchedule;
+use crate::order_book::OrderBook;
+
+/// 一次跨交易所套利机会：在一个交易所买入，同时在另一个交易所卖出
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArbitrageOpportunity {
+    /// 买入的交易所
+    pub buy_venue: String,
+    /// 买入交易所的交易对
+    pub buy_symbol: String,
+    /// 卖出的交易所
+    pub sell_venue: String,
+    /// 卖出交易所的交易对
+    pub sell_symbol: String,
+    /// 扣除手续费后仍有利润的可成交数量
+    pub quantity: Decimal,
+    /// 买入均价（不含手续费）
+    pub buy_avg_price: Decimal,
+    /// 卖出均价（不含手续费）
+    pub sell_avg_price: Decimal,
+    /// 扣除双边手续费后的预期利润（计价资产）
+    pub expected_profit: Decimal,
+    /// 预期利润占买入金额的基点数
+    pub profit_bps: Decimal,
+}
+
+/// 跨交易所套利检测器
+///
+/// 对同一品种在多个交易所的订单薄两两比较，沿买方交易所的卖盘和卖方交易所的买盘逐档撮合，
+/// 直到扣除双边吃单手续费后不再有利润，得到可执行的数量和预期利润
+#[derive(Debug, Clone)]
+pub struct ArbitrageDetector {
+    /// 参与比较的 (交易所, 交易对)
+    legs: Vec<(String, String)>,
+    /// 手续费表，使用吃单手续费
+    fees: FeeSchedule,
+    /// 产生事件的最小预期利润
+    min_profit: Decimal,
+    /// 上次输出的机会：(买入交易所, 卖出交易所) -> (数量, 利润)
+    last_emitted: HashMap<(String, String), (Decimal, Decimal)>,
+}
+
+impl ArbitrageDetector {
+    /// 创建检测器
+    ///
+    /// # 参数
+    ///
+    /// * `legs` - (交易所, 交易对) 列表，例如 [("binance", "BTCUSDT"), ("okx", "BTC-USDT")]
+    pub fn new(legs: Vec<(String, String)>) -> Self {
+        ArbitrageDetector {
+            legs,
+            fees: FeeSchedule::new(),
+            min_profit: Decimal::ZERO,
+            last_emitted: HashMap::new(),
+        }
+    }
+
+    /// 设置手续费表
+    pub fn set_fee_schedule(&mut self, fees: FeeSchedule) {
+        self.fees = fees;
+    }
+
+    /// 设置产生事件的最小预期利润
+    pub fn set_min_profit(&mut self, min_profit: Decimal) {
+        self.min_profit = min_profit;
+    }
+
+    /// 参与比较的 (交易所, 交易对)
+    pub fn legs(&self) -> &[(String, String)] {
+        &self.legs
+    }
+
+    /// 判断交易所的交易对是否参与比较
+    pub fn contains(&self, venue: &str, symbol: &str) -> bool {
+        self.legs.iter().any(|(leg_venue, leg_symbol)| leg_venue == venue && leg_symbol == symbol)
+    }
+
+    /// 计算当前所有可执行的套利机会
+    ///
+    /// # 参数
+    ///
+    /// * `books` - 按 `legs` 查找订单薄的函数，尚未建立时返回 None
+    pub fn detect<'a>(&self, books: impl Fn(&str, &str) -> Option<&'a OrderBook>) -> Vec<ArbitrageOpportunity> {
+        let mut opportunities = Vec::new();
+        for (buy_venue, buy_symbol) in &self.legs {
+            for (sell_venue, sell_symbol) in &self.legs {
+                if buy_venue == sell_venue && buy_symbol == sell_symbol {
+                    continue;
+                }
+                let (Some(buy_book), Some(sell_book)) = (books(buy_venue, buy_symbol), books(sell_venue, sell_symbol)) else {
+                    continue;
+                };
+                if let Some(opportunity) = self.match_books(buy_venue, buy_symbol, buy_book, sell_venue, sell_symbol, sell_book)
+                    && opportunity.expected_profit > self.min_profit
+                {
+                    opportunities.push(opportunity);
+                }
+            }
+        }
+        opportunities
+    }
+
+    /// 计算套利机会，只返回与上次输出相比出现或变化的机会
+    pub fn update<'a>(&mut self, books: impl Fn(&str, &str) -> Option<&'a OrderBook>) -> Vec<ArbitrageOpportunity> {
+        let opportunities = self.detect(books);
+        let mut changed = Vec::new();
+        let mut emitted = HashMap::with_capacity(opportunities.len());
+        for opportunity in opportunities {
+            let key = (opportunity.buy_venue.clone(), opportunity.sell_venue.clone());
+            let value = (opportunity.quantity, opportunity.expected_profit);
+            if self.last_emitted.get(&key) != Some(&value) {
+                changed.push(opportunity);
+            }
+            emitted.insert(key, value);
+        }
+        self.last_emitted = emitted;
+        changed
+    }
+
+    /// 沿买方卖盘和卖方买盘逐档撮合
+    fn match_books(
+        &self,
+        buy_venue: &str,
+        buy_symbol: &str,
+        buy_book: &OrderBook,
+        sell_venue: &str,
+        sell_symbol: &str,
+        sell_book: &OrderBook,
+    ) -> Option<ArbitrageOpportunity> {
+        let bps = Decimal::from(10_000);
+        let buy_fee = self.fees.taker_rate(buy_venue);
+        let sell_fee = self.fees.taker_rate(sell_venue);
+
+        let mut asks = buy_book.asks.iter().map(|(price, quantity)| (*price, *quantity));
+        let mut bids = sell_book.bids.iter().rev().map(|(price, quantity)| (*price, *quantity));
+        let mut ask = asks.next();
+        let mut bid = bids.next();
+
+        let mut quantity = Decimal::ZERO;
+        let mut buy_notional = Decimal::ZERO;
+        let mut sell_notional = Decimal::ZERO;
+        let mut profit = Decimal::ZERO;
+        while let (Some((ask_price, ask_quantity)), Some((bid_price, bid_quantity))) = (ask, bid) {
+            let unit_profit = bid_price * (Decimal::ONE - sell_fee) - ask_price * (Decimal::ONE + buy_fee);
+            if unit_profit <= Decimal::ZERO {
+                break;
+            }
+            let fill = ask_quantity.min(bid_quantity);
+            quantity += fill;
+            buy_notional += ask_price * fill;
+            sell_notional += bid_price * fill;
+            profit += unit_profit * fill;
+
+            ask = if fill == ask_quantity { asks.next() } else { Some((ask_price, ask_quantity - fill)) };
+            bid = if fill == bid_quantity { bids.next() } else { Some((bid_price, bid_quantity - fill)) };
+        }
+
+        if quantity.is_zero() {
+            return None;
+        }
+        Some(ArbitrageOpportunity {
+            buy_venue: buy_venue.to_string(),
+            buy_symbol: buy_symbol.to_string(),
+            sell_venue: sell_venue.to_string(),
+            sell_symbol: sell_symbol.to_string(),
+            quantity,
+            buy_avg_price: buy_notional / quantity,
+            sell_avg_price: sell_notional / quantity,
+            expected_profit: profit,
+            profit_bps: profit / buy_notional * bps,
+        })
+    }
+}
@@ -0,0 +1,678 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use tracing::info;
+
+use crate::exchange::{Continuity, DepthKind, DepthMessage};
+use crate::order_book::{depth_histogram, HISTOGRAM_WIDTH};
+
+/// 币安市场类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Market {
+    /// 现货
+    Spot,
+    /// U本位合约
+    UsdmFutures,
+    /// Binance.US 现货，消息格式与现货相同，仅域名和交易对不同
+    UsSpot,
+}
+
+impl Market {
+    /// 从命令行参数解析市场类型
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "spot" => Some(Market::Spot),
+            "futures" | "usdm" => Some(Market::UsdmFutures),
+            "us" | "binance-us" => Some(Market::UsSpot),
+            _ => None,
+        }
+    }
+
+    /// WebSocket 组合流行情地址
+    pub fn ws_url(&self) -> &'static str {
+        match self {
+            Market::Spot => "wss://stream.binance.com:9443/stream",
+            Market::UsdmFutures => "wss://fstream.binance.com/stream",
+            Market::UsSpot => "wss://stream.binance.us:9443/stream",
+        }
+    }
+
+    /// REST 深度快照地址
+    pub fn depth_url(&self) -> &'static str {
+        match self {
+            Market::Spot => "https://api.binance.com/api/v3/depth",
+            Market::UsdmFutures => "https://fapi.binance.com/fapi/v1/depth",
+            Market::UsSpot => "https://api.binance.us/api/v3/depth",
+        }
+    }
+
+    /// WebSocket API 地址，用于通过长连接请求深度快照
+    pub fn ws_api_url(&self) -> &'static str {
+        match self {
+            Market::Spot => "wss://ws-api.binance.com:443/ws-api/v3",
+            Market::UsdmFutures => "wss://ws-fapi.binance.com/ws-fapi/v1",
+            Market::UsSpot => "wss://ws-api.binance.us:443/ws-api/v3",
+        }
+    }
+
+    /// REST 交易规则地址
+    pub fn exchange_info_url(&self) -> &'static str {
+        match self {
+            Market::Spot => "https://api.binance.com/api/v3/exchangeInfo",
+            Market::UsdmFutures => "https://fapi.binance.com/fapi/v1/exchangeInfo",
+            Market::UsSpot => "https://api.binance.us/api/v3/exchangeInfo",
+        }
+    }
+
+    /// REST 服务器时间地址
+    pub fn time_url(&self) -> &'static str {
+        match self {
+            Market::Spot => "https://api.binance.com/api/v3/time",
+            Market::UsdmFutures => "https://fapi.binance.com/fapi/v1/time",
+            Market::UsSpot => "https://api.binance.us/api/v3/time",
+        }
+    }
+
+    /// data.binance.vision 历史数据的市场路径，Binance.US 没有公开的历史数据
+    pub fn vision_path(&self) -> Option<&'static str> {
+        match self {
+            Market::Spot => Some("spot"),
+            Market::UsdmFutures => Some("futures/um"),
+            Market::UsSpot => None,
+        }
+    }
+
+    /// 深度快照允许的最大档位数
+    pub fn max_depth_limit(&self) -> u32 {
+        match self {
+            Market::Spot | Market::UsSpot => 5000,
+            Market::UsdmFutures => 1000,
+        }
+    }
+
+    /// 深度快照请求的权重，按档位数分档
+    pub fn depth_weight(&self, limit: u32) -> u32 {
+        match self {
+            Market::Spot | Market::UsSpot => match limit {
+                0..=100 => 5,
+                101..=500 => 25,
+                501..=1000 => 50,
+                _ => 250,
+            },
+            Market::UsdmFutures => match limit {
+                0..=50 => 2,
+                51..=100 => 5,
+                101..=500 => 10,
+                _ => 20,
+            },
+        }
+    }
+
+    /// 每分钟允许的请求权重
+    pub fn request_weight_limit(&self) -> u32 {
+        match self {
+            Market::Spot => 6000,
+            Market::UsdmFutures => 2400,
+            Market::UsSpot => 1200,
+        }
+    }
+
+    /// 是否为现货类市场（有限档深度推送为快照格式）
+    pub fn is_spot(&self) -> bool {
+        matches!(self, Market::Spot | Market::UsSpot)
+    }
+
+    /// 深度流的更新速度后缀，市场不支持该速度时返回 None
+    ///
+    /// 现货默认 1000ms，合约默认 250ms，默认速度不带后缀
+    pub fn speed_suffix(&self, speed: UpdateSpeed) -> Option<&'static str> {
+        match (self, speed) {
+            (_, UpdateSpeed::Ms100) => Some("@100ms"),
+            (Market::Spot | Market::UsSpot, UpdateSpeed::Ms1000) => Some(""),
+            (Market::UsdmFutures, UpdateSpeed::Ms250) => Some(""),
+            (Market::UsdmFutures, UpdateSpeed::Ms500) => Some("@500ms"),
+            _ => None,
+        }
+    }
+
+    /// 生成单个交易对需要订阅的流名称
+    ///
+    /// 合约另外订阅标记价格和强平订单流
+    pub fn stream_params(&self, config: &SymbolConfig) -> Vec<String> {
+        let symbol = config.symbol.to_lowercase();
+        let speed = self.speed_suffix(config.update_speed).unwrap_or("@100ms");
+        let mut params = vec![format!("{}@depth{}", symbol, speed)];
+        if let Some(levels) = config.partial_depth {
+            params.push(format!("{}@depth{}{}", symbol, levels, speed));
+        }
+        if *self == Market::UsdmFutures {
+            params.push(format!("{}@markPrice@1s", symbol));
+            params.push(format!("{}@forceOrder", symbol));
+        }
+        params
+    }
+}
+
+/// 深度流更新速度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateSpeed {
+    Ms100,
+    Ms250,
+    Ms500,
+    Ms1000,
+}
+
+impl UpdateSpeed {
+    /// 从字符串解析，例如 "100ms"
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "100ms" => Some(UpdateSpeed::Ms100),
+            "250ms" => Some(UpdateSpeed::Ms250),
+            "500ms" => Some(UpdateSpeed::Ms500),
+            "1000ms" | "1s" => Some(UpdateSpeed::Ms1000),
+            _ => None,
+        }
+    }
+}
+
+/// 单个交易对的深度订阅配置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolConfig {
+    /// 交易对(大写)
+    pub symbol: String,
+    /// 深度流更新速度
+    pub update_speed: UpdateSpeed,
+    /// 有限档深度档位数（5/10/20），None 表示不订阅
+    pub partial_depth: Option<u8>,
+    /// 深度快照档位数
+    pub snapshot_limit: u32,
+}
+
+impl SymbolConfig {
+    /// 使用市场默认值创建配置：100ms 更新，现货订阅 20 档有限深度
+    pub fn new(market: Market, symbol: &str) -> Self {
+        SymbolConfig {
+            symbol: symbol.to_uppercase(),
+            update_speed: UpdateSpeed::Ms100,
+            partial_depth: match market {
+                Market::Spot | Market::UsSpot => Some(20),
+                Market::UsdmFutures => None,
+            },
+            snapshot_limit: market.max_depth_limit(),
+        }
+    }
+
+    /// 解析交易对配置，格式为 `SYMBOL[:速度[:档位]]`
+    ///
+    /// 例如 `BNBUSDT`、`BNBUSDT:1000ms`、`BNBUSDT:100ms:5`、`BNBUSDT:100ms:none`
+    pub fn parse(market: Market, spec: &str) -> Result<Self, Box<dyn Error>> {
+        let mut parts = spec.split(':');
+        let symbol = parts.next().filter(|symbol| !symbol.is_empty())
+            .ok_or_else(|| format!("交易对配置为空: {}", spec))?;
+        let mut config = SymbolConfig::new(market, symbol);
+
+        if let Some(speed) = parts.next() {
+            config.update_speed = UpdateSpeed::parse(speed)
+                .ok_or_else(|| format!("无效的更新速度: {}", speed))?;
+            if market.speed_suffix(config.update_speed).is_none() {
+                return Err(format!("{:?} 不支持更新速度 {}", market, speed).into());
+            }
+        }
+        if let Some(levels) = parts.next() {
+            config.partial_depth = match levels {
+                "none" | "0" => None,
+                "5" => Some(5),
+                "10" => Some(10),
+                "20" => Some(20),
+                _ => return Err(format!("无效的有限深度档位: {}", levels).into()),
+            };
+        }
+        if parts.next().is_some() {
+            return Err(format!("交易对配置格式错误: {}", spec).into());
+        }
+        Ok(config)
+    }
+}
+
+/// 组合流消息外层结构 `{"stream":"<流名称>","data":{...}}`
+#[derive(Debug, Deserialize)]
+pub struct StreamMessage<'a> {
+    pub stream: String,
+    #[serde(borrow)]
+    pub data: &'a serde_json::value::RawValue,
+}
+
+/// 判断流名称是否为有限档深度流，例如 `bnbusdt@depth20@100ms`
+pub fn is_partial_depth_stream(stream: &str) -> bool {
+    stream.split('@').nth(1)
+        .and_then(|name| name.strip_prefix("depth"))
+        .is_some_and(|levels| !levels.is_empty())
+}
+
+/// 有限档深度信息结构体，对应币安深度信息
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize, Serialize)]
+pub struct LimitedDepthInfo {
+    pub lastUpdateId: u64,                // 末次更新ID
+    pub bids: Vec<[String; 2]>,           // 买单 [价格, 数量]
+    pub asks: Vec<[String; 2]>,           // 卖单 [价格, 数量]
+}
+
+impl LimitedDepthInfo {
+    /// 打印深度信息摘要
+    ///
+    /// # 参数
+    ///
+    /// * `limit` - 要显示的档位数量
+    pub fn print_summary(&self, limit: usize) {
+        // println!("深度信息摘要:");
+        println!("有限深度信息 最后更新 ID: {}", self.lastUpdateId);
+        // println!("买单数量: {}", self.bids.len());
+        // println!("卖单数量: {}", self.asks.len());
+
+        self.print_bids(limit);
+        // self.print_asks(limit);
+    }
+
+    /// 打印买单信息（按价格降序）
+    ///
+    /// # 参数
+    ///
+    /// * `limit` - 要显示的档位数量
+    pub fn print_bids(&self, limit: usize) {
+        // 转换买单为 (价格, 数量) 元组
+        let mut bids: Vec<(f64, f64)> = self.bids.iter()
+            .map(|bid| {
+                let price = bid[0].parse::<f64>().unwrap_or(0.0);
+                let quantity = bid[1].parse::<f64>().unwrap_or(0.0);
+                (price, quantity)
+            })
+            .collect();
+
+        // 按价格降序排列
+        bids.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        // 打印前N个买单
+        println!("前{}个买单 (价格降序):", limit);
+        for (i, (price, quantity)) in bids.iter().take(limit).enumerate() {
+            println!("{}. 价格: {}, 数量: {}", i+1, price, quantity);
+        }
+        println!();
+    }
+
+    /// 打印卖单信息（按价格升序）
+    ///
+    /// # 参数
+    ///
+    /// * `limit` - 要显示的档位数量
+    pub fn print_asks(&self, limit: usize) {
+        // 转换卖单为 (价格, 数量) 元组
+        let mut asks: Vec<(f64, f64)> = self.asks.iter()
+            .map(|ask| {
+                let price = ask[0].parse::<f64>().unwrap_or(0.0);
+                let quantity = ask[1].parse::<f64>().unwrap_or(0.0);
+                (price, quantity)
+            })
+            .collect();
+
+        // 按价格升序排列
+        asks.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        // 打印前N个卖单
+        println!("\n前{}个卖单 (价格升序):", limit);
+        for (i, (price, quantity)) in asks.iter().take(limit).enumerate() {
+            println!("{}. 价格: {}, 数量: {}", i+1, price, quantity);
+        }
+    }
+
+    /// 打印市场深度信息（同时展示买卖盘）
+    ///
+    /// # 参数
+    ///
+    /// * `limit` - 要显示的档位数量
+    pub fn print_market_depth(&self, limit: usize) {
+        // 转换买单和卖单为 (价格, 数量) 元组
+        let mut bids: Vec<(f64, f64)> = self.bids.iter()
+            .map(|bid| {
+                let price = bid[0].parse::<f64>().unwrap_or(0.0);
+                let quantity = bid[1].parse::<f64>().unwrap_or(0.0);
+                (price, quantity)
+            })
+            .collect();
+
+        let mut asks: Vec<(f64, f64)> = self.asks.iter()
+            .map(|ask| {
+                let price = ask[0].parse::<f64>().unwrap_or(0.0);
+                let quantity = ask[1].parse::<f64>().unwrap_or(0.0);
+                (price, quantity)
+            })
+            .collect();
+
+        // 排序
+        bids.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+        asks.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        println!("\n市场深度信息 (深度: {}):", limit);
+        println!("{:<5} {:<15} {:<15} | {:<15} {:<15} {:<5}",
+                 "档位", "买单价格", "买单数量", "卖单价格", "卖单数量", "档位");
+        println!("{:-<70}", "");
+
+        for i in 0..limit {
+            let bid_info = if i < bids.len() {
+                format!("{:<15.8} {:<15.8}", bids[i].0, bids[i].1)
+            } else {
+                format!("{:<15} {:<15}", "-", "-")
+            };
+
+            let ask_info = if i < asks.len() {
+                format!("{:<15.8} {:<15.8}", asks[i].0, asks[i].1)
+            } else {
+                format!("{:<15} {:<15}", "-", "-")
+            };
+
+            println!("{:<5} {} | {} {:<5}", i+1, bid_info, ask_info, i+1);
+        }
+    }
+
+    /// 以 ASCII 柱状图打印市场深度，格式见 [`depth_histogram`]
+    ///
+    /// # 参数
+    ///
+    /// * `limit` - 每侧要显示的档位数量
+    pub fn print_histogram(&self, limit: usize) {
+        // 无法解析的档位跳过
+        let parse = |levels: &[[String; 2]]| levels.iter()
+            .filter_map(|[price, quantity]| Some((price.parse::<Decimal>().ok()?, quantity.parse::<Decimal>().ok()?)))
+            .collect::<Vec<_>>();
+        let mut bids = parse(&self.bids);
+        let mut asks = parse(&self.asks);
+        bids.sort_by_key(|(price, _)| std::cmp::Reverse(*price));
+        asks.sort_by_key(|(price, _)| *price);
+        bids.truncate(limit);
+        asks.truncate(limit);
+        println!("\n市场深度柱状图 最后更新 ID: {}", self.lastUpdateId);
+        println!("{}", depth_histogram(&bids, &asks, HISTOGRAM_WIDTH));
+    }
+}
+
+/// 深度更新事件结构体，对应币安WebSocket深度更新消息
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize, Serialize)]
+pub struct DepthUpdate {
+    pub e: String,             // 事件类型
+    pub E: u64,                // 事件时间
+    pub s: String,             // 交易对
+    pub U: u64,                // 从上次推送至今新增的第一个update Id
+    pub u: u64,                // 从上次推送至今新增的最后一个update Id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pu: Option<u64>,       // 上一条推送的 u，只有合约推送带有
+    pub b: Vec<[String; 2]>,   // 变动的买单深度 [价格, 数量]
+    pub a: Vec<[String; 2]>,   // 变动的卖单深度 [价格, 数量]
+}
+
+impl DepthUpdate {
+    /// 转换为统一的深度消息
+    ///
+    /// 合约推送的 U 与上一条的 u 不连续，带有 `pu` 时按上一序号 pu、序号 u 检查连续性；现货没有 `pu`，序号区间为 [U, u]
+    pub fn to_depth_message(&self) -> Result<DepthMessage, Box<dyn Error>> {
+        let parse = |levels: &[[String; 2]]| {
+            levels.iter()
+                .map(|[price, quantity]| Ok((price.parse::<Decimal>()?, quantity.parse::<Decimal>()?)))
+                .collect::<Result<Vec<(Decimal, Decimal)>, Box<dyn Error>>>()
+        };
+        Ok(DepthMessage {
+            symbol: self.s.clone(),
+            kind: DepthKind::Delta,
+            bids: parse(&self.b)?,
+            asks: parse(&self.a)?,
+            continuity: match self.pu {
+                Some(prev) => Continuity::Prev { prev, sequence: self.u },
+                None => Continuity::Range { first: self.U, last: self.u },
+            },
+            checksum: None,
+            max_depth: None,
+            timestamp: self.E,
+        })
+    }
+}
+
+/// 深度快照结构体，对应币安REST API深度快照
+#[allow(non_snake_case)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DepthSnapshot {
+    pub lastUpdateId: u64,
+    pub bids: Vec<[String; 2]>,
+    pub asks: Vec<[String; 2]>,
+}
+
+/// 标记价格事件结构体，对应币安合约 `@markPrice` 推送
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize, Serialize)]
+pub struct MarkPriceUpdate {
+    pub e: String,             // 事件类型
+    pub E: u64,                // 事件时间
+    pub s: String,             // 交易对
+    pub p: String,             // 标记价格
+    pub i: String,             // 现货指数价格
+    pub P: String,             // 预估结算价
+    pub r: String,             // 资金费率
+    pub T: u64,                // 下次资金时间
+}
+
+/// 强平订单事件结构体，对应币安合约 `@forceOrder` 推送
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ForceOrderEvent {
+    pub e: String,             // 事件类型
+    pub E: u64,                // 事件时间
+    pub o: ForceOrder,         // 强平订单
+}
+
+/// 强平订单详情
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ForceOrder {
+    pub s: String,             // 交易对
+    pub S: String,             // 订单方向 BUY/SELL
+    pub q: String,             // 订单数量
+    pub p: String,             // 订单价格
+    pub ap: String,            // 平均成交价
+    pub X: String,             // 订单状态
+    pub z: String,             // 累计成交量
+    pub T: u64,                // 交易时间
+}
+
+/// 归集成交事件结构体，对应币安 `@aggTrade` 推送
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AggTradeEvent {
+    pub e: String,             // 事件类型
+    pub E: u64,                // 事件时间
+    pub s: String,             // 交易对
+    pub a: u64,                // 归集成交ID
+    pub p: String,             // 成交价格
+    pub q: String,             // 成交数量
+    pub f: u64,                // 被归集的首个成交ID
+    pub l: u64,                // 被归集的末次成交ID
+    pub T: u64,                // 成交时间
+    pub m: bool,               // 买方是否为挂单方
+}
+
+/// K线事件结构体，对应币安 `@kline_<interval>` 推送
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize, Serialize)]
+pub struct KlineEvent {
+    pub e: String,             // 事件类型
+    pub E: u64,                // 事件时间
+    pub s: String,             // 交易对
+    pub k: Kline,              // K线数据
+}
+
+/// K线详情
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Kline {
+    pub t: u64,                // 开盘时间
+    pub T: u64,                // 收盘时间
+    pub i: String,             // K线间隔
+    pub o: String,             // 开盘价
+    pub c: String,             // 收盘价
+    pub h: String,             // 最高价
+    pub l: String,             // 最低价
+    pub v: String,             // 成交量
+    pub n: u64,                // 成交笔数
+    pub x: bool,               // 是否已收盘
+    pub q: String,             // 成交额
+}
+
+/// 24小时完整行情事件，对应币安 `@ticker` 推送
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TickerEvent {
+    pub e: String,             // 事件类型
+    pub E: u64,                // 事件时间
+    pub s: String,             // 交易对
+    pub p: String,             // 24小时价格变化
+    pub P: String,             // 24小时价格变化（百分比）
+    pub c: String,             // 最新成交价
+    pub o: String,             // 24小时内第一笔成交价
+    pub h: String,             // 24小时内最高价
+    pub l: String,             // 24小时内最低价
+    pub v: String,             // 24小时成交量
+    pub q: String,             // 24小时成交额
+    pub n: u64,                // 24小时成交笔数
+}
+
+/// 24小时精简行情事件，对应币安 `@miniTicker` 推送
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize, Serialize)]
+pub struct MiniTickerEvent {
+    pub e: String,             // 事件类型
+    pub E: u64,                // 事件时间
+    pub s: String,             // 交易对
+    pub c: String,             // 最新成交价
+    pub o: String,             // 24小时内第一笔成交价
+    pub h: String,             // 24小时内最高价
+    pub l: String,             // 24小时内最低价
+    pub v: String,             // 24小时成交量
+    pub q: String,             // 24小时成交额
+}
+
+/// 交易规则信息，对应 REST `exchangeInfo`
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ExchangeInfo {
+    pub symbols: Vec<SymbolInfo>,
+}
+
+/// 单个交易对的交易规则
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SymbolInfo {
+    pub symbol: String,        // 交易对
+    pub status: String,        // 交易状态，例如 TRADING
+    pub baseAsset: String,     // 基础资产
+    pub quoteAsset: String,    // 计价资产
+}
+
+/// 获取交易规则信息
+pub fn get_exchange_info(market: Market) -> Result<ExchangeInfo, Box<dyn Error>> {
+    let url = market.exchange_info_url();
+
+    info!(url, "正在请求交易规则");
+
+    let client = reqwest::blocking::Client::new();
+    let response = client.get(url).send()?;
+
+    if response.status().is_success() {
+        let exchange_info: ExchangeInfo = response.json()?;
+        Ok(exchange_info)
+    } else {
+        Err(format!("API 请求失败: {}", response.status()).into())
+    }
+}
+
+/// 获取币安交易所的深度快照数据
+///
+/// # 参数
+///
+/// * `market` - 市场类型，决定请求现货还是合约接口
+/// * `symbol` - 交易对符号，例如 "BNBBTC"
+/// * `limit` - 返回的深度级别，可选值：5, 10, 20, 50, 100, 500, 1000, 5000（合约最大 1000）
+///
+/// # 返回值
+///
+/// 返回 Result，成功时包含 DepthSnapshot 结构体，失败时包含错误信息
+pub fn get_depth_snapshot(market: Market, symbol: &str, limit: u32) -> Result<DepthSnapshot, Box<dyn Error>> {
+    let url = format!(
+        "{}?symbol={}&limit={}",
+        market.depth_url(), symbol, limit.min(market.max_depth_limit())
+    );
+
+    info!(url, "正在请求深度数据");
+
+    // 使用 reqwest 的阻塞客户端发送请求
+    let client = reqwest::blocking::Client::new();
+    let response = client.get(&url).send()?;
+
+    if response.status().is_success() {
+        let snapshot: DepthSnapshot = response.json()?;
+        Ok(snapshot)
+    } else {
+        Err(format!("API 请求失败: {}", response.status()).into())
+    }
+}
+
+/// 服务器时间响应
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ServerTime {
+    pub serverTime: u64,       // 服务器时间（毫秒）
+}
+
+/// 获取币安服务器时间（毫秒）
+///
+/// 由调用方提供客户端，多次请求复用同一连接，往返时间不包含建立连接的耗时
+pub fn get_server_time(client: &reqwest::blocking::Client, market: Market) -> Result<u64, Box<dyn Error>> {
+    let response = client.get(market.time_url()).send()?;
+
+    if response.status().is_success() {
+        let time: ServerTime = response.json()?;
+        Ok(time.serverTime)
+    } else {
+        Err(format!("API 请求失败: {}", response.status()).into())
+    }
+}
+
+/// 历史资金费率记录，对应合约 REST `/fapi/v1/fundingRate`
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize, Serialize)]
+pub struct FundingRateRecord {
+    pub symbol: String,        // 交易对
+    pub fundingRate: String,   // 资金费率
+    pub fundingTime: u64,      // 资金费时间
+}
+
+/// 获取合约历史资金费率（按时间升序）
+///
+/// # 参数
+///
+/// * `symbol` - 交易对符号，例如 "BNBUSDT"
+/// * `limit` - 返回的记录数，最大 1000
+pub fn get_funding_rate_history(symbol: &str, limit: u32) -> Result<Vec<FundingRateRecord>, Box<dyn Error>> {
+    let url = format!(
+        "https://fapi.binance.com/fapi/v1/fundingRate?symbol={}&limit={}",
+        symbol, limit.min(1000)
+    );
+
+    info!(url, "正在请求资金费率");
+
+    let client = reqwest::blocking::Client::new();
+    let response = client.get(&url).send()?;
+
+    if response.status().is_success() {
+        let records: Vec<FundingRateRecord> = response.json()?;
+        Ok(records)
+    } else {
+        Err(format!("API 请求失败: {}", response.status()).into())
+    }
+}
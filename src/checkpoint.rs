@@ -0,0 +1,163 @@
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::binance::Market;
+use crate::manager::BINANCE_VENUE;
+use crate::order_book::OrderBook;
+use crate::recorder::capture::CaptureReader;
+use crate::recorder::{read_file, utc_millis_label};
+use crate::replay::book_at;
+
+/// 单个订单薄的检查点
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookCheckpoint {
+    pub symbol: String,
+    /// 已应用的最后一个更新ID
+    pub last_update_id: u64,
+    /// 买单，价格降序
+    pub bids: Vec<(Decimal, Decimal)>,
+    /// 卖单，价格升序
+    pub asks: Vec<(Decimal, Decimal)>,
+}
+
+impl BookCheckpoint {
+    /// 保存订单薄的完整状态
+    pub fn from_book(symbol: &str, book: &OrderBook) -> Self {
+        BookCheckpoint {
+            symbol: symbol.to_string(),
+            last_update_id: book.last_update_id,
+            bids: book.bids_list(),
+            asks: book.asks_list(),
+        }
+    }
+
+    /// 恢复为订单薄
+    pub fn to_book(&self) -> OrderBook {
+        OrderBook::from_levels(self.last_update_id, &self.bids, &self.asks)
+    }
+}
+
+/// 诊断用的订单薄转储，包含所有档位
+///
+/// 与线上行为不一致时对照交易所快照或记录回放的结果，找出分歧的档位
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookDump {
+    pub venue: String,
+    pub symbol: String,
+    /// 转储时间（毫秒）
+    pub dumped_at: u64,
+    /// 已应用的最后一个更新ID
+    pub last_update_id: u64,
+    /// 买单，价格降序
+    pub bids: Vec<(Decimal, Decimal)>,
+    /// 卖单，价格升序
+    pub asks: Vec<(Decimal, Decimal)>,
+}
+
+impl BookDump {
+    pub fn from_book(venue: &str, symbol: &str, book: &OrderBook, dumped_at: u64) -> Self {
+        BookDump {
+            venue: venue.to_string(),
+            symbol: symbol.to_string(),
+            dumped_at,
+            last_update_id: book.last_update_id,
+            bids: book.bids_list(),
+            asks: book.asks_list(),
+        }
+    }
+
+    /// 写入 `目录/交易所-交易对-YYYYMMDD-HHMMSS.mmm.json`，先写临时文件再重命名，返回文件路径
+    ///
+    /// 交易对中不能用于文件名的字符（例如 `BTC/USD` 的斜杠）替换为下划线
+    pub fn save(&self, dir: impl AsRef<Path>) -> Result<PathBuf, Box<dyn Error>> {
+        let symbol: String = self.symbol.chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        let path = dir.as_ref().join(format!("{}-{}-{}.json", self.venue, symbol, utc_millis_label(self.dumped_at)));
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        fs::write(&temporary, serde_json::to_vec_pretty(self)?)?;
+        fs::rename(&temporary, &path)?;
+        Ok(path)
+    }
+}
+
+/// 币安本地订单薄的磁盘检查点
+///
+/// 重启时先载入检查点，再用 WebSocket 收到的第一条增量更新检查能否衔接：
+/// 更新ID连续时直接继续，出现缺口时丢弃检查点并回退到深度快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub market: Market,
+    /// 保存时间（毫秒）
+    pub saved_at: u64,
+    pub books: Vec<BookCheckpoint>,
+}
+
+impl Checkpoint {
+    /// 写入文件，先写临时文件再重命名，避免中途退出留下不完整的检查点
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+        let path = path.as_ref();
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        fs::write(&temporary, serde_json::to_vec(self)?)?;
+        fs::rename(&temporary, path)?;
+        Ok(())
+    }
+
+    /// 读取文件
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+
+    /// 用记录文件末尾的数据重建币安订单薄，作为启动时的检查点
+    ///
+    /// `path` 为目录时使用其中文件名最大（即最新一小时）的 `.zcap`、`.ndjson` 或 `.msgpack` 文件。
+    /// 压缩记录借助关键帧只解压末尾需要的块。另一个进程仍在写入记录时，记录末尾与实时更新
+    /// 通常能够衔接，订单薄不需要重新获取快照
+    ///
+    /// # 参数
+    ///
+    /// * `path` - 记录文件或目录
+    /// * `market` - 记录对应的市场，恢复时与当前市场比较
+    /// * `symbols` - 需要重建的交易对
+    pub fn from_recording(path: impl AsRef<Path>, market: Market, symbols: &[String]) -> Result<Self, Box<dyn Error>> {
+        let path = latest_recording(path.as_ref())?;
+        let mut books = Vec::new();
+        let saved_at;
+        if path.extension().is_some_and(|extension| extension == "zcap") {
+            let mut reader = CaptureReader::open(&path)?;
+            saved_at = reader.blocks().last().map_or(0, |block| block.last_time);
+            for symbol in symbols {
+                if let Some(book) = reader.book_at(BINANCE_VENUE, symbol, u64::MAX)? {
+                    books.push(BookCheckpoint::from_book(symbol, &book));
+                }
+            }
+        } else {
+            let mut records = read_file(&path)?;
+            records.sort_by_key(|record| record.recv_time());
+            saved_at = records.last().map_or(0, |record| record.recv_time());
+            for symbol in symbols {
+                if let Some(book) = book_at(&records, BINANCE_VENUE, symbol, u64::MAX) {
+                    books.push(BookCheckpoint::from_book(symbol, &book));
+                }
+            }
+        }
+        Ok(Checkpoint { market, saved_at, books })
+    }
+}
+
+/// 目录中最新的记录文件，`path` 不是目录时原样返回
+fn latest_recording(path: &Path) -> Result<PathBuf, Box<dyn Error>> {
+    if !path.is_dir() {
+        return Ok(path.to_path_buf());
+    }
+    fs::read_dir(path)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|entry| entry.extension().is_some_and(|extension| extension == "zcap" || extension == "ndjson" || extension == "msgpack"))
+        .max()
+        .ok_or_else(|| format!("{} 中没有记录文件", path.display()).into())
+}
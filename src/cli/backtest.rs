@@ -0,0 +1,19 @@
+//! `backtest`：在记录文件上回测模拟交易策略
+
+use std::error::Error;
+use tracing::info;
+
+use order_book::strategy::{backtest, parse_strategy};
+
+use super::{parse_fees, read_sorted, Options};
+
+/// 在 `--file` 指定的记录文件上回测 `--strategy` 指定的策略并打印结果
+pub fn run(options: &Options) -> Result<(), Box<dyn Error>> {
+    let path = options.require("file", "backtest 需要用 --file=记录文件 指定记录文件")?;
+    let spec = options.require("strategy", "backtest 需要用 --strategy= 指定策略")?;
+    let strategy = parse_strategy(spec).ok_or_else(|| format!("策略参数格式错误: {}", spec))?;
+    let records = read_sorted(path)?;
+    info!(records = records.len(), "载入记录");
+    backtest(&records, strategy, parse_fees(options)?)?.print();
+    Ok(())
+}
@@ -0,0 +1,39 @@
+//! `book-at`：从记录文件重建指定时刻的订单薄
+
+use std::error::Error;
+
+use order_book::checkpoint::BookCheckpoint;
+use order_book::order_book::BookMetadata;
+use order_book::recorder::capture::CaptureReader;
+use order_book::recorder::read_file;
+use order_book::replay::book_at;
+
+use super::Options;
+
+/// 从记录文件重建指定时刻的订单薄，打印并按需导出
+pub fn run(options: &Options) -> Result<(), Box<dyn Error>> {
+    let path = options.require("file", "book-at 需要用 --file=记录文件 指定记录文件")?;
+    let venue = options.require("venue", "book-at 需要用 --venue= 指定交易所")?;
+    let symbol = options.require("symbol", "book-at 需要用 --symbol= 指定交易对")?;
+    let time = options.parse::<u64>("time")?.ok_or("book-at 需要用 --time= 指定毫秒时间戳")?;
+    let depth = options.parse::<usize>("depth")?.unwrap_or(20);
+
+    let book = if path.ends_with(".zcap") {
+        CaptureReader::open(path)?.book_at(venue, symbol, time)?
+    } else {
+        book_at(&read_file(path)?, venue, symbol, time)
+    };
+    let book = book.ok_or_else(|| format!("{} 之前没有 {} {} 的关键帧或快照", time, venue, symbol))?;
+    println!("{} {} 在 {} 的订单薄，序号 {}", venue, symbol, time, book.last_update_id);
+    book.print_summary(depth);
+    if let Some(out) = options.value("out") {
+        if out.ends_with(".book") {
+            let metadata = BookMetadata { venue: venue.to_string(), symbol: symbol.to_string(), time };
+            book.save_to(out, &metadata)?;
+        } else {
+            std::fs::write(out, serde_json::to_vec_pretty(&BookCheckpoint::from_book(symbol, &book))?)?;
+        }
+        println!("已导出到 {}", out);
+    }
+    Ok(())
+}
@@ -0,0 +1,38 @@
+//! `compact`：把记录文件压缩改写为关键帧加增量的格式
+
+use std::error::Error;
+use std::path::Path;
+
+use order_book::recorder::Recorder;
+use order_book::recorder::capture::{CaptureRecorder, DEFAULT_KEYFRAME_MS, DEFAULT_LEVEL};
+use order_book::recorder::compact::Compactor;
+
+use super::{read_sorted, Options};
+
+/// 把 `--file` 指定的记录文件压缩改写到 `--out` 目录
+pub fn run(options: &Options) -> Result<(), Box<dyn Error>> {
+    let path = options.require("file", "compact 需要用 --file=记录文件 指定记录文件")?;
+    let out = options.require("out", "compact 需要用 --out= 指定输出目录")?;
+    let depth = options.parse::<usize>("depth")?;
+    let keyframe = options.parse::<u64>("keyframe")?.map_or(DEFAULT_KEYFRAME_MS, |secs| secs * 1000);
+    let level = options.parse::<i32>("level")?.unwrap_or(DEFAULT_LEVEL);
+    let keep_frames = options.flag("keep-raw");
+
+    let records = read_sorted(path)?;
+    let mut compactor = Compactor::new(depth, keep_frames);
+    let mut recorder = CaptureRecorder::new(out, level, keyframe)?;
+    for record in records {
+        compactor.push(record, &mut recorder)?;
+    }
+    recorder.flush()?;
+    let stats = compactor.stats();
+    println!("读取 {} 条记录，写入 {} 条，丢弃原始消息 {} 条、增量更新 {} 条、原关键帧 {} 条，序号断开 {} 次",
+             stats.input, stats.written, stats.dropped_frames, stats.dropped_deltas, stats.dropped_keyframes, stats.gaps);
+    let size = |path: &Path| std::fs::metadata(path).map_or(0, |metadata| metadata.len());
+    let output_size: u64 = std::fs::read_dir(out)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| size(&entry.path()))
+        .sum();
+    println!("输入 {} 字节，输出目录 {} 共 {} 字节", size(Path::new(path)), out, output_size);
+    Ok(())
+}
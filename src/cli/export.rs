@@ -0,0 +1,45 @@
+//! `export`：把深度快照的前若干档写入 CSV
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use rust_decimal::Decimal;
+
+use order_book::binance::{get_depth_snapshot, Market};
+use order_book::order_book::{OrderBook, Side};
+
+use super::Options;
+
+/// 币安深度接口接受的档位数
+const DEPTH_LIMITS: [u32; 8] = [5, 10, 20, 50, 100, 500, 1000, 5000];
+
+/// 拉取深度快照，把前若干档写入 CSV
+pub fn run(market: Market, options: &Options) -> Result<(), Box<dyn Error>> {
+    let symbol = options.require("symbol", "export 需要用 --symbol=交易对 指定交易对")?.to_uppercase();
+    let depth = options.parse::<usize>("depth")?.unwrap_or(100);
+    match options.value("format").unwrap_or("csv") {
+        "csv" => {}
+        other => return Err(format!("无效的格式: {}，可选 csv", other).into()),
+    }
+    let path = options.value("out").unwrap_or("depth.csv");
+
+    // 合约只接受固定的档位数，取不小于所需档位的最小值
+    let limit = DEPTH_LIMITS.iter().copied().find(|limit| *limit as usize >= depth).unwrap_or(market.max_depth_limit());
+    let snapshot = get_depth_snapshot(market, &symbol, limit)?;
+    let book = OrderBook::from_snapshot(snapshot)?;
+
+    let mut file = BufWriter::new(File::create(path)?);
+    writeln!(file, "price,quantity,side,cumulative_quantity,notional")?;
+    let mut rows = 0;
+    for (side, name) in [(Side::Bid, "bid"), (Side::Ask, "ask")] {
+        let mut cumulative_quantity = Decimal::ZERO;
+        for level in book.notional_levels(side, depth) {
+            cumulative_quantity += level.quantity;
+            writeln!(file, "{},{},{},{},{}", level.price, level.quantity, name, cumulative_quantity, level.notional)?;
+            rows += 1;
+        }
+    }
+    file.flush()?;
+    println!("{} 深度共 {} 档，已写入 {}", symbol, rows, path);
+    Ok(())
+}
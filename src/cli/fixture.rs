@@ -0,0 +1,70 @@
+//! `fixture`：从记录文件生成测试数据，或校验已有的测试数据
+
+use std::error::Error;
+use std::path::PathBuf;
+
+use order_book::binance::Market;
+use order_book::fixture::Fixture;
+
+use super::{read_sorted, split_list, Options};
+
+/// 用 `--check` 校验测试数据，或从 `--file` 指定的记录文件生成测试数据
+pub fn run(options: &Options) -> Result<(), Box<dyn Error>> {
+    match options.value("check") {
+        Some(paths) => check(paths),
+        None => distill(options),
+    }
+}
+
+/// 校验逗号分隔的测试数据文件，目录按其中的 `.json` 文件展开
+fn check(paths: &str) -> Result<(), Box<dyn Error>> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    for path in split_list(paths) {
+        if std::fs::metadata(&path)?.is_dir() {
+            let mut entries = std::fs::read_dir(&path)?
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<Result<Vec<_>, _>>()?;
+            entries.retain(|entry| entry.extension().is_some_and(|extension| extension == "json"));
+            entries.sort();
+            files.extend(entries);
+        } else {
+            files.push(path.into());
+        }
+    }
+    let mut failed = 0;
+    for file in &files {
+        match Fixture::load(file).and_then(|fixture| fixture.verify()) {
+            Ok(()) => println!("通过 {}", file.display()),
+            Err(e) => {
+                println!("失败 {}: {}", file.display(), e);
+                failed += 1;
+            }
+        }
+    }
+    println!("共 {} 个，失败 {} 个", files.len(), failed);
+    if failed > 0 {
+        return Err(format!("{} 个测试数据不一致", failed).into());
+    }
+    Ok(())
+}
+
+/// 截取记录文件中一个交易对的快照和之后的增量更新，写成测试数据
+fn distill(options: &Options) -> Result<(), Box<dyn Error>> {
+    let path = options.require("file", "fixture 需要用 --file=记录文件 指定记录文件，或用 --check= 校验测试数据")?;
+    let venue = options.require("venue", "fixture 需要用 --venue= 指定交易所")?;
+    let symbol = options.require("symbol", "fixture 需要用 --symbol= 指定交易对")?;
+    let out = options.require("out", "fixture 需要用 --out= 指定输出文件")?;
+    let from = options.parse::<u64>("from")?.unwrap_or(0);
+    let deltas = options.parse::<usize>("deltas")?.unwrap_or(100);
+    let market = match options.value("market") {
+        Some(name) => Market::parse(name).ok_or_else(|| format!("未知市场: {}", name))?,
+        None => Market::Spot,
+    };
+
+    let records = read_sorted(path)?;
+    let fixture = Fixture::distill(&records, market, venue, symbol, from, deltas)?;
+    fixture.save(out)?;
+    println!("{} {} 起点时间 {}，{} 条增量更新，序号 {}，哈希 {:08x}，已写入 {}", venue, symbol,
+             fixture.records[0].recv_time(), fixture.deltas(), fixture.expected_update_id, fixture.expected_hash, out);
+    Ok(())
+}
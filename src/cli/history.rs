@@ -0,0 +1,29 @@
+//! `history`：下载币安历史数据并转为记录文件
+
+use std::error::Error;
+
+use order_book::binance::Market;
+use order_book::history::{HistoryData, HistoryDownloader};
+
+use super::{split_list, Options};
+
+/// 下载 `--from` 到 `--to` 之间的历史数据并转为记录文件
+pub fn run(market: Market, symbol: &str, options: &Options) -> Result<(), Box<dyn Error>> {
+    let from = options.require("from", "history 需要用 --from=YYYY-MM-DD 指定起始日期")?;
+    let to = options.value("to").unwrap_or(from);
+    let dir = options.value("out").unwrap_or("history");
+    let data = match options.value("data") {
+        Some(list) => split_list(list).iter()
+            .map(|name| HistoryData::parse(name).ok_or_else(|| format!("无效的数据类型: {}，可选 aggTrades 或 bookTicker", name)))
+            .collect::<Result<Vec<_>, _>>()?,
+        None => HistoryData::defaults(market),
+    };
+    let written = HistoryDownloader::new(market, symbol, dir)?.download(from, to, &data)?;
+    for path in &written {
+        println!("已写入 {}", path.display());
+    }
+    if written.is_empty() {
+        println!("{} 到 {} 没有可用的数据", from, to);
+    }
+    Ok(())
+}
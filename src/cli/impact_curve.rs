@@ -0,0 +1,39 @@
+//! `impact-curve`：按深度快照计算市场冲击曲线
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use rust_decimal::Decimal;
+
+use order_book::binance::{get_depth_snapshot, Market};
+use order_book::order_book::{OrderBook, Side};
+
+use super::Options;
+
+/// 拉取深度快照并把市场冲击曲线写入 CSV
+pub fn run(market: Market, symbol: &str, options: &Options) -> Result<(), Box<dyn Error>> {
+    let side = match options.value("side").unwrap_or("buy") {
+        "buy" => Side::Ask,
+        "sell" => Side::Bid,
+        other => return Err(format!("无效的方向: {}，可选 buy 或 sell", other).into()),
+    };
+    let max_notional = options.parse::<Decimal>("max-notional")?.unwrap_or(Decimal::from(100_000));
+    let step = options.parse::<Decimal>("step")?.unwrap_or(Decimal::from(1_000));
+    let path = options.value("out").unwrap_or("impact.csv");
+
+    let snapshot = get_depth_snapshot(market, symbol, market.max_depth_limit())?;
+    let book = OrderBook::from_snapshot(snapshot)?;
+    let points = book.impact_curve(side, max_notional, step);
+
+    let mut file = BufWriter::new(File::create(path)?);
+    writeln!(file, "notional,quantity,average_price,impact_bps")?;
+    for point in &points {
+        writeln!(file, "{},{},{},{}", point.notional, point.quantity.round_dp(8), point.average_price.round_dp(8), point.impact_bps.round_dp(4))?;
+    }
+    file.flush()?;
+    println!("{} 冲击曲线共 {} 个点，已写入 {}", symbol, points.len(), path);
+    if points.last().is_none_or(|point| point.notional + step <= max_notional) {
+        println!("深度不足，曲线未达到 {}", max_notional);
+    }
+    Ok(())
+}
@@ -0,0 +1,26 @@
+//! `itch`：把记录文件转为 ITCH 风格的二进制消息
+
+use std::error::Error;
+use std::path::Path;
+
+use order_book::recorder::Recorder;
+use order_book::recorder::itch::{ItchRecorder, DEFAULT_DECIMALS};
+
+use super::{read_sorted, Options};
+
+/// 把 `--file` 指定的记录文件转为 ITCH 风格的二进制文件
+pub fn run(options: &Options) -> Result<(), Box<dyn Error>> {
+    let path = options.require("file", "itch 需要用 --file=记录文件 指定记录文件")?;
+    let out = options.require("out", "itch 需要用 --out= 指定输出目录")?;
+    let price_decimals = options.parse::<u32>("price-decimals")?.unwrap_or(DEFAULT_DECIMALS);
+    let quantity_decimals = options.parse::<u32>("quantity-decimals")?.unwrap_or(DEFAULT_DECIMALS);
+
+    let records = read_sorted(path)?;
+    let mut recorder = ItchRecorder::new(Some(Path::new(out)), None, price_decimals, quantity_decimals)?;
+    for record in &records {
+        recorder.record(record)?;
+    }
+    recorder.flush()?;
+    println!("已把 {} 条记录转为 ITCH 文件，目录 {}", records.len(), out);
+    Ok(())
+}
@@ -0,0 +1,25 @@
+//! `lobster`：把记录文件转为 LOBSTER 格式
+
+use std::error::Error;
+
+use order_book::recorder::Recorder;
+use order_book::recorder::lobster::{LobsterRecorder, DEFAULT_LEVELS, DEFAULT_PRICE_SCALE};
+
+use super::{read_sorted, Options};
+
+/// 把 `--file` 指定的记录文件转为 LOBSTER 格式的文件
+pub fn run(options: &Options) -> Result<(), Box<dyn Error>> {
+    let path = options.require("file", "lobster 需要用 --file=记录文件 指定记录文件")?;
+    let out = options.require("out", "lobster 需要用 --out= 指定输出目录")?;
+    let levels = options.parse::<usize>("levels")?.unwrap_or(DEFAULT_LEVELS);
+    let price_scale = options.parse::<u32>("price-scale")?.unwrap_or(DEFAULT_PRICE_SCALE);
+
+    let records = read_sorted(path)?;
+    let mut recorder = LobsterRecorder::new(out, levels, price_scale)?;
+    for record in &records {
+        recorder.record(record)?;
+    }
+    recorder.flush()?;
+    println!("已把 {} 条记录转为 LOBSTER 文件，目录 {}", records.len(), out);
+    Ok(())
+}
@@ -0,0 +1,159 @@
+//! 命令行子命令：每个子命令一个模块，共用 `--名称=值` 形式的参数解析
+
+pub mod backtest;
+pub mod book_at;
+pub mod compact;
+pub mod export;
+pub mod fixture;
+pub mod history;
+pub mod impact_curve;
+pub mod itch;
+pub mod lobster;
+pub mod query;
+pub mod run;
+pub mod verify;
+
+use std::error::Error;
+use std::fmt::Display;
+use std::str::FromStr;
+use rust_decimal::Decimal;
+
+use order_book::binance::Market;
+use order_book::fees::FeeSchedule;
+use order_book::instrument::{Instrument, SymbolMap};
+use order_book::recorder::{read_file, Record};
+use order_book::telemetry::{LogFile, LogRotation};
+
+/// 命令行中以 `--` 开头的参数，按出现顺序保存
+#[derive(Debug, Default)]
+pub struct Options(Vec<String>);
+
+impl Options {
+    pub fn new(options: Vec<String>) -> Self {
+        Self(options)
+    }
+
+    /// `--名称=值` 的值，出现多次时取第一个
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.values(name).next()
+    }
+
+    /// 所有 `--名称=值` 的值，按出现顺序
+    pub fn values<'a>(&'a self, name: &str) -> impl Iterator<Item = &'a str> {
+        self.0.iter().filter_map(move |option| option.strip_prefix("--")?.strip_prefix(name)?.strip_prefix('='))
+    }
+
+    /// 是否有不带值的 `--名称`
+    pub fn flag(&self, name: &str) -> bool {
+        self.0.iter().any(|option| option.strip_prefix("--") == Some(name))
+    }
+
+    /// 解析 `--名称=值`，没有指定时返回 None
+    pub fn parse<T>(&self, name: &str) -> Result<Option<T>, Box<dyn Error>>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.value(name)
+            .map(|value| value.parse::<T>().map_err(|e| format!("--{} 参数无效: {} ({})", name, value, e).into()))
+            .transpose()
+    }
+
+    /// 必需的 `--名称=值`，缺少时以 `message` 作为错误
+    pub fn require(&self, name: &str, message: &str) -> Result<&str, Box<dyn Error>> {
+        self.value(name).ok_or_else(|| message.into())
+    }
+
+    /// 所有参数，按出现顺序
+    pub fn iter(&self) -> std::slice::Iter<'_, String> {
+        self.0.iter()
+    }
+
+    /// 只看名称不看值：`--名称` 或 `--名称=值` 都算
+    pub fn has(&self, name: &str) -> bool {
+        self.flag(name) || self.value(name).is_some()
+    }
+}
+
+/// 取出位置参数开头的市场名称，没有时为现货
+pub fn parse_market(args: &mut std::iter::Peekable<impl Iterator<Item = String>>) -> Market {
+    match args.peek().and_then(|arg| Market::parse(arg)) {
+        Some(market) => {
+            args.next();
+            market
+        }
+        None => Market::Spot,
+    }
+}
+
+/// 解析 `--log-file` 及轮转参数，没有 `--log-file` 时返回 None
+pub fn parse_log_file(options: &Options) -> Result<Option<LogFile>, Box<dyn Error>> {
+    let Some(path) = options.value("log-file") else {
+        return Ok(None);
+    };
+    let mut log_file = LogFile::new(path);
+    if let Some(rotation) = options.value("log-rotate") {
+        log_file.rotation = LogRotation::parse(rotation).ok_or_else(|| format!("未知的日志轮转周期: {}，可选 daily、hourly、never", rotation))?;
+    }
+    if let Some(size) = options.value("log-max-size") {
+        let megabytes = size.parse::<u64>().map_err(|_| format!("日志文件大小无效: {}", size))?;
+        log_file.max_size = Some(megabytes * 1024 * 1024).filter(|bytes| *bytes > 0);
+    }
+    if let Some(keep) = options.value("log-keep") {
+        log_file.keep = keep.parse().map_err(|_| format!("日志保留数量无效: {}", keep))?;
+    }
+    Ok(Some(log_file))
+}
+
+/// 读取 `--fees` 指定的手续费配置，再用 `--taker-fee` 覆盖吃单手续费（基点）
+pub fn parse_fees(options: &Options) -> Result<FeeSchedule, Box<dyn Error>> {
+    let mut fees = FeeSchedule::new();
+    for path in options.values("fees") {
+        fees = FeeSchedule::load(path)?;
+    }
+    for list in options.values("taker-fee") {
+        for (venue, fee_bps) in split_legs(list) {
+            if let Ok(fee_bps) = fee_bps.parse::<Decimal>() {
+                let maker_bps = fees.maker_bps(&venue);
+                fees.set_venue_fees(&venue, maker_bps, fee_bps);
+            }
+        }
+    }
+    Ok(fees)
+}
+
+/// 拆分逗号分隔的参数列表
+pub fn split_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .filter(|item| !item.is_empty())
+        .map(|item| item.to_string())
+        .collect()
+}
+
+/// 拆分逗号分隔的 `交易所:交易对` 列表，统一格式的品种（含 `/`）转为交易所原生名称
+pub fn resolve_legs(list: &str, symbol_map: &SymbolMap) -> Vec<(String, String)> {
+    split_legs(list).into_iter()
+        .map(|(venue, symbol)| match Instrument::parse(&symbol) {
+            Ok(instrument) if symbol.contains('/') => {
+                let symbol = symbol_map.venue_symbol(&instrument, &venue);
+                (venue, symbol)
+            }
+            _ => (venue, symbol),
+        })
+        .collect()
+}
+
+/// 拆分逗号分隔的 `名称:值` 列表，例如 `binance:BTCUSDT,okx:BTC-USDT`
+pub fn split_legs(list: &str) -> Vec<(String, String)> {
+    split_list(list).iter()
+        .filter_map(|leg| leg.split_once(':'))
+        .map(|(venue, value)| (venue.to_string(), value.to_string()))
+        .collect()
+}
+
+/// 读取记录文件并按接收时间排序
+pub fn read_sorted(path: &str) -> Result<Vec<Record>, Box<dyn Error>> {
+    let mut records = read_file(path)?;
+    records.sort_by_key(|record| record.recv_time());
+    Ok(records)
+}
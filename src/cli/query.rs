@@ -0,0 +1,24 @@
+//! `query`：用 SQL 查询记录文件
+
+use std::error::Error;
+use tracing::info;
+
+use order_book::recorder::query::QueryEngine;
+
+use super::{split_list, Options};
+
+/// 载入 `--data` 指定的记录文件并执行查询
+pub fn run(options: &Options, sql: &str) -> Result<(), Box<dyn Error>> {
+    let paths = options.require("data", "query 需要用 --data=文件或目录 指定记录文件")?;
+    if sql.trim().is_empty() {
+        return Err("缺少 SQL 语句".into());
+    }
+    let mut engine = QueryEngine::new()?;
+    let mut loaded = 0;
+    for path in split_list(paths) {
+        loaded += engine.load(&path)?;
+    }
+    info!(files = loaded, "已载入记录文件");
+    engine.query(sql)?.print();
+    Ok(())
+}
@@ -0,0 +1,210 @@
+//! 实时分析：策略、套利、价差和各类订单薄指标
+
+use std::error::Error;
+use rust_decimal::Decimal;
+
+use order_book::analytics::bars::{BarBuilder, BarKind, BarSource};
+use order_book::analytics::imbalance::ImbalanceMonitor;
+use order_book::analytics::kyle_lambda::KyleLambdaEstimator;
+use order_book::analytics::ofi::OfiCalculator;
+use order_book::analytics::queue::RestingOrder;
+use order_book::analytics::spread_stats::SpreadTracker;
+use order_book::analytics::trade_spread::TradeSpreadAnalyzer;
+use order_book::analytics::update_rate::UpdateRateMonitor;
+use order_book::analytics::volatility::VolatilityTracker;
+use order_book::analytics::vpin::VpinCalculator;
+use order_book::analytics::wall::WallDetector;
+use order_book::arbitrage::ArbitrageDetector;
+use order_book::fees::FeeSchedule;
+use order_book::instrument::SymbolMap;
+use order_book::latency::LeadLagTracker;
+use order_book::manager::{BookManager, BINANCE_VENUE};
+use order_book::order_book::Side;
+use order_book::spread::SpreadRecorder;
+use order_book::strategy::parse_strategy;
+use order_book::triangular::TriangularScanner;
+
+use crate::cli::{resolve_legs, split_list, Options};
+
+/// 按命令行参数给订单薄管理器加上分析指标
+pub fn configure(manager: &mut BookManager, options: &Options, fees: &FeeSchedule, symbol_map: &SymbolMap) -> Result<(), Box<dyn Error>> {
+    // 模拟交易策略，按实时行情模拟成交，退出时打印结果
+    for spec in options.values("strategy") {
+        let strategy = parse_strategy(spec).ok_or_else(|| format!("策略参数格式错误: {}", spec))?;
+        manager.add_strategy(strategy, fees.clone());
+    }
+
+    // 跨交易所套利检测：交易所:交易对
+    for legs in options.values("arb") {
+        let mut detector = ArbitrageDetector::new(resolve_legs(legs, symbol_map));
+        detector.set_fee_schedule(fees.clone());
+        for min_profit in options.values("arb-min-profit").filter_map(|profit| profit.parse::<Decimal>().ok()) {
+            detector.set_min_profit(min_profit);
+        }
+        manager.add_arbitrage_detector(detector);
+    }
+
+    // 价格发现领先者统计
+    let lead_lag_window = options.value("lead-lag-window")
+        .and_then(|window| window.parse::<u64>().ok())
+        .unwrap_or(500);
+    for legs in options.values("lead-lag") {
+        manager.add_lead_lag_tracker(LeadLagTracker::new(resolve_legs(legs, symbol_map), lead_lag_window));
+    }
+
+    // 跨交易所价差记录，未指定文件时输出到标准输出
+    let spread_interval = options.value("spread-interval")
+        .and_then(|interval| interval.parse::<u64>().ok())
+        .unwrap_or(1000);
+    let spread_file = options.value("spread-file");
+    for legs in options.values("spread") {
+        let legs = resolve_legs(legs, symbol_map);
+        let [leg_a, leg_b] = legs.as_slice() else {
+            return Err("价差记录需要两个交易所".into());
+        };
+        let recorder = match spread_file {
+            Some(path) => SpreadRecorder::to_file(leg_a.clone(), leg_b.clone(), spread_interval, path),
+            None => SpreadRecorder::new(leg_a.clone(), leg_b.clone(), spread_interval, Box::new(std::io::stdout())),
+        };
+        manager.add_spread_recorder(recorder.map_err(|e| format!("创建价差记录失败: {}", e))?);
+    }
+
+    // 买卖盘不平衡度穿越阈值提醒
+    for spec in options.values("imbalance") {
+        let (levels, thresholds) = spec.split_once(':').unwrap_or((spec, "-0.5,0.5"));
+        let thresholds: Vec<Decimal> = split_list(thresholds).iter()
+            .filter_map(|threshold| threshold.parse::<Decimal>().ok())
+            .collect();
+        let levels = levels.parse::<usize>().map_err(|_| format!("不平衡度档位数格式错误: {}", spec))?;
+        manager.set_imbalance_monitor(ImbalanceMonitor::new(levels, &thresholds));
+    }
+
+    // 订单流不平衡
+    for spec in options.values("ofi") {
+        let (window, interval) = spec.split_once(':').unwrap_or((spec, "1000"));
+        let (Ok(window), Ok(interval)) = (window.parse::<u64>(), interval.parse::<u64>()) else {
+            return Err(format!("订单流不平衡参数格式错误: {}", spec).into());
+        };
+        manager.set_ofi_calculator(OfiCalculator::new(window, interval));
+    }
+
+    // 中间价已实现波动率
+    for spec in options.values("volatility") {
+        let (interval, window) = spec.split_once(':').unwrap_or((spec, "300"));
+        let (Ok(interval), Ok(window)) = (interval.parse::<u64>(), window.parse::<usize>()) else {
+            return Err(format!("波动率参数格式错误: {}", spec).into());
+        };
+        manager.set_volatility_tracker(VolatilityTracker::new(interval, window));
+    }
+
+    // 买卖价差统计
+    for window in options.values("spread-stats") {
+        let window = window.parse::<u64>().map_err(|_| format!("价差统计窗口格式错误: {}", window))?;
+        manager.set_spread_tracker(SpreadTracker::new(window));
+    }
+
+    // 挂单墙检测
+    for spec in options.values("walls") {
+        let (multiple, depth) = spec.split_once(':').unwrap_or((spec, "50"));
+        let (Ok(multiple), Ok(depth)) = (multiple.parse::<Decimal>(), depth.parse::<usize>()) else {
+            return Err(format!("挂单墙参数格式错误: {}", spec).into());
+        };
+        manager.set_wall_detector(WallDetector::new(multiple, depth));
+    }
+
+    // 深度更新频率异常检测，以最近 300 个正常区间为基准
+    for spec in options.values("update-rate") {
+        let (interval, threshold) = spec.split_once(':').unwrap_or((spec, "4"));
+        let (Ok(interval), Ok(threshold)) = (interval.parse::<u64>(), threshold.parse::<f64>()) else {
+            return Err(format!("更新频率参数格式错误: {}", spec).into());
+        };
+        manager.set_update_rate_monitor(UpdateRateMonitor::new(interval, 300, threshold));
+    }
+
+    // VPIN 毒性监控，基于币安归集成交
+    for spec in options.values("vpin") {
+        let (bucket_volume, window) = spec.split_once(':').unwrap_or((spec, "50"));
+        let (Ok(bucket_volume), Ok(window)) = (bucket_volume.parse::<Decimal>(), window.parse::<usize>()) else {
+            return Err(format!("VPIN 参数格式错误: {}", spec).into());
+        };
+        manager.set_vpin_calculator(VpinCalculator::new(bucket_volume, window));
+    }
+
+    // Kyle's lambda 价格冲击估计，基于币安归集成交和中间价
+    for spec in options.values("kyle-lambda") {
+        let (interval, window) = spec.split_once(':').unwrap_or((spec, "300"));
+        let (Ok(interval), Ok(window)) = (interval.parse::<u64>(), window.parse::<usize>()) else {
+            return Err(format!("价格冲击估计参数格式错误: {}", spec).into());
+        };
+        manager.set_kyle_lambda_estimator(KyleLambdaEstimator::new(interval, window));
+    }
+
+    // 成交的有效价差、实现价差和价格改善
+    for spec in options.values("trade-spread") {
+        let (horizon, window) = spec.split_once(':').unwrap_or((spec, "1000"));
+        let (Ok(horizon), Ok(window)) = (horizon.parse::<u64>(), window.parse::<usize>()) else {
+            return Err(format!("成交价差参数格式错误: {}", spec).into());
+        };
+        manager.set_trade_spread_analyzer(TradeSpreadAnalyzer::new(horizon, window));
+    }
+
+    // 由中间价或成交生成 OHLCV bar
+    for list in options.values("bars") {
+        for spec in split_list(list) {
+            let parsed = spec.split_once(':')
+                .and_then(|(source, kind)| Some((BarSource::parse(source)?, BarKind::parse(kind)?)));
+            let Some((source, kind)) = parsed else {
+                return Err(format!("bar 参数格式错误: {}", spec).into());
+            };
+            manager.add_bar_builder(BarBuilder::new(kind, source)?);
+        }
+    }
+
+    // 币安内三角套利：基础/中间,中间/计价,基础/计价
+    let triangle_notional = options.value("triangle-notional")
+        .and_then(|notional| notional.parse::<Decimal>().ok())
+        .unwrap_or(Decimal::from(1000));
+    for list in options.values("triangle") {
+        let symbols: Vec<String> = split_list(list).iter().map(|symbol| symbol.to_uppercase()).collect();
+        let [cross, mid, base] = symbols.as_slice() else {
+            return Err(format!("三角套利需要三个交易对: {}", list).into());
+        };
+        let mut scanner = TriangularScanner::new(BINANCE_VENUE, cross, mid, base, triangle_notional);
+        scanner.set_fee_schedule(fees.clone());
+        for threshold in options.values("triangle-threshold").filter_map(|bps| bps.parse::<Decimal>().ok()) {
+            scanner.set_threshold_bps(threshold);
+        }
+        manager.add_triangular_scanner(scanner);
+    }
+    Ok(())
+}
+
+/// 解析假想挂单 `交易对:bid|ask:价格:数量`，订单薄建立后才能放置
+pub fn parse_resting_orders(options: &Options) -> Result<Vec<RestingOrder>, Box<dyn Error>> {
+    let mut orders = Vec::new();
+    for spec in options.values("queue") {
+        let parts: Vec<&str> = spec.split(':').collect();
+        let order = match parts.as_slice() {
+            [symbol, side, price, quantity] => {
+                let side = match *side {
+                    "bid" | "buy" => Some(Side::Bid),
+                    "ask" | "sell" => Some(Side::Ask),
+                    _ => None,
+                };
+                match (side, price.parse::<Decimal>(), quantity.parse::<Decimal>()) {
+                    (Some(side), Ok(price), Ok(quantity)) => Some(RestingOrder {
+                        venue: BINANCE_VENUE.to_string(),
+                        symbol: symbol.to_uppercase(),
+                        side,
+                        price,
+                        quantity,
+                    }),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        orders.push(order.ok_or_else(|| format!("假想挂单参数格式错误: {}", spec))?);
+    }
+    Ok(orders)
+}
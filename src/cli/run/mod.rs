@@ -0,0 +1,445 @@
+//! 实时行情和回放：连接交易所或读取记录，经同一主循环更新订单薄
+
+mod analytics;
+mod recorders;
+mod report;
+mod servers;
+mod venues;
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+use rust_decimal::Decimal;
+use tracing::{error, info, warn};
+
+use order_book::analytics::churn::{ChurnMonitor, DEFAULT_CHURN_INTERVAL_MS};
+use order_book::binance::{Market, SymbolConfig};
+use order_book::checkpoint::Checkpoint;
+use order_book::clock::{spawn_clock_sync, ClockSkew, DEFAULT_SYNC_INTERVAL};
+use order_book::discovery::{discover_symbols, SymbolFilter};
+use order_book::exchange::fix::{spawn_fix_feed, FIX_VENUE};
+use order_book::exchange::binance::BinanceStream;
+use order_book::exchange::{spawn_feed, FeedCommand, FeedEvent};
+use order_book::instrument::SymbolMap;
+use order_book::latency::now_millis;
+use order_book::manager::{BookManager, BINANCE_VENUE};
+use order_book::metrics;
+use order_book::metrics::statsd::{StatsdFormat, StatsdRecorder};
+use order_book::order_book::DepthDisplay;
+use order_book::profile::{self, DEFAULT_PROFILE_WINDOW};
+use order_book::replay::{ReplayCommand, ReplayEvent, ReplaySpeed, Replayer};
+use order_book::serve::health::{Readiness, DEFAULT_MAX_AGE};
+use order_book::serve::metrics::spawn_metrics_server;
+use order_book::synthetic::SyntheticPair;
+use order_book::ticker::TickerStream;
+use order_book::tui::spawn_tui;
+use order_book::ws_api::SnapshotSource;
+
+use crate::cli::{parse_fees, split_list, Options};
+use analytics::{configure, parse_resting_orders};
+use recorders::add_recorders;
+use report::{log_event, Reporter};
+use servers::start_servers;
+use venues::{parse_fix_feed, parse_venues, FixFeed, Venue};
+
+/// 币安单个连接允许订阅的最大流数量
+const MAX_STREAMS_PER_CONNECTION: usize = 1024;
+/// 按筛选条件发现的交易对默认的深度快照档位数，交易对较多时减少启动时的请求权重
+const DISCOVERED_SNAPSHOT_LIMIT: u32 = 100;
+/// 回放线程与主循环之间的队列长度
+const REPLAY_QUEUE_SIZE: usize = 1024;
+/// 默认的订单薄检查点保存间隔（秒）
+const DEFAULT_CHECKPOINT_SECS: u64 = 10;
+
+/// 订阅行情（或回放记录）并持续更新订单薄，直到行情结束或终端界面退出
+pub fn run(market: Market, specs: Vec<String>, replay: bool, options: &Options) -> Result<(), Box<dyn Error>> {
+    let mut symbols = parse_symbols(market, specs, options)?;
+    let venues = parse_venues(options);
+    let fix_feed = parse_fix_feed(options)?;
+
+    // 币安合成交易对：名称=腿1*腿2 或 名称=腿1/腿2
+    let synthetic_pairs = options.values("synthetic")
+        .map(|spec| SyntheticPair::parse(BINANCE_VENUE, spec))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // 三角套利和合成交易对的腿需要订阅深度
+    let mut leg_symbols: Vec<String> = options.values("triangle")
+        .flat_map(split_list)
+        .map(|symbol| symbol.to_uppercase())
+        .collect();
+    for pair in &synthetic_pairs {
+        leg_symbols.push(pair.first.symbol.clone());
+        leg_symbols.push(pair.second.symbol.clone());
+    }
+    for symbol in leg_symbols {
+        if !symbols.iter().any(|config| config.symbol == symbol) {
+            symbols.push(SymbolConfig::new(market, &symbol));
+        }
+    }
+
+    if symbols.is_empty() && venues.is_empty() && fix_feed.is_none() && !replay {
+        symbols.push(SymbolConfig::new(market, "BNBUSDT"));
+    }
+    for limit in options.values("snapshot-limit").filter_map(|limit| limit.parse::<u32>().ok()) {
+        for config in symbols.iter_mut() {
+            config.snapshot_limit = limit.min(market.max_depth_limit());
+        }
+    }
+
+    let mut manager = BookManager::new(market, &symbols);
+    configure_manager(&mut manager, market, options)?;
+    // 回放使用记录中的行情，不请求交易所
+    if !replay {
+        manager.load_funding_history(100);
+    }
+
+    // 手续费表：配置文件加上命令行覆盖的吃单手续费（基点）
+    let fees = parse_fees(options)?;
+    // 统一品种名称到各交易所原生交易对的映射
+    let symbol_map = SymbolMap::new();
+    configure(&mut manager, options, &fees, &symbol_map)?;
+    let pending_orders = parse_resting_orders(options)?;
+    if !pending_orders.is_empty() {
+        manager.enable_queue_estimator();
+    }
+    let names: Vec<String> = symbols.iter().map(|config| config.symbol.clone()).collect();
+    let mut reporter = Reporter::new(options, &symbol_map, &fees, synthetic_pairs, pending_orders, names.clone());
+
+    add_recorders(&mut manager, options)?;
+
+    // 按需转储：信号处理函数和 HTTP 服务只设置标志，由主循环写文件
+    let dump_dir = options.value("dump-dir");
+    let dump_requested = Arc::new(AtomicBool::new(false));
+    #[cfg(unix)]
+    if dump_dir.is_some() {
+        signal_hook::flag::register(signal_hook::consts::SIGUSR1, dump_requested.clone())
+            .map_err(|e| format!("注册 SIGUSR1 失败: {}", e))?;
+    }
+
+    // 对外服务共享的订单薄，由行情线程在每次更新后写入
+    let hub = start_servers(&mut manager, options, dump_dir.map(|_| dump_requested.clone()))?;
+
+    // 热路径各阶段计时，在所有行情线程启动之前开启
+    if let Some(window) = interval_option(options, "profile", DEFAULT_PROFILE_WINDOW)? {
+        profile::enable(window);
+    }
+
+    let readiness = start_metrics(&mut manager, options, (!replay).then_some((symbols.as_slice(), venues.as_slice(), &fix_feed)))?;
+
+    // 订单薄检查点：启动时载入，之后定期保存
+    let mut checkpoint = None;
+    if let Some(spec) = options.value("checkpoint") {
+        let (path, secs) = spec.split_once(':').unwrap_or((spec, ""));
+        let secs = match secs {
+            "" => DEFAULT_CHECKPOINT_SECS,
+            secs => secs.parse::<u64>().ok().filter(|secs| *secs > 0)
+                .ok_or_else(|| format!("检查点参数格式错误: {}", spec))?,
+        };
+        if Path::new(path).exists() {
+            let restored = Checkpoint::load(path)
+                .and_then(|saved| manager.restore_checkpoint(&saved).map(|restored| (restored, saved.saved_at)));
+            match restored {
+                Ok((restored, saved_at)) => info!(restored, age_ms = now_millis().saturating_sub(saved_at), "从检查点恢复订单薄"),
+                Err(e) => warn!(error = %e, "载入检查点失败，使用深度快照"),
+            }
+        }
+        checkpoint = Some((path.to_string(), Duration::from_secs(secs), Instant::now()));
+    }
+    // 从记录末尾恢复，与检查点使用相同的衔接检查
+    if let Some(path) = options.value("warm-start") {
+        let restored = Checkpoint::from_recording(path, market, &names)
+            .and_then(|saved| manager.restore_checkpoint(&saved).map(|restored| (restored, saved.saved_at)));
+        match restored {
+            Ok((restored, saved_at)) => info!(restored, age_ms = now_millis().saturating_sub(saved_at), "从记录恢复订单薄"),
+            Err(e) => warn!(error = %e, "从记录恢复失败，使用深度快照"),
+        }
+    }
+
+    // 终端界面显示共享订单薄，代替打印深度
+    let tui = match (options.flag("tui"), &hub, &readiness) {
+        (true, Some(hub), Some(readiness)) => {
+            manager.set_depth_display(DepthDisplay::None);
+            Some(spawn_tui(hub.clone(), readiness.clone()).map_err(|e| format!("启动终端界面失败: {}", e))?)
+        }
+        _ => None,
+    };
+
+    let mut feeds: HashMap<&'static str, Sender<FeedCommand>> = HashMap::new();
+    // 币安订单薄由管理器重新同步，不需要发送命令，但发送端要保留到主循环结束，否则行情线程退出
+    let mut binance_feeds: Vec<Sender<FeedCommand>> = Vec::new();
+    let events_rx = if replay {
+        // 回放模式不连接交易所，记录经同一主循环处理
+        start_replay(options).map_err(|e| format!("启动回放失败: {}", e))?
+    } else {
+        let (events_tx, events_rx) = mpsc::channel();
+        // 币安原始消息帧由管理器记录，不需要行情线程转发
+        for streams in binance_connections(&manager) {
+            let symbols = streams.iter().map(|(symbol, _)| symbol.clone()).collect();
+            binance_feeds.push(spawn_feed(Box::new(BinanceStream::new(market, streams)), symbols, events_tx.clone(), false));
+        }
+        let record_frames = options.value("record").is_some() || options.value("capture").is_some();
+        for (exchange, venue_symbols) in venues {
+            let name = exchange.name();
+            feeds.insert(name, spawn_feed(exchange, venue_symbols, events_tx.clone(), record_frames));
+        }
+        if let Some((config, fix_symbols)) = fix_feed {
+            feeds.insert(FIX_VENUE, spawn_fix_feed(config, fix_symbols, events_tx.clone()));
+        }
+        events_rx
+    };
+
+    // 有终端界面时定期检查用户是否已退出
+    let events = std::iter::from_fn(|| loop {
+        if tui.as_ref().is_some_and(|tui| tui.is_closed()) {
+            return None;
+        }
+        match events_rx.recv_timeout(Duration::from_millis(100)) {
+            Ok(event) => return Some(event),
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => return None,
+        }
+    });
+    for event in events {
+        match event {
+            FeedEvent::Binance(msg, span) => span.in_scope(|| manager.handle_message(&msg)),
+            FeedEvent::Depth { venue, message, span } => {
+                let _entered = span.enter();
+                let symbol = message.symbol.clone();
+                if let Err(e) = manager.handle_venue_depth(venue, message) {
+                    warn!(venue, symbol, error = %e, "应用深度消息失败，重新同步");
+                    if let Some(commands) = feeds.get(venue) {
+                        metrics::resync(venue, &symbol);
+                        let _ = commands.send(FeedCommand::Resync(symbol));
+                    }
+                }
+            }
+            FeedEvent::Frame { venue, frame, local_time } => manager.record_frame(venue, &frame, local_time),
+            FeedEvent::Disconnected { venue, symbols } => manager.feed_disconnected(venue, &symbols),
+            FeedEvent::Replay(ReplayEvent::Record(record)) => {
+                if let Err(e) = manager.replay_record(record) {
+                    warn!(error = %e, "回放记录失败");
+                }
+            }
+            FeedEvent::Replay(ReplayEvent::Reset) => manager.reset_books(),
+        }
+        reporter.report(&mut manager);
+        if let Some((path, interval, last_saved)) = checkpoint.as_mut() && last_saved.elapsed() >= *interval {
+            if let Err(e) = manager.checkpoint(now_millis()).save(path.as_str()) {
+                error!(error = %e, "保存检查点失败");
+            }
+            *last_saved = Instant::now();
+        }
+        if let Some(dir) = dump_dir && dump_requested.swap(false, Ordering::Relaxed) {
+            match manager.dump_books(Path::new(dir), now_millis()) {
+                Ok(paths) => info!(dir, books = paths.len(), "转储订单薄"),
+                Err(e) => error!(error = %e, "转储订单薄失败"),
+            }
+        }
+        for event in manager.poll_events() {
+            log_event(&manager, event);
+        }
+    }
+    // 先恢复终端，之后的输出才能留在屏幕上
+    drop(tui);
+    manager.flush_recorders();
+    for report in manager.strategy_reports() {
+        report.print();
+    }
+    if let Some((path, _, _)) = &checkpoint {
+        manager.checkpoint(now_millis()).save(path.as_str()).map_err(|e| format!("保存检查点失败: {}", e))?;
+    }
+    Ok(())
+}
+
+/// 命令行指定的币安交易对，加上按筛选条件发现的交易对
+fn parse_symbols(market: Market, specs: Vec<String>, options: &Options) -> Result<Vec<SymbolConfig>, Box<dyn Error>> {
+    let mut symbols = specs.iter()
+        .map(|spec| SymbolConfig::parse(market, spec))
+        .collect::<Result<Vec<_>, _>>()?;
+    for spec in options.values("discover") {
+        let discovered = SymbolFilter::parse(spec)
+            .and_then(|filter| discover_symbols(market, &filter))
+            .map_err(|e| format!("发现交易对失败: {}", e))?;
+        info!(symbols = discovered.len(), "发现交易对");
+        for symbol in discovered {
+            if !symbols.iter().any(|config| config.symbol == symbol) {
+                let mut config = SymbolConfig::new(market, &symbol);
+                config.snapshot_limit = DISCOVERED_SNAPSHOT_LIMIT;
+                symbols.push(config);
+            }
+        }
+    }
+    Ok(symbols)
+}
+
+/// 订单薄管理器的订阅、快照、显示和对时设置
+fn configure_manager(manager: &mut BookManager, market: Market, options: &Options) -> Result<(), Box<dyn Error>> {
+    for intervals in options.values("klines") {
+        manager.set_kline_intervals(&split_list(intervals));
+    }
+    for ticker_stream in options.values("ticker").filter_map(TickerStream::parse) {
+        manager.set_ticker_stream(ticker_stream);
+    }
+    for snapshot_source in options.values("snapshot").filter_map(|name| SnapshotSource::parse(market, name)) {
+        manager.set_snapshot_source(snapshot_source);
+    }
+    for depth_display in options.values("depth-display").filter_map(DepthDisplay::parse) {
+        manager.set_depth_display(depth_display);
+    }
+    for spec in options.values("volume-profile") {
+        let (bucket_size, session) = spec.split_once(':').unwrap_or((spec, "86400000"));
+        let (Ok(bucket_size), Ok(session)) = (bucket_size.parse::<Decimal>(), session.parse::<u64>()) else {
+            return Err(format!("成交量分布参数格式错误: {}", spec).into());
+        };
+        manager.set_volume_profile(bucket_size, session);
+    }
+    if let Some(interval) = interval_option(options, "clock-sync", DEFAULT_SYNC_INTERVAL)? {
+        let skew = Arc::new(ClockSkew::default());
+        spawn_clock_sync(market, interval, skew.clone());
+        manager.set_clock_skew(skew);
+    }
+    Ok(())
+}
+
+/// `--名称` 使用默认间隔，`--名称=秒数` 指定间隔，都没有时返回 None
+fn interval_option(options: &Options, name: &str, default: Duration) -> Result<Option<Duration>, Box<dyn Error>> {
+    if options.flag(name) {
+        return Ok(Some(default));
+    }
+    options.value(name)
+        .map(|seconds| match seconds.parse::<u64>() {
+            Ok(seconds) if seconds > 0 => Ok(Duration::from_secs(seconds)),
+            _ => Err(format!("--{} 间隔无效: {}", name, seconds).into()),
+        })
+        .transpose()
+}
+
+/// 就绪检查需要覆盖的实时行情：币安交易对、其他交易所和 FIX 行情
+type ExpectedBooks<'a> = (&'a [SymbolConfig], &'a [Venue], &'a Option<FixFeed>);
+
+/// 运行指标由 Prometheus 抓取或推送到 StatsD，可以同时使用；返回 /readyz 和终端界面使用的就绪检查
+fn start_metrics(manager: &mut BookManager, options: &Options, expected: Option<ExpectedBooks<'_>>) -> Result<Option<Arc<Readiness>>, Box<dyn Error>> {
+    let metrics_addr = options.value("metrics");
+    let statsd = match options.value("statsd") {
+        Some(addr) => {
+            let format = options.value("statsd-format").unwrap_or("dogstatsd");
+            let format = StatsdFormat::parse(format).ok_or_else(|| format!("未知的 StatsD 格式: {}", format))?;
+            let prefix = options.value("statsd-prefix").unwrap_or_default();
+            let recorder = StatsdRecorder::new(addr, format, prefix).map_err(|e| format!("创建 StatsD 输出失败: {}", e))?;
+            info!(addr, "发送指标到 StatsD");
+            Some(recorder)
+        }
+        None => None,
+    };
+    // 有指标输出时统计每个交易对的更新频率和档位变化
+    let churn = (metrics_addr.is_some() || statsd.is_some()).then(|| {
+        let interval = options.value("churn-interval")
+            .and_then(|interval| interval.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_CHURN_INTERVAL_MS);
+        let churn = Arc::new(ChurnMonitor::new(interval));
+        manager.set_churn_monitor(churn.clone());
+        churn
+    });
+    let prometheus = metrics::install(metrics_addr.is_some(), statsd).map_err(|e| format!("安装指标记录器失败: {}", e))?;
+    // 就绪检查覆盖命令行配置的所有订单薄
+    let readiness = (metrics_addr.is_some() || options.flag("tui")).then(|| {
+        let max_age = options.value("ready-max-age")
+            .and_then(|seconds| seconds.parse::<u64>().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_MAX_AGE);
+        let readiness = Arc::new(Readiness::new(max_age));
+        if let Some((symbols, venues, fix_feed)) = expected {
+            for config in symbols {
+                readiness.expect(BINANCE_VENUE, &config.symbol);
+            }
+            for (exchange, venue_symbols) in venues {
+                for symbol in venue_symbols {
+                    readiness.expect(exchange.name(), symbol);
+                }
+            }
+            if let Some((_, fix_symbols)) = fix_feed {
+                for symbol in fix_symbols {
+                    readiness.expect(FIX_VENUE, symbol);
+                }
+            }
+        }
+        manager.set_readiness(readiness.clone());
+        readiness
+    });
+    if let (Some(addr), Some(handle), Some(churn), Some(readiness)) = (metrics_addr, prometheus, churn, readiness.clone()) {
+        let addr = addr.parse::<SocketAddr>().map_err(|e| format!("--metrics 地址无效: {} ({})", addr, e))?;
+        spawn_metrics_server(addr, handle, manager.feed_latency(), churn, readiness)
+            .map_err(|e| format!("启动指标服务失败: {}", e))?;
+        info!(%addr, "指标服务监听");
+    }
+    Ok(readiness)
+}
+
+/// 交易对和需要订阅的流名称
+type SymbolStreams = (String, Vec<String>);
+
+/// 币安交易对按连接分组，每个连接最多订阅 [`MAX_STREAMS_PER_CONNECTION`] 个流，同一交易对的流放在同一连接中
+fn binance_connections(manager: &BookManager) -> Vec<Vec<SymbolStreams>> {
+    let mut connections: Vec<Vec<SymbolStreams>> = Vec::new();
+    for (symbol, params) in manager.subscribe_params() {
+        match connections.last_mut() {
+            Some(symbols) if symbols.iter().map(|(_, streams)| streams.len()).sum::<usize>() + params.len() <= MAX_STREAMS_PER_CONNECTION => {
+                symbols.push((symbol, params));
+            }
+            _ => connections.push(vec![(symbol, params)]),
+        }
+    }
+    if connections.len() > 1 {
+        info!(connections = connections.len(), limit = MAX_STREAMS_PER_CONNECTION, "订阅流数量超过单连接上限，分多个连接订阅");
+    }
+    connections
+}
+
+/// 打开 `--file` 指定的记录文件，在新线程中回放，并在另一线程中从标准输入读取控制命令
+fn start_replay(options: &Options) -> Result<Receiver<FeedEvent>, Box<dyn Error>> {
+    let path = options.require("file", "replay 需要用 --file=记录文件 指定回放文件")?;
+    let speed = match options.value("speed") {
+        Some(spec) => ReplaySpeed::parse(spec).ok_or_else(|| format!("无效的回放速度: {}，例如 1x、10x 或 max", spec))?,
+        None => ReplaySpeed::Multiple(1.0),
+    };
+    let from = options.parse::<u64>("from")?;
+    let mut replayer = Replayer::open(path, speed)?;
+    let (start, end) = replayer.time_range().ok_or("记录文件为空")?;
+    info!(records = replayer.len(), start, end, speed = ?speed, "载入回放记录");
+
+    let (commands_tx, commands_rx) = mpsc::channel();
+    thread::spawn(move || {
+        for line in std::io::stdin().lines() {
+            let Ok(line) = line else {
+                return;
+            };
+            match ReplayCommand::parse(&line) {
+                Some(command) => {
+                    if commands_tx.send(command).is_err() {
+                        return;
+                    }
+                }
+                None => warn!(line, "无效的回放命令"),
+            }
+        }
+    });
+
+    // 有界队列，暂停和跳转能及时生效
+    let (events_tx, events_rx) = mpsc::sync_channel(REPLAY_QUEUE_SIZE);
+    thread::spawn(move || {
+        let mut emit = |event| events_tx.send(FeedEvent::Replay(event)).is_ok();
+        if let Some(from) = from
+            && !replayer.seek(from, &mut emit)
+        {
+            return;
+        }
+        replayer.run(&commands_rx, emit);
+    });
+    Ok(events_rx)
+}
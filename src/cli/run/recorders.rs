@@ -0,0 +1,213 @@
+//! 记录行情到文件、数据库和消息队列
+
+use std::error::Error;
+use std::net::SocketAddr;
+use std::path::Path;
+use rust_decimal::Decimal;
+use tracing::info;
+
+use order_book::manager::BookManager;
+use order_book::proto::FeedFormat;
+use order_book::recorder::Recorder;
+use order_book::recorder::capture::{CaptureRecorder, DEFAULT_KEYFRAME_MS, DEFAULT_LEVEL};
+use order_book::recorder::clickhouse::{ClickHouseConfig, ClickHouseRecorder};
+use order_book::recorder::ilp::{IlpEndpoint, IlpRecorder};
+use order_book::recorder::ipc::IpcRecorder;
+use order_book::recorder::itch::{ItchRecorder, DEFAULT_DECIMALS};
+use order_book::recorder::kafka::{Delivery, KafkaConfig, KafkaRecorder};
+use order_book::recorder::lobster::{LobsterRecorder, DEFAULT_LEVELS, DEFAULT_PRICE_SCALE};
+use order_book::recorder::mqtt::{MqttRecorder, DEFAULT_MQTT_INTERVAL_MS, DEFAULT_MQTT_PREFIX};
+use order_book::recorder::msgpack::MsgpackRecorder;
+use order_book::recorder::nats::{NatsConfig, NatsRecorder, DEFAULT_STREAM};
+use order_book::recorder::ndjson::NdjsonRecorder;
+use order_book::recorder::parquet::ParquetRecorder;
+use order_book::recorder::postgres::{PostgresConfig, PostgresRecorder};
+use order_book::recorder::shm::{ShmFormat, ShmRecorder, DEFAULT_SLOTS};
+use order_book::recorder::sqlite::SqliteRecorder;
+
+use crate::cli::Options;
+
+/// 按命令行参数创建记录器并加到订单薄管理器
+pub fn add_recorders(manager: &mut BookManager, options: &Options) -> Result<(), Box<dyn Error>> {
+    // 记录原始消息和统一格式的深度更新，供回放和排查问题
+    if let Some(dir) = options.value("record") {
+        let recorder = match options.value("record-format").unwrap_or("ndjson") {
+            "ndjson" => NdjsonRecorder::new(dir).map(|recorder| Box::new(recorder) as Box<dyn Recorder>),
+            "msgpack" => MsgpackRecorder::new(dir).map(|recorder| Box::new(recorder) as Box<dyn Recorder>),
+            format => return Err(format!("未知的记录格式: {}", format).into()),
+        };
+        manager.add_recorder(recorder.map_err(|e| format!("创建记录目录失败: {}", e))?);
+        info!(dir, "记录行情到目录");
+    }
+    if let Some(spec) = options.value("capture") {
+        let mut parts = spec.split(':');
+        let dir = parts.next().unwrap_or(spec);
+        let level = parts.next().map_or(Some(DEFAULT_LEVEL), |level| level.parse::<i32>().ok());
+        let keyframe = parts.next().map_or(Some(DEFAULT_KEYFRAME_MS), |secs| secs.parse::<u64>().ok().map(|secs| secs * 1000));
+        let (Some(level), Some(keyframe)) = (level, keyframe) else {
+            return Err(format!("压缩记录参数格式错误: {}", spec).into());
+        };
+        let recorder = CaptureRecorder::new(dir, level, keyframe).map_err(|e| format!("创建记录目录失败: {}", e))?;
+        info!(dir, "压缩记录行情到目录");
+        manager.add_recorder(Box::new(recorder));
+    }
+
+    if let Some(spec) = options.value("parquet") {
+        let (dir, interval, depth) = parse_snapshot_spec(spec).ok_or_else(|| format!("Parquet 参数格式错误: {}", spec))?;
+        let recorder = ParquetRecorder::new(dir, interval, depth).map_err(|e| format!("创建记录目录失败: {}", e))?;
+        info!(dir, "写入 Parquet 到目录");
+        manager.add_recorder(Box::new(recorder));
+    }
+    if let Some(spec) = options.value("sqlite") {
+        let (path, interval, depth) = parse_snapshot_spec(spec).ok_or_else(|| format!("SQLite 参数格式错误: {}", spec))?;
+        let recorder = SqliteRecorder::open(path, interval, depth).map_err(|e| format!("打开 SQLite 数据库失败: {}", e))?;
+        info!(path, "写入 SQLite 数据库");
+        manager.add_recorder(Box::new(recorder));
+    }
+    if let Some(url) = options.value("postgres") {
+        let mut config = PostgresConfig::new(url);
+        for batch_size in options.values("postgres-batch").filter_map(|size| size.parse::<usize>().ok()) {
+            config.batch_size = batch_size.max(1);
+        }
+        for retries in options.values("postgres-retries").filter_map(|retries| retries.parse::<u32>().ok()) {
+            config.max_retries = retries;
+        }
+        let recorder = PostgresRecorder::connect(config).map_err(|e| format!("连接 PostgreSQL 失败: {}", e))?;
+        manager.add_recorder(Box::new(recorder));
+    }
+    if let Some(url) = options.value("clickhouse") {
+        let mut config = ClickHouseConfig::new(url);
+        for database in options.values("clickhouse-db") {
+            config.database = database.to_string();
+        }
+        for (user, password) in options.values("clickhouse-user").filter_map(|user| user.split_once(':')) {
+            config.user = Some((user.to_string(), password.to_string()));
+        }
+        for batch_size in options.values("clickhouse-batch").filter_map(|size| size.parse::<usize>().ok()) {
+            config.batch_size = batch_size.max(1);
+        }
+        let recorder = ClickHouseRecorder::connect(config).map_err(|e| format!("连接 ClickHouse 失败: {}", e))?;
+        manager.add_recorder(Box::new(recorder));
+    }
+    if let Some(spec) = options.value("ilp") {
+        let mut endpoint = IlpEndpoint::parse(spec)
+            .ok_or_else(|| format!("行协议地址格式错误，应为 tcp://地址 或 http(s)://写入地址: {}", spec))?;
+        if let IlpEndpoint::Http { token, .. } = &mut endpoint {
+            *token = options.value("ilp-token").map(|token| token.to_string());
+        }
+        let interval = options.value("ilp-interval")
+            .and_then(|interval| interval.parse::<u64>().ok())
+            .unwrap_or(1000);
+        let bps = options.value("ilp-bps")
+            .and_then(|bps| bps.parse::<Decimal>().ok())
+            .unwrap_or(Decimal::from(10));
+        let recorder = IlpRecorder::new(endpoint, interval, bps).map_err(|e| format!("创建行协议输出失败: {}", e))?;
+        manager.add_recorder(Box::new(recorder));
+    }
+    if let Some(url) = options.value("nats") {
+        let mut config = NatsConfig::new(url);
+        for prefix in options.values("nats-prefix") {
+            config.prefix = prefix.to_string();
+        }
+        if options.flag("nats-jetstream") {
+            config.stream = Some(DEFAULT_STREAM.to_string());
+        }
+        for stream in options.values("nats-jetstream") {
+            config.stream = Some(stream.to_string());
+        }
+        for format in options.values("nats-format") {
+            config.format = FeedFormat::parse(format).ok_or_else(|| format!("未知的消息格式: {}", format))?;
+        }
+        let recorder = NatsRecorder::new(config).map_err(|e| format!("连接 NATS 失败: {}", e))?;
+        info!(url, "发布到 NATS");
+        manager.add_recorder(Box::new(recorder));
+    }
+    if let Some(brokers) = options.value("kafka") {
+        let mut config = KafkaConfig::new(brokers);
+        for (deltas, snapshots) in options.values("kafka-topics").filter_map(|topics| topics.split_once(',')) {
+            config.delta_topic = deltas.to_string();
+            config.snapshot_topic = snapshots.to_string();
+        }
+        for compression in options.values("kafka-compression") {
+            config.compression = compression.to_string();
+        }
+        for delivery in options.values("kafka-delivery") {
+            config.delivery = Delivery::parse(delivery).ok_or_else(|| format!("未知的投递保证: {}", delivery))?;
+        }
+        for secs in options.values("kafka-keyframe").filter_map(|secs| secs.parse::<u64>().ok()) {
+            config.keyframe_interval_ms = secs * 1000;
+        }
+        for format in options.values("kafka-format") {
+            config.format = FeedFormat::parse(format).ok_or_else(|| format!("未知的消息格式: {}", format))?;
+        }
+        let recorder = KafkaRecorder::new(config).map_err(|e| format!("创建 Kafka 生产者失败: {}", e))?;
+        info!(brokers, "写入 Kafka");
+        manager.add_recorder(Box::new(recorder));
+    }
+    if let Some(address) = options.value("mqtt") {
+        let prefix = options.value("mqtt-prefix").unwrap_or(DEFAULT_MQTT_PREFIX);
+        let interval_ms = options.value("mqtt-interval")
+            .and_then(|interval| interval.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_MQTT_INTERVAL_MS);
+        let recorder = MqttRecorder::new(address, prefix, interval_ms).map_err(|e| format!("创建 MQTT 客户端失败: {}", e))?;
+        info!(address, "发布到 MQTT");
+        manager.add_recorder(Box::new(recorder));
+    }
+    if let Some(spec) = options.value("shm") {
+        let (path, slots) = match spec.split_once(':') {
+            Some((path, slots)) => (path, slots.parse::<usize>().ok()),
+            None => (spec, Some(DEFAULT_SLOTS)),
+        };
+        let slots = slots.ok_or_else(|| format!("共享内存参数格式错误: {}", spec))?;
+        let format = options.value("shm-format").unwrap_or("raw");
+        let format = ShmFormat::parse(format).ok_or_else(|| format!("未知的共享内存格式: {}", format))?;
+        let recorder = ShmRecorder::new(path, slots, format).map_err(|e| format!("创建共享内存失败: {}", e))?;
+        info!(path, "写入共享内存");
+        manager.add_recorder(Box::new(recorder));
+    }
+    if let Some(spec) = options.value("lobster") {
+        let mut parts = spec.split(':');
+        let dir = parts.next().unwrap_or(spec);
+        let levels = parts.next().map_or(Some(DEFAULT_LEVELS), |levels| levels.parse::<usize>().ok());
+        let price_scale = parts.next().map_or(Some(DEFAULT_PRICE_SCALE), |scale| scale.parse::<u32>().ok());
+        let (Some(levels), Some(price_scale)) = (levels, price_scale) else {
+            return Err(format!("LOBSTER 参数格式错误: {}", spec).into());
+        };
+        let recorder = LobsterRecorder::new(dir, levels, price_scale).map_err(|e| format!("创建记录目录失败: {}", e))?;
+        info!(dir, "写入 LOBSTER 文件到目录");
+        manager.add_recorder(Box::new(recorder));
+    }
+    let itch_dir = options.value("itch");
+    let itch_multicast = options.value("itch-multicast");
+    if itch_dir.is_some() || itch_multicast.is_some() {
+        let mut parts = itch_dir.unwrap_or_default().split(':');
+        let dir = parts.next().filter(|dir| !dir.is_empty());
+        let price_decimals = parts.next().map_or(Some(DEFAULT_DECIMALS), |decimals| decimals.parse::<u32>().ok());
+        let quantity_decimals = parts.next().map_or(Some(DEFAULT_DECIMALS), |decimals| decimals.parse::<u32>().ok());
+        let multicast = itch_multicast.map(|addr| addr.parse::<SocketAddr>()).transpose();
+        let (Some(price_decimals), Some(quantity_decimals), Ok(multicast)) = (price_decimals, quantity_decimals, multicast) else {
+            return Err(format!("ITCH 参数格式错误: {:?} {:?}", itch_dir, itch_multicast).into());
+        };
+        let recorder = ItchRecorder::new(dir.map(Path::new), multicast, price_decimals, quantity_decimals)
+            .map_err(|e| format!("创建 ITCH 输出失败: {}", e))?;
+        info!(dir, multicast = ?multicast, "输出 ITCH 消息");
+        manager.add_recorder(Box::new(recorder));
+    }
+    if let Some(path) = options.value("arrow-ipc") {
+        let recorder = match path {
+            "-" => IpcRecorder::to_stdout(),
+            path => IpcRecorder::to_file(path),
+        };
+        manager.add_recorder(Box::new(recorder.map_err(|e| format!("创建 Arrow IPC 输出失败: {}", e))?));
+    }
+    Ok(())
+}
+
+/// 解析 `路径[:快照间隔毫秒:快照档位]`，默认每秒记录前20档
+fn parse_snapshot_spec(spec: &str) -> Option<(&str, u64, usize)> {
+    let mut parts = spec.split(':');
+    let path = parts.next().filter(|path| !path.is_empty())?;
+    let interval = parts.next().map_or(Some(1000), |interval| interval.parse::<u64>().ok())?;
+    let depth = parts.next().map_or(Some(20), |depth| depth.parse::<usize>().ok())?;
+    Some((path, interval, depth))
+}
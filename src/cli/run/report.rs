@@ -0,0 +1,298 @@
+//! 主循环中定期输出的统计和市场事件日志
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use rust_decimal::Decimal;
+use tracing::{info, warn};
+
+use order_book::analytics::queue::RestingOrder;
+use order_book::analytics::volume_profile::DEFAULT_VALUE_AREA;
+use order_book::analytics::wall::WallChange;
+use order_book::consolidated::ConsolidatedLevel;
+use order_book::events::MarketEvent;
+use order_book::fees::FeeSchedule;
+use order_book::instrument::SymbolMap;
+use order_book::latency::now_millis;
+use order_book::manager::BookManager;
+use order_book::order_book::Side;
+use order_book::router::OrderRouter;
+use order_book::synthetic::SyntheticPair;
+
+use crate::cli::{resolve_legs, Options};
+
+/// 拆单模拟的输出间隔
+const ROUTE_INTERVAL: Duration = Duration::from_secs(5);
+/// 延迟统计的输出间隔
+const LATENCY_INTERVAL: Duration = Duration::from_secs(10);
+/// 波动率的输出间隔
+const VOLATILITY_INTERVAL: Duration = Duration::from_secs(10);
+/// 价差统计的输出间隔
+const SPREAD_STATS_INTERVAL: Duration = Duration::from_secs(10);
+/// 基于成交的分析指标（VPIN、价格冲击、成交价差、排队估计）的输出间隔
+const TRADE_ANALYTICS_INTERVAL: Duration = Duration::from_secs(10);
+/// 中间价附近深度的输出间隔
+const LIQUIDITY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 合并订单薄的最优买卖价
+type ConsolidatedBbo = (Option<ConsolidatedLevel>, Option<ConsolidatedLevel>);
+/// 合成订单薄的最优买卖价和数量
+type SyntheticBbo = (Option<(Decimal, Decimal)>, Option<(Decimal, Decimal)>);
+
+/// 每处理一条行情后检查是否到了输出间隔，并记住上次输出的最优价，变化时才打印
+pub struct Reporter {
+    /// 合并订单薄的组成：交易所:交易对
+    consolidate: Vec<(String, String)>,
+    consolidated_bbo: Option<ConsolidatedBbo>,
+    synthetic_pairs: Vec<SyntheticPair>,
+    synthetic_bbo: HashMap<String, SyntheticBbo>,
+    /// 拆单模拟：定期按合并订单薄计算买入和卖出目标数量的方案
+    route_legs: Vec<(String, String)>,
+    route_quantity: Decimal,
+    router: OrderRouter,
+    print_latency: bool,
+    /// 尚未放置的假想挂单，订单薄建立后放置
+    pending_orders: Vec<RestingOrder>,
+    /// 币安交易对中间价附近的深度
+    liquidity_bps: Option<Decimal>,
+    symbols: Vec<String>,
+    last_route: Instant,
+    last_latency: Instant,
+    last_volatility: Instant,
+    last_spread_stats: Instant,
+    last_lambda: Instant,
+    last_trade_spread: Instant,
+    last_queue: Instant,
+    last_liquidity: Instant,
+}
+
+impl Reporter {
+    pub fn new(options: &Options, symbol_map: &SymbolMap, fees: &FeeSchedule, synthetic_pairs: Vec<SyntheticPair>,
+               pending_orders: Vec<RestingOrder>, symbols: Vec<String>) -> Self {
+        let consolidate = options.values("consolidate")
+            .flat_map(|list| resolve_legs(list, symbol_map))
+            .collect();
+        let route_legs = options.values("route")
+            .flat_map(|list| resolve_legs(list, symbol_map))
+            .collect();
+        let route_quantity = options.value("route-qty")
+            .and_then(|quantity| quantity.parse::<Decimal>().ok())
+            .unwrap_or(Decimal::ONE);
+        let mut router = OrderRouter::new();
+        router.set_fee_schedule(fees.clone());
+        let liquidity_bps = options.value("liquidity-bps").and_then(|bps| bps.parse::<Decimal>().ok());
+        let now = Instant::now();
+        Self {
+            consolidate,
+            consolidated_bbo: None,
+            synthetic_pairs,
+            synthetic_bbo: HashMap::new(),
+            route_legs,
+            route_quantity,
+            router,
+            print_latency: options.flag("latency"),
+            pending_orders,
+            liquidity_bps,
+            symbols,
+            last_route: now,
+            last_latency: now,
+            last_volatility: now,
+            last_spread_stats: now,
+            last_lambda: now,
+            last_trade_spread: now,
+            last_queue: now,
+            last_liquidity: now,
+        }
+    }
+
+    /// 输出到期的统计，放置可以放置的假想挂单
+    pub fn report(&mut self, manager: &mut BookManager) {
+        if !self.consolidate.is_empty() {
+            let book = manager.consolidated_book(&self.consolidate, 1);
+            let bbo = (book.best_bid(), book.best_ask());
+            if self.consolidated_bbo.as_ref() != Some(&bbo) {
+                if let (Some(bid), Some(ask)) = &bbo {
+                    info!("合并最优价 买: {} {:?} / 卖: {} {:?}, 价差: {}",
+                             bid.price, bid.venues.iter().map(|v| &v.venue).collect::<Vec<_>>(),
+                             ask.price, ask.venues.iter().map(|v| &v.venue).collect::<Vec<_>>(),
+                             ask.price - bid.price);
+                }
+                self.consolidated_bbo = Some(bbo);
+            }
+        }
+        if !self.route_legs.is_empty() && self.last_route.elapsed() >= ROUTE_INTERVAL {
+            let book = manager.consolidated_book(&self.route_legs, usize::MAX);
+            for side in [Side::Ask, Side::Bid] {
+                let plan = self.router.route(&book, side, self.route_quantity);
+                let direction = if side == Side::Ask { "买入" } else { "卖出" };
+                if let Some(effective_price) = plan.effective_price() {
+                    info!("拆单{} {} 均价: {}, 含手续费: {}, 未成交: {}, 分配: {:?}",
+                             direction, self.route_quantity, plan.average_price().unwrap_or_default().round_dp(8),
+                             effective_price.round_dp(8), plan.unfilled(), plan.venue_quantities());
+                }
+            }
+            self.last_route = Instant::now();
+        }
+        if (self.print_latency || !manager.lead_lag_trackers().is_empty()) && self.last_latency.elapsed() >= LATENCY_INTERVAL {
+            let latency = manager.latency();
+            if self.print_latency {
+                for venue in latency.venues() {
+                    info!(venue, "行情延迟 p50: {:?} ms, p99: {:?} ms",
+                          latency.venue_percentile(&venue, 50.0), latency.venue_percentile(&venue, 99.0));
+                }
+            }
+            for tracker in manager.lead_lag_trackers() {
+                if let Some((venue, share)) = tracker.leader() {
+                    info!(venue, "价格发现领先 ({:.1}%), 领先次数: {:?}", share * 100.0, tracker.lead_counts());
+                }
+            }
+            self.last_latency = Instant::now();
+        }
+        if let Some(tracker) = manager.volatility() && self.last_volatility.elapsed() >= VOLATILITY_INTERVAL {
+            for (venue, symbol) in tracker.symbols() {
+                if let (Some(realized), Some(annualized)) = (tracker.realized_volatility(&venue, &symbol), tracker.annualized_volatility(&venue, &symbol)) {
+                    info!(venue, symbol, "已实现波动率: {:.6}, 年化: {:.2}%, 收益率个数: {}",
+                          realized, annualized * 100.0, tracker.returns(&venue, &symbol).len());
+                }
+            }
+            self.last_volatility = Instant::now();
+        }
+        if let Some(tracker) = manager.spread_stats() && self.last_spread_stats.elapsed() >= SPREAD_STATS_INTERVAL {
+            let now = now_millis();
+            for (venue, symbol) in tracker.symbols() {
+                if let Some(summary) = tracker.summary(&venue, &symbol, now) {
+                    info!(venue, symbol, "价差 当前: {}, 时间加权: {}, 最小: {}, 最大: {}, p50: {}, p90: {}, p99: {}",
+                          summary.current, summary.twap.round_dp(8), summary.min, summary.max,
+                             summary.p50, summary.p90, summary.p99);
+                }
+            }
+            self.last_spread_stats = Instant::now();
+        }
+        if let Some(estimator) = manager.kyle_lambda() && self.last_lambda.elapsed() >= TRADE_ANALYTICS_INTERVAL {
+            for (venue, symbol) in estimator.symbols() {
+                if let Some(lambda) = estimator.lambda(&venue, &symbol) {
+                    info!(venue, symbol, "Kyle's lambda: {:.8}, 样本: {}", lambda, estimator.sample_count(&venue, &symbol));
+                }
+            }
+            self.last_lambda = Instant::now();
+        }
+        if let Some(analyzer) = manager.trade_spread() && self.last_trade_spread.elapsed() >= TRADE_ANALYTICS_INTERVAL {
+            for (venue, symbol) in analyzer.symbols() {
+                if let Some(stats) = analyzer.stats(&venue, &symbol) {
+                    info!(venue, symbol, "成交价差 {} 笔 有效: {} bps, 实现: {:?} bps, 冲击: {:?} bps, 价格改善占比: {}%, 平均改善: {} bps",
+                          stats.trades, stats.effective_bps.round_dp(2),
+                             stats.realized_bps.map(|bps| bps.round_dp(2)), stats.price_impact_bps.map(|bps| bps.round_dp(2)),
+                             (stats.improved_share * Decimal::ONE_HUNDRED).round_dp(1), stats.improvement_bps.round_dp(2));
+                }
+            }
+            self.last_trade_spread = Instant::now();
+        }
+        self.pending_orders.retain(|order| {
+            let placed = manager.place_resting_order(order.clone());
+            if let Some(id) = placed {
+                info!(symbol = order.symbol, "放置假想挂单 #{} {:?} 价格: {}, 数量: {}", id, order.side, order.price, order.quantity);
+            }
+            placed.is_none()
+        });
+        if let Some(queue) = manager.queue_estimator() && self.last_queue.elapsed() >= TRADE_ANALYTICS_INTERVAL {
+            for (id, order) in queue.orders() {
+                if let Some(estimate) = queue.estimate(id) {
+                    info!(symbol = order.symbol, "假想挂单 #{} {:?} {} 前方: {}, 已成交: {}/{}, 消耗速度: {}/s, 预计成交: {:?} ms",
+                          id, order.side, order.price, estimate.queue_ahead.round_dp(8), estimate.filled.round_dp(8),
+                             order.quantity, estimate.depletion_rate.round_dp(8), estimate.expected_fill_ms);
+                }
+            }
+            self.last_queue = Instant::now();
+        }
+        if let Some(bps) = self.liquidity_bps && self.last_liquidity.elapsed() >= LIQUIDITY_INTERVAL {
+            for symbol in &self.symbols {
+                if let Some(depth) = manager.book(symbol).and_then(|book| book.depth_within_bps(bps)) {
+                    info!(symbol, "中间价 ±{} bps 深度 买: {} ({}), 卖: {} ({})",
+                          bps, depth.bid_quantity, depth.bid_notional.round_dp(2),
+                             depth.ask_quantity, depth.ask_notional.round_dp(2));
+                }
+            }
+            self.last_liquidity = Instant::now();
+        }
+        for pair in &self.synthetic_pairs {
+            let Some(book) = manager.synthetic_book(pair, 20) else {
+                continue;
+            };
+            let bbo = (book.best_bid(), book.best_ask());
+            if self.synthetic_bbo.get(&pair.name) != Some(&bbo) {
+                if let (Some((bid_price, bid_quantity)), Some((ask_price, ask_quantity))) = bbo {
+                    info!(symbol = pair.name, "合成 买: {} ({}) / 卖: {} ({})",
+                          bid_price.round_dp(8), bid_quantity.round_dp(8), ask_price.round_dp(8), ask_quantity.round_dp(8));
+                }
+                self.synthetic_bbo.insert(pair.name.clone(), bbo);
+            }
+        }
+    }
+}
+
+/// 打印订单薄管理器产生的市场事件
+pub fn log_event(manager: &BookManager, event: MarketEvent) {
+    match event {
+        MarketEvent::Liquidation(liquidation) => {
+            info!(symbol = liquidation.symbol, "强平 {:?} 价格: {}, 数量: {}, 距中间价: {:?} bps, 吃掉 {} 档 / {}",
+                  liquidation.side, liquidation.avg_price, liquidation.quantity,
+                     liquidation.distance_bps.map(|d| d.round_dp(2)),
+                     liquidation.consumed_levels, liquidation.consumed_quantity);
+        }
+        MarketEvent::CandleClosed { symbol, interval, candle } => {
+            info!(symbol, interval, "K线收盘 开: {}, 高: {}, 低: {}, 收: {}, 量: {}",
+                  candle.open, candle.high, candle.low, candle.close, candle.volume);
+        }
+        MarketEvent::Arbitrage(opportunity) => {
+            info!("套利机会 {} {} 买入 @ {} -> {} {} 卖出 @ {}, 数量: {}, 预期利润: {} ({} bps)",
+                     opportunity.buy_venue, opportunity.buy_symbol, opportunity.buy_avg_price.round_dp(8),
+                     opportunity.sell_venue, opportunity.sell_symbol, opportunity.sell_avg_price.round_dp(8),
+                     opportunity.quantity, opportunity.expected_profit.round_dp(8), opportunity.profit_bps.round_dp(2));
+        }
+        MarketEvent::ImbalanceCrossed(cross) => {
+            info!(venue = cross.venue, symbol = cross.symbol, "不平衡度 {:?} 穿越 {}: {}",
+                  cross.direction, cross.threshold, cross.imbalance.round_dp(4));
+        }
+        MarketEvent::OrderFlowImbalance(interval) => {
+            let rolling = manager.ofi().and_then(|ofi| ofi.rolling(&interval.venue, &interval.symbol));
+            info!(venue = interval.venue, symbol = interval.symbol, "订单流不平衡 区间: {}, 更新 {} 次, 滚动窗口: {:?}",
+                  interval.ofi, interval.updates, rolling);
+        }
+        MarketEvent::VolumeProfileClosed { symbol, profile } => {
+            if let (Some((poc, volume)), Some((low, high))) = (profile.poc(), profile.value_area(DEFAULT_VALUE_AREA)) {
+                info!(symbol, "成交量分布会话结束 POC: {} ({}), 价值区域: {} - {}, 总成交量: {}",
+                      poc, volume, low, high, profile.total_volume());
+            }
+        }
+        MarketEvent::Vpin { venue, symbol, point } => {
+            info!(venue, symbol, time = point.time, "成交量桶完成 VPIN: {}", point.vpin.round_dp(4));
+        }
+        MarketEvent::Wall(wall) => {
+            let change = match wall.change {
+                WallChange::Appeared => "出现".to_string(),
+                WallChange::Moved { from } => format!("从 {} 移动", from),
+                WallChange::Removed => "消失".to_string(),
+            };
+            info!(venue = wall.venue, symbol = wall.symbol, "{:?} 挂单墙{} 价格: {}, 数量: {}, 距中间价: {:?} bps",
+                  wall.side, change, wall.price, wall.quantity,
+                     wall.distance_bps.map(|d| d.round_dp(2)));
+        }
+        MarketEvent::UpdateRateAnomaly(anomaly) => {
+            warn!(venue = anomaly.venue, symbol = anomaly.symbol, "更新频率异常 消息: {}, 变化档位: {}, 偏离: {:.1} 倍标准差",
+                  anomaly.messages, anomaly.level_changes, anomaly.max_z_score);
+        }
+        MarketEvent::BarClosed { venue, symbol, kind, source, bar } => {
+            info!(venue, symbol, "{:?} {:?} bar 收盘 开: {}, 高: {}, 低: {}, 收: {}, 量: {}, 笔数: {}",
+                  source, kind, bar.open, bar.high, bar.low, bar.close, bar.volume, bar.trades);
+        }
+        MarketEvent::TriangularArbitrage(opportunity) => {
+            info!(venue = opportunity.venue, "三角套利 {:?} {} 投入: {}, 收回: {}, 利润: {} bps",
+                  opportunity.direction, opportunity.path.join(" -> "),
+                     opportunity.start_notional, opportunity.end_notional.round_dp(8), opportunity.profit_bps.round_dp(2));
+        }
+        MarketEvent::StrategyFill { strategy, fill } => {
+            info!(strategy, venue = fill.venue, symbol = fill.symbol, "策略成交 {:?} 数量: {}, 均价: {}, 手续费: {}, 滑点: {:?} bps",
+                  fill.side, fill.quantity, fill.average_price.round_dp(8),
+                     fill.fee.round_dp(8), fill.slippage_bps.map(|bps| bps.round_dp(2)));
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! 对外提供订单薄的服务：gRPC、WebSocket、HTTP、ZeroMQ、Redis、FIX 和 Unix 套接字
+
+use std::error::Error;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+use tracing::info;
+
+use order_book::manager::BookManager;
+use order_book::serve::dashboard::spawn_dashboard_server;
+use order_book::serve::fix::spawn_fix_server;
+use order_book::serve::grpc::spawn_grpc_server;
+use order_book::serve::redis::{spawn_redis_mirror, DEFAULT_PREFIX, DEFAULT_REDIS_DEPTH};
+use order_book::serve::rest::spawn_rest_server;
+#[cfg(unix)]
+use order_book::serve::uds::spawn_uds_server;
+use order_book::serve::websocket::spawn_websocket_server;
+use order_book::serve::zmq::{spawn_zmq_publisher, DEFAULT_SNAPSHOT_SECS};
+use order_book::serve::{BookHub, HubRecorder, DEFAULT_DEPTH};
+
+use crate::cli::Options;
+
+/// 使用共享订单薄的参数，终端界面也从共享订单薄读取
+const HUB_OPTIONS: [&str; 8] = ["grpc", "ws-server", "rest", "zmq", "redis", "uds", "fix-server", "tui"];
+
+/// 启动命令行指定的服务，返回行情线程写入的共享订单薄；没有服务使用时返回 None
+pub fn start_servers(manager: &mut BookManager, options: &Options, dump_requested: Option<Arc<AtomicBool>>) -> Result<Option<Arc<BookHub>>, Box<dyn Error>> {
+    let hub = HUB_OPTIONS.iter().any(|name| options.has(name)).then(|| {
+        let serve_depth = options.value("serve-depth")
+            .and_then(|depth| depth.parse::<usize>().ok())
+            .unwrap_or(DEFAULT_DEPTH);
+        let hub = BookHub::new(serve_depth);
+        manager.add_recorder(Box::new(HubRecorder::new(hub.clone())));
+        hub
+    });
+    if let Some(addr) = options.value("dashboard") {
+        // 网页在浏览器中连接 --ws-server 接收订单薄
+        let ws_port = options.value("ws-server")
+            .and_then(|addr| addr.parse::<SocketAddr>().ok())
+            .map(|addr| addr.port())
+            .ok_or("网页看板需要同时使用 --ws-server")?;
+        spawn_dashboard_server(parse_addr("dashboard", addr)?, ws_port).map_err(|e| format!("启动网页看板失败: {}", e))?;
+        info!(%addr, "网页看板监听");
+    }
+    let Some(hub) = hub else {
+        return Ok(None);
+    };
+    if let Some(addr) = options.value("grpc") {
+        spawn_grpc_server(parse_addr("grpc", addr)?, hub.clone()).map_err(|e| format!("启动 gRPC 服务失败: {}", e))?;
+        info!(%addr, "gRPC 服务监听");
+    }
+    if let Some(addr) = options.value("ws-server") {
+        spawn_websocket_server(parse_addr("ws-server", addr)?, hub.clone()).map_err(|e| format!("启动 WebSocket 服务失败: {}", e))?;
+        info!(%addr, "WebSocket 服务监听");
+    }
+    if let Some(addr) = options.value("rest") {
+        spawn_rest_server(parse_addr("rest", addr)?, hub.clone(), dump_requested).map_err(|e| format!("启动 HTTP 服务失败: {}", e))?;
+        info!(%addr, "HTTP 服务监听");
+    }
+    if let Some(endpoint) = options.value("zmq") {
+        let secs = options.value("zmq-snapshot")
+            .and_then(|secs| secs.parse::<u64>().ok())
+            .filter(|secs| *secs > 0)
+            .unwrap_or(DEFAULT_SNAPSHOT_SECS);
+        spawn_zmq_publisher(endpoint, hub.clone(), Duration::from_secs(secs)).map_err(|e| format!("启动 ZeroMQ 发布失败: {}", e))?;
+        info!(endpoint, "ZeroMQ 发布");
+    }
+    if let Some(url) = options.value("redis") {
+        let prefix = options.value("redis-prefix").unwrap_or(DEFAULT_PREFIX);
+        let depth = options.value("redis-depth")
+            .and_then(|depth| depth.parse::<usize>().ok())
+            .unwrap_or(DEFAULT_REDIS_DEPTH);
+        spawn_redis_mirror(url, prefix, depth, hub.clone()).map_err(|e| format!("连接 Redis 失败: {}", e))?;
+        info!(url, "写入 Redis");
+    }
+    if let Some(addr) = options.value("fix-server") {
+        let sender = options.value("fix-sender").unwrap_or("ORDERBOOK");
+        spawn_fix_server(parse_addr("fix-server", addr)?, hub.clone(), sender).map_err(|e| format!("启动 FIX 服务失败: {}", e))?;
+        info!(%addr, "FIX 服务监听");
+    }
+    #[cfg(unix)]
+    if let Some(path) = options.value("uds") {
+        spawn_uds_server(path, hub.clone()).map_err(|e| format!("启动 Unix 套接字服务失败: {}", e))?;
+        info!(path, "Unix 套接字服务");
+    }
+    Ok(Some(hub))
+}
+
+/// 解析服务的监听地址
+fn parse_addr(name: &str, addr: &str) -> Result<SocketAddr, Box<dyn Error>> {
+    addr.parse().map_err(|e| format!("--{} 地址无效: {} ({})", name, addr, e).into())
+}
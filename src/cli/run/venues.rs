@@ -0,0 +1,80 @@
+//! 币安之外的交易所和 FIX 行情的订阅参数
+
+use std::error::Error;
+
+use order_book::exchange::Exchange;
+use order_book::exchange::bitget::{Bitget, BitgetCategory};
+use order_book::exchange::bitstamp::Bitstamp;
+use order_book::exchange::bybit::{Bybit, BybitCategory};
+use order_book::exchange::coinbase::Coinbase;
+use order_book::exchange::deribit::Deribit;
+use order_book::exchange::dydx::Dydx;
+use order_book::exchange::fix::FixFeedConfig;
+use order_book::exchange::gateio::GateIo;
+use order_book::exchange::htx::Htx;
+use order_book::exchange::hyperliquid::Hyperliquid;
+use order_book::exchange::kraken::Kraken;
+use order_book::exchange::kucoin::Kucoin;
+use order_book::exchange::okx::Okx;
+
+use crate::cli::{split_list, Options};
+
+/// 交易所及其要订阅的交易对
+pub type Venue = (Box<dyn Exchange>, Vec<String>);
+
+/// FIX 行情配置及其要订阅的交易对
+pub type FixFeed = (FixFeedConfig, Vec<String>);
+
+/// 按命令行中出现的顺序创建各交易所及其交易对
+pub fn parse_venues(options: &Options) -> Vec<Venue> {
+    let okx_channel = options.value("okx-channel").unwrap_or("books");
+    let kraken_depth = options.value("kraken-depth")
+        .and_then(|depth| depth.parse::<usize>().ok())
+        .unwrap_or(10);
+    let coinbase_channel = options.value("coinbase-channel").unwrap_or("level2_batch");
+    let bybit_depth = options.value("bybit-depth")
+        .and_then(|depth| depth.parse::<u32>().ok())
+        .unwrap_or(50);
+    let gateio_interval = options.value("gateio-interval").unwrap_or("100ms");
+    let htx_levels = options.value("htx-levels")
+        .and_then(|levels| levels.parse::<u32>().ok())
+        .unwrap_or(150);
+    let deribit_interval = options.value("deribit-interval").unwrap_or("raw");
+
+    let mut venues: Vec<Venue> = Vec::new();
+    for option in options.iter() {
+        let Some((name, list)) = option.strip_prefix("--").and_then(|option| option.split_once('=')) else {
+            continue;
+        };
+        let exchange: Box<dyn Exchange> = match name {
+            "okx" => Box::new(Okx::new(okx_channel)),
+            "kraken" => Box::new(Kraken::new(kraken_depth)),
+            "coinbase" => Box::new(Coinbase::new(coinbase_channel)),
+            "bybit-spot" => Box::new(Bybit::new(BybitCategory::Spot, bybit_depth)),
+            "bybit-linear" => Box::new(Bybit::new(BybitCategory::Linear, bybit_depth)),
+            "bitstamp" => Box::new(Bitstamp::new()),
+            "kucoin" => Box::new(Kucoin::new()),
+            "gateio" => Box::new(GateIo::new(gateio_interval)),
+            "htx" => Box::new(Htx::new(htx_levels)),
+            "deribit" => Box::new(Deribit::new(deribit_interval)),
+            "bitget-spot" => Box::new(Bitget::new(BitgetCategory::Spot)),
+            "bitget-futures" => Box::new(Bitget::new(BitgetCategory::UsdtFutures)),
+            "hyperliquid" => Box::new(Hyperliquid::new()),
+            "dydx" => Box::new(Dydx::new()),
+            _ => continue,
+        };
+        venues.push((exchange, split_list(list)));
+    }
+    venues
+}
+
+/// FIX 行情不是 WebSocket 连接，单独启动
+pub fn parse_fix_feed(options: &Options) -> Result<Option<FixFeed>, Box<dyn Error>> {
+    let Some(list) = options.value("fix") else {
+        return Ok(None);
+    };
+    let addr = options.require("fix-host", "--fix 需要用 --fix-host=地址:端口 指定行情服务")?;
+    let mut config = FixFeedConfig::new(addr, options.value("fix-sender").unwrap_or("ORDERBOOK"), options.value("fix-target").unwrap_or_default());
+    config.depth = options.value("fix-depth").and_then(|depth| depth.parse::<usize>().ok()).unwrap_or(0);
+    Ok(Some((config, split_list(list))))
+}
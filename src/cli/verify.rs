@@ -0,0 +1,33 @@
+//! `verify`：检查记录文件的序号连续性和快照一致性
+
+use std::error::Error;
+use tracing::error;
+
+use order_book::verify::verify_file;
+
+/// 依次检查每个记录文件并打印报告，有文件不通过时返回错误
+pub fn run(paths: &[String]) -> Result<(), Box<dyn Error>> {
+    if paths.is_empty() {
+        return Err("verify 需要指定记录文件".into());
+    }
+    let mut failed = 0;
+    for path in paths {
+        println!("检查 {}", path);
+        match verify_file(path) {
+            Ok(report) => {
+                report.print();
+                if !report.is_ok() {
+                    failed += 1;
+                }
+            }
+            Err(e) => {
+                error!(error = %e, "读取失败");
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 {
+        return Err(format!("{} 个记录文件没有通过检查", failed).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,173 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use crate::binance::{get_server_time, Market};
+use crate::latency::now_millis;
+use crate::metrics;
+
+/// 默认的对时间隔
+pub const DEFAULT_SYNC_INTERVAL: Duration = Duration::from_secs(60);
+/// 估计偏差使用的最近对时次数
+const DEFAULT_SAMPLE_WINDOW: usize = 30;
+/// 每次对时连续请求的次数，取往返时间最短的一次
+const PROBES_PER_SYNC: usize = 4;
+
+/// 一次对时的结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClockSample {
+    /// 请求发出和收到响应的中点，本地时间（毫秒）
+    pub local_time: u64,
+    /// 服务器时间减去本地时间（毫秒），为正表示本地时钟偏慢
+    pub offset_ms: f64,
+    /// 请求往返时间（毫秒），偏差的误差不超过其一半
+    pub rtt_ms: u64,
+}
+
+impl ClockSample {
+    /// 由请求发出时间 `sent`、收到响应时间 `received`（本地毫秒）和服务器时间计算，
+    /// 假设去程和回程耗时相同，服务器时间对应两者的中点
+    pub fn new(sent: u64, received: u64, server_time: u64) -> Self {
+        let local_time = sent + received.saturating_sub(sent) / 2;
+        ClockSample {
+            local_time,
+            offset_ms: server_time as f64 - (sent as f64 + received as f64) / 2.0,
+            rtt_ms: received.saturating_sub(sent),
+        }
+    }
+}
+
+/// 本地时钟相对服务器的偏差估计
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClockEstimate {
+    /// `reference` 时刻的偏差（毫秒）
+    pub offset_ms: f64,
+    /// 偏差的变化率，百万分之一（ppm），为正表示本地时钟比服务器走得慢
+    pub drift_ppm: f64,
+    /// 估计所在的本地时间（毫秒），即最近一次对时
+    pub reference: u64,
+    /// 参与估计的对时次数
+    pub samples: usize,
+}
+
+impl ClockEstimate {
+    /// 按漂移外推到本地时间 `local_time` 的偏差（毫秒）
+    pub fn offset_at(&self, local_time: u64) -> f64 {
+        self.offset_ms + self.drift_ppm * 1e-6 * (local_time as f64 - self.reference as f64)
+    }
+}
+
+/// 本地时钟偏差和漂移估计，在对时线程和主循环之间共享
+///
+/// 对最近若干次对时的偏差按本地时间做最小二乘拟合，斜率为漂移，拟合值为偏差；
+/// 只有一次对时时漂移为 0。校正后的本地时间为本地时间加上外推的偏差
+#[derive(Debug)]
+pub struct ClockSkew {
+    state: Mutex<SkewState>,
+    window: usize,
+}
+
+#[derive(Debug, Default)]
+struct SkewState {
+    samples: VecDeque<ClockSample>,
+    /// 每次对时后重新拟合，主循环每条消息只读取
+    estimate: Option<ClockEstimate>,
+}
+
+impl Default for ClockSkew {
+    fn default() -> Self {
+        ClockSkew::new(DEFAULT_SAMPLE_WINDOW)
+    }
+}
+
+impl ClockSkew {
+    /// 创建估计，保留最近 `window` 次对时
+    pub fn new(window: usize) -> Self {
+        ClockSkew {
+            state: Mutex::new(SkewState::default()),
+            window: window.max(1),
+        }
+    }
+
+    /// 记录一次对时并更新估计
+    pub fn add_sample(&self, sample: ClockSample) {
+        let mut state = self.lock();
+        if state.samples.len() >= self.window {
+            state.samples.pop_front();
+        }
+        state.samples.push_back(sample);
+        state.estimate = fit(&state.samples);
+    }
+
+    /// 当前估计，还没有对时时返回 None
+    pub fn estimate(&self) -> Option<ClockEstimate> {
+        self.lock().estimate
+    }
+
+    /// 把本地时间校正到服务器时间，还没有对时时原样返回
+    pub fn correct(&self, local_time: u64) -> u64 {
+        match self.estimate() {
+            Some(estimate) => (local_time as f64 + estimate.offset_at(local_time)).round().max(0.0) as u64,
+            None => local_time,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, SkewState> {
+        // 持锁的代码不会 panic，锁中毒时数据仍然可用
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// 偏差对本地时间的最小二乘拟合
+fn fit(samples: &VecDeque<ClockSample>) -> Option<ClockEstimate> {
+    let last = samples.back()?;
+    let n = samples.len() as f64;
+    // 以最近一次对时为原点，避免毫秒时间戳平方后丢失精度
+    let xs = samples.iter().map(|sample| sample.local_time as f64 - last.local_time as f64);
+    let mean_x = xs.clone().sum::<f64>() / n;
+    let mean_y = samples.iter().map(|sample| sample.offset_ms).sum::<f64>() / n;
+    let (covariance, variance) = xs.zip(samples.iter())
+        .fold((0.0, 0.0), |(covariance, variance), (x, sample)| {
+            (covariance + (x - mean_x) * (sample.offset_ms - mean_y), variance + (x - mean_x).powi(2))
+        });
+    let slope = if variance > 0.0 { covariance / variance } else { 0.0 };
+    Some(ClockEstimate {
+        offset_ms: mean_y - slope * mean_x,
+        drift_ppm: slope * 1e6,
+        reference: last.local_time,
+        samples: samples.len(),
+    })
+}
+
+/// 在新线程中每隔 `interval` 请求一次币安服务器时间，更新 `skew`
+///
+/// 每次对时连续请求几次，只保留往返时间最短的结果，请求失败时等到下次再试
+pub fn spawn_clock_sync(market: Market, interval: Duration, skew: Arc<ClockSkew>) {
+    thread::spawn(move || {
+        let client = reqwest::blocking::Client::new();
+        loop {
+            let best = (0..PROBES_PER_SYNC)
+                .filter_map(|_| {
+                    let sent = now_millis();
+                    match get_server_time(&client, market) {
+                        Ok(server_time) => Some(ClockSample::new(sent, now_millis(), server_time)),
+                        Err(e) => {
+                            warn!(error = %e, "获取服务器时间失败");
+                            None
+                        }
+                    }
+                })
+                .min_by_key(|sample| sample.rtt_ms);
+            if let Some(sample) = best {
+                skew.add_sample(sample);
+                if let Some(estimate) = skew.estimate() {
+                    debug!(offset_ms = estimate.offset_ms, drift_ppm = estimate.drift_ppm, rtt_ms = sample.rtt_ms, "时钟偏差");
+                    metrics::clock_skew(estimate.offset_ms, estimate.drift_ppm);
+                }
+            }
+            thread::sleep(interval);
+        }
+    });
+}
@@ -0,0 +1,160 @@
+use std::collections::{BTreeMap, HashMap};
+use rust_decimal::Decimal;
+
+use crate::order_book::{OrderBook, Side};
+
+/// 某个交易所在一个价格上的挂单
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VenueQuantity {
+    /// 交易所名称，例如 "okx"
+    pub venue: String,
+    /// 挂单数量
+    pub quantity: Decimal,
+}
+
+/// 合并订单薄的一个价格档位
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsolidatedLevel {
+    /// 价格
+    pub price: Decimal,
+    /// 所有交易所在该价格的总数量
+    pub quantity: Decimal,
+    /// 各交易所的数量（按数量降序）
+    pub venues: Vec<VenueQuantity>,
+}
+
+/// 跨交易所合并订单薄
+///
+/// 把同一品种在多个交易所的订单薄按价格合并，每个档位保留各交易所的数量，
+/// 用于查看全市场最优买卖价、深度以及流动性来源
+#[derive(Debug, Default)]
+pub struct ConsolidatedBook {
+    /// 买单映射 (价格 -> 各交易所数量)
+    bids: BTreeMap<Decimal, Vec<VenueQuantity>>,
+    /// 卖单映射 (价格 -> 各交易所数量)
+    asks: BTreeMap<Decimal, Vec<VenueQuantity>>,
+    /// 参与合并的交易所
+    venues: Vec<String>,
+}
+
+impl ConsolidatedBook {
+    /// 创建空的合并订单薄
+    pub fn new() -> Self {
+        ConsolidatedBook::default()
+    }
+
+    /// 合并多个交易所的订单薄
+    ///
+    /// # 参数
+    ///
+    /// * `books` - (交易所名称, 订单薄) 列表
+    /// * `depth` - 每个交易所每侧最多合并的档位数
+    pub fn from_books<'a>(books: impl IntoIterator<Item = (&'a str, &'a OrderBook)>, depth: usize) -> Self {
+        let mut consolidated = ConsolidatedBook::new();
+        for (venue, book) in books {
+            consolidated.add_book(venue, book, depth);
+        }
+        consolidated
+    }
+
+    /// 加入一个交易所订单薄的前 `depth` 档
+    pub fn add_book(&mut self, venue: &str, book: &OrderBook, depth: usize) {
+        for (price, quantity) in book.bids.iter().rev().take(depth) {
+            insert_level(&mut self.bids, venue, *price, *quantity);
+        }
+        for (price, quantity) in book.asks.iter().take(depth) {
+            insert_level(&mut self.asks, venue, *price, *quantity);
+        }
+        if !self.venues.iter().any(|name| name == venue) {
+            self.venues.push(venue.to_string());
+        }
+    }
+
+    /// 参与合并的交易所
+    pub fn venues(&self) -> &[String] {
+        &self.venues
+    }
+
+    /// 全市场最高买价
+    pub fn best_bid(&self) -> Option<ConsolidatedLevel> {
+        self.bids.iter().next_back().map(|(price, venues)| consolidated_level(*price, venues))
+    }
+
+    /// 全市场最低卖价
+    pub fn best_ask(&self) -> Option<ConsolidatedLevel> {
+        self.asks.iter().next().map(|(price, venues)| consolidated_level(*price, venues))
+    }
+
+    /// 全市场买卖价差，交叉时为负数
+    pub fn spread(&self) -> Option<Decimal> {
+        match (self.best_bid(), self.best_ask()) {
+            (Some(bid), Some(ask)) => Some(ask.price - bid.price),
+            _ => None,
+        }
+    }
+
+    /// 最优买价高于或等于最优卖价（不同交易所之间的价格交叉）
+    pub fn is_crossed(&self) -> bool {
+        self.spread().is_some_and(|spread| spread <= Decimal::ZERO)
+    }
+
+    /// 获取一侧前 `limit` 档（买盘价格降序，卖盘价格升序）
+    pub fn levels(&self, side: Side, limit: usize) -> Vec<ConsolidatedLevel> {
+        let levels: Box<dyn Iterator<Item = (&Decimal, &Vec<VenueQuantity>)>> = match side {
+            Side::Bid => Box::new(self.bids.iter().rev()),
+            Side::Ask => Box::new(self.asks.iter()),
+        };
+        levels.take(limit)
+            .map(|(price, venues)| consolidated_level(*price, venues))
+            .collect()
+    }
+
+    /// 统计一侧前 `limit` 档中各交易所提供的数量
+    pub fn venue_attribution(&self, side: Side, limit: usize) -> HashMap<String, Decimal> {
+        let mut attribution = HashMap::new();
+        for level in self.levels(side, limit) {
+            for venue in level.venues {
+                *attribution.entry(venue.venue).or_insert(Decimal::ZERO) += venue.quantity;
+            }
+        }
+        attribution
+    }
+
+    /// 打印合并订单薄
+    pub fn print_summary(&self, limit: usize) {
+        println!("合并订单薄 交易所: {}", self.venues.join(", "));
+        println!("前{}个卖单 (价格降序):", limit);
+        for level in self.levels(Side::Ask, limit).iter().rev() {
+            println!("  价格: {}, 数量: {} {}", level.price, level.quantity, format_venues(&level.venues));
+        }
+        println!("前{}个买单 (价格降序):", limit);
+        for level in self.levels(Side::Bid, limit) {
+            println!("  价格: {}, 数量: {} {}", level.price, level.quantity, format_venues(&level.venues));
+        }
+        println!();
+    }
+}
+
+fn insert_level(side: &mut BTreeMap<Decimal, Vec<VenueQuantity>>, venue: &str, price: Decimal, quantity: Decimal) {
+    side.entry(price).or_default().push(VenueQuantity {
+        venue: venue.to_string(),
+        quantity,
+    });
+}
+
+fn consolidated_level(price: Decimal, venues: &[VenueQuantity]) -> ConsolidatedLevel {
+    let mut venues = venues.to_vec();
+    venues.sort_by_key(|venue| std::cmp::Reverse(venue.quantity));
+    ConsolidatedLevel {
+        price,
+        quantity: venues.iter().map(|venue| venue.quantity).sum(),
+        venues,
+    }
+}
+
+fn format_venues(venues: &[VenueQuantity]) -> String {
+    let parts: Vec<String> = venues.iter()
+        .map(|venue| format!("{}={}", venue.venue, venue.quantity))
+        .collect();
+    format!("[{}]", parts.join(", "))
+}
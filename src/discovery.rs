@@ -0,0 +1,95 @@
+use std::error::Error;
+
+use crate::binance::{get_exchange_info, Market, SymbolInfo};
+
+/// 交易对筛选条件，用于通配订阅
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolFilter {
+    /// 交易对名称模式，支持 `*` 通配符，例如 `BNB*`
+    pub pattern: Option<String>,
+    /// 基础资产，例如 BNB
+    pub base_asset: Option<String>,
+    /// 计价资产，例如 USDT
+    pub quote_asset: Option<String>,
+    /// 交易状态，默认 TRADING
+    pub status: Option<String>,
+}
+
+impl Default for SymbolFilter {
+    fn default() -> Self {
+        SymbolFilter {
+            pattern: None,
+            base_asset: None,
+            quote_asset: None,
+            status: Some("TRADING".to_string()),
+        }
+    }
+}
+
+impl SymbolFilter {
+    /// 解析筛选条件，格式为逗号分隔的 `键=值`
+    ///
+    /// 支持的键：`symbol`、`base`、`quote`、`status`（`status=*` 表示不限状态），
+    /// 例如 `quote=USDT,status=TRADING`
+    pub fn parse(spec: &str) -> Result<Self, Box<dyn Error>> {
+        let mut filter = SymbolFilter::default();
+        for condition in spec.split(',').filter(|condition| !condition.is_empty()) {
+            let (key, value) = condition.split_once('=')
+                .ok_or_else(|| format!("筛选条件格式错误: {}", condition))?;
+            let value = value.to_uppercase();
+            match key {
+                "symbol" => filter.pattern = Some(value),
+                "base" => filter.base_asset = Some(value),
+                "quote" => filter.quote_asset = Some(value),
+                "status" => filter.status = if value == "*" { None } else { Some(value) },
+                _ => return Err(format!("未知的筛选条件: {}", key).into()),
+            }
+        }
+        Ok(filter)
+    }
+
+    /// 判断交易对是否满足筛选条件
+    pub fn matches(&self, info: &SymbolInfo) -> bool {
+        self.pattern.as_ref().is_none_or(|pattern| wildcard_match(pattern, &info.symbol))
+            && self.base_asset.as_ref().is_none_or(|base| *base == info.baseAsset)
+            && self.quote_asset.as_ref().is_none_or(|quote| *quote == info.quoteAsset)
+            && self.status.as_ref().is_none_or(|status| *status == info.status)
+    }
+}
+
+/// 查询交易规则，返回满足筛选条件的交易对（按名称排序）
+pub fn discover_symbols(market: Market, filter: &SymbolFilter) -> Result<Vec<String>, Box<dyn Error>> {
+    let exchange_info = get_exchange_info(market)?;
+    let mut symbols: Vec<String> = exchange_info.symbols.iter()
+        .filter(|info| filter.matches(info))
+        .map(|info| info.symbol.clone())
+        .collect();
+    symbols.sort();
+    Ok(symbols)
+}
+
+/// 简单通配符匹配，`*` 匹配任意长度字符
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == text;
+    }
+
+    let mut rest = text;
+    for (i, part) in parts.iter().enumerate() {
+        if i == 0 {
+            match rest.strip_prefix(part) {
+                Some(remaining) => rest = remaining,
+                None => return false,
+            }
+        } else if i == parts.len() - 1 {
+            return rest.ends_with(part);
+        } else {
+            match rest.find(part) {
+                Some(index) => rest = &rest[index + part.len()..],
+                None => return false,
+            }
+        }
+    }
+    true
+}
@@ -0,0 +1,88 @@
+use rust_decimal::Decimal;
+
+use crate::analytics::bars::{BarKind, BarSource};
+use crate::analytics::imbalance::ImbalanceCross;
+use crate::analytics::ofi::OfiInterval;
+use crate::analytics::update_rate::UpdateRateAnomaly;
+use crate::analytics::volume_profile::VolumeProfile;
+use crate::analytics::vpin::VpinPoint;
+use crate::analytics::wall::WallEvent;
+use crate::arbitrage::ArbitrageOpportunity;
+use crate::kline::Candle;
+use crate::order_book::Side;
+use crate::strategy::Fill;
+use crate::triangular::TriangularOpportunity;
+
+/// 管理器产生的行情事件，供策略代码消费
+#[derive(Debug, Clone)]
+pub enum MarketEvent {
+    /// 强平订单
+    Liquidation(LiquidationEvent),
+    /// K线收盘
+    CandleClosed {
+        symbol: String,
+        interval: String,
+        candle: Candle,
+    },
+    /// 跨交易所套利机会
+    Arbitrage(ArbitrageOpportunity),
+    /// 交易所内三角套利机会
+    TriangularArbitrage(TriangularOpportunity),
+    /// 买卖盘不平衡度穿越阈值
+    ImbalanceCrossed(ImbalanceCross),
+    /// 订单流不平衡区间汇总
+    OrderFlowImbalance(OfiInterval),
+    /// 成交量分布会话结束
+    VolumeProfileClosed {
+        symbol: String,
+        profile: VolumeProfile,
+    },
+    /// 成交量桶完成，附带完成时的 VPIN
+    Vpin {
+        venue: String,
+        symbol: String,
+        point: VpinPoint,
+    },
+    /// 挂单墙出现、移动或消失
+    Wall(WallEvent),
+    /// 深度更新频率异常
+    UpdateRateAnomaly(UpdateRateAnomaly),
+    /// 由中间价或成交生成的 bar 收盘
+    BarClosed {
+        venue: String,
+        symbol: String,
+        kind: BarKind,
+        source: BarSource,
+        bar: Candle,
+    },
+    /// 策略的模拟成交
+    StrategyFill {
+        strategy: String,
+        fill: Fill,
+    },
+}
+
+/// 强平事件，附带发生时本地订单薄的状态
+#[derive(Debug, Clone)]
+pub struct LiquidationEvent {
+    /// 交易对
+    pub symbol: String,
+    /// 事件时间（毫秒）
+    pub event_time: u64,
+    /// 强平单吃掉的盘口方向（卖出强平吃买盘，买入强平吃卖盘）
+    pub side: Side,
+    /// 强平单价格
+    pub price: Decimal,
+    /// 平均成交价
+    pub avg_price: Decimal,
+    /// 强平单数量
+    pub quantity: Decimal,
+    /// 强平发生时的中间价，订单薄未建立时为 None
+    pub mid_price: Option<Decimal>,
+    /// 平均成交价距中间价的距离（基点）
+    pub distance_bps: Option<Decimal>,
+    /// 按当前订单薄计算会吃掉的档位数
+    pub consumed_levels: usize,
+    /// 按当前订单薄计算可成交的数量
+    pub consumed_quantity: Decimal,
+}
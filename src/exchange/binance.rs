@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::time::Duration;
+use serde_json::json;
+
+use crate::binance::Market;
+use crate::exchange::{AdapterOutput, Exchange};
+use crate::manager::BINANCE_VENUE;
+
+/// 单条订阅消息包含的最大流数量
+const SUBSCRIBE_BATCH_SIZE: usize = 200;
+/// 连续发送订阅消息的间隔，币安限制每个连接每秒收到的消息数量
+const SUBSCRIBE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// 币安组合流适配器
+///
+/// 深度、标记价格、K线等消息不在这里解析，原样转发给主循环，由订单薄管理器获取快照并按序号同步；
+/// 连接断开后管理器丢弃这个连接上的订单薄，重连后重新获取快照
+pub struct BinanceStream {
+    market: Market,
+    /// 交易对到需要订阅的流名称
+    streams: HashMap<String, Vec<String>>,
+}
+
+impl BinanceStream {
+    /// 创建适配器
+    ///
+    /// # 参数
+    ///
+    /// * `market` - 市场类型
+    /// * `streams` - 这个连接订阅的交易对和各自的流名称
+    pub fn new(market: Market, streams: Vec<(String, Vec<String>)>) -> Self {
+        BinanceStream { market, streams: streams.into_iter().collect() }
+    }
+}
+
+impl Exchange for BinanceStream {
+    fn name(&self) -> &'static str {
+        BINANCE_VENUE
+    }
+
+    fn connect_url(&mut self) -> Result<String, Box<dyn Error>> {
+        Ok(self.market.ws_url().to_string())
+    }
+
+    /// 交易对较多时分批订阅
+    fn subscribe_messages(&self, symbols: &[String]) -> Vec<String> {
+        let params: Vec<&String> = symbols.iter()
+            .filter_map(|symbol| self.streams.get(symbol))
+            .flatten()
+            .collect();
+        params
+            .chunks(SUBSCRIBE_BATCH_SIZE)
+            .enumerate()
+            .map(|(i, params)| json!({
+                "method": "SUBSCRIBE",
+                "params": params,
+                "id": i + 1
+            }).to_string())
+            .collect()
+    }
+
+    fn subscribe_interval(&self) -> Duration {
+        SUBSCRIBE_INTERVAL
+    }
+
+    fn parse_text(&mut self, text: &str) -> Result<Vec<AdapterOutput>, Box<dyn Error>> {
+        Ok(vec![AdapterOutput::Raw(text.to_string())])
+    }
+}
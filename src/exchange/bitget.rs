@@ -0,0 +1,159 @@
+use std::error::Error;
+use std::time::Duration;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::exchange::{parse_levels, AdapterOutput, Checksum, Continuity, DepthKind, DepthMessage, Exchange};
+
+/// Bitget 产品类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitgetCategory {
+    /// 现货
+    Spot,
+    /// USDT 本位合约
+    UsdtFutures,
+}
+
+impl BitgetCategory {
+    fn inst_type(&self) -> &'static str {
+        match self {
+            BitgetCategory::Spot => "SPOT",
+            BitgetCategory::UsdtFutures => "USDT-FUTURES",
+        }
+    }
+}
+
+/// Bitget v2 公共频道消息
+#[derive(Debug, Deserialize)]
+struct BitgetMessage {
+    event: Option<String>,
+    code: Option<serde_json::Value>,
+    msg: Option<String>,
+    action: Option<String>,
+    arg: Option<BitgetArg>,
+    data: Option<Vec<BitgetBook>>,
+}
+
+/// 订阅频道参数
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize)]
+struct BitgetArg {
+    channel: String,
+    instId: String,            // 产品ID，例如 BTCUSDT
+}
+
+/// 深度数据
+#[derive(Debug, Deserialize)]
+struct BitgetBook {
+    asks: Vec<Vec<String>>,    // 卖单 [价格, 数量]
+    bids: Vec<Vec<String>>,    // 买单 [价格, 数量]
+    checksum: Option<i32>,     // 前25档校验和
+    ts: String,                // 数据产生时间
+}
+
+/// Bitget 订单薄适配器，使用 `books` 增量频道
+///
+/// 推送没有可校验的连续序号，依赖每条消息附带的前25档校验和发现错误
+pub struct Bitget {
+    category: BitgetCategory,
+}
+
+impl Bitget {
+    /// 创建适配器
+    pub fn new(category: BitgetCategory) -> Self {
+        Bitget { category }
+    }
+
+    fn channel_message(&self, op: &str, symbols: &[String]) -> String {
+        let args: Vec<serde_json::Value> = symbols.iter()
+            .map(|symbol| json!({ "instType": self.category.inst_type(), "channel": "books", "instId": symbol }))
+            .collect();
+        json!({ "op": op, "args": args }).to_string()
+    }
+}
+
+impl Exchange for Bitget {
+    fn name(&self) -> &'static str {
+        match self.category {
+            BitgetCategory::Spot => "bitget-spot",
+            BitgetCategory::UsdtFutures => "bitget-futures",
+        }
+    }
+
+    fn connect_url(&mut self) -> Result<String, Box<dyn Error>> {
+        Ok("wss://ws.bitget.com/v2/ws/public".to_string())
+    }
+
+    fn subscribe_messages(&self, symbols: &[String]) -> Vec<String> {
+        vec![self.channel_message("subscribe", symbols)]
+    }
+
+    fn resync_messages(&self, symbol: &str) -> Vec<String> {
+        // 重新订阅后服务端会重新推送全量快照
+        let symbols = [symbol.to_string()];
+        vec![
+            self.channel_message("unsubscribe", &symbols),
+            self.channel_message("subscribe", &symbols),
+        ]
+    }
+
+    fn heartbeat(&self) -> Option<(String, Duration)> {
+        // 2分钟内没有收到 ping 服务端会断开连接
+        Some(("ping".to_string(), Duration::from_secs(30)))
+    }
+
+    fn parse_text(&mut self, text: &str) -> Result<Vec<AdapterOutput>, Box<dyn Error>> {
+        if text == "pong" {
+            return Ok(Vec::new());
+        }
+        let message: BitgetMessage = serde_json::from_str(text)?;
+        if message.event.as_deref() == Some("error") {
+            let code = message.code.map(|code| code.to_string()).unwrap_or_default();
+            return Err(format!("Bitget 错误: {} {}", code, message.msg.unwrap_or_default()).into());
+        }
+        let (Some(arg), Some(action), Some(data)) = (message.arg, message.action, message.data) else {
+            return Ok(Vec::new());
+        };
+        if arg.channel != "books" {
+            return Ok(Vec::new());
+        }
+
+        let kind = if action == "snapshot" { DepthKind::Snapshot } else { DepthKind::Delta };
+        let mut outputs = Vec::with_capacity(data.len());
+        for book in data {
+            outputs.push(AdapterOutput::Depth(DepthMessage {
+                symbol: arg.instId.clone(),
+                kind,
+                bids: parse_levels(&book.bids)?,
+                asks: parse_levels(&book.asks)?,
+                continuity: Continuity::None,
+                checksum: book.checksum.map(Checksum::Bitget),
+                max_depth: None,
+                timestamp: book.ts.parse::<u64>()?,
+            }));
+        }
+        Ok(outputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order_book::OrderBook;
+
+    /// 快照消息中的校验和按 OKX 算法与本地订单薄比较，`3366.1:7:3366.8:9:3366:6:3368:8` 的 CRC32 为 -1881014294
+    #[test]
+    fn snapshot_checksum_verifies() {
+        let text = r#"{"action":"snapshot","arg":{"instType":"SPOT","channel":"books","instId":"BTCUSDT"},
+            "data":[{"asks":[["3366.8","9"],["3368","8"]],"bids":[["3366.1","7"],["3366","6"]],"checksum":-1881014294,"ts":"1695710946294"}]}"#;
+        let outputs = Bitget::new(BitgetCategory::Spot).parse_text(text).unwrap();
+        let [AdapterOutput::Depth(message)] = outputs.as_slice() else {
+            panic!("应解析出一条深度消息: {:?}", outputs);
+        };
+        assert_eq!(message.kind, DepthKind::Snapshot);
+        assert_eq!(message.checksum, Some(Checksum::Bitget(-1881014294)));
+        let book = OrderBook::from_levels(0, &message.bids, &message.asks);
+        assert!(Checksum::Bitget(-1881014294).verify(&book));
+        assert!(!Checksum::Bitget(-1881014293).verify(&book));
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::time::Duration;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::exchange::{parse_levels, AdapterOutput, Continuity, DepthKind, DepthMessage, Exchange};
+
+/// Bitstamp 频道消息
+#[derive(Debug, Deserialize)]
+struct BitstampMessage {
+    event: String,
+    channel: String,
+    data: Option<BitstampBook>,
+}
+
+/// 深度数据
+#[derive(Debug, Deserialize)]
+struct BitstampBook {
+    microtimestamp: Option<String>,   // 微秒时间戳
+    bids: Option<Vec<Vec<String>>>,   // 买单 [价格, 数量]
+    asks: Option<Vec<Vec<String>>>,   // 卖单 [价格, 数量]
+}
+
+/// Bitstamp 订单薄适配器
+///
+/// 订阅 `diff_order_book` 增量频道，同时订阅 `order_book` 频道取得一次快照后退订，
+/// 增量按微秒时间戳排序，早于快照的增量丢弃
+pub struct Bitstamp {
+    /// 正在等待快照的交易对
+    awaiting_snapshot: HashSet<String>,
+}
+
+impl Bitstamp {
+    /// 创建适配器
+    pub fn new() -> Self {
+        Bitstamp { awaiting_snapshot: HashSet::new() }
+    }
+
+    fn channel_message(event: &str, channel: &str, symbol: &str) -> String {
+        json!({
+            "event": event,
+            "data": { "channel": format!("{}_{}", channel, symbol.to_lowercase()) },
+        }).to_string()
+    }
+}
+
+impl Default for Bitstamp {
+    fn default() -> Self {
+        Bitstamp::new()
+    }
+}
+
+impl Exchange for Bitstamp {
+    fn name(&self) -> &'static str {
+        "bitstamp"
+    }
+
+    fn connect_url(&mut self) -> Result<String, Box<dyn Error>> {
+        self.awaiting_snapshot.clear();
+        Ok("wss://ws.bitstamp.net".to_string())
+    }
+
+    fn subscribe_messages(&self, symbols: &[String]) -> Vec<String> {
+        symbols.iter()
+            .flat_map(|symbol| [
+                Self::channel_message("bts:subscribe", "diff_order_book", symbol),
+                Self::channel_message("bts:subscribe", "order_book", symbol),
+            ])
+            .collect()
+    }
+
+    fn resync_messages(&self, symbol: &str) -> Vec<String> {
+        vec![Self::channel_message("bts:subscribe", "order_book", symbol)]
+    }
+
+    fn heartbeat(&self) -> Option<(String, Duration)> {
+        Some((json!({ "event": "bts:heartbeat" }).to_string(), Duration::from_secs(30)))
+    }
+
+    fn parse_text(&mut self, text: &str) -> Result<Vec<AdapterOutput>, Box<dyn Error>> {
+        let message: BitstampMessage = serde_json::from_str(text)?;
+        if message.event == "bts:subscription_succeeded" {
+            if let Some(symbol) = message.channel.strip_prefix("order_book_") {
+                self.awaiting_snapshot.insert(symbol.to_string());
+            }
+            return Ok(Vec::new());
+        }
+        if message.event == "bts:error" {
+            return Err(format!("Bitstamp 错误: {}", text).into());
+        }
+        if message.event != "data" {
+            return Ok(Vec::new());
+        }
+        let Some(book) = message.data else {
+            return Ok(Vec::new());
+        };
+
+        let (kind, symbol) = if let Some(symbol) = message.channel.strip_prefix("diff_order_book_") {
+            (DepthKind::Delta, symbol.to_string())
+        } else if let Some(symbol) = message.channel.strip_prefix("order_book_") {
+            // 只取一次快照，之后靠增量维护
+            if !self.awaiting_snapshot.remove(symbol) {
+                return Ok(Vec::new());
+            }
+            (DepthKind::Snapshot, symbol.to_string())
+        } else {
+            return Ok(Vec::new());
+        };
+
+        let microtimestamp = book.microtimestamp.as_deref().unwrap_or("0").parse::<u64>()?;
+        let mut outputs = vec![AdapterOutput::Depth(DepthMessage {
+            symbol: symbol.clone(),
+            kind,
+            bids: parse_levels(&book.bids.unwrap_or_default())?,
+            asks: parse_levels(&book.asks.unwrap_or_default())?,
+            continuity: Continuity::Monotonic(microtimestamp),
+            checksum: None,
+            max_depth: None,
+            timestamp: microtimestamp / 1000,
+        })];
+        if kind == DepthKind::Snapshot {
+            outputs.push(AdapterOutput::Reply(Self::channel_message("bts:unsubscribe", "order_book", &symbol)));
+        }
+        Ok(outputs)
+    }
+}
@@ -0,0 +1,120 @@
+use std::error::Error;
+use std::time::Duration;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::exchange::{parse_levels, AdapterOutput, Continuity, DepthKind, DepthMessage, Exchange};
+
+/// Bybit 市场类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BybitCategory {
+    /// 现货
+    Spot,
+    /// USDT/USDC 永续及交割合约
+    Linear,
+}
+
+/// Bybit v5 公共频道消息
+#[derive(Debug, Deserialize)]
+struct BybitMessage {
+    topic: Option<String>,
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    ts: Option<u64>,
+    data: Option<BybitBook>,
+    success: Option<bool>,
+    ret_msg: Option<String>,
+}
+
+/// 深度数据
+#[derive(Debug, Deserialize)]
+struct BybitBook {
+    s: String,                 // 交易对
+    b: Vec<Vec<String>>,       // 买单 [价格, 数量]
+    a: Vec<Vec<String>>,       // 卖单 [价格, 数量]
+    u: u64,                    // 更新ID，连续递增，为1时表示服务重启后的快照
+}
+
+/// Bybit v5 订单薄适配器，使用 `orderbook.{depth}.{symbol}` 频道
+pub struct Bybit {
+    category: BybitCategory,
+    depth: u32,
+}
+
+impl Bybit {
+    /// 创建适配器
+    ///
+    /// # 参数
+    ///
+    /// * `category` - 市场类别
+    /// * `depth` - 订阅深度，例如 50 或 500（现货最大 200）
+    pub fn new(category: BybitCategory, depth: u32) -> Self {
+        Bybit { category, depth }
+    }
+
+    fn topic_message(&self, op: &str, symbols: &[String]) -> String {
+        let args: Vec<String> = symbols.iter()
+            .map(|symbol| format!("orderbook.{}.{}", self.depth, symbol))
+            .collect();
+        json!({ "op": op, "args": args }).to_string()
+    }
+}
+
+impl Exchange for Bybit {
+    fn name(&self) -> &'static str {
+        match self.category {
+            BybitCategory::Spot => "bybit-spot",
+            BybitCategory::Linear => "bybit-linear",
+        }
+    }
+
+    fn connect_url(&mut self) -> Result<String, Box<dyn Error>> {
+        let url = match self.category {
+            BybitCategory::Spot => "wss://stream.bybit.com/v5/public/spot",
+            BybitCategory::Linear => "wss://stream.bybit.com/v5/public/linear",
+        };
+        Ok(url.to_string())
+    }
+
+    fn subscribe_messages(&self, symbols: &[String]) -> Vec<String> {
+        vec![self.topic_message("subscribe", symbols)]
+    }
+
+    fn resync_messages(&self, symbol: &str) -> Vec<String> {
+        let symbols = [symbol.to_string()];
+        vec![
+            self.topic_message("unsubscribe", &symbols),
+            self.topic_message("subscribe", &symbols),
+        ]
+    }
+
+    fn heartbeat(&self) -> Option<(String, Duration)> {
+        Some((json!({ "op": "ping" }).to_string(), Duration::from_secs(20)))
+    }
+
+    fn parse_text(&mut self, text: &str) -> Result<Vec<AdapterOutput>, Box<dyn Error>> {
+        let message: BybitMessage = serde_json::from_str(text)?;
+        if message.success == Some(false) {
+            return Err(format!("Bybit 错误: {}", message.ret_msg.unwrap_or_default()).into());
+        }
+        if !message.topic.as_deref().is_some_and(|topic| topic.starts_with("orderbook.")) {
+            return Ok(Vec::new());
+        }
+        let (Some(kind), Some(book)) = (message.kind, message.data) else {
+            return Ok(Vec::new());
+        };
+
+        // u 为 1 的增量同样表示服务重启后的全量快照
+        let kind = if kind == "snapshot" || book.u == 1 { DepthKind::Snapshot } else { DepthKind::Delta };
+        Ok(vec![AdapterOutput::Depth(DepthMessage {
+            symbol: book.s,
+            kind,
+            bids: parse_levels(&book.b)?,
+            asks: parse_levels(&book.a)?,
+            continuity: Continuity::Range { first: book.u, last: book.u },
+            checksum: None,
+            max_depth: None,
+            timestamp: message.ts.unwrap_or_default(),
+        })])
+    }
+}
@@ -0,0 +1,107 @@
+use std::error::Error;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::exchange::{parse_levels, parse_rfc3339_millis, AdapterOutput, Continuity, DepthKind, DepthMessage, Exchange};
+
+/// Coinbase Exchange 消息
+#[derive(Debug, Deserialize)]
+struct CoinbaseMessage {
+    #[serde(rename = "type")]
+    kind: String,
+    product_id: Option<String>,
+    message: Option<String>,
+    reason: Option<String>,
+    time: Option<String>,
+    /// snapshot 的买单 [价格, 数量]
+    bids: Option<Vec<Vec<String>>>,
+    /// snapshot 的卖单 [价格, 数量]
+    asks: Option<Vec<Vec<String>>>,
+    /// l2update 的变动 [方向, 价格, 数量]
+    changes: Option<Vec<Vec<String>>>,
+}
+
+/// Coinbase Exchange 订单薄适配器，使用 level2 / level2_batch 频道
+///
+/// level2 频道需要鉴权，未鉴权时使用 level2_batch（50ms 批量推送）
+pub struct Coinbase {
+    channel: String,
+}
+
+impl Coinbase {
+    /// 创建适配器
+    ///
+    /// # 参数
+    ///
+    /// * `channel` - 深度频道，"level2" 或 "level2_batch"
+    pub fn new(channel: &str) -> Self {
+        Coinbase { channel: channel.to_string() }
+    }
+
+    fn channel_message(&self, kind: &str, symbols: &[String]) -> String {
+        json!({
+            "type": kind,
+            "product_ids": symbols,
+            "channels": [self.channel],
+        }).to_string()
+    }
+}
+
+impl Exchange for Coinbase {
+    fn name(&self) -> &'static str {
+        "coinbase"
+    }
+
+    fn connect_url(&mut self) -> Result<String, Box<dyn Error>> {
+        Ok("wss://ws-feed.exchange.coinbase.com".to_string())
+    }
+
+    fn subscribe_messages(&self, symbols: &[String]) -> Vec<String> {
+        vec![self.channel_message("subscribe", symbols)]
+    }
+
+    fn resync_messages(&self, symbol: &str) -> Vec<String> {
+        let symbols = [symbol.to_string()];
+        vec![
+            self.channel_message("unsubscribe", &symbols),
+            self.channel_message("subscribe", &symbols),
+        ]
+    }
+
+    fn parse_text(&mut self, text: &str) -> Result<Vec<AdapterOutput>, Box<dyn Error>> {
+        let message: CoinbaseMessage = serde_json::from_str(text)?;
+        let kind = match message.kind.as_str() {
+            "snapshot" => DepthKind::Snapshot,
+            "l2update" => DepthKind::Delta,
+            "error" => {
+                return Err(format!("Coinbase 错误: {} {}",
+                                   message.message.unwrap_or_default(), message.reason.unwrap_or_default()).into());
+            }
+            _ => return Ok(Vec::new()),
+        };
+        let symbol = message.product_id.ok_or("Coinbase 消息缺少交易对")?;
+
+        let mut depth = DepthMessage {
+            symbol,
+            kind,
+            bids: parse_levels(&message.bids.unwrap_or_default())?,
+            asks: parse_levels(&message.asks.unwrap_or_default())?,
+            continuity: Continuity::None,
+            checksum: None,
+            max_depth: None,
+            timestamp: message.time.as_deref().and_then(parse_rfc3339_millis).unwrap_or_default(),
+        };
+        for change in message.changes.unwrap_or_default() {
+            let [side, price, size] = change.as_slice() else {
+                return Err(format!("Coinbase 变动格式错误: {:?}", change).into());
+            };
+            let level = (price.parse()?, size.parse()?);
+            match side.as_str() {
+                "buy" => depth.bids.push(level),
+                "sell" => depth.asks.push(level),
+                _ => return Err(format!("Coinbase 未知方向: {}", side).into()),
+            }
+        }
+        Ok(vec![AdapterOutput::Depth(depth)])
+    }
+}
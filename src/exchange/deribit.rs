@@ -0,0 +1,155 @@
+use std::error::Error;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::exchange::{AdapterOutput, Continuity, DepthKind, DepthMessage, Exchange};
+
+/// 服务端心跳间隔（秒）
+const HEARTBEAT_INTERVAL: u64 = 30;
+
+/// Deribit JSON-RPC 消息
+#[derive(Debug, Deserialize)]
+struct DeribitMessage {
+    method: Option<String>,
+    params: Option<DeribitParams>,
+    error: Option<DeribitError>,
+}
+
+/// 推送参数
+#[derive(Debug, Deserialize)]
+struct DeribitParams {
+    channel: Option<String>,
+    #[serde(rename = "type")]
+    kind: Option<String>,      // 心跳类型，test_request 需要回复
+    data: Option<DeribitBook>,
+}
+
+/// 错误信息
+#[derive(Debug, Deserialize)]
+struct DeribitError {
+    code: i64,
+    message: String,
+}
+
+/// 深度数据
+#[derive(Debug, Deserialize)]
+struct DeribitBook {
+    #[serde(rename = "type")]
+    kind: String,                          // snapshot 或 change
+    timestamp: u64,
+    instrument_name: String,
+    change_id: u64,
+    prev_change_id: Option<u64>,           // 快照没有此字段
+    bids: Vec<(String, Decimal, Decimal)>, // [new|change|delete, 价格, 数量]
+    asks: Vec<(String, Decimal, Decimal)>,
+}
+
+/// Deribit 订单薄适配器，使用 `book.{instrument}.{interval}` 频道
+///
+/// 期权、永续和交割合约都可以订阅，增量的 `prev_change_id` 需等于上一条的 `change_id`
+pub struct Deribit {
+    interval: String,
+}
+
+impl Deribit {
+    /// 创建适配器
+    ///
+    /// # 参数
+    ///
+    /// * `interval` - 推送间隔，"raw"、"100ms" 或 "agg2"
+    pub fn new(interval: &str) -> Self {
+        Deribit { interval: interval.to_string() }
+    }
+
+    fn channel_message(&self, method: &str, symbols: &[String]) -> String {
+        let channels: Vec<String> = symbols.iter()
+            .map(|symbol| format!("book.{}.{}", symbol, self.interval))
+            .collect();
+        json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": { "channels": channels },
+        }).to_string()
+    }
+
+    /// 档位动作转为 (价格, 数量)，删除的档位数量为0
+    fn levels(levels: Vec<(String, Decimal, Decimal)>) -> Vec<(Decimal, Decimal)> {
+        levels.into_iter()
+            .map(|(action, price, amount)| {
+                if action == "delete" { (price, Decimal::ZERO) } else { (price, amount) }
+            })
+            .collect()
+    }
+}
+
+impl Exchange for Deribit {
+    fn name(&self) -> &'static str {
+        "deribit"
+    }
+
+    fn connect_url(&mut self) -> Result<String, Box<dyn Error>> {
+        Ok("wss://www.deribit.com/ws/api/v2".to_string())
+    }
+
+    fn subscribe_messages(&self, symbols: &[String]) -> Vec<String> {
+        // 开启服务端心跳，收到 test_request 后回复 public/test
+        let set_heartbeat = json!({
+            "jsonrpc": "2.0",
+            "id": 0,
+            "method": "public/set_heartbeat",
+            "params": { "interval": HEARTBEAT_INTERVAL },
+        }).to_string();
+        vec![set_heartbeat, self.channel_message("public/subscribe", symbols)]
+    }
+
+    fn resync_messages(&self, symbol: &str) -> Vec<String> {
+        // 重新订阅后服务端会重新推送全量快照
+        let symbols = [symbol.to_string()];
+        vec![
+            self.channel_message("public/unsubscribe", &symbols),
+            self.channel_message("public/subscribe", &symbols),
+        ]
+    }
+
+    fn parse_text(&mut self, text: &str) -> Result<Vec<AdapterOutput>, Box<dyn Error>> {
+        let message: DeribitMessage = serde_json::from_str(text)?;
+        if let Some(error) = message.error {
+            return Err(format!("Deribit 错误: {} {}", error.code, error.message).into());
+        }
+        let (Some(method), Some(params)) = (message.method, message.params) else {
+            return Ok(Vec::new());
+        };
+        if method == "heartbeat" {
+            if params.kind.as_deref() == Some("test_request") {
+                let reply = json!({ "jsonrpc": "2.0", "id": 2, "method": "public/test", "params": {} });
+                return Ok(vec![AdapterOutput::Reply(reply.to_string())]);
+            }
+            return Ok(Vec::new());
+        }
+        if method != "subscription" || !params.channel.as_deref().is_some_and(|channel| channel.starts_with("book.")) {
+            return Ok(Vec::new());
+        }
+        let Some(book) = params.data else {
+            return Ok(Vec::new());
+        };
+
+        let (kind, continuity) = if book.kind == "snapshot" {
+            (DepthKind::Snapshot, Continuity::Prev { prev: book.change_id, sequence: book.change_id })
+        } else {
+            let prev = book.prev_change_id.ok_or("Deribit 增量缺少 prev_change_id")?;
+            (DepthKind::Delta, Continuity::Prev { prev, sequence: book.change_id })
+        };
+        Ok(vec![AdapterOutput::Depth(DepthMessage {
+            symbol: book.instrument_name,
+            kind,
+            bids: Self::levels(book.bids),
+            asks: Self::levels(book.asks),
+            continuity,
+            checksum: None,
+            max_depth: None,
+            timestamp: book.timestamp,
+        })])
+    }
+}
@@ -0,0 +1,139 @@
+use std::error::Error;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::exchange::{parse_levels, AdapterOutput, Continuity, DepthKind, DepthMessage, Exchange};
+
+/// dYdX v4 Indexer WebSocket 消息
+#[derive(Debug, Deserialize)]
+struct DydxMessage {
+    #[serde(rename = "type")]
+    kind: String,
+    channel: Option<String>,
+    id: Option<String>,
+    message: Option<String>,
+    contents: Option<serde_json::Value>,
+}
+
+/// 订阅成功时的全量快照，档位为对象
+#[derive(Debug, Deserialize)]
+struct DydxSnapshot {
+    #[serde(default)]
+    bids: Vec<DydxLevel>,
+    #[serde(default)]
+    asks: Vec<DydxLevel>,
+}
+
+/// 快照档位
+#[derive(Debug, Deserialize)]
+struct DydxLevel {
+    price: String,
+    size: String,
+}
+
+/// 增量更新，档位为 [价格, 数量]
+#[derive(Debug, Deserialize)]
+struct DydxUpdate {
+    #[serde(default)]
+    bids: Vec<Vec<String>>,
+    #[serde(default)]
+    asks: Vec<Vec<String>>,
+}
+
+/// dYdX v4 永续合约订单薄适配器，使用 `v4_orderbook` 频道
+///
+/// `message_id` 在整个连接内递增而不是按市场递增，无法校验单个市场的连续性
+pub struct Dydx;
+
+impl Dydx {
+    /// 创建适配器
+    pub fn new() -> Self {
+        Dydx
+    }
+
+    fn channel_message(kind: &str, market: &str) -> String {
+        json!({ "type": kind, "channel": "v4_orderbook", "id": market }).to_string()
+    }
+}
+
+impl Default for Dydx {
+    fn default() -> Self {
+        Dydx::new()
+    }
+}
+
+impl Exchange for Dydx {
+    fn name(&self) -> &'static str {
+        "dydx"
+    }
+
+    fn connect_url(&mut self) -> Result<String, Box<dyn Error>> {
+        Ok("wss://indexer.dydx.trade/v4/ws".to_string())
+    }
+
+    fn subscribe_messages(&self, symbols: &[String]) -> Vec<String> {
+        symbols.iter()
+            .map(|market| Self::channel_message("subscribe", market))
+            .collect()
+    }
+
+    fn resync_messages(&self, symbol: &str) -> Vec<String> {
+        // 重新订阅后服务端会重新推送全量快照
+        vec![
+            Self::channel_message("unsubscribe", symbol),
+            Self::channel_message("subscribe", symbol),
+        ]
+    }
+
+    fn parse_text(&mut self, text: &str) -> Result<Vec<AdapterOutput>, Box<dyn Error>> {
+        let message: DydxMessage = serde_json::from_str(text)?;
+        if message.kind == "error" {
+            return Err(format!("dYdX 错误: {}", message.message.unwrap_or_default()).into());
+        }
+        if message.channel.as_deref() != Some("v4_orderbook") {
+            return Ok(Vec::new());
+        }
+        let (Some(market), Some(contents)) = (message.id, message.contents) else {
+            return Ok(Vec::new());
+        };
+
+        let depth = match message.kind.as_str() {
+            "subscribed" => {
+                let snapshot: DydxSnapshot = serde_json::from_value(contents)?;
+                DepthMessage {
+                    symbol: market,
+                    kind: DepthKind::Snapshot,
+                    bids: snapshot_levels(&snapshot.bids)?,
+                    asks: snapshot_levels(&snapshot.asks)?,
+                    continuity: Continuity::None,
+                    checksum: None,
+                    max_depth: None,
+                    timestamp: 0,
+                }
+            }
+            "channel_data" => {
+                let update: DydxUpdate = serde_json::from_value(contents)?;
+                DepthMessage {
+                    symbol: market,
+                    kind: DepthKind::Delta,
+                    bids: parse_levels(&update.bids)?,
+                    asks: parse_levels(&update.asks)?,
+                    continuity: Continuity::None,
+                    checksum: None,
+                    max_depth: None,
+                    timestamp: 0,
+                }
+            }
+            _ => return Ok(Vec::new()),
+        };
+        Ok(vec![AdapterOutput::Depth(depth)])
+    }
+}
+
+/// 解析快照档位
+fn snapshot_levels(levels: &[DydxLevel]) -> Result<Vec<(Decimal, Decimal)>, Box<dyn Error>> {
+    levels.iter()
+        .map(|level| Ok((level.price.parse::<Decimal>()?, level.size.parse::<Decimal>()?)))
+        .collect()
+}
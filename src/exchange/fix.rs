@@ -0,0 +1,144 @@
+use std::error::Error;
+use std::io::Read;
+use std::net::TcpStream;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{debug_span, info, warn};
+
+use crate::exchange::{is_timeout, FeedCommand, FeedEvent, READ_TIMEOUT, RECONNECT_DELAY};
+use crate::fix::{self, FixReader, FixWriter};
+use crate::metrics;
+use crate::profile::{self, Stage};
+
+/// FIX 行情在本地的交易所名称
+pub const FIX_VENUE: &str = "fix";
+/// 默认心跳间隔
+pub const DEFAULT_HEARTBEAT: Duration = Duration::from_secs(30);
+
+/// FIX 行情连接参数
+#[derive(Debug, Clone)]
+pub struct FixFeedConfig {
+    /// 对方地址，例如 `127.0.0.1:9878`
+    pub addr: String,
+    /// 本方 SenderCompID
+    pub sender: String,
+    /// 对方 TargetCompID
+    pub target: String,
+    /// 每侧档位数，0 表示全部
+    pub depth: usize,
+    pub heartbeat: Duration,
+}
+
+impl FixFeedConfig {
+    /// 订阅全部档位，使用默认心跳间隔
+    pub fn new(addr: &str, sender: &str, target: &str) -> Self {
+        FixFeedConfig {
+            addr: addr.to_string(),
+            sender: sender.to_string(),
+            target: target.to_string(),
+            depth: 0,
+            heartbeat: DEFAULT_HEARTBEAT,
+        }
+    }
+}
+
+/// 在新线程中作为发起方连接 FIX 4.4 行情服务，断线自动重连
+///
+/// 登录后为每个交易对发送 MarketDataRequest（MDReqID 为交易对名称），快照 (W) 和增量更新 (X) 转为统一深度消息，
+/// 交易所名称为 [`FIX_VENUE`]。FIX 行情没有逐个交易对的序号，改为检查会话序号：出现缺口时重连，
+/// 所有交易对重新从快照开始；主循环请求重新同步时退订后重新订阅该交易对
+pub fn spawn_fix_feed(config: FixFeedConfig, symbols: Vec<String>, events: Sender<FeedEvent>) -> Sender<FeedCommand> {
+    let (command_tx, command_rx) = mpsc::channel();
+    thread::spawn(move || {
+        loop {
+            match run_fix_feed(&config, &symbols, &events, &command_rx) {
+                Ok(()) => return,
+                Err(e) => {
+                    warn!(venue = FIX_VENUE, error = %e, "行情连接中断，{}秒后重连", RECONNECT_DELAY.as_secs());
+                    thread::sleep(RECONNECT_DELAY);
+                    metrics::reconnect(FIX_VENUE);
+                }
+            }
+        }
+    });
+    command_tx
+}
+
+/// 运行一次会话，主循环退出时返回 Ok
+fn run_fix_feed(
+    config: &FixFeedConfig,
+    symbols: &[String],
+    events: &Sender<FeedEvent>,
+    commands: &Receiver<FeedCommand>,
+) -> Result<(), Box<dyn Error>> {
+    info!(venue = FIX_VENUE, addr = config.addr, "正在连接");
+    let mut stream = TcpStream::connect(&config.addr)?;
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut writer = FixWriter::new(stream.try_clone()?, &config.sender, &config.target);
+    writer.send(&fix::logon(config.heartbeat))?;
+    for symbol in symbols {
+        writer.send(&fix::market_data_request(symbol, fix::SNAPSHOT_AND_UPDATES, std::slice::from_ref(symbol), config.depth))?;
+    }
+
+    let mut reader = FixReader::default();
+    let mut buffer = [0u8; 65536];
+    // 登录时要求重置序号，对方从 1 开始
+    let mut expected_seq = 1;
+    loop {
+        match commands.try_recv() {
+            Ok(FeedCommand::Resync(symbol)) => {
+                info!(venue = FIX_VENUE, symbol, "重新同步");
+                let symbols = std::slice::from_ref(&symbol);
+                writer.send(&fix::market_data_request(&symbol, fix::UNSUBSCRIBE, symbols, config.depth))?;
+                writer.send(&fix::market_data_request(&symbol, fix::SNAPSHOT_AND_UPDATES, symbols, config.depth))?;
+            }
+            Err(TryRecvError::Empty) => {}
+            Err(TryRecvError::Disconnected) => return Ok(()),
+        }
+        writer.heartbeat_if_idle(config.heartbeat)?;
+
+        let started = Instant::now();
+        let read = match stream.read(&mut buffer) {
+            Ok(0) => return Err("连接已关闭".into()),
+            Ok(read) => read,
+            Err(e) if is_timeout(&e) => continue,
+            Err(e) => return Err(e.into()),
+        };
+        if profile::is_enabled() {
+            profile::record(Stage::SocketRead, started.elapsed());
+        }
+        reader.push(&buffer[..read]);
+        while let Some(message) = reader.next_message()? {
+            let seq = message.get_u64(fix::MSG_SEQ_NUM).ok_or("缺少 MsgSeqNum")?;
+            // 序号重置消息本身的序号不做检查
+            if message.msg_type == fix::SEQUENCE_RESET {
+                expected_seq = message.get_u64(fix::NEW_SEQ_NO).ok_or("缺少 NewSeqNo")?;
+                continue;
+            }
+            if seq != expected_seq {
+                return Err(format!("会话序号不连续: 期望 {}, 收到 {}", expected_seq, seq).into());
+            }
+            expected_seq += 1;
+            match message.msg_type.as_str() {
+                fix::TEST_REQUEST => writer.send(&fix::heartbeat_reply(&message))?,
+                fix::LOGOUT => return Err(format!("对方登出: {}", message.get(fix::TEXT).unwrap_or_default()).into()),
+                fix::MARKET_DATA_REQUEST_REJECT => {
+                    warn!(venue = FIX_VENUE, md_req_id = message.get(fix::MD_REQ_ID).unwrap_or_default(),
+                          "行情请求被拒绝: {}", message.get(fix::TEXT).unwrap_or_default());
+                }
+                fix::MARKET_DATA_SNAPSHOT | fix::MARKET_DATA_INCREMENTAL => {
+                    let update = debug_span!("update", venue = FIX_VENUE, seq);
+                    let depths = debug_span!(parent: &update, "parse").in_scope(|| profile::time(Stage::JsonParse, || fix::depth_messages(&message)))?;
+                    for depth in depths {
+                        let span = update.clone();
+                        if events.send(FeedEvent::Depth { venue: FIX_VENUE, message: depth, span }).is_err() {
+                            return Ok(());
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
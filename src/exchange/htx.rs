@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::Read;
+use flate2::read::GzDecoder;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::exchange::{AdapterOutput, Continuity, DepthKind, DepthMessage, Exchange};
+
+/// HTX 消息（解压后）
+#[derive(Debug, Deserialize)]
+struct HtxMessage {
+    ping: Option<u64>,         // 服务端心跳，需回复相同时间戳的 pong
+    ch: Option<String>,        // 推送频道
+    rep: Option<String>,       // 请求响应频道
+    status: Option<String>,
+    #[serde(rename = "err-msg")]
+    err_msg: Option<String>,
+    ts: Option<u64>,
+    tick: Option<HtxBook>,     // 增量推送
+    data: Option<HtxBook>,     // 快照请求响应
+}
+
+/// 深度数据，档位为 [价格, 数量] 数字
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize)]
+struct HtxBook {
+    seqNum: u64,
+    prevSeqNum: Option<u64>,
+    #[serde(default)]
+    bids: Vec<(Decimal, Decimal)>,
+    #[serde(default)]
+    asks: Vec<(Decimal, Decimal)>,
+}
+
+/// HTX（火币）现货订单薄适配器，使用 `market.{symbol}.mbp.{levels}` 增量频道
+///
+/// 所有消息都是 gzip 压缩的二进制帧；订阅增量后通过 `req` 请求同频道的全量快照，
+/// 序号不大于快照的增量丢弃
+pub struct Htx {
+    levels: u32,
+    /// 交易对 -> 快照序号
+    snapshot_sequences: HashMap<String, u64>,
+}
+
+impl Htx {
+    /// 创建适配器
+    ///
+    /// # 参数
+    ///
+    /// * `levels` - 深度档位，5、20、150 或 400
+    pub fn new(levels: u32) -> Self {
+        Htx { levels, snapshot_sequences: HashMap::new() }
+    }
+
+    fn topic(&self, symbol: &str) -> String {
+        format!("market.{}.mbp.{}", symbol, self.levels)
+    }
+
+    /// 从频道名中取出交易对
+    fn topic_symbol(topic: &str) -> Option<&str> {
+        topic.strip_prefix("market.")?.split('.').next()
+    }
+}
+
+impl Exchange for Htx {
+    fn name(&self) -> &'static str {
+        "htx"
+    }
+
+    fn connect_url(&mut self) -> Result<String, Box<dyn Error>> {
+        self.snapshot_sequences.clear();
+        Ok("wss://api.huobi.pro/feed".to_string())
+    }
+
+    fn subscribe_messages(&self, symbols: &[String]) -> Vec<String> {
+        let mut messages = Vec::with_capacity(symbols.len() * 2);
+        for symbol in symbols {
+            messages.push(json!({ "sub": self.topic(symbol), "id": symbol }).to_string());
+        }
+        for symbol in symbols {
+            messages.extend(self.resync_messages(symbol));
+        }
+        messages
+    }
+
+    fn resync_messages(&self, symbol: &str) -> Vec<String> {
+        vec![json!({ "req": self.topic(symbol), "id": symbol }).to_string()]
+    }
+
+    fn parse_text(&mut self, text: &str) -> Result<Vec<AdapterOutput>, Box<dyn Error>> {
+        let message: HtxMessage = serde_json::from_str(text)?;
+        if let Some(ping) = message.ping {
+            return Ok(vec![AdapterOutput::Reply(json!({ "pong": ping }).to_string())]);
+        }
+        if message.status.as_deref() == Some("error") {
+            return Err(format!("HTX 错误: {}", message.err_msg.unwrap_or_default()).into());
+        }
+
+        // 快照请求响应
+        if let (Some(rep), Some(book)) = (message.rep, message.data) {
+            let symbol = Self::topic_symbol(&rep).ok_or("HTX 响应频道格式错误")?.to_string();
+            self.snapshot_sequences.insert(symbol.clone(), book.seqNum);
+            return Ok(vec![AdapterOutput::Depth(DepthMessage {
+                symbol,
+                kind: DepthKind::Snapshot,
+                bids: book.bids,
+                asks: book.asks,
+                continuity: Continuity::Prev { prev: book.seqNum, sequence: book.seqNum },
+                checksum: None,
+                max_depth: Some(self.levels as usize),
+                timestamp: message.ts.unwrap_or_default(),
+            })]);
+        }
+
+        let (Some(ch), Some(book)) = (message.ch, message.tick) else {
+            return Ok(Vec::new());
+        };
+        let symbol = Self::topic_symbol(&ch).ok_or("HTX 推送频道格式错误")?.to_string();
+        let Some(&snapshot_sequence) = self.snapshot_sequences.get(&symbol) else {
+            return Ok(Vec::new());
+        };
+        if book.seqNum <= snapshot_sequence {
+            return Ok(Vec::new());
+        }
+
+        Ok(vec![AdapterOutput::Depth(DepthMessage {
+            symbol,
+            kind: DepthKind::Delta,
+            bids: book.bids,
+            asks: book.asks,
+            continuity: Continuity::Prev { prev: book.prevSeqNum.unwrap_or_default(), sequence: book.seqNum },
+            checksum: None,
+            max_depth: Some(self.levels as usize),
+            timestamp: message.ts.unwrap_or_default(),
+        })])
+    }
+
+    fn parse_binary(&mut self, data: &[u8]) -> Result<Vec<AdapterOutput>, Box<dyn Error>> {
+        let mut text = String::new();
+        GzDecoder::new(data).read_to_string(&mut text)?;
+        self.parse_text(&text)
+    }
+}
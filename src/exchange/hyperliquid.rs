@@ -0,0 +1,98 @@
+use std::error::Error;
+use std::time::Duration;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::exchange::{AdapterOutput, Continuity, DepthKind, DepthMessage, Exchange};
+
+/// Hyperliquid WebSocket 消息
+#[derive(Debug, Deserialize)]
+struct HyperliquidMessage {
+    channel: String,
+    data: Option<serde_json::Value>,
+}
+
+/// l2Book 数据
+#[derive(Debug, Deserialize)]
+struct L2Book {
+    coin: String,              // 合约名称，例如 BTC
+    time: u64,                 // 数据产生时间（毫秒）
+    levels: [Vec<L2Level>; 2], // [买单, 卖单]
+}
+
+/// 档位
+#[derive(Debug, Deserialize)]
+struct L2Level {
+    px: Decimal,               // 价格
+    sz: Decimal,               // 数量
+}
+
+/// Hyperliquid 永续合约订单薄适配器，使用 `l2Book` 频道
+///
+/// 每次推送都是前20档的全量快照，直接替换本地订单薄
+pub struct Hyperliquid;
+
+impl Hyperliquid {
+    /// 创建适配器
+    pub fn new() -> Self {
+        Hyperliquid
+    }
+
+    fn subscription_message(method: &str, coin: &str) -> String {
+        json!({
+            "method": method,
+            "subscription": { "type": "l2Book", "coin": coin },
+        }).to_string()
+    }
+}
+
+impl Default for Hyperliquid {
+    fn default() -> Self {
+        Hyperliquid::new()
+    }
+}
+
+impl Exchange for Hyperliquid {
+    fn name(&self) -> &'static str {
+        "hyperliquid"
+    }
+
+    fn connect_url(&mut self) -> Result<String, Box<dyn Error>> {
+        Ok("wss://api.hyperliquid.xyz/ws".to_string())
+    }
+
+    fn subscribe_messages(&self, symbols: &[String]) -> Vec<String> {
+        // 每条订阅消息只能包含一个合约
+        symbols.iter()
+            .map(|coin| Self::subscription_message("subscribe", coin))
+            .collect()
+    }
+
+    fn heartbeat(&self) -> Option<(String, Duration)> {
+        // 60秒内没有消息服务端会断开连接
+        Some((json!({ "method": "ping" }).to_string(), Duration::from_secs(30)))
+    }
+
+    fn parse_text(&mut self, text: &str) -> Result<Vec<AdapterOutput>, Box<dyn Error>> {
+        let message: HyperliquidMessage = serde_json::from_str(text)?;
+        match message.channel.as_str() {
+            "l2Book" => {}
+            "error" => return Err(format!("Hyperliquid 错误: {}", message.data.unwrap_or_default()).into()),
+            _ => return Ok(Vec::new()),
+        }
+        let book: L2Book = serde_json::from_value(message.data.ok_or("Hyperliquid 消息缺少数据")?)?;
+        let [bids, asks] = book.levels;
+
+        Ok(vec![AdapterOutput::Depth(DepthMessage {
+            symbol: book.coin,
+            kind: DepthKind::Snapshot,
+            bids: bids.into_iter().map(|level| (level.px, level.sz)).collect(),
+            asks: asks.into_iter().map(|level| (level.px, level.sz)).collect(),
+            continuity: Continuity::Monotonic(book.time),
+            checksum: None,
+            max_depth: None,
+            timestamp: book.time,
+        })])
+    }
+}
@@ -0,0 +1,156 @@
+use std::error::Error;
+use serde_json::{json, Value};
+
+use crate::exchange::{parse_levels, AdapterOutput, Checksum, Continuity, DepthKind, DepthMessage, Exchange};
+use crate::order_book::OrderBook;
+
+/// 参与校验和计算的档位数
+const CHECKSUM_LEVELS: usize = 10;
+
+/// Kraken 订单薄适配器，使用 WebSocket v1 的 `book` 频道
+///
+/// 价格和数量以固定小数位字符串下发，校验和依赖原始字符串格式
+pub struct Kraken {
+    depth: usize,
+}
+
+impl Kraken {
+    /// 创建适配器
+    ///
+    /// # 参数
+    ///
+    /// * `depth` - 订阅深度，可选值：10, 25, 100, 500, 1000
+    pub fn new(depth: usize) -> Self {
+        Kraken { depth }
+    }
+
+    fn book_message(&self, event: &str, symbols: &[String]) -> String {
+        json!({
+            "event": event,
+            "pair": symbols,
+            "subscription": { "name": "book", "depth": self.depth },
+        }).to_string()
+    }
+}
+
+impl Exchange for Kraken {
+    fn name(&self) -> &'static str {
+        "kraken"
+    }
+
+    fn connect_url(&mut self) -> Result<String, Box<dyn Error>> {
+        Ok("wss://ws.kraken.com".to_string())
+    }
+
+    fn subscribe_messages(&self, symbols: &[String]) -> Vec<String> {
+        vec![self.book_message("subscribe", symbols)]
+    }
+
+    fn resync_messages(&self, symbol: &str) -> Vec<String> {
+        let symbols = [symbol.to_string()];
+        vec![
+            self.book_message("unsubscribe", &symbols),
+            self.book_message("subscribe", &symbols),
+        ]
+    }
+
+    fn parse_text(&mut self, text: &str) -> Result<Vec<AdapterOutput>, Box<dyn Error>> {
+        let message: Value = serde_json::from_str(text)?;
+
+        // 事件消息：心跳、系统状态、订阅状态
+        if let Some(event) = message.as_object() {
+            if event.get("status").and_then(Value::as_str) == Some("error") {
+                let error = event.get("errorMessage").and_then(Value::as_str).unwrap_or_default();
+                return Err(format!("Kraken 错误: {}", error).into());
+            }
+            return Ok(Vec::new());
+        }
+
+        // 深度消息：[channelID, {..}, ({..},) channelName, pair]
+        let Some(items) = message.as_array().filter(|items| items.len() >= 4) else {
+            return Ok(Vec::new());
+        };
+        let symbol = items[items.len() - 1].as_str().ok_or("Kraken 消息缺少交易对")?.to_string();
+        let payloads = &items[1..items.len() - 2];
+
+        let mut message = DepthMessage {
+            symbol,
+            kind: DepthKind::Delta,
+            bids: Vec::new(),
+            asks: Vec::new(),
+            continuity: Continuity::None,
+            checksum: None,
+            max_depth: Some(self.depth),
+            timestamp: 0,
+        };
+        for payload in payloads {
+            for (key, levels) in payload.as_object().into_iter().flatten() {
+                match key.as_str() {
+                    "as" | "bs" => message.kind = DepthKind::Snapshot,
+                    "c" => {
+                        let checksum = levels.as_str().ok_or("Kraken 校验和格式错误")?.parse::<u32>()?;
+                        message.checksum = Some(Checksum::Kraken(checksum));
+                        continue;
+                    }
+                    _ => {}
+                }
+                let levels: Vec<Vec<String>> = serde_json::from_value(levels.clone())?;
+                // 档位第三个字段为秒级时间戳
+                for level in &levels {
+                    if let Some(timestamp) = level.get(2).and_then(|ts| ts.parse::<f64>().ok()) {
+                        message.timestamp = message.timestamp.max((timestamp * 1000.0) as u64);
+                    }
+                }
+                let levels = parse_levels(&levels)?;
+                match key.as_str() {
+                    "as" | "a" => message.asks.extend(levels),
+                    "bs" | "b" => message.bids.extend(levels),
+                    _ => {}
+                }
+            }
+        }
+        Ok(vec![AdapterOutput::Depth(message)])
+    }
+}
+
+/// 计算 Kraken 订单薄校验和
+///
+/// 依次拼接前10档卖单（价格升序）和前10档买单（价格降序），
+/// 每档价格和数量去掉小数点及前导零后连接，对结果做 CRC32
+pub fn checksum(book: &OrderBook) -> u32 {
+    let mut payload = String::new();
+    let asks = book.asks.iter().take(CHECKSUM_LEVELS);
+    let bids = book.bids.iter().rev().take(CHECKSUM_LEVELS);
+    for (price, quantity) in asks.chain(bids) {
+        payload.push_str(&checksum_field(&price.to_string()));
+        payload.push_str(&checksum_field(&quantity.to_string()));
+    }
+    crc32fast::hash(payload.as_bytes())
+}
+
+fn checksum_field(value: &str) -> String {
+    value.replace('.', "").trim_start_matches('0').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    /// Kraken 文档中的示例订单薄，每档数量都是 0.00000500
+    #[test]
+    fn checksum_matches_documented_example() {
+        let asks: Vec<(Decimal, Decimal)> = (0..10)
+            .map(|i| (dec!(0.05005) + Decimal::new(5 * i, 5), dec!(0.00000500)))
+            .collect();
+        let bids: Vec<(Decimal, Decimal)> = [dec!(0.05000), dec!(0.04995), dec!(0.04990), dec!(0.04980), dec!(0.04975),
+                                             dec!(0.04970), dec!(0.04965), dec!(0.04960), dec!(0.04955), dec!(0.04950)]
+            .into_iter()
+            .map(|price| (price, dec!(0.00000500)))
+            .collect();
+        let book = OrderBook::from_levels(0, &bids, &asks);
+        assert_eq!(checksum(&book), 974947235);
+    }
+}
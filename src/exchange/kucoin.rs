@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::time::Duration;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::exchange::{parse_levels, AdapterOutput, Continuity, DepthKind, DepthMessage, Exchange};
+
+/// REST 接口地址
+const REST_URL: &str = "https://api.kucoin.com";
+
+/// KuCoin REST 响应外层结构
+#[derive(Debug, Deserialize)]
+struct KucoinResponse<T> {
+    code: String,
+    msg: Option<String>,
+    data: Option<T>,
+}
+
+/// bullet-public 返回的连接令牌
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize)]
+struct BulletToken {
+    token: String,
+    instanceServers: Vec<InstanceServer>,
+}
+
+/// WebSocket 服务器信息
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize)]
+struct InstanceServer {
+    endpoint: String,
+    pingInterval: u64,         // 心跳间隔（毫秒）
+}
+
+/// REST 深度快照
+#[derive(Debug, Deserialize)]
+struct KucoinSnapshot {
+    sequence: String,
+    time: u64,
+    bids: Vec<Vec<String>>,
+    asks: Vec<Vec<String>>,
+}
+
+/// WebSocket 消息
+#[derive(Debug, Deserialize)]
+struct KucoinMessage {
+    #[serde(rename = "type")]
+    kind: String,
+    subject: Option<String>,
+    data: Option<serde_json::Value>,
+}
+
+/// level2 增量数据
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize)]
+struct Level2Update {
+    symbol: String,
+    sequenceStart: u64,
+    sequenceEnd: u64,
+    time: Option<u64>,
+    changes: Level2Changes,
+}
+
+/// 增量变动 [价格, 数量, 序号]
+#[derive(Debug, Deserialize)]
+struct Level2Changes {
+    asks: Vec<Vec<String>>,
+    bids: Vec<Vec<String>>,
+}
+
+/// KuCoin 现货订单薄适配器
+///
+/// 连接前需先通过 `POST /api/v1/bullet-public` 申请令牌和服务器地址，
+/// 订阅 `/market/level2` 后用 REST 快照的序号对齐增量
+pub struct Kucoin {
+    ping_interval: Duration,
+    /// 交易对 -> 快照序号，序号不大于快照的变动会被丢弃
+    snapshot_sequences: HashMap<String, u64>,
+    client: reqwest::blocking::Client,
+}
+
+impl Kucoin {
+    /// 创建适配器
+    pub fn new() -> Self {
+        Kucoin {
+            ping_interval: Duration::from_secs(18),
+            snapshot_sequences: HashMap::new(),
+            client: reqwest::blocking::Client::new(),
+        }
+    }
+
+    /// 解析一侧的变动，丢弃快照之前的变动
+    fn parse_changes(levels: &[Vec<String>], snapshot_sequence: u64) -> Result<Vec<(Decimal, Decimal)>, Box<dyn Error>> {
+        let mut changes = Vec::with_capacity(levels.len());
+        for level in levels {
+            let sequence = level.get(2).ok_or("KuCoin 变动缺少序号")?.parse::<u64>()?;
+            if sequence > snapshot_sequence {
+                changes.push(level.clone());
+            }
+        }
+        parse_levels(&changes)
+    }
+}
+
+impl Default for Kucoin {
+    fn default() -> Self {
+        Kucoin::new()
+    }
+}
+
+impl Exchange for Kucoin {
+    fn name(&self) -> &'static str {
+        "kucoin"
+    }
+
+    fn connect_url(&mut self) -> Result<String, Box<dyn Error>> {
+        let response: KucoinResponse<BulletToken> = self.client
+            .post(format!("{}/api/v1/bullet-public", REST_URL))
+            .send()?
+            .json()?;
+        let bullet = response.data
+            .ok_or_else(|| format!("KuCoin 申请令牌失败: {} {}", response.code, response.msg.unwrap_or_default()))?;
+        let server = bullet.instanceServers.first().ok_or("KuCoin 没有可用的服务器")?;
+        self.ping_interval = Duration::from_millis(server.pingInterval);
+        self.snapshot_sequences.clear();
+
+        let connect_id = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_millis();
+        Ok(format!("{}?token={}&connectId={}", server.endpoint, bullet.token, connect_id))
+    }
+
+    fn subscribe_messages(&self, symbols: &[String]) -> Vec<String> {
+        // 单个主题最多 100 个交易对
+        symbols.chunks(100)
+            .enumerate()
+            .map(|(i, symbols)| json!({
+                "id": i + 1,
+                "type": "subscribe",
+                "topic": format!("/market/level2:{}", symbols.join(",")),
+                "response": true,
+            }).to_string())
+            .collect()
+    }
+
+    fn snapshot(&mut self, symbol: &str) -> Result<Option<DepthMessage>, Box<dyn Error>> {
+        // 完整深度接口需要鉴权，公开接口只提供前100档
+        let response: KucoinResponse<KucoinSnapshot> = self.client
+            .get(format!("{}/api/v1/market/orderbook/level2_100?symbol={}", REST_URL, symbol))
+            .send()?
+            .json()?;
+        let snapshot = response.data
+            .ok_or_else(|| format!("KuCoin 获取快照失败: {} {}", response.code, response.msg.unwrap_or_default()))?;
+        let sequence = snapshot.sequence.parse::<u64>()?;
+        self.snapshot_sequences.insert(symbol.to_string(), sequence);
+
+        Ok(Some(DepthMessage {
+            symbol: symbol.to_string(),
+            kind: DepthKind::Snapshot,
+            bids: parse_levels(&snapshot.bids)?,
+            asks: parse_levels(&snapshot.asks)?,
+            continuity: Continuity::Range { first: sequence, last: sequence },
+            checksum: None,
+            max_depth: None,
+            timestamp: snapshot.time,
+        }))
+    }
+
+    fn heartbeat(&self) -> Option<(String, Duration)> {
+        Some((json!({ "id": "ping", "type": "ping" }).to_string(), self.ping_interval))
+    }
+
+    fn parse_text(&mut self, text: &str) -> Result<Vec<AdapterOutput>, Box<dyn Error>> {
+        let message: KucoinMessage = serde_json::from_str(text)?;
+        if message.kind == "error" {
+            return Err(format!("KuCoin 错误: {}", text).into());
+        }
+        if message.kind != "message" || message.subject.as_deref() != Some("trade.l2update") {
+            return Ok(Vec::new());
+        }
+        let update: Level2Update = serde_json::from_value(message.data.ok_or("KuCoin 消息缺少数据")?)?;
+        let Some(&snapshot_sequence) = self.snapshot_sequences.get(&update.symbol) else {
+            return Ok(Vec::new());
+        };
+        if update.sequenceEnd <= snapshot_sequence {
+            return Ok(Vec::new());
+        }
+
+        Ok(vec![AdapterOutput::Depth(DepthMessage {
+            symbol: update.symbol,
+            kind: DepthKind::Delta,
+            bids: Self::parse_changes(&update.changes.bids, snapshot_sequence)?,
+            asks: Self::parse_changes(&update.changes.asks, snapshot_sequence)?,
+            continuity: Continuity::Range {
+                first: update.sequenceStart.max(snapshot_sequence + 1),
+                last: update.sequenceEnd,
+            },
+            checksum: None,
+            max_depth: None,
+            timestamp: update.time.unwrap_or_default(),
+        })])
+    }
+}
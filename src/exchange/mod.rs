@@ -0,0 +1,425 @@
+use std::error::Error;
+use std::net::TcpStream;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{connect, Message, Utf8Bytes, WebSocket};
+use tracing::{debug_span, info, warn, Span};
+
+use crate::latency::now_millis;
+use crate::metrics;
+use crate::order_book::OrderBook;
+use crate::profile::{self, Stage};
+use crate::replay::ReplayEvent;
+
+pub mod binance;
+pub mod okx;
+pub mod kraken;
+pub mod coinbase;
+pub mod bybit;
+pub mod bitstamp;
+pub mod kucoin;
+pub mod gateio;
+pub mod htx;
+pub mod deribit;
+pub mod bitget;
+pub mod hyperliquid;
+pub mod dydx;
+pub mod fix;
+
+/// 重连前的等待时间
+pub(crate) const RECONNECT_DELAY: Duration = Duration::from_secs(3);
+/// 读取超时，用于定期处理命令和心跳
+pub(crate) const READ_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// 深度消息类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DepthKind {
+    /// 全量快照，替换本地订单薄
+    Snapshot,
+    /// 增量更新
+    Delta,
+}
+
+/// 增量消息的连续性信息，不同交易所的序号语义不同
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Continuity {
+    /// 没有序号，依赖校验和发现错误
+    None,
+    /// 消息带有上一条消息的序号，需与本地序号相等
+    Prev { prev: u64, sequence: u64 },
+    /// 消息覆盖 [first, last] 区间，需满足 first <= 本地序号 + 1 <= last + 1
+    Range { first: u64, last: u64 },
+    /// 单调递增的时间戳，不大于本地值的消息丢弃，无法发现缺失
+    Monotonic(u64),
+}
+
+impl Continuity {
+    /// 消息处理后本地订单薄的序号
+    pub fn sequence(&self) -> Option<u64> {
+        match self {
+            Continuity::None => None,
+            Continuity::Prev { sequence, .. } => Some(*sequence),
+            Continuity::Range { last, .. } => Some(*last),
+            Continuity::Monotonic(timestamp) => Some(*timestamp),
+        }
+    }
+}
+
+/// 交易所下发的订单薄校验和
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Checksum {
+    /// OKX 前25档 CRC32
+    Okx(i32),
+    /// Kraken 前10档 CRC32
+    Kraken(u32),
+    /// Bitget 前25档 CRC32，算法与 OKX 相同
+    Bitget(i32),
+}
+
+impl Checksum {
+    /// 使用本地订单薄计算校验和并比较
+    pub fn verify(&self, book: &OrderBook) -> bool {
+        match self {
+            Checksum::Okx(expected) => okx::checksum(book) == *expected,
+            Checksum::Kraken(expected) => kraken::checksum(book) == *expected,
+            Checksum::Bitget(expected) => okx::checksum(book) == *expected,
+        }
+    }
+}
+
+/// 交易所适配器输出的统一深度消息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DepthMessage {
+    /// 交易所原生交易对名称，例如 BTC-USDT
+    pub symbol: String,
+    pub kind: DepthKind,
+    /// 买单变动 (价格, 数量)，数量为0表示删除
+    pub bids: Vec<(Decimal, Decimal)>,
+    /// 卖单变动 (价格, 数量)，数量为0表示删除
+    pub asks: Vec<(Decimal, Decimal)>,
+    pub continuity: Continuity,
+    pub checksum: Option<Checksum>,
+    /// 本地只保留的档位数，超出订阅深度的档位交易所不再维护
+    pub max_depth: Option<usize>,
+    /// 交易所事件时间（毫秒）
+    pub timestamp: u64,
+}
+
+impl DepthMessage {
+    /// 由本地订单薄生成完整快照，序号为订单薄当前序号
+    pub fn from_book(symbol: &str, book: &OrderBook, timestamp: u64) -> Self {
+        DepthMessage {
+            symbol: symbol.to_string(),
+            kind: DepthKind::Snapshot,
+            bids: book.bids_list(),
+            asks: book.asks_list(),
+            continuity: Continuity::Range { first: book.last_update_id, last: book.last_update_id },
+            checksum: None,
+            max_depth: None,
+            timestamp,
+        }
+    }
+}
+
+/// 适配器解析一帧消息的输出
+#[derive(Debug, Clone)]
+pub enum AdapterOutput {
+    /// 深度消息
+    Depth(DepthMessage),
+    /// 需要回复给交易所的消息（例如应用层 pong）
+    Reply(String),
+    /// 原样转发给主循环的文本消息，以 [`FeedEvent::Binance`] 交给订单薄管理器解析
+    Raw(String),
+}
+
+/// 交易所适配器
+///
+/// 负责连接地址、订阅消息、心跳以及把原始消息转换为统一的深度消息，
+/// 连接的读写和重连由 [`spawn_feed`] 统一处理
+pub trait Exchange: Send {
+    /// 交易所名称，例如 "okx"
+    fn name(&self) -> &'static str;
+
+    /// 返回 WebSocket 地址，每次（重新）连接前调用
+    fn connect_url(&mut self) -> Result<String, Box<dyn Error>>;
+
+    /// 订阅交易对的消息
+    fn subscribe_messages(&self, symbols: &[String]) -> Vec<String>;
+
+    /// 连续发送订阅消息的间隔，默认不等待
+    fn subscribe_interval(&self) -> Duration {
+        Duration::ZERO
+    }
+
+    /// 重新同步单个交易对的消息，默认先退订再订阅
+    fn resync_messages(&self, symbol: &str) -> Vec<String> {
+        self.subscribe_messages(&[symbol.to_string()])
+    }
+
+    /// 通过 REST 获取交易对快照，返回 None 表示快照由 WebSocket 推送
+    ///
+    /// 订阅完成后以及重新同步时调用，返回 Some 时不再发送重新同步消息
+    fn snapshot(&mut self, _symbol: &str) -> Result<Option<DepthMessage>, Box<dyn Error>> {
+        Ok(None)
+    }
+
+    /// 应用层心跳消息和发送间隔
+    fn heartbeat(&self) -> Option<(String, Duration)> {
+        None
+    }
+
+    /// 解析文本消息
+    fn parse_text(&mut self, text: &str) -> Result<Vec<AdapterOutput>, Box<dyn Error>>;
+
+    /// 解析二进制消息，默认忽略
+    fn parse_binary(&mut self, _data: &[u8]) -> Result<Vec<AdapterOutput>, Box<dyn Error>> {
+        Ok(Vec::new())
+    }
+}
+
+/// 行情线程发往主循环的事件
+#[derive(Debug)]
+pub enum FeedEvent {
+    /// 币安组合流原始文本消息，附带这条消息的 `update` span，主循环在其中解析和处理
+    Binance(String, Span),
+    /// 其他交易所的统一深度消息
+    Depth {
+        venue: &'static str,
+        message: DepthMessage,
+        /// 收到消息帧时创建的 `update` span，快照为 `snapshot` span
+        span: Span,
+    },
+    /// 其他交易所的原始消息帧，仅在开启转发时发送
+    Frame {
+        venue: &'static str,
+        frame: RawFrame,
+        /// 本地接收时间（毫秒）
+        local_time: u64,
+    },
+    /// 行情连接中断，连接上的订单薄在重连并重新同步之前不再可用
+    Disconnected {
+        venue: &'static str,
+        symbols: Vec<String>,
+    },
+    /// 回放记录
+    Replay(ReplayEvent),
+}
+
+/// WebSocket 原始消息帧
+#[derive(Debug, Clone)]
+pub enum RawFrame {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+/// 主循环发往行情线程的命令
+#[derive(Debug, Clone)]
+pub enum FeedCommand {
+    /// 重新同步交易对（连续性或校验和失败后）
+    Resync(String),
+}
+
+/// 在新线程中运行交易所行情连接，断线自动重连
+///
+/// 连接中断时向主循环发送 [`FeedEvent::Disconnected`]
+///
+/// # 返回值
+///
+/// 返回命令发送端，用于请求重新同步
+///
+/// `forward_frames` 为 true 时额外把收到的原始消息帧以 [`FeedEvent::Frame`] 转发到主循环
+pub fn spawn_feed(mut exchange: Box<dyn Exchange>, symbols: Vec<String>, events: Sender<FeedEvent>, forward_frames: bool) -> Sender<FeedCommand> {
+    let (command_tx, command_rx) = mpsc::channel();
+    thread::spawn(move || {
+        loop {
+            match run_feed(exchange.as_mut(), &symbols, &events, &command_rx, forward_frames) {
+                Ok(()) => return,
+                Err(e) => {
+                    warn!(venue = exchange.name(), error = %e, "行情连接中断，{}秒后重连", RECONNECT_DELAY.as_secs());
+                    if events.send(FeedEvent::Disconnected { venue: exchange.name(), symbols: symbols.clone() }).is_err() {
+                        return;
+                    }
+                    thread::sleep(RECONNECT_DELAY);
+                    metrics::reconnect(exchange.name());
+                }
+            }
+        }
+    });
+    command_tx
+}
+
+/// 运行一次连接，主循环退出时返回 Ok
+fn run_feed(
+    exchange: &mut dyn Exchange,
+    symbols: &[String],
+    events: &Sender<FeedEvent>,
+    commands: &Receiver<FeedCommand>,
+    forward_frames: bool,
+) -> Result<(), Box<dyn Error>> {
+    let url = exchange.connect_url()?;
+    info!(venue = exchange.name(), url, "正在连接");
+    let (mut socket, _) = connect(url.as_str())?;
+    set_read_timeout(&socket, READ_TIMEOUT)?;
+
+    for (i, subscribe) in exchange.subscribe_messages(symbols).into_iter().enumerate() {
+        if i > 0 {
+            thread::sleep(exchange.subscribe_interval());
+        }
+        socket.send(Message::Text(Utf8Bytes::from(subscribe)))?;
+    }
+    // 先订阅再取快照，快照之前的增量由序号过滤
+    for symbol in symbols {
+        let span = debug_span!("snapshot", venue = exchange.name(), symbol);
+        if let Some(message) = span.in_scope(|| exchange.snapshot(symbol))?
+            && events.send(FeedEvent::Depth { venue: exchange.name(), message, span }).is_err()
+        {
+            return Ok(());
+        }
+    }
+
+    let heartbeat = exchange.heartbeat();
+    let mut last_heartbeat = Instant::now();
+    loop {
+        match commands.try_recv() {
+            Ok(FeedCommand::Resync(symbol)) => {
+                info!(venue = exchange.name(), symbol, "重新同步");
+                // 获取快照失败时重连，重连后所有交易对重新同步
+                let span = debug_span!("snapshot", venue = exchange.name(), symbol);
+                match span.in_scope(|| exchange.snapshot(&symbol))? {
+                    Some(message) => {
+                        if events.send(FeedEvent::Depth { venue: exchange.name(), message, span }).is_err() {
+                            return Ok(());
+                        }
+                    }
+                    None => {
+                        for msg in exchange.resync_messages(&symbol) {
+                            socket.send(Message::Text(Utf8Bytes::from(msg)))?;
+                        }
+                    }
+                }
+            }
+            Err(TryRecvError::Empty) => {}
+            Err(TryRecvError::Disconnected) => return Ok(()),
+        }
+
+        if let Some((ref ping, interval)) = heartbeat
+            && last_heartbeat.elapsed() >= interval
+        {
+            socket.send(Message::Text(Utf8Bytes::from(ping.clone())))?;
+            last_heartbeat = Instant::now();
+        }
+
+        let started = Instant::now();
+        let message = match socket.read() {
+            Ok(message) => message,
+            Err(tungstenite::Error::Io(e)) if is_timeout(&e) => continue,
+            Err(e) => return Err(e.into()),
+        };
+        if profile::is_enabled() {
+            profile::record(Stage::SocketRead, started.elapsed());
+        }
+        let update = debug_span!("update", venue = exchange.name());
+        // 收到消息帧的时刻，开启转发时包括把原始消息帧交给主循环
+        let receive = debug_span!(parent: &update, "receive").entered();
+        if forward_frames {
+            let frame = match &message {
+                Message::Text(text) => Some(RawFrame::Text(text.to_string())),
+                Message::Binary(data) => Some(RawFrame::Binary(data.to_vec())),
+                _ => None,
+            };
+            if let Some(frame) = frame
+                && events.send(FeedEvent::Frame { venue: exchange.name(), frame, local_time: now_millis() }).is_err()
+            {
+                return Ok(());
+            }
+        }
+        drop(receive);
+
+        let outputs = {
+            let _parse = debug_span!(parent: &update, "parse").entered();
+            match message {
+                Message::Text(text) => profile::time(Stage::JsonParse, || exchange.parse_text(&text)),
+                Message::Binary(data) => profile::time(Stage::JsonParse, || exchange.parse_binary(&data)),
+                Message::Close(_) => return Err("连接已关闭".into()),
+                _ => continue,
+            }
+        };
+
+        match outputs {
+            Ok(outputs) => {
+                for output in outputs {
+                    match output {
+                        AdapterOutput::Depth(message) => {
+                            let span = update.clone();
+                            if events.send(FeedEvent::Depth { venue: exchange.name(), message, span }).is_err() {
+                                return Ok(());
+                            }
+                        }
+                        AdapterOutput::Reply(reply) => {
+                            socket.send(Message::Text(Utf8Bytes::from(reply)))?;
+                        }
+                        AdapterOutput::Raw(text) => {
+                            if events.send(FeedEvent::Binance(text, update.clone())).is_err() {
+                                return Ok(());
+                            }
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                warn!(venue = exchange.name(), error = %e, "解析消息失败");
+            }
+        }
+    }
+}
+
+/// 设置底层连接的读取超时
+pub(crate) fn set_read_timeout(socket: &WebSocket<MaybeTlsStream<TcpStream>>, timeout: Duration) -> Result<(), Box<dyn Error>> {
+    match socket.get_ref() {
+        MaybeTlsStream::Plain(stream) => stream.set_read_timeout(Some(timeout))?,
+        MaybeTlsStream::NativeTls(stream) => stream.get_ref().set_read_timeout(Some(timeout))?,
+        _ => {}
+    }
+    Ok(())
+}
+
+pub(crate) fn is_timeout(e: &std::io::Error) -> bool {
+    matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut)
+}
+
+/// 解析 [价格, 数量, ...] 字符串数组形式的档位
+pub(crate) fn parse_levels(levels: &[Vec<String>]) -> Result<Vec<(Decimal, Decimal)>, Box<dyn Error>> {
+    levels.iter()
+        .map(|level| {
+            let price = level.first().ok_or("档位缺少价格")?.parse::<Decimal>()?;
+            let quantity = level.get(1).ok_or("档位缺少数量")?.parse::<Decimal>()?;
+            Ok((price, quantity))
+        })
+        .collect()
+}
+
+/// 解析 RFC3339 UTC 时间（例如 `2019-08-14T20:42:27.265Z`）为毫秒时间戳，可以省略 `Z`，毫秒以下的小数截断
+pub(crate) fn parse_rfc3339_millis(time: &str) -> Option<u64> {
+    let (date, clock) = time.trim_end_matches('Z').split_once('T')?;
+    let mut date = date.split('-').map(|part| part.parse::<i64>());
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+    let (clock, fraction) = clock.split_once('.').unwrap_or((clock, "0"));
+    let mut clock = clock.split(':').map(|part| part.parse::<i64>());
+    let (hour, minute, second) = (clock.next()?.ok()?, clock.next()?.ok()?, clock.next()?.ok()?);
+    let millis = format!("{:0<3}", fraction).get(..3)?.parse::<i64>().ok()?;
+
+    // 公历日期转为 1970-01-01 起的天数
+    let (y, m) = if month <= 2 { (year - 1, month + 9) } else { (year, month - 3) };
+    let era = y.div_euclid(400);
+    let year_of_era = y - era * 400;
+    let day_of_year = (153 * m + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    let seconds = days * 86_400 + hour * 3_600 + minute * 60 + second;
+    u64::try_from(seconds * 1_000 + millis).ok()
+}
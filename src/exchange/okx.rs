@@ -0,0 +1,164 @@
+use std::error::Error;
+use std::time::Duration;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::exchange::{parse_levels, AdapterOutput, Checksum, Continuity, DepthKind, DepthMessage, Exchange};
+use crate::order_book::OrderBook;
+
+/// 参与校验和计算的档位数
+const CHECKSUM_LEVELS: usize = 25;
+
+/// OKX 深度消息
+#[derive(Debug, Deserialize)]
+struct OkxMessage {
+    event: Option<String>,
+    code: Option<String>,
+    msg: Option<String>,
+    arg: Option<OkxArg>,
+    action: Option<String>,
+    data: Option<Vec<OkxBookData>>,
+}
+
+/// 订阅频道参数
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize)]
+struct OkxArg {
+    instId: String,            // 产品ID，例如 BTC-USDT
+}
+
+/// 深度数据
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize)]
+struct OkxBookData {
+    asks: Vec<Vec<String>>,    // 卖单 [价格, 数量, 已弃用, 订单数]
+    bids: Vec<Vec<String>>,    // 买单 [价格, 数量, 已弃用, 订单数]
+    ts: String,                // 数据产生时间
+    checksum: Option<i32>,     // 前25档校验和
+    prevSeqId: Option<i64>,    // 上一条推送的序号，快照为 -1
+    seqId: Option<i64>,        // 推送序号
+}
+
+/// OKX 订单薄适配器，支持 books / books-l2-tbt / books50-l2-tbt 频道
+pub struct Okx {
+    channel: String,
+}
+
+impl Okx {
+    /// 创建适配器
+    ///
+    /// # 参数
+    ///
+    /// * `channel` - 深度频道，例如 "books" 或 "books-l2-tbt"
+    pub fn new(channel: &str) -> Self {
+        Okx { channel: channel.to_string() }
+    }
+
+    fn channel_message(&self, op: &str, symbols: &[String]) -> String {
+        let args: Vec<serde_json::Value> = symbols.iter()
+            .map(|symbol| json!({ "channel": self.channel, "instId": symbol }))
+            .collect();
+        json!({ "op": op, "args": args }).to_string()
+    }
+}
+
+impl Exchange for Okx {
+    fn name(&self) -> &'static str {
+        "okx"
+    }
+
+    fn connect_url(&mut self) -> Result<String, Box<dyn Error>> {
+        Ok("wss://ws.okx.com:8443/ws/v5/public".to_string())
+    }
+
+    fn subscribe_messages(&self, symbols: &[String]) -> Vec<String> {
+        vec![self.channel_message("subscribe", symbols)]
+    }
+
+    fn resync_messages(&self, symbol: &str) -> Vec<String> {
+        // 重新订阅后服务端会重新推送全量快照
+        let symbols = [symbol.to_string()];
+        vec![
+            self.channel_message("unsubscribe", &symbols),
+            self.channel_message("subscribe", &symbols),
+        ]
+    }
+
+    fn heartbeat(&self) -> Option<(String, Duration)> {
+        // 30秒内没有数据服务端会断开连接
+        Some(("ping".to_string(), Duration::from_secs(25)))
+    }
+
+    fn parse_text(&mut self, text: &str) -> Result<Vec<AdapterOutput>, Box<dyn Error>> {
+        if text == "pong" {
+            return Ok(Vec::new());
+        }
+        let message: OkxMessage = serde_json::from_str(text)?;
+        if message.event.as_deref() == Some("error") {
+            return Err(format!("OKX 错误: {} {}", message.code.unwrap_or_default(), message.msg.unwrap_or_default()).into());
+        }
+        let (Some(arg), Some(action), Some(data)) = (message.arg, message.action, message.data) else {
+            return Ok(Vec::new());
+        };
+
+        let kind = if action == "snapshot" { DepthKind::Snapshot } else { DepthKind::Delta };
+        let mut outputs = Vec::with_capacity(data.len());
+        for book in data {
+            let sequence = book.seqId.unwrap_or_default();
+            let continuity = match kind {
+                DepthKind::Snapshot => Continuity::Prev { prev: sequence as u64, sequence: sequence as u64 },
+                DepthKind::Delta => Continuity::Prev {
+                    prev: book.prevSeqId.unwrap_or_default() as u64,
+                    sequence: sequence as u64,
+                },
+            };
+            outputs.push(AdapterOutput::Depth(DepthMessage {
+                symbol: arg.instId.clone(),
+                kind,
+                bids: parse_levels(&book.bids)?,
+                asks: parse_levels(&book.asks)?,
+                continuity,
+                checksum: book.checksum.map(Checksum::Okx),
+                max_depth: None,
+                timestamp: book.ts.parse::<u64>()?,
+            }));
+        }
+        Ok(outputs)
+    }
+}
+
+/// 计算 OKX 订单薄校验和
+///
+/// 买卖盘前25档交替拼接为 `买价:买量:卖价:卖量:...`，一侧不足时只拼接另一侧，
+/// 对字符串做 CRC32 并按有符号整数比较
+pub fn checksum(book: &OrderBook) -> i32 {
+    let bids: Vec<_> = book.bids.iter().rev().take(CHECKSUM_LEVELS).collect();
+    let asks: Vec<_> = book.asks.iter().take(CHECKSUM_LEVELS).collect();
+
+    let mut parts = Vec::with_capacity(CHECKSUM_LEVELS * 4);
+    for i in 0..CHECKSUM_LEVELS {
+        if let Some((price, quantity)) = bids.get(i) {
+            parts.push(price.to_string());
+            parts.push(quantity.to_string());
+        }
+        if let Some((price, quantity)) = asks.get(i) {
+            parts.push(price.to_string());
+            parts.push(quantity.to_string());
+        }
+    }
+    crc32fast::hash(parts.join(":").as_bytes()) as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    /// OKX 文档中的示例：`3366.1:7:3366.8:9:3366:6:3368:8`
+    #[test]
+    fn checksum_matches_documented_example() {
+        let book = OrderBook::from_levels(0, &[(dec!(3366.1), dec!(7)), (dec!(3366), dec!(6))], &[(dec!(3366.8), dec!(9)), (dec!(3368), dec!(8))]);
+        assert_eq!(checksum(&book), -1881014294);
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+use std::error::Error;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+/// 一档手续费等级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct FeeTier {
+    /// 达到该等级需要的30日成交量（计价资产）
+    #[serde(default)]
+    pub min_volume: Decimal,
+    /// 挂单手续费（基点），返佣为负数
+    pub maker_bps: Decimal,
+    /// 吃单手续费（基点）
+    pub taker_bps: Decimal,
+}
+
+/// 单个交易所的手续费配置
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct VenueFees {
+    /// 手续费等级，按成交量下限升序
+    #[serde(default)]
+    pub tiers: Vec<FeeTier>,
+    /// 当前30日成交量，用于确定等级
+    #[serde(default)]
+    pub volume: Decimal,
+}
+
+impl VenueFees {
+    /// 当前成交量对应的等级
+    pub fn current_tier(&self) -> Option<&FeeTier> {
+        self.tiers.iter()
+            .filter(|tier| tier.min_volume <= self.volume)
+            .max_by_key(|tier| tier.min_volume)
+    }
+}
+
+/// 各交易所手续费表，供套利检测、拆单模拟、滑点估算和模拟交易使用
+///
+/// 配置文件为 JSON，例如：
+///
+/// ```json
+/// {
+///   "default": { "maker_bps": 10, "taker_bps": 10 },
+///   "venues": {
+///     "binance": { "volume": 2000000, "tiers": [
+///       { "min_volume": 0, "maker_bps": 10, "taker_bps": 10 },
+///       { "min_volume": 1000000, "maker_bps": 9, "taker_bps": 10 }
+///     ] },
+///     "okx": { "tiers": [{ "maker_bps": 8, "taker_bps": 10 }] }
+///   }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct FeeSchedule {
+    /// 未配置交易所使用的手续费
+    #[serde(default = "default_tier")]
+    pub default: FeeTier,
+    /// 交易所 -> 手续费配置
+    #[serde(default)]
+    pub venues: HashMap<String, VenueFees>,
+}
+
+impl Default for FeeSchedule {
+    fn default() -> Self {
+        FeeSchedule {
+            default: default_tier(),
+            venues: HashMap::new(),
+        }
+    }
+}
+
+impl FeeSchedule {
+    /// 创建手续费表，未配置的交易所挂单和吃单均为10个基点
+    pub fn new() -> Self {
+        FeeSchedule::default()
+    }
+
+    /// 解析 JSON 配置
+    pub fn parse(config: &str) -> Result<Self, Box<dyn Error>> {
+        let mut schedule: FeeSchedule = serde_json::from_str(config)?;
+        for fees in schedule.venues.values_mut() {
+            fees.tiers.sort_by_key(|tier| tier.min_volume);
+        }
+        Ok(schedule)
+    }
+
+    /// 从 JSON 配置文件加载
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let config = std::fs::read_to_string(path)
+            .map_err(|e| format!("读取手续费配置 {} 失败: {}", path, e))?;
+        FeeSchedule::parse(&config)
+    }
+
+    /// 设置交易所的单一等级手续费（基点）
+    pub fn set_venue_fees(&mut self, venue: &str, maker_bps: Decimal, taker_bps: Decimal) {
+        let fees = self.venues.entry(venue.to_string()).or_default();
+        fees.tiers = vec![FeeTier { min_volume: Decimal::ZERO, maker_bps, taker_bps }];
+    }
+
+    /// 设置交易所当前30日成交量
+    pub fn set_volume(&mut self, venue: &str, volume: Decimal) {
+        self.venues.entry(venue.to_string()).or_default().volume = volume;
+    }
+
+    /// 交易所当前等级的手续费
+    pub fn tier(&self, venue: &str) -> FeeTier {
+        self.venues.get(venue)
+            .and_then(|fees| fees.current_tier())
+            .copied()
+            .unwrap_or(self.default)
+    }
+
+    /// 挂单手续费（基点）
+    pub fn maker_bps(&self, venue: &str) -> Decimal {
+        self.tier(venue).maker_bps
+    }
+
+    /// 吃单手续费（基点）
+    pub fn taker_bps(&self, venue: &str) -> Decimal {
+        self.tier(venue).taker_bps
+    }
+
+    /// 挂单手续费率，例如 10 个基点为 0.001
+    pub fn maker_rate(&self, venue: &str) -> Decimal {
+        self.maker_bps(venue) / Decimal::from(10_000)
+    }
+
+    /// 吃单手续费率，例如 10 个基点为 0.001
+    pub fn taker_rate(&self, venue: &str) -> Decimal {
+        self.taker_bps(venue) / Decimal::from(10_000)
+    }
+}
+
+fn default_tier() -> FeeTier {
+    FeeTier {
+        min_volume: Decimal::ZERO,
+        maker_bps: Decimal::from(10),
+        taker_bps: Decimal::from(10),
+    }
+}
@@ -0,0 +1,514 @@
+//! FIX 4.4 行情消息的编解码
+//!
+//! 只实现行情会话需要的部分：会话层的登录、心跳、测试请求、重发请求和登出，应用层的
+//! MarketDataRequest (V)、MarketDataSnapshotFullRefresh (W)、MarketDataIncrementalRefresh (X)
+//! 和 MarketDataRequestReject (Y)。服务端见 [`spawn_fix_server`](crate::serve::fix::spawn_fix_server)，
+//! 客户端见 [`spawn_fix_feed`](crate::exchange::fix::spawn_fix_feed)
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+use rust_decimal::Decimal;
+
+use crate::exchange::{parse_rfc3339_millis, Continuity, DepthKind, DepthMessage};
+use crate::latency::now_millis;
+use crate::order_book::Side;
+use crate::recorder::{utc_hour_label, HOUR_MS};
+use crate::serve::BookView;
+
+/// 字段分隔符
+pub const SOH: u8 = 0x01;
+const BEGIN_STRING: &str = "FIX.4.4";
+/// BodyLength 的上限，超过时视为无效消息，不按对方声明的长度继续缓存
+const MAX_BODY_LENGTH: usize = 64 * 1024;
+/// BeginString 和 BodyLength 字段的最大长度，超过仍没有分隔符时视为无效消息
+const MAX_HEADER_FIELD: usize = 32;
+
+pub const MSG_SEQ_NUM: u32 = 34;
+pub const MSG_TYPE: u32 = 35;
+pub const NEW_SEQ_NO: u32 = 36;
+pub const SENDER_COMP_ID: u32 = 49;
+pub const SENDING_TIME: u32 = 52;
+pub const SYMBOL: u32 = 55;
+pub const TARGET_COMP_ID: u32 = 56;
+pub const TEXT: u32 = 58;
+pub const ENCRYPT_METHOD: u32 = 98;
+pub const HEART_BT_INT: u32 = 108;
+pub const TEST_REQ_ID: u32 = 112;
+pub const GAP_FILL_FLAG: u32 = 123;
+pub const RESET_SEQ_NUM_FLAG: u32 = 141;
+pub const NO_RELATED_SYM: u32 = 146;
+pub const SECURITY_EXCHANGE: u32 = 207;
+pub const MD_REQ_ID: u32 = 262;
+pub const SUBSCRIPTION_REQUEST_TYPE: u32 = 263;
+pub const MARKET_DEPTH: u32 = 264;
+pub const MD_UPDATE_TYPE: u32 = 265;
+pub const NO_MD_ENTRY_TYPES: u32 = 267;
+pub const NO_MD_ENTRIES: u32 = 268;
+pub const MD_ENTRY_TYPE: u32 = 269;
+pub const MD_ENTRY_PX: u32 = 270;
+pub const MD_ENTRY_SIZE: u32 = 271;
+pub const MD_UPDATE_ACTION: u32 = 279;
+pub const MD_REQ_REJ_REASON: u32 = 281;
+
+/// 消息类型
+pub const HEARTBEAT: &str = "0";
+pub const TEST_REQUEST: &str = "1";
+pub const RESEND_REQUEST: &str = "2";
+pub const SEQUENCE_RESET: &str = "4";
+pub const LOGOUT: &str = "5";
+pub const LOGON: &str = "A";
+pub const MARKET_DATA_REQUEST: &str = "V";
+pub const MARKET_DATA_SNAPSHOT: &str = "W";
+pub const MARKET_DATA_INCREMENTAL: &str = "X";
+pub const MARKET_DATA_REQUEST_REJECT: &str = "Y";
+
+/// SubscriptionRequestType
+pub const SNAPSHOT: &str = "0";
+pub const SNAPSHOT_AND_UPDATES: &str = "1";
+pub const UNSUBSCRIBE: &str = "2";
+
+/// MDEntryType
+const BID: &str = "0";
+const OFFER: &str = "1";
+
+/// 一条 FIX 消息，不含 BeginString、BodyLength、MsgType 和 CheckSum
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixMessage {
+    pub msg_type: String,
+    /// 按出现顺序的字段，重复组的字段依次排列
+    pub fields: Vec<(u32, String)>,
+}
+
+impl FixMessage {
+    pub fn new(msg_type: &str) -> Self {
+        FixMessage { msg_type: msg_type.to_string(), fields: Vec::new() }
+    }
+
+    /// 追加字段
+    pub fn push(&mut self, tag: u32, value: impl ToString) -> &mut Self {
+        self.fields.push((tag, value.to_string()));
+        self
+    }
+
+    /// 第一个该编号字段的值
+    pub fn get(&self, tag: u32) -> Option<&str> {
+        self.fields.iter().find(|(t, _)| *t == tag).map(|(_, value)| value.as_str())
+    }
+
+    pub fn get_u64(&self, tag: u32) -> Option<u64> {
+        self.get(tag).and_then(|value| value.parse().ok())
+    }
+
+    /// 按重复组的第一个字段切分，返回每个组的字段
+    pub fn groups(&self, first: u32) -> Vec<&[(u32, String)]> {
+        let starts: Vec<usize> = self.fields.iter().enumerate()
+            .filter(|(_, (tag, _))| *tag == first)
+            .map(|(i, _)| i)
+            .collect();
+        starts.iter().enumerate()
+            .map(|(i, start)| &self.fields[*start..starts.get(i + 1).copied().unwrap_or(self.fields.len())])
+            .collect()
+    }
+
+    /// 编码为完整的消息，加上标准消息头和校验和
+    pub fn encode(&self, sender: &str, target: &str, seq: u64, sending_time: u64) -> Vec<u8> {
+        let mut body = Vec::new();
+        let mut field = |tag: u32, value: &str| {
+            body.extend_from_slice(format!("{}={}", tag, value).as_bytes());
+            body.push(SOH);
+        };
+        field(MSG_TYPE, &self.msg_type);
+        field(SENDER_COMP_ID, sender);
+        field(TARGET_COMP_ID, target);
+        field(MSG_SEQ_NUM, &seq.to_string());
+        field(SENDING_TIME, &fix_timestamp(sending_time));
+        for (tag, value) in &self.fields {
+            field(*tag, value);
+        }
+        let mut message = format!("8={}\x019={}\x01", BEGIN_STRING, body.len()).into_bytes();
+        message.extend_from_slice(&body);
+        let checksum = checksum(&message);
+        message.extend_from_slice(format!("10={:03}\x01", checksum).as_bytes());
+        message
+    }
+
+    /// 从缓冲区开头解码一条消息，数据不完整时返回 None，否则返回消息和消耗的字节数
+    pub fn decode(buffer: &[u8]) -> Result<Option<(FixMessage, usize)>, Box<dyn Error>> {
+        // 消息头 8=FIX.4.4<SOH>9=长度<SOH>
+        let Some(first) = buffer.iter().position(|b| *b == SOH) else {
+            return header_pending(buffer);
+        };
+        if buffer[..first] != *format!("8={}", BEGIN_STRING).as_bytes() {
+            return Err(format!("无效的 BeginString: {}", String::from_utf8_lossy(&buffer[..first])).into());
+        }
+        let Some(second) = buffer[first + 1..].iter().position(|b| *b == SOH).map(|i| first + 1 + i) else {
+            return header_pending(&buffer[first + 1..]);
+        };
+        let length = std::str::from_utf8(&buffer[first + 1..second])?
+            .strip_prefix("9=")
+            .ok_or("缺少 BodyLength")?
+            .parse::<usize>()?;
+        if length > MAX_BODY_LENGTH {
+            return Err(format!("BodyLength 超过上限: {} > {}", length, MAX_BODY_LENGTH).into());
+        }
+        // 校验和字段固定为 10=三位数字<SOH>
+        let body_end = (second + 1).checked_add(length).ok_or("BodyLength 溢出")?;
+        let end = body_end.checked_add(7).ok_or("BodyLength 溢出")?;
+        if buffer.len() < end {
+            return Ok(None);
+        }
+        let trailer = std::str::from_utf8(&buffer[body_end..end])?;
+        let expected = trailer.strip_prefix("10=")
+            .and_then(|trailer| trailer.strip_suffix('\x01'))
+            .ok_or("缺少 CheckSum")?
+            .parse::<u8>()?;
+        let actual = checksum(&buffer[..body_end]);
+        if actual != expected {
+            return Err(format!("校验和不一致: 消息 {}, 计算 {}", expected, actual).into());
+        }
+        let mut message = FixMessage::new("");
+        for field in std::str::from_utf8(&buffer[second + 1..body_end])?.split('\x01').filter(|field| !field.is_empty()) {
+            let (tag, value) = field.split_once('=').ok_or_else(|| format!("无效的字段: {}", field))?;
+            let tag = tag.parse::<u32>()?;
+            if tag == MSG_TYPE {
+                message.msg_type = value.to_string();
+            } else {
+                message.fields.push((tag, value.to_string()));
+            }
+        }
+        if message.msg_type.is_empty() {
+            return Err("缺少 MsgType".into());
+        }
+        Ok(Some((message, end)))
+    }
+}
+
+/// 消息头字段还没有分隔符：长度未超过上限时等待更多数据
+fn header_pending(field: &[u8]) -> Result<Option<(FixMessage, usize)>, Box<dyn Error>> {
+    if field.len() > MAX_HEADER_FIELD {
+        return Err(format!("消息头字段超过 {} 字节仍没有分隔符", MAX_HEADER_FIELD).into());
+    }
+    Ok(None)
+}
+
+/// 从消息开头到校验和字段之前所有字节的和
+fn checksum(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0u8, |sum, b| sum.wrapping_add(*b))
+}
+
+/// UTCTimestamp 格式 `YYYYMMDD-HH:MM:SS.sss`
+pub fn fix_timestamp(millis: u64) -> String {
+    let within_hour = millis % HOUR_MS;
+    format!("{}:{:02}:{:02}.{:03}", utc_hour_label(millis), within_hour / 60_000, within_hour / 1_000 % 60, millis % 1_000)
+}
+
+/// 解析 UTCTimestamp，毫秒部分可省略
+pub fn parse_fix_timestamp(time: &str) -> Option<u64> {
+    let (date, clock) = time.split_once('-')?;
+    let (year, month, day) = (date.get(..4)?, date.get(4..6)?, date.get(6..8)?);
+    parse_rfc3339_millis(&format!("{}-{}-{}T{}Z", year, month, day, clock))
+}
+
+/// 把字节流切分为消息
+#[derive(Debug, Default)]
+pub struct FixReader {
+    buffer: Vec<u8>,
+}
+
+impl FixReader {
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// 取出下一条完整的消息
+    pub fn next_message(&mut self) -> Result<Option<FixMessage>, Box<dyn Error>> {
+        match FixMessage::decode(&self.buffer)? {
+            Some((message, consumed)) => {
+                self.buffer.drain(..consumed);
+                Ok(Some(message))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// 会话的发送端，填写消息头并维护发送序号
+#[derive(Debug)]
+pub struct FixWriter<W: Write> {
+    writer: W,
+    sender: String,
+    target: String,
+    next_seq: u64,
+    last_sent: Instant,
+}
+
+impl<W: Write> FixWriter<W> {
+    pub fn new(writer: W, sender: &str, target: &str) -> Self {
+        FixWriter {
+            writer,
+            sender: sender.to_string(),
+            target: target.to_string(),
+            next_seq: 1,
+            last_sent: Instant::now(),
+        }
+    }
+
+    /// 对方的 CompID，服务端在收到登录消息后设置
+    pub fn set_target(&mut self, target: &str) {
+        self.target = target.to_string();
+    }
+
+    pub fn next_seq(&self) -> u64 {
+        self.next_seq
+    }
+
+    pub fn send(&mut self, message: &FixMessage) -> io::Result<()> {
+        self.writer.write_all(&message.encode(&self.sender, &self.target, self.next_seq, now_millis()))?;
+        self.writer.flush()?;
+        self.next_seq += 1;
+        self.last_sent = Instant::now();
+        Ok(())
+    }
+
+    /// 超过心跳间隔没有发送消息时发送心跳
+    pub fn heartbeat_if_idle(&mut self, interval: Duration) -> io::Result<()> {
+        if self.last_sent.elapsed() >= interval {
+            self.send(&FixMessage::new(HEARTBEAT))?;
+        }
+        Ok(())
+    }
+}
+
+/// 登录消息，要求对方重置序号
+pub fn logon(heartbeat: Duration) -> FixMessage {
+    let mut message = FixMessage::new(LOGON);
+    message.push(ENCRYPT_METHOD, 0)
+        .push(HEART_BT_INT, heartbeat.as_secs())
+        .push(RESET_SEQ_NUM_FLAG, "Y");
+    message
+}
+
+/// 回复测试请求的心跳
+pub fn heartbeat_reply(test_request: &FixMessage) -> FixMessage {
+    let mut message = FixMessage::new(HEARTBEAT);
+    if let Some(id) = test_request.get(TEST_REQ_ID) {
+        message.push(TEST_REQ_ID, id);
+    }
+    message
+}
+
+/// 订阅或退订交易对的买卖盘，`depth` 为 0 表示全部档位，增量更新按档位推送
+pub fn market_data_request(md_req_id: &str, request_type: &str, symbols: &[String], depth: usize) -> FixMessage {
+    let mut message = FixMessage::new(MARKET_DATA_REQUEST);
+    message.push(MD_REQ_ID, md_req_id)
+        .push(SUBSCRIPTION_REQUEST_TYPE, request_type)
+        .push(MARKET_DEPTH, depth)
+        .push(MD_UPDATE_TYPE, 1)
+        .push(NO_MD_ENTRY_TYPES, 2)
+        .push(MD_ENTRY_TYPE, BID)
+        .push(MD_ENTRY_TYPE, OFFER)
+        .push(NO_RELATED_SYM, symbols.len());
+    for symbol in symbols {
+        message.push(SYMBOL, symbol);
+    }
+    message
+}
+
+/// 拒绝行情请求，原因 0 为未知交易对
+pub fn market_data_reject(md_req_id: &str, reason: u8, text: &str) -> FixMessage {
+    let mut message = FixMessage::new(MARKET_DATA_REQUEST_REJECT);
+    message.push(MD_REQ_ID, md_req_id)
+        .push(MD_REQ_REJ_REASON, reason)
+        .push(TEXT, text);
+    message
+}
+
+/// 订单薄的完整快照，SecurityExchange 为交易所名称
+pub fn snapshot_full_refresh(md_req_id: &str, view: &BookView) -> FixMessage {
+    let mut message = FixMessage::new(MARKET_DATA_SNAPSHOT);
+    message.push(MD_REQ_ID, md_req_id)
+        .push(SYMBOL, &view.symbol)
+        .push(SECURITY_EXCHANGE, &view.venue)
+        .push(NO_MD_ENTRIES, view.bids.len() + view.asks.len());
+    for (entry_type, levels) in [(BID, &view.bids), (OFFER, &view.asks)] {
+        for (price, quantity) in levels {
+            message.push(MD_ENTRY_TYPE, entry_type)
+                .push(MD_ENTRY_PX, price)
+                .push(MD_ENTRY_SIZE, quantity);
+        }
+    }
+    message
+}
+
+/// 变化档位的增量更新
+///
+/// `changes` 只包含变化的档位，数量为 0 时为删除；价格在 `previous` 中已存在时为修改，否则为新增
+pub fn incremental_refresh(md_req_id: &str, changes: &BookView, previous: &BookView) -> FixMessage {
+    let mut message = FixMessage::new(MARKET_DATA_INCREMENTAL);
+    message.push(MD_REQ_ID, md_req_id)
+        .push(NO_MD_ENTRIES, changes.bids.len() + changes.asks.len());
+    for (entry_type, levels, known) in [(BID, &changes.bids, &previous.bids), (OFFER, &changes.asks, &previous.asks)] {
+        for (price, quantity) in levels {
+            let action = if quantity.is_zero() {
+                2
+            } else if known.iter().any(|(known, _)| known == price) {
+                1
+            } else {
+                0
+            };
+            message.push(MD_UPDATE_ACTION, action)
+                .push(MD_ENTRY_TYPE, entry_type)
+                .push(SYMBOL, &changes.symbol)
+                .push(SECURITY_EXCHANGE, &changes.venue)
+                .push(MD_ENTRY_PX, price);
+            if action != 2 {
+                message.push(MD_ENTRY_SIZE, quantity);
+            }
+        }
+    }
+    message
+}
+
+/// 行情条目的 (方向, 价格, 数量)
+type EntryLevel = (Side, Decimal, Decimal);
+
+/// 把 W 或 X 消息转为统一深度消息，X 中不同交易对的条目拆为多条消息，其他类型的条目（成交等）忽略
+pub fn depth_messages(message: &FixMessage) -> Result<Vec<DepthMessage>, Box<dyn Error>> {
+    let timestamp = message.get(SENDING_TIME).and_then(parse_fix_timestamp).unwrap_or_else(now_millis);
+    let depth_message = |symbol: &str, kind| DepthMessage {
+        symbol: symbol.to_string(),
+        kind,
+        bids: Vec::new(),
+        asks: Vec::new(),
+        continuity: Continuity::None,
+        checksum: None,
+        max_depth: None,
+        timestamp,
+    };
+    let level = |entry: &[(u32, String)]| -> Result<Option<EntryLevel>, Box<dyn Error>> {
+        let value = |tag: u32| entry.iter().find(|(t, _)| *t == tag).map(|(_, value)| value.as_str());
+        let side = match value(MD_ENTRY_TYPE) {
+            Some(BID) => Side::Bid,
+            Some(OFFER) => Side::Ask,
+            _ => return Ok(None),
+        };
+        let price = value(MD_ENTRY_PX).ok_or("缺少 MDEntryPx")?.parse::<Decimal>()?;
+        // 删除的条目可以不带数量
+        let quantity = match (value(MD_UPDATE_ACTION), value(MD_ENTRY_SIZE)) {
+            (Some("2"), _) | (_, None) => Decimal::ZERO,
+            (_, Some(size)) => size.parse::<Decimal>()?,
+        };
+        Ok(Some((side, price, quantity)))
+    };
+    let push = |message: &mut DepthMessage, (side, price, quantity): EntryLevel| match side {
+        Side::Bid => message.bids.push((price, quantity)),
+        Side::Ask => message.asks.push((price, quantity)),
+    };
+
+    match message.msg_type.as_str() {
+        MARKET_DATA_SNAPSHOT => {
+            let symbol = message.get(SYMBOL).ok_or("快照缺少 Symbol")?;
+            let mut snapshot = depth_message(symbol, DepthKind::Snapshot);
+            for entry in message.groups(MD_ENTRY_TYPE) {
+                if let Some(level) = level(entry)? {
+                    push(&mut snapshot, level);
+                }
+            }
+            Ok(vec![snapshot])
+        }
+        MARKET_DATA_INCREMENTAL => {
+            let mut deltas: Vec<DepthMessage> = Vec::new();
+            let mut index: HashMap<String, usize> = HashMap::new();
+            for entry in message.groups(MD_UPDATE_ACTION) {
+                let Some(level) = level(entry)? else {
+                    continue;
+                };
+                let symbol = entry.iter().find(|(tag, _)| *tag == SYMBOL).map(|(_, symbol)| symbol.as_str()).ok_or("增量条目缺少 Symbol")?;
+                let i = *index.entry(symbol.to_string()).or_insert_with(|| {
+                    deltas.push(depth_message(symbol, DepthKind::Delta));
+                    deltas.len() - 1
+                });
+                push(&mut deltas[i], level);
+            }
+            Ok(deltas)
+        }
+        _ => Ok(Vec::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn heartbeat() -> Vec<u8> {
+        let mut message = FixMessage::new(HEARTBEAT);
+        message.push(TEST_REQ_ID, "ping");
+        message.encode("SERVER", "CLIENT", 7, 1_700_000_000_123)
+    }
+
+    #[test]
+    fn decode_round_trip() {
+        let mut bytes = heartbeat();
+        let len = bytes.len();
+        bytes.extend_from_slice(b"8=FIX");
+        let (message, consumed) = FixMessage::decode(&bytes).unwrap().unwrap();
+        assert_eq!(consumed, len);
+        assert_eq!(message.msg_type, HEARTBEAT);
+        assert_eq!(message.get(TEST_REQ_ID), Some("ping"));
+        assert_eq!(message.get_u64(MSG_SEQ_NUM), Some(7));
+        assert_eq!(message.get(SENDING_TIME).and_then(parse_fix_timestamp), Some(1_700_000_000_123));
+    }
+
+    #[test]
+    fn truncated_message_waits_for_more_data() {
+        let bytes = heartbeat();
+        for len in 0..bytes.len() {
+            assert!(FixMessage::decode(&bytes[..len]).unwrap().is_none(), "前 {} 字节", len);
+        }
+    }
+
+    #[test]
+    fn oversized_body_length_rejected() {
+        for length in [usize::MAX.to_string(), u128::MAX.to_string(), (MAX_BODY_LENGTH + 1).to_string()] {
+            let bytes = format!("8=FIX.4.4\x019={}\x0135=0\x01", length);
+            assert!(FixMessage::decode(bytes.as_bytes()).is_err(), "BodyLength {}", length);
+        }
+    }
+
+    #[test]
+    fn header_without_separator_rejected() {
+        assert!(FixMessage::decode(&[b'8'; MAX_HEADER_FIELD + 1]).is_err());
+        let mut bytes = b"8=FIX.4.4\x019=".to_vec();
+        bytes.extend_from_slice(&[b'1'; MAX_HEADER_FIELD]);
+        assert!(FixMessage::decode(&bytes).is_err());
+    }
+
+    #[test]
+    fn bad_checksum_rejected() {
+        let mut bytes = heartbeat();
+        let len = bytes.len();
+        // 校验和的最后一位数字
+        bytes[len - 2] = if bytes[len - 2] == b'0' { b'1' } else { b'0' };
+        assert!(FixMessage::decode(&bytes).is_err());
+
+        let mut bytes = heartbeat();
+        let body = bytes.iter().position(|b| *b == b'p').unwrap();
+        bytes[body] = b'q';
+        assert!(FixMessage::decode(&bytes).is_err());
+    }
+
+    #[test]
+    fn reader_splits_stream() {
+        let mut reader = FixReader::default();
+        let bytes = [heartbeat(), heartbeat()].concat();
+        let (head, tail) = bytes.split_at(bytes.len() / 2 + 3);
+        reader.push(head);
+        assert!(reader.next_message().unwrap().is_some());
+        assert!(reader.next_message().unwrap().is_none());
+        reader.push(tail);
+        assert!(reader.next_message().unwrap().is_some());
+        assert!(reader.next_message().unwrap().is_none());
+    }
+}
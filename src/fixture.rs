@@ -0,0 +1,210 @@
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::binance::{is_partial_depth_stream, DepthSnapshot, DepthUpdate, Market, SymbolConfig};
+use crate::exchange::DepthKind;
+use crate::manager::{BookManager, BINANCE_VENUE};
+use crate::order_book::{DepthDisplay, OrderBook};
+use crate::recorder::Record;
+use crate::replay::is_book_start;
+use crate::ws_api::SnapshotSource;
+
+/// 订单薄内容的哈希
+///
+/// 对序号和按价格排序的全部档位计算 CRC32，价格和数量先去掉末尾的0，
+/// 同一订单薄不论档位的小数位写法如何得到相同的结果
+pub fn book_hash(book: &OrderBook) -> u32 {
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(book.last_update_id.to_string().as_bytes());
+    for (side, levels) in [("b", book.bids_list()), ("a", book.asks_list())] {
+        for (price, quantity) in levels {
+            hasher.update(format!("|{}{}:{}", side, price.normalize(), quantity.normalize()).as_bytes());
+        }
+    }
+    hasher.finalize()
+}
+
+/// 同步算法的回归测试数据
+///
+/// 从记录中截取一个订单薄的起点（关键帧或快照）和之后的若干条增量更新，
+/// 并保存依次应用后订单薄的哈希。修改序号检查、档位合并或截断逻辑后重新应用这些记录，
+/// 结果与保存的哈希不同即说明行为发生了变化。
+///
+/// 币安订单薄保存获取的深度快照和原始消息帧，重新应用时经过与实时行情相同的同步流程（获取快照、
+/// 丢弃过期更新、按市场检查序号）；其他交易所保存统一格式的记录，经过回放流程
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fixture {
+    pub venue: String,
+    pub symbol: String,
+    /// 币安订单薄所属的市场
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub market: Option<Market>,
+    /// 币安订单薄同步时获取到的深度快照
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<DepthSnapshot>,
+    /// 第一条为起点，之后为增量更新，均按接收时间排序；
+    /// 币安订单薄为原始消息帧，第一条是触发获取快照的增量更新
+    pub records: Vec<Record>,
+    /// 应用全部记录后订单薄的序号
+    pub expected_update_id: u64,
+    /// 应用全部记录后订单薄的 [`book_hash`]
+    pub expected_hash: u32,
+}
+
+impl Fixture {
+    /// 从按时间排序的记录中截取测试数据
+    ///
+    /// # 参数
+    ///
+    /// * `records` - 按接收时间排序的记录
+    /// * `market` - 币安订单薄所属的市场，其他交易所忽略
+    /// * `venue` - 交易所名称
+    /// * `symbol` - 交易所原生交易对名称
+    /// * `from` - 起点不早于该时间（毫秒）
+    /// * `deltas` - 起点之后截取的增量更新条数，不足时截取到记录结束
+    pub fn distill(records: &[Record], market: Market, venue: &str, symbol: &str, from: u64, deltas: usize) -> Result<Self, Box<dyn Error>> {
+        if venue == BINANCE_VENUE {
+            return Self::distill_binance(records, market, symbol, from, deltas);
+        }
+        let start = records.iter()
+            .position(|record| record.recv_time() >= from && is_book_start(record, venue, symbol))
+            .ok_or_else(|| format!("{} 之后没有 {} {} 的关键帧或快照", from, venue, symbol))?;
+        let mut fixture = Fixture {
+            venue: venue.to_string(),
+            symbol: symbol.to_string(),
+            market: None,
+            snapshot: None,
+            records: vec![records[start].clone()],
+            expected_update_id: 0,
+            expected_hash: 0,
+        };
+        let updates = records[start + 1..].iter()
+            .filter(|record| match record {
+                Record::Update { venue: record_venue, message, .. } => {
+                    record_venue == venue && message.symbol == symbol && message.kind == DepthKind::Delta
+                }
+                Record::Raw { .. } | Record::Binary { .. } | Record::Trade { .. } | Record::Keyframe { .. } => false,
+            })
+            .take(deltas);
+        fixture.records.extend(updates.cloned());
+        let book = fixture.apply()?;
+        fixture.expected_update_id = book.last_update_id;
+        fixture.expected_hash = book_hash(&book);
+        Ok(fixture)
+    }
+
+    /// 从币安的记录中截取测试数据
+    ///
+    /// 实时同步时记录的快照序号已改写为触发创建的增量更新的 u，原始快照序号没有保存；
+    /// 同步流程只要求快照序号大于该 u，这里取 u + 1，得到的订单薄与实时同步时相同
+    fn distill_binance(records: &[Record], market: Market, symbol: &str, from: u64, deltas: usize) -> Result<Self, Box<dyn Error>> {
+        let (start, message) = records.iter().enumerate()
+            .find_map(|(index, record)| match record {
+                Record::Update { message, .. } if record.recv_time() >= from && is_book_start(record, BINANCE_VENUE, symbol) => {
+                    Some((index, message))
+                }
+                _ => None,
+            })
+            .ok_or_else(|| format!("{} 之后没有 {} {} 的快照", from, BINANCE_VENUE, symbol))?;
+        let update_id = message.continuity.sequence().ok_or("快照没有序号")?;
+        let trigger = records[..start].iter()
+            .rposition(|record| binance_depth_update(record, symbol).is_some_and(|update| update.u == update_id))
+            .ok_or("快照之前没有触发创建订单薄的原始增量消息，记录中需要保留原始消息帧")?;
+        let levels = |levels: &[(Decimal, Decimal)]| levels.iter()
+            .map(|(price, quantity)| [price.to_string(), quantity.to_string()])
+            .collect();
+        let mut fixture = Fixture {
+            venue: BINANCE_VENUE.to_string(),
+            symbol: symbol.to_string(),
+            market: Some(market),
+            snapshot: Some(DepthSnapshot { lastUpdateId: update_id + 1, bids: levels(&message.bids), asks: levels(&message.asks) }),
+            records: vec![records[trigger].clone()],
+            expected_update_id: 0,
+            expected_hash: 0,
+        };
+        let updates = records[start + 1..].iter()
+            .filter(|record| binance_depth_update(record, symbol).is_some())
+            .take(deltas);
+        fixture.records.extend(updates.cloned());
+        let book = fixture.apply()?;
+        fixture.expected_update_id = book.last_update_id;
+        fixture.expected_hash = book_hash(&book);
+        Ok(fixture)
+    }
+
+    /// 增量更新条数
+    pub fn deltas(&self) -> usize {
+        self.records.len().saturating_sub(1)
+    }
+
+    /// 依次应用全部记录，返回得到的订单薄；序号断开或校验和不一致时返回错误
+    ///
+    /// 币安订单薄经过实时同步流程，序号断开时与实时行情一样只跳过该条更新，差异体现在结果中
+    pub fn apply(&self) -> Result<OrderBook, Box<dyn Error>> {
+        if self.venue == BINANCE_VENUE {
+            return self.apply_binance();
+        }
+        let mut manager = BookManager::new(Market::Spot, &[]);
+        for record in &self.records {
+            manager.replay_record(record.clone())?;
+        }
+        manager.venue_book(&self.venue, &self.symbol).cloned()
+            .ok_or_else(|| format!("没有建立 {} {} 的订单薄", self.venue, self.symbol).into())
+    }
+
+    fn apply_binance(&self) -> Result<OrderBook, Box<dyn Error>> {
+        let market = self.market.ok_or("币安测试数据缺少市场类型")?;
+        let snapshot = self.snapshot.clone().ok_or("币安测试数据缺少深度快照")?;
+        let mut manager = BookManager::new(market, &[SymbolConfig::new(market, &self.symbol)]);
+        manager.set_snapshot_source(SnapshotSource::Fixed(snapshot));
+        manager.set_depth_display(DepthDisplay::None);
+        for record in &self.records {
+            if let Record::Raw { frame, .. } = record {
+                manager.handle_message(frame);
+            }
+        }
+        manager.book(&self.symbol).cloned()
+            .ok_or_else(|| format!("没有建立 {} {} 的订单薄", self.venue, self.symbol).into())
+    }
+
+    /// 重新应用记录并与保存的结果比较
+    pub fn verify(&self) -> Result<(), Box<dyn Error>> {
+        let book = self.apply()?;
+        let hash = book_hash(&book);
+        if book.last_update_id != self.expected_update_id || hash != self.expected_hash {
+            return Err(format!("{} {} 结果不一致: 序号 {} 哈希 {:08x}，期望序号 {} 哈希 {:08x}",
+                               self.venue, self.symbol, book.last_update_id, hash, self.expected_update_id, self.expected_hash).into());
+        }
+        Ok(())
+    }
+
+    /// 写入 JSON 文件
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+        fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// 读取 JSON 文件
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+}
+
+/// 币安原始消息帧中 `symbol` 的增量深度更新，有限档深度流和其他消息返回 None
+fn binance_depth_update(record: &Record, symbol: &str) -> Option<DepthUpdate> {
+    let Record::Raw { venue, frame, .. } = record else {
+        return None;
+    };
+    if venue != BINANCE_VENUE {
+        return None;
+    }
+    let value: serde_json::Value = serde_json::from_str(frame).ok()?;
+    if value.get("stream").and_then(|stream| stream.as_str()).is_some_and(is_partial_depth_stream) {
+        return None;
+    }
+    let update: DepthUpdate = serde_json::from_value(value.get("data").unwrap_or(&value).clone()).ok()?;
+    (update.e == "depthUpdate" && update.s == symbol).then_some(update)
+}
@@ -0,0 +1,79 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use rust_decimal::Decimal;
+
+use crate::binance::{FundingRateRecord, MarkPriceUpdate};
+
+/// 保留的历史资金费率条数
+const MAX_FUNDING_HISTORY: usize = 1000;
+
+/// 一次已结算的资金费率
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FundingRate {
+    /// 资金费时间（毫秒）
+    pub funding_time: u64,
+    /// 资金费率
+    pub rate: Decimal,
+}
+
+/// 单个合约的资金费率状态
+#[derive(Debug, Default)]
+pub struct FundingInfo {
+    /// 下次资金费时间（毫秒）
+    pub next_funding_time: Option<u64>,
+    /// 当前周期的预测资金费率
+    pub predicted_rate: Option<Decimal>,
+    /// 已结算的历史资金费率（按时间升序）
+    history: VecDeque<FundingRate>,
+}
+
+impl FundingInfo {
+    /// 使用 REST 返回的历史记录初始化
+    pub fn load_history(&mut self, records: &[FundingRateRecord]) -> Result<(), Box<dyn Error>> {
+        for record in records {
+            self.push_history(FundingRate {
+                funding_time: record.fundingTime,
+                rate: record.fundingRate.parse::<Decimal>()?,
+            });
+        }
+        Ok(())
+    }
+
+    /// 根据标记价格推送更新预测费率
+    ///
+    /// 下次资金费时间前移时，上一周期最后一次推送的费率即为结算费率，记入历史
+    pub fn apply_mark_price(&mut self, update: &MarkPriceUpdate) -> Result<(), Box<dyn Error>> {
+        let rate = update.r.parse::<Decimal>()?;
+        if let (Some(funding_time), Some(predicted_rate)) = (self.next_funding_time, self.predicted_rate)
+            && update.T > funding_time
+        {
+            self.push_history(FundingRate { funding_time, rate: predicted_rate });
+        }
+        self.next_funding_time = Some(update.T);
+        self.predicted_rate = Some(rate);
+        Ok(())
+    }
+
+    /// 已结算的历史资金费率（按时间升序）
+    pub fn history(&self) -> impl Iterator<Item = &FundingRate> {
+        self.history.iter()
+    }
+
+    /// 最近一次已结算的资金费率
+    pub fn last_settled(&self) -> Option<&FundingRate> {
+        self.history.back()
+    }
+
+    /// 插入一条历史记录，忽略重复或更早的时间
+    fn push_history(&mut self, funding_rate: FundingRate) {
+        if let Some(last) = self.history.back()
+            && funding_rate.funding_time <= last.funding_time
+        {
+            return;
+        }
+        self.history.push_back(funding_rate);
+        if self.history.len() > MAX_FUNDING_HISTORY {
+            self.history.pop_front();
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{BufWriter, Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use rust_decimal::Decimal;
+use zip::ZipArchive;
+use tracing::info;
+
+use crate::binance::Market;
+use crate::exchange::{parse_rfc3339_millis, Continuity, DepthKind, DepthMessage};
+use crate::manager::BINANCE_VENUE;
+use crate::recorder::{utc_hour_label, Record, DAY_MS};
+use crate::trade::Trade;
+
+/// 币安公开历史数据地址
+const VISION_URL: &str = "https://data.binance.vision/data";
+/// 超过该值的时间戳为微秒，现货数据自 2025 年起使用微秒
+const MICROS_THRESHOLD: u64 = 100_000_000_000_000;
+
+/// data.binance.vision 的数据类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryData {
+    /// 归集成交，现货和合约都有
+    AggTrades,
+    /// 逐笔最优挂单，仅合约，转为只有一档的订单薄快照
+    BookTicker,
+}
+
+impl HistoryData {
+    /// 解析 `aggTrades` 或 `bookTicker`
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "aggTrades" => Some(HistoryData::AggTrades),
+            "bookTicker" => Some(HistoryData::BookTicker),
+            _ => None,
+        }
+    }
+
+    /// 市场默认下载的数据类型，现货没有公开的盘口数据
+    pub fn defaults(market: Market) -> Vec<Self> {
+        if market.is_spot() {
+            vec![HistoryData::AggTrades]
+        } else {
+            vec![HistoryData::BookTicker, HistoryData::AggTrades]
+        }
+    }
+
+    /// 数据类型在地址和文件名中的名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            HistoryData::AggTrades => "aggTrades",
+            HistoryData::BookTicker => "bookTicker",
+        }
+    }
+}
+
+/// data.binance.vision 历史数据下载器
+///
+/// 按日下载 `data/<市场>/daily/<类型>/<交易对>/<交易对>-<类型>-YYYY-MM-DD.zip`，解压出的 CSV
+/// 保存在输出目录中，再次运行时直接使用已有的 CSV。每天的数据合并为按时间排序的
+/// `<交易对>-YYYY-MM-DD.ndjson` 记录文件，可直接用于回放、重建订单薄和回测
+#[derive(Debug)]
+pub struct HistoryDownloader {
+    market: Market,
+    symbol: String,
+    dir: PathBuf,
+    client: reqwest::blocking::Client,
+}
+
+impl HistoryDownloader {
+    /// 创建下载器，目录不存在时自动创建
+    pub fn new(market: Market, symbol: &str, dir: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        if market.vision_path().is_none() {
+            return Err(format!("{:?} 没有公开的历史数据", market).into());
+        }
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(HistoryDownloader {
+            market,
+            symbol: symbol.to_uppercase(),
+            dir: dir.as_ref().to_path_buf(),
+            client: reqwest::blocking::Client::new(),
+        })
+    }
+
+    /// 下载 `[from, to]` 之间每天的数据并写入记录文件，返回写入的文件
+    ///
+    /// # 参数
+    ///
+    /// * `from` - 起始日期 `YYYY-MM-DD`
+    /// * `to` - 结束日期 `YYYY-MM-DD`，包含当天
+    /// * `data` - 下载的数据类型
+    pub fn download(&self, from: &str, to: &str, data: &[HistoryData]) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+        let day_of = |date: &str| parse_rfc3339_millis(&format!("{}T00:00:00", date))
+            .map(|millis| millis / DAY_MS)
+            .ok_or_else(|| format!("无效的日期: {}，应为 YYYY-MM-DD", date));
+        let (first, last) = (day_of(from)?, day_of(to)?);
+        let mut written = Vec::new();
+        for day in first..=last {
+            let label = utc_hour_label(day * DAY_MS);
+            let date = format!("{}-{}-{}", &label[..4], &label[4..6], &label[6..8]);
+            let mut records = Vec::new();
+            for data in data {
+                match self.fetch(*data, &date)? {
+                    Some(csv) => {
+                        let parsed = match data {
+                            HistoryData::AggTrades => parse_agg_trades(&self.symbol, &csv)?,
+                            HistoryData::BookTicker => parse_book_ticker(&self.symbol, &csv)?,
+                        };
+                        info!(symbol = self.symbol, date, data = data.name(), records = parsed.len(), "历史数据已解析");
+                        records.extend(parsed);
+                    }
+                    None => info!(symbol = self.symbol, date, data = data.name(), "没有历史数据"),
+                }
+            }
+            if records.is_empty() {
+                continue;
+            }
+            records.sort_by_key(|record| record.recv_time());
+            let path = self.dir.join(format!("{}-{}.ndjson", self.symbol, date));
+            let mut file = BufWriter::new(File::create(&path)?);
+            for record in &records {
+                serde_json::to_writer(&mut file, record)?;
+                file.write_all(b"\n")?;
+            }
+            file.flush()?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+
+    /// 读取一天的 CSV，本地没有时下载并解压；数据不存在时返回 None
+    fn fetch(&self, data: HistoryData, date: &str) -> Result<Option<String>, Box<dyn Error>> {
+        let name = format!("{}-{}-{}", self.symbol, data.name(), date);
+        let csv_path = self.dir.join(format!("{}.csv", name));
+        if csv_path.exists() {
+            return Ok(Some(fs::read_to_string(csv_path)?));
+        }
+        let url = format!("{}/{}/daily/{}/{}/{}.zip", VISION_URL, self.market.vision_path().unwrap_or_default(), data.name(), self.symbol, name);
+        info!(url, "正在下载");
+        let response = self.client.get(&url).send()?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(format!("下载失败: {}", response.status()).into());
+        }
+        let mut archive = ZipArchive::new(Cursor::new(response.bytes()?))?;
+        if archive.is_empty() {
+            return Err(format!("压缩包为空: {}", url).into());
+        }
+        let mut csv = String::new();
+        archive.by_index(0)?.read_to_string(&mut csv)?;
+        fs::write(&csv_path, &csv)?;
+        Ok(Some(csv))
+    }
+}
+
+/// 统一为毫秒时间戳
+fn to_millis(time: u64) -> u64 {
+    if time >= MICROS_THRESHOLD { time / 1000 } else { time }
+}
+
+/// 拆分 CSV 的数据行，跳过表头（合约文件有表头，现货没有）
+fn data_rows(csv: &str) -> impl Iterator<Item = Vec<&str>> {
+    csv.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.split(',').collect::<Vec<_>>())
+        .filter(|fields| fields.first().is_some_and(|field| field.parse::<u64>().is_ok()))
+}
+
+/// 解析归集成交 CSV：`归集成交ID,价格,数量,首个成交ID,末个成交ID,成交时间,买方是否为挂单方[,是否最优撮合]`
+pub fn parse_agg_trades(symbol: &str, csv: &str) -> Result<Vec<Record>, Box<dyn Error>> {
+    data_rows(csv)
+        .map(|fields| {
+            let [_, price, quantity, _, _, time, buyer_is_maker, ..] = fields.as_slice() else {
+                return Err(format!("归集成交的列数不足: {}", fields.join(",")).into());
+            };
+            let trade = Trade {
+                price: price.parse()?,
+                quantity: quantity.parse()?,
+                buyer_is_maker: buyer_is_maker.eq_ignore_ascii_case("true"),
+                trade_time: to_millis(time.parse()?),
+            };
+            Ok(Record::trade(BINANCE_VENUE, symbol, &trade, trade.trade_time))
+        })
+        .collect()
+}
+
+/// 解析最优挂单 CSV：`更新ID,买一价,买一量,卖一价,卖一量,撮合时间,事件时间`，每行转为只有一档的订单薄快照
+pub fn parse_book_ticker(symbol: &str, csv: &str) -> Result<Vec<Record>, Box<dyn Error>> {
+    data_rows(csv)
+        .map(|fields| {
+            let [update_id, bid_price, bid_quantity, ask_price, ask_quantity, _, event_time, ..] = fields.as_slice() else {
+                return Err(format!("最优挂单的列数不足: {}", fields.join(",")).into());
+            };
+            let update_id = update_id.parse::<u64>()?;
+            let event_time = to_millis(event_time.parse()?);
+            let message = DepthMessage {
+                symbol: symbol.to_string(),
+                kind: DepthKind::Snapshot,
+                bids: vec![(bid_price.parse::<Decimal>()?, bid_quantity.parse::<Decimal>()?)],
+                asks: vec![(ask_price.parse::<Decimal>()?, ask_quantity.parse::<Decimal>()?)],
+                continuity: Continuity::Range { first: update_id, last: update_id },
+                checksum: None,
+                max_depth: None,
+                timestamp: event_time,
+            };
+            Ok(Record::update(BINANCE_VENUE, &message, event_time))
+        })
+        .collect()
+}
@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::error::Error;
+
+/// 无分隔符交易对（例如 BTCUSDT）拆分时识别的计价资产，较长的排在前面
+const KNOWN_QUOTES: [&str; 14] = [
+    "FDUSD", "USDT", "USDC", "BUSD", "TUSD", "USD", "EUR", "GBP", "TRY", "BRL", "JPY", "BTC", "ETH", "BNB",
+];
+
+/// 品种类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InstrumentType {
+    /// 现货
+    Spot,
+    /// 永续合约
+    Perpetual,
+}
+
+/// 跨交易所统一的品种标识，例如 BTC/USDT 现货、BTC/USDT 永续
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Instrument {
+    /// 基础资产，例如 BTC
+    pub base: String,
+    /// 计价资产，例如 USDT
+    pub quote: String,
+    /// 品种类型
+    pub kind: InstrumentType,
+}
+
+impl Instrument {
+    /// 创建品种，资产名称统一为大写
+    pub fn new(base: &str, quote: &str, kind: InstrumentType) -> Self {
+        Instrument {
+            base: base.to_uppercase(),
+            quote: quote.to_uppercase(),
+            kind,
+        }
+    }
+
+    /// 解析统一格式 `BASE/QUOTE`，永续合约加 `-PERP` 后缀，例如 `BTC/USDT-PERP`
+    pub fn parse(spec: &str) -> Result<Self, Box<dyn Error>> {
+        let spec = spec.to_uppercase();
+        let (pair, kind) = match spec.strip_suffix("-PERP") {
+            Some(pair) => (pair, InstrumentType::Perpetual),
+            None => (spec.as_str(), InstrumentType::Spot),
+        };
+        let (base, quote) = pair.split_once('/').ok_or_else(|| format!("品种格式错误: {}", spec))?;
+        if base.is_empty() || quote.is_empty() {
+            return Err(format!("品种格式错误: {}", spec).into());
+        }
+        Ok(Instrument::new(base, quote, kind))
+    }
+
+    /// 按交易所的命名规则生成原生交易对名称
+    pub fn venue_symbol(&self, venue: &str) -> String {
+        let (base, quote) = (self.base.as_str(), self.quote.as_str());
+        let perpetual = self.kind == InstrumentType::Perpetual;
+        match venue {
+            "okx" if perpetual => format!("{}-{}-SWAP", base, quote),
+            "okx" | "coinbase" | "kucoin" => format!("{}-{}", base, quote),
+            "kraken" => format!("{}/{}", kraken_asset(base), kraken_asset(quote)),
+            "bitstamp" => format!("{}{}", base, quote).to_lowercase(),
+            "htx" => format!("{}{}", base, quote).to_lowercase(),
+            "gateio" => format!("{}_{}", base, quote),
+            "deribit" if perpetual && quote == "USD" => format!("{}-PERPETUAL", base),
+            "deribit" if perpetual => format!("{}_{}-PERPETUAL", base, quote),
+            "deribit" => format!("{}_{}", base, quote),
+            "hyperliquid" => base.to_string(),
+            "dydx" => format!("{}-{}", base, quote),
+            _ => format!("{}{}", base, quote),
+        }
+    }
+
+    /// 从交易所原生交易对名称推断品种，无分隔符的名称按常见计价资产拆分
+    pub fn from_venue_symbol(venue: &str, symbol: &str) -> Option<Self> {
+        let upper = symbol.to_uppercase();
+        if let Some(pair) = upper.strip_suffix("-SWAP") {
+            let (base, quote) = pair.split_once('-')?;
+            return Some(Instrument::new(base, quote, InstrumentType::Perpetual));
+        }
+        if let Some(base) = upper.strip_suffix("-PERPETUAL") {
+            return Some(match base.split_once('_') {
+                Some((base, quote)) => Instrument::new(base, quote, InstrumentType::Perpetual),
+                None => Instrument::new(base, "USD", InstrumentType::Perpetual),
+            });
+        }
+        let kind = match venue {
+            "bybit-linear" | "bitget-futures" | "dydx" => InstrumentType::Perpetual,
+            // Hyperliquid 永续合约只有币种名称，以 USD 计价
+            "hyperliquid" => return Some(Instrument::new(&upper, "USD", InstrumentType::Perpetual)),
+            _ => InstrumentType::Spot,
+        };
+        if let Some((base, quote)) = upper.split_once(['-', '/', '_']) {
+            return Some(Instrument::new(&normalize_asset(base), &normalize_asset(quote), kind));
+        }
+        let quote = KNOWN_QUOTES.iter().find(|quote| upper.len() > quote.len() && upper.ends_with(*quote))?;
+        let base = &upper[..upper.len() - quote.len()];
+        Some(Instrument::new(base, quote, kind))
+    }
+}
+
+impl fmt::Display for Instrument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            InstrumentType::Spot => write!(f, "{}/{}", self.base, self.quote),
+            InstrumentType::Perpetual => write!(f, "{}/{}-PERP", self.base, self.quote),
+        }
+    }
+}
+
+/// 品种与各交易所原生交易对名称的映射表
+///
+/// 未显式登记的品种按交易所命名规则生成名称，登记的映射优先
+#[derive(Debug, Clone, Default)]
+pub struct SymbolMap {
+    /// (品种, 交易所) -> 原生交易对
+    to_venue: HashMap<(Instrument, String), String>,
+    /// (交易所, 原生交易对) -> 品种
+    from_venue: HashMap<(String, String), Instrument>,
+}
+
+impl SymbolMap {
+    /// 创建空的映射表
+    pub fn new() -> Self {
+        SymbolMap::default()
+    }
+
+    /// 登记一条映射，覆盖命名规则
+    pub fn insert(&mut self, instrument: Instrument, venue: &str, symbol: &str) {
+        self.to_venue.insert((instrument.clone(), venue.to_string()), symbol.to_string());
+        self.from_venue.insert((venue.to_string(), symbol.to_string()), instrument);
+    }
+
+    /// 品种在交易所的原生交易对名称
+    pub fn venue_symbol(&self, instrument: &Instrument, venue: &str) -> String {
+        self.to_venue.get(&(instrument.clone(), venue.to_string()))
+            .cloned()
+            .unwrap_or_else(|| instrument.venue_symbol(venue))
+    }
+
+    /// 交易所原生交易对对应的品种
+    pub fn instrument(&self, venue: &str, symbol: &str) -> Option<Instrument> {
+        self.from_venue.get(&(venue.to_string(), symbol.to_string()))
+            .cloned()
+            .or_else(|| Instrument::from_venue_symbol(venue, symbol))
+    }
+
+    /// 品种在多个交易所的 (交易所, 原生交易对) 列表，供合并订单薄、套利检测等使用
+    pub fn legs(&self, instrument: &Instrument, venues: &[&str]) -> Vec<(String, String)> {
+        venues.iter()
+            .map(|venue| (venue.to_string(), self.venue_symbol(instrument, venue)))
+            .collect()
+    }
+}
+
+/// Kraken 使用 XBT 表示 BTC
+fn kraken_asset(asset: &str) -> &str {
+    match asset {
+        "BTC" => "XBT",
+        _ => asset,
+    }
+}
+
+/// 把交易所特有的资产名称转为统一名称
+fn normalize_asset(asset: &str) -> String {
+    match asset {
+        "XBT" => "BTC".to_string(),
+        _ => asset.to_string(),
+    }
+}
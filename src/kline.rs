@@ -0,0 +1,92 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use rust_decimal::Decimal;
+
+use crate::binance::Kline;
+
+/// 每个周期保留的已收盘K线数量
+const MAX_CLOSED_CANDLES: usize = 500;
+
+/// 一根K线（OHLCV）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Candle {
+    /// 开盘时间（毫秒）
+    pub open_time: u64,
+    /// 收盘时间（毫秒）
+    pub close_time: u64,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    /// 成交量（基础资产）
+    pub volume: Decimal,
+    /// 成交额（计价资产）
+    pub quote_volume: Decimal,
+    /// 成交笔数
+    pub trades: u64,
+    /// 是否已收盘
+    pub closed: bool,
+}
+
+impl Candle {
+    /// 从K线推送创建
+    pub fn from_kline(kline: &Kline) -> Result<Self, Box<dyn Error>> {
+        Ok(Candle {
+            open_time: kline.t,
+            close_time: kline.T,
+            open: kline.o.parse::<Decimal>()?,
+            high: kline.h.parse::<Decimal>()?,
+            low: kline.l.parse::<Decimal>()?,
+            close: kline.c.parse::<Decimal>()?,
+            volume: kline.v.parse::<Decimal>()?,
+            quote_volume: kline.q.parse::<Decimal>()?,
+            trades: kline.n,
+            closed: kline.x,
+        })
+    }
+}
+
+/// 单个周期的K线序列：当前未收盘K线和最近的已收盘K线
+#[derive(Debug, Default)]
+pub struct CandleSeries {
+    current: Option<Candle>,
+    closed: VecDeque<Candle>,
+}
+
+impl CandleSeries {
+    /// 应用一根K线推送
+    ///
+    /// # 返回值
+    ///
+    /// K线收盘时返回该K线
+    pub fn apply(&mut self, candle: Candle) -> Option<Candle> {
+        if !candle.closed {
+            self.current = Some(candle);
+            return None;
+        }
+        self.current = None;
+        if self.closed.back().is_some_and(|last| last.open_time >= candle.open_time) {
+            return None;
+        }
+        self.closed.push_back(candle);
+        if self.closed.len() > MAX_CLOSED_CANDLES {
+            self.closed.pop_front();
+        }
+        Some(candle)
+    }
+
+    /// 当前未收盘K线
+    pub fn current(&self) -> Option<&Candle> {
+        self.current.as_ref()
+    }
+
+    /// 最近的已收盘K线（按时间升序）
+    pub fn closed(&self) -> impl Iterator<Item = &Candle> {
+        self.closed.iter()
+    }
+
+    /// 最近 n 根已收盘K线（按时间升序）
+    pub fn recent(&self, n: usize) -> Vec<Candle> {
+        self.closed.iter().skip(self.closed.len().saturating_sub(n)).copied().collect()
+    }
+}
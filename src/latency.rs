@@ -0,0 +1,336 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use hdrhistogram::Histogram;
+use rust_decimal::Decimal;
+use serde_json::json;
+
+use crate::metrics;
+
+/// 每个交易对保留的延迟样本数
+const DEFAULT_LATENCY_WINDOW: usize = 1000;
+/// 延迟直方图可记录的最大值（毫秒），超过时记为该值
+const MAX_TRACKED_LATENCY_MS: u64 = 3_600_000;
+/// 延迟直方图的有效数字位数
+const LATENCY_SIGNIFICANT_FIGURES: u8 = 3;
+/// 延迟分位数写入指标的最短间隔（毫秒）
+const LATENCY_EXPORT_INTERVAL_MS: u64 = 1000;
+
+/// 当前 Unix 时间（毫秒）
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// 滚动窗口内的延迟样本（毫秒）
+///
+/// 交易所时钟与本地时钟存在偏差，延迟可能为负数
+#[derive(Debug, Clone, Default)]
+pub struct LatencyStats {
+    samples: VecDeque<i64>,
+    window: usize,
+}
+
+impl LatencyStats {
+    /// 创建统计，最多保留 `window` 个样本
+    pub fn new(window: usize) -> Self {
+        LatencyStats {
+            samples: VecDeque::with_capacity(window),
+            window,
+        }
+    }
+
+    /// 记录一个样本
+    pub fn record(&mut self, latency_ms: i64) {
+        if self.samples.len() >= self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(latency_ms);
+    }
+
+    /// 样本数
+    pub fn count(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// 平均延迟
+    pub fn mean(&self) -> Option<f64> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        Some(self.samples.iter().sum::<i64>() as f64 / self.samples.len() as f64)
+    }
+
+    /// 分位数，`percentile` 取值 0-100，例如 50、99
+    pub fn percentile(&self, percentile: f64) -> Option<i64> {
+        percentile_of(self.samples.iter().copied().collect(), percentile)
+    }
+}
+
+/// 按交易所和交易对统计行情延迟（交易所事件时间到本地处理时间）
+#[derive(Debug, Clone)]
+pub struct LatencyMonitor {
+    /// (交易所, 交易对) -> 延迟统计
+    stats: HashMap<(String, String), LatencyStats>,
+    window: usize,
+}
+
+impl Default for LatencyMonitor {
+    fn default() -> Self {
+        LatencyMonitor::new(DEFAULT_LATENCY_WINDOW)
+    }
+}
+
+impl LatencyMonitor {
+    /// 创建监控，每个交易对最多保留 `window` 个样本
+    pub fn new(window: usize) -> Self {
+        LatencyMonitor {
+            stats: HashMap::new(),
+            window,
+        }
+    }
+
+    /// 记录一条消息的延迟，事件时间为0（交易所未提供）时忽略
+    pub fn record(&mut self, venue: &str, symbol: &str, event_time: u64, local_time: u64) {
+        if event_time == 0 {
+            return;
+        }
+        let window = self.window;
+        self.stats.entry((venue.to_string(), symbol.to_string()))
+            .or_insert_with(|| LatencyStats::new(window))
+            .record(local_time as i64 - event_time as i64);
+    }
+
+    /// 交易对的延迟统计
+    pub fn stats(&self, venue: &str, symbol: &str) -> Option<&LatencyStats> {
+        self.stats.get(&(venue.to_string(), symbol.to_string()))
+    }
+
+    /// 有样本的交易所（按名称排序）
+    pub fn venues(&self) -> Vec<String> {
+        let mut venues: Vec<String> = self.stats.keys().map(|(venue, _)| venue.clone()).collect();
+        venues.sort();
+        venues.dedup();
+        venues
+    }
+
+    /// 交易所所有交易对合并后的分位数
+    pub fn venue_percentile(&self, venue: &str, percentile: f64) -> Option<i64> {
+        let samples = self.stats.iter()
+            .filter(|((name, _), _)| name == venue)
+            .flat_map(|(_, stats)| stats.samples.iter().copied())
+            .collect();
+        percentile_of(samples, percentile)
+    }
+}
+
+/// 一个交易对的延迟直方图
+#[derive(Debug)]
+struct LatencyHistogram {
+    histogram: Histogram<u64>,
+    /// 延迟为负（交易所时钟快于本地）的样本数，这些样本记为 0
+    negative: u64,
+    /// 上次写入指标的本地时间（毫秒）
+    exported: u64,
+}
+
+/// 一个交易对的延迟分位数（毫秒）
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatencySummary {
+    pub venue: String,
+    pub symbol: String,
+    pub count: u64,
+    pub negative: u64,
+    pub p50: u64,
+    pub p99: u64,
+    pub max: u64,
+}
+
+impl LatencySummary {
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "venue": self.venue,
+            "symbol": self.symbol,
+            "count": self.count,
+            "negative": self.negative,
+            "p50": self.p50,
+            "p99": self.p99,
+            "max": self.max,
+        })
+    }
+}
+
+/// 按交易所和交易对记录的行情延迟 HDR 直方图（交易所事件时间 `E` 到本地接收时间）
+///
+/// 与 [`LatencyMonitor`] 的滚动窗口不同，直方图保留启动以来的全部样本，内存占用固定，
+/// 在主循环和指标服务之间共享。记录时每秒最多把 p50、p99 和最大值写入一次指标
+#[derive(Debug, Default)]
+pub struct FeedLatency {
+    histograms: Mutex<HashMap<(String, String), LatencyHistogram>>,
+}
+
+impl FeedLatency {
+    /// 记录一条消息的延迟，事件时间为0（交易所未提供）时忽略
+    pub fn record(&self, venue: &str, symbol: &str, event_time: u64, local_time: u64) {
+        if event_time == 0 {
+            return;
+        }
+        let mut histograms = self.lock();
+        let entry = match histograms.entry((venue.to_string(), symbol.to_string())) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                // 参数固定，创建不会失败
+                let Ok(histogram) = Histogram::new_with_bounds(1, MAX_TRACKED_LATENCY_MS, LATENCY_SIGNIFICANT_FIGURES) else {
+                    return;
+                };
+                entry.insert(LatencyHistogram { histogram, negative: 0, exported: 0 })
+            }
+        };
+        if local_time < event_time {
+            entry.negative += 1;
+        }
+        entry.histogram.saturating_record(local_time.saturating_sub(event_time));
+        if local_time.saturating_sub(entry.exported) >= LATENCY_EXPORT_INTERVAL_MS {
+            entry.exported = local_time;
+            let histogram = &entry.histogram;
+            metrics::feed_latency(venue, symbol, histogram.value_at_quantile(0.5), histogram.value_at_quantile(0.99), histogram.max());
+        }
+    }
+
+    /// 交易对的延迟分位数，没有样本时返回 None
+    pub fn summary(&self, venue: &str, symbol: &str) -> Option<LatencySummary> {
+        self.lock().get(&(venue.to_string(), symbol.to_string())).map(|entry| summarize(venue, symbol, entry))
+    }
+
+    /// 所有交易对的延迟分位数，按交易所和交易对排序
+    pub fn summaries(&self) -> Vec<LatencySummary> {
+        let mut summaries: Vec<_> = self.lock().iter()
+            .map(|((venue, symbol), entry)| summarize(venue, symbol, entry))
+            .collect();
+        summaries.sort_by(|a, b| (&a.venue, &a.symbol).cmp(&(&b.venue, &b.symbol)));
+        summaries
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<(String, String), LatencyHistogram>> {
+        // 持锁的代码不会 panic，锁中毒时数据仍然可用
+        self.histograms.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn summarize(venue: &str, symbol: &str, entry: &LatencyHistogram) -> LatencySummary {
+    LatencySummary {
+        venue: venue.to_string(),
+        symbol: symbol.to_string(),
+        count: entry.histogram.len(),
+        negative: entry.negative,
+        p50: entry.histogram.value_at_quantile(0.5),
+        p99: entry.histogram.value_at_quantile(0.99),
+        max: entry.histogram.max(),
+    }
+}
+
+/// 一条腿最近一次中间价变动
+#[derive(Debug, Clone, Copy)]
+struct MidMove {
+    mid: Decimal,
+    /// 变动方向，1 为上涨，-1 为下跌，0 为尚未变动
+    direction: i8,
+    /// 本地时间（毫秒）
+    local_time: u64,
+    /// 是否已被计为领先
+    credited: bool,
+}
+
+/// 价格发现领先者统计
+///
+/// 同一品种在多个交易所的中间价变动时，若另一交易所在 `window_ms` 之前已向同一方向变动，
+/// 记为该交易所领先一次，领先次数最多的交易所即为价格发现的领先者
+#[derive(Debug, Clone)]
+pub struct LeadLagTracker {
+    /// 参与比较的 (交易所, 交易对)
+    legs: Vec<(String, String)>,
+    window_ms: u64,
+    last: HashMap<(String, String), MidMove>,
+    /// 交易所 -> 领先次数
+    leads: HashMap<String, u64>,
+}
+
+impl LeadLagTracker {
+    /// 创建统计
+    ///
+    /// # 参数
+    ///
+    /// * `legs` - (交易所, 交易对) 列表
+    /// * `window_ms` - 认定领先的最长时间差（毫秒）
+    pub fn new(legs: Vec<(String, String)>, window_ms: u64) -> Self {
+        LeadLagTracker {
+            legs,
+            window_ms,
+            last: HashMap::new(),
+            leads: HashMap::new(),
+        }
+    }
+
+    /// 判断交易所的交易对是否参与比较
+    pub fn contains(&self, venue: &str, symbol: &str) -> bool {
+        self.legs.iter().any(|(leg_venue, leg_symbol)| leg_venue == venue && leg_symbol == symbol)
+    }
+
+    /// 参与比较的 (交易所, 交易对)
+    pub fn legs(&self) -> &[(String, String)] {
+        &self.legs
+    }
+
+    /// 记录一条腿的最新中间价
+    pub fn update(&mut self, venue: &str, symbol: &str, mid: Decimal, local_time: u64) {
+        let key = (venue.to_string(), symbol.to_string());
+        let direction = match self.last.get(&key) {
+            Some(last) if mid > last.mid => 1,
+            Some(last) if mid < last.mid => -1,
+            Some(_) => return,
+            None => 0,
+        };
+        if direction != 0 {
+            // 找到在窗口内最早同向变动且尚未计数的其他交易所
+            let leader = self.last.iter_mut()
+                .filter(|((leg_venue, _), last)| {
+                    leg_venue != venue
+                        && last.direction == direction
+                        && !last.credited
+                        && local_time.saturating_sub(last.local_time) <= self.window_ms
+                        && last.local_time < local_time
+                })
+                .min_by_key(|(_, last)| last.local_time);
+            if let Some(((leader_venue, _), last)) = leader {
+                last.credited = true;
+                *self.leads.entry(leader_venue.clone()).or_insert(0) += 1;
+            }
+        }
+        self.last.insert(key, MidMove { mid, direction, local_time, credited: false });
+    }
+
+    /// 各交易所的领先次数
+    pub fn lead_counts(&self) -> &HashMap<String, u64> {
+        &self.leads
+    }
+
+    /// 领先次数最多的交易所及其占比
+    pub fn leader(&self) -> Option<(String, f64)> {
+        let total: u64 = self.leads.values().sum();
+        self.leads.iter()
+            .max_by_key(|(_, count)| **count)
+            .map(|(venue, count)| (venue.clone(), *count as f64 / total as f64))
+    }
+}
+
+fn percentile_of(mut samples: Vec<i64>, percentile: f64) -> Option<i64> {
+    if samples.is_empty() {
+        return None;
+    }
+    samples.sort_unstable();
+    let rank = (percentile.clamp(0.0, 100.0) / 100.0 * (samples.len() - 1) as f64).round() as usize;
+    samples.get(rank).copied()
+}
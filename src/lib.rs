@@ -0,0 +1,38 @@
+pub mod binance;
+pub mod order_book;
+pub mod funding;
+pub mod events;
+pub mod kline;
+pub mod ticker;
+pub mod trade;
+pub mod discovery;
+pub mod ws_api;
+pub mod exchange;
+pub mod consolidated;
+pub mod arbitrage;
+pub mod triangular;
+pub mod synthetic;
+pub mod router;
+pub mod fees;
+pub mod instrument;
+pub mod latency;
+pub mod clock;
+pub mod spread;
+pub mod analytics;
+pub mod recorder;
+pub mod checkpoint;
+pub mod replay;
+pub mod fixture;
+pub mod history;
+pub mod verify;
+pub mod proto;
+pub mod sbe;
+pub mod capnp;
+pub mod fix;
+pub mod serve;
+pub mod strategy;
+pub mod metrics;
+pub mod telemetry;
+pub mod profile;
+pub mod tui;
+pub mod manager;
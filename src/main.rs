@@ -1,431 +1,222 @@
-use serde_json::json;
-use tungstenite::{connect, Message, Utf8Bytes};
-use serde::{Deserialize, Serialize};
+mod cli;
+
 use std::error::Error;
-use std::cmp::Ordering;
-use std::collections::BTreeMap;
-use rust_decimal::Decimal;
-use rust_decimal::prelude::*;
+use tracing::error;
 
-/// 有限档深度信息结构体，对应币安深度信息
-#[derive(Debug, Deserialize, Serialize)]
-struct LimitedDepthInfo {
-    lastUpdateId: u64,                // 末次更新ID
-    bids: Vec<[String; 2]>,           // 买单 [价格, 数量]
-    asks: Vec<[String; 2]>,           // 卖单 [价格, 数量]
-}
+use order_book::telemetry;
 
-impl LimitedDepthInfo {
-    /// 打印深度信息摘要
-    /// 
-    /// # 参数
-    /// 
-    /// * `limit` - 要显示的档位数量
-    pub fn print_summary(&self, limit: usize) {
-        // println!("深度信息摘要:");
-        println!("有限深度信息 最后更新 ID: {}", self.lastUpdateId);
-        // println!("买单数量: {}", self.bids.len());
-        // println!("卖单数量: {}", self.asks.len());
-        
-        self.print_bids(limit);
-        // self.print_asks(limit);
-    }
-    
-    /// 打印买单信息（按价格降序）
-    /// 
-    /// # 参数
-    /// 
-    /// * `limit` - 要显示的档位数量
-    pub fn print_bids(&self, limit: usize) {
-        // 转换买单为 (价格, 数量) 元组
-        let mut bids: Vec<(f64, f64)> = self.bids.iter()
-            .map(|bid| {
-                let price = bid[0].parse::<f64>().unwrap_or(0.0);
-                let quantity = bid[1].parse::<f64>().unwrap_or(0.0);
-                (price, quantity)
-            })
-            .collect();
-        
-        // 按价格降序排列
-        bids.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
-        
-        // 打印前N个买单
-        println!("前{}个买单 (价格降序):", limit);
-        for (i, (price, quantity)) in bids.iter().take(limit).enumerate() {
-            println!("{}. 价格: {}, 数量: {}", i+1, price, quantity);
+use cli::{parse_log_file, parse_market, Options};
+
+fn main() {
+    // 命令行参数: [spot|futures|us] [--klines=1m,5m] [--ticker=none|mini|full]
+    //            [--discover=quote=USDT,status=TRADING] [--snapshot-limit=1000] [--snapshot=rest|ws]
+    //            [--depth-display=base|notional|histogram|none]
+    //            [--okx=BTC-USDT,ETH-USDT] [--okx-channel=books|books-l2-tbt|books50-l2-tbt]
+    //            [--kraken=XBT/USD,ETH/USD] [--kraken-depth=10|25|100|500|1000]
+    //            [--coinbase=BTC-USD,ETH-USD] [--coinbase-channel=level2_batch|level2]
+    //            [--bybit-spot=BTCUSDT] [--bybit-linear=BTCUSDT] [--bybit-depth=50|200|500]
+    //            [--bitstamp=btcusd,ethusd] [--kucoin=BTC-USDT,ETH-USDT]
+    //            [--gateio=BTC_USDT,ETH_USDT] [--gateio-interval=100ms|1000ms]
+    //            [--htx=btcusdt,ethusdt] [--htx-levels=5|20|150|400]
+    //            [--deribit=BTC-PERPETUAL,ETH-PERPETUAL] [--deribit-interval=raw|100ms|agg2]
+    //            [--bitget-spot=BTCUSDT] [--bitget-futures=BTCUSDT]
+    //            [--hyperliquid=BTC,ETH] [--dydx=BTC-USD,ETH-USD]
+    //            [--fix=交易对,...] [--fix-host=地址:端口] [--fix-sender=ORDERBOOK] [--fix-target=对方CompID] [--fix-depth=0]，
+    //            作为发起方连接 FIX 4.4 行情服务，订阅交易对的 MarketDataRequest，交易所名称为 fix
+    //            [--consolidate=binance:BTCUSDT,okx:BTC-USDT]（交易对也可写为统一格式 BTC/USDT 或 BTC/USDT-PERP）
+    //            [--arb=binance:BTCUSDT,okx:BTC-USDT] [--arb-min-profit=1]
+    //            [--triangle=BNBBTC,BTCUSDT,BNBUSDT] [--triangle-notional=1000] [--triangle-threshold=5]
+    //            [--synthetic=BNBEUR=BNBUSDT/EURUSDT]
+    //            [--route=binance:BTCUSDT,okx:BTC-USDT] [--route-qty=1]
+    //            [--fees=fees.json] [--taker-fee=binance:10,okx:8]
+    //            [--latency] [--lead-lag=binance:BTCUSDT,okx:BTC-USDT] [--lead-lag-window=500]
+    //            [--clock-sync[=间隔秒]]，定期请求币安服务器时间，估计本地时钟偏差和漂移，行情延迟按校正后的时间计算，默认 60 秒
+    //            [--spread=binance:BTCUSDT,okx:BTC-USDT] [--spread-file=spread.csv] [--spread-interval=1000]
+    //            [--imbalance=档位数:阈值1,阈值2]，例如 --imbalance=5:-0.6,0.6
+    //            [--liquidity-bps=10] [--ofi=滚动窗口毫秒:区间毫秒]，例如 --ofi=10000:1000
+    //            [--volatility=采样间隔毫秒:窗口收益率个数]，例如 --volatility=1000:300
+    //            [--spread-stats=滚动窗口毫秒]，例如 --spread-stats=60000
+    //            [--volume-profile=价格桶宽度:会话毫秒]，例如 --volume-profile=10:86400000
+    //            [--walls=倍数:扫描档位数]，例如 --walls=5:50
+    //            [--update-rate=区间毫秒:标准差倍数]，例如 --update-rate=1000:4
+    //            [--vpin=桶成交量:桶数]，例如 --vpin=10:50
+    //            [--kyle-lambda=区间毫秒:区间数]，例如 --kyle-lambda=1000:300
+    //            [--trade-spread=实现价差间隔毫秒:成交笔数]，例如 --trade-spread=5000:1000
+    //            [--queue=交易对:bid|ask:价格:数量]，例如 --queue=BTCUSDT:bid:65000:0.5
+    //            [--bars=来源:类型:大小,...]，例如 --bars=mid:time:60000,trade:volume:10,trade:tick:100
+    //            [--record=目录] [--record-format=ndjson|msgpack]，按小时把原始消息和深度更新写入 NDJSON 或 MessagePack 文件
+    //            [--capture=目录[:压缩级别[:关键帧间隔秒]]]，按小时写入 zstd 压缩的二进制记录和订单薄关键帧，
+    //            例如 --capture=data:3:60
+    //            [--sqlite=数据库文件[:快照间隔毫秒:快照档位]]，写入深度变动、定期快照和指标，例如 --sqlite=book.db:1000:20
+    //            [--postgres=连接字符串] [--postgres-batch=1000] [--postgres-retries=5]，写入最优价、深度快照和成交，
+    //            例如 --postgres="host=localhost user=postgres dbname=market"
+    //            [--clickhouse=HTTP地址] [--clickhouse-db=default] [--clickhouse-user=用户:密码] [--clickhouse-batch=100000]，
+    //            批量写入全部深度变动，例如 --clickhouse=http://localhost:8123
+    //            [--ilp=tcp://地址|http(s)://写入地址] [--ilp-token=令牌] [--ilp-interval=1000] [--ilp-bps=10]，
+    //            以行协议把价差、中间价、不平衡度、深度和延迟发送到 InfluxDB 或 QuestDB，例如 --ilp=tcp://localhost:9009
+    //            [--nats=服务器地址] [--nats-prefix=book] [--nats-jetstream[=流名称]] [--nats-format=json|protobuf|sbe|capnp]，把增量更新、快照和最优价变化发布到
+    //            book.交易所.交易对.delta|snapshot|top，使用 JetStream 时写入持久化的流（默认 BOOK），例如 --nats=nats://localhost:4222
+    //            [--kafka=服务器列表] [--kafka-topics=增量主题,快照主题] [--kafka-compression=none|gzip|snappy|lz4|zstd]
+    //            [--kafka-delivery=at-most-once|at-least-once|exactly-once] [--kafka-keyframe=秒] [--kafka-format=json|protobuf|sbe|capnp]，
+    //            以交易对为键写入增量更新和快照（Protobuf 定义见 proto/feed.proto，SBE 和 Cap'n Proto 定义见 schema/），
+    //            默认主题 book.deltas、book.snapshots，lz4 压缩，at-least-once，例如 --kafka=localhost:9092 --kafka-keyframe=60
+    //            [--mqtt=mqtt://主机[:端口]] [--mqtt-prefix=order_book] [--mqtt-interval=1000]，按间隔发布保留消息
+    //            前缀/交易所/交易对/top（最优价）和 summary（中间价、价差、不平衡度、深度、更新次数），例如 --mqtt=mqtt://localhost
+    //            [--shm=文件[:槽位数]] [--shm-format=raw|flatbuffers]，每次订单薄更新后把前 10 档写入共享内存环形缓冲区，
+    //            供同一主机的进程用 order_book::recorder::shm::ShmReader 读取；flatbuffers 格式按 schema/book_event.fbs 编码，
+    //            其他语言用 flatc 生成的代码直接读取，例如 --shm=/dev/shm/order_book:16384
+    //            [--arrow-ipc=文件|-]，以 Arrow IPC 流格式输出深度变动，- 表示标准输出（日志同样写到标准输出，
+    //            需要干净的数据流时使用文件或命名管道）
+    //            [--parquet=目录[:快照间隔毫秒:快照档位]]，按小时写入深度变动和定期快照，例如 --parquet=data:1000:20
+    //            [--lobster=目录[:档位[:价格倍数]]]，按日写入 LOBSTER 格式的消息和订单薄 CSV，例如 --lobster=lobster:10:10000
+    //            [--itch=目录[:价格小数位[:数量小数位]]] [--itch-multicast=组地址:端口]，把深度变化转为 ITCH 风格的新增、
+    //            替换、删除订单消息（每个价位视为一个订单），写入二进制文件或以 MoldUDP64 组播，格式见 ItchRecorder，
+    //            例如 --itch=itch:8:8 --itch-multicast=239.1.1.1:30001
+    //            [--grpc=监听地址]，提供 gRPC 服务（proto/order_book.proto），订阅订单薄快照和之后的变化档位，
+    //            例如 --grpc=127.0.0.1:50051
+    //            [--ws-server=监听地址]，WebSocket 服务 ws://地址/?venue=交易所&symbol=交易对&depth=档位&format=json|msgpack
+    //            （参数可省略），连接后推送 JSON 或 MessagePack 快照，之后推送变化的档位，例如 --ws-server=127.0.0.1:8765
+    //            [--dashboard=监听地址]，网页看板：浏览器打开 http://地址/ 查看深度、价差曲线和不平衡度，数据来自 --ws-server
+    //            （需要同时使用），例如 --ws-server=0.0.0.0:8765 --dashboard=0.0.0.0:8088
+    //            [--rest=监听地址]，与币安深度接口兼容的 HTTP 服务 /depth?symbol=交易对&limit=档位（也提供 /api/v3/depth、
+    //            /fapi/v1/depth），数据来自本地订单薄，例如 --rest=127.0.0.1:8080
+    //            [--dump-dir=目录]，收到 SIGUSR1 或 --rest 服务的 POST /admin/dump 时把所有订单薄的完整档位转储为
+    //            目录/交易所-交易对-时间.json，用于排查与交易所的分歧，例如 kill -USR1 进程号
+    //            [--zmq=绑定地址] [--zmq-snapshot=10]，ZeroMQ PUB 发布，主题为 交易所.交易对.snapshot|update，
+    //            内容为 JSON，每隔若干秒重新发送快照，例如 --zmq=tcp://*:5556
+    //            [--redis=地址] [--redis-prefix=book] [--redis-depth=20]，在 Redis 中维护每个订单薄的前若干档（有序集合
+    //            前缀:交易所:交易对:bids|asks），更新后在频道 前缀:交易所:交易对 发布通知，例如 --redis=redis://127.0.0.1:6379
+    //            [--uds=套接字文件]，Unix 域套接字服务，推送 4 字节大端长度前缀的 JSON 帧（先快照后变化档位），
+    //            例如 --uds=/tmp/order_book.sock
+    //            [--fix-server=监听地址]，FIX 4.4 行情服务，CompID 为 --fix-sender，客户端登录后用 MarketDataRequest
+    //            订阅交易对（SecurityExchange 指定交易所），收到 MarketDataSnapshotFullRefresh 和之后的 IncrementalRefresh，
+    //            例如 --fix-server=0.0.0.0:9878
+    //            [--serve-depth=100]，对外服务的每侧最大档位数
+    //            [--metrics=监听地址]，在 /metrics 提供 Prometheus 格式的运行指标：收到的消息、应用的增量更新、序号缺口、
+    //            重新同步、重连次数，最优价、价差、档位数和处理耗时直方图，按交易所和交易对区分，例如 --metrics=0.0.0.0:9100；
+    //            同一端口的 /latency 以 JSON 返回各交易对行情延迟（事件时间到本地接收）的 p50、p99 和最大值，
+    //            /churn 返回各交易对每秒更新数、每个区间新增/删除/修改的档位数和消息大小（档位数），
+    //            /healthz 在进程运行时返回 200，/readyz 在所有配置的订单薄都已同步且最近有更新时返回 200，否则返回 503
+    //            /profile 返回热路径各阶段耗时，需要 --profile 开启
+    //            [--profile[=窗口秒]]，按阶段统计热路径耗时：读取消息、解析 JSON、转换 Decimal、更新订单薄和写入输出，默认窗口 10 秒
+    //            [--tui]，终端界面：以中间价为中心的深度阶梯（价格、买量、卖量）、价差、最后更新 ID 和同步状态，
+    //            代替打印订单薄，日志只写入 --log-file（没有时丢弃），按 q 退出
+    //            [--ready-max-age=30]，订单薄超过该秒数没有更新时 /readyz 视为过期
+    //            [--churn-interval=10000]，更新频率和档位变化的统计区间（毫秒），有 --metrics 或 --statsd 时统计
+    //            [--statsd=地址:端口] [--statsd-format=dogstatsd|statsd] [--statsd-prefix=前缀]，通过 UDP 把同一组指标
+    //            发送到 StatsD 或 DogStatsD（Datadog Agent），DogStatsD 以标签区分交易所和交易对，原始 StatsD 把标签值拼接到指标名后
+    //            [--checkpoint=文件[:间隔秒]]，定期保存币安订单薄，重启时载入并从实时更新继续，例如 --checkpoint=book.ckpt:10
+    //            [--warm-start=记录文件或目录]，启动时用记录末尾重建币安订单薄，检查与实时更新的衔接，不能衔接时重新获取快照；
+    //            目录时使用最新的记录文件，与 --checkpoint 同时使用时以记录为准，例如 --warm-start=data
+    //            [--strategy=imbalance:交易所:交易对:阈值:数量:持仓上限]，按实时行情模拟交易，退出时打印结果，
+    //            例如 --strategy=imbalance:binance:BNBUSDT:0.6:1:5
+    //            [交易对[:速度[:档位]]...]，默认现货 BNBUSDT
+    // 子命令: impact-curve [spot|futures|us] 交易对 [--side=buy|sell] [--max-notional=100000]
+    //            [--step=1000] [--out=impact.csv]，导出市场冲击曲线后退出
+    //       export [spot|futures] --symbol=交易对 [--depth=100] [--format=csv] [--out=depth.csv]，拉取当前深度快照，
+    //            每档一行写入 price,quantity,side,cumulative_quantity,notional，买卖两侧都从最优价开始累计
+    //       query --data=文件或目录,... "SQL"，用内嵌的 DuckDB 在记录的 NDJSON、压缩记录和 Parquet 文件上执行 SQL，
+    //            表为 deltas、snapshots 和视图 top_of_book，例如按小时统计平均价差:
+    //            query --data=data "SELECT strftime(epoch_ms(time), '%Y-%m-%d %H:00') AS hour,
+    //            symbol, AVG(best_ask - best_bid) AS spread FROM top_of_book GROUP BY hour, symbol"
+    //       replay --file=记录文件 [--speed=1x|10x|max] [--from=毫秒时间戳] [其他参数...]，按记录时间回放 NDJSON、MessagePack
+    //            或压缩记录文件中的深度更新，经过与实时行情相同的订单薄和分析流程，不连接交易所；
+    //            回放中输入 p 暂停/继续，s 时间戳 或 s +秒/-秒 跳转，x 速度 修改速度，q 结束
+    //       replay、book-at、backtest、lobster 和 fixture 的记录文件也可以是 Tardis.dev 的标准化 CSV（incremental_book_L2、trades）或币安原始消息
+    //            文件（.csv、.txt，可为 .gz 压缩），例如 replay --file=binance_incremental_book_L2_2024-01-01_BTCUSDT.csv.gz
+    //       book-at --file=记录文件 --venue=交易所 --symbol=交易对 --time=毫秒时间戳 [--depth=20] [--out=book.json|book.book]，
+    //            重建指定时刻的订单薄，打印前若干档并可导出为 JSON 或二进制订单薄文件（.book，见 OrderBook::load_from）；压缩记录借助关键帧和索引只解压需要的块
+    //       backtest --file=记录文件 --strategy=策略 [--fees=fees.json] [--taker-fee=binance:10]，以记录时间为模拟时钟
+    //            在记录的深度更新和成交上运行策略，市价单按当时的订单薄成交，打印成交、手续费、盈亏和最大回撤
+    //       verify 记录文件...，检查压缩记录或 NDJSON 文件的截断、损坏和索引一致性，重放深度记录检查序号连续性，
+    //            打印每个订单薄的时间范围、消息数、断开次数和关键帧一致性，有问题时以非零状态退出
+    //       compact --file=记录文件 --out=目录 [--depth=档位] [--keyframe=关键帧间隔秒] [--level=压缩级别] [--keep-raw]，
+    //            重放记录并改写为压缩记录：增量更新只保留前若干档的变化，按新的间隔重新写入关键帧，默认丢弃原始消息帧
+    //       history [spot|futures] 交易对 --from=YYYY-MM-DD [--to=YYYY-MM-DD] [--data=bookTicker,aggTrades] [--out=history]，
+    //            从 data.binance.vision 下载并解压历史数据，每天合并为一个 NDJSON 记录文件，最优挂单转为一档订单薄快照，
+    //            可直接用于 replay、book-at 和 backtest；现货默认只下载 aggTrades，合约默认两种都下载
+    //       itch --file=记录文件 --out=目录 [--price-decimals=8] [--quantity-decimals=8]，把记录文件转为 ITCH 风格的二进制文件
+    //       lobster --file=记录文件 --out=目录 [--levels=10] [--price-scale=10000]，把记录文件转为 LOBSTER 格式的
+    //            消息和订单薄 CSV
+    //       fixture --file=记录文件 --venue=交易所 --symbol=交易对 [--market=spot|futures|us] [--from=毫秒时间戳] [--deltas=100] --out=fixture.json，
+    //            截取一个关键帧或快照和之后的若干条增量更新，连同应用后订单薄的哈希保存为回归测试数据；
+    //            币安订单薄保存深度快照和原始消息帧，校验时经过实时同步流程，需要记录原始消息帧并用 --market 指定市场；
+    //            fixture --check=文件或目录,... 重新应用测试数据并比较结果，有不一致时以非零状态退出
+    //       日志级别由环境变量 RUST_LOG 控制，默认 info，可按模块设置，例如
+    //            RUST_LOG=info,order_book::manager=debug,order_book::exchange=warn
+    //       [--otlp=http://127.0.0.1:4317]，通过 OTLP gRPC 发送每条行情消息 receive → parse → apply → publish 各阶段的 span，
+    //            写入 Kafka、NATS 的消息头带有 traceparent，可在 Jaeger、Tempo 中查看消息在进程内的耗时
+    //       [--log-file=logs/order_book.log] [--log-rotate=daily|hourly|never] [--log-max-size=MB] [--log-keep=7]，
+    //            日志以 JSON 行写入文件，不再输出到标准输出，便于把数据输出重定向到管道；按 UTC 日期或小时轮转，
+    //            超过大小时也轮转，保留最近若干个已轮转文件（0 为全部保留）
+    let (options, args): (Vec<String>, Vec<String>) = std::env::args().skip(1)
+        .partition(|arg| arg.starts_with("--"));
+    let options = Options::new(options);
+    // 终端界面占用整个终端，日志只能写入文件
+    let tui = options.flag("tui");
+    let log_file = match parse_log_file(&options) {
+        Ok(log_file) => log_file,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
         }
-        println!();
-    }
-    
-    /// 打印卖单信息（按价格升序）
-    /// 
-    /// # 参数
-    /// 
-    /// * `limit` - 要显示的档位数量
-    pub fn print_asks(&self, limit: usize) {
-        // 转换卖单为 (价格, 数量) 元组
-        let mut asks: Vec<(f64, f64)> = self.asks.iter()
-            .map(|ask| {
-                let price = ask[0].parse::<f64>().unwrap_or(0.0);
-                let quantity = ask[1].parse::<f64>().unwrap_or(0.0);
-                (price, quantity)
-            })
-            .collect();
-        
-        // 按价格升序排列
-        asks.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
-        
-        // 打印前N个卖单
-        println!("\n前{}个卖单 (价格升序):", limit);
-        for (i, (price, quantity)) in asks.iter().take(limit).enumerate() {
-            println!("{}. 价格: {}, 数量: {}", i+1, price, quantity);
+    };
+    let logs_discarded = tui && log_file.is_none();
+    let telemetry = match telemetry::init(options.value("otlp"), log_file, !tui) {
+        Ok(telemetry) => telemetry,
+        Err(e) => {
+            eprintln!("初始化日志失败: {}", e);
+            std::process::exit(1);
         }
-    }
-    
-    /// 打印市场深度信息（同时展示买卖盘）
-    /// 
-    /// # 参数
-    /// 
-    /// * `limit` - 要显示的档位数量
-    pub fn print_market_depth(&self, limit: usize) {
-        // 转换买单和卖单为 (价格, 数量) 元组
-        let mut bids: Vec<(f64, f64)> = self.bids.iter()
-            .map(|bid| {
-                let price = bid[0].parse::<f64>().unwrap_or(0.0);
-                let quantity = bid[1].parse::<f64>().unwrap_or(0.0);
-                (price, quantity)
-            })
-            .collect();
-        
-        let mut asks: Vec<(f64, f64)> = self.asks.iter()
-            .map(|ask| {
-                let price = ask[0].parse::<f64>().unwrap_or(0.0);
-                let quantity = ask[1].parse::<f64>().unwrap_or(0.0);
-                (price, quantity)
-            })
-            .collect();
-        
-        // 排序
-        bids.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
-        asks.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
-        
-        println!("\n市场深度信息 (深度: {}):", limit);
-        println!("{:<5} {:<15} {:<15} | {:<15} {:<15} {:<5}", 
-                 "档位", "买单价格", "买单数量", "卖单价格", "卖单数量", "档位");
-        println!("{:-<70}", "");
-        
-        for i in 0..limit {
-            let bid_info = if i < bids.len() {
-                format!("{:<15.8} {:<15.8}", bids[i].0, bids[i].1)
-            } else {
-                format!("{:<15} {:<15}", "-", "-")
-            };
-            
-            let ask_info = if i < asks.len() {
-                format!("{:<15.8} {:<15.8}", asks[i].0, asks[i].1)
-            } else {
-                format!("{:<15} {:<15}", "-", "-")
-            };
-            
-            println!("{:<5} {} | {} {:<5}", i+1, bid_info, ask_info, i+1);
+    };
+    let (failure, result) = dispatch(args, &options);
+    if let Err(e) = result {
+        // 终端界面没有写日志文件时日志已丢弃，只能打印到标准错误
+        if logs_discarded {
+            eprintln!("{}: {}", failure, e);
+        } else {
+            error!(error = %e, "{}", failure);
         }
+        // 退出前刷新日志和追踪数据
+        drop(telemetry);
+        std::process::exit(1);
     }
 }
 
-/// 深度更新事件结构体，对应币安WebSocket深度更新消息
-#[derive(Debug, Deserialize, Serialize)]
-struct DepthUpdate {
-    e: String,             // 事件类型
-    E: u64,                // 事件时间
-    s: String,             // 交易对
-    U: u64,                // 从上次推送至今新增的第一个update Id
-    u: u64,                // 从上次推送至今新增的最后一个update Id
-    b: Vec<[String; 2]>,   // 变动的买单深度 [价格, 数量]
-    a: Vec<[String; 2]>,   // 变动的卖单深度 [价格, 数量]
-}
-
-/// 深度快照结构体，对应币安REST API深度快照
-#[derive(Debug, Deserialize, Serialize)]
-struct DepthSnapshot {
-    lastUpdateId: u64,
-    bids: Vec<[String; 2]>,
-    asks: Vec<[String; 2]>,
-}
-
-/// 订单薄结构体，包含买单和卖单
-#[derive(Debug)]
-struct OrderBook {
-    last_update_id: u64,
-    /// 买单映射 (价格 -> 数量)
-    bids: BTreeMap<Decimal, Decimal>,
-    /// 卖单映射 (价格 -> 数量)
-    asks: BTreeMap<Decimal, Decimal>,
-}
-
-impl OrderBook {
-    /// 从深度快照创建订单薄
-    fn from_snapshot(snapshot: DepthSnapshot) -> Result<Self, Box<dyn Error>> {
-        // 创建BTreeMap用于买单和卖单
-        let mut bids = BTreeMap::new();
-        let mut asks = BTreeMap::new();
-        
-        // 处理买单，转换字符串为Decimal并插入到映射中
-        for bid in snapshot.bids {
-            let price = bid[0].parse::<Decimal>()?;
-            let quantity = bid[1].parse::<Decimal>()?;
-            if !quantity.is_zero() {
-                bids.insert(price, quantity);
-            }
+/// 按第一个位置参数选择子命令并执行，返回失败时的说明和执行结果
+fn dispatch(args: Vec<String>, options: &Options) -> (&'static str, Result<(), Box<dyn Error>>) {
+    let mut args = args.into_iter().peekable();
+    let command = args.next_if(|arg| {
+        ["replay", "book-at", "backtest", "verify", "compact", "lobster", "itch", "fixture", "query", "impact-curve", "history", "export"]
+            .contains(&arg.as_str())
+    });
+    match command.as_deref() {
+        Some("book-at") => ("重建订单薄失败", cli::book_at::run(options)),
+        Some("backtest") => ("回测失败", cli::backtest::run(options)),
+        Some("verify") => ("检查记录失败", cli::verify::run(&args.collect::<Vec<_>>())),
+        Some("compact") => ("压缩记录失败", cli::compact::run(options)),
+        Some("lobster") => ("导出 LOBSTER 文件失败", cli::lobster::run(options)),
+        Some("itch") => ("导出 ITCH 文件失败", cli::itch::run(options)),
+        Some("fixture") => ("测试数据失败", cli::fixture::run(options)),
+        Some("query") => ("查询失败", cli::query::run(options, &args.collect::<Vec<_>>().join(" "))),
+        Some("impact-curve") => {
+            let market = parse_market(&mut args);
+            let result = args.next()
+                .ok_or_else(|| "impact-curve 需要指定交易对".into())
+                .and_then(|symbol| cli::impact_curve::run(market, &symbol.to_uppercase(), options));
+            ("导出冲击曲线失败", result)
         }
-        
-        // 处理卖单，转换字符串为Decimal并插入到映射中
-        for ask in snapshot.asks {
-            let price = ask[0].parse::<Decimal>()?;
-            let quantity = ask[1].parse::<Decimal>()?;
-            if !quantity.is_zero() {
-                asks.insert(price, quantity);
-            }
-        }
-        
-        // 创建订单薄实例
-        let order_book = OrderBook {
-            last_update_id: snapshot.lastUpdateId,
-            bids,
-            asks,
-        };
-        
-        Ok(order_book)
-    }
-    
-    /// 应用深度更新到订单薄
-    fn apply_depth_update(&mut self, update: &DepthUpdate) -> Result<(), Box<dyn Error>> {
-        // 如果快照中的 lastUpdateId 小于等于步骤 2 中的 U 值，请返回步骤 3。
-        // println!("当前self u {}",self.last_update_id);
-        if  self.last_update_id < update.u {
-            // 更新买单
-            for bid in &update.b {
-                let price = bid[0].parse::<Decimal>()?;
-                let quantity = bid[1].parse::<Decimal>()?;
-                
-                if quantity.is_zero() {
-                    // 数量为0表示删除此价格的订单
-                    self.bids.remove(&price);
-                } else {
-                    // 更新或添加此价格的订单
-                    self.bids.insert(price, quantity);
-                }
-            }
-            
-            // 更新卖单
-            for ask in &update.a {
-                let price = ask[0].parse::<Decimal>()?;
-                let quantity = ask[1].parse::<Decimal>()?;
-                
-                if quantity.is_zero() {
-                    // 数量为0表示删除此价格的订单
-                    self.asks.remove(&price);
-                } else {
-                    // 更新或添加此价格的订单
-                    self.asks.insert(price, quantity);
-                }
-            }
-            
-            // 更新最后更新ID
-            self.last_update_id = update.u;
-            Ok(())
-        } else {
-            Err("深度更新ID不连续，需要重新获取快照".into())
-        }
-    }
-    
-    /// 获取买单列表（按价格降序排列）
-    fn bids_list(&self) -> Vec<(Decimal, Decimal)> {
-        let mut bids: Vec<(Decimal, Decimal)> = self.bids.iter()
-            .map(|(price, quantity)| (*price, *quantity))
-            .collect();
-        
-        // 按价格降序排列
-        bids.sort_by(|a, b| b.0.cmp(&a.0));
-        bids
-    }
-    
-    /// 获取卖单列表（按价格升序排列）
-    fn asks_list(&self) -> Vec<(Decimal, Decimal)> {
-        // BTreeMap已经按键升序排列，所以不需要额外排序
-        self.asks.iter()
-            .map(|(price, quantity)| (*price, *quantity))
-            .collect()
-    }
-    
-    /// 打印订单薄信息
-    fn print_summary(&self, limit: usize) {
-        // println!("订单薄信息:");
-        println!("订单薄信息 最后更新 ID: {}", self.last_update_id);
-        // println!("买单数量: {}", self.bids.len());
-        // println!("卖单数量: {}", self.asks.len());
-        
-        // 打印前N个买单（价格降序）
-        println!("前{}个买单 (价格降序):", limit);
-        for (i, (price, quantity)) in self.bids_list().iter().take(limit).enumerate() {
-            println!("{}. 价格: {}, 数量: {}", i+1, price, quantity);
+        Some("history") => {
+            let market = parse_market(&mut args);
+            let result = args.next()
+                .ok_or_else(|| "history 需要指定交易对".into())
+                .and_then(|symbol| cli::history::run(market, &symbol, options));
+            ("下载历史数据失败", result)
         }
-        
-        // 打印前N个卖单（价格升序）
-        // println!("\n前{}个卖单 (价格升序):", limit);
-        // for (i, (price, quantity)) in self.asks_list().iter().take(limit).enumerate() {
-        //     println!("{}. 价格: {}, 数量: {}", i+1, price, quantity);
-        // }
-        println!();
-    }
-    
-    /// 获取最高买价
-    fn best_bid(&self) -> Option<(Decimal, Decimal)> {
-        self.bids.iter()
-            .max_by(|a, b| a.0.cmp(b.0))
-            .map(|(price, quantity)| (*price, *quantity))
-    }
-    
-    /// 获取最低卖价
-    fn best_ask(&self) -> Option<(Decimal, Decimal)> {
-        self.asks.iter()
-            .min_by(|a, b| a.0.cmp(b.0))
-            .map(|(price, quantity)| (*price, *quantity))
-    }
-    
-    /// 获取买卖价差
-    fn spread(&self) -> Option<Decimal> {
-        match (self.best_bid(), self.best_ask()) {
-            (Some((bid_price, _)), Some((ask_price, _))) => Some(ask_price - bid_price),
-            _ => None,
+        Some("export") => ("导出深度失败", cli::export::run(parse_market(&mut args), options)),
+        command => {
+            let market = parse_market(&mut args);
+            ("运行失败", cli::run::run(market, args.collect(), command == Some("replay"), options))
         }
     }
 }
-
-/// 获取币安交易所的深度快照数据
-/// 
-/// # 参数
-/// 
-/// * `symbol` - 交易对符号，例如 "BNBBTC"
-/// * `limit` - 返回的深度级别，可选值：5, 10, 20, 50, 100, 500, 1000, 5000
-/// 
-/// # 返回值
-/// 
-/// 返回 Result，成功时包含 DepthSnapshot 结构体，失败时包含错误信息
-fn get_depth_snapshot(symbol: &str, limit: u32) -> Result<DepthSnapshot, Box<dyn Error>> {
-    let url = format!(
-        "https://api.binance.com/api/v3/depth?symbol={}&limit={}",
-        symbol, limit
-    );
-    
-    println!("正在请求深度数据: {}", url);
-    
-    // 使用 reqwest 的阻塞客户端发送请求
-    let client = reqwest::blocking::Client::new();
-    let response = client.get(&url).send()?;
-    
-    if response.status().is_success() {
-        let snapshot: DepthSnapshot = response.json()?;
-        Ok(snapshot)
-    } else {
-        Err(format!("API 请求失败: {}", response.status()).into())
-    }
-}
-
-fn main() {
-    // 获取深度快照示例
-
-    // WebSocket深度更新示例（注释掉的代码）
-    let subscribe = json!({
-        "method": "SUBSCRIBE",
-        "params": ["bnbusdt@depth@100ms","bnbusdt@depth20@100ms"],
-        "id": 1
-    }).to_string();
-
-    match connect("wss://stream.binance.com:9443/ws") {
-        Ok((mut socket, response)) => {
-            if response.status().as_u16() == 101 {
-                // 订阅深度更新
-                if let Ok(_) = socket.send(Message::Text(Utf8Bytes::from(subscribe))) {
-
-                    let mut  order_book: Option<OrderBook> = None;
-                    loop {
-                         match socket.read(){
-                            Ok(Message::Text(msg)) => {
-                                if msg.contains(r#""lastUpdateId""#) {
-                                    // println!("{}",msg);
-                                   match serde_json::from_str::<LimitedDepthInfo>(&msg){
-                                       Ok(limiteddepthinfo) => {
-                                           // println!("收到有限深度信息: {:?}", limiteddepthinfo)
-                                           limiteddepthinfo.print_summary(20);
-                                       }
-                                       Err(_) => {
-                                           println!("无法解析有限深度信息")
-                                       }
-                                   }
-                                }
-                                // println!("收到消息: {}", msg);
-                               if msg.contains(r#""e":"depthUpdate""#) {
-
-                                   match serde_json::from_str::<DepthUpdate>(&msg) {
-                                       Ok(update) => {
-                                           // println!("收到深度更新ID u: {} U {}", update.u,update.U);
-                                           if let  Some(ref mut o_b) = order_book {
-
-                                               match o_b.apply_depth_update(&update){
-                                                   Ok(_) => {
-                                                       // println!("订单薄更新成功");
-                                                       o_b.print_summary(1000);
-                                                   }
-                                                   Err(e) => {
-                                                       println!("{}", e)
-                                                   }
-                                               }
-                                           }else {
-                                               match get_depth_snapshot("BNBUSDT",5000) {
-                                                   Ok(snapshot) => {
-                                                       match OrderBook::from_snapshot(snapshot) {
-                                                           Ok(mut ob) => {
-
-                                                               // println!("当前e的 U{} u{} ob u{}",update.U,update.u,ob.last_update_id);
-                                                               //如果event U (第一次更新 ID) > 您本地order book的更新 ID，则说明出现问题。请丢弃您的本地order book并从头开始开始重建。
-                                                               if update.U < ob.last_update_id && ob.last_update_id > update.u {
-                                                                   println!("创建order book");
-                                                                   ob.last_update_id = update.u;
-                                                                   order_book = Some(ob);
-                                                               }
-
-                                                           }
-                                                           Err(e) => {
-                                                               println!("创建订单薄失败{}",e);
-                                                           }
-                                                       }
-                                                   },
-                                                   Err(e) => {
-                                                       println!("获取深度快照失败: {}", e)
-                                                   }
-                                               }
-                                           }
-                                           // 这里可以处理更新数据
-                                       },
-                                       Err(e) => {
-                                           println!("解析深度更新失败: {} {}", e,msg);
-                                       }
-                                   }
-
-                               }
-                            }
-                            Err(e) => {
-                                println!("读取WebSocket消息失败: {}", e);
-                            }
-                            _ => {}
-                        };
-                    }
-
-                }
-            } else {}
-        },
-        Err(e) => {
-            println!("WebSocket连接失败: {}", e);
-        }
-    };
-}
@@ -0,0 +1,1471 @@
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+use rust_decimal::Decimal;
+use tracing::{debug, debug_span, error, info, trace, warn};
+
+use crate::analytics::bars::{BarBuilder, BarSource};
+use crate::analytics::churn::{ChurnMonitor, LevelChanges};
+use crate::analytics::imbalance::ImbalanceMonitor;
+use crate::analytics::kyle_lambda::KyleLambdaEstimator;
+use crate::analytics::ofi::OfiCalculator;
+use crate::analytics::queue::{QueuePositionEstimator, RestingOrder};
+use crate::analytics::spread_stats::SpreadTracker;
+use crate::analytics::trade_spread::TradeSpreadAnalyzer;
+use crate::analytics::update_rate::UpdateRateMonitor;
+use crate::analytics::volatility::VolatilityTracker;
+use crate::analytics::volume_profile::VolumeProfile;
+use crate::analytics::vpin::VpinCalculator;
+use crate::analytics::wall::WallDetector;
+use crate::arbitrage::ArbitrageDetector;
+use crate::binance::{get_funding_rate_history, AggTradeEvent, is_partial_depth_stream, DepthSnapshot, DepthUpdate, ForceOrderEvent, KlineEvent, LimitedDepthInfo, Market, MarkPriceUpdate, MiniTickerEvent, StreamMessage, SymbolConfig, TickerEvent};
+use crate::checkpoint::{BookCheckpoint, BookDump, Checkpoint};
+use crate::clock::ClockSkew;
+use crate::consolidated::ConsolidatedBook;
+use crate::events::{LiquidationEvent, MarketEvent};
+use crate::fees::FeeSchedule;
+use crate::exchange::{Continuity, DepthKind, DepthMessage, RawFrame};
+use crate::funding::FundingInfo;
+use crate::kline::{Candle, CandleSeries};
+use crate::latency::{now_millis, FeedLatency, LatencyMonitor, LeadLagTracker};
+use crate::metrics;
+use crate::order_book::{DepthDisplay, MarkPrice, OrderBook, Side};
+use crate::profile::{self, Stage};
+use crate::recorder::{retain_recorders, Record, Recorder};
+use crate::serve::health::Readiness;
+use crate::triangular::TriangularScanner;
+use crate::spread::SpreadRecorder;
+use crate::strategy::{BacktestReport, Fill, Strategy, StrategyRunner};
+use crate::synthetic::SyntheticPair;
+use crate::ticker::{Ticker24h, TickerStream};
+use crate::trade::Trade;
+use crate::ws_api::{SnapshotFetcher, SnapshotSource};
+
+/// 币安在多交易所组件中使用的交易所名称
+pub const BINANCE_VENUE: &str = "binance";
+/// 订单薄建立之前每个交易对最多缓存的增量更新数，超出时丢弃最早的更新
+const MAX_PENDING_UPDATES: usize = 1000;
+/// 同时请求历史资金费率的交易对数量
+const FUNDING_HISTORY_CONCURRENCY: usize = 4;
+
+/// 单个交易对的本地状态
+#[derive(Debug)]
+pub struct SymbolState {
+    /// 订阅配置
+    pub config: SymbolConfig,
+    /// 最新有限档深度
+    pub partial_depth: Option<LimitedDepthInfo>,
+    /// 本地订单薄，获取快照并对齐之前为 None
+    pub book: Option<OrderBook>,
+    /// 最新标记价格（仅合约），在订单薄建立之前也会保存
+    pub mark_price: Option<MarkPrice>,
+    /// 资金费率（仅合约）
+    pub funding: FundingInfo,
+    /// K线周期 -> K线序列
+    pub klines: HashMap<String, CandleSeries>,
+    /// 最新24小时统计
+    pub ticker: Option<Ticker24h>,
+    /// 当前会话的成交量分布，未启用时为 None
+    pub volume_profile: Option<VolumeProfile>,
+    /// 订单薄来自检查点，尚未用实时增量更新确认能否衔接
+    pub from_checkpoint: bool,
+    /// 订单薄建立之前缓存的增量更新，等待快照衔接
+    pending_updates: VecDeque<DepthUpdate>,
+    /// 已取回、还没有能衔接的增量更新的快照
+    pending_snapshot: Option<DepthSnapshot>,
+}
+
+impl SymbolState {
+    fn new(config: SymbolConfig) -> Self {
+        SymbolState {
+            config,
+            partial_depth: None,
+            book: None,
+            mark_price: None,
+            funding: FundingInfo::default(),
+            klines: HashMap::new(),
+            ticker: None,
+            volume_profile: None,
+            from_checkpoint: false,
+            pending_updates: VecDeque::new(),
+            pending_snapshot: None,
+        }
+    }
+}
+
+/// 订单薄管理器，按交易对维护本地订单薄及相关行情
+#[derive(Debug)]
+pub struct BookManager {
+    market: Market,
+    /// 交易对(大写) -> 本地状态
+    symbols: HashMap<String, SymbolState>,
+    /// 订阅的K线周期，例如 ["1m"]
+    kline_intervals: Vec<String>,
+    /// 订阅的24小时行情流
+    ticker_stream: TickerStream,
+    /// 成交量分布的 (价格桶宽度, 会话毫秒)，启用时订阅归集成交流
+    volume_profile: Option<(Decimal, u64)>,
+    /// 在后台线程中获取深度快照
+    snapshots: SnapshotFetcher,
+    /// 深度展示方式
+    depth_display: DepthDisplay,
+    /// 其他交易所的订单薄：交易所 -> 交易对 -> 订单薄
+    venue_books: HashMap<String, HashMap<String, OrderBook>>,
+    /// 跨交易所套利检测器
+    arbitrage: Vec<ArbitrageDetector>,
+    /// 交易所内三角套利扫描器
+    triangular: Vec<TriangularScanner>,
+    /// 各交易所行情延迟
+    latency: LatencyMonitor,
+    /// 各交易对行情延迟直方图，与指标服务共享
+    feed_latency: Arc<FeedLatency>,
+    /// 更新频率和档位变化统计，与指标服务共享
+    churn: Option<Arc<ChurnMonitor>>,
+    /// 订单薄同步状态，供 `/readyz` 判断是否就绪
+    readiness: Option<Arc<Readiness>>,
+    /// 本地时钟偏差估计，设置后延迟统计使用校正后的接收时间
+    clock_skew: Option<Arc<ClockSkew>>,
+    /// 价格发现领先者统计
+    lead_lag: Vec<LeadLagTracker>,
+    /// 跨交易所价差记录器
+    spread_recorders: Vec<SpreadRecorder>,
+    /// 买卖盘不平衡度监控
+    imbalance: Option<ImbalanceMonitor>,
+    /// 订单流不平衡计算器
+    ofi: Option<OfiCalculator>,
+    /// 中间价已实现波动率
+    volatility: Option<VolatilityTracker>,
+    /// 买卖价差统计
+    spread_stats: Option<SpreadTracker>,
+    /// 挂单墙检测
+    walls: Option<WallDetector>,
+    /// 深度更新频率异常检测
+    update_rate: Option<UpdateRateMonitor>,
+    /// VPIN，启用时订阅归集成交流
+    vpin: Option<VpinCalculator>,
+    /// Kyle's lambda 价格冲击估计，启用时订阅归集成交流
+    kyle_lambda: Option<KyleLambdaEstimator>,
+    /// 成交的有效价差和实现价差，启用时订阅归集成交流
+    trade_spread: Option<TradeSpreadAnalyzer>,
+    /// 假想挂单排队位置估计，启用时订阅归集成交流
+    queue: Option<QueuePositionEstimator>,
+    /// OHLCV bar 生成器
+    bar_builders: Vec<BarBuilder>,
+    /// 原始消息和深度更新记录器
+    recorders: Vec<Box<dyn Recorder>>,
+    /// 模拟交易策略
+    strategies: Vec<StrategyRunner>,
+    /// 待消费的行情事件
+    events: Vec<MarketEvent>,
+    /// 回放时的模拟时钟（毫秒），为 None 时使用系统时间
+    clock: Option<u64>,
+}
+
+impl BookManager {
+    /// 创建管理器
+    ///
+    /// # 参数
+    ///
+    /// * `market` - 市场类型
+    /// * `symbols` - 各交易对的订阅配置
+    pub fn new(market: Market, symbols: &[SymbolConfig]) -> Self {
+        let symbols = symbols.iter()
+            .map(|config| (config.symbol.clone(), SymbolState::new(config.clone())))
+            .collect();
+        BookManager {
+            market,
+            symbols,
+            kline_intervals: vec!["1m".to_string()],
+            ticker_stream: TickerStream::Mini,
+            volume_profile: None,
+            snapshots: SnapshotFetcher::new(market, SnapshotSource::Rest),
+            depth_display: DepthDisplay::Base,
+            venue_books: HashMap::new(),
+            arbitrage: Vec::new(),
+            triangular: Vec::new(),
+            latency: LatencyMonitor::default(),
+            feed_latency: Arc::new(FeedLatency::default()),
+            clock_skew: None,
+            churn: None,
+            readiness: None,
+            lead_lag: Vec::new(),
+            spread_recorders: Vec::new(),
+            imbalance: None,
+            ofi: None,
+            volatility: None,
+            spread_stats: None,
+            walls: None,
+            update_rate: None,
+            vpin: None,
+            kyle_lambda: None,
+            trade_spread: None,
+            queue: None,
+            bar_builders: Vec::new(),
+            recorders: Vec::new(),
+            strategies: Vec::new(),
+            events: Vec::new(),
+            clock: None,
+        }
+    }
+
+    /// 设置模拟时钟，之后的处理以该时间代替系统时间，回放记录时使用
+    pub fn set_clock(&mut self, time: u64) {
+        self.clock = Some(time);
+    }
+
+    /// 当前时间（毫秒），设置了模拟时钟时返回模拟时间
+    pub fn now(&self) -> u64 {
+        self.clock.unwrap_or_else(now_millis)
+    }
+
+    /// 清空所有订单薄，回放向前跳转时从头重建使用，各分析指标的累计状态不受影响
+    pub fn reset_books(&mut self) {
+        for state in self.symbols.values_mut() {
+            state.book = None;
+            state.from_checkpoint = false;
+            state.pending_updates.clear();
+            state.pending_snapshot = None;
+        }
+        self.venue_books.clear();
+    }
+
+    /// 行情连接中断，丢弃连接上的订单薄
+    ///
+    /// 币安订单薄在重连后收到增量更新时重新获取快照，其他交易所在重新订阅后由快照重建；
+    /// 重新同步之前订单薄不算就绪
+    pub fn feed_disconnected(&mut self, venue: &str, symbols: &[String]) {
+        for symbol in symbols {
+            if venue == BINANCE_VENUE {
+                let Some(state) = self.symbols.get_mut(symbol) else {
+                    continue;
+                };
+                state.book = None;
+                state.from_checkpoint = false;
+                state.pending_updates.clear();
+                state.pending_snapshot = None;
+            } else if let Some(books) = self.venue_books.get_mut(venue) {
+                books.remove(symbol);
+            }
+            if let Some(readiness) = &self.readiness {
+                readiness.book_lost(venue, symbol);
+            }
+        }
+    }
+
+    /// 市场类型
+    pub fn market(&self) -> Market {
+        self.market
+    }
+
+    /// 设置订阅的K线周期，例如 ["1m", "5m"]，为空则不订阅K线
+    pub fn set_kline_intervals(&mut self, intervals: &[String]) {
+        self.kline_intervals = intervals.to_vec();
+    }
+
+    /// 设置订阅的24小时行情流类型
+    pub fn set_ticker_stream(&mut self, ticker_stream: TickerStream) {
+        self.ticker_stream = ticker_stream;
+    }
+
+    /// 启用成交量分布，订阅各交易对的归集成交流
+    ///
+    /// # 参数
+    ///
+    /// * `bucket_size` - 价格桶宽度
+    /// * `session_ms` - 会话长度（毫秒）
+    pub fn set_volume_profile(&mut self, bucket_size: Decimal, session_ms: u64) {
+        self.volume_profile = Some((bucket_size, session_ms));
+    }
+
+    /// 获取交易对当前会话的成交量分布
+    pub fn volume_profile(&self, symbol: &str) -> Option<&VolumeProfile> {
+        self.symbols.get(&symbol.to_uppercase()).and_then(|state| state.volume_profile.as_ref())
+    }
+
+    /// 设置深度快照来源（REST 或 WebSocket API）
+    pub fn set_snapshot_source(&mut self, snapshot_source: SnapshotSource) {
+        self.snapshots = SnapshotFetcher::new(self.market, snapshot_source);
+    }
+
+    /// 设置深度展示方式（基础资产数量、计价货币金额、柱状图或不打印）
+    pub fn set_depth_display(&mut self, depth_display: DepthDisplay) {
+        self.depth_display = depth_display;
+    }
+
+    /// 用两条腿的当前深度合成订单薄，任意一条腿尚未建立时返回 None
+    pub fn synthetic_book(&self, pair: &SyntheticPair, depth: usize) -> Option<OrderBook> {
+        pair.build(|venue, symbol| self.venue_book(venue, symbol), depth)
+    }
+
+    /// 添加跨交易所套利检测器，相关订单薄更新后产生套利事件
+    pub fn add_arbitrage_detector(&mut self, detector: ArbitrageDetector) {
+        self.arbitrage.push(detector);
+    }
+
+    /// 添加三角套利扫描器，相关订单薄更新后产生提醒事件
+    pub fn add_triangular_scanner(&mut self, scanner: TriangularScanner) {
+        self.triangular.push(scanner);
+    }
+
+    /// 开启更新频率和档位变化统计，之后每条应用的增量更新都与订单薄比较
+    pub fn set_churn_monitor(&mut self, monitor: Arc<ChurnMonitor>) {
+        self.churn = Some(monitor);
+    }
+
+    /// 设置订单薄同步状态，之后每次处理消息时更新
+    pub fn set_readiness(&mut self, readiness: Arc<Readiness>) {
+        self.readiness = Some(readiness);
+    }
+
+    /// 设置本地时钟偏差估计，由对时线程更新
+    pub fn set_clock_skew(&mut self, skew: Arc<ClockSkew>) {
+        self.clock_skew = Some(skew);
+    }
+
+    /// 计算延迟使用的本地接收时间，有时钟偏差估计时校正到交易所时间；回放时使用记录的接收时间，不做校正
+    fn latency_time(&self, now: u64) -> u64 {
+        match (&self.clock_skew, self.clock) {
+            (Some(skew), None) => skew.correct(now),
+            _ => now,
+        }
+    }
+
+    /// 添加价格发现领先者统计，相关订单薄更新后记录中间价变动
+    pub fn add_lead_lag_tracker(&mut self, tracker: LeadLagTracker) {
+        self.lead_lag.push(tracker);
+    }
+
+    /// 价格发现领先者统计
+    pub fn lead_lag_trackers(&self) -> &[LeadLagTracker] {
+        &self.lead_lag
+    }
+
+    /// 添加跨交易所价差记录器，相关订单薄更新后按间隔采样
+    pub fn add_spread_recorder(&mut self, recorder: SpreadRecorder) {
+        self.spread_recorders.push(recorder);
+    }
+
+    /// 设置买卖盘不平衡度监控，所有订单薄更新后检查是否穿越阈值
+    pub fn set_imbalance_monitor(&mut self, monitor: ImbalanceMonitor) {
+        self.imbalance = Some(monitor);
+    }
+
+    /// 启用订单流不平衡计算，每个区间结束时产生事件
+    pub fn set_ofi_calculator(&mut self, calculator: OfiCalculator) {
+        self.ofi = Some(calculator);
+    }
+
+    /// 订单流不平衡计算器，未启用时为 None
+    pub fn ofi(&self) -> Option<&OfiCalculator> {
+        self.ofi.as_ref()
+    }
+
+    /// 启用中间价已实现波动率统计
+    pub fn set_volatility_tracker(&mut self, tracker: VolatilityTracker) {
+        self.volatility = Some(tracker);
+    }
+
+    /// 中间价已实现波动率统计，未启用时为 None
+    pub fn volatility(&self) -> Option<&VolatilityTracker> {
+        self.volatility.as_ref()
+    }
+
+    /// 启用买卖价差统计
+    pub fn set_spread_tracker(&mut self, tracker: SpreadTracker) {
+        self.spread_stats = Some(tracker);
+    }
+
+    /// 买卖价差统计，未启用时为 None
+    pub fn spread_stats(&self) -> Option<&SpreadTracker> {
+        self.spread_stats.as_ref()
+    }
+
+    /// 启用挂单墙检测，挂单墙变化时产生事件
+    pub fn set_wall_detector(&mut self, detector: WallDetector) {
+        self.walls = Some(detector);
+    }
+
+    /// 启用深度更新频率异常检测
+    pub fn set_update_rate_monitor(&mut self, monitor: UpdateRateMonitor) {
+        self.update_rate = Some(monitor);
+    }
+
+    /// 深度更新频率异常检测，未启用时为 None
+    pub fn update_rate(&self) -> Option<&UpdateRateMonitor> {
+        self.update_rate.as_ref()
+    }
+
+    /// 启用 VPIN，订阅各交易对的归集成交流
+    pub fn set_vpin_calculator(&mut self, calculator: VpinCalculator) {
+        self.vpin = Some(calculator);
+    }
+
+    /// VPIN 计算器，未启用时为 None
+    pub fn vpin(&self) -> Option<&VpinCalculator> {
+        self.vpin.as_ref()
+    }
+
+    /// 启用 Kyle's lambda 价格冲击估计，订阅各交易对的归集成交流
+    pub fn set_kyle_lambda_estimator(&mut self, estimator: KyleLambdaEstimator) {
+        self.kyle_lambda = Some(estimator);
+    }
+
+    /// Kyle's lambda 价格冲击估计，未启用时为 None
+    pub fn kyle_lambda(&self) -> Option<&KyleLambdaEstimator> {
+        self.kyle_lambda.as_ref()
+    }
+
+    /// 启用成交价差统计，订阅各交易对的归集成交流
+    pub fn set_trade_spread_analyzer(&mut self, analyzer: TradeSpreadAnalyzer) {
+        self.trade_spread = Some(analyzer);
+    }
+
+    /// 成交价差统计，未启用时为 None
+    pub fn trade_spread(&self) -> Option<&TradeSpreadAnalyzer> {
+        self.trade_spread.as_ref()
+    }
+
+    /// 启用假想挂单排队位置估计，订阅各交易对的归集成交流
+    pub fn enable_queue_estimator(&mut self) {
+        self.queue.get_or_insert_with(QueuePositionEstimator::new);
+    }
+
+    /// 按当前订单薄放置假想挂单，返回编号
+    ///
+    /// 未启用排队估计或订单薄尚未建立时返回 None
+    pub fn place_resting_order(&mut self, order: RestingOrder) -> Option<usize> {
+        let now = self.now();
+        let mut queue = self.queue.take()?;
+        let id = self.venue_book(&order.venue, &order.symbol)
+            .map(|book| queue.place(order, book, now));
+        self.queue = Some(queue);
+        id
+    }
+
+    /// 假想挂单排队位置估计，未启用时为 None
+    pub fn queue_estimator(&self) -> Option<&QueuePositionEstimator> {
+        self.queue.as_ref()
+    }
+
+    /// 添加 bar 生成器，来源为成交时订阅各交易对的归集成交流
+    pub fn add_bar_builder(&mut self, builder: BarBuilder) {
+        self.bar_builders.push(builder);
+    }
+
+    /// 已添加的 bar 生成器
+    pub fn bar_builders(&self) -> &[BarBuilder] {
+        &self.bar_builders
+    }
+
+    /// 添加模拟交易策略，订单薄更新和成交时调用，市价单按当时的订单薄成交并按 `fees` 扣除吃单手续费
+    pub fn add_strategy(&mut self, strategy: Box<dyn Strategy>, fees: FeeSchedule) {
+        self.strategies.push(StrategyRunner::new(strategy, fees));
+    }
+
+    /// 各策略的运行结果，盈亏按当前订单薄计算
+    pub fn strategy_reports(&self) -> Vec<BacktestReport> {
+        self.strategies.iter().map(|runner| runner.report(self)).collect()
+    }
+
+    /// 添加原始消息和深度更新记录器
+    pub fn add_recorder(&mut self, recorder: Box<dyn Recorder>) {
+        self.recorders.push(recorder);
+    }
+
+    /// 记录其他交易所的原始消息帧，币安消息在 [`handle_message`](Self::handle_message) 中记录
+    pub fn record_frame(&mut self, venue: &str, frame: &RawFrame, local_time: u64) {
+        if !self.recorders.is_empty() {
+            self.record(&Record::frame(venue, frame, local_time));
+        }
+    }
+
+    /// 把记录器缓冲的数据写入存储，退出前调用
+    pub fn flush_recorders(&mut self) {
+        for recorder in &mut self.recorders {
+            if let Err(e) = recorder.flush() {
+                error!(error = %e, "写入记录失败");
+            }
+        }
+    }
+
+    /// 写入所有记录器，失败的记录器停止记录
+    fn record(&mut self, record: &Record) {
+        profile::time(Stage::SinkWrite, || retain_recorders(&mut self.recorders, |recorder| recorder.record(record)));
+    }
+
+    fn trade_stream_enabled(&self) -> bool {
+        self.volume_profile.is_some() || self.vpin.is_some() || self.kyle_lambda.is_some() || self.trade_spread.is_some()
+            || self.queue.is_some()
+            || self.bar_builders.iter().any(|builder| builder.source() == BarSource::Trade)
+            || self.recorders.iter().any(|recorder| recorder.wants_trades())
+            || !self.strategies.is_empty()
+    }
+
+    /// 各交易所行情延迟统计
+    pub fn latency(&self) -> &LatencyMonitor {
+        &self.latency
+    }
+
+    /// 各交易对行情延迟直方图，可以在其他线程读取
+    pub fn feed_latency(&self) -> Arc<FeedLatency> {
+        self.feed_latency.clone()
+    }
+
+    /// 生成所有交易对的订阅参数，按交易对分组，同一交易对的流需要在同一连接中订阅
+    pub fn subscribe_params(&self) -> Vec<(String, Vec<String>)> {
+        let mut symbols: Vec<&SymbolState> = self.symbols.values().collect();
+        symbols.sort_by(|a, b| a.config.symbol.cmp(&b.config.symbol));
+        symbols.iter()
+            .map(|state| {
+                let symbol = &state.config.symbol;
+                let mut params = self.market.stream_params(&state.config);
+                for interval in &self.kline_intervals {
+                    params.push(format!("{}@kline_{}", symbol.to_lowercase(), interval));
+                }
+                if let Some(stream_name) = self.ticker_stream.stream_name() {
+                    params.push(format!("{}@{}", symbol.to_lowercase(), stream_name));
+                }
+                if self.trade_stream_enabled() {
+                    params.push(format!("{}@aggTrade", symbol.to_lowercase()));
+                }
+                (symbol.clone(), params)
+            })
+            .collect()
+    }
+
+    /// 获取交易对的订单薄
+    pub fn book(&self, symbol: &str) -> Option<&OrderBook> {
+        self.symbols.get(&symbol.to_uppercase()).and_then(|state| state.book.as_ref())
+    }
+
+    /// 获取交易对的最新有限档深度
+    pub fn partial_depth(&self, symbol: &str) -> Option<&LimitedDepthInfo> {
+        self.symbols.get(&symbol.to_uppercase()).and_then(|state| state.partial_depth.as_ref())
+    }
+
+    /// 获取交易对的最新标记价格
+    pub fn mark_price(&self, symbol: &str) -> Option<&MarkPrice> {
+        self.symbols.get(&symbol.to_uppercase()).and_then(|state| state.mark_price.as_ref())
+    }
+
+    /// 获取交易对的资金费率状态
+    pub fn funding(&self, symbol: &str) -> Option<&FundingInfo> {
+        self.symbols.get(&symbol.to_uppercase()).map(|state| &state.funding)
+    }
+
+    /// 通过 REST 加载所有合约交易对的历史资金费率，现货不做处理
+    ///
+    /// 每次最多同时请求 [`FUNDING_HISTORY_CONCURRENCY`] 个交易对，交易对较多时不必逐个等待
+    pub fn load_funding_history(&mut self, limit: u32) {
+        if self.market != Market::UsdmFutures {
+            return;
+        }
+        let symbols: Vec<String> = self.symbols.keys().cloned().collect();
+        let mut results = Vec::with_capacity(symbols.len());
+        for batch in symbols.chunks(FUNDING_HISTORY_CONCURRENCY) {
+            thread::scope(|scope| {
+                let handles: Vec<_> = batch.iter()
+                    .map(|symbol| scope.spawn(move || get_funding_rate_history(symbol, limit).map_err(|e| e.to_string())))
+                    .collect();
+                for (symbol, handle) in batch.iter().zip(handles) {
+                    let result = handle.join().unwrap_or_else(|_| Err("请求线程异常退出".to_string()));
+                    results.push((symbol, result));
+                }
+            });
+        }
+        for (symbol, result) in results {
+            let Some(state) = self.symbols.get_mut(symbol) else {
+                continue;
+            };
+            match result {
+                Ok(records) => {
+                    if let Err(e) = state.funding.load_history(&records) {
+                        warn!(symbol, error = %e, "解析资金费率失败");
+                    }
+                }
+                Err(e) => {
+                    warn!(symbol, error = %e, "获取资金费率失败");
+                }
+            }
+        }
+    }
+
+    /// 获取交易对指定周期的当前未收盘K线
+    pub fn current_candle(&self, symbol: &str, interval: &str) -> Option<&Candle> {
+        self.symbols.get(&symbol.to_uppercase())
+            .and_then(|state| state.klines.get(interval))
+            .and_then(|series| series.current())
+    }
+
+    /// 获取交易对指定周期最近 n 根已收盘K线（按时间升序）
+    pub fn recent_candles(&self, symbol: &str, interval: &str, n: usize) -> Vec<Candle> {
+        self.symbols.get(&symbol.to_uppercase())
+            .and_then(|state| state.klines.get(interval))
+            .map(|series| series.recent(n))
+            .unwrap_or_default()
+    }
+
+    /// 获取交易对的最新24小时统计
+    pub fn ticker(&self, symbol: &str) -> Option<&Ticker24h> {
+        self.symbols.get(&symbol.to_uppercase()).and_then(|state| state.ticker.as_ref())
+    }
+
+    /// 获取交易所的订单薄
+    ///
+    /// # 参数
+    ///
+    /// * `venue` - 交易所名称，例如 "okx"，"binance" 表示币安本身
+    /// * `symbol` - 交易所原生交易对名称，例如 "BTC-USDT"
+    pub fn venue_book(&self, venue: &str, symbol: &str) -> Option<&OrderBook> {
+        // 回放记录时币安订单薄与其他交易所一样由统一格式的深度消息建立
+        if venue == BINANCE_VENUE
+            && let Some(book) = self.book(symbol)
+        {
+            return Some(book);
+        }
+        self.venue_books.get(venue).and_then(|books| books.get(symbol))
+    }
+
+    /// 合并多个交易所同一品种的订单薄，尚未建立的订单薄会被跳过
+    ///
+    /// # 参数
+    ///
+    /// * `legs` - (交易所名称, 交易对) 列表，例如 [("binance", "BTCUSDT"), ("okx", "BTC-USDT")]
+    /// * `depth` - 每个交易所每侧最多合并的档位数
+    pub fn consolidated_book(&self, legs: &[(String, String)], depth: usize) -> ConsolidatedBook {
+        let books = legs.iter()
+            .filter_map(|(venue, symbol)| self.venue_book(venue, symbol).map(|book| (venue.as_str(), book)));
+        ConsolidatedBook::from_books(books, depth)
+    }
+
+    /// 应用交易所适配器输出的深度消息
+    ///
+    /// 序号不连续或校验和不一致时丢弃本地订单薄并返回错误，调用方应请求重新同步
+    pub fn handle_venue_depth(&mut self, venue: &str, message: DepthMessage) -> Result<(), Box<dyn Error>> {
+        let started = Instant::now();
+        let now = self.now();
+        let symbol = message.symbol.clone();
+        metrics::message_received(venue, &symbol);
+        if !self.recorders.is_empty() {
+            self.record(&Record::update(venue, &message, now));
+        }
+        let receive_time = self.latency_time(now);
+        self.latency.record(venue, &symbol, message.timestamp, receive_time);
+        self.feed_latency.record(venue, &symbol, message.timestamp, receive_time);
+        if let Some(monitor) = self.update_rate.as_mut()
+            && let Some(anomaly) = monitor.record(venue, &symbol, message.bids.len() + message.asks.len(), now)
+        {
+            self.events.push(MarketEvent::UpdateRateAnomaly(anomaly));
+        }
+        if let Err(e) = debug_span!("apply", symbol).in_scope(|| self.apply_venue_depth(venue, message)) {
+            if let Some(readiness) = &self.readiness {
+                readiness.book_lost(venue, &symbol);
+            }
+            return Err(e);
+        }
+        debug_span!("publish", symbol).in_scope(|| self.on_book_update(venue, &symbol));
+        metrics::processing_time(venue, &symbol, started.elapsed());
+        Ok(())
+    }
+
+    /// 处理一条回放记录，以记录的接收时间作为当前时间
+    ///
+    /// 统一格式的深度更新与实时行情经过相同的订单薄和分析流程，关键帧直接替换订单薄，成交经过成交分析和策略，原始消息帧忽略
+    pub fn replay_record(&mut self, record: Record) -> Result<(), Box<dyn Error>> {
+        self.set_clock(record.recv_time());
+        match record {
+            Record::Update { venue, message, .. } => self.handle_venue_depth(&venue, message),
+            // 关键帧与当时的订单薄一致，只用于恢复状态，不触发分析
+            Record::Keyframe { venue, message, .. } => self.apply_venue_depth(&venue, message),
+            Record::Trade { venue, symbol, trade, .. } => {
+                self.handle_trade(&venue, &symbol, trade);
+                Ok(())
+            }
+            Record::Raw { .. } | Record::Binary { .. } => Ok(()),
+        }
+    }
+
+    /// 应用深度消息到交易所订单薄
+    fn apply_venue_depth(&mut self, venue: &str, message: DepthMessage) -> Result<(), Box<dyn Error>> {
+        let now = self.now();
+        let books = self.venue_books.entry(venue.to_string()).or_default();
+        match message.kind {
+            DepthKind::Snapshot => {
+                let sequence = message.continuity.sequence().unwrap_or_default();
+                let book = OrderBook::from_levels(sequence, &message.bids, &message.asks);
+                if books.insert(message.symbol.clone(), book).is_none() {
+                    info!(venue, symbol = message.symbol, update_id = sequence, "创建订单薄");
+                }
+            }
+            DepthKind::Delta => {
+                let Some(book) = books.get_mut(&message.symbol) else {
+                    // 尚未收到快照
+                    return Ok(());
+                };
+                match message.continuity {
+                    Continuity::None => {}
+                    Continuity::Prev { prev, sequence } => {
+                        if sequence <= book.last_update_id {
+                            // 快照之前的旧消息
+                            return Ok(());
+                        }
+                        if prev != book.last_update_id {
+                            let local = book.last_update_id;
+                            books.remove(&message.symbol);
+                            metrics::gap(venue, &message.symbol);
+                            return Err(format!("{} {} 序号不连续: 本地 {}, 消息上一序号 {}, 序号 {}", venue, message.symbol, local, prev, sequence).into());
+                        }
+                    }
+                    Continuity::Range { first, last } => {
+                        if last <= book.last_update_id {
+                            // 快照之前的旧消息
+                            return Ok(());
+                        }
+                        if first > book.last_update_id + 1 {
+                            let local = book.last_update_id;
+                            books.remove(&message.symbol);
+                            metrics::gap(venue, &message.symbol);
+                            return Err(format!("{} {} 序号不连续: 本地 {}, 消息 [{}, {}]", venue, message.symbol, local, first, last).into());
+                        }
+                    }
+                    Continuity::Monotonic(timestamp) => {
+                        if timestamp <= book.last_update_id {
+                            return Ok(());
+                        }
+                    }
+                }
+                if let Some(churn) = &self.churn {
+                    let changes = LevelChanges::between(book, &message.bids, &message.asks);
+                    churn.record(venue, &message.symbol, changes, message.bids.len() + message.asks.len(), now);
+                }
+                profile::time(Stage::MapUpdate, || {
+                    book.apply_levels(Side::Bid, &message.bids);
+                    book.apply_levels(Side::Ask, &message.asks);
+                });
+                if let Some(sequence) = message.continuity.sequence() {
+                    book.last_update_id = sequence;
+                }
+                metrics::delta_applied(venue, &message.symbol);
+            }
+        }
+
+        if let Some(max_depth) = message.max_depth
+            && let Some(book) = books.get_mut(&message.symbol)
+        {
+            book.truncate(max_depth);
+        }
+
+        if let Some(checksum) = message.checksum
+            && let Some(book) = books.get(&message.symbol)
+            && !checksum.verify(book)
+        {
+            books.remove(&message.symbol);
+            return Err(format!("{} {} 校验和不一致", venue, message.symbol).into());
+        }
+        Ok(())
+    }
+
+    /// 取出自上次调用以来产生的所有事件
+    pub fn poll_events(&mut self) -> Vec<MarketEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// 处理一条WebSocket文本消息
+    pub fn handle_message(&mut self, msg: &str) {
+        let now = self.now();
+        if !self.recorders.is_empty() {
+            self.record(&Record::frame(BINANCE_VENUE, &RawFrame::Text(msg.to_string()), now));
+        }
+        self.apply_snapshots(None);
+        // 组合流消息带有流名称，订阅响应等其他消息原样处理
+        let parsed = debug_span!("parse").in_scope(|| profile::time(Stage::JsonParse, || serde_json::from_str::<StreamMessage>(msg)));
+        match parsed {
+            Ok(message) => {
+                let symbol = message.stream.split('@').next().unwrap_or_default().to_uppercase();
+                metrics::message_received(BINANCE_VENUE, &symbol);
+                self.handle_stream_data(&message.stream, message.data.get());
+            }
+            Err(_) => self.handle_stream_data("", msg),
+        }
+    }
+
+    /// 根据流名称和事件类型分发消息
+    fn handle_stream_data(&mut self, stream: &str, msg: &str) {
+        if is_partial_depth_stream(stream) {
+            self.handle_partial_depth(stream, msg);
+            return;
+        }
+        trace!(stream, msg, "收到消息");
+        if msg.contains(r#""e":"depthUpdate""#) {
+            let parsed = debug_span!("parse", event = "depthUpdate")
+                .in_scope(|| profile::time(Stage::JsonParse, || serde_json::from_str::<DepthUpdate>(msg)));
+            match parsed {
+                Ok(update) => {
+                    let started = Instant::now();
+                    let symbol = update.s.clone();
+                    self.handle_depth_update(update);
+                    metrics::processing_time(BINANCE_VENUE, &symbol, started.elapsed());
+                }
+                Err(e) => {
+                    warn!(stream, error = %e, msg, "解析深度更新失败");
+                }
+            }
+        }
+        if msg.contains(r#""e":"markPriceUpdate""#) {
+            match serde_json::from_str::<MarkPriceUpdate>(msg) {
+                Ok(update) => self.handle_mark_price(update),
+                Err(e) => {
+                    warn!(stream, error = %e, msg, "解析标记价格失败");
+                }
+            }
+        }
+        if msg.contains(r#""e":"forceOrder""#) {
+            match serde_json::from_str::<ForceOrderEvent>(msg) {
+                Ok(event) => self.handle_force_order(event),
+                Err(e) => {
+                    warn!(stream, error = %e, msg, "解析强平订单失败");
+                }
+            }
+        }
+        if msg.contains(r#""e":"kline""#) {
+            match serde_json::from_str::<KlineEvent>(msg) {
+                Ok(event) => self.handle_kline(event),
+                Err(e) => {
+                    warn!(stream, error = %e, msg, "解析K线失败");
+                }
+            }
+        }
+        if msg.contains(r#""e":"aggTrade""#) {
+            match serde_json::from_str::<AggTradeEvent>(msg) {
+                Ok(event) => self.handle_agg_trade(event),
+                Err(e) => {
+                    warn!(stream, error = %e, msg, "解析归集成交失败");
+                }
+            }
+        }
+        if msg.contains(r#""e":"24hrTicker""#) {
+            match serde_json::from_str::<TickerEvent>(msg) {
+                Ok(event) => {
+                    let ticker = Ticker24h::from_ticker(&event);
+                    self.update_ticker(&event.s, ticker);
+                }
+                Err(e) => {
+                    warn!(stream, error = %e, msg, "解析24小时行情失败");
+                }
+            }
+        }
+        if msg.contains(r#""e":"24hrMiniTicker""#) {
+            match serde_json::from_str::<MiniTickerEvent>(msg) {
+                Ok(event) => {
+                    let ticker = Ticker24h::from_mini_ticker(&event);
+                    self.update_ticker(&event.s, ticker);
+                }
+                Err(e) => {
+                    warn!(stream, error = %e, msg, "解析24小时精简行情失败");
+                }
+            }
+        }
+    }
+
+    /// 处理有限档深度推送
+    ///
+    /// 现货推送为快照格式，合约推送与增量深度同为 `depthUpdate` 格式
+    fn handle_partial_depth(&mut self, stream: &str, msg: &str) {
+        let symbol = stream.split('@').next().unwrap_or_default().to_uppercase();
+        let Some(state) = self.symbols.get_mut(&symbol) else {
+            return;
+        };
+        let depth = if self.market.is_spot() {
+            serde_json::from_str::<LimitedDepthInfo>(msg)
+        } else {
+            serde_json::from_str::<DepthUpdate>(msg).map(|update| LimitedDepthInfo {
+                lastUpdateId: update.u,
+                bids: update.b,
+                asks: update.a,
+            })
+        };
+        match depth {
+            Ok(limiteddepthinfo) => {
+                debug!(symbol, last_update_id = limiteddepthinfo.lastUpdateId, "收到有限深度信息");
+                match self.depth_display {
+                    DepthDisplay::Base | DepthDisplay::Notional => limiteddepthinfo.print_summary(20),
+                    DepthDisplay::Histogram => limiteddepthinfo.print_histogram(20),
+                    DepthDisplay::None => {}
+                }
+                state.partial_depth = Some(limiteddepthinfo);
+            }
+            Err(e) => {
+                warn!(symbol, error = %e, "无法解析有限深度信息")
+            }
+        }
+    }
+
+    /// 保存所有币安本地订单薄的检查点
+    pub fn checkpoint(&self, saved_at: u64) -> Checkpoint {
+        let mut books: Vec<BookCheckpoint> = self.symbols.iter()
+            .filter_map(|(symbol, state)| state.book.as_ref().map(|book| BookCheckpoint::from_book(symbol, book)))
+            .collect();
+        books.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        Checkpoint { market: self.market, saved_at, books }
+    }
+
+    /// 把币安和其他交易所的所有订单薄转储为 JSON 文件，每个订单薄一个文件，返回写入的文件
+    pub fn dump_books(&self, dir: &Path, dumped_at: u64) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+        fs::create_dir_all(dir)?;
+        let binance = self.symbols.iter()
+            .filter_map(|(symbol, state)| state.book.as_ref().map(|book| (BINANCE_VENUE, symbol, book)));
+        let venues = self.venue_books.iter()
+            .flat_map(|(venue, books)| books.iter().map(move |(symbol, book)| (venue.as_str(), symbol, book)));
+        binance.chain(venues)
+            .map(|(venue, symbol, book)| BookDump::from_book(venue, symbol, book, dumped_at).save(dir))
+            .collect()
+    }
+
+    /// 从检查点恢复订阅中的交易对的订单薄，返回恢复的数量
+    ///
+    /// 恢复的订单薄在收到第一条增量更新时检查更新ID能否衔接，不能衔接时重新获取快照
+    pub fn restore_checkpoint(&mut self, checkpoint: &Checkpoint) -> Result<usize, Box<dyn Error>> {
+        if checkpoint.market != self.market {
+            return Err(format!("检查点市场 {:?} 与当前市场 {:?} 不一致", checkpoint.market, self.market).into());
+        }
+        let mut restored = 0;
+        for saved in &checkpoint.books {
+            let Some(state) = self.symbols.get_mut(&saved.symbol) else {
+                continue;
+            };
+            let mut book = saved.to_book();
+            if let Some(mark_price) = state.mark_price {
+                book.set_mark_price(mark_price);
+            }
+            state.book = Some(book);
+            state.from_checkpoint = true;
+            restored += 1;
+        }
+        Ok(restored)
+    }
+
+    /// 处理增量深度更新，本地订单薄不存在时获取快照创建
+    fn handle_depth_update(&mut self, update: DepthUpdate) {
+        let now = self.now();
+        // 价格和数量只转换一次，记录、档位变化统计和应用到订单薄共用
+        let message = profile::time(Stage::DecimalConversion, || update.to_depth_message());
+        if !self.recorders.is_empty() {
+            match &message {
+                Ok(message) => self.record(&Record::update(BINANCE_VENUE, message, now)),
+                Err(e) => warn!(symbol = update.s, error = %e, "转换深度更新失败"),
+            }
+        }
+        let market = self.market;
+        let receive_time = self.latency_time(now);
+        let Some(state) = self.symbols.get_mut(&update.s) else {
+            return;
+        };
+        self.latency.record(BINANCE_VENUE, &update.s, update.E, receive_time);
+        self.feed_latency.record(BINANCE_VENUE, &update.s, update.E, receive_time);
+        if let Some(monitor) = self.update_rate.as_mut()
+            && let Some(anomaly) = monitor.record(BINANCE_VENUE, &update.s, update.b.len() + update.a.len(), now)
+        {
+            self.events.push(MarketEvent::UpdateRateAnomaly(anomaly));
+        }
+        debug!(venue = BINANCE_VENUE, symbol = update.s, first_update_id = update.U, final_update_id = update.u,
+               latency_ms = receive_time as i64 - update.E as i64, "收到深度更新");
+        if state.from_checkpoint && let Some(book) = state.book.as_ref() {
+            if update.u <= book.last_update_id {
+                // 检查点之前的更新，丢弃
+                return;
+            }
+            if binance_gap(market, book.last_update_id, &update) {
+                warn!(symbol = update.s, checkpoint_update_id = book.last_update_id, first_update_id = update.U,
+                      prev_update_id = update.pu, "检查点与实时更新之间有缺口，重新获取快照");
+                metrics::gap(BINANCE_VENUE, &update.s);
+                metrics::resync(BINANCE_VENUE, &update.s);
+                state.book = None;
+                if let Some(readiness) = &self.readiness {
+                    readiness.book_lost(BINANCE_VENUE, &update.s);
+                }
+            } else {
+                info!(symbol = update.s, checkpoint_update_id = book.last_update_id, "从检查点继续更新");
+            }
+            state.from_checkpoint = false;
+        }
+        // 实时更新之间有缺口时丢弃本地订单薄，在下面重新获取快照，重新同步之前订单薄不算就绪；
+        // 不大于本地序号的过期更新不算缺口
+        if let Some(book) = state.book.as_ref()
+            && update.u > book.last_update_id
+            && binance_gap(market, book.last_update_id, &update)
+        {
+            warn!(symbol = update.s, last_update_id = book.last_update_id, first_update_id = update.U,
+                  prev_update_id = update.pu, "深度更新有缺口，重新获取快照");
+            metrics::gap(BINANCE_VENUE, &update.s);
+            metrics::resync(BINANCE_VENUE, &update.s);
+            state.book = None;
+            if let Some(readiness) = &self.readiness {
+                readiness.book_lost(BINANCE_VENUE, &update.s);
+            }
+        }
+        let symbol = update.s.clone();
+        if let Some(ref mut o_b) = state.book {
+            // 档位变化需要在应用之前与订单薄比较
+            let changes = self.churn.as_ref()
+                .and_then(|_| message.as_ref().ok())
+                .map(|message| (LevelChanges::between(o_b, &message.bids, &message.asks), message.bids.len() + message.asks.len()));
+            let applied = debug_span!("apply", symbol = update.s, final_update_id = update.u).in_scope(|| {
+                let message = message?;
+                profile::time(Stage::MapUpdate, || o_b.apply_delta(update.u, &message.bids, &message.asks))
+            });
+            match applied {
+                Ok(_) => {
+                    metrics::delta_applied(BINANCE_VENUE, &update.s);
+                    if let (Some(churn), Some((changes, levels))) = (&self.churn, changes) {
+                        churn.record(BINANCE_VENUE, &update.s, changes, levels, now);
+                    }
+                    match self.depth_display {
+                        DepthDisplay::Base => o_b.print_summary(1000),
+                        DepthDisplay::Notional => o_b.print_notional_summary(1000),
+                        DepthDisplay::Histogram => o_b.print_histogram(20),
+                        DepthDisplay::None => {}
+                    }
+                }
+                Err(e) => {
+                    metrics::gap(BINANCE_VENUE, &update.s);
+                    warn!(symbol = update.s, first_update_id = update.U, final_update_id = update.u,
+                          last_update_id = o_b.last_update_id, error = %e, "应用深度更新失败")
+                }
+            }
+        } else {
+            // 订单薄未建立：缓存更新，等待后台取回的快照衔接，期间不阻塞事件循环
+            if state.pending_updates.len() >= MAX_PENDING_UPDATES {
+                state.pending_updates.pop_front();
+            }
+            let limit = state.config.snapshot_limit;
+            state.pending_updates.push_back(update);
+            if state.pending_snapshot.is_none() {
+                self.snapshots.request(&symbol, limit);
+            }
+        }
+        self.apply_snapshots(Some(&symbol));
+        debug_span!("publish", symbol).in_scope(|| self.on_book_update(BINANCE_VENUE, &symbol));
+    }
+
+    /// 取回后台获取的快照，与缓存的增量更新衔接后建立订单薄
+    ///
+    /// 尝试衔接刚取回快照的交易对和 `current`（刚缓存了更新的交易对），`current` 之外新建的订单薄在这里发布
+    fn apply_snapshots(&mut self, current: Option<&str>) {
+        let mut symbols = Vec::new();
+        for (symbol, snapshot) in self.snapshots.poll() {
+            let Some(state) = self.symbols.get_mut(&symbol) else {
+                continue;
+            };
+            match snapshot {
+                Ok(snapshot) if state.book.is_none() => {
+                    state.pending_snapshot = Some(snapshot);
+                    symbols.push(symbol);
+                }
+                Ok(_) => {}
+                Err(e) => warn!(symbol, error = %e, "获取深度快照失败"),
+            }
+        }
+        if let Some(current) = current
+            && !symbols.iter().any(|symbol| symbol == current)
+        {
+            symbols.push(current.to_string());
+        }
+        for symbol in symbols {
+            if self.bridge_snapshot(&symbol) && current != Some(symbol.as_str()) {
+                debug_span!("publish", symbol).in_scope(|| self.on_book_update(BINANCE_VENUE, &symbol));
+            }
+        }
+    }
+
+    /// 用快照和缓存的增量更新建立订单薄，建立时返回 true
+    ///
+    /// 丢弃快照之前的更新，第一条保留的更新须包含快照序号（现货为 U <= lastUpdateId + 1 <= u，
+    /// 合约为 U <= lastUpdateId <= u），之后的更新依次应用；快照早于缓存的更新时重新获取快照
+    fn bridge_snapshot(&mut self, symbol: &str) -> bool {
+        let now = self.now();
+        let market = self.market;
+        let Some(state) = self.symbols.get_mut(symbol) else {
+            return false;
+        };
+        let Some(snapshot) = state.pending_snapshot.take() else {
+            return false;
+        };
+        let snapshot_id = snapshot.lastUpdateId;
+        let bridge_id = if market.is_spot() { snapshot_id + 1 } else { snapshot_id };
+        while state.pending_updates.front().is_some_and(|update| update.u < bridge_id) {
+            state.pending_updates.pop_front();
+        }
+        let Some(first) = state.pending_updates.front() else {
+            // 还没有收到快照之后的更新
+            state.pending_snapshot = Some(snapshot);
+            return false;
+        };
+        if first.U > bridge_id {
+            warn!(symbol, snapshot_update_id = snapshot_id, first_update_id = first.U, "快照早于缓存的增量更新，重新获取快照");
+            metrics::resync(BINANCE_VENUE, symbol);
+            self.snapshots.request(symbol, state.config.snapshot_limit);
+            return false;
+        }
+        let mut book = match OrderBook::from_snapshot(snapshot) {
+            Ok(book) => book,
+            Err(e) => {
+                warn!(symbol, error = %e, "创建订单薄失败");
+                return false;
+            }
+        };
+        if let Some(mark_price) = state.mark_price {
+            book.set_mark_price(mark_price);
+        }
+        info!(symbol, snapshot_update_id = snapshot_id, pending_updates = state.pending_updates.len(), "创建订单薄");
+        let mut bridged = false;
+        let mut event_time = now;
+        while let Some(update) = state.pending_updates.pop_front() {
+            if update.u <= book.last_update_id {
+                // 已包含在快照中的更新
+                continue;
+            }
+            if bridged && binance_gap(market, book.last_update_id, &update) {
+                warn!(symbol, last_update_id = book.last_update_id, first_update_id = update.U,
+                      prev_update_id = update.pu, "缓存的深度更新有缺口，重新获取快照");
+                metrics::gap(BINANCE_VENUE, symbol);
+                metrics::resync(BINANCE_VENUE, symbol);
+                state.pending_updates.push_front(update);
+                self.snapshots.request(symbol, state.config.snapshot_limit);
+                return false;
+            }
+            bridged = true;
+            let applied = update.to_depth_message()
+                .and_then(|message| book.apply_delta(update.u, &message.bids, &message.asks));
+            match applied {
+                Ok(()) => {
+                    metrics::delta_applied(BINANCE_VENUE, symbol);
+                    event_time = update.E;
+                }
+                Err(e) => warn!(symbol, first_update_id = update.U, final_update_id = update.u,
+                                last_update_id = book.last_update_id, error = %e, "应用深度更新失败"),
+            }
+        }
+        // 缓存的更新在收到时已经记录，新建的订单薄记录为快照以便回放时重建
+        let created = DepthMessage::from_book(symbol, &book, event_time);
+        state.book = Some(book);
+        if !self.recorders.is_empty() {
+            self.record(&Record::update(BINANCE_VENUE, &created, now));
+        }
+        true
+    }
+
+    /// 订单薄更新后记录中间价变动和价差，并运行相关的套利检测器和三角套利扫描器
+    fn on_book_update(&mut self, venue: &str, symbol: &str) {
+        let now = self.now();
+        if let Some(book) = self.venue_book(venue, symbol) {
+            metrics::book_state(venue, symbol, book);
+        }
+        if let Some(readiness) = &self.readiness {
+            readiness.book_updated(venue, symbol, self.venue_book(venue, symbol).is_some(), now);
+        }
+        let mut recorders = std::mem::take(&mut self.spread_recorders);
+        for recorder in recorders.iter_mut().filter(|recorder| recorder.contains(venue, symbol)) {
+            if let Err(e) = recorder.record(|venue, symbol| self.venue_book(venue, symbol), now) {
+                error!(venue, symbol, error = %e, "写入价差记录失败");
+            }
+        }
+        self.spread_recorders = recorders;
+
+        if let Some(mut monitor) = self.imbalance.take() {
+            if let Some(book) = self.venue_book(venue, symbol) {
+                let crosses = monitor.update(venue, symbol, book);
+                self.events.extend(crosses.into_iter().map(MarketEvent::ImbalanceCrossed));
+            }
+            self.imbalance = Some(monitor);
+        }
+
+        if let Some(mut calculator) = self.ofi.take() {
+            if let Some(book) = self.venue_book(venue, symbol)
+                && let Some(interval) = calculator.update(venue, symbol, book, now)
+            {
+                self.events.push(MarketEvent::OrderFlowImbalance(interval));
+            }
+            self.ofi = Some(calculator);
+        }
+
+        if let Some(mid) = self.venue_book(venue, symbol).and_then(|book| book.mid_price()) {
+            if let Some(tracker) = self.volatility.as_mut() {
+                tracker.update(venue, symbol, mid, now);
+            }
+            if let Some(estimator) = self.kyle_lambda.as_mut() {
+                estimator.update_mid(venue, symbol, mid, now);
+            }
+            if let Some(analyzer) = self.trade_spread.as_mut() {
+                analyzer.update_mid(venue, symbol, mid, now);
+            }
+            for builder in &mut self.bar_builders {
+                for bar in builder.on_mid(venue, symbol, mid, now) {
+                    self.events.push(MarketEvent::BarClosed {
+                        venue: venue.to_string(),
+                        symbol: symbol.to_string(),
+                        kind: builder.kind(),
+                        source: builder.source(),
+                        bar,
+                    });
+                }
+            }
+        }
+
+        if !self.recorders.is_empty() {
+            let mut recorders = std::mem::take(&mut self.recorders);
+            if let Some(book) = self.venue_book(venue, symbol) {
+                profile::time(Stage::SinkWrite, || retain_recorders(&mut recorders, |recorder| recorder.on_book(venue, symbol, book, now)));
+            }
+            self.recorders = recorders;
+        }
+
+        if let Some(mut tracker) = self.spread_stats.take() {
+            if let Some(book) = self.venue_book(venue, symbol) {
+                tracker.update(venue, symbol, book, now);
+            }
+            self.spread_stats = Some(tracker);
+        }
+
+        if let Some(mut queue) = self.queue.take() {
+            if let Some(book) = self.venue_book(venue, symbol) {
+                queue.on_book_update(venue, symbol, book, now);
+            }
+            self.queue = Some(queue);
+        }
+
+        if let Some(mut detector) = self.walls.take() {
+            if let Some(book) = self.venue_book(venue, symbol) {
+                let events = detector.update(venue, symbol, book);
+                self.events.extend(events.into_iter().map(MarketEvent::Wall));
+            }
+            self.walls = Some(detector);
+        }
+
+        if !self.lead_lag.is_empty()
+            && let Some(mid) = self.venue_book(venue, symbol).and_then(|book| book.mid_price())
+        {
+            for tracker in self.lead_lag.iter_mut().filter(|tracker| tracker.contains(venue, symbol)) {
+                tracker.update(venue, symbol, mid, now);
+            }
+        }
+
+        let mut detectors = std::mem::take(&mut self.arbitrage);
+        for detector in detectors.iter_mut().filter(|detector| detector.contains(venue, symbol)) {
+            let opportunities = detector.update(|venue, symbol| self.venue_book(venue, symbol));
+            self.events.extend(opportunities.into_iter().map(MarketEvent::Arbitrage));
+        }
+        self.arbitrage = detectors;
+
+        let mut scanners = std::mem::take(&mut self.triangular);
+        for scanner in scanners.iter_mut().filter(|scanner| scanner.contains(venue, symbol)) {
+            let opportunities = scanner.update(|venue, symbol| self.venue_book(venue, symbol));
+            self.events.extend(opportunities.into_iter().map(MarketEvent::TriangularArbitrage));
+        }
+        self.triangular = scanners;
+
+        if !self.strategies.is_empty() {
+            let mut strategies = std::mem::take(&mut self.strategies);
+            for runner in &mut strategies {
+                let fills = runner.on_book_update(self, venue, symbol, now);
+                self.push_fills(runner.name(), fills);
+            }
+            self.strategies = strategies;
+        }
+    }
+
+    /// 处理标记价格推送，保存最新值、更新资金费率并计算基差
+    fn handle_mark_price(&mut self, update: MarkPriceUpdate) {
+        let Some(state) = self.symbols.get_mut(&update.s) else {
+            return;
+        };
+        let mark_price = match MarkPrice::from_update(&update) {
+            Ok(mark_price) => mark_price,
+            Err(e) => {
+                warn!(symbol = update.s, error = %e, "解析标记价格失败");
+                return;
+            }
+        };
+        state.mark_price = Some(mark_price);
+        if let Err(e) = state.funding.apply_mark_price(&update) {
+            warn!(symbol = update.s, error = %e, "解析资金费率失败");
+        }
+        if let Some(ref mut book) = state.book {
+            book.set_mark_price(mark_price);
+            if let (Some(basis), Some(basis_bps)) = (book.basis(), book.basis_bps()) {
+                info!(symbol = update.s, mark_price = %mark_price.mark_price, index_price = %mark_price.index_price,
+                      basis = %basis, "基差 {:.2} bps", basis_bps);
+            }
+            if let (Some(rate), Some(funding_time)) = (state.funding.predicted_rate, state.funding.next_funding_time) {
+                info!(symbol = update.s, rate = %rate, funding_time, last_update_id = book.last_update_id, "预测资金费率");
+            }
+        }
+    }
+
+    /// 处理K线推送，更新当前K线，收盘时产生事件
+    fn handle_kline(&mut self, event: KlineEvent) {
+        let Some(state) = self.symbols.get_mut(&event.s) else {
+            return;
+        };
+        let candle = match Candle::from_kline(&event.k) {
+            Ok(candle) => candle,
+            Err(e) => {
+                warn!(symbol = event.s, interval = event.k.i, error = %e, "解析K线失败");
+                return;
+            }
+        };
+        let series = state.klines.entry(event.k.i.clone()).or_default();
+        if let Some(candle) = series.apply(candle) {
+            self.events.push(MarketEvent::CandleClosed {
+                symbol: event.s,
+                interval: event.k.i,
+                candle,
+            });
+        }
+    }
+
+    /// 处理归集成交，只处理已订阅的交易对
+    fn handle_agg_trade(&mut self, event: AggTradeEvent) {
+        if !self.symbols.contains_key(&event.s) {
+            return;
+        }
+        match Trade::from_agg_trade(&event) {
+            Ok(trade) => self.handle_trade(BINANCE_VENUE, &event.s, trade),
+            Err(e) => {
+                warn!(symbol = event.s, error = %e, "解析归集成交失败");
+            }
+        }
+    }
+
+    /// 处理成交，累计到成交量分布、VPIN、价格冲击估计、成交价差统计、排队估计和成交 bar，并通知策略
+    fn handle_trade(&mut self, venue: &str, symbol: &str, trade: Trade) {
+        let now = self.now();
+        if let Some((bucket_size, session_ms)) = self.volume_profile
+            && venue == BINANCE_VENUE
+            && let Some(state) = self.symbols.get_mut(symbol)
+        {
+            let profile = state.volume_profile.get_or_insert_with(|| VolumeProfile::new(bucket_size, session_ms));
+            if let Some(profile) = profile.add_trade(trade.price, trade.quantity, trade.trade_time) {
+                self.events.push(MarketEvent::VolumeProfileClosed { symbol: symbol.to_string(), profile });
+            }
+        }
+        if let Some(vpin) = self.vpin.as_mut() {
+            for point in vpin.add_trade(venue, symbol, trade.quantity, trade.buyer_is_maker, trade.trade_time) {
+                self.events.push(MarketEvent::Vpin { venue: venue.to_string(), symbol: symbol.to_string(), point });
+            }
+        }
+        if let Some(estimator) = self.kyle_lambda.as_mut() {
+            estimator.add_trade(venue, symbol, trade.quantity, trade.buyer_is_maker, now);
+        }
+        if let Some(mut analyzer) = self.trade_spread.take() {
+            if let Some(book) = self.venue_book(venue, symbol) {
+                analyzer.add_trade(venue, symbol, book, &trade, now);
+            }
+            self.trade_spread = Some(analyzer);
+        }
+        if let Some(queue) = self.queue.as_mut() {
+            queue.on_trade(venue, symbol, &trade, now);
+        }
+        for builder in &mut self.bar_builders {
+            for bar in builder.on_trade(venue, symbol, &trade) {
+                self.events.push(MarketEvent::BarClosed {
+                    venue: venue.to_string(),
+                    symbol: symbol.to_string(),
+                    kind: builder.kind(),
+                    source: builder.source(),
+                    bar,
+                });
+            }
+        }
+        retain_recorders(&mut self.recorders, |recorder| recorder.on_trade(venue, symbol, &trade, now));
+
+        if !self.strategies.is_empty() {
+            let mut strategies = std::mem::take(&mut self.strategies);
+            for runner in &mut strategies {
+                let fills = runner.on_trade(self, venue, symbol, &trade, now);
+                self.push_fills(runner.name(), fills);
+            }
+            self.strategies = strategies;
+        }
+    }
+
+    /// 记录策略成交事件
+    fn push_fills(&mut self, strategy: &str, fills: Vec<Fill>) {
+        self.events.extend(fills.into_iter().map(|fill| MarketEvent::StrategyFill { strategy: strategy.to_string(), fill }));
+    }
+
+    /// 保存最新24小时统计
+    fn update_ticker(&mut self, symbol: &str, ticker: Result<Ticker24h, Box<dyn Error>>) {
+        let Some(state) = self.symbols.get_mut(symbol) else {
+            return;
+        };
+        match ticker {
+            Ok(ticker) => state.ticker = Some(ticker),
+            Err(e) => {
+                warn!(symbol, error = %e, "解析24小时行情失败");
+            }
+        }
+    }
+
+    /// 处理强平订单推送，结合本地订单薄生成强平事件
+    fn handle_force_order(&mut self, event: ForceOrderEvent) {
+        let Some(state) = self.symbols.get(&event.o.s) else {
+            return;
+        };
+        match liquidation_event(&event, state.book.as_ref()) {
+            Ok(liquidation) => self.events.push(MarketEvent::Liquidation(liquidation)),
+            Err(e) => {
+                warn!(symbol = event.o.s, error = %e, "解析强平订单失败");
+            }
+        }
+    }
+}
+
+/// 币安增量更新与序号为 `last_update_id` 的本地订单薄之间是否有缺口
+///
+/// 现货第一条更新的 U 不能超过本地序号 + 1；合约的 U 与上一条不连续，需要 pu 等于本地序号
+fn binance_gap(market: Market, last_update_id: u64, update: &DepthUpdate) -> bool {
+    match market {
+        Market::Spot | Market::UsSpot => update.U > last_update_id + 1,
+        Market::UsdmFutures => update.pu != Some(last_update_id),
+    }
+}
+
+/// 根据强平推送和当前订单薄计算强平事件
+fn liquidation_event(event: &ForceOrderEvent, book: Option<&OrderBook>) -> Result<LiquidationEvent, Box<dyn Error>> {
+    let order = &event.o;
+    let price = order.p.parse::<Decimal>()?;
+    let avg_price = order.ap.parse::<Decimal>()?;
+    let quantity = order.q.parse::<Decimal>()?;
+    // 卖出强平吃买盘，买入强平吃卖盘
+    let side = if order.S == "SELL" { Side::Bid } else { Side::Ask };
+
+    let mut liquidation = LiquidationEvent {
+        symbol: order.s.clone(),
+        event_time: event.E,
+        side,
+        price,
+        avg_price,
+        quantity,
+        mid_price: None,
+        distance_bps: None,
+        consumed_levels: 0,
+        consumed_quantity: Decimal::ZERO,
+    };
+
+    if let Some(book) = book {
+        if let Some(mid) = book.mid_price() {
+            liquidation.mid_price = Some(mid);
+            if !mid.is_zero() {
+                liquidation.distance_bps = Some((avg_price - mid).abs() / mid * Decimal::from(10_000));
+            }
+        }
+        let (consumed_levels, consumed_quantity) = book.consumed_depth(side, price, quantity);
+        liquidation.consumed_levels = consumed_levels;
+        liquidation.consumed_quantity = consumed_quantity;
+    }
+    Ok(liquidation)
+}
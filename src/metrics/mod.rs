@@ -0,0 +1,154 @@
+use std::error::Error;
+use std::time::Duration;
+use metrics::{counter, describe_counter, describe_gauge, describe_histogram, gauge, histogram, Unit};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use metrics_util::layers::FanoutBuilder;
+use rust_decimal::prelude::ToPrimitive;
+
+use crate::metrics::statsd::StatsdRecorder;
+use crate::order_book::OrderBook;
+
+pub mod statsd;
+
+/// 收到的行情消息数
+pub const MESSAGES_RECEIVED: &str = "orderbook_messages_received_total";
+/// 应用到本地订单薄的增量更新数
+pub const DELTAS_APPLIED: &str = "orderbook_deltas_applied_total";
+/// 序号不连续的次数
+pub const GAPS: &str = "orderbook_gaps_total";
+/// 丢弃本地订单薄、重新从快照开始的次数
+pub const RESYNCS: &str = "orderbook_resyncs_total";
+/// 行情连接断开后重连的次数
+pub const RECONNECTS: &str = "orderbook_reconnects_total";
+pub const BEST_BID: &str = "orderbook_best_bid";
+pub const BEST_ASK: &str = "orderbook_best_ask";
+pub const SPREAD: &str = "orderbook_spread";
+/// 每侧档位数，`side` 为 bid 或 ask
+pub const DEPTH_LEVELS: &str = "orderbook_depth_levels";
+/// 处理一条深度消息（应用到订单薄和之后的分析）的耗时
+pub const PROCESSING_SECONDS: &str = "orderbook_processing_seconds";
+/// 增量更新引起的档位变化，`change` 为 added、removed 或 modified
+pub const LEVEL_CHANGES: &str = "orderbook_level_changes_total";
+/// 每条增量更新携带的档位数
+pub const MESSAGE_LEVELS: &str = "orderbook_message_levels";
+/// 最近结束的统计区间内每秒的增量更新数
+pub const UPDATES_PER_SECOND: &str = "orderbook_updates_per_second";
+/// 本地时钟相对币安服务器时间的偏差，为正表示本地时钟偏慢
+pub const CLOCK_OFFSET: &str = "orderbook_clock_offset_milliseconds";
+/// 本地时钟偏差的变化率（百万分之一）
+pub const CLOCK_DRIFT: &str = "orderbook_clock_drift_ppm";
+/// 交易所事件时间到本地接收的延迟分位数，`quantile` 为 0.5、0.99 或 1（最大值）
+pub const FEED_LATENCY: &str = "orderbook_feed_latency_milliseconds";
+
+/// 处理耗时直方图的桶上界（秒），从 10 微秒到 1 秒
+const PROCESSING_BUCKETS: [f64; 11] = [0.00001, 0.000025, 0.00005, 0.0001, 0.00025, 0.0005, 0.001, 0.0025, 0.01, 0.1, 1.0];
+/// 消息档位数直方图的桶上界
+const MESSAGE_LEVEL_BUCKETS: [f64; 11] = [1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0, 200.0, 500.0, 1000.0, 5000.0];
+
+/// 登记各指标的说明和单位，安装导出器后调用
+pub fn describe() {
+    describe_counter!(MESSAGES_RECEIVED, "收到的行情消息数");
+    describe_counter!(DELTAS_APPLIED, "应用到本地订单薄的增量更新数");
+    describe_counter!(GAPS, "序号不连续的次数");
+    describe_counter!(RESYNCS, "重新从快照开始的次数");
+    describe_counter!(RECONNECTS, "行情连接重连次数");
+    describe_gauge!(BEST_BID, "最优买价");
+    describe_gauge!(BEST_ASK, "最优卖价");
+    describe_gauge!(SPREAD, "最优卖价与最优买价之差");
+    describe_gauge!(DEPTH_LEVELS, "本地订单薄每侧档位数");
+    describe_histogram!(PROCESSING_SECONDS, Unit::Seconds, "处理一条深度消息的耗时");
+    describe_counter!(LEVEL_CHANGES, "增量更新引起的档位新增、删除和修改数");
+    describe_histogram!(MESSAGE_LEVELS, "每条增量更新携带的档位数");
+    describe_gauge!(UPDATES_PER_SECOND, "最近统计区间内每秒的增量更新数");
+    describe_gauge!(CLOCK_OFFSET, Unit::Milliseconds, "本地时钟相对币安服务器时间的偏差");
+    describe_gauge!(CLOCK_DRIFT, "本地时钟偏差的变化率（ppm）");
+    describe_gauge!(FEED_LATENCY, Unit::Milliseconds, "交易所事件时间到本地接收的延迟分位数");
+}
+
+/// 安装全局记录器，`prometheus` 为真时返回用于生成 `/metrics` 文本的句柄
+///
+/// 同时使用 Prometheus 和 StatsD 时两者都收到所有指标；都不使用时不安装，记录指标没有开销
+pub fn install(prometheus: bool, statsd: Option<StatsdRecorder>) -> Result<Option<PrometheusHandle>, Box<dyn Error>> {
+    let mut fanout = FanoutBuilder::default();
+    let mut handle = None;
+    if prometheus {
+        let recorder = PrometheusBuilder::new()
+            .set_buckets_for_metric(Matcher::Full(PROCESSING_SECONDS.to_string()), &PROCESSING_BUCKETS)?
+            .set_buckets_for_metric(Matcher::Full(MESSAGE_LEVELS.to_string()), &MESSAGE_LEVEL_BUCKETS)?
+            .build_recorder();
+        handle = Some(recorder.handle());
+        fanout = fanout.add_recorder(recorder);
+    }
+    match statsd {
+        Some(statsd) => fanout = fanout.add_recorder(statsd),
+        None if !prometheus => return Ok(None),
+        None => {}
+    }
+    metrics::set_global_recorder(fanout.build())?;
+    describe();
+    Ok(handle)
+}
+
+pub fn message_received(venue: &str, symbol: &str) {
+    counter!(MESSAGES_RECEIVED, "venue" => venue.to_string(), "symbol" => symbol.to_string()).increment(1);
+}
+
+pub fn delta_applied(venue: &str, symbol: &str) {
+    counter!(DELTAS_APPLIED, "venue" => venue.to_string(), "symbol" => symbol.to_string()).increment(1);
+}
+
+pub fn gap(venue: &str, symbol: &str) {
+    counter!(GAPS, "venue" => venue.to_string(), "symbol" => symbol.to_string()).increment(1);
+}
+
+pub fn resync(venue: &str, symbol: &str) {
+    counter!(RESYNCS, "venue" => venue.to_string(), "symbol" => symbol.to_string()).increment(1);
+}
+
+/// 重连时还不知道交易对，只按交易所统计
+pub fn reconnect(venue: &str) {
+    counter!(RECONNECTS, "venue" => venue.to_string()).increment(1);
+}
+
+pub fn processing_time(venue: &str, symbol: &str, elapsed: Duration) {
+    histogram!(PROCESSING_SECONDS, "venue" => venue.to_string(), "symbol" => symbol.to_string()).record(elapsed.as_secs_f64());
+}
+
+pub fn level_changes(venue: &str, symbol: &str, added: u64, removed: u64, modified: u64, levels: usize) {
+    for (change, count) in [("added", added), ("removed", removed), ("modified", modified)] {
+        counter!(LEVEL_CHANGES, "venue" => venue.to_string(), "symbol" => symbol.to_string(), "change" => change).increment(count);
+    }
+    histogram!(MESSAGE_LEVELS, "venue" => venue.to_string(), "symbol" => symbol.to_string()).record(levels as f64);
+}
+
+pub fn updates_per_second(venue: &str, symbol: &str, rate: f64) {
+    gauge!(UPDATES_PER_SECOND, "venue" => venue.to_string(), "symbol" => symbol.to_string()).set(rate);
+}
+
+pub fn clock_skew(offset_ms: f64, drift_ppm: f64) {
+    gauge!(CLOCK_OFFSET).set(offset_ms);
+    gauge!(CLOCK_DRIFT).set(drift_ppm);
+}
+
+/// 延迟分位数由 [`crate::latency::FeedLatency`] 计算，这里只写入仪表
+pub fn feed_latency(venue: &str, symbol: &str, p50: u64, p99: u64, max: u64) {
+    for (quantile, value) in [("0.5", p50), ("0.99", p99), ("1", max)] {
+        gauge!(FEED_LATENCY, "venue" => venue.to_string(), "symbol" => symbol.to_string(), "quantile" => quantile).set(value as f64);
+    }
+}
+
+/// 订单薄更新后记录最优价、价差和档位数，一侧为空时不更新对应的价格
+pub fn book_state(venue: &str, symbol: &str, book: &OrderBook) {
+    let labels = [("venue", venue.to_string()), ("symbol", symbol.to_string())];
+    if let Some(price) = book.best_bid().and_then(|(price, _)| price.to_f64()) {
+        gauge!(BEST_BID, &labels).set(price);
+    }
+    if let Some(price) = book.best_ask().and_then(|(price, _)| price.to_f64()) {
+        gauge!(BEST_ASK, &labels).set(price);
+    }
+    if let Some(spread) = book.spread().and_then(|spread| spread.to_f64()) {
+        gauge!(SPREAD, &labels).set(spread);
+    }
+    gauge!(DEPTH_LEVELS, "venue" => venue.to_string(), "symbol" => symbol.to_string(), "side" => "bid").set(book.bids.len() as f64);
+    gauge!(DEPTH_LEVELS, "venue" => venue.to_string(), "symbol" => symbol.to_string(), "side" => "ask").set(book.asks.len() as f64);
+}
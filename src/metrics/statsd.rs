@@ -0,0 +1,193 @@
+use std::error::Error;
+use std::net::{ToSocketAddrs, UdpSocket};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use std::time::Duration;
+use metrics::{Counter, CounterFn, Gauge, GaugeFn, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder, SharedString, Unit};
+
+/// 单个 UDP 包的最大长度，低于常见 MTU，避免分片
+const MAX_PACKET: usize = 1432;
+/// 缓冲的指标发送到 StatsD 的最长间隔
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// StatsD 协议变体
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsdFormat {
+    /// 原始 StatsD，不支持标签，标签值依次拼接到指标名后，例如 `orderbook_gaps_total.okx.BTC-USDT`
+    Statsd,
+    /// DogStatsD，标签附加为 `|#venue:okx,symbol:BTC-USDT`
+    DogStatsd,
+}
+
+impl StatsdFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "statsd" => Some(StatsdFormat::Statsd),
+            "dogstatsd" => Some(StatsdFormat::DogStatsd),
+            _ => None,
+        }
+    }
+}
+
+/// 发送缓冲，多条指标合并为一个 UDP 包，由后台线程定期发送
+#[derive(Debug)]
+struct Sink {
+    socket: UdpSocket,
+    buffer: Mutex<String>,
+}
+
+impl Sink {
+    fn push(&self, line: &str) {
+        let Ok(mut buffer) = self.buffer.lock() else {
+            return;
+        };
+        if !buffer.is_empty() && buffer.len() + line.len() + 1 > MAX_PACKET {
+            self.send(&mut buffer);
+        }
+        if !buffer.is_empty() {
+            buffer.push('\n');
+        }
+        buffer.push_str(line);
+    }
+
+    /// UDP 发送失败（例如对方未监听）时丢弃这批指标
+    fn send(&self, buffer: &mut String) {
+        let _ = self.socket.send(buffer.as_bytes());
+        buffer.clear();
+    }
+
+    fn flush(&self) {
+        if let Ok(mut buffer) = self.buffer.lock()
+            && !buffer.is_empty()
+        {
+            self.send(&mut buffer);
+        }
+    }
+}
+
+/// 通过 UDP 发送到 StatsD 或 DogStatsD 的指标记录器
+///
+/// 记录 [`crate::metrics`] 中的同一组指标：计数器发送增量 (`c`)，仪表发送当前值 (`g`)，
+/// 直方图逐个发送样本 (`h`)，由 StatsD 端聚合。指标在缓冲中合并，每秒或接近包长上限时发送
+#[derive(Debug)]
+pub struct StatsdRecorder {
+    sink: Arc<Sink>,
+    format: StatsdFormat,
+    prefix: String,
+}
+
+impl StatsdRecorder {
+    /// 创建记录器并启动发送线程，`prefix` 非空时加在所有指标名前，以 `.` 分隔
+    pub fn new(addr: impl ToSocketAddrs, format: StatsdFormat, prefix: &str) -> Result<Self, Box<dyn Error>> {
+        let socket = UdpSocket::bind("0.0.0.0:0")?;
+        socket.connect(addr)?;
+        let sink = Arc::new(Sink { socket, buffer: Mutex::new(String::new()) });
+        let weak: Weak<Sink> = Arc::downgrade(&sink);
+        thread::spawn(move || {
+            loop {
+                thread::sleep(FLUSH_INTERVAL);
+                let Some(sink) = weak.upgrade() else {
+                    return;
+                };
+                sink.flush();
+            }
+        });
+        let prefix = if prefix.is_empty() { String::new() } else { format!("{}.", prefix) };
+        Ok(StatsdRecorder { sink, format, prefix })
+    }
+
+    fn metric(&self, key: &Key) -> Arc<StatsdMetric> {
+        let mut name = format!("{}{}", self.prefix, sanitize(key.name()));
+        let mut tags = String::new();
+        for label in key.labels() {
+            match self.format {
+                StatsdFormat::Statsd => {
+                    name.push('.');
+                    name.push_str(&sanitize(label.value()));
+                }
+                StatsdFormat::DogStatsd => {
+                    tags.push(if tags.is_empty() { '#' } else { ',' });
+                    tags.push_str(&format!("{}:{}", sanitize(label.key()), sanitize(label.value())));
+                }
+            }
+        }
+        Arc::new(StatsdMetric { sink: self.sink.clone(), name, tags })
+    }
+}
+
+/// 去掉协议中的分隔符
+fn sanitize(value: &str) -> String {
+    value.replace([':', '|', '@', '#', ',', '\n'], "_")
+}
+
+/// 一个指标名和标签组合，格式化后的名称和标签在注册时生成
+#[derive(Debug)]
+struct StatsdMetric {
+    sink: Arc<Sink>,
+    name: String,
+    tags: String,
+}
+
+impl StatsdMetric {
+    fn send(&self, value: &str, kind: &str) {
+        let line = if self.tags.is_empty() {
+            format!("{}:{}|{}", self.name, value, kind)
+        } else {
+            format!("{}:{}|{}|{}", self.name, value, kind, self.tags)
+        };
+        self.sink.push(&line);
+    }
+}
+
+impl CounterFn for StatsdMetric {
+    fn increment(&self, value: u64) {
+        self.send(&value.to_string(), "c");
+    }
+
+    /// StatsD 计数器只接受增量，不支持设置绝对值
+    fn absolute(&self, _value: u64) {}
+}
+
+impl GaugeFn for StatsdMetric {
+    fn increment(&self, value: f64) {
+        self.send(&format!("+{}", value), "g");
+    }
+
+    fn decrement(&self, value: f64) {
+        self.send(&format!("-{}", value), "g");
+    }
+
+    fn set(&self, value: f64) {
+        // 负值会被解释为减量，先归零再减
+        if value < 0.0 {
+            self.send("0", "g");
+        }
+        self.send(&value.to_string(), "g");
+    }
+}
+
+impl HistogramFn for StatsdMetric {
+    fn record(&self, value: f64) {
+        self.send(&value.to_string(), "h");
+    }
+}
+
+impl Recorder for StatsdRecorder {
+    fn describe_counter(&self, _key: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+
+    fn describe_gauge(&self, _key: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+
+    fn describe_histogram(&self, _key: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+
+    fn register_counter(&self, key: &Key, _metadata: &Metadata<'_>) -> Counter {
+        Counter::from_arc(self.metric(key))
+    }
+
+    fn register_gauge(&self, key: &Key, _metadata: &Metadata<'_>) -> Gauge {
+        Gauge::from_arc(self.metric(key))
+    }
+
+    fn register_histogram(&self, key: &Key, _metadata: &Metadata<'_>) -> Histogram {
+        Histogram::from_arc(self.metric(key))
+    }
+}
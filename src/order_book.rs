@@ -0,0 +1,761 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+use crate::binance::{DepthSnapshot, DepthUpdate, MarkPriceUpdate};
+
+/// 盘口方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    /// 买盘
+    Bid,
+    /// 卖盘
+    Ask,
+}
+
+/// 深度展示方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepthDisplay {
+    /// 按基础资产数量展示
+    Base,
+    /// 按计价货币金额（例如 USDT）展示
+    Notional,
+    /// 按基础资产数量画 ASCII 柱状图
+    Histogram,
+    /// 不打印，终端界面等占用标准输出时使用
+    None,
+}
+
+impl DepthDisplay {
+    /// 从命令行参数解析
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "base" => Some(DepthDisplay::Base),
+            "notional" | "quote" => Some(DepthDisplay::Notional),
+            "histogram" => Some(DepthDisplay::Histogram),
+            "none" => Some(DepthDisplay::None),
+            _ => None,
+        }
+    }
+}
+
+/// 深度柱状图的默认宽度（字符数）
+pub const HISTOGRAM_WIDTH: usize = 40;
+
+/// 以 ASCII 柱状图画出两侧档位：卖单在上、买单在下，价格均为降序，两侧之间一行为价差和中间价
+///
+/// `bids` 价格降序、`asks` 价格升序，都从最优价开始。柱长按所有档位中的最大数量缩放到 `width` 个字符，
+/// 数量不为 0 的档位至少一个字符，卖单用 `=`、买单用 `#`。列宽固定，不依赖终端控制字符，可以通过 ssh 查看或写入日志
+pub fn depth_histogram(bids: &[(Decimal, Decimal)], asks: &[(Decimal, Decimal)], width: usize) -> String {
+    let levels = || asks.iter().chain(bids);
+    let max = levels().map(|(_, quantity)| *quantity).max().unwrap_or_default();
+    let price_width = levels().map(|(price, _)| price.to_string().len()).max().unwrap_or(0);
+    let quantity_width = levels().map(|(_, quantity)| quantity.to_string().len()).max().unwrap_or(0);
+    let line = |label: &str, (price, quantity): &(Decimal, Decimal), fill: &str| {
+        let length = if quantity.is_zero() || max.is_zero() {
+            0
+        } else {
+            (quantity / max * Decimal::from(width)).round().to_usize().unwrap_or(0).max(1)
+        };
+        format!("{} {:>price_width$} {:>quantity_width$} |{}",
+                label, price.to_string(), quantity.to_string(), fill.repeat(length))
+    };
+    let mut lines: Vec<_> = asks.iter().rev().map(|level| line("卖", level, "=")).collect();
+    if let (Some((bid, _)), Some((ask, _))) = (bids.first(), asks.first()) {
+        lines.push(format!("价差: {}  中间价: {}", ask - bid, ((bid + ask) / Decimal::TWO).normalize()));
+    }
+    lines.extend(bids.iter().map(|level| line("买", level, "#")));
+    lines.join("\n")
+}
+
+/// 合约标记价格信息
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MarkPrice {
+    /// 事件时间
+    pub event_time: u64,
+    /// 标记价格
+    pub mark_price: Decimal,
+    /// 现货指数价格
+    pub index_price: Decimal,
+}
+
+impl MarkPrice {
+    /// 从标记价格推送创建
+    pub fn from_update(update: &MarkPriceUpdate) -> Result<Self, Box<dyn Error>> {
+        Ok(MarkPrice {
+            event_time: update.E,
+            mark_price: update.p.parse::<Decimal>()?,
+            index_price: update.i.parse::<Decimal>()?,
+        })
+    }
+}
+
+/// 市价单模拟成交结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FillSimulation {
+    /// 吃的盘口方向，买入为 Ask，卖出为 Bid
+    pub side: Side,
+    /// 成交数量
+    pub filled: Decimal,
+    /// 成交金额
+    pub notional: Decimal,
+    /// 成交均价，没有成交时为 None
+    pub average_price: Option<Decimal>,
+    /// 最差成交价（最后一档的价格）
+    pub worst_price: Option<Decimal>,
+    /// 模拟成交前的最优价
+    pub best_price: Option<Decimal>,
+    /// 吃掉的档位数
+    pub levels: usize,
+    /// 深度不足而未成交的数量
+    pub unfilled: Decimal,
+}
+
+impl FillSimulation {
+    /// 成交均价相对最优价的滑点（基点），始终为非负数
+    pub fn slippage_bps(&self) -> Option<Decimal> {
+        let average_price = self.average_price?;
+        let best_price = self.best_price?;
+        if best_price.is_zero() {
+            return None;
+        }
+        Some((average_price - best_price).abs() / best_price * Decimal::from(10_000))
+    }
+
+    /// 含手续费的实际均价，买入加上手续费，卖出扣除手续费
+    ///
+    /// # 参数
+    ///
+    /// * `fee_rate` - 吃单手续费率，例如 0.001
+    pub fn effective_price(&self, fee_rate: Decimal) -> Option<Decimal> {
+        let average_price = self.average_price?;
+        Some(match self.side {
+            Side::Ask => average_price * (Decimal::ONE + fee_rate),
+            Side::Bid => average_price * (Decimal::ONE - fee_rate),
+        })
+    }
+}
+
+/// 冲击曲线上的一个点
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImpactPoint {
+    /// 累计成交金额
+    pub notional: Decimal,
+    /// 累计成交数量
+    pub quantity: Decimal,
+    /// 成交均价
+    pub average_price: Decimal,
+    /// 成交均价相对中间价的冲击（基点），始终为非负数
+    pub impact_bps: Decimal,
+}
+
+/// 中间价附近一定范围内的挂单深度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LiquidityDepth {
+    /// 范围内买盘数量
+    pub bid_quantity: Decimal,
+    /// 范围内卖盘数量
+    pub ask_quantity: Decimal,
+    /// 范围内买盘金额
+    pub bid_notional: Decimal,
+    /// 范围内卖盘金额
+    pub ask_notional: Decimal,
+}
+
+/// 按金额表示的一档深度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotionalLevel {
+    /// 价格
+    pub price: Decimal,
+    /// 数量（基础资产）
+    pub quantity: Decimal,
+    /// 本档金额（价格 × 数量，计价货币）
+    pub notional: Decimal,
+    /// 从最优价到本档的累计金额
+    pub cumulative_notional: Decimal,
+}
+
+/// 订单薄文件的标识
+const BOOK_FILE_MAGIC: &[u8; 8] = b"OBSTATE\0";
+/// 订单薄文件的格式版本，内容结构变化时递增
+const BOOK_FILE_VERSION: u16 = 1;
+
+/// 订单薄文件中随订单薄保存的说明信息
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookMetadata {
+    pub venue: String,
+    pub symbol: String,
+    /// 订单薄对应的时间（毫秒）
+    pub time: u64,
+}
+
+/// 十进制数的 16 字节二进制形式，见 [`Decimal::serialize`]
+type DecimalBytes = [u8; 16];
+
+/// 订单薄文件的内容，字段顺序即编码顺序
+#[derive(Serialize, Deserialize)]
+struct BookFile {
+    metadata: BookMetadata,
+    last_update_id: u64,
+    /// 买单 (价格, 数量)，价格降序
+    bids: Vec<(DecimalBytes, DecimalBytes)>,
+    /// 卖单 (价格, 数量)，价格升序
+    asks: Vec<(DecimalBytes, DecimalBytes)>,
+    /// (事件时间, 标记价格, 指数价格)
+    mark_price: Option<(u64, DecimalBytes, DecimalBytes)>,
+}
+
+/// 订单薄结构体，包含买单和卖单
+#[derive(Debug, Clone)]
+pub struct OrderBook {
+    pub last_update_id: u64,
+    /// 买单映射 (价格 -> 数量)
+    pub(crate) bids: BTreeMap<Decimal, Decimal>,
+    /// 卖单映射 (价格 -> 数量)
+    pub(crate) asks: BTreeMap<Decimal, Decimal>,
+    /// 最新标记价格（仅合约）
+    pub(crate) mark_price: Option<MarkPrice>,
+}
+
+impl OrderBook {
+    /// 从深度快照创建订单薄
+    pub fn from_snapshot(snapshot: DepthSnapshot) -> Result<Self, Box<dyn Error>> {
+        // 创建BTreeMap用于买单和卖单
+        let mut bids = BTreeMap::new();
+        let mut asks = BTreeMap::new();
+
+        // 处理买单，转换字符串为Decimal并插入到映射中
+        for bid in snapshot.bids {
+            let price = bid[0].parse::<Decimal>()?;
+            let quantity = bid[1].parse::<Decimal>()?;
+            if !quantity.is_zero() {
+                bids.insert(price, quantity);
+            }
+        }
+
+        // 处理卖单，转换字符串为Decimal并插入到映射中
+        for ask in snapshot.asks {
+            let price = ask[0].parse::<Decimal>()?;
+            let quantity = ask[1].parse::<Decimal>()?;
+            if !quantity.is_zero() {
+                asks.insert(price, quantity);
+            }
+        }
+
+        // 创建订单薄实例
+        let order_book = OrderBook {
+            last_update_id: snapshot.lastUpdateId,
+            bids,
+            asks,
+            mark_price: None,
+        };
+
+        Ok(order_book)
+    }
+
+    /// 从已解析的档位创建订单薄，数量为0的档位会被忽略
+    pub fn from_levels(last_update_id: u64, bids: &[(Decimal, Decimal)], asks: &[(Decimal, Decimal)]) -> Self {
+        let mut order_book = OrderBook {
+            last_update_id,
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            mark_price: None,
+        };
+        order_book.apply_levels(Side::Bid, bids);
+        order_book.apply_levels(Side::Ask, asks);
+        order_book
+    }
+
+    /// 应用一组档位变动，数量为0表示删除该价格
+    pub fn apply_levels(&mut self, side: Side, levels: &[(Decimal, Decimal)]) {
+        let book_side = match side {
+            Side::Bid => &mut self.bids,
+            Side::Ask => &mut self.asks,
+        };
+        for (price, quantity) in levels {
+            if quantity.is_zero() {
+                book_side.remove(price);
+            } else {
+                book_side.insert(*price, *quantity);
+            }
+        }
+    }
+
+    /// 买卖盘各只保留最优的 `depth` 档
+    pub fn truncate(&mut self, depth: usize) {
+        while self.bids.len() > depth {
+            self.bids.pop_first();
+        }
+        while self.asks.len() > depth {
+            self.asks.pop_last();
+        }
+    }
+
+    /// 应用已转换为 Decimal 的增量更新，`final_update_id` 为更新的最后序号，不大于本地序号时返回错误
+    pub fn apply_delta(&mut self, final_update_id: u64, bids: &[(Decimal, Decimal)], asks: &[(Decimal, Decimal)]) -> Result<(), Box<dyn Error>> {
+        if self.last_update_id >= final_update_id {
+            return Err("深度更新ID不连续，需要重新获取快照".into());
+        }
+        self.apply_levels(Side::Bid, bids);
+        self.apply_levels(Side::Ask, asks);
+        self.last_update_id = final_update_id;
+        Ok(())
+    }
+
+    /// 应用深度更新到订单薄
+    pub fn apply_depth_update(&mut self, update: &DepthUpdate) -> Result<(), Box<dyn Error>> {
+        // 如果快照中的 lastUpdateId 小于等于步骤 2 中的 U 值，请返回步骤 3。
+        // println!("当前self u {}",self.last_update_id);
+        if  self.last_update_id < update.u {
+            // 更新买单
+            for bid in &update.b {
+                let price = bid[0].parse::<Decimal>()?;
+                let quantity = bid[1].parse::<Decimal>()?;
+
+                if quantity.is_zero() {
+                    // 数量为0表示删除此价格的订单
+                    self.bids.remove(&price);
+                } else {
+                    // 更新或添加此价格的订单
+                    self.bids.insert(price, quantity);
+                }
+            }
+
+            // 更新卖单
+            for ask in &update.a {
+                let price = ask[0].parse::<Decimal>()?;
+                let quantity = ask[1].parse::<Decimal>()?;
+
+                if quantity.is_zero() {
+                    // 数量为0表示删除此价格的订单
+                    self.asks.remove(&price);
+                } else {
+                    // 更新或添加此价格的订单
+                    self.asks.insert(price, quantity);
+                }
+            }
+
+            // 更新最后更新ID
+            self.last_update_id = update.u;
+            Ok(())
+        } else {
+            Err("深度更新ID不连续，需要重新获取快照".into())
+        }
+    }
+
+    /// 获取买单列表（按价格降序排列）
+    pub fn bids_list(&self) -> Vec<(Decimal, Decimal)> {
+        let mut bids: Vec<(Decimal, Decimal)> = self.bids.iter()
+            .map(|(price, quantity)| (*price, *quantity))
+            .collect();
+
+        // 按价格降序排列
+        bids.sort_by_key(|bid| std::cmp::Reverse(bid.0));
+        bids
+    }
+
+    /// 获取卖单列表（按价格升序排列）
+    pub fn asks_list(&self) -> Vec<(Decimal, Decimal)> {
+        // BTreeMap已经按键升序排列，所以不需要额外排序
+        self.asks.iter()
+            .map(|(price, quantity)| (*price, *quantity))
+            .collect()
+    }
+
+    /// 打印订单薄信息
+    pub fn print_summary(&self, limit: usize) {
+        // println!("订单薄信息:");
+        println!("订单薄信息 最后更新 ID: {}", self.last_update_id);
+        // println!("买单数量: {}", self.bids.len());
+        // println!("卖单数量: {}", self.asks.len());
+
+        // 打印前N个买单（价格降序）
+        println!("前{}个买单 (价格降序):", limit);
+        for (i, (price, quantity)) in self.bids_list().iter().take(limit).enumerate() {
+            println!("{}. 价格: {}, 数量: {}", i+1, price, quantity);
+        }
+
+        // 打印前N个卖单（价格升序）
+        // println!("\n前{}个卖单 (价格升序):", limit);
+        // for (i, (price, quantity)) in self.asks_list().iter().take(limit).enumerate() {
+        //     println!("{}. 价格: {}, 数量: {}", i+1, price, quantity);
+        // }
+        println!();
+    }
+
+    /// 按金额打印订单薄信息，数量换算为计价货币（例如 USDT）
+    pub fn print_notional_summary(&self, limit: usize) {
+        println!("订单薄信息 最后更新 ID: {}", self.last_update_id);
+        println!("前{}个买单 (价格降序，计价货币金额):", limit);
+        for (i, level) in self.notional_levels(Side::Bid, limit).iter().enumerate() {
+            println!("{}. 价格: {}, 金额: {}, 累计: {}",
+                     i+1, level.price, level.notional.round_dp(2), level.cumulative_notional.round_dp(2));
+        }
+        println!();
+    }
+
+    /// 以 ASCII 柱状图打印前 `limit` 档，格式见 [`depth_histogram`]
+    pub fn print_histogram(&self, limit: usize) {
+        println!("订单薄信息 最后更新 ID: {}", self.last_update_id);
+        println!("{}", depth_histogram(&self.top_levels(Side::Bid, limit), &self.top_levels(Side::Ask, limit), HISTOGRAM_WIDTH));
+        println!();
+    }
+
+    /// 获取一侧前 `limit` 档 (价格, 数量)，从最优价开始
+    pub fn top_levels(&self, side: Side, limit: usize) -> Vec<(Decimal, Decimal)> {
+        self.side_levels(side).take(limit).map(|(price, quantity)| (*price, *quantity)).collect()
+    }
+
+    /// 获取一侧前 `limit` 档的金额深度，从最优价开始
+    pub fn notional_levels(&self, side: Side, limit: usize) -> Vec<NotionalLevel> {
+        let mut cumulative_notional = Decimal::ZERO;
+        self.side_levels(side).take(limit)
+            .map(|(price, quantity)| {
+                let notional = price * quantity;
+                cumulative_notional += notional;
+                NotionalLevel { price: *price, quantity: *quantity, notional, cumulative_notional }
+            })
+            .collect()
+    }
+
+    /// 获取一侧前 `n_levels` 档的累计金额
+    pub fn cumulative_notional(&self, side: Side, n_levels: usize) -> Decimal {
+        self.side_totals(side, n_levels).0
+    }
+
+    /// 获取某一价位的挂单数量，价位不存在时为 0
+    pub fn level_quantity(&self, side: Side, price: Decimal) -> Decimal {
+        let levels = match side {
+            Side::Bid => &self.bids,
+            Side::Ask => &self.asks,
+        };
+        levels.get(&price).copied().unwrap_or_default()
+    }
+
+    /// 获取最高买价
+    pub fn best_bid(&self) -> Option<(Decimal, Decimal)> {
+        self.bids.iter()
+            .max_by(|a, b| a.0.cmp(b.0))
+            .map(|(price, quantity)| (*price, *quantity))
+    }
+
+    /// 获取最低卖价
+    pub fn best_ask(&self) -> Option<(Decimal, Decimal)> {
+        self.asks.iter()
+            .min_by(|a, b| a.0.cmp(b.0))
+            .map(|(price, quantity)| (*price, *quantity))
+    }
+
+    /// 获取买卖价差
+    pub fn spread(&self) -> Option<Decimal> {
+        match (self.best_bid(), self.best_ask()) {
+            (Some((bid_price, _)), Some((ask_price, _))) => Some(ask_price - bid_price),
+            _ => None,
+        }
+    }
+
+    /// 获取中间价（最高买价与最低卖价的平均值）
+    pub fn mid_price(&self) -> Option<Decimal> {
+        let (bid_price, _) = self.best_bid()?;
+        let (ask_price, _) = self.best_ask()?;
+        Some((bid_price + ask_price) / Decimal::TWO)
+    }
+
+    /// 获取微观价格（按买一卖一数量加权的中间价）
+    ///
+    /// `买一价 × 卖一量 + 卖一价 × 买一量` 除以两侧数量之和，买盘较厚时偏向卖一价
+    pub fn microprice(&self) -> Option<Decimal> {
+        self.weighted_mid(1)
+    }
+
+    /// 获取前 `n_levels` 档加权中间价
+    ///
+    /// 先分别计算两侧前N档的成交量加权价，再按对侧数量加权，`n_levels` 为1时即微观价格
+    pub fn weighted_mid(&self, n_levels: usize) -> Option<Decimal> {
+        let bid_vwap = self.vwap_bid(n_levels)?;
+        let ask_vwap = self.vwap_ask(n_levels)?;
+        let (_, bid_volume) = self.side_totals(Side::Bid, n_levels);
+        let (_, ask_volume) = self.side_totals(Side::Ask, n_levels);
+        Some((bid_vwap * ask_volume + ask_vwap * bid_volume) / (bid_volume + ask_volume))
+    }
+
+    /// 获取买盘前 `n_levels` 档的成交量加权均价
+    pub fn vwap_bid(&self, n_levels: usize) -> Option<Decimal> {
+        self.vwap(Side::Bid, n_levels)
+    }
+
+    /// 获取卖盘前 `n_levels` 档的成交量加权均价
+    pub fn vwap_ask(&self, n_levels: usize) -> Option<Decimal> {
+        self.vwap(Side::Ask, n_levels)
+    }
+
+    /// 获取买盘从买一开始累计 `notional` 计价金额的均价，深度不足时返回 None
+    pub fn vwap_bid_notional(&self, notional: Decimal) -> Option<Decimal> {
+        self.vwap_notional(Side::Bid, notional)
+    }
+
+    /// 获取卖盘从卖一开始累计 `notional` 计价金额的均价，深度不足时返回 None
+    pub fn vwap_ask_notional(&self, notional: Decimal) -> Option<Decimal> {
+        self.vwap_notional(Side::Ask, notional)
+    }
+
+    /// 模拟数量为 `quantity` 的市价买单，沿卖盘逐档成交
+    pub fn simulate_market_buy(&self, quantity: Decimal) -> FillSimulation {
+        self.simulate_market_order(Side::Ask, quantity)
+    }
+
+    /// 模拟数量为 `quantity` 的市价卖单，沿买盘逐档成交
+    pub fn simulate_market_sell(&self, quantity: Decimal) -> FillSimulation {
+        self.simulate_market_order(Side::Bid, quantity)
+    }
+
+    fn simulate_market_order(&self, side: Side, quantity: Decimal) -> FillSimulation {
+        let mut simulation = FillSimulation {
+            side,
+            filled: Decimal::ZERO,
+            notional: Decimal::ZERO,
+            average_price: None,
+            worst_price: None,
+            best_price: self.side_levels(side).next().map(|(price, _)| *price),
+            levels: 0,
+            unfilled: quantity,
+        };
+        for (price, level_quantity) in self.side_levels(side) {
+            if simulation.unfilled <= Decimal::ZERO {
+                break;
+            }
+            let fill = (*level_quantity).min(simulation.unfilled);
+            simulation.filled += fill;
+            simulation.notional += price * fill;
+            simulation.unfilled -= fill;
+            simulation.worst_price = Some(*price);
+            simulation.levels += 1;
+        }
+        if !simulation.filled.is_zero() {
+            simulation.average_price = Some(simulation.notional / simulation.filled);
+        }
+        simulation
+    }
+
+    /// 计算市场冲击曲线
+    ///
+    /// 沿 `side` 方向按成交金额 `step`、`2 × step`... 直到 `max_notional` 取点，
+    /// 深度不足时曲线提前结束
+    ///
+    /// # 参数
+    ///
+    /// * `side` - 吃的盘口方向，买入为 Side::Ask，卖出为 Side::Bid
+    /// * `max_notional` - 最大累计成交金额
+    /// * `step` - 取点间隔（成交金额）
+    pub fn impact_curve(&self, side: Side, max_notional: Decimal, step: Decimal) -> Vec<ImpactPoint> {
+        let mut points = Vec::new();
+        let Some(reference) = self.mid_price().or_else(|| self.side_levels(side).next().map(|(price, _)| *price)) else {
+            return points;
+        };
+        if step <= Decimal::ZERO || reference.is_zero() {
+            return points;
+        }
+
+        let mut target = step;
+        let mut notional = Decimal::ZERO;
+        let mut quantity = Decimal::ZERO;
+        for (price, level_quantity) in self.side_levels(side) {
+            let level_end = notional + price * level_quantity;
+            // 目标金额落在本档内时按本档价格补足
+            while target <= max_notional && target <= level_end {
+                let point_quantity = quantity + (target - notional) / price;
+                let average_price = target / point_quantity;
+                points.push(ImpactPoint {
+                    notional: target,
+                    quantity: point_quantity,
+                    average_price,
+                    impact_bps: (average_price - reference).abs() / reference * Decimal::from(10_000),
+                });
+                target += step;
+            }
+            if target > max_notional {
+                break;
+            }
+            notional = level_end;
+            quantity += level_quantity;
+        }
+        points
+    }
+
+    /// 获取中间价上下 `bps` 基点以内的挂单深度
+    ///
+    /// 买盘统计价格不低于 `中间价 × (1 - bps / 10000)` 的档位，卖盘统计价格不高于
+    /// `中间价 × (1 + bps / 10000)` 的档位，缺少任意一侧时无法计算中间价，返回 None
+    pub fn depth_within_bps(&self, bps: Decimal) -> Option<LiquidityDepth> {
+        let mid = self.mid_price()?;
+        let offset = mid * bps / Decimal::from(10_000);
+        let (bid_notional, bid_quantity) = self.bids.range(mid - offset..).rev()
+            .fold((Decimal::ZERO, Decimal::ZERO), |(notional, volume), (price, quantity)| (notional + price * quantity, volume + quantity));
+        let (ask_notional, ask_quantity) = self.asks.range(..=mid + offset)
+            .fold((Decimal::ZERO, Decimal::ZERO), |(notional, volume), (price, quantity)| (notional + price * quantity, volume + quantity));
+        Some(LiquidityDepth { bid_quantity, ask_quantity, bid_notional, ask_notional })
+    }
+
+    /// 一侧前 `n_levels` 档的 (金额, 数量)
+    fn side_totals(&self, side: Side, n_levels: usize) -> (Decimal, Decimal) {
+        self.side_levels(side).take(n_levels)
+            .fold((Decimal::ZERO, Decimal::ZERO), |(notional, volume), (price, quantity)| (notional + price * quantity, volume + quantity))
+    }
+
+    fn vwap(&self, side: Side, n_levels: usize) -> Option<Decimal> {
+        let (notional, volume) = self.side_totals(side, n_levels);
+        if volume.is_zero() {
+            return None;
+        }
+        Some(notional / volume)
+    }
+
+    fn vwap_notional(&self, side: Side, notional: Decimal) -> Option<Decimal> {
+        if notional <= Decimal::ZERO {
+            return None;
+        }
+        let mut remaining = notional;
+        let mut volume = Decimal::ZERO;
+        for (price, quantity) in self.side_levels(side) {
+            let level_notional = price * quantity;
+            if level_notional >= remaining {
+                volume += remaining / price;
+                return Some(notional / volume);
+            }
+            volume += quantity;
+            remaining -= level_notional;
+        }
+        None
+    }
+
+    /// 一侧档位，从最优价开始
+    fn side_levels(&self, side: Side) -> Box<dyn Iterator<Item = (&Decimal, &Decimal)> + '_> {
+        match side {
+            Side::Bid => Box::new(self.bids.iter().rev()),
+            Side::Ask => Box::new(self.asks.iter()),
+        }
+    }
+
+    /// 获取前 `n_levels` 档的买卖盘不平衡度
+    ///
+    /// (买盘数量 - 卖盘数量) / (买盘数量 + 卖盘数量)，取值 -1 到 1，买盘较厚时为正
+    pub fn imbalance(&self, n_levels: usize) -> Option<Decimal> {
+        let bid_volume: Decimal = self.bids.values().rev().take(n_levels).sum();
+        let ask_volume: Decimal = self.asks.values().take(n_levels).sum();
+        let total = bid_volume + ask_volume;
+        if total.is_zero() {
+            return None;
+        }
+        Some((bid_volume - ask_volume) / total)
+    }
+
+    /// 计算一笔订单在限价以内会吃掉的深度
+    ///
+    /// 从最优价开始沿 `side` 方向逐档累计，直到数量满足或价格越过 `limit_price`
+    ///
+    /// # 返回值
+    ///
+    /// 返回 (涉及档位数, 可成交数量)
+    pub fn consumed_depth(&self, side: Side, limit_price: Decimal, quantity: Decimal) -> (usize, Decimal) {
+        let levels: Box<dyn Iterator<Item = (&Decimal, &Decimal)>> = match side {
+            Side::Bid => Box::new(self.bids.iter().rev().take_while(|(price, _)| **price >= limit_price)),
+            Side::Ask => Box::new(self.asks.iter().take_while(|(price, _)| **price <= limit_price)),
+        };
+
+        let mut consumed_levels = 0;
+        let mut consumed_quantity = Decimal::ZERO;
+        for (_, level_quantity) in levels {
+            if consumed_quantity >= quantity {
+                break;
+            }
+            consumed_levels += 1;
+            consumed_quantity += (*level_quantity).min(quantity - consumed_quantity);
+        }
+        (consumed_levels, consumed_quantity)
+    }
+
+    /// 更新标记价格
+    pub fn set_mark_price(&mut self, mark_price: MarkPrice) {
+        self.mark_price = Some(mark_price);
+    }
+
+    /// 获取最新标记价格
+    pub fn mark_price(&self) -> Option<Decimal> {
+        self.mark_price.map(|m| m.mark_price)
+    }
+
+    /// 获取最新指数价格
+    pub fn index_price(&self) -> Option<Decimal> {
+        self.mark_price.map(|m| m.index_price)
+    }
+
+    /// 获取基差（标记价格 - 中间价）
+    pub fn basis(&self) -> Option<Decimal> {
+        Some(self.mark_price()? - self.mid_price()?)
+    }
+
+    /// 把订单薄完整状态保存为二进制文件，可在进程之间传递或作为测试的初始订单薄
+    ///
+    /// 文件为 8 字节标识、2 字节小端格式版本，之后是 bincode 编码的说明信息、序号、档位和标记价格，
+    /// 价格和数量以十进制数的二进制形式保存，不损失精度。
+    /// 先写临时文件再重命名，读取方不会看到写了一半的文件
+    pub fn save_to(&self, path: impl AsRef<Path>, metadata: &BookMetadata) -> Result<(), Box<dyn Error>> {
+        let levels = |levels: Vec<(Decimal, Decimal)>| levels.iter()
+            .map(|(price, quantity)| (price.serialize(), quantity.serialize()))
+            .collect();
+        let file = BookFile {
+            metadata: metadata.clone(),
+            last_update_id: self.last_update_id,
+            bids: levels(self.bids_list()),
+            asks: levels(self.asks_list()),
+            mark_price: self.mark_price.map(|mark| (mark.event_time, mark.mark_price.serialize(), mark.index_price.serialize())),
+        };
+        let mut bytes = BOOK_FILE_MAGIC.to_vec();
+        bytes.extend_from_slice(&BOOK_FILE_VERSION.to_le_bytes());
+        bincode::serialize_into(&mut bytes, &file)?;
+        let path = path.as_ref();
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        fs::write(&temporary, bytes)?;
+        fs::rename(&temporary, path)?;
+        Ok(())
+    }
+
+    /// 读取 [`save_to`](Self::save_to) 保存的文件，返回订单薄和说明信息
+    pub fn load_from(path: impl AsRef<Path>) -> Result<(Self, BookMetadata), Box<dyn Error>> {
+        let bytes = fs::read(path)?;
+        let Some(payload) = bytes.strip_prefix(BOOK_FILE_MAGIC) else {
+            return Err("不是订单薄文件".into());
+        };
+        let (version, payload) = payload.split_at_checked(2).ok_or("订单薄文件不完整")?;
+        let version = u16::from_le_bytes(version.try_into()?);
+        if version != BOOK_FILE_VERSION {
+            return Err(format!("不支持的订单薄文件版本: {}", version).into());
+        }
+        let file: BookFile = bincode::deserialize(payload)?;
+        let levels = |levels: &[(DecimalBytes, DecimalBytes)]| levels.iter()
+            .map(|(price, quantity)| (Decimal::deserialize(*price), Decimal::deserialize(*quantity)))
+            .collect::<Vec<_>>();
+        let mut book = OrderBook::from_levels(file.last_update_id, &levels(&file.bids), &levels(&file.asks));
+        book.mark_price = file.mark_price.map(|(event_time, mark_price, index_price)| MarkPrice {
+            event_time,
+            mark_price: Decimal::deserialize(mark_price),
+            index_price: Decimal::deserialize(index_price),
+        });
+        Ok((book, file.metadata))
+    }
+
+    /// 获取基差（以中间价为基准的基点数）
+    pub fn basis_bps(&self) -> Option<Decimal> {
+        let basis = self.basis()?;
+        let mid = self.mid_price()?;
+        if mid.is_zero() {
+            return None;
+        }
+        Some(basis / mid * Decimal::from(10_000))
+    }
+}
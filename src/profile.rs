@@ -0,0 +1,163 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use serde_json::json;
+
+/// 默认采样窗口
+pub const DEFAULT_PROFILE_WINDOW: Duration = Duration::from_secs(10);
+
+/// 行情处理热路径上的阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// 从 WebSocket 或 TCP 读取一条消息，包括等待数据到达的时间
+    SocketRead,
+    /// 解析 JSON（FIX 行情为标签值格式）；其他交易所的适配器在解析时同时转换价格和数量，两者合计在此阶段
+    JsonParse,
+    /// 币安深度更新中价格和数量字符串转为 Decimal
+    DecimalConversion,
+    /// 把档位变动写入订单薄的有序映射
+    MapUpdate,
+    /// 写入记录器和各输出
+    SinkWrite,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 5] = [Stage::SocketRead, Stage::JsonParse, Stage::DecimalConversion, Stage::MapUpdate, Stage::SinkWrite];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stage::SocketRead => "socket_read",
+            Stage::JsonParse => "json_parse",
+            Stage::DecimalConversion => "decimal_conversion",
+            Stage::MapUpdate => "map_update",
+            Stage::SinkWrite => "sink_write",
+        }
+    }
+}
+
+/// 一个阶段在当前窗口内的累计
+#[derive(Debug)]
+struct StageCounter {
+    count: AtomicU64,
+    nanos: AtomicU64,
+    max_nanos: AtomicU64,
+}
+
+impl StageCounter {
+    const fn new() -> Self {
+        StageCounter { count: AtomicU64::new(0), nanos: AtomicU64::new(0), max_nanos: AtomicU64::new(0) }
+    }
+}
+
+/// 一个阶段在一个窗口内的耗时
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StageReport {
+    pub stage: Stage,
+    pub count: u64,
+    pub total: Duration,
+    pub max: Duration,
+}
+
+impl StageReport {
+    pub fn mean(&self) -> Option<Duration> {
+        (self.count > 0).then(|| Duration::from_nanos((self.total.as_nanos() / self.count as u128) as u64))
+    }
+}
+
+/// 最近一个完整采样窗口的各阶段耗时
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileReport {
+    pub window: Duration,
+    pub stages: Vec<StageReport>,
+}
+
+impl ProfileReport {
+    /// JSON 格式，时间单位为微秒，`share` 为该阶段占处理耗时的比例；读取消息包括等待时间，不计入处理耗时
+    pub fn to_json(&self) -> serde_json::Value {
+        let total: Duration = self.stages.iter()
+            .filter(|stage| stage.stage != Stage::SocketRead)
+            .map(|stage| stage.total)
+            .sum();
+        let stages: Vec<_> = self.stages.iter()
+            .map(|stage| json!({
+                "stage": stage.stage.name(),
+                "count": stage.count,
+                "totalUs": stage.total.as_secs_f64() * 1e6,
+                "meanUs": stage.mean().map(|mean| mean.as_secs_f64() * 1e6),
+                "maxUs": stage.max.as_secs_f64() * 1e6,
+                "share": (stage.stage != Stage::SocketRead && !total.is_zero())
+                    .then(|| stage.total.as_secs_f64() / total.as_secs_f64()),
+            }))
+            .collect();
+        json!({ "windowMs": self.window.as_millis() as u64, "stages": stages })
+    }
+}
+
+/// 进程内唯一的阶段计时器
+///
+/// 各行情线程和主循环都会记录，计数使用原子变量；未开启时 [`time`] 只多一次原子读取
+#[derive(Debug)]
+struct StageProfiler {
+    enabled: AtomicBool,
+    counters: [StageCounter; 5],
+    last: Mutex<Option<ProfileReport>>,
+}
+
+static PROFILER: StageProfiler = StageProfiler {
+    enabled: AtomicBool::new(false),
+    counters: [StageCounter::new(), StageCounter::new(), StageCounter::new(), StageCounter::new(), StageCounter::new()],
+    last: Mutex::new(None),
+};
+
+/// 开始按阶段计时，后台线程每隔 `window` 结束一个采样窗口，重复调用无效
+pub fn enable(window: Duration) {
+    if PROFILER.enabled.swap(true, Ordering::Relaxed) {
+        return;
+    }
+    thread::spawn(move || {
+        loop {
+            thread::sleep(window);
+            let stages = Stage::ALL.iter()
+                .zip(&PROFILER.counters)
+                .map(|(stage, counter)| StageReport {
+                    stage: *stage,
+                    count: counter.count.swap(0, Ordering::Relaxed),
+                    total: Duration::from_nanos(counter.nanos.swap(0, Ordering::Relaxed)),
+                    max: Duration::from_nanos(counter.max_nanos.swap(0, Ordering::Relaxed)),
+                })
+                .collect();
+            let report = ProfileReport { window, stages };
+            *PROFILER.last.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(report);
+        }
+    });
+}
+
+pub fn is_enabled() -> bool {
+    PROFILER.enabled.load(Ordering::Relaxed)
+}
+
+/// 记录一个阶段的一次耗时
+pub fn record(stage: Stage, elapsed: Duration) {
+    let counter = &PROFILER.counters[stage as usize];
+    let nanos = elapsed.as_nanos() as u64;
+    counter.count.fetch_add(1, Ordering::Relaxed);
+    counter.nanos.fetch_add(nanos, Ordering::Relaxed);
+    counter.max_nanos.fetch_max(nanos, Ordering::Relaxed);
+}
+
+/// 执行 `f` 并计入阶段 `stage`，未开启时直接执行
+pub fn time<T>(stage: Stage, f: impl FnOnce() -> T) -> T {
+    if !is_enabled() {
+        return f();
+    }
+    let started = Instant::now();
+    let result = f();
+    record(stage, started.elapsed());
+    result
+}
+
+/// 最近一个完整采样窗口的报告，未开启或第一个窗口尚未结束时返回 None
+pub fn report() -> Option<ProfileReport> {
+    PROFILER.last.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+}
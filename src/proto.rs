@@ -0,0 +1,120 @@
+//! Protobuf 格式的订单薄服务和统一行情，定义见 `proto/order_book.proto` 和 `proto/feed.proto`
+//!
+//! gRPC 服务和 Kafka、NATS 等输出使用同一套消息，下游用任意语言从同一份定义生成代码即可解码
+
+use std::error::Error;
+use prost::Message;
+use rust_decimal::Decimal;
+
+use crate::exchange::{Continuity, DepthKind, DepthMessage};
+use crate::order_book::OrderBook;
+use crate::capnp;
+use crate::recorder::Record;
+use crate::sbe;
+
+tonic::include_proto!("order_book");
+
+/// 输出消息的编码
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FeedFormat {
+    /// 与 NDJSON 记录的一行相同
+    #[default]
+    Json,
+    /// [`FeedMessage`]
+    Protobuf,
+    /// SBE，定义见 `schema/order_book_sbe.xml`
+    Sbe,
+    /// Cap'n Proto，定义见 `schema/feed.capnp`
+    CapnProto,
+}
+
+impl FeedFormat {
+    /// 解析 `json`、`protobuf`、`sbe` 或 `capnp`
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "json" => Some(FeedFormat::Json),
+            "protobuf" => Some(FeedFormat::Protobuf),
+            "sbe" => Some(FeedFormat::Sbe),
+            "capnp" => Some(FeedFormat::CapnProto),
+            _ => None,
+        }
+    }
+
+    /// 编码一条记录，原始消息帧只有 JSON 格式，其他格式返回 None
+    pub fn encode(&self, record: &Record) -> Result<Option<Vec<u8>>, Box<dyn Error>> {
+        match self {
+            FeedFormat::Json => Ok(Some(serde_json::to_vec(record)?)),
+            FeedFormat::Protobuf => Ok(FeedMessage::from_record(record).map(|message| message.encode_to_vec())),
+            FeedFormat::Sbe => sbe::encode_record(record),
+            FeedFormat::CapnProto => Ok(capnp::encode_record(record)),
+        }
+    }
+}
+
+impl From<&(Decimal, Decimal)> for Level {
+    fn from((price, quantity): &(Decimal, Decimal)) -> Self {
+        Level { price: price.to_string(), quantity: quantity.to_string() }
+    }
+}
+
+impl From<&DepthMessage> for Depth {
+    fn from(message: &DepthMessage) -> Self {
+        let continuity = match message.continuity {
+            Continuity::None => None,
+            Continuity::Prev { prev, sequence } => Some(depth::Continuity::Prev(PrevSequence { prev, sequence })),
+            Continuity::Range { first, last } => Some(depth::Continuity::Range(SequenceRange { first, last })),
+            Continuity::Monotonic(timestamp) => Some(depth::Continuity::Monotonic(timestamp)),
+        };
+        Depth {
+            bids: message.bids.iter().map(Level::from).collect(),
+            asks: message.asks.iter().map(Level::from).collect(),
+            continuity,
+            exchange_time: message.timestamp,
+            max_depth: message.max_depth.unwrap_or_default() as u32,
+        }
+    }
+}
+
+impl FeedMessage {
+    /// 由记录创建，原始消息帧返回 None
+    pub fn from_record(record: &Record) -> Option<Self> {
+        let (symbol, payload) = match record {
+            Record::Raw { .. } | Record::Binary { .. } => return None,
+            Record::Update { message, .. } => {
+                let depth = Depth::from(message);
+                let payload = match message.kind {
+                    DepthKind::Snapshot => feed_message::Payload::Snapshot(depth),
+                    DepthKind::Delta => feed_message::Payload::Delta(depth),
+                };
+                (message.symbol.clone(), payload)
+            }
+            Record::Keyframe { message, .. } => (message.symbol.clone(), feed_message::Payload::Keyframe(Depth::from(message))),
+            Record::Trade { symbol, trade, .. } => (symbol.clone(), feed_message::Payload::Trade(Trade {
+                price: trade.price.to_string(),
+                quantity: trade.quantity.to_string(),
+                buyer_is_maker: trade.buyer_is_maker,
+                trade_time: trade.trade_time,
+            })),
+        };
+        Some(FeedMessage {
+            venue: record.venue().to_string(),
+            symbol,
+            recv_time: record.recv_time(),
+            payload: Some(payload),
+        })
+    }
+
+    /// 订单薄的最优买卖价
+    pub fn top_of_book(venue: &str, symbol: &str, book: &OrderBook, local_time: u64) -> Self {
+        FeedMessage {
+            venue: venue.to_string(),
+            symbol: symbol.to_string(),
+            recv_time: local_time,
+            payload: Some(feed_message::Payload::TopOfBook(TopOfBook {
+                bid: book.best_bid().as_ref().map(Level::from),
+                ask: book.best_ask().as_ref().map(Level::from),
+                last_update_id: book.last_update_id,
+            })),
+        }
+    }
+}
@@ -0,0 +1,466 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use tracing::error;
+
+use crate::order_book::OrderBook;
+use crate::recorder::{utc_hour_label, Record, Recorder, HOUR_MS};
+use crate::replay::{book_at, is_book_start};
+use crate::trade::Trade;
+
+/// 默认 zstd 压缩级别
+pub const DEFAULT_LEVEL: i32 = 3;
+/// 默认关键帧间隔（毫秒）
+pub const DEFAULT_KEYFRAME_MS: u64 = 60_000;
+/// 压缩前数据达到该大小时结束当前块
+const BLOCK_SIZE: usize = 1 << 20;
+/// 块内第一条记录之后超过该时间（毫秒）结束当前块，限制异常退出时丢失的数据
+const BLOCK_MAX_MS: u64 = 5_000;
+/// 索引条目的字节数
+const INDEX_ENTRY_SIZE: usize = 28;
+
+/// 记录类型标记
+const TYPE_RAW: u8 = 0;
+const TYPE_BINARY: u8 = 1;
+const TYPE_UPDATE: u8 = 2;
+const TYPE_KEYFRAME: u8 = 3;
+const TYPE_TRADE: u8 = 4;
+
+/// 压缩块的索引条目
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockIndex {
+    /// 块在数据文件中的偏移（字节）
+    pub offset: u64,
+    /// 块内第一条记录的接收时间（毫秒）
+    pub first_time: u64,
+    /// 块内最后一条记录的接收时间（毫秒）
+    pub last_time: u64,
+    /// 块内记录数
+    pub frames: u32,
+}
+
+impl BlockIndex {
+    fn to_bytes(self) -> [u8; INDEX_ENTRY_SIZE] {
+        let mut bytes = [0u8; INDEX_ENTRY_SIZE];
+        bytes[0..8].copy_from_slice(&self.offset.to_le_bytes());
+        bytes[8..16].copy_from_slice(&self.first_time.to_le_bytes());
+        bytes[16..24].copy_from_slice(&self.last_time.to_le_bytes());
+        bytes[24..28].copy_from_slice(&self.frames.to_le_bytes());
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        let u64_at = |i: usize| u64::from_le_bytes(bytes[i..i + 8].try_into().unwrap_or_default());
+        BlockIndex {
+            offset: u64_at(0),
+            first_time: u64_at(8),
+            last_time: u64_at(16),
+            frames: u32::from_le_bytes(bytes[24..28].try_into().unwrap_or_default()),
+        }
+    }
+}
+
+/// 正在写入的文件
+#[derive(Debug)]
+struct CaptureFile {
+    hour: u64,
+    data: BufWriter<File>,
+    index: File,
+    /// 数据文件当前长度
+    offset: u64,
+}
+
+/// zstd 压缩的二进制行情记录器
+///
+/// 数据文件 `目录/YYYYMMDD-HH.zcap` 由压缩块组成，每块为 `u32 压缩长度 + zstd 数据`，
+/// 解压后是若干 `u32 长度 + 记录` 的帧；记录为 `u64 接收时间 + u8 类型 + u8 交易所名长度 + 交易所名 + 内容`，
+/// 内容为原始文本、原始二进制，或深度更新、关键帧、`[交易对, 成交]` 的 JSON。每写完一块在 `.zidx` 索引文件中追加
+/// `u64 偏移 + u64 首条时间 + u64 末条时间 + u32 记录数`，所有整数均为小端序。
+/// 每个订单薄按固定间隔写入关键帧（完整快照），重建任意时刻的订单薄时只需从之前最近的关键帧开始
+#[derive(Debug)]
+pub struct CaptureRecorder {
+    dir: PathBuf,
+    level: i32,
+    keyframe_interval_ms: u64,
+    /// (交易所, 交易对) -> 上次写入关键帧的时间
+    last_keyframe: HashMap<(String, String), u64>,
+    file: Option<CaptureFile>,
+    /// 当前块未压缩的帧
+    block: Vec<u8>,
+    block_frames: u32,
+    block_first: u64,
+    block_last: u64,
+}
+
+impl CaptureRecorder {
+    /// 创建记录器，目录不存在时自动创建
+    ///
+    /// # 参数
+    ///
+    /// * `dir` - 记录目录
+    /// * `level` - zstd 压缩级别，例如 [`DEFAULT_LEVEL`]
+    /// * `keyframe_interval_ms` - 每个订单薄的关键帧间隔（毫秒），例如 [`DEFAULT_KEYFRAME_MS`]
+    pub fn new(dir: impl AsRef<Path>, level: i32, keyframe_interval_ms: u64) -> Result<Self, Box<dyn Error>> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(CaptureRecorder {
+            dir: dir.as_ref().to_path_buf(),
+            level,
+            keyframe_interval_ms,
+            last_keyframe: HashMap::new(),
+            file: None,
+            block: Vec::new(),
+            block_frames: 0,
+            block_first: 0,
+            block_last: 0,
+        })
+    }
+
+    /// 当前正在写入的数据文件
+    pub fn current_path(&self) -> Option<PathBuf> {
+        self.file.as_ref().map(|file| self.path_for(file.hour * HOUR_MS))
+    }
+
+    /// 压缩当前块并写入数据文件和索引
+    fn finish_block(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.block_frames == 0 {
+            return Ok(());
+        }
+        let Some(file) = self.file.as_mut() else {
+            return Ok(());
+        };
+        let compressed = zstd::bulk::compress(&self.block, self.level)?;
+        let entry = BlockIndex {
+            offset: file.offset,
+            first_time: self.block_first,
+            last_time: self.block_last,
+            frames: self.block_frames,
+        };
+        file.data.write_all(&(compressed.len() as u32).to_le_bytes())?;
+        file.data.write_all(&compressed)?;
+        file.data.flush()?;
+        file.offset += 4 + compressed.len() as u64;
+        // 数据写入后再追加索引，索引中的块总是完整的
+        file.index.write_all(&entry.to_bytes())?;
+
+        self.block.clear();
+        self.block_frames = 0;
+        Ok(())
+    }
+
+    /// 切换到 `recv_time` 所在小时的文件
+    fn rotate(&mut self, recv_time: u64) -> Result<(), Box<dyn Error>> {
+        let hour = recv_time / HOUR_MS;
+        if self.file.as_ref().is_some_and(|file| file.hour == hour) {
+            return Ok(());
+        }
+        self.finish_block()?;
+        let path = self.path_for(recv_time);
+        let data = OpenOptions::new().create(true).append(true).open(&path)?;
+        let index = OpenOptions::new().create(true).append(true).open(path.with_extension("zidx"))?;
+        self.file = Some(CaptureFile {
+            hour,
+            offset: data.metadata()?.len(),
+            data: BufWriter::new(data),
+            index,
+        });
+        Ok(())
+    }
+
+    fn path_for(&self, local_time: u64) -> PathBuf {
+        self.dir.join(format!("{}.zcap", utc_hour_label(local_time)))
+    }
+}
+
+impl Recorder for CaptureRecorder {
+    fn record(&mut self, record: &Record) -> Result<(), Box<dyn Error>> {
+        let recv_time = record.recv_time();
+        self.rotate(recv_time)?;
+        if self.block_frames > 0 && recv_time >= self.block_first + BLOCK_MAX_MS {
+            self.finish_block()?;
+        }
+
+        let frame = encode_record(record)?;
+        self.block.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        self.block.extend_from_slice(&frame);
+        if self.block_frames == 0 {
+            self.block_first = recv_time;
+        }
+        self.block_last = recv_time;
+        self.block_frames += 1;
+
+        if self.block.len() >= BLOCK_SIZE {
+            self.finish_block()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Box<dyn Error>> {
+        self.finish_block()
+    }
+
+    fn on_book(&mut self, venue: &str, symbol: &str, book: &OrderBook, local_time: u64) -> Result<(), Box<dyn Error>> {
+        let key = (venue.to_string(), symbol.to_string());
+        if self.last_keyframe.get(&key).is_some_and(|last| local_time < last + self.keyframe_interval_ms) {
+            return Ok(());
+        }
+        self.last_keyframe.insert(key, local_time);
+        self.record(&Record::keyframe(venue, symbol, book, local_time))
+    }
+
+    fn wants_trades(&self) -> bool {
+        true
+    }
+
+    fn on_trade(&mut self, venue: &str, symbol: &str, trade: &Trade, local_time: u64) -> Result<(), Box<dyn Error>> {
+        self.record(&Record::trade(venue, symbol, trade, local_time))
+    }
+}
+
+impl Drop for CaptureRecorder {
+    fn drop(&mut self) {
+        if let Err(e) = self.finish_block() {
+            error!(error = %e, "写入压缩记录失败");
+        }
+    }
+}
+
+/// 压缩记录文件的读取器
+#[derive(Debug)]
+pub struct CaptureReader {
+    file: File,
+    blocks: Vec<BlockIndex>,
+}
+
+impl CaptureReader {
+    /// 打开数据文件，索引文件不存在或为空时扫描数据文件重建索引
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        let path = path.as_ref();
+        let mut file = File::open(path)?;
+        let blocks = match fs::read(path.with_extension("zidx")) {
+            Ok(index) if !index.is_empty() => index.chunks_exact(INDEX_ENTRY_SIZE).map(BlockIndex::from_bytes).collect(),
+            _ => scan_blocks(&mut file)?,
+        };
+        Ok(CaptureReader { file, blocks })
+    }
+
+    /// 所有压缩块的索引
+    pub fn blocks(&self) -> &[BlockIndex] {
+        &self.blocks
+    }
+
+    /// 读取并解压第 `i` 个块
+    pub fn read_block(&mut self, i: usize) -> Result<Vec<Record>, Box<dyn Error>> {
+        let block = self.blocks.get(i).ok_or("块序号超出范围")?;
+        let data = read_block_at(&mut self.file, block.offset)?;
+        decode_block(&data)
+    }
+
+    /// 读取接收时间不早于 `time` 的所有记录，借助索引跳过之前的块
+    pub fn read_from(&mut self, time: u64) -> Result<Vec<Record>, Box<dyn Error>> {
+        let start = self.blocks.partition_point(|block| block.last_time < time);
+        let mut records = Vec::new();
+        for i in start..self.blocks.len() {
+            records.extend(self.read_block(i)?.into_iter().filter(|record| record.recv_time() >= time));
+        }
+        Ok(records)
+    }
+
+    /// 读取所有记录
+    pub fn read_all(&mut self) -> Result<Vec<Record>, Box<dyn Error>> {
+        self.read_from(0)
+    }
+
+    /// 重建 `time` 时刻的订单薄，没有足够的数据时返回 None
+    ///
+    /// 从包含该时刻的块向前查找最近的关键帧或快照，只解压从那里开始的块
+    pub fn book_at(&mut self, venue: &str, symbol: &str, time: u64) -> Result<Option<OrderBook>, Box<dyn Error>> {
+        let end = self.blocks.partition_point(|block| block.first_time <= time);
+        let mut records = Vec::new();
+        for i in (0..end).rev() {
+            let mut block = self.read_block(i)?;
+            let found = block.iter().any(|record| record.recv_time() <= time && is_book_start(record, venue, symbol));
+            block.append(&mut records);
+            records = block;
+            if found {
+                break;
+            }
+        }
+        Ok(book_at(&records, venue, symbol, time))
+    }
+}
+
+/// 压缩记录文件的完整性检查结果
+#[derive(Debug, Default)]
+pub struct CaptureScan {
+    /// 能够解压和解码的块数
+    pub blocks: usize,
+    /// 全部可读的记录，按文件中的顺序
+    pub records: Vec<Record>,
+    /// 发现的问题
+    pub problems: Vec<String>,
+}
+
+/// 顺序读取数据文件的全部块并与索引比较
+///
+/// 与 [`CaptureReader`] 不同，遇到损坏的块时记录问题并跳过，继续读取之后的块；
+/// 块头或块数据超出文件末尾时认为文件被截断并停止
+pub fn scan_capture(path: impl AsRef<Path>) -> Result<CaptureScan, Box<dyn Error>> {
+    let path = path.as_ref();
+    let mut file = File::open(path)?;
+    let file_len = file.metadata()?.len();
+    let mut scan = CaptureScan::default();
+    let mut found = Vec::new();
+    let mut offset = 0;
+    while offset < file_len {
+        if offset + 4 > file_len {
+            scan.problems.push(format!("末尾 {} 字节不足一个块头，文件可能被截断", file_len - offset));
+            break;
+        }
+        file.seek(SeekFrom::Start(offset))?;
+        let mut len = [0u8; 4];
+        file.read_exact(&mut len)?;
+        let end = offset + 4 + u64::from(u32::from_le_bytes(len));
+        if end > file_len {
+            scan.problems.push(format!("偏移 {} 的块结束于 {}，超出文件长度 {}，文件可能被截断", offset, end, file_len));
+            break;
+        }
+        match read_block_at(&mut file, offset).and_then(|data| decode_block(&data)) {
+            Ok(records) => {
+                if let (Some(first), Some(last)) = (records.first(), records.last()) {
+                    found.push(BlockIndex {
+                        offset,
+                        first_time: first.recv_time(),
+                        last_time: last.recv_time(),
+                        frames: records.len() as u32,
+                    });
+                }
+                scan.blocks += 1;
+                scan.records.extend(records);
+            }
+            Err(e) => scan.problems.push(format!("偏移 {} 的块已损坏: {}", offset, e)),
+        }
+        offset = end;
+    }
+
+    let index = fs::read(path.with_extension("zidx")).unwrap_or_default();
+    if index.is_empty() {
+        scan.problems.push("索引文件不存在或为空".to_string());
+        return Ok(scan);
+    }
+    if index.len() % INDEX_ENTRY_SIZE != 0 {
+        scan.problems.push(format!("索引文件末尾有 {} 字节不完整的条目", index.len() % INDEX_ENTRY_SIZE));
+    }
+    let entries: Vec<BlockIndex> = index.chunks_exact(INDEX_ENTRY_SIZE).map(BlockIndex::from_bytes).collect();
+    for entry in &entries {
+        match found.iter().find(|block| block.offset == entry.offset) {
+            Some(block) if block != entry => {
+                scan.problems.push(format!("偏移 {} 的索引条目与块内容不一致: 索引 {:?}，实际 {:?}", entry.offset, entry, block));
+            }
+            Some(_) => {}
+            None => scan.problems.push(format!("索引条目指向的偏移 {} 没有有效的块", entry.offset)),
+        }
+    }
+    for block in &found {
+        if !entries.iter().any(|entry| entry.offset == block.offset) {
+            scan.problems.push(format!("偏移 {} 的块不在索引中", block.offset));
+        }
+    }
+    Ok(scan)
+}
+
+/// 编码一条记录
+fn encode_record(record: &Record) -> Result<Vec<u8>, Box<dyn Error>> {
+    let (record_type, content) = match record {
+        Record::Raw { frame, .. } => (TYPE_RAW, frame.as_bytes().to_vec()),
+        Record::Binary { frame, .. } => (TYPE_BINARY, decode_hex(frame)?),
+        Record::Update { message, .. } => (TYPE_UPDATE, serde_json::to_vec(message)?),
+        Record::Keyframe { message, .. } => (TYPE_KEYFRAME, serde_json::to_vec(message)?),
+        Record::Trade { symbol, trade, .. } => (TYPE_TRADE, serde_json::to_vec(&(symbol, trade))?),
+    };
+    let venue = record.venue().as_bytes();
+    let venue_len = u8::try_from(venue.len()).map_err(|_| "交易所名称过长")?;
+    let mut bytes = Vec::with_capacity(10 + venue.len() + content.len());
+    bytes.extend_from_slice(&record.recv_time().to_le_bytes());
+    bytes.push(record_type);
+    bytes.push(venue_len);
+    bytes.extend_from_slice(venue);
+    bytes.extend_from_slice(&content);
+    Ok(bytes)
+}
+
+/// 解码一条记录
+fn decode_record(bytes: &[u8]) -> Result<Record, Box<dyn Error>> {
+    let header = bytes.get(..10).ok_or("记录长度不足")?;
+    let recv_time = u64::from_le_bytes(header[..8].try_into()?);
+    let venue_end = 10 + header[9] as usize;
+    let venue = std::str::from_utf8(bytes.get(10..venue_end).ok_or("记录长度不足")?)?.to_string();
+    let content = &bytes[venue_end..];
+    match header[8] {
+        TYPE_RAW => Ok(Record::Raw { recv_time, venue, frame: String::from_utf8(content.to_vec())? }),
+        TYPE_BINARY => Ok(Record::Binary { recv_time, venue, frame: content.iter().map(|byte| format!("{:02x}", byte)).collect() }),
+        TYPE_UPDATE => Ok(Record::Update { recv_time, venue, message: serde_json::from_slice(content)? }),
+        TYPE_KEYFRAME => Ok(Record::Keyframe { recv_time, venue, message: serde_json::from_slice(content)? }),
+        TYPE_TRADE => {
+            let (symbol, trade) = serde_json::from_slice(content)?;
+            Ok(Record::Trade { recv_time, venue, symbol, trade })
+        }
+        other => Err(format!("未知的记录类型: {}", other).into()),
+    }
+}
+
+/// 解码解压后的块
+fn decode_block(data: &[u8]) -> Result<Vec<Record>, Box<dyn Error>> {
+    let mut records = Vec::new();
+    let mut rest = data;
+    while !rest.is_empty() {
+        let (len, tail) = rest.split_at_checked(4).ok_or("帧长度不完整")?;
+        let len = u32::from_le_bytes(len.try_into()?) as usize;
+        let (frame, tail) = tail.split_at_checked(len).ok_or("帧数据不完整")?;
+        records.push(decode_record(frame)?);
+        rest = tail;
+    }
+    Ok(records)
+}
+
+/// 读取并解压 `offset` 处的块
+fn read_block_at(file: &mut File, offset: u64) -> Result<Vec<u8>, Box<dyn Error>> {
+    file.seek(SeekFrom::Start(offset))?;
+    let mut len = [0u8; 4];
+    file.read_exact(&mut len)?;
+    let mut compressed = vec![0u8; u32::from_le_bytes(len) as usize];
+    file.read_exact(&mut compressed)?;
+    Ok(zstd::stream::decode_all(compressed.as_slice())?)
+}
+
+/// 顺序扫描数据文件重建索引，末尾不完整的块被忽略
+fn scan_blocks(file: &mut File) -> Result<Vec<BlockIndex>, Box<dyn Error>> {
+    let file_len = file.metadata()?.len();
+    let mut blocks = Vec::new();
+    let mut offset = 0;
+    while offset + 4 <= file_len {
+        let Ok(data) = read_block_at(file, offset) else {
+            break;
+        };
+        let records = decode_block(&data)?;
+        if let (Some(first), Some(last)) = (records.first(), records.last()) {
+            blocks.push(BlockIndex {
+                offset,
+                first_time: first.recv_time(),
+                last_time: last.recv_time(),
+                frames: records.len() as u32,
+            });
+        }
+        offset = file.stream_position()?;
+    }
+    Ok(blocks)
+}
+
+/// 十六进制字符串转为字节
+fn decode_hex(hex: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| Ok(u8::from_str_radix(hex.get(i..i + 2).ok_or("十六进制字符串长度为奇数")?, 16)?))
+        .collect()
+}
@@ -0,0 +1,248 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use reqwest::blocking::Client;
+use serde_json::json;
+use tracing::{error, warn};
+
+use crate::exchange::{DepthKind, DepthMessage};
+use crate::recorder::columns::to_f64;
+use crate::recorder::{Record, Recorder};
+
+/// HTTP 请求超时
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// 第一次重试前的等待时间，之后每次翻倍
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+/// 重试等待时间上限
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// ClickHouse 写入配置
+#[derive(Debug, Clone)]
+pub struct ClickHouseConfig {
+    /// HTTP 接口地址，例如 `http://localhost:8123`
+    pub url: String,
+    /// 数据库
+    pub database: String,
+    /// 用户名和密码，为 None 时使用服务端默认用户
+    pub user: Option<(String, String)>,
+    /// 每批写入的档位变动行数
+    pub batch_size: usize,
+    /// 行数不足一批时最长等待时间
+    pub flush_interval: Duration,
+    /// 写入失败后内存中保留的最大行数，超出时丢弃最早的批次
+    pub max_backlog_rows: usize,
+}
+
+impl ClickHouseConfig {
+    /// 使用默认参数：default 数据库、每批10万行、最长等待1秒、积压上限1000万行
+    pub fn new(url: &str) -> Self {
+        ClickHouseConfig {
+            url: url.trim_end_matches('/').to_string(),
+            database: "default".to_string(),
+            user: None,
+            batch_size: 100_000,
+            flush_interval: Duration::from_secs(1),
+            max_backlog_rows: 10_000_000,
+        }
+    }
+}
+
+/// 待写入的一批数据，已编码为 JSONEachRow
+#[derive(Debug)]
+struct Batch {
+    body: String,
+    rows: usize,
+}
+
+/// 发往写入线程的命令
+#[derive(Debug)]
+enum Command {
+    Update {
+        recv_time: u64,
+        venue: String,
+        message: DepthMessage,
+    },
+    Flush,
+}
+
+/// ClickHouse 行情记录器
+///
+/// 通过 HTTP 接口把统一格式的深度更新按档位展开批量写入 `deltas` 表（MergeTree，
+/// 按天分区，按交易所、交易对和时间排序），启动时自动建表。写入在后台线程进行，
+/// 失败的批次保留在内存中，按指数退避重试，恢复后按原顺序补写
+#[derive(Debug)]
+pub struct ClickHouseRecorder {
+    sender: Option<Sender<Command>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl ClickHouseRecorder {
+    /// 连接服务、建表并启动写入线程
+    pub fn connect(config: ClickHouseConfig) -> Result<Self, Box<dyn Error>> {
+        let client = Client::builder().timeout(REQUEST_TIMEOUT).build()?;
+        execute(&client, &config, &format!(
+            "CREATE TABLE IF NOT EXISTS {}.deltas (
+                recv_time UInt64,
+                exchange_time UInt64,
+                venue LowCardinality(String),
+                symbol LowCardinality(String),
+                kind Enum8('snapshot' = 1, 'delta' = 2),
+                sequence Nullable(UInt64),
+                side Enum8('bid' = 1, 'ask' = 2),
+                price Float64,
+                quantity Float64
+            ) ENGINE = MergeTree
+            PARTITION BY toYYYYMMDD(toDateTime(intDiv(recv_time, 1000)))
+            ORDER BY (venue, symbol, recv_time)",
+            config.database,
+        ), "")?;
+        let (sender, receiver) = mpsc::channel();
+        let writer = thread::spawn(move || run_writer(&client, &config, receiver));
+        Ok(ClickHouseRecorder {
+            sender: Some(sender),
+            writer: Some(writer),
+        })
+    }
+
+    fn send(&self, command: Command) -> Result<(), Box<dyn Error>> {
+        let sender = self.sender.as_ref().ok_or("写入线程已停止")?;
+        sender.send(command).map_err(|_| "写入线程已停止".into())
+    }
+}
+
+impl Recorder for ClickHouseRecorder {
+    fn record(&mut self, record: &Record) -> Result<(), Box<dyn Error>> {
+        let Record::Update { recv_time, venue, message } = record else {
+            return Ok(());
+        };
+        self.send(Command::Update {
+            recv_time: *recv_time,
+            venue: venue.clone(),
+            message: message.clone(),
+        })
+    }
+
+    fn flush(&mut self) -> Result<(), Box<dyn Error>> {
+        self.send(Command::Flush)
+    }
+}
+
+impl Drop for ClickHouseRecorder {
+    fn drop(&mut self) {
+        // 关闭通道后写入线程尝试写完剩余数据退出
+        self.sender.take();
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+/// 执行一条语句，`body` 为随语句发送的数据
+fn execute(client: &Client, config: &ClickHouseConfig, query: &str, body: &str) -> Result<(), Box<dyn Error>> {
+    let mut request = client.post(&config.url)
+        .query(&[("query", query), ("database", config.database.as_str())])
+        .body(body.to_string());
+    if let Some((user, password)) = &config.user {
+        request = request.header("X-ClickHouse-User", user).header("X-ClickHouse-Key", password);
+    }
+    let response = request.send()?;
+    if !response.status().is_success() {
+        let status = response.status();
+        return Err(format!("ClickHouse 返回 {}: {}", status, response.text().unwrap_or_default().trim()).into());
+    }
+    Ok(())
+}
+
+/// 把深度更新按档位编码为 JSONEachRow 行
+fn encode_update(body: &mut String, recv_time: u64, venue: &str, message: &DepthMessage) -> usize {
+    let kind = match message.kind {
+        DepthKind::Snapshot => "snapshot",
+        DepthKind::Delta => "delta",
+    };
+    let levels = message.bids.iter().map(|level| ("bid", level))
+        .chain(message.asks.iter().map(|level| ("ask", level)));
+    let mut rows = 0;
+    for (side, (price, quantity)) in levels {
+        let row = json!({
+            "recv_time": recv_time,
+            "exchange_time": message.timestamp,
+            "venue": venue,
+            "symbol": message.symbol,
+            "kind": kind,
+            "sequence": message.continuity.sequence(),
+            "side": side,
+            "price": to_f64(*price),
+            "quantity": to_f64(*quantity),
+        });
+        body.push_str(&row.to_string());
+        body.push('\n');
+        rows += 1;
+    }
+    rows
+}
+
+/// 写入线程：积累到批大小或时间间隔后写入，失败的批次保留到积压队列中按顺序补写
+fn run_writer(client: &Client, config: &ClickHouseConfig, receiver: Receiver<Command>) {
+    let insert = format!("INSERT INTO {}.deltas FORMAT JSONEachRow", config.database);
+    let mut current = Batch { body: String::new(), rows: 0 };
+    let mut backlog: VecDeque<Batch> = VecDeque::new();
+    let mut backlog_rows = 0;
+    let mut batch_start = Instant::now();
+    let mut retry_delay = RETRY_DELAY;
+    let mut next_retry = Instant::now();
+    loop {
+        let timeout = config.flush_interval.saturating_sub(batch_start.elapsed());
+        let (flush, closed) = match receiver.recv_timeout(timeout) {
+            Ok(Command::Update { recv_time, venue, message }) => {
+                if current.rows == 0 {
+                    batch_start = Instant::now();
+                }
+                current.rows += encode_update(&mut current.body, recv_time, &venue, &message);
+                (current.rows >= config.batch_size, false)
+            }
+            Ok(Command::Flush) | Err(RecvTimeoutError::Timeout) => (true, false),
+            Err(RecvTimeoutError::Disconnected) => (true, true),
+        };
+        if !flush {
+            continue;
+        }
+        batch_start = Instant::now();
+        if current.rows > 0 {
+            backlog_rows += current.rows;
+            backlog.push_back(std::mem::replace(&mut current, Batch { body: String::new(), rows: 0 }));
+        }
+        while backlog_rows > config.max_backlog_rows
+            && let Some(dropped) = backlog.pop_front()
+        {
+            backlog_rows -= dropped.rows;
+            warn!(max_backlog_rows = config.max_backlog_rows, dropped_rows = dropped.rows, "ClickHouse 积压过多，丢弃最早的数据");
+        }
+
+        // 退避期间只积累数据，退出前最后尝试一次
+        if closed || Instant::now() >= next_retry {
+            while let Some(batch) = backlog.front() {
+                match execute(client, config, &insert, &batch.body) {
+                    Ok(()) => {
+                        backlog_rows -= batch.rows;
+                        backlog.pop_front();
+                        retry_delay = RETRY_DELAY;
+                    }
+                    Err(e) => {
+                        warn!(backlog_rows, error = %e, "ClickHouse 写入失败，{} 秒后重试", retry_delay.as_secs());
+                        next_retry = Instant::now() + retry_delay;
+                        retry_delay = (retry_delay * 2).min(MAX_RETRY_DELAY);
+                        break;
+                    }
+                }
+            }
+        }
+        if closed {
+            if backlog_rows > 0 {
+                error!(backlog_rows, "ClickHouse 写入线程退出，丢弃积压的数据");
+            }
+            return;
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use std::error::Error;
+use std::sync::Arc;
+use arrow::array::{ArrayRef, Float64Array, RecordBatch, StringArray, TimestampMillisecondArray, UInt32Array, UInt64Array};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+use crate::exchange::{DepthKind, DepthMessage};
+use crate::order_book::{OrderBook, Side};
+
+/// 缓冲的行数达到该值时写入一个批次
+pub(crate) const BATCH_ROWS: usize = 8192;
+
+/// 时间列类型，UTC 毫秒时间戳
+pub fn timestamp_type() -> DataType {
+    DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into()))
+}
+
+/// 深度变动表结构，每行为一个档位变动
+///
+/// | 列 | 类型 | 说明 |
+/// |---|---|---|
+/// | recv_time | timestamp[ms, UTC] | 本地接收时间 |
+/// | exchange_time | timestamp[ms, UTC] | 交易所事件时间 |
+/// | venue | string | 交易所 |
+/// | symbol | string | 交易所原生交易对 |
+/// | kind | string | snapshot 或 delta |
+/// | sequence | uint64，可为空 | 消息处理后的序号 |
+/// | side | string | bid 或 ask |
+/// | price | float64 | 价格 |
+/// | quantity | float64 | 数量，0 表示删除 |
+pub fn delta_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("recv_time", timestamp_type(), false),
+        Field::new("exchange_time", timestamp_type(), false),
+        Field::new("venue", DataType::Utf8, false),
+        Field::new("symbol", DataType::Utf8, false),
+        Field::new("kind", DataType::Utf8, false),
+        Field::new("sequence", DataType::UInt64, true),
+        Field::new("side", DataType::Utf8, false),
+        Field::new("price", DataType::Float64, false),
+        Field::new("quantity", DataType::Float64, false),
+    ]))
+}
+
+/// 定期快照表结构，每行为快照中的一档
+///
+/// | 列 | 类型 | 说明 |
+/// |---|---|---|
+/// | time | timestamp[ms, UTC] | 本地快照时间 |
+/// | venue | string | 交易所 |
+/// | symbol | string | 交易所原生交易对 |
+/// | sequence | uint64 | 订单薄当前序号 |
+/// | side | string | bid 或 ask |
+/// | level | uint32 | 档位，0 为最优价 |
+/// | price | float64 | 价格 |
+/// | quantity | float64 | 数量 |
+pub fn snapshot_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("time", timestamp_type(), false),
+        Field::new("venue", DataType::Utf8, false),
+        Field::new("symbol", DataType::Utf8, false),
+        Field::new("sequence", DataType::UInt64, false),
+        Field::new("side", DataType::Utf8, false),
+        Field::new("level", DataType::UInt32, false),
+        Field::new("price", DataType::Float64, false),
+        Field::new("quantity", DataType::Float64, false),
+    ]))
+}
+
+pub(crate) fn side_name(side: Side) -> &'static str {
+    match side {
+        Side::Bid => "bid",
+        Side::Ask => "ask",
+    }
+}
+
+pub(crate) fn to_f64(value: Decimal) -> f64 {
+    value.to_f64().unwrap_or(f64::NAN)
+}
+
+/// 缓冲的深度变动行
+#[derive(Debug, Default)]
+pub(crate) struct DeltaRows {
+    recv_time: Vec<i64>,
+    exchange_time: Vec<i64>,
+    venue: Vec<String>,
+    symbol: Vec<String>,
+    kind: Vec<&'static str>,
+    sequence: Vec<Option<u64>>,
+    side: Vec<&'static str>,
+    price: Vec<f64>,
+    quantity: Vec<f64>,
+}
+
+impl DeltaRows {
+    /// 把一条深度消息按档位展开
+    pub(crate) fn push_update(&mut self, recv_time: u64, venue: &str, message: &DepthMessage) {
+        let kind = match message.kind {
+            DepthKind::Snapshot => "snapshot",
+            DepthKind::Delta => "delta",
+        };
+        let levels = message.bids.iter().map(|level| (Side::Bid, level))
+            .chain(message.asks.iter().map(|level| (Side::Ask, level)));
+        for (side, (price, quantity)) in levels {
+            self.recv_time.push(recv_time as i64);
+            self.exchange_time.push(message.timestamp as i64);
+            self.venue.push(venue.to_string());
+            self.symbol.push(message.symbol.clone());
+            self.kind.push(kind);
+            self.sequence.push(message.continuity.sequence());
+            self.side.push(side_name(side));
+            self.price.push(to_f64(*price));
+            self.quantity.push(to_f64(*quantity));
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.recv_time.len()
+    }
+
+    pub(crate) fn take_batch(&mut self) -> Result<RecordBatch, Box<dyn Error>> {
+        let rows = std::mem::take(self);
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(TimestampMillisecondArray::from(rows.recv_time).with_timezone("UTC")),
+            Arc::new(TimestampMillisecondArray::from(rows.exchange_time).with_timezone("UTC")),
+            Arc::new(StringArray::from(rows.venue)),
+            Arc::new(StringArray::from(rows.symbol)),
+            Arc::new(StringArray::from(rows.kind)),
+            Arc::new(UInt64Array::from(rows.sequence)),
+            Arc::new(StringArray::from(rows.side)),
+            Arc::new(Float64Array::from(rows.price)),
+            Arc::new(Float64Array::from(rows.quantity)),
+        ];
+        Ok(RecordBatch::try_new(delta_schema(), columns)?)
+    }
+}
+
+/// 缓冲的快照行
+#[derive(Debug, Default)]
+pub(crate) struct SnapshotRows {
+    time: Vec<i64>,
+    venue: Vec<String>,
+    symbol: Vec<String>,
+    sequence: Vec<u64>,
+    side: Vec<&'static str>,
+    level: Vec<u32>,
+    price: Vec<f64>,
+    quantity: Vec<f64>,
+}
+
+impl SnapshotRows {
+    /// 记录订单薄两侧前 `depth` 档
+    pub(crate) fn push_book(&mut self, venue: &str, symbol: &str, book: &OrderBook, depth: usize, local_time: u64) {
+        for side in [Side::Bid, Side::Ask] {
+            for (level, (price, quantity)) in book.top_levels(side, depth).into_iter().enumerate() {
+                self.time.push(local_time as i64);
+                self.venue.push(venue.to_string());
+                self.symbol.push(symbol.to_string());
+                self.sequence.push(book.last_update_id);
+                self.side.push(side_name(side));
+                self.level.push(level as u32);
+                self.price.push(to_f64(price));
+                self.quantity.push(to_f64(quantity));
+            }
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.time.len()
+    }
+
+    pub(crate) fn take_batch(&mut self) -> Result<RecordBatch, Box<dyn Error>> {
+        let rows = std::mem::take(self);
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(TimestampMillisecondArray::from(rows.time).with_timezone("UTC")),
+            Arc::new(StringArray::from(rows.venue)),
+            Arc::new(StringArray::from(rows.symbol)),
+            Arc::new(UInt64Array::from(rows.sequence)),
+            Arc::new(StringArray::from(rows.side)),
+            Arc::new(UInt32Array::from(rows.level)),
+            Arc::new(Float64Array::from(rows.price)),
+            Arc::new(Float64Array::from(rows.quantity)),
+        ];
+        Ok(RecordBatch::try_new(snapshot_schema(), columns)?)
+    }
+}
@@ -0,0 +1,168 @@
+use std::error::Error;
+use rust_decimal::Decimal;
+use tracing::warn;
+
+use crate::binance::Market;
+use crate::exchange::{Continuity, DepthKind, DepthMessage};
+use crate::manager::BookManager;
+use crate::order_book::{OrderBook, Side};
+use crate::recorder::{Record, Recorder};
+
+/// 压缩结果统计
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CompactStats {
+    /// 读取的记录数
+    pub input: usize,
+    /// 写入的记录数，不含记录器自己写入的关键帧
+    pub written: usize,
+    /// 丢弃的原始消息帧
+    pub dropped_frames: usize,
+    /// 超出档位限制或已过期、整条丢弃的增量更新
+    pub dropped_deltas: usize,
+    /// 丢弃的原始关键帧，由输出记录器按新的间隔重新生成
+    pub dropped_keyframes: usize,
+    /// 重放时序号断开的次数，断开后到下一个快照之前的增量更新不写入
+    pub gaps: usize,
+}
+
+/// 记录压缩
+///
+/// 用与实时行情相同的订单薄逻辑重放记录，把深度更新改写为只包含前 `depth` 档变化的消息后
+/// 交给输出记录器；输出记录器在每次更新后收到截断的订单薄，按自己的间隔写入关键帧
+/// （例如 [`CaptureRecorder`](crate::recorder::capture::CaptureRecorder)），原有的关键帧被丢弃。
+/// 改写后的增量更新保留原来的序号，重放时的序号检查与原记录相同
+#[derive(Debug)]
+pub struct Compactor {
+    manager: BookManager,
+    /// 每侧保留的档位数，None 时不截断
+    depth: Option<usize>,
+    keep_frames: bool,
+    stats: CompactStats,
+}
+
+impl Compactor {
+    /// # 参数
+    ///
+    /// * `depth` - 每侧保留的档位数，None 时保留全部档位
+    /// * `keep_frames` - 是否保留原始消息帧，原始消息帧通常占记录的大部分空间
+    pub fn new(depth: Option<usize>, keep_frames: bool) -> Self {
+        Compactor {
+            manager: BookManager::new(Market::Spot, &[]),
+            depth: depth.map(|depth| depth.max(1)),
+            keep_frames,
+            stats: CompactStats::default(),
+        }
+    }
+
+    /// 压缩统计
+    pub fn stats(&self) -> CompactStats {
+        self.stats
+    }
+
+    /// 处理一条按时间排序的记录，改写后写入 `output`
+    pub fn push(&mut self, record: Record, output: &mut dyn Recorder) -> Result<(), Box<dyn Error>> {
+        self.stats.input += 1;
+        let (venue, message, recv_time) = match record {
+            Record::Raw { .. } | Record::Binary { .. } => {
+                if self.keep_frames {
+                    output.record(&record)?;
+                    self.stats.written += 1;
+                } else {
+                    self.stats.dropped_frames += 1;
+                }
+                return Ok(());
+            }
+            Record::Trade { .. } => {
+                output.record(&record)?;
+                self.stats.written += 1;
+                return Ok(());
+            }
+            Record::Keyframe { .. } => {
+                self.stats.dropped_keyframes += 1;
+                if let Err(e) = self.manager.replay_record(record) {
+                    warn!(error = %e, "回放关键帧失败");
+                }
+                return Ok(());
+            }
+            Record::Update { venue, message, recv_time } => (venue, message, recv_time),
+        };
+
+        let symbol = message.symbol.clone();
+        let before = self.manager.venue_book(&venue, &symbol).map(|book| self.top_book(book));
+        let kind = message.kind;
+        let continuity = message.continuity;
+        let timestamp = message.timestamp;
+        let original = self.depth.is_none().then(|| message.clone());
+        if let Err(e) = self.manager.replay_record(Record::Update { recv_time, venue: venue.clone(), message }) {
+            warn!(venue, symbol, error = %e, "应用深度消息失败");
+            self.stats.gaps += 1;
+            return Ok(());
+        }
+        let Some(after) = self.manager.venue_book(&venue, &symbol).map(|book| self.top_book(book)) else {
+            // 尚未建立订单薄
+            self.stats.dropped_deltas += 1;
+            return Ok(());
+        };
+
+        let message = match (original, kind, &before) {
+            (Some(message), _, _) => message,
+            (None, DepthKind::Snapshot, _) | (None, DepthKind::Delta, None) => DepthMessage {
+                symbol: symbol.clone(),
+                kind: DepthKind::Snapshot,
+                bids: after.bids_list(),
+                asks: after.asks_list(),
+                continuity,
+                checksum: None,
+                max_depth: self.depth,
+                timestamp,
+            },
+            (None, DepthKind::Delta, Some(before)) => {
+                let bids = level_changes(before, &after, Side::Bid);
+                let asks = level_changes(before, &after, Side::Ask);
+                // 没有变化且序号未前进的是过期消息；没有序号或按时间戳排序的消息不需要占位
+                let placeholder = matches!(continuity, Continuity::Prev { .. } | Continuity::Range { .. })
+                    && after.last_update_id != before.last_update_id;
+                if bids.is_empty() && asks.is_empty() && !placeholder {
+                    self.stats.dropped_deltas += 1;
+                    return Ok(());
+                }
+                DepthMessage {
+                    symbol: symbol.clone(),
+                    kind: DepthKind::Delta,
+                    bids,
+                    asks,
+                    continuity,
+                    checksum: None,
+                    max_depth: self.depth,
+                    timestamp,
+                }
+            }
+        };
+        output.record(&Record::Update { recv_time, venue: venue.clone(), message })?;
+        self.stats.written += 1;
+        output.on_book(&venue, &symbol, &after, recv_time)
+    }
+
+    /// 截断到保留档位数的订单薄副本
+    fn top_book(&self, book: &OrderBook) -> OrderBook {
+        match self.depth {
+            Some(depth) => OrderBook::from_levels(book.last_update_id, &book.top_levels(Side::Bid, depth), &book.top_levels(Side::Ask, depth)),
+            None => book.clone(),
+        }
+    }
+}
+
+/// 一侧从 `before` 到 `after` 的档位变化，删除的档位数量为 0
+fn level_changes(before: &OrderBook, after: &OrderBook, side: Side) -> Vec<(Decimal, Decimal)> {
+    let (old, new) = match side {
+        Side::Bid => (before.bids_list(), after.bids_list()),
+        Side::Ask => (before.asks_list(), after.asks_list()),
+    };
+    let removed = old.iter()
+        .filter(|(price, _)| after.level_quantity(side, *price).is_zero())
+        .map(|(price, _)| (*price, Decimal::ZERO));
+    let changed = new.iter()
+        .filter(|(price, quantity)| before.level_quantity(side, *price) != *quantity)
+        .copied();
+    removed.chain(changed).collect()
+}
@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::Write;
+use std::net::TcpStream;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use reqwest::blocking::Client;
+use rust_decimal::Decimal;
+use tracing::warn;
+
+use crate::order_book::OrderBook;
+use crate::recorder::columns::to_f64;
+use crate::recorder::{Record, Recorder};
+
+/// 缓冲的行发送间隔
+const SEND_INTERVAL: Duration = Duration::from_secs(1);
+/// 发送失败时最多保留的行数，超出时丢弃最早的行
+const MAX_BUFFERED_LINES: usize = 100_000;
+/// 网络超时
+const NETWORK_TIMEOUT: Duration = Duration::from_secs(10);
+/// 表名
+const MEASUREMENT: &str = "order_book";
+
+/// 行协议的发送目标
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IlpEndpoint {
+    /// TCP，例如 QuestDB 的 9009 端口
+    Tcp(String),
+    /// HTTP 写入接口，例如 InfluxDB 2 的 `/api/v2/write?org=..&bucket=..` 或 QuestDB 的 `/write`
+    Http {
+        url: String,
+        /// InfluxDB 令牌，以 `Authorization: Token ...` 发送
+        token: Option<String>,
+    },
+}
+
+impl IlpEndpoint {
+    /// 从命令行参数解析，`tcp://host:port` 或 `http(s)://...`
+    pub fn parse(spec: &str) -> Option<Self> {
+        if let Some(address) = spec.strip_prefix("tcp://") {
+            return Some(IlpEndpoint::Tcp(address.to_string()));
+        }
+        if spec.starts_with("http://") || spec.starts_with("https://") {
+            return Some(IlpEndpoint::Http { url: spec.to_string(), token: None });
+        }
+        None
+    }
+}
+
+/// InfluxDB 行协议（ILP）指标记录器
+///
+/// 按固定间隔为每个订单薄输出一行 `order_book,venue=..,symbol=.. 字段 时间戳(纳秒)`，字段包括
+/// 最优价、中间价、价差（绝对值和基点）、前5档不平衡度、中间价附近 N 基点内的买卖深度和金额，
+/// 以及最近一条深度消息的延迟（本地接收时间 - 交易所事件时间，毫秒）。发送在后台线程进行，
+/// 适用于 InfluxDB 和 QuestDB，可直接在 Grafana 中作图
+#[derive(Debug)]
+pub struct IlpRecorder {
+    interval_ms: u64,
+    depth_bps: Decimal,
+    sender: Option<Sender<String>>,
+    writer: Option<JoinHandle<()>>,
+    /// (交易所, 交易对) -> 上次输出时间
+    last_emit: HashMap<(String, String), u64>,
+    /// (交易所, 交易对) -> 最近一条深度消息的延迟（毫秒）
+    latency: HashMap<(String, String), i64>,
+}
+
+impl IlpRecorder {
+    /// 创建记录器并启动发送线程
+    ///
+    /// # 参数
+    ///
+    /// * `endpoint` - 发送目标
+    /// * `interval_ms` - 每个订单薄的输出间隔（毫秒）
+    /// * `depth_bps` - 统计深度的中间价范围（基点）
+    pub fn new(endpoint: IlpEndpoint, interval_ms: u64, depth_bps: Decimal) -> Result<Self, Box<dyn Error>> {
+        let (sender, receiver) = mpsc::channel();
+        let client = Client::builder().timeout(NETWORK_TIMEOUT).build()?;
+        let writer = thread::spawn(move || run_writer(&endpoint, &client, receiver));
+        Ok(IlpRecorder {
+            interval_ms,
+            depth_bps,
+            sender: Some(sender),
+            writer: Some(writer),
+            last_emit: HashMap::new(),
+            latency: HashMap::new(),
+        })
+    }
+
+    /// 生成一个订单薄的指标行，缺少任意一侧时返回 None
+    fn line(&self, venue: &str, symbol: &str, book: &OrderBook, local_time: u64) -> Option<String> {
+        let (bid, _) = book.best_bid()?;
+        let (ask, _) = book.best_ask()?;
+        let mid = book.mid_price()?;
+        let spread = ask - bid;
+        let mut fields = vec![
+            format!("best_bid={}", to_f64(bid)),
+            format!("best_ask={}", to_f64(ask)),
+            format!("mid={}", to_f64(mid)),
+            format!("spread={}", to_f64(spread)),
+        ];
+        if !mid.is_zero() {
+            fields.push(format!("spread_bps={}", to_f64(spread / mid * Decimal::from(10_000))));
+        }
+        if let Some(imbalance) = book.imbalance(5) {
+            fields.push(format!("imbalance={}", to_f64(imbalance)));
+        }
+        if let Some(depth) = book.depth_within_bps(self.depth_bps) {
+            fields.push(format!("bid_depth={}", to_f64(depth.bid_quantity)));
+            fields.push(format!("ask_depth={}", to_f64(depth.ask_quantity)));
+            fields.push(format!("bid_depth_notional={}", to_f64(depth.bid_notional)));
+            fields.push(format!("ask_depth_notional={}", to_f64(depth.ask_notional)));
+        }
+        if let Some(latency) = self.latency.get(&(venue.to_string(), symbol.to_string())) {
+            fields.push(format!("latency_ms={}i", latency));
+        }
+        Some(format!("{},venue={},symbol={} {} {}\n",
+                     MEASUREMENT, escape_tag(venue), escape_tag(symbol), fields.join(","), local_time * 1_000_000))
+    }
+}
+
+impl Recorder for IlpRecorder {
+    fn record(&mut self, record: &Record) -> Result<(), Box<dyn Error>> {
+        if let Record::Update { recv_time, venue, message } = record {
+            let latency = *recv_time as i64 - message.timestamp as i64;
+            self.latency.insert((venue.clone(), message.symbol.clone()), latency);
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn on_book(&mut self, venue: &str, symbol: &str, book: &OrderBook, local_time: u64) -> Result<(), Box<dyn Error>> {
+        let key = (venue.to_string(), symbol.to_string());
+        if self.last_emit.get(&key).is_some_and(|last| local_time < last + self.interval_ms) {
+            return Ok(());
+        }
+        let Some(line) = self.line(venue, symbol, book, local_time) else {
+            return Ok(());
+        };
+        self.last_emit.insert(key, local_time);
+        let sender = self.sender.as_ref().ok_or("发送线程已停止")?;
+        sender.send(line).map_err(|_| "发送线程已停止".into())
+    }
+}
+
+impl Drop for IlpRecorder {
+    fn drop(&mut self) {
+        self.sender.take();
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+/// 转义标签值中的逗号、等号和空格
+fn escape_tag(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, ',' | '=' | ' ') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// 发送线程：每隔一段时间把缓冲的行发出，失败时保留并在下次重试
+fn run_writer(endpoint: &IlpEndpoint, client: &Client, receiver: Receiver<String>) {
+    let mut buffer: Vec<String> = Vec::new();
+    let mut stream: Option<TcpStream> = None;
+    loop {
+        let deadline = Instant::now() + SEND_INTERVAL;
+        let closed = loop {
+            match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(line) => buffer.push(line),
+                Err(RecvTimeoutError::Timeout) => break false,
+                Err(RecvTimeoutError::Disconnected) => break true,
+            }
+        };
+        if !buffer.is_empty() {
+            match send_lines(endpoint, client, &mut stream, &buffer.concat()) {
+                Ok(()) => buffer.clear(),
+                Err(e) => {
+                    warn!(error = %e, "发送行协议指标失败");
+                    stream = None;
+                    if buffer.len() > MAX_BUFFERED_LINES {
+                        let excess = buffer.len() - MAX_BUFFERED_LINES;
+                        buffer.drain(..excess);
+                    }
+                }
+            }
+        }
+        if closed {
+            return;
+        }
+    }
+}
+
+fn send_lines(endpoint: &IlpEndpoint, client: &Client, stream: &mut Option<TcpStream>, body: &str) -> Result<(), Box<dyn Error>> {
+    match endpoint {
+        IlpEndpoint::Tcp(address) => {
+            if stream.is_none() {
+                let connected = TcpStream::connect(address)?;
+                connected.set_write_timeout(Some(NETWORK_TIMEOUT))?;
+                *stream = Some(connected);
+            }
+            if let Some(stream) = stream.as_mut() {
+                stream.write_all(body.as_bytes())?;
+                stream.flush()?;
+            }
+        }
+        IlpEndpoint::Http { url, token } => {
+            let mut request = client.post(url).body(body.to_string());
+            if let Some(token) = token {
+                request = request.header("Authorization", format!("Token {}", token));
+            }
+            let response = request.send()?;
+            if !response.status().is_success() {
+                let status = response.status();
+                return Err(format!("{}: {}", status, response.text().unwrap_or_default().trim()).into());
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use arrow::ipc::writer::StreamWriter;
+use tracing::error;
+
+use crate::recorder::columns::{delta_schema, DeltaRows, BATCH_ROWS};
+use crate::recorder::{Record, Recorder};
+
+/// 缓冲的行最长等待时间（毫秒），到期后即使行数不足也输出一个批次
+const BATCH_MAX_MS: u64 = 1_000;
+
+/// Arrow IPC 流输出
+///
+/// 把统一格式的深度更新按档位展开，以 Arrow IPC 流格式（Feather v2 流）输出记录批次，
+/// 表结构见 [`delta_schema`]。行数达到批次大小或距第一行超过一秒时输出一个批次，
+/// 读取方可以用 `pyarrow.ipc.open_stream` 或 Polars 的 `read_ipc_stream` 逐批消费
+pub struct IpcRecorder {
+    writer: StreamWriter<Box<dyn Write + Send>>,
+    rows: DeltaRows,
+    /// 缓冲中第一行的接收时间
+    first_time: u64,
+}
+
+impl std::fmt::Debug for IpcRecorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IpcRecorder")
+            .field("buffered_rows", &self.rows.len())
+            .finish()
+    }
+}
+
+impl IpcRecorder {
+    /// 输出到任意写入端，立即写入表结构
+    pub fn new(writer: Box<dyn Write + Send>) -> Result<Self, Box<dyn Error>> {
+        Ok(IpcRecorder {
+            writer: StreamWriter::try_new(writer, &delta_schema())?,
+            rows: DeltaRows::default(),
+            first_time: 0,
+        })
+    }
+
+    /// 输出到文件，文件已存在时覆盖
+    pub fn to_file(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        IpcRecorder::new(Box::new(BufWriter::new(File::create(path)?)))
+    }
+
+    /// 输出到标准输出
+    pub fn to_stdout() -> Result<Self, Box<dyn Error>> {
+        IpcRecorder::new(Box::new(std::io::stdout()))
+    }
+
+    /// 输出缓冲的行
+    fn write_batch(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.rows.len() > 0 {
+            self.writer.write(&self.rows.take_batch()?)?;
+            self.writer.flush()?;
+        }
+        Ok(())
+    }
+}
+
+impl Recorder for IpcRecorder {
+    fn record(&mut self, record: &Record) -> Result<(), Box<dyn Error>> {
+        let Record::Update { recv_time, venue, message } = record else {
+            return Ok(());
+        };
+        if self.rows.len() == 0 {
+            self.first_time = *recv_time;
+        }
+        self.rows.push_update(*recv_time, venue, message);
+        if self.rows.len() >= BATCH_ROWS || *recv_time >= self.first_time + BATCH_MAX_MS {
+            self.write_batch()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Box<dyn Error>> {
+        self.write_batch()
+    }
+}
+
+impl Drop for IpcRecorder {
+    fn drop(&mut self) {
+        // 写入流结束标记
+        let finished = self.write_batch()
+            .and_then(|()| Ok(self.writer.finish()?));
+        if let Err(e) = finished {
+            error!(error = %e, "结束 Arrow IPC 流失败");
+        }
+    }
+}
@@ -0,0 +1,368 @@
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::net::{SocketAddr, UdpSocket};
+use std::path::{Path, PathBuf};
+use rust_decimal::Decimal;
+
+use crate::exchange::{Continuity, DepthKind, DepthMessage};
+use crate::order_book::Side;
+use crate::recorder::{utc_hour_label, utc_millis_label, Record, Recorder, DAY_MS};
+
+/// 默认价格和数量的小数位数
+pub const DEFAULT_DECIMALS: u32 = 8;
+/// MoldUDP64 数据包的最大长度，不超过常见的以太网 MTU
+const MAX_PACKET_SIZE: usize = 1400;
+/// MoldUDP64 包头：会话 10 字节、序号 8 字节、消息数 2 字节
+const PACKET_HEADER_SIZE: usize = 20;
+
+/// 消息类型
+const STOCK_DIRECTORY: u8 = b'R';
+const ADD_ORDER: u8 = b'A';
+const ORDER_REPLACE: u8 = b'U';
+const ORDER_DELETE: u8 = b'D';
+
+/// 单个订单薄的输出状态
+#[derive(Debug)]
+struct ItchBook {
+    locate: u16,
+    /// 价格 -> (订单编号, 数量)，每个价位视为一个订单
+    bids: BTreeMap<Decimal, (u64, Decimal)>,
+    asks: BTreeMap<Decimal, (u64, Decimal)>,
+    last_update_id: u64,
+}
+
+impl ItchBook {
+    fn side(&mut self, side: Side) -> &mut BTreeMap<Decimal, (u64, Decimal)> {
+        match side {
+            Side::Bid => &mut self.bids,
+            Side::Ask => &mut self.asks,
+        }
+    }
+}
+
+/// 按 UTC 日切换的输出文件
+#[derive(Debug)]
+struct ItchFile {
+    dir: PathBuf,
+    day: u64,
+    writer: Option<BufWriter<File>>,
+}
+
+/// MoldUDP64 组播发送
+#[derive(Debug)]
+struct MoldUdp {
+    socket: UdpSocket,
+    group: SocketAddr,
+    /// 下一条消息的序号，从 1 开始
+    sequence: u64,
+}
+
+/// ITCH 风格的二进制行情输出
+///
+/// 交易所只推送按价位聚合的深度，这里把每个价位视为一个订单：价位出现时输出新增订单 (`A`)，数量变化时输出
+/// 替换订单 (`U`，旧订单编号换为新编号)，价位消失时输出删除订单 (`D`)，为交易所二进制行情编写的工具可以直接用
+/// 加密货币数据测试。每个订单薄第一次出现时先输出目录消息 (`R`)，之后的消息以目录中的定位码引用订单薄。
+///
+/// 所有整数为大端，消息头为 类型 (1)、定位码 (u16)、跟踪号 (u16，固定为 0)、UTC 当日纳秒 (6 字节)：
+///
+/// | 类型 | 消息头之后的字段 |
+/// |------|------------------|
+/// | `R` | 交易所 (8 字节 ASCII，右补空格)、交易对 (16 字节)、价格小数位 (u8)、数量小数位 (u8) |
+/// | `A` | 订单编号 (u64)、方向 (`B`/`S`)、数量 (u64)、价格 (u64) |
+/// | `U` | 原订单编号 (u64)、新订单编号 (u64)、数量 (u64)、价格 (u64) |
+/// | `D` | 订单编号 (u64) |
+///
+/// 价格和数量乘以 10 的小数位次方后取整。文件输出在启动和每个 UTC 日开始时创建 `目录/YYYYMMDD-HHMMSS.mmm.itch`，
+/// 每条消息前有 2 字节长度，与交易所分发的 ITCH 文件相同；新文件开头写入所有目录消息和当前档位的新增订单，
+/// 每个文件可以单独回放。
+/// 组播输出使用 MoldUDP64 封装，会话为当日日期，没有重传，接收方发现序号缺口时需要从文件恢复
+#[derive(Debug)]
+pub struct ItchRecorder {
+    file: Option<ItchFile>,
+    multicast: Option<MoldUdp>,
+    price_scale: Decimal,
+    quantity_scale: Decimal,
+    price_decimals: u8,
+    quantity_decimals: u8,
+    /// (交易所, 交易对) -> 输出状态，收到快照或关键帧之后建立
+    books: HashMap<(String, String), ItchBook>,
+    next_order_ref: u64,
+}
+
+impl ItchRecorder {
+    /// 创建输出，目录不存在时自动创建
+    ///
+    /// # 参数
+    ///
+    /// * `dir` - 文件输出目录，None 时不写文件
+    /// * `multicast` - MoldUDP64 组播地址，例如 `239.1.1.1:30001`，None 时不发送
+    /// * `price_decimals`、`quantity_decimals` - 价格和数量的小数位数，例如 [`DEFAULT_DECIMALS`]
+    pub fn new(dir: Option<&Path>, multicast: Option<SocketAddr>, price_decimals: u32, quantity_decimals: u32) -> Result<Self, Box<dyn Error>> {
+        if price_decimals > 18 || quantity_decimals > 18 {
+            return Err("小数位数不能超过 18".into());
+        }
+        let file = match dir {
+            Some(dir) => {
+                fs::create_dir_all(dir)?;
+                Some(ItchFile { dir: dir.to_path_buf(), day: 0, writer: None })
+            }
+            None => None,
+        };
+        let multicast = match multicast {
+            Some(group) => {
+                let bind: SocketAddr = if group.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" }.parse()?;
+                Some(MoldUdp { socket: UdpSocket::bind(bind)?, group, sequence: 1 })
+            }
+            None => None,
+        };
+        Ok(ItchRecorder {
+            file,
+            multicast,
+            price_scale: Decimal::from(10u64.pow(price_decimals)),
+            quantity_scale: Decimal::from(10u64.pow(quantity_decimals)),
+            price_decimals: price_decimals as u8,
+            quantity_decimals: quantity_decimals as u8,
+            books: HashMap::new(),
+            next_order_ref: 1,
+        })
+    }
+
+    /// 应用深度消息并输出档位变化
+    fn write_depth(&mut self, venue: &str, message: &DepthMessage, local_time: u64) -> Result<(), Box<dyn Error>> {
+        let key = (venue.to_string(), message.symbol.clone());
+        let timestamp = (local_time % DAY_MS) * 1_000_000;
+        let mut messages = Vec::new();
+        if !self.books.contains_key(&key) {
+            if message.kind == DepthKind::Delta {
+                return Ok(());
+            }
+            let locate = u16::try_from(self.books.len() + 1).map_err(|_| "订单薄数量超过定位码上限")?;
+            self.books.insert(key.clone(), ItchBook { locate, bids: BTreeMap::new(), asks: BTreeMap::new(), last_update_id: 0 });
+            messages.push(self.directory(venue, &message.symbol, locate, timestamp));
+        }
+        let Some(book) = self.books.get_mut(&key) else {
+            return Ok(());
+        };
+        let changes: Vec<(Side, Decimal, Decimal)> = match message.kind {
+            DepthKind::Snapshot => {
+                // 快照与当前档位的差异，不在快照中的档位删除
+                let mut changes = Vec::new();
+                for (side, levels) in [(Side::Bid, &message.bids), (Side::Ask, &message.asks)] {
+                    changes.extend(book.side(side).keys()
+                        .filter(|price| !levels.iter().any(|(level, _)| level == *price))
+                        .map(|price| (side, *price, Decimal::ZERO))
+                        .collect::<Vec<_>>());
+                    changes.extend(levels.iter().map(|(price, quantity)| (side, *price, *quantity)));
+                }
+                changes
+            }
+            DepthKind::Delta => {
+                let stale = match message.continuity {
+                    Continuity::Range { last, .. } => last <= book.last_update_id,
+                    Continuity::Monotonic(timestamp) => timestamp <= book.last_update_id,
+                    Continuity::None | Continuity::Prev { .. } => false,
+                };
+                if stale {
+                    return Ok(());
+                }
+                message.bids.iter().map(|(price, quantity)| (Side::Bid, *price, *quantity))
+                    .chain(message.asks.iter().map(|(price, quantity)| (Side::Ask, *price, *quantity)))
+                    .collect()
+            }
+        };
+        if let Some(sequence) = message.continuity.sequence() {
+            book.last_update_id = sequence;
+        }
+        let (price_scale, quantity_scale) = (self.price_scale, self.quantity_scale);
+        let locate = book.locate;
+        for (side, price, quantity) in changes {
+            let levels = book.side(side);
+            let current = levels.get(&price).copied();
+            let mut body = Vec::with_capacity(32);
+            let kind = match (current, quantity.is_zero()) {
+                (None, true) => continue,
+                (Some((_, current)), false) if current == quantity => continue,
+                (Some((order_ref, _)), true) => {
+                    levels.remove(&price);
+                    body.extend_from_slice(&order_ref.to_be_bytes());
+                    ORDER_DELETE
+                }
+                (Some((order_ref, _)), false) => {
+                    let new_ref = self.next_order_ref;
+                    self.next_order_ref += 1;
+                    levels.insert(price, (new_ref, quantity));
+                    body.extend_from_slice(&order_ref.to_be_bytes());
+                    body.extend_from_slice(&new_ref.to_be_bytes());
+                    body.extend_from_slice(&scaled(quantity, quantity_scale).to_be_bytes());
+                    body.extend_from_slice(&scaled(price, price_scale).to_be_bytes());
+                    ORDER_REPLACE
+                }
+                (None, false) => {
+                    let order_ref = self.next_order_ref;
+                    self.next_order_ref += 1;
+                    levels.insert(price, (order_ref, quantity));
+                    messages.push(add_order(locate, timestamp, order_ref, side, scaled(price, price_scale), scaled(quantity, quantity_scale)));
+                    continue;
+                }
+            };
+            messages.push(message_with_header(kind, locate, timestamp, &body));
+        }
+        if let Some(max_depth) = message.max_depth {
+            // 超出交易所维护深度的档位不会再收到更新，与本地订单薄一样截断
+            for side in [Side::Bid, Side::Ask] {
+                let levels = book.side(side);
+                let removed: Vec<Decimal> = match side {
+                    Side::Bid => levels.keys().rev().skip(max_depth).copied().collect(),
+                    Side::Ask => levels.keys().skip(max_depth).copied().collect(),
+                };
+                for price in removed {
+                    if let Some((order_ref, _)) = levels.remove(&price) {
+                        messages.push(message_with_header(ORDER_DELETE, locate, timestamp, &order_ref.to_be_bytes()));
+                    }
+                }
+            }
+        }
+        self.emit(&messages, local_time)
+    }
+
+    fn directory(&self, venue: &str, symbol: &str, locate: u16, timestamp: u64) -> Vec<u8> {
+        let mut body = Vec::with_capacity(26);
+        body.extend_from_slice(&alpha(venue, 8));
+        body.extend_from_slice(&alpha(symbol, 16));
+        body.push(self.price_decimals);
+        body.push(self.quantity_decimals);
+        message_with_header(STOCK_DIRECTORY, locate, timestamp, &body)
+    }
+
+    /// 当前所有订单薄的目录消息和档位，写在每个新文件的开头
+    fn spin(&self, timestamp: u64) -> Vec<Vec<u8>> {
+        let mut books: Vec<_> = self.books.iter().collect();
+        books.sort_by_key(|(_, book)| book.locate);
+        let mut messages = Vec::new();
+        for ((venue, symbol), book) in books {
+            messages.push(self.directory(venue, symbol, book.locate, timestamp));
+            for (side, levels) in [(Side::Bid, &book.bids), (Side::Ask, &book.asks)] {
+                for (price, (order_ref, quantity)) in levels {
+                    let (price, quantity) = (scaled(*price, self.price_scale), scaled(*quantity, self.quantity_scale));
+                    messages.push(add_order(book.locate, timestamp, *order_ref, side, price, quantity));
+                }
+            }
+        }
+        messages
+    }
+
+    /// 写入文件并发送组播
+    fn emit(&mut self, messages: &[Vec<u8>], local_time: u64) -> Result<(), Box<dyn Error>> {
+        if messages.is_empty() {
+            return Ok(());
+        }
+        let day = local_time / DAY_MS;
+        if self.file.as_ref().is_some_and(|file| file.writer.is_none() || file.day != day) {
+            // 新文件先写入当前状态，状态中已包含本次的变化，只需要写入这些状态
+            let spin = self.spin((local_time % DAY_MS) * 1_000_000);
+            if let Some(file) = self.file.as_mut() {
+                if let Some(writer) = file.writer.as_mut() {
+                    writer.flush()?;
+                }
+                let path = file.dir.join(format!("{}.itch", utc_millis_label(local_time)));
+                let mut writer = BufWriter::new(File::create(path)?);
+                for message in &spin {
+                    writer.write_all(&(message.len() as u16).to_be_bytes())?;
+                    writer.write_all(message)?;
+                }
+                file.writer = Some(writer);
+                file.day = day;
+            }
+        } else if let Some(writer) = self.file.as_mut().and_then(|file| file.writer.as_mut()) {
+            for message in messages {
+                writer.write_all(&(message.len() as u16).to_be_bytes())?;
+                writer.write_all(message)?;
+            }
+        }
+        if let Some(multicast) = self.multicast.as_mut() {
+            multicast.send(messages, local_time)?;
+        }
+        Ok(())
+    }
+}
+
+impl MoldUdp {
+    /// 把消息装入若干个数据包发送
+    fn send(&mut self, messages: &[Vec<u8>], local_time: u64) -> Result<(), Box<dyn Error>> {
+        let session = alpha(&utc_hour_label(local_time)[..8], 10);
+        let mut start = 0;
+        while start < messages.len() {
+            let mut size = PACKET_HEADER_SIZE;
+            let mut end = start;
+            while end < messages.len() && (end == start || size + 2 + messages[end].len() <= MAX_PACKET_SIZE) {
+                size += 2 + messages[end].len();
+                end += 1;
+            }
+            let mut packet = Vec::with_capacity(size);
+            packet.extend_from_slice(&session);
+            packet.extend_from_slice(&self.sequence.to_be_bytes());
+            packet.extend_from_slice(&((end - start) as u16).to_be_bytes());
+            for message in &messages[start..end] {
+                packet.extend_from_slice(&(message.len() as u16).to_be_bytes());
+                packet.extend_from_slice(message);
+            }
+            self.socket.send_to(&packet, self.group)?;
+            self.sequence += (end - start) as u64;
+            start = end;
+        }
+        Ok(())
+    }
+}
+
+/// 消息头：类型、定位码、跟踪号、6 字节纳秒时间戳
+fn message_with_header(kind: u8, locate: u16, timestamp: u64, body: &[u8]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(11 + body.len());
+    message.push(kind);
+    message.extend_from_slice(&locate.to_be_bytes());
+    message.extend_from_slice(&0u16.to_be_bytes());
+    message.extend_from_slice(&timestamp.to_be_bytes()[2..]);
+    message.extend_from_slice(body);
+    message
+}
+
+/// 新增订单消息，价格和数量已按小数位放大
+fn add_order(locate: u16, timestamp: u64, order_ref: u64, side: Side, price: u64, quantity: u64) -> Vec<u8> {
+    let mut body = Vec::with_capacity(25);
+    body.extend_from_slice(&order_ref.to_be_bytes());
+    body.push(if side == Side::Bid { b'B' } else { b'S' });
+    body.extend_from_slice(&quantity.to_be_bytes());
+    body.extend_from_slice(&price.to_be_bytes());
+    message_with_header(ADD_ORDER, locate, timestamp, &body)
+}
+
+/// 乘以 10 的小数位次方后取整，超出范围时取最大值
+fn scaled(value: Decimal, scale: Decimal) -> u64 {
+    u64::try_from((value * scale).round()).unwrap_or(u64::MAX)
+}
+
+/// 定长 ASCII 字段，右补空格，超长时截断
+fn alpha(value: &str, len: usize) -> Vec<u8> {
+    let mut bytes: Vec<u8> = value.bytes().take(len).collect();
+    bytes.resize(len, b' ');
+    bytes
+}
+
+impl Recorder for ItchRecorder {
+    fn record(&mut self, record: &Record) -> Result<(), Box<dyn Error>> {
+        match record {
+            Record::Update { recv_time, venue, message } | Record::Keyframe { recv_time, venue, message } => {
+                self.write_depth(venue, message, *recv_time)
+            }
+            Record::Trade { .. } | Record::Raw { .. } | Record::Binary { .. } => Ok(()),
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), Box<dyn Error>> {
+        if let Some(writer) = self.file.as_mut().and_then(|file| file.writer.as_mut()) {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+}
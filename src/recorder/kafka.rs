@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::time::Duration;
+use rdkafka::config::ClientConfig;
+use rdkafka::error::{KafkaError, RDKafkaErrorCode};
+use rdkafka::message::{Header, OwnedHeaders};
+use rdkafka::producer::{BaseProducer, BaseRecord, DeliveryResult, Producer, ProducerContext};
+use rdkafka::ClientContext;
+use tracing::{error, warn};
+
+use crate::exchange::DepthKind;
+use crate::order_book::OrderBook;
+use crate::proto::FeedFormat;
+use crate::recorder::{Record, Recorder};
+use crate::telemetry;
+
+/// 默认主题
+pub const DEFAULT_DELTA_TOPIC: &str = "book.deltas";
+pub const DEFAULT_SNAPSHOT_TOPIC: &str = "book.snapshots";
+/// 关闭时等待发送完成的时间
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(10);
+/// 发送队列已满时等待的时间
+const QUEUE_FULL_WAIT: Duration = Duration::from_millis(100);
+
+/// 投递保证
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    /// 不等待确认，不重试，可能丢失
+    AtMostOnce,
+    /// 等待所有副本确认并重试，可能重复
+    AtLeastOnce,
+    /// 幂等生产者，重试不会产生重复，单个分区内不丢失、不重复、不乱序
+    ExactlyOnce,
+}
+
+impl Delivery {
+    /// 解析 `at-most-once`、`at-least-once` 或 `exactly-once`
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "at-most-once" => Some(Delivery::AtMostOnce),
+            "at-least-once" => Some(Delivery::AtLeastOnce),
+            "exactly-once" => Some(Delivery::ExactlyOnce),
+            _ => None,
+        }
+    }
+}
+
+/// Kafka 生产者配置
+#[derive(Debug, Clone)]
+pub struct KafkaConfig {
+    /// 服务器列表，例如 `localhost:9092,localhost:9093`
+    pub brokers: String,
+    /// 增量更新的主题
+    pub delta_topic: String,
+    /// 交易所快照和本地订单薄定期快照的主题
+    pub snapshot_topic: String,
+    /// 压缩算法：none、gzip、snappy、lz4、zstd
+    pub compression: String,
+    pub delivery: Delivery,
+    /// 每个订单薄写入本地快照的间隔（毫秒），0 表示不写入
+    pub keyframe_interval_ms: u64,
+    pub format: FeedFormat,
+}
+
+impl KafkaConfig {
+    pub fn new(brokers: &str) -> Self {
+        KafkaConfig {
+            brokers: brokers.to_string(),
+            delta_topic: DEFAULT_DELTA_TOPIC.to_string(),
+            snapshot_topic: DEFAULT_SNAPSHOT_TOPIC.to_string(),
+            compression: "lz4".to_string(),
+            delivery: Delivery::AtLeastOnce,
+            keyframe_interval_ms: 0,
+            format: FeedFormat::Json,
+        }
+    }
+}
+
+/// 打印投递失败的消息
+#[derive(Debug)]
+struct DeliveryLogger;
+
+impl ClientContext for DeliveryLogger {}
+
+impl ProducerContext for DeliveryLogger {
+    type DeliveryOpaque = ();
+
+    fn delivery(&self, result: &DeliveryResult<'_>, _opaque: ()) {
+        if let Err((e, _)) = result {
+            warn!(error = %e, "Kafka 投递失败");
+        }
+    }
+}
+
+/// Kafka 记录器
+///
+/// 把统一格式的增量更新写入增量主题，交易所快照和定期的本地订单薄快照写入快照主题，
+/// 消息键为交易对，同一交易对的消息进入同一分区并保持顺序；内容为 JSON（与 NDJSON 记录的一行相同）、
+/// Protobuf 的 [`FeedMessage`](crate::proto::FeedMessage)、SBE（`schema/order_book_sbe.xml`）或 Cap'n Proto（`schema/feed.capnp`）。
+/// 发送由 librdkafka 在后台批量进行
+pub struct KafkaRecorder {
+    producer: BaseProducer<DeliveryLogger>,
+    delta_topic: String,
+    snapshot_topic: String,
+    keyframe_interval_ms: u64,
+    format: FeedFormat,
+    /// (交易所, 交易对) -> 上次写入本地快照的时间
+    last_keyframe: HashMap<(String, String), u64>,
+}
+
+impl std::fmt::Debug for KafkaRecorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KafkaRecorder")
+            .field("delta_topic", &self.delta_topic)
+            .field("snapshot_topic", &self.snapshot_topic)
+            .field("keyframe_interval_ms", &self.keyframe_interval_ms)
+            .finish()
+    }
+}
+
+impl KafkaRecorder {
+    /// 创建生产者，配置无效时返回错误；服务器不可用时 librdkafka 在后台重连
+    pub fn new(config: KafkaConfig) -> Result<Self, Box<dyn Error>> {
+        let mut client = ClientConfig::new();
+        client.set("bootstrap.servers", &config.brokers)
+            .set("compression.type", &config.compression)
+            .set("linger.ms", "20");
+        match config.delivery {
+            Delivery::AtMostOnce => client.set("acks", "0").set("message.send.max.retries", "0"),
+            Delivery::AtLeastOnce => client.set("acks", "all"),
+            Delivery::ExactlyOnce => client.set("acks", "all").set("enable.idempotence", "true"),
+        };
+        Ok(KafkaRecorder {
+            producer: client.create_with_context(DeliveryLogger)?,
+            delta_topic: config.delta_topic,
+            snapshot_topic: config.snapshot_topic,
+            keyframe_interval_ms: config.keyframe_interval_ms,
+            format: config.format,
+            last_keyframe: HashMap::new(),
+        })
+    }
+
+    /// 发送一条记录，发送队列已满时等待 librdkafka 发出消息后重试；启用 OTLP 时消息头带有当前链路的 traceparent
+    fn send(&self, topic: &str, symbol: &str, record: &Record) -> Result<(), Box<dyn Error>> {
+        let Some(payload) = self.format.encode(record)? else {
+            return Ok(());
+        };
+        let trace_headers = telemetry::trace_headers();
+        let headers = (!trace_headers.is_empty()).then(|| trace_headers.iter()
+            .fold(OwnedHeaders::new(), |headers, (key, value)| headers.insert(Header { key, value: Some(value) })));
+        loop {
+            let mut message = BaseRecord::to(topic).key(symbol).payload(&payload);
+            if let Some(headers) = &headers {
+                message = message.headers(headers.clone());
+            }
+            match self.producer.send(message) {
+                Ok(()) => break,
+                Err((KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull), _)) => {
+                    self.producer.poll(QUEUE_FULL_WAIT);
+                }
+                Err((e, _)) => return Err(e.into()),
+            }
+        }
+        // 处理投递回调，不等待
+        self.producer.poll(Duration::ZERO);
+        Ok(())
+    }
+}
+
+impl Recorder for KafkaRecorder {
+    fn record(&mut self, record: &Record) -> Result<(), Box<dyn Error>> {
+        let Record::Update { message, .. } = record else {
+            return Ok(());
+        };
+        let topic = match message.kind {
+            DepthKind::Snapshot => &self.snapshot_topic,
+            DepthKind::Delta => &self.delta_topic,
+        };
+        self.send(topic, &message.symbol, record)
+    }
+
+    fn flush(&mut self) -> Result<(), Box<dyn Error>> {
+        self.producer.poll(Duration::ZERO);
+        Ok(())
+    }
+
+    fn on_book(&mut self, venue: &str, symbol: &str, book: &OrderBook, local_time: u64) -> Result<(), Box<dyn Error>> {
+        if self.keyframe_interval_ms == 0 {
+            return Ok(());
+        }
+        let key = (venue.to_string(), symbol.to_string());
+        if self.last_keyframe.get(&key).is_some_and(|last| local_time < last + self.keyframe_interval_ms) {
+            return Ok(());
+        }
+        self.last_keyframe.insert(key, local_time);
+        self.send(&self.snapshot_topic, symbol, &Record::keyframe(venue, symbol, book, local_time))
+    }
+}
+
+impl Drop for KafkaRecorder {
+    fn drop(&mut self) {
+        if let Err(e) = self.producer.flush(CLOSE_TIMEOUT) {
+            error!(error = %e, "Kafka 关闭时仍有消息未发送");
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use rust_decimal::Decimal;
+
+use crate::exchange::{Continuity, DepthKind, DepthMessage};
+use crate::order_book::{OrderBook, Side};
+use crate::recorder::{utc_hour_label, Record, Recorder, DAY_MS};
+use crate::trade::Trade;
+
+/// 默认价格倍数，LOBSTER 以美元价格乘以 10000 的整数表示价格
+pub const DEFAULT_PRICE_SCALE: u32 = 10_000;
+/// 默认输出档位数
+pub const DEFAULT_LEVELS: usize = 10;
+/// 空档位的卖价
+const EMPTY_ASK_PRICE: i64 = 9_999_999_999;
+/// 空档位的买价
+const EMPTY_BID_PRICE: i64 = -9_999_999_999;
+
+/// LOBSTER 事件类型
+const EVENT_SUBMISSION: u8 = 1;
+const EVENT_CANCELLATION: u8 = 2;
+const EVENT_DELETION: u8 = 3;
+const EVENT_EXECUTION: u8 = 4;
+
+/// 单个订单薄的输出状态
+#[derive(Debug)]
+struct LobsterBook {
+    /// 按已输出事件维护的订单薄，与交易所订单薄独立
+    book: OrderBook,
+    /// 当前文件对应的 UTC 日序号
+    day: u64,
+    messages: BufWriter<File>,
+    orderbook: BufWriter<File>,
+}
+
+/// LOBSTER 格式的消息和订单薄文件输出
+///
+/// 每个订单薄每个 UTC 日写入一对文件 `目录/交易所_交易对_YYYYMMDD_message_档位.csv` 和
+/// `目录/交易所_交易对_YYYYMMDD_orderbook_档位.csv`，没有表头，已存在的文件追加写入。
+/// 消息文件每行为 `当日秒数,事件类型,订单ID,数量,价格,方向`，订单薄文件的同一行为事件之后前若干档的
+/// `卖价,卖量,买价,买量`，空档位的价格为 ±9999999999、数量为 0。
+///
+/// 数据为按价位聚合的深度，没有逐笔订单：档位数量增加输出为提交（1），减少为部分撤单（2），
+/// 删除为撤单（3），成交输出为可见成交（4）并立即从对应档位扣除，之后的深度更新只输出剩余的差额。
+/// 订单ID 固定为 0，价格乘以价格倍数后取整，数量保持原始小数
+#[derive(Debug)]
+pub struct LobsterRecorder {
+    dir: PathBuf,
+    levels: usize,
+    price_scale: Decimal,
+    /// (交易所, 交易对) -> 输出状态，收到快照或关键帧之后建立
+    books: HashMap<(String, String), LobsterBook>,
+}
+
+impl LobsterRecorder {
+    /// 创建记录器，目录不存在时自动创建
+    ///
+    /// # 参数
+    ///
+    /// * `dir` - 输出目录
+    /// * `levels` - 订单薄文件每侧的档位数，例如 [`DEFAULT_LEVELS`]
+    /// * `price_scale` - 价格倍数，例如 [`DEFAULT_PRICE_SCALE`]，价格小于 1 的品种可以调大
+    pub fn new(dir: impl AsRef<Path>, levels: usize, price_scale: u32) -> Result<Self, Box<dyn Error>> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(LobsterRecorder {
+            dir: dir.as_ref().to_path_buf(),
+            levels: levels.max(1),
+            price_scale: Decimal::from(price_scale),
+            books: HashMap::new(),
+        })
+    }
+
+    /// 应用深度消息并输出每个档位变动
+    fn write_depth(&mut self, venue: &str, message: &DepthMessage, local_time: u64) -> Result<(), Box<dyn Error>> {
+        let key = (venue.to_string(), message.symbol.clone());
+        let changes = match (message.kind, self.books.get(&key)) {
+            (DepthKind::Snapshot, book) => {
+                // 快照与当前订单薄的差异，首次建立时全部为提交
+                let empty = OrderBook::from_levels(0, &[], &[]);
+                let book = book.map_or(&empty, |state| &state.book);
+                let target = OrderBook::from_levels(0, &message.bids, &message.asks);
+                let mut changes = Vec::new();
+                for side in [Side::Bid, Side::Ask] {
+                    let (current, levels) = match side {
+                        Side::Bid => (book.bids_list(), &message.bids),
+                        Side::Ask => (book.asks_list(), &message.asks),
+                    };
+                    changes.extend(current.iter()
+                        .filter(|(price, _)| target.level_quantity(side, *price).is_zero())
+                        .map(|(price, _)| (side, *price, Decimal::ZERO)));
+                    changes.extend(levels.iter().map(|(price, quantity)| (side, *price, *quantity)));
+                }
+                changes
+            }
+            (DepthKind::Delta, None) => return Ok(()),
+            (DepthKind::Delta, Some(state)) => {
+                let stale = match message.continuity {
+                    Continuity::Range { last, .. } => last <= state.book.last_update_id,
+                    Continuity::Monotonic(timestamp) => timestamp <= state.book.last_update_id,
+                    Continuity::None | Continuity::Prev { .. } => false,
+                };
+                if stale {
+                    return Ok(());
+                }
+                message.bids.iter().map(|(price, quantity)| (Side::Bid, *price, *quantity))
+                    .chain(message.asks.iter().map(|(price, quantity)| (Side::Ask, *price, *quantity)))
+                    .collect()
+            }
+        };
+        if !self.books.contains_key(&key) {
+            let (messages, orderbook) = self.open_files(venue, &message.symbol, local_time)?;
+            self.books.insert(key.clone(), LobsterBook {
+                book: OrderBook::from_levels(0, &[], &[]),
+                day: local_time / DAY_MS,
+                messages,
+                orderbook,
+            });
+        }
+        for (side, price, quantity) in changes {
+            let Some(state) = self.books.get(&key) else {
+                break;
+            };
+            let current = state.book.level_quantity(side, price);
+            let event = if quantity.is_zero() {
+                EVENT_DELETION
+            } else if quantity > current {
+                EVENT_SUBMISSION
+            } else {
+                EVENT_CANCELLATION
+            };
+            if quantity != current {
+                self.write_event(&key, local_time, event, side, price, (quantity - current).abs(), quantity)?;
+            }
+        }
+        if let Some(state) = self.books.get_mut(&key) {
+            if let Some(sequence) = message.continuity.sequence() {
+                state.book.last_update_id = sequence;
+            }
+            if let Some(max_depth) = message.max_depth {
+                state.book.truncate(max_depth);
+            }
+        }
+        Ok(())
+    }
+
+    /// 输出一笔成交，并从挂单一侧的档位扣除成交数量
+    fn write_trade(&mut self, venue: &str, symbol: &str, trade: &Trade, local_time: u64) -> Result<(), Box<dyn Error>> {
+        let key = (venue.to_string(), symbol.to_string());
+        let Some(state) = self.books.get(&key) else {
+            return Ok(());
+        };
+        // 主动卖出成交的是买单
+        let side = if trade.buyer_is_maker { Side::Bid } else { Side::Ask };
+        let remaining = (state.book.level_quantity(side, trade.price) - trade.quantity).max(Decimal::ZERO);
+        self.write_event(&key, local_time, EVENT_EXECUTION, side, trade.price, trade.quantity, remaining)
+    }
+
+    /// 把档位设置为 `quantity`，再写入一行消息和对应的订单薄
+    #[allow(clippy::too_many_arguments)]
+    fn write_event(&mut self, key: &(String, String), local_time: u64, event: u8, side: Side, price: Decimal, size: Decimal, quantity: Decimal) -> Result<(), Box<dyn Error>> {
+        let day = local_time / DAY_MS;
+        if self.books.get(key).is_some_and(|state| state.day != day) {
+            let (messages, orderbook) = self.open_files(&key.0, &key.1, local_time)?;
+            if let Some(state) = self.books.get_mut(key) {
+                state.messages.flush()?;
+                state.orderbook.flush()?;
+                state.day = day;
+                state.messages = messages;
+                state.orderbook = orderbook;
+            }
+        }
+        let (levels, price_scale) = (self.levels, self.price_scale);
+        let Some(state) = self.books.get_mut(key) else {
+            return Ok(());
+        };
+        state.book.apply_levels(side, &[(price, quantity)]);
+
+        let scale = |price: Decimal| (price * price_scale).round().to_string();
+        let direction = match side {
+            Side::Bid => 1,
+            Side::Ask => -1,
+        };
+        let millis = local_time % DAY_MS;
+        writeln!(state.messages, "{}.{:03},{},0,{},{},{}", millis / 1000, millis % 1000, event, size.normalize(), scale(price), direction)?;
+
+        let asks = state.book.top_levels(Side::Ask, levels);
+        let bids = state.book.top_levels(Side::Bid, levels);
+        let mut row = Vec::with_capacity(levels * 4);
+        for level in 0..levels {
+            match asks.get(level) {
+                Some((price, quantity)) => row.extend([scale(*price), quantity.normalize().to_string()]),
+                None => row.extend([EMPTY_ASK_PRICE.to_string(), "0".to_string()]),
+            }
+            match bids.get(level) {
+                Some((price, quantity)) => row.extend([scale(*price), quantity.normalize().to_string()]),
+                None => row.extend([EMPTY_BID_PRICE.to_string(), "0".to_string()]),
+            }
+        }
+        writeln!(state.orderbook, "{}", row.join(","))?;
+        Ok(())
+    }
+
+    /// 打开 `local_time` 所在日的消息文件和订单薄文件
+    fn open_files(&self, venue: &str, symbol: &str, local_time: u64) -> Result<(BufWriter<File>, BufWriter<File>), Box<dyn Error>> {
+        let append = |kind: &str| -> Result<BufWriter<File>, Box<dyn Error>> {
+            let path = self.path_for(venue, symbol, local_time, kind);
+            Ok(BufWriter::new(OpenOptions::new().create(true).append(true).open(path)?))
+        };
+        Ok((append("message")?, append("orderbook")?))
+    }
+
+    fn path_for(&self, venue: &str, symbol: &str, local_time: u64, kind: &str) -> PathBuf {
+        let date = &utc_hour_label(local_time)[..8];
+        // 交易对中的 `/` 不能出现在文件名中
+        let symbol = symbol.replace(['/', '\\'], "-");
+        self.dir.join(format!("{}_{}_{}_{}_{}.csv", venue, symbol, date, kind, self.levels))
+    }
+}
+
+impl Recorder for LobsterRecorder {
+    fn record(&mut self, record: &Record) -> Result<(), Box<dyn Error>> {
+        match record {
+            Record::Update { recv_time, venue, message } | Record::Keyframe { recv_time, venue, message } => {
+                self.write_depth(venue, message, *recv_time)
+            }
+            Record::Trade { recv_time, venue, symbol, trade } => self.write_trade(venue, symbol, trade, *recv_time),
+            Record::Raw { .. } | Record::Binary { .. } => Ok(()),
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), Box<dyn Error>> {
+        for state in self.books.values_mut() {
+            state.messages.flush()?;
+            state.orderbook.flush()?;
+        }
+        Ok(())
+    }
+
+    fn wants_trades(&self) -> bool {
+        true
+    }
+
+    fn on_trade(&mut self, venue: &str, symbol: &str, trade: &Trade, local_time: u64) -> Result<(), Box<dyn Error>> {
+        self.write_trade(venue, symbol, trade, local_time)
+    }
+}
@@ -0,0 +1,213 @@
+use std::error::Error;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::exchange::{DepthMessage, RawFrame};
+use crate::order_book::OrderBook;
+use crate::trade::Trade;
+
+pub mod capture;
+pub mod clickhouse;
+pub mod columns;
+pub mod compact;
+pub mod ilp;
+pub mod ipc;
+pub mod itch;
+pub mod kafka;
+pub mod lobster;
+pub mod mqtt;
+pub mod msgpack;
+pub mod nats;
+pub mod ndjson;
+pub mod parquet;
+pub mod postgres;
+pub mod query;
+pub mod shm;
+pub mod sqlite;
+pub mod tardis;
+
+/// 一条行情记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Record {
+    /// 原始文本消息帧
+    Raw {
+        /// 本地接收时间（毫秒）
+        recv_time: u64,
+        venue: String,
+        frame: String,
+    },
+    /// 原始二进制消息帧，内容为十六进制字符串
+    Binary {
+        /// 本地接收时间（毫秒）
+        recv_time: u64,
+        venue: String,
+        frame: String,
+    },
+    /// 统一格式的深度更新
+    Update {
+        /// 本地接收时间（毫秒）
+        recv_time: u64,
+        venue: String,
+        message: DepthMessage,
+    },
+    /// 一笔成交
+    Trade {
+        /// 本地接收时间（毫秒）
+        recv_time: u64,
+        venue: String,
+        symbol: String,
+        trade: Trade,
+    },
+    /// 本地订单薄的完整快照，定期写入以便快速重建任意时刻的订单薄
+    Keyframe {
+        /// 本地时间（毫秒）
+        recv_time: u64,
+        venue: String,
+        message: DepthMessage,
+    },
+}
+
+impl Record {
+    /// 由原始消息帧创建记录
+    pub fn frame(venue: &str, frame: &RawFrame, local_time: u64) -> Self {
+        match frame {
+            RawFrame::Text(text) => Record::Raw {
+                recv_time: local_time,
+                venue: venue.to_string(),
+                frame: text.clone(),
+            },
+            RawFrame::Binary(data) => Record::Binary {
+                recv_time: local_time,
+                venue: venue.to_string(),
+                frame: data.iter().map(|byte| format!("{:02x}", byte)).collect(),
+            },
+        }
+    }
+
+    /// 由统一格式的深度更新创建记录
+    pub fn update(venue: &str, message: &DepthMessage, local_time: u64) -> Self {
+        Record::Update {
+            recv_time: local_time,
+            venue: venue.to_string(),
+            message: message.clone(),
+        }
+    }
+
+    /// 由成交创建记录
+    pub fn trade(venue: &str, symbol: &str, trade: &Trade, local_time: u64) -> Self {
+        Record::Trade {
+            recv_time: local_time,
+            venue: venue.to_string(),
+            symbol: symbol.to_string(),
+            trade: *trade,
+        }
+    }
+
+    /// 由本地订单薄创建关键帧
+    pub fn keyframe(venue: &str, symbol: &str, book: &OrderBook, local_time: u64) -> Self {
+        Record::Keyframe {
+            recv_time: local_time,
+            venue: venue.to_string(),
+            message: DepthMessage::from_book(symbol, book, local_time),
+        }
+    }
+
+    /// 本地接收时间（毫秒）
+    pub fn recv_time(&self) -> u64 {
+        match self {
+            Record::Raw { recv_time, .. } | Record::Binary { recv_time, .. }
+            | Record::Update { recv_time, .. } | Record::Trade { recv_time, .. }
+            | Record::Keyframe { recv_time, .. } => *recv_time,
+        }
+    }
+
+    /// 交易所名称
+    pub fn venue(&self) -> &str {
+        match self {
+            Record::Raw { venue, .. } | Record::Binary { venue, .. }
+            | Record::Update { venue, .. } | Record::Trade { venue, .. }
+            | Record::Keyframe { venue, .. } => venue,
+        }
+    }
+}
+
+/// 行情记录器，接收原始消息帧和统一格式的深度更新并写入存储
+pub trait Recorder: Send + std::fmt::Debug {
+    /// 写入一条记录
+    fn record(&mut self, record: &Record) -> Result<(), Box<dyn Error>>;
+
+    /// 把缓冲的数据写入存储
+    fn flush(&mut self) -> Result<(), Box<dyn Error>>;
+
+    /// 订单薄更新后调用，用于记录定期快照，默认忽略
+    fn on_book(&mut self, _venue: &str, _symbol: &str, _book: &OrderBook, _local_time: u64) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    /// 是否需要成交数据，返回 true 时订阅成交流
+    fn wants_trades(&self) -> bool {
+        false
+    }
+
+    /// 收到成交后调用，默认忽略
+    fn on_trade(&mut self, _venue: &str, _symbol: &str, _trade: &Trade, _local_time: u64) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
+
+/// 按扩展名读取 NDJSON（`.ndjson`）、MessagePack（`.msgpack`）、压缩记录（`.zcap`）或 Tardis.dev 历史数据（`.csv`、`.txt`，
+/// 可为 `.gz`）文件的所有记录
+pub fn read_file(path: impl AsRef<Path>) -> Result<Vec<Record>, Box<dyn Error>> {
+    let path = path.as_ref();
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("ndjson") => ndjson::read_records(path),
+        Some("msgpack") => msgpack::read_records(path),
+        Some("zcap") => capture::CaptureReader::open(path)?.read_all(),
+        Some("csv" | "txt" | "gz") => tardis::read_records(path),
+        _ => Err(format!("不支持的记录文件: {}，可选 .ndjson、.msgpack、.zcap、.csv、.txt 或 .gz", path.display()).into()),
+    }
+}
+
+/// 对每个记录器执行操作，失败的记录器停止记录
+pub fn retain_recorders(recorders: &mut Vec<Box<dyn Recorder>>, mut action: impl FnMut(&mut dyn Recorder) -> Result<(), Box<dyn Error>>) {
+    recorders.retain_mut(|recorder| match action(recorder.as_mut()) {
+        Ok(()) => true,
+        Err(e) => {
+            error!(error = %e, "写入记录失败，停止记录");
+            false
+        }
+    });
+}
+
+/// 一小时的毫秒数
+pub(crate) const HOUR_MS: u64 = 3_600_000;
+/// 一天的毫秒数
+pub(crate) const DAY_MS: u64 = 86_400_000;
+
+/// 把毫秒时间戳格式化为 UTC 小时标签，例如 `20240101-08`，用于按小时命名文件
+pub(crate) fn utc_hour_label(millis: u64) -> String {
+    let hours = millis / HOUR_MS;
+    let days = (hours / 24) as i64;
+    let hour = hours % 24;
+
+    // 1970-01-01 起的天数转为公历日期
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}{:02}{:02}-{:02}", year, month, day, hour)
+}
+
+/// 把毫秒时间戳格式化为精确到毫秒的 UTC 标签，例如 `20240101-083015.250`
+pub(crate) fn utc_millis_label(millis: u64) -> String {
+    let within_hour = millis % HOUR_MS;
+    format!("{}{:02}{:02}.{:03}", utc_hour_label(millis), within_hour / 60_000, within_hour / 1_000 % 60, millis % 1_000)
+}
+
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::thread;
+use std::time::Duration;
+use rumqttc::{Client, MqttOptions, QoS};
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use tracing::warn;
+
+use crate::order_book::OrderBook;
+use crate::recorder::columns::to_f64;
+use crate::recorder::{Record, Recorder};
+
+/// 默认主题前缀
+pub const DEFAULT_MQTT_PREFIX: &str = "order_book";
+/// 默认每个订单薄的发布间隔
+pub const DEFAULT_MQTT_INTERVAL_MS: u64 = 1000;
+/// 默认端口
+const DEFAULT_PORT: u16 = 1883;
+/// 深度统计的中间价范围（基点）
+const DEPTH_BPS: u64 = 10;
+/// 发送队列长度，已满时丢弃消息
+const QUEUE_CAPACITY: usize = 1000;
+/// 连接断开后重试的间隔
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 单个订单薄的发布状态
+#[derive(Debug, Default)]
+struct SymbolState {
+    last_emit: u64,
+    /// 上次发布的最优价
+    top: Option<Value>,
+    /// 本间隔内的更新次数
+    updates: u64,
+}
+
+/// MQTT 发布记录器
+///
+/// 每个订单薄按固定间隔发布两个保留消息，新连接的订阅方立即收到最新值：
+///
+/// * `前缀/交易所/交易对/top` - 最优价变化时发布 `{"bid","bidQty","ask","askQty","time"}`
+/// * `前缀/交易所/交易对/summary` - `{"mid","spread","spreadBps","imbalance","bidDepth","askDepth","updates","time"}`，
+///   深度为中间价 10 基点内的数量，`updates` 为上个间隔内的更新次数
+///
+/// 数值为 JSON 数字，消息很小，适合仪表盘和家庭实验室环境。连接在后台线程维护，断开后自动重连
+pub struct MqttRecorder {
+    client: Client,
+    prefix: String,
+    interval_ms: u64,
+    states: HashMap<(String, String), SymbolState>,
+}
+
+impl std::fmt::Debug for MqttRecorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MqttRecorder")
+            .field("prefix", &self.prefix)
+            .field("interval_ms", &self.interval_ms)
+            .finish()
+    }
+}
+
+impl MqttRecorder {
+    /// 创建记录器并在后台连接服务器
+    ///
+    /// # 参数
+    ///
+    /// * `address` - 服务器地址 `mqtt://主机[:端口]` 或 `主机[:端口]`，默认端口 1883
+    /// * `prefix` - 主题前缀，例如 [`DEFAULT_MQTT_PREFIX`]
+    /// * `interval_ms` - 每个订单薄的发布间隔（毫秒），例如 [`DEFAULT_MQTT_INTERVAL_MS`]
+    pub fn new(address: &str, prefix: &str, interval_ms: u64) -> Result<Self, Box<dyn Error>> {
+        let address = address.strip_prefix("mqtt://").unwrap_or(address);
+        let (host, port) = match address.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse::<u16>().map_err(|_| format!("无效的端口: {}", port))?),
+            None => (address, DEFAULT_PORT),
+        };
+        let mut options = MqttOptions::new(format!("order_book-{}", std::process::id()), host, port);
+        options.set_keep_alive(Duration::from_secs(30));
+        let (client, mut connection) = Client::new(options, QUEUE_CAPACITY);
+        thread::spawn(move || {
+            for event in connection.iter() {
+                if let Err(e) = event {
+                    warn!(error = %e, "MQTT 连接错误");
+                    thread::sleep(RECONNECT_INTERVAL);
+                }
+            }
+        });
+        Ok(MqttRecorder {
+            client,
+            prefix: prefix.trim_end_matches('/').to_string(),
+            interval_ms,
+            states: HashMap::new(),
+        })
+    }
+
+    fn publish(&self, venue: &str, symbol: &str, kind: &str, payload: &Value) {
+        let topic = format!("{}/{}/{}/{}", self.prefix, venue, symbol, kind);
+        // 队列已满或连接断开时丢弃，下个间隔会发布新值
+        let _ = self.client.try_publish(topic, QoS::AtMostOnce, true, payload.to_string());
+    }
+}
+
+impl Recorder for MqttRecorder {
+    fn record(&mut self, _record: &Record) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn on_book(&mut self, venue: &str, symbol: &str, book: &OrderBook, local_time: u64) -> Result<(), Box<dyn Error>> {
+        let state = self.states.entry((venue.to_string(), symbol.to_string())).or_default();
+        state.updates += 1;
+        if local_time < state.last_emit + self.interval_ms {
+            return Ok(());
+        }
+        let (Some((bid, bid_quantity)), Some((ask, ask_quantity)), Some(mid)) = (book.best_bid(), book.best_ask(), book.mid_price()) else {
+            return Ok(());
+        };
+        let top = json!({
+            "bid": to_f64(bid),
+            "bidQty": to_f64(bid_quantity),
+            "ask": to_f64(ask),
+            "askQty": to_f64(ask_quantity),
+        });
+        let top_changed = state.top.as_ref() != Some(&top);
+        let spread = ask - bid;
+        let depth = book.depth_within_bps(Decimal::from(DEPTH_BPS));
+        let summary = json!({
+            "mid": to_f64(mid),
+            "spread": to_f64(spread),
+            "spreadBps": (!mid.is_zero()).then(|| to_f64(spread / mid * Decimal::from(10_000))),
+            "imbalance": book.imbalance(5).map(to_f64),
+            "bidDepth": depth.as_ref().map(|depth| to_f64(depth.bid_quantity)),
+            "askDepth": depth.as_ref().map(|depth| to_f64(depth.ask_quantity)),
+            "updates": state.updates,
+            "time": local_time,
+        });
+        state.last_emit = local_time;
+        state.updates = 0;
+        if top_changed {
+            state.top = Some(top.clone());
+            let mut top = top;
+            top["time"] = json!(local_time);
+            self.publish(venue, symbol, "top", &top);
+        }
+        self.publish(venue, symbol, "summary", &summary);
+        Ok(())
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::thread::{self, JoinHandle};
+use async_nats::jetstream;
+use prost::Message;
+use rust_decimal::Decimal;
+use serde_json::json;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tracing::{error, warn};
+
+use crate::capnp;
+use crate::exchange::DepthKind;
+use crate::order_book::OrderBook;
+use crate::proto::{FeedFormat, FeedMessage};
+use crate::recorder::{Record, Recorder};
+use crate::sbe;
+use crate::telemetry;
+
+/// 默认主题前缀
+pub const DEFAULT_PREFIX: &str = "book";
+/// 默认 JetStream 流名称
+pub const DEFAULT_STREAM: &str = "BOOK";
+/// JetStream 确认的批量大小，达到后等待服务端确认再继续发送
+const ACK_BATCH: usize = 256;
+
+/// 发送线程的一条消息：主题、内容和链路追踪消息头
+type Outgoing = (String, Vec<u8>, HashMap<String, String>);
+
+type TopOfBook = (Option<(Decimal, Decimal)>, Option<(Decimal, Decimal)>);
+
+/// NATS 连接配置
+#[derive(Debug, Clone)]
+pub struct NatsConfig {
+    /// 服务器地址，例如 `nats://localhost:4222`
+    pub url: String,
+    /// 主题前缀，主题为 `前缀.交易所.交易对.类型`
+    pub prefix: String,
+    /// JetStream 流名称，None 时使用普通发布；流不存在时创建，收集 `前缀.>` 的所有消息
+    pub stream: Option<String>,
+    pub format: FeedFormat,
+}
+
+impl NatsConfig {
+    pub fn new(url: &str) -> Self {
+        NatsConfig {
+            url: url.to_string(),
+            prefix: DEFAULT_PREFIX.to_string(),
+            stream: None,
+            format: FeedFormat::Json,
+        }
+    }
+}
+
+/// NATS 发布记录器
+///
+/// 每个订单薄发布到三个主题：
+///
+/// * `book.交易所.交易对.delta` - 统一格式的增量更新，内容与 NDJSON 记录的一行相同
+/// * `book.交易所.交易对.snapshot` - 交易所推送或重新获取的快照
+/// * `book.交易所.交易对.top` - 最优买卖价或数量变化时发送 `{"venue","symbol","time","bid":[价格,数量],"ask"}`
+///
+/// 使用 Protobuf 格式时三种消息都是 [`FeedMessage`](crate::proto::FeedMessage)，使用 SBE 或 Cap'n Proto 格式时按
+/// `schema/order_book_sbe.xml` 或 `schema/feed.capnp` 编码。
+/// 使用 JetStream 时消息写入持久化的流，消费者可以用持久订阅从断开处继续读取。发送在后台线程进行
+#[derive(Debug)]
+pub struct NatsRecorder {
+    prefix: String,
+    format: FeedFormat,
+    sender: Option<UnboundedSender<Outgoing>>,
+    writer: Option<JoinHandle<()>>,
+    /// (交易所, 交易对) -> 上次发送的最优价
+    tops: HashMap<(String, String), TopOfBook>,
+}
+
+impl NatsRecorder {
+    /// 连接服务器（使用 JetStream 时同时创建流）并启动发送线程，连接失败时返回错误
+    pub fn new(config: NatsConfig) -> Result<Self, Box<dyn Error>> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        let client = runtime.block_on(async_nats::connect(&config.url))?;
+        let context = match &config.stream {
+            Some(stream) => {
+                let context = jetstream::new(client.clone());
+                runtime.block_on(context.get_or_create_stream(jetstream::stream::Config {
+                    name: stream.clone(),
+                    subjects: vec![format!("{}.>", config.prefix)],
+                    ..Default::default()
+                }))?;
+                Some(context)
+            }
+            None => None,
+        };
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let writer = thread::spawn(move || runtime.block_on(run_writer(client, context, receiver)));
+        Ok(NatsRecorder {
+            prefix: config.prefix,
+            format: config.format,
+            sender: Some(sender),
+            writer: Some(writer),
+            tops: HashMap::new(),
+        })
+    }
+
+    /// 主题，交易所和交易对中的 `.`、空格和通配符替换为 `_`
+    fn subject(&self, venue: &str, symbol: &str, kind: &str) -> String {
+        let token = |value: &str| value.replace(['.', ' ', '*', '>'], "_");
+        format!("{}.{}.{}.{}", self.prefix, token(venue), token(symbol), kind)
+    }
+
+    /// 交给发送线程，启用 OTLP 时附带当前链路的 traceparent 作为消息头
+    fn send(&self, subject: String, payload: Vec<u8>) -> Result<(), Box<dyn Error>> {
+        let sender = self.sender.as_ref().ok_or("发送线程已停止")?;
+        sender.send((subject, payload, telemetry::trace_headers())).map_err(|_| "发送线程已停止".into())
+    }
+}
+
+impl Recorder for NatsRecorder {
+    fn record(&mut self, record: &Record) -> Result<(), Box<dyn Error>> {
+        let Record::Update { venue, message, .. } = record else {
+            return Ok(());
+        };
+        let kind = match message.kind {
+            DepthKind::Snapshot => "snapshot",
+            DepthKind::Delta => "delta",
+        };
+        match self.format.encode(record)? {
+            Some(payload) => self.send(self.subject(venue, &message.symbol, kind), payload),
+            None => Ok(()),
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn on_book(&mut self, venue: &str, symbol: &str, book: &OrderBook, local_time: u64) -> Result<(), Box<dyn Error>> {
+        let top = (book.best_bid(), book.best_ask());
+        let key = (venue.to_string(), symbol.to_string());
+        if self.tops.get(&key) == Some(&top) {
+            return Ok(());
+        }
+        self.tops.insert(key, top);
+        match self.format {
+            FeedFormat::Protobuf => {
+                let payload = FeedMessage::top_of_book(venue, symbol, book, local_time).encode_to_vec();
+                return self.send(self.subject(venue, symbol, "top"), payload);
+            }
+            FeedFormat::Sbe => {
+                let payload = sbe::encode_top_of_book(venue, symbol, book, local_time)?;
+                return self.send(self.subject(venue, symbol, "top"), payload);
+            }
+            FeedFormat::CapnProto => {
+                let payload = capnp::encode_top_of_book(venue, symbol, book, local_time);
+                return self.send(self.subject(venue, symbol, "top"), payload);
+            }
+            FeedFormat::Json => {}
+        }
+        let level = |level: Option<(Decimal, Decimal)>| level.map(|(price, quantity)| [price.to_string(), quantity.to_string()]);
+        let payload = json!({
+            "venue": venue,
+            "symbol": symbol,
+            "time": local_time,
+            "bid": level(top.0),
+            "ask": level(top.1),
+        });
+        self.send(self.subject(venue, symbol, "top"), payload.to_string().into_bytes())
+    }
+}
+
+impl Drop for NatsRecorder {
+    fn drop(&mut self) {
+        self.sender.take();
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+/// 发送线程：依次发布消息，使用 JetStream 时成批等待服务端确认；记录器关闭时发出剩余消息后退出
+async fn run_writer(client: async_nats::Client, context: Option<jetstream::Context>, mut receiver: UnboundedReceiver<Outgoing>) {
+    let mut acks = Vec::new();
+    while let Some((subject, payload, trace_headers)) = receiver.recv().await {
+        // 没有链路信息时不带消息头，与未启用 OTLP 时的消息相同
+        let headers = (!trace_headers.is_empty()).then(|| {
+            let mut headers = async_nats::HeaderMap::new();
+            for (key, value) in trace_headers {
+                headers.insert(key, value);
+            }
+            headers
+        });
+        match &context {
+            Some(context) => {
+                let published = match headers {
+                    Some(headers) => context.publish_with_headers(subject, headers, payload.into()).await,
+                    None => context.publish(subject, payload.into()).await,
+                };
+                match published {
+                    Ok(ack) => acks.push(ack),
+                    Err(e) => warn!(error = %e, "JetStream 发布失败"),
+                }
+            }
+            None => {
+                let published = match headers {
+                    Some(headers) => client.publish_with_headers(subject, headers, payload.into()).await,
+                    None => client.publish(subject, payload.into()).await,
+                };
+                if let Err(e) = published {
+                    warn!(error = %e, "NATS 发布失败");
+                }
+            }
+        }
+        if acks.len() >= ACK_BATCH || (receiver.is_empty() && !acks.is_empty()) {
+            for ack in acks.drain(..) {
+                if let Err(e) = ack.await {
+                    warn!(error = %e, "JetStream 未确认");
+                }
+            }
+        }
+    }
+    if let Err(e) = client.flush().await {
+        error!(error = %e, "NATS 发送失败");
+    }
+}
@@ -0,0 +1,122 @@
+use std::error::Error;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::recorder::{utc_hour_label, Record, Recorder, HOUR_MS};
+use crate::trade::Trade;
+
+/// 缓冲数据写入文件的最长间隔（毫秒）
+const FLUSH_INTERVAL_MS: u64 = 1_000;
+
+/// 行情记录器
+///
+/// 把原始消息帧、统一格式的深度更新和成交逐行写入 NDJSON 文件，按 UTC 小时切换文件，
+/// 文件名为 `目录/YYYYMMDD-HH.ndjson`，已存在的文件追加写入
+#[derive(Debug)]
+pub struct NdjsonRecorder {
+    dir: PathBuf,
+    /// 当前文件对应的小时序号
+    hour: Option<u64>,
+    writer: Option<BufWriter<File>>,
+    last_flush: u64,
+}
+
+impl NdjsonRecorder {
+    /// 创建记录器，目录不存在时自动创建
+    pub fn new(dir: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(NdjsonRecorder {
+            dir: dir.as_ref().to_path_buf(),
+            hour: None,
+            writer: None,
+            last_flush: 0,
+        })
+    }
+
+    /// 记录目录
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// 当前正在写入的文件
+    pub fn current_path(&self) -> Option<PathBuf> {
+        self.hour.map(|hour| self.path_for(hour * HOUR_MS))
+    }
+
+    fn write(&mut self, record: &Record, local_time: u64) -> Result<(), Box<dyn Error>> {
+        let hour = local_time / HOUR_MS;
+        if self.hour != Some(hour) {
+            self.flush()?;
+            let file = OpenOptions::new().create(true).append(true).open(self.path_for(local_time))?;
+            self.writer = Some(BufWriter::new(file));
+            self.hour = Some(hour);
+        }
+        let Some(writer) = self.writer.as_mut() else {
+            return Ok(());
+        };
+        serde_json::to_writer(&mut *writer, record)?;
+        writer.write_all(b"\n")?;
+        if local_time >= self.last_flush + FLUSH_INTERVAL_MS {
+            writer.flush()?;
+            self.last_flush = local_time;
+        }
+        Ok(())
+    }
+
+    fn path_for(&self, local_time: u64) -> PathBuf {
+        self.dir.join(format!("{}.ndjson", utc_hour_label(local_time)))
+    }
+}
+
+impl Recorder for NdjsonRecorder {
+    fn record(&mut self, record: &Record) -> Result<(), Box<dyn Error>> {
+        self.write(record, record.recv_time())
+    }
+
+    fn flush(&mut self) -> Result<(), Box<dyn Error>> {
+        if let Some(writer) = self.writer.as_mut() {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+
+    fn wants_trades(&self) -> bool {
+        true
+    }
+
+    fn on_trade(&mut self, venue: &str, symbol: &str, trade: &Trade, local_time: u64) -> Result<(), Box<dyn Error>> {
+        self.record(&Record::trade(venue, symbol, trade, local_time))
+    }
+}
+
+/// 读取记录文件的所有行
+pub fn read_records(path: impl AsRef<Path>) -> Result<Vec<Record>, Box<dyn Error>> {
+    let content = fs::read_to_string(path)?;
+    content.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Ok(serde_json::from_str::<Record>(line)?))
+        .collect()
+}
+
+/// 读取记录文件中所有可解析的行，并返回无法解析的行的说明，不在第一个错误处停止
+pub fn scan_records(path: impl AsRef<Path>) -> Result<(Vec<Record>, Vec<String>), Box<dyn Error>> {
+    let content = fs::read_to_string(path)?;
+    let mut records = Vec::new();
+    let mut problems = Vec::new();
+    let line_count = content.lines().count();
+    for (i, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<Record>(line) {
+            Ok(record) => records.push(record),
+            // 最后一行没有换行符且无法解析，通常是写入中途退出
+            Err(_) if i + 1 == line_count && !content.ends_with('\n') => {
+                problems.push(format!("第 {} 行不完整，文件可能被截断", i + 1));
+            }
+            Err(e) => problems.push(format!("第 {} 行解析失败: {}", i + 1, e)),
+        }
+    }
+    Ok((records, problems))
+}
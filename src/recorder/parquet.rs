@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use arrow::datatypes::SchemaRef;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+use tracing::error;
+
+use crate::order_book::OrderBook;
+use crate::recorder::columns::{delta_schema, snapshot_schema, DeltaRows, SnapshotRows, BATCH_ROWS};
+use crate::recorder::{utc_hour_label, Record, Recorder, HOUR_MS};
+
+/// 一个小时内的两个输出文件
+struct HourFiles {
+    hour: u64,
+    deltas: ArrowWriter<File>,
+    snapshots: ArrowWriter<File>,
+}
+
+impl HourFiles {
+    fn close(self) -> Result<(), Box<dyn Error>> {
+        self.deltas.close()?;
+        self.snapshots.close()?;
+        Ok(())
+    }
+}
+
+/// Parquet 行情记录器
+///
+/// 把统一格式的深度更新按档位展开写入 `目录/YYYYMMDD-HH.deltas.parquet`，
+/// 并按固定间隔把各订单薄前 N 档写入 `目录/YYYYMMDD-HH.snapshots.parquet`，
+/// 表结构见 [`delta_schema`] 和 [`snapshot_schema`]。Parquet 文件在切换小时或退出时才写入文件尾，
+/// 之前无法读取；同一小时的文件已存在时以 `-1`、`-2` 等后缀新建文件
+pub struct ParquetRecorder {
+    dir: PathBuf,
+    snapshot_interval_ms: u64,
+    snapshot_depth: usize,
+    files: Option<HourFiles>,
+    deltas: DeltaRows,
+    snapshots: SnapshotRows,
+    /// (交易所, 交易对) -> 上次快照时间
+    last_snapshot: HashMap<(String, String), u64>,
+}
+
+impl std::fmt::Debug for ParquetRecorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParquetRecorder")
+            .field("dir", &self.dir)
+            .field("snapshot_interval_ms", &self.snapshot_interval_ms)
+            .field("snapshot_depth", &self.snapshot_depth)
+            .field("hour", &self.files.as_ref().map(|files| files.hour))
+            .finish()
+    }
+}
+
+impl ParquetRecorder {
+    /// 创建记录器，目录不存在时自动创建
+    ///
+    /// # 参数
+    ///
+    /// * `dir` - 输出目录
+    /// * `snapshot_interval_ms` - 快照间隔（毫秒）
+    /// * `snapshot_depth` - 快照档位数
+    pub fn new(dir: impl AsRef<Path>, snapshot_interval_ms: u64, snapshot_depth: usize) -> Result<Self, Box<dyn Error>> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(ParquetRecorder {
+            dir: dir.as_ref().to_path_buf(),
+            snapshot_interval_ms,
+            snapshot_depth,
+            files: None,
+            deltas: DeltaRows::default(),
+            snapshots: SnapshotRows::default(),
+            last_snapshot: HashMap::new(),
+        })
+    }
+
+    /// 写入缓冲的行，不写入文件尾
+    fn write_buffered(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(files) = self.files.as_mut() else {
+            return Ok(());
+        };
+        if self.deltas.len() > 0 {
+            files.deltas.write(&self.deltas.take_batch()?)?;
+        }
+        if self.snapshots.len() > 0 {
+            files.snapshots.write(&self.snapshots.take_batch()?)?;
+        }
+        Ok(())
+    }
+
+    /// 切换到 `local_time` 所在小时的文件，关闭之前的文件
+    fn rotate(&mut self, local_time: u64) -> Result<(), Box<dyn Error>> {
+        let hour = local_time / HOUR_MS;
+        if self.files.as_ref().is_some_and(|files| files.hour == hour) {
+            return Ok(());
+        }
+        self.write_buffered()?;
+        if let Some(files) = self.files.take() {
+            files.close()?;
+        }
+        let label = utc_hour_label(local_time);
+        self.files = Some(HourFiles {
+            hour,
+            deltas: self.create_writer(&label, "deltas", delta_schema())?,
+            snapshots: self.create_writer(&label, "snapshots", snapshot_schema())?,
+        });
+        Ok(())
+    }
+
+    fn create_writer(&self, label: &str, table: &str, schema: SchemaRef) -> Result<ArrowWriter<File>, Box<dyn Error>> {
+        let mut path = self.dir.join(format!("{}.{}.parquet", label, table));
+        let mut suffix = 1;
+        while path.exists() {
+            path = self.dir.join(format!("{}-{}.{}.parquet", label, suffix, table));
+            suffix += 1;
+        }
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::ZSTD(ZstdLevel::default()))
+            .build();
+        Ok(ArrowWriter::try_new(File::create(path)?, schema, Some(properties))?)
+    }
+}
+
+impl Recorder for ParquetRecorder {
+    fn record(&mut self, record: &Record) -> Result<(), Box<dyn Error>> {
+        let Record::Update { recv_time, venue, message } = record else {
+            return Ok(());
+        };
+        self.rotate(*recv_time)?;
+        self.deltas.push_update(*recv_time, venue, message);
+        if self.deltas.len() >= BATCH_ROWS {
+            self.write_buffered()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Box<dyn Error>> {
+        self.write_buffered()?;
+        if let Some(files) = self.files.as_mut() {
+            files.deltas.flush()?;
+            files.snapshots.flush()?;
+        }
+        Ok(())
+    }
+
+    fn on_book(&mut self, venue: &str, symbol: &str, book: &OrderBook, local_time: u64) -> Result<(), Box<dyn Error>> {
+        let key = (venue.to_string(), symbol.to_string());
+        if self.last_snapshot.get(&key).is_some_and(|last| local_time < last + self.snapshot_interval_ms) {
+            return Ok(());
+        }
+        self.last_snapshot.insert(key, local_time);
+        self.rotate(local_time)?;
+        self.snapshots.push_book(venue, symbol, book, self.snapshot_depth, local_time);
+        if self.snapshots.len() >= BATCH_ROWS {
+            self.write_buffered()?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for ParquetRecorder {
+    fn drop(&mut self) {
+        let closed = self.write_buffered()
+            .and_then(|()| self.files.take().map_or(Ok(()), HourFiles::close));
+        if let Err(e) = closed {
+            error!(error = %e, "关闭 Parquet 文件失败");
+        }
+    }
+}
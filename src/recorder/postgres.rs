@@ -0,0 +1,363 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use postgres::{Client, NoTls};
+use rust_decimal::Decimal;
+use tracing::{error, info, warn};
+
+use crate::order_book::{OrderBook, Side};
+use crate::recorder::{Record, Recorder};
+use crate::trade::Trade;
+
+/// 第一次重试前的等待时间，之后每次翻倍
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+/// 重试等待时间上限
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// 按版本顺序执行的建表语句，已执行的版本记录在 `schema_migrations` 表中
+const MIGRATIONS: &[&str] = &[
+    "
+    CREATE TABLE IF NOT EXISTS top_of_book (
+        time TIMESTAMPTZ NOT NULL,
+        venue TEXT NOT NULL,
+        symbol TEXT NOT NULL,
+        bid_price NUMERIC,
+        bid_quantity NUMERIC,
+        ask_price NUMERIC,
+        ask_quantity NUMERIC
+    );
+    CREATE INDEX IF NOT EXISTS top_of_book_symbol_time ON top_of_book (venue, symbol, time DESC);
+
+    CREATE TABLE IF NOT EXISTS depth_snapshots (
+        time TIMESTAMPTZ NOT NULL,
+        venue TEXT NOT NULL,
+        symbol TEXT NOT NULL,
+        sequence BIGINT NOT NULL,
+        bid_prices NUMERIC[] NOT NULL,
+        bid_quantities NUMERIC[] NOT NULL,
+        ask_prices NUMERIC[] NOT NULL,
+        ask_quantities NUMERIC[] NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS depth_snapshots_symbol_time ON depth_snapshots (venue, symbol, time DESC);
+
+    CREATE TABLE IF NOT EXISTS trades (
+        time TIMESTAMPTZ NOT NULL,
+        recv_time TIMESTAMPTZ NOT NULL,
+        venue TEXT NOT NULL,
+        symbol TEXT NOT NULL,
+        price NUMERIC NOT NULL,
+        quantity NUMERIC NOT NULL,
+        buyer_is_maker BOOLEAN NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS trades_symbol_time ON trades (venue, symbol, time DESC);
+    ",
+];
+
+/// 安装了 TimescaleDB 时转为超表的表
+const HYPERTABLES: &[&str] = &["top_of_book", "depth_snapshots", "trades"];
+
+/// PostgreSQL 写入配置
+#[derive(Debug, Clone)]
+pub struct PostgresConfig {
+    /// 连接字符串，例如 `host=localhost user=postgres dbname=market`
+    pub url: String,
+    /// 每批写入的行数
+    pub batch_size: usize,
+    /// 行数不足一批时最长等待时间
+    pub flush_interval: Duration,
+    /// 写入失败后的最大重试次数，超过后丢弃该批数据
+    pub max_retries: u32,
+    /// 深度快照间隔（毫秒）
+    pub snapshot_interval_ms: u64,
+    /// 深度快照档位数
+    pub snapshot_depth: usize,
+}
+
+impl PostgresConfig {
+    /// 使用默认参数：每批1000行、最长等待1秒、重试5次、每秒记录前20档快照
+    pub fn new(url: &str) -> Self {
+        PostgresConfig {
+            url: url.to_string(),
+            batch_size: 1000,
+            flush_interval: Duration::from_secs(1),
+            max_retries: 5,
+            snapshot_interval_ms: 1000,
+            snapshot_depth: 20,
+        }
+    }
+}
+
+/// 待写入的一行
+#[derive(Debug, Clone)]
+enum Row {
+    TopOfBook {
+        time: u64,
+        venue: String,
+        symbol: String,
+        bid: Option<(Decimal, Decimal)>,
+        ask: Option<(Decimal, Decimal)>,
+    },
+    Snapshot {
+        time: u64,
+        venue: String,
+        symbol: String,
+        sequence: u64,
+        bids: Vec<(Decimal, Decimal)>,
+        asks: Vec<(Decimal, Decimal)>,
+    },
+    Trade {
+        venue: String,
+        symbol: String,
+        trade: Trade,
+        recv_time: u64,
+    },
+}
+
+/// 发往写入线程的命令
+#[derive(Debug)]
+enum Command {
+    Row(Row),
+    Flush,
+}
+
+/// (买一, 卖一)
+type TopOfBook = (Option<(Decimal, Decimal)>, Option<(Decimal, Decimal)>);
+
+/// PostgreSQL / TimescaleDB 行情记录器
+///
+/// 启动时执行建表迁移，安装了 TimescaleDB 时把各表转为超表。最优价变化、定期深度快照和成交
+/// 发送到后台线程，按批在事务中写入；写入失败时重新连接并按指数退避重试
+#[derive(Debug)]
+pub struct PostgresRecorder {
+    config: PostgresConfig,
+    sender: Option<Sender<Command>>,
+    writer: Option<JoinHandle<()>>,
+    /// (交易所, 交易对) -> 上次记录的最优价
+    last_top: HashMap<(String, String), TopOfBook>,
+    /// (交易所, 交易对) -> 上次快照时间
+    last_snapshot: HashMap<(String, String), u64>,
+}
+
+impl PostgresRecorder {
+    /// 连接数据库、执行迁移并启动写入线程
+    pub fn connect(config: PostgresConfig) -> Result<Self, Box<dyn Error>> {
+        let mut client = Client::connect(&config.url, NoTls)?;
+        migrate(&mut client)?;
+        let (sender, receiver) = mpsc::channel();
+        let writer_config = config.clone();
+        let writer = thread::spawn(move || run_writer(Some(client), &writer_config, receiver));
+        Ok(PostgresRecorder {
+            config,
+            sender: Some(sender),
+            writer: Some(writer),
+            last_top: HashMap::new(),
+            last_snapshot: HashMap::new(),
+        })
+    }
+
+    fn send(&self, command: Command) -> Result<(), Box<dyn Error>> {
+        let sender = self.sender.as_ref().ok_or("写入线程已停止")?;
+        sender.send(command).map_err(|_| "写入线程已停止".into())
+    }
+}
+
+impl Recorder for PostgresRecorder {
+    fn record(&mut self, _record: &Record) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Box<dyn Error>> {
+        self.send(Command::Flush)
+    }
+
+    fn on_book(&mut self, venue: &str, symbol: &str, book: &OrderBook, local_time: u64) -> Result<(), Box<dyn Error>> {
+        let key = (venue.to_string(), symbol.to_string());
+        let top = (book.best_bid(), book.best_ask());
+        if self.last_top.get(&key) != Some(&top) {
+            self.last_top.insert(key.clone(), top);
+            self.send(Command::Row(Row::TopOfBook {
+                time: local_time,
+                venue: venue.to_string(),
+                symbol: symbol.to_string(),
+                bid: top.0,
+                ask: top.1,
+            }))?;
+        }
+        if self.last_snapshot.get(&key).is_none_or(|last| local_time >= last + self.config.snapshot_interval_ms) {
+            self.last_snapshot.insert(key, local_time);
+            self.send(Command::Row(Row::Snapshot {
+                time: local_time,
+                venue: venue.to_string(),
+                symbol: symbol.to_string(),
+                sequence: book.last_update_id,
+                bids: book.top_levels(Side::Bid, self.config.snapshot_depth),
+                asks: book.top_levels(Side::Ask, self.config.snapshot_depth),
+            }))?;
+        }
+        Ok(())
+    }
+
+    fn wants_trades(&self) -> bool {
+        true
+    }
+
+    fn on_trade(&mut self, venue: &str, symbol: &str, trade: &Trade, local_time: u64) -> Result<(), Box<dyn Error>> {
+        self.send(Command::Row(Row::Trade {
+            venue: venue.to_string(),
+            symbol: symbol.to_string(),
+            trade: *trade,
+            recv_time: local_time,
+        }))
+    }
+}
+
+impl Drop for PostgresRecorder {
+    fn drop(&mut self) {
+        // 关闭通道后写入线程写完剩余数据退出
+        self.sender.take();
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+/// 执行尚未执行的迁移，安装了 TimescaleDB 时创建超表
+fn migrate(client: &mut Client) -> Result<(), Box<dyn Error>> {
+    client.batch_execute(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            applied_at TIMESTAMPTZ NOT NULL DEFAULT now()
+        )",
+    )?;
+    let mut transaction = client.transaction()?;
+    let applied: i32 = transaction.query_one("SELECT COALESCE(MAX(version), 0) FROM schema_migrations", &[])?.get(0);
+    for (version, migration) in MIGRATIONS.iter().enumerate().skip(applied as usize) {
+        let version = version as i32 + 1;
+        transaction.batch_execute(migration)?;
+        transaction.execute("INSERT INTO schema_migrations (version) VALUES ($1)", &[&version])?;
+        info!(version, "PostgreSQL 迁移完成");
+    }
+    transaction.commit()?;
+
+    let timescale: bool = client.query_one("SELECT EXISTS (SELECT 1 FROM pg_extension WHERE extname = 'timescaledb')", &[])?.get(0);
+    if timescale {
+        for table in HYPERTABLES {
+            client.batch_execute(&format!("SELECT create_hypertable('{}', 'time', if_not_exists => TRUE, migrate_data => TRUE)", table))?;
+        }
+    }
+    Ok(())
+}
+
+/// 写入线程：按批大小或时间间隔写入，通道关闭后写完剩余数据退出
+fn run_writer(mut client: Option<Client>, config: &PostgresConfig, receiver: Receiver<Command>) {
+    let mut batch = Vec::with_capacity(config.batch_size);
+    let mut batch_start = Instant::now();
+    loop {
+        let timeout = config.flush_interval.saturating_sub(batch_start.elapsed());
+        let (flush, closed) = match receiver.recv_timeout(timeout) {
+            Ok(Command::Row(row)) => {
+                if batch.is_empty() {
+                    batch_start = Instant::now();
+                }
+                batch.push(row);
+                (batch.len() >= config.batch_size, false)
+            }
+            Ok(Command::Flush) => (true, false),
+            Err(RecvTimeoutError::Timeout) => (true, false),
+            Err(RecvTimeoutError::Disconnected) => (true, true),
+        };
+        if flush && !batch.is_empty() {
+            write_with_retry(&mut client, config, &batch);
+            batch.clear();
+        }
+        if flush {
+            batch_start = Instant::now();
+        }
+        if closed {
+            return;
+        }
+    }
+}
+
+/// 写入一批数据，失败时重新连接并重试，超过重试次数后丢弃
+fn write_with_retry(client: &mut Option<Client>, config: &PostgresConfig, batch: &[Row]) {
+    let mut delay = RETRY_DELAY;
+    for attempt in 0..=config.max_retries {
+        if attempt > 0 {
+            thread::sleep(delay);
+            delay = (delay * 2).min(MAX_RETRY_DELAY);
+        }
+        let mut connected = match client.take() {
+            Some(connected) => connected,
+            None => match Client::connect(&config.url, NoTls) {
+                Ok(connected) => connected,
+                Err(e) => {
+                    warn!(attempt = attempt + 1, error = %e, "PostgreSQL 重新连接失败");
+                    continue;
+                }
+            },
+        };
+        match write_batch(&mut connected, batch) {
+            Ok(()) => {
+                *client = Some(connected);
+                return;
+            }
+            Err(e) => {
+                // 连接可能已失效，下次重试时重新连接
+                warn!(attempt = attempt + 1, error = %e, "PostgreSQL 写入失败");
+            }
+        }
+    }
+    error!(rows = batch.len(), "PostgreSQL 写入重试次数用尽，丢弃数据");
+}
+
+/// 在一个事务中写入一批数据
+fn write_batch(client: &mut Client, batch: &[Row]) -> Result<(), Box<dyn Error>> {
+    let mut transaction = client.transaction()?;
+    let top_of_book = transaction.prepare(
+        "INSERT INTO top_of_book (time, venue, symbol, bid_price, bid_quantity, ask_price, ask_quantity)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)",
+    )?;
+    let snapshot = transaction.prepare(
+        "INSERT INTO depth_snapshots (time, venue, symbol, sequence, bid_prices, bid_quantities, ask_prices, ask_quantities)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+    )?;
+    let trade_insert = transaction.prepare(
+        "INSERT INTO trades (time, recv_time, venue, symbol, price, quantity, buyer_is_maker)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)",
+    )?;
+    for row in batch {
+        match row {
+            Row::TopOfBook { time, venue, symbol, bid, ask } => {
+                transaction.execute(&top_of_book, &[
+                    &system_time(*time), venue, symbol,
+                    &bid.map(|(price, _)| price), &bid.map(|(_, quantity)| quantity),
+                    &ask.map(|(price, _)| price), &ask.map(|(_, quantity)| quantity),
+                ])?;
+            }
+            Row::Snapshot { time, venue, symbol, sequence, bids, asks } => {
+                let (bid_prices, bid_quantities): (Vec<Decimal>, Vec<Decimal>) = bids.iter().copied().unzip();
+                let (ask_prices, ask_quantities): (Vec<Decimal>, Vec<Decimal>) = asks.iter().copied().unzip();
+                transaction.execute(&snapshot, &[
+                    &system_time(*time), venue, symbol, &(*sequence as i64),
+                    &bid_prices, &bid_quantities, &ask_prices, &ask_quantities,
+                ])?;
+            }
+            Row::Trade { venue, symbol, trade, recv_time } => {
+                transaction.execute(&trade_insert, &[
+                    &system_time(trade.trade_time), &system_time(*recv_time), venue, symbol,
+                    &trade.price, &trade.quantity, &trade.buyer_is_maker,
+                ])?;
+            }
+        }
+    }
+    transaction.commit()?;
+    Ok(())
+}
+
+/// 毫秒时间戳转为 TIMESTAMPTZ 参数
+fn system_time(millis: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_millis(millis)
+}
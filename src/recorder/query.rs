@@ -0,0 +1,264 @@
+use std::error::Error;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use arrow::array::{Array, AsArray};
+use arrow::datatypes::{DataType, Float64Type, TimeUnit, TimestampMillisecondType, UInt32Type, UInt64Type};
+use duckdb::types::Value;
+use duckdb::{appender_params_from_iter, params, Connection};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+use crate::exchange::DepthKind;
+use crate::order_book::Side;
+use crate::recorder::columns::{side_name, to_f64};
+use crate::recorder::{read_file, Record};
+
+/// 建表语句，表结构与 [`SqliteRecorder`](crate::recorder::sqlite::SqliteRecorder) 的同名表相同；
+/// 最优价视图由快照第0档得到
+const SCHEMA: &str = "
+CREATE TABLE deltas (
+    recv_time BIGINT NOT NULL,
+    exchange_time BIGINT NOT NULL,
+    venue VARCHAR NOT NULL,
+    symbol VARCHAR NOT NULL,
+    kind VARCHAR NOT NULL,
+    sequence BIGINT,
+    side VARCHAR NOT NULL,
+    price DOUBLE NOT NULL,
+    quantity DOUBLE NOT NULL
+);
+
+CREATE TABLE snapshots (
+    time BIGINT NOT NULL,
+    venue VARCHAR NOT NULL,
+    symbol VARCHAR NOT NULL,
+    sequence BIGINT NOT NULL,
+    side VARCHAR NOT NULL,
+    level INTEGER NOT NULL,
+    price DOUBLE NOT NULL,
+    quantity DOUBLE NOT NULL
+);
+
+CREATE VIEW top_of_book AS
+SELECT time, venue, symbol,
+       MAX(CASE WHEN side = 'bid' THEN price END) AS best_bid,
+       MAX(CASE WHEN side = 'ask' THEN price END) AS best_ask
+FROM snapshots
+WHERE level = 0
+GROUP BY time, venue, symbol;
+";
+
+/// 查询结果，所有值已格式化为文本
+#[derive(Debug, Clone, Default)]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl QueryResult {
+    /// 按列宽对齐输出
+    pub fn print(&self) {
+        let mut widths: Vec<usize> = self.columns.iter().map(|column| column.chars().count()).collect();
+        for row in &self.rows {
+            for (width, value) in widths.iter_mut().zip(row) {
+                *width = (*width).max(value.chars().count());
+            }
+        }
+        let format_row = |values: &[String]| {
+            values.iter().zip(&widths)
+                .map(|(value, width)| format!("{:<width$}", value, width = width))
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        };
+        println!("{}", format_row(&self.columns));
+        println!("{}", widths.iter().map(|width| "-".repeat(*width)).collect::<Vec<_>>().join("  "));
+        for row in &self.rows {
+            println!("{}", format_row(row));
+        }
+        println!("共 {} 行", self.rows.len());
+    }
+}
+
+/// 对记录文件做即席 SQL 查询
+///
+/// 把 NDJSON（`.ndjson`）、压缩记录（`.zcap`）和 Parquet（`.deltas.parquet`、`.snapshots.parquet`）
+/// 文件载入内嵌的 DuckDB 内存数据库，表结构与 [`SqliteRecorder`](crate::recorder::sqlite::SqliteRecorder) 相同：
+///
+/// * `deltas` - 深度变动，每行一个档位，来自全部三种文件
+/// * `snapshots` - 定期快照，来自 Parquet 快照文件
+/// * `top_of_book` - 视图，快照第0档的 `time, venue, symbol, best_bid, best_ask`
+///
+/// 时间列均为毫秒时间戳，可用 `strftime(epoch_ms(time), '%Y-%m-%d %H:00')` 按小时分组
+pub struct QueryEngine {
+    conn: Connection,
+}
+
+impl std::fmt::Debug for QueryEngine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QueryEngine").finish_non_exhaustive()
+    }
+}
+
+impl QueryEngine {
+    /// 创建空的内存数据库
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        let conn = Connection::open_in_memory()?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(QueryEngine { conn })
+    }
+
+    /// 载入文件，目录会载入其中所有支持的文件，返回载入的文件数
+    pub fn load(&mut self, path: impl AsRef<Path>) -> Result<usize, Box<dyn Error>> {
+        let path = path.as_ref();
+        if !path.is_dir() {
+            return self.load_file(path).map(|()| 1);
+        }
+        let mut files: Vec<PathBuf> = fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<_, _>>()?;
+        files.sort();
+        let mut loaded = 0;
+        for file in files {
+            if file.is_file() && file_kind(&file).is_some() {
+                self.load_file(&file)?;
+                loaded += 1;
+            }
+        }
+        Ok(loaded)
+    }
+
+    fn load_file(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let records = match file_kind(path) {
+            Some(FileKind::Ndjson | FileKind::Capture) => read_file(path)?,
+            Some(FileKind::Parquet(table)) => return self.load_parquet(path, table),
+            None => return Err(format!("不支持的文件: {}", path.display()).into()),
+        };
+        let mut appender = self.conn.appender("deltas")?;
+        for record in &records {
+            let Record::Update { recv_time, venue, message } = record else {
+                continue;
+            };
+            let kind = match message.kind {
+                DepthKind::Snapshot => "snapshot",
+                DepthKind::Delta => "delta",
+            };
+            let sequence = message.continuity.sequence().map(|sequence| sequence as i64);
+            let levels = message.bids.iter().map(|level| (Side::Bid, level))
+                .chain(message.asks.iter().map(|level| (Side::Ask, level)));
+            for (side, (price, quantity)) in levels {
+                appender.append_row(params![
+                    *recv_time as i64,
+                    message.timestamp as i64,
+                    venue,
+                    message.symbol,
+                    kind,
+                    sequence,
+                    side_name(side),
+                    to_f64(*price),
+                    to_f64(*quantity),
+                ])?;
+            }
+        }
+        appender.flush()?;
+        Ok(())
+    }
+
+    /// 把 Parquet 文件的所有行按列名追加到同名表
+    fn load_parquet(&mut self, path: &Path, table: &str) -> Result<(), Box<dyn Error>> {
+        let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?)?.build()?;
+        for batch in reader {
+            let batch = batch?;
+            let schema = batch.schema();
+            let columns: Vec<&str> = schema.fields().iter().map(|field| field.name().as_str()).collect();
+            let mut appender = self.conn.appender_with_columns(table, &columns)?;
+            for row in 0..batch.num_rows() {
+                let values = batch.columns().iter()
+                    .map(|column| arrow_value(column.as_ref(), row))
+                    .collect::<Result<Vec<_>, _>>()?;
+                appender.append_row(appender_params_from_iter(values))?;
+            }
+            appender.flush()?;
+        }
+        Ok(())
+    }
+
+    /// 执行查询
+    pub fn query(&self, sql: &str) -> Result<QueryResult, Box<dyn Error>> {
+        let mut statement = self.conn.prepare(sql)?;
+        let mut rows = statement.query([])?;
+        // 列名在执行之后才能取得
+        let columns = rows.as_ref().map(|statement| statement.column_names()).unwrap_or_default();
+        let mut result = QueryResult { columns, rows: Vec::new() };
+        while let Some(row) = rows.next()? {
+            let values = (0..result.columns.len())
+                .map(|i| row.get::<_, Value>(i).map(format_value))
+                .collect::<Result<Vec<_>, _>>()?;
+            result.rows.push(values);
+        }
+        Ok(result)
+    }
+}
+
+/// 支持的文件类型
+enum FileKind {
+    Ndjson,
+    Capture,
+    /// Parquet 文件及其对应的表
+    Parquet(&'static str),
+}
+
+fn file_kind(path: &Path) -> Option<FileKind> {
+    let name = path.file_name()?.to_str()?;
+    if name.ends_with(".ndjson") {
+        Some(FileKind::Ndjson)
+    } else if name.ends_with(".zcap") {
+        Some(FileKind::Capture)
+    } else if name.ends_with(".deltas.parquet") {
+        Some(FileKind::Parquet("deltas"))
+    } else if name.ends_with(".snapshots.parquet") {
+        Some(FileKind::Parquet("snapshots"))
+    } else {
+        None
+    }
+}
+
+/// 取出 Arrow 列中的一个值，时间戳转为毫秒整数
+fn arrow_value(column: &dyn Array, row: usize) -> Result<Value, Box<dyn Error>> {
+    if column.is_null(row) {
+        return Ok(Value::Null);
+    }
+    let value = match column.data_type() {
+        DataType::Timestamp(TimeUnit::Millisecond, _) => Value::BigInt(column.as_primitive::<TimestampMillisecondType>().value(row)),
+        DataType::UInt64 => Value::BigInt(column.as_primitive::<UInt64Type>().value(row) as i64),
+        DataType::UInt32 => Value::BigInt(column.as_primitive::<UInt32Type>().value(row) as i64),
+        DataType::Float64 => Value::Double(column.as_primitive::<Float64Type>().value(row)),
+        DataType::Utf8 => Value::Text(column.as_string::<i32>().value(row).to_string()),
+        other => return Err(format!("不支持的列类型: {}", other).into()),
+    };
+    Ok(value)
+}
+
+/// 格式化一个值，时间戳等不常用的类型按调试格式输出，需要时可在 SQL 中用 `strftime` 转为文本
+fn format_value(value: Value) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::Boolean(value) => value.to_string(),
+        Value::TinyInt(value) => value.to_string(),
+        Value::SmallInt(value) => value.to_string(),
+        Value::Int(value) => value.to_string(),
+        Value::BigInt(value) => value.to_string(),
+        Value::HugeInt(value) => value.to_string(),
+        Value::UHugeInt(value) => value.to_string(),
+        Value::UTinyInt(value) => value.to_string(),
+        Value::USmallInt(value) => value.to_string(),
+        Value::UInt(value) => value.to_string(),
+        Value::UBigInt(value) => value.to_string(),
+        Value::Float(value) => value.to_string(),
+        Value::Double(value) => value.to_string(),
+        Value::Decimal(value) => value.to_string(),
+        Value::Text(value) | Value::Enum(value) => value,
+        Value::Blob(value) => format!("<{} 字节>", value.len()),
+        other => format!("{:?}", other),
+    }
+}
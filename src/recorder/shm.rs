@@ -0,0 +1,548 @@
+use std::error::Error;
+use std::fs::{self, File, OpenOptions};
+use std::mem::size_of;
+use std::path::Path;
+use std::ptr;
+use std::sync::atomic::{fence, AtomicU64, Ordering};
+use flatbuffers::FlatBufferBuilder;
+use memmap2::{Mmap, MmapMut};
+use tracing::warn;
+
+use crate::order_book::{OrderBook, Side};
+use crate::recorder::columns::to_f64;
+use crate::recorder::{Record, Recorder};
+
+#[allow(warnings, clippy::all)]
+#[rustfmt::skip]
+mod book_event_generated;
+
+/// `schema/book_event.fbs` 生成的 FlatBuffers 访问代码
+pub use book_event_generated::order_book::fb;
+
+/// 每个事件每侧的档位数
+pub const SHM_LEVELS: usize = 10;
+/// 默认槽位数
+pub const DEFAULT_SLOTS: usize = 16384;
+/// 文件标识 "OBRING01"
+const MAGIC: u64 = u64::from_le_bytes(*b"OBRING01");
+/// 文件头大小，写入位置独占一个缓存行
+const HEADER_SIZE: usize = 128;
+/// 写入位置在文件头中的偏移
+const WRITE_INDEX_OFFSET: usize = 64;
+/// 槽位按缓存行对齐
+const SLOT_ALIGN: usize = 64;
+/// FlatBuffers 格式每个事件的最大字节数（含 4 字节长度前缀），名称和档位数有上限，正常的编码结果不会超过
+const FLATBUFFERS_CAPACITY: usize = 1024;
+
+/// 槽位中事件的编码
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ShmFormat {
+    /// 定长的 [`BookEvent`] 结构体
+    #[default]
+    Raw,
+    /// 带长度前缀的 FlatBuffers 缓冲区，定义见 `schema/book_event.fbs`，其他语言用 flatc 生成访问代码直接读取
+    FlatBuffers,
+}
+
+impl ShmFormat {
+    /// 解析 `raw` 或 `flatbuffers`
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "raw" => Some(ShmFormat::Raw),
+            "flatbuffers" => Some(ShmFormat::FlatBuffers),
+            _ => None,
+        }
+    }
+
+    /// 写入文件头的格式编号
+    fn code(&self) -> u32 {
+        match self {
+            ShmFormat::Raw => 0,
+            ShmFormat::FlatBuffers => 1,
+        }
+    }
+
+    fn from_code(code: u64) -> Option<Self> {
+        match code {
+            0 => Some(ShmFormat::Raw),
+            1 => Some(ShmFormat::FlatBuffers),
+            _ => None,
+        }
+    }
+
+    /// 槽位中事件部分的大小
+    fn payload_size(&self) -> usize {
+        match self {
+            ShmFormat::Raw => size_of::<BookEvent>(),
+            ShmFormat::FlatBuffers => FLATBUFFERS_CAPACITY,
+        }
+    }
+
+    /// 每个槽位的大小：8 字节序号加事件，按缓存行对齐
+    fn slot_size(&self) -> usize {
+        (size_of::<u64>() + self.payload_size()).div_ceil(SLOT_ALIGN) * SLOT_ALIGN
+    }
+}
+
+/// 环形缓冲区中的订单薄事件，定长、小端、C 布局
+///
+/// 交易所和交易对名称以 0 填充，价格和数量为 f64，`[价格, 数量]`，只有前 `bid_count`、`ask_count` 档有效
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct BookEvent {
+    pub venue: [u8; 16],
+    pub symbol: [u8; 32],
+    pub last_update_id: u64,
+    /// 本地更新时间（毫秒）
+    pub local_time: u64,
+    pub bid_count: u32,
+    pub ask_count: u32,
+    /// 买单，价格降序
+    pub bids: [[f64; 2]; SHM_LEVELS],
+    /// 卖单，价格升序
+    pub asks: [[f64; 2]; SHM_LEVELS],
+}
+
+impl BookEvent {
+    /// 由订单薄创建，名称超长时截断
+    pub fn from_book(venue: &str, symbol: &str, book: &OrderBook, local_time: u64) -> Self {
+        fn name<const N: usize>(value: &str) -> [u8; N] {
+            let mut bytes = [0; N];
+            let len = value.len().min(N);
+            bytes[..len].copy_from_slice(&value.as_bytes()[..len]);
+            bytes
+        }
+        fn levels(book: &OrderBook, side: Side) -> ([[f64; 2]; SHM_LEVELS], u32) {
+            let mut levels = [[0.0; 2]; SHM_LEVELS];
+            let top = book.top_levels(side, SHM_LEVELS);
+            for (level, (price, quantity)) in levels.iter_mut().zip(&top) {
+                *level = [to_f64(*price), to_f64(*quantity)];
+            }
+            (levels, top.len() as u32)
+        }
+        let (bids, bid_count) = levels(book, Side::Bid);
+        let (asks, ask_count) = levels(book, Side::Ask);
+        BookEvent {
+            venue: name(venue),
+            symbol: name(symbol),
+            last_update_id: book.last_update_id,
+            local_time,
+            bid_count,
+            ask_count,
+            bids,
+            asks,
+        }
+    }
+
+    /// 交易所名称
+    pub fn venue(&self) -> &str {
+        trim_name(&self.venue)
+    }
+
+    /// 交易对名称
+    pub fn symbol(&self) -> &str {
+        trim_name(&self.symbol)
+    }
+
+    /// 由 FlatBuffers 事件创建，名称和档位超长时截断
+    pub fn from_flatbuffers(event: &fb::BookEvent) -> Self {
+        fn name<const N: usize>(value: Option<&str>) -> [u8; N] {
+            let mut bytes = [0; N];
+            let value = value.unwrap_or_default();
+            let len = value.len().min(N);
+            bytes[..len].copy_from_slice(&value.as_bytes()[..len]);
+            bytes
+        }
+        fn levels(side: Option<flatbuffers::Vector<fb::Level>>) -> ([[f64; 2]; SHM_LEVELS], u32) {
+            let mut levels = [[0.0; 2]; SHM_LEVELS];
+            let mut count = 0;
+            for (level, source) in levels.iter_mut().zip(side.iter().flatten()) {
+                *level = [source.price(), source.quantity()];
+                count += 1;
+            }
+            (levels, count)
+        }
+        let (bids, bid_count) = levels(event.bids());
+        let (asks, ask_count) = levels(event.asks());
+        BookEvent {
+            venue: name(event.venue()),
+            symbol: name(event.symbol()),
+            last_update_id: event.last_update_id(),
+            local_time: event.local_time(),
+            bid_count,
+            ask_count,
+            bids,
+            asks,
+        }
+    }
+
+    /// 编码为带长度前缀的 FlatBuffers 缓冲区
+    pub fn to_flatbuffers<'a>(&self, builder: &'a mut FlatBufferBuilder<'static>) -> &'a [u8] {
+        fn levels(levels: &[[f64; 2]], count: u32) -> [fb::Level; SHM_LEVELS] {
+            let mut result = [fb::Level::default(); SHM_LEVELS];
+            for (level, [price, quantity]) in result.iter_mut().zip(&levels[..(count as usize).min(SHM_LEVELS)]) {
+                *level = fb::Level::new(*price, *quantity);
+            }
+            result
+        }
+        builder.reset();
+        let venue = builder.create_string(self.venue());
+        let symbol = builder.create_string(self.symbol());
+        let bid_count = (self.bid_count as usize).min(SHM_LEVELS);
+        let ask_count = (self.ask_count as usize).min(SHM_LEVELS);
+        let bids = builder.create_vector(&levels(&self.bids, self.bid_count)[..bid_count]);
+        let asks = builder.create_vector(&levels(&self.asks, self.ask_count)[..ask_count]);
+        let event = fb::BookEvent::create(builder, &fb::BookEventArgs {
+            venue: Some(venue),
+            symbol: Some(symbol),
+            last_update_id: self.last_update_id,
+            local_time: self.local_time,
+            bids: Some(bids),
+            asks: Some(asks),
+        });
+        fb::finish_size_prefixed_book_event_buffer(builder, event);
+        builder.finished_data()
+    }
+}
+
+fn trim_name(bytes: &[u8]) -> &str {
+    let len = bytes.iter().position(|byte| *byte == 0).unwrap_or(bytes.len());
+    std::str::from_utf8(&bytes[..len]).unwrap_or_default()
+}
+
+/// 映射到共享内存的环形缓冲区
+///
+/// 文件布局（小端）：
+///
+/// * 文件头 128 字节：`magic u64`、`槽位大小 u32`、`每侧档位数 u32`、`槽位数 u64`、`格式 u32`（0 为 [`BookEvent`]，
+///   1 为 FlatBuffers），偏移 64 处为写入位置 `u64`（已写入的事件数）
+/// * 之后为槽位，第 n 个事件写入槽位 `n % 槽位数`：`序号 u64` 后接 [`BookEvent`] 或带 4 字节长度前缀的
+///   FlatBuffers 缓冲区
+///
+/// 槽位序号为顺序锁：写入期间为 `2n + 1`，写完为 `2n + 2`。读取方复制事件前后各读一次序号，
+/// 两次都等于 `2n + 2` 时事件完整，否则已被覆盖
+#[derive(Debug)]
+struct Ring {
+    map: Map,
+    slots: u64,
+    format: ShmFormat,
+}
+
+/// 写入方可写映射，读取方只读映射
+#[derive(Debug)]
+enum Map {
+    Write(MmapMut),
+    Read(Mmap),
+}
+
+impl Ring {
+    fn base(&self) -> *const u8 {
+        match &self.map {
+            Map::Write(map) => map.as_ptr(),
+            Map::Read(map) => map.as_ptr(),
+        }
+    }
+
+    fn write_index(&self) -> &AtomicU64 {
+        // 映射按页对齐，偏移 64 满足 8 字节对齐
+        unsafe { &*(self.base().add(WRITE_INDEX_OFFSET) as *const AtomicU64) }
+    }
+
+    /// 槽位的序号和事件部分，只有写入方通过事件指针写入
+    fn slot(&self, sequence: u64) -> (&AtomicU64, *mut u8) {
+        let offset = HEADER_SIZE + (sequence % self.slots) as usize * self.format.slot_size();
+        unsafe {
+            let base = self.base().add(offset) as *mut u8;
+            (&*(base as *const AtomicU64), base.add(size_of::<u64>()))
+        }
+    }
+}
+
+/// 共享内存环形缓冲区记录器，单写多读
+///
+/// 每次订单薄更新后在行情线程中直接写入一个定长的 [`BookEvent`]，不经过序列化和套接字，同一主机上的
+/// 策略进程用 [`ShmReader`] 读取。缓冲区写满后覆盖最早的事件，读取过慢的一方跳过被覆盖的事件。
+/// 文件建议放在 `/dev/shm` 下，记录器退出后保留，读取方可以读完剩余事件
+pub struct ShmRecorder {
+    ring: Ring,
+    next: u64,
+    builder: FlatBufferBuilder<'static>,
+    /// 编码后超过槽位容量、没有写入的事件数
+    dropped: u64,
+}
+
+impl ShmRecorder {
+    /// 创建或覆盖缓冲区文件
+    ///
+    /// 先在同一目录写好临时文件再改名替换，已经打开旧文件的读取方继续映射旧文件，
+    /// 不会因为文件被截断而在访问映射时收到 SIGBUS
+    ///
+    /// # 参数
+    ///
+    /// * `path` - 文件路径，例如 `/dev/shm/order_book`
+    /// * `slots` - 槽位数，例如 [`DEFAULT_SLOTS`]
+    /// * `format` - 事件的编码
+    pub fn new(path: impl AsRef<Path>, slots: usize, format: ShmFormat) -> Result<Self, Box<dyn Error>> {
+        let slots = slots.max(1);
+        let path = path.as_ref();
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(".tmp");
+        let file = OpenOptions::new().read(true).write(true).create(true).truncate(true).open(&temp)?;
+        file.set_len((HEADER_SIZE + slots * format.slot_size()) as u64)?;
+        let mut map = unsafe { MmapMut::map_mut(&file)? };
+        map[8..12].copy_from_slice(&(format.slot_size() as u32).to_le_bytes());
+        map[12..16].copy_from_slice(&(SHM_LEVELS as u32).to_le_bytes());
+        map[16..24].copy_from_slice(&(slots as u64).to_le_bytes());
+        map[24..28].copy_from_slice(&format.code().to_le_bytes());
+        // 最后写入标识，读取方看到标识时文件头已完整
+        fence(Ordering::Release);
+        map[..8].copy_from_slice(&MAGIC.to_le_bytes());
+        fs::rename(&temp, path)?;
+        Ok(ShmRecorder {
+            ring: Ring { map: Map::Write(map), slots: slots as u64, format },
+            next: 0,
+            builder: FlatBufferBuilder::with_capacity(FLATBUFFERS_CAPACITY),
+            dropped: 0,
+        })
+    }
+
+    /// 写入一个事件
+    ///
+    /// FlatBuffers 编码结果超过槽位容量时返回错误，不写入也不占用序号
+    pub fn publish(&mut self, event: &BookEvent) -> Result<(), Box<dyn Error>> {
+        let data = match self.ring.format {
+            ShmFormat::Raw => None,
+            ShmFormat::FlatBuffers => {
+                let data = event.to_flatbuffers(&mut self.builder);
+                if data.len() > FLATBUFFERS_CAPACITY {
+                    return Err(format!("FlatBuffers 事件 {} 字节，超过槽位容量 {} 字节", data.len(), FLATBUFFERS_CAPACITY).into());
+                }
+                Some(data)
+            }
+        };
+        let sequence = self.next;
+        let (stamp, slot) = self.ring.slot(sequence);
+        stamp.store(2 * sequence + 1, Ordering::Relaxed);
+        fence(Ordering::Release);
+        match data {
+            None => unsafe { ptr::write_volatile(slot as *mut BookEvent, *event) },
+            Some(data) => unsafe { ptr::copy_nonoverlapping(data.as_ptr(), slot, data.len()) },
+        }
+        stamp.store(2 * sequence + 2, Ordering::Release);
+        self.next += 1;
+        self.ring.write_index().store(self.next, Ordering::Release);
+        Ok(())
+    }
+
+    /// 编码后超过槽位容量、没有写入的事件数
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+impl std::fmt::Debug for ShmRecorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ShmRecorder")
+            .field("ring", &self.ring)
+            .field("next", &self.next)
+            .field("dropped", &self.dropped)
+            .finish()
+    }
+}
+
+impl Recorder for ShmRecorder {
+    fn record(&mut self, _record: &Record) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    /// 单个事件写入失败时只计数丢弃，不停止记录
+    fn on_book(&mut self, venue: &str, symbol: &str, book: &OrderBook, local_time: u64) -> Result<(), Box<dyn Error>> {
+        if let Err(e) = self.publish(&BookEvent::from_book(venue, symbol, book, local_time)) {
+            self.dropped += 1;
+            warn!(venue, symbol, dropped = self.dropped, error = %e, "共享内存事件丢弃");
+        }
+        Ok(())
+    }
+}
+
+/// 共享内存环形缓冲区的读取方，每个读取方独立维护读取位置
+#[derive(Debug)]
+pub struct ShmReader {
+    ring: Ring,
+    next: u64,
+    /// 从槽位复制出的事件部分
+    buffer: Vec<u8>,
+}
+
+impl ShmReader {
+    /// 打开缓冲区文件，从最新写入的位置开始读取
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        let file = File::open(path)?;
+        let map = unsafe { Mmap::map(&file)? };
+        if map.len() < HEADER_SIZE || map[..8] != MAGIC.to_le_bytes() {
+            return Err("不是订单薄共享内存文件".into());
+        }
+        let field = |range: std::ops::Range<usize>| map[range].iter().rev().fold(0u64, |value, byte| value << 8 | *byte as u64);
+        let format = ShmFormat::from_code(field(24..28));
+        let Some(format) = format.filter(|format| field(8..12) == format.slot_size() as u64 && field(12..16) == SHM_LEVELS as u64) else {
+            return Err("共享内存文件的布局与当前版本不一致".into());
+        };
+        let slots = field(16..24);
+        if slots == 0 || map.len() < HEADER_SIZE + slots as usize * format.slot_size() {
+            return Err("共享内存文件不完整".into());
+        }
+        let ring = Ring { map: Map::Read(map), slots, format };
+        let next = ring.write_index().load(Ordering::Acquire);
+        Ok(ShmReader { ring, next, buffer: Vec::with_capacity(format.payload_size()) })
+    }
+
+    /// 缓冲区中事件的编码
+    pub fn format(&self) -> ShmFormat {
+        self.ring.format
+    }
+
+    /// 读取下一个事件，不阻塞；没有新事件时返回 None，两种编码都转换为 [`BookEvent`]
+    ///
+    /// 读取过慢、事件已被覆盖时返回跳过的事件数，之后从仍然有效的最早事件继续
+    pub fn poll(&mut self) -> Result<Option<BookEvent>, u64> {
+        match self.ring.format {
+            ShmFormat::Raw => Ok(self.poll_payload()?
+                .then(|| unsafe { ptr::read_unaligned(self.buffer.as_ptr() as *const BookEvent) })),
+            ShmFormat::FlatBuffers => Ok(self.poll_flatbuffers()?.map(|event| BookEvent::from_flatbuffers(&event))),
+        }
+    }
+
+    /// 读取下一个 FlatBuffers 事件，只复制字节，通过生成的访问方法直接读取字段，不需要解码
+    ///
+    /// 返回值与 [`poll`](Self::poll) 相同，内容校验失败时按跳过一个事件处理；缓冲区不是 FlatBuffers 格式时始终返回 None
+    pub fn poll_flatbuffers(&mut self) -> Result<Option<fb::BookEvent<'_>>, u64> {
+        if self.ring.format != ShmFormat::FlatBuffers || !self.poll_payload()? {
+            return Ok(None);
+        }
+        fb::size_prefixed_root_as_book_event(&self.buffer).map(Some).map_err(|_| 1)
+    }
+
+    /// 把下一个事件复制到 `buffer`，没有新事件时返回 false
+    fn poll_payload(&mut self) -> Result<bool, u64> {
+        let written = self.ring.write_index().load(Ordering::Acquire);
+        if self.next >= written {
+            return Ok(false);
+        }
+        if written - self.next > self.ring.slots {
+            let skipped = written - self.ring.slots - self.next;
+            self.next += skipped;
+            return Err(skipped);
+        }
+        let sequence = self.next;
+        let (stamp, slot) = self.ring.slot(sequence);
+        let expected = 2 * sequence + 2;
+        if stamp.load(Ordering::Acquire) != expected {
+            return self.overrun();
+        }
+        let len = match self.ring.format {
+            ShmFormat::Raw => size_of::<BookEvent>(),
+            // 长度前缀可能正在被覆盖，限制在槽位范围内，之后由序号检查丢弃
+            ShmFormat::FlatBuffers => {
+                let prefix = unsafe { ptr::read_unaligned(slot as *const u32) };
+                (u32::from_le(prefix) as usize + size_of::<u32>()).min(FLATBUFFERS_CAPACITY)
+            }
+        };
+        self.buffer.clear();
+        self.buffer.reserve(len);
+        unsafe {
+            ptr::copy_nonoverlapping(slot as *const u8, self.buffer.as_mut_ptr(), len);
+            self.buffer.set_len(len);
+        }
+        fence(Ordering::Acquire);
+        if stamp.load(Ordering::Relaxed) != expected {
+            return self.overrun();
+        }
+        self.next += 1;
+        Ok(true)
+    }
+
+    /// 读取期间槽位被覆盖，跳到仍然有效的最早事件
+    fn overrun(&mut self) -> Result<bool, u64> {
+        let written = self.ring.write_index().load(Ordering::Acquire);
+        // 为正在写入的槽位留出余量
+        let oldest = written.saturating_sub(self.ring.slots - 1).max(self.next + 1);
+        let skipped = oldest - self.next;
+        self.next = oldest;
+        Err(skipped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("order_book_shm_{}_{}", std::process::id(), name))
+    }
+
+    fn event(update_id: u64) -> BookEvent {
+        let book = OrderBook::from_levels(update_id, &[(dec!(100), dec!(1))], &[(dec!(101), dec!(2))]);
+        BookEvent::from_book("binance", "BTCUSDT", &book, update_id)
+    }
+
+    fn poll_id(reader: &mut ShmReader) -> Result<Option<u64>, u64> {
+        reader.poll().map(|event| event.map(|event| event.last_update_id))
+    }
+
+    #[test]
+    fn ring_wraps_and_skips_overwritten_events() {
+        let path = temp_path("wrap");
+        let mut recorder = ShmRecorder::new(&path, 4, ShmFormat::Raw).unwrap();
+        let mut reader = ShmReader::open(&path).unwrap();
+        for id in 0..3 {
+            recorder.publish(&event(id)).unwrap();
+        }
+        for id in 0..3 {
+            assert_eq!(poll_id(&mut reader), Ok(Some(id)));
+        }
+        assert_eq!(poll_id(&mut reader), Ok(None));
+
+        // 写满一圈以上，事件 3、4 已被覆盖
+        for id in 3..9 {
+            recorder.publish(&event(id)).unwrap();
+        }
+        assert_eq!(poll_id(&mut reader), Err(2));
+        for id in 5..9 {
+            assert_eq!(poll_id(&mut reader), Ok(Some(id)));
+        }
+        assert_eq!(poll_id(&mut reader), Ok(None));
+
+        // 重新创建文件时旧读取方仍然映射旧文件
+        let _recreated = ShmRecorder::new(&path, 4, ShmFormat::Raw).unwrap();
+        assert_eq!(poll_id(&mut reader), Ok(None));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn torn_slot_is_skipped() {
+        let path = temp_path("torn");
+        let mut recorder = ShmRecorder::new(&path, 4, ShmFormat::FlatBuffers).unwrap();
+        let mut reader = ShmReader::open(&path).unwrap();
+        assert_eq!(reader.format(), ShmFormat::FlatBuffers);
+        recorder.publish(&event(0)).unwrap();
+        recorder.publish(&event(1)).unwrap();
+        assert_eq!(reader.poll(), Ok(Some(event(0))));
+
+        // 事件 1 的槽位正在被事件 5 覆盖
+        let (stamp, _) = recorder.ring.slot(1);
+        stamp.store(2 * 5 + 1, Ordering::Release);
+        assert_eq!(poll_id(&mut reader), Err(1));
+        assert_eq!(poll_id(&mut reader), Ok(None));
+
+        recorder.publish(&event(2)).unwrap();
+        assert_eq!(poll_id(&mut reader), Ok(Some(2)));
+        fs::remove_file(&path).unwrap();
+    }
+}
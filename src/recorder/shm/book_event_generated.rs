@@ -0,0 +1,424 @@
+// automatically generated by the FlatBuffers compiler, do not modify
+
+
+// @generated
+
+use core::mem;
+use core::cmp::Ordering;
+
+extern crate flatbuffers;
+use self::flatbuffers::{EndianScalar, Follow};
+
+#[allow(unused_imports, dead_code)]
+pub mod order_book {
+
+  use core::mem;
+  use core::cmp::Ordering;
+
+  extern crate flatbuffers;
+  use self::flatbuffers::{EndianScalar, Follow};
+#[allow(unused_imports, dead_code)]
+pub mod fb {
+
+  use core::mem;
+  use core::cmp::Ordering;
+
+  extern crate flatbuffers;
+  use self::flatbuffers::{EndianScalar, Follow};
+
+// struct Level, aligned to 8
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq)]
+pub struct Level(pub [u8; 16]);
+impl Default for Level {
+  fn default() -> Self {
+    Self([0; 16])
+  }
+}
+impl core::fmt::Debug for Level {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    f.debug_struct("Level")
+      .field("price", &self.price())
+      .field("quantity", &self.quantity())
+      .finish()
+  }
+}
+
+impl flatbuffers::SimpleToVerifyInSlice for Level {}
+impl<'a> flatbuffers::Follow<'a> for Level {
+  type Inner = &'a Level;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    <&'a Level>::follow(buf, loc)
+  }
+}
+impl<'a> flatbuffers::Follow<'a> for &'a Level {
+  type Inner = &'a Level;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    flatbuffers::follow_cast_ref::<Level>(buf, loc)
+  }
+}
+impl<'b> flatbuffers::Push for Level {
+    type Output = Level;
+    #[inline]
+    unsafe fn push(&self, dst: &mut [u8], _written_len: usize) {
+        let src = ::core::slice::from_raw_parts(self as *const Level as *const u8, <Self as flatbuffers::Push>::size());
+        dst.copy_from_slice(src);
+    }
+    #[inline]
+    fn alignment() -> flatbuffers::PushAlignment {
+        flatbuffers::PushAlignment::new(8)
+    }
+}
+
+impl<'a> flatbuffers::Verifiable for Level {
+  #[inline]
+  fn run_verifier(
+    v: &mut flatbuffers::Verifier, pos: usize
+  ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+    use self::flatbuffers::Verifiable;
+    v.in_buffer::<Self>(pos)
+  }
+}
+
+impl<'a> Level {
+  #[allow(clippy::too_many_arguments)]
+  pub fn new(
+    price: f64,
+    quantity: f64,
+  ) -> Self {
+    let mut s = Self([0; 16]);
+    s.set_price(price);
+    s.set_quantity(quantity);
+    s
+  }
+
+  pub fn price(&self) -> f64 {
+    let mut mem = core::mem::MaybeUninit::<<f64 as EndianScalar>::Scalar>::uninit();
+    // Safety:
+    // Created from a valid Table for this object
+    // Which contains a valid value in this slot
+    EndianScalar::from_little_endian(unsafe {
+      core::ptr::copy_nonoverlapping(
+        self.0[0..].as_ptr(),
+        mem.as_mut_ptr() as *mut u8,
+        core::mem::size_of::<<f64 as EndianScalar>::Scalar>(),
+      );
+      mem.assume_init()
+    })
+  }
+
+  pub fn set_price(&mut self, x: f64) {
+    let x_le = x.to_little_endian();
+    // Safety:
+    // Created from a valid Table for this object
+    // Which contains a valid value in this slot
+    unsafe {
+      core::ptr::copy_nonoverlapping(
+        &x_le as *const _ as *const u8,
+        self.0[0..].as_mut_ptr(),
+        core::mem::size_of::<<f64 as EndianScalar>::Scalar>(),
+      );
+    }
+  }
+
+  pub fn quantity(&self) -> f64 {
+    let mut mem = core::mem::MaybeUninit::<<f64 as EndianScalar>::Scalar>::uninit();
+    // Safety:
+    // Created from a valid Table for this object
+    // Which contains a valid value in this slot
+    EndianScalar::from_little_endian(unsafe {
+      core::ptr::copy_nonoverlapping(
+        self.0[8..].as_ptr(),
+        mem.as_mut_ptr() as *mut u8,
+        core::mem::size_of::<<f64 as EndianScalar>::Scalar>(),
+      );
+      mem.assume_init()
+    })
+  }
+
+  pub fn set_quantity(&mut self, x: f64) {
+    let x_le = x.to_little_endian();
+    // Safety:
+    // Created from a valid Table for this object
+    // Which contains a valid value in this slot
+    unsafe {
+      core::ptr::copy_nonoverlapping(
+        &x_le as *const _ as *const u8,
+        self.0[8..].as_mut_ptr(),
+        core::mem::size_of::<<f64 as EndianScalar>::Scalar>(),
+      );
+    }
+  }
+
+}
+
+pub enum BookEventOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct BookEvent<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for BookEvent<'a> {
+  type Inner = BookEvent<'a>;
+  #[inline]
+  unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    Self { _tab: flatbuffers::Table::new(buf, loc) }
+  }
+}
+
+impl<'a> BookEvent<'a> {
+  pub const VT_VENUE: flatbuffers::VOffsetT = 4;
+  pub const VT_SYMBOL: flatbuffers::VOffsetT = 6;
+  pub const VT_LAST_UPDATE_ID: flatbuffers::VOffsetT = 8;
+  pub const VT_LOCAL_TIME: flatbuffers::VOffsetT = 10;
+  pub const VT_BIDS: flatbuffers::VOffsetT = 12;
+  pub const VT_ASKS: flatbuffers::VOffsetT = 14;
+
+  #[inline]
+  pub unsafe fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+    BookEvent { _tab: table }
+  }
+  #[allow(unused_mut)]
+  pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr, A: flatbuffers::Allocator + 'bldr>(
+    _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr, A>,
+    args: &'args BookEventArgs<'args>
+  ) -> flatbuffers::WIPOffset<BookEvent<'bldr>> {
+    let mut builder = BookEventBuilder::new(_fbb);
+    builder.add_local_time(args.local_time);
+    builder.add_last_update_id(args.last_update_id);
+    if let Some(x) = args.asks { builder.add_asks(x); }
+    if let Some(x) = args.bids { builder.add_bids(x); }
+    if let Some(x) = args.symbol { builder.add_symbol(x); }
+    if let Some(x) = args.venue { builder.add_venue(x); }
+    builder.finish()
+  }
+
+
+  #[inline]
+  pub fn venue(&self) -> Option<&'a str> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<&str>>(BookEvent::VT_VENUE, None)}
+  }
+  #[inline]
+  pub fn symbol(&self) -> Option<&'a str> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<&str>>(BookEvent::VT_SYMBOL, None)}
+  }
+  #[inline]
+  pub fn last_update_id(&self) -> u64 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u64>(BookEvent::VT_LAST_UPDATE_ID, Some(0)).unwrap()}
+  }
+  #[inline]
+  pub fn local_time(&self) -> u64 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u64>(BookEvent::VT_LOCAL_TIME, Some(0)).unwrap()}
+  }
+  #[inline]
+  pub fn bids(&self) -> Option<flatbuffers::Vector<'a, Level>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, Level>>>(BookEvent::VT_BIDS, None)}
+  }
+  #[inline]
+  pub fn asks(&self) -> Option<flatbuffers::Vector<'a, Level>> {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, Level>>>(BookEvent::VT_ASKS, None)}
+  }
+}
+
+impl flatbuffers::Verifiable for BookEvent<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut flatbuffers::Verifier, pos: usize
+  ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+    use self::flatbuffers::Verifiable;
+    v.visit_table(pos)?
+     .visit_field::<flatbuffers::ForwardsUOffset<&str>>("venue", Self::VT_VENUE, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<&str>>("symbol", Self::VT_SYMBOL, false)?
+     .visit_field::<u64>("last_update_id", Self::VT_LAST_UPDATE_ID, false)?
+     .visit_field::<u64>("local_time", Self::VT_LOCAL_TIME, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, Level>>>("bids", Self::VT_BIDS, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, Level>>>("asks", Self::VT_ASKS, false)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct BookEventArgs<'a> {
+    pub venue: Option<flatbuffers::WIPOffset<&'a str>>,
+    pub symbol: Option<flatbuffers::WIPOffset<&'a str>>,
+    pub last_update_id: u64,
+    pub local_time: u64,
+    pub bids: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, Level>>>,
+    pub asks: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, Level>>>,
+}
+impl<'a> Default for BookEventArgs<'a> {
+  #[inline]
+  fn default() -> Self {
+    BookEventArgs {
+      venue: None,
+      symbol: None,
+      last_update_id: 0,
+      local_time: 0,
+      bids: None,
+      asks: None,
+    }
+  }
+}
+
+pub struct BookEventBuilder<'a: 'b, 'b, A: flatbuffers::Allocator + 'a> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a, A>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b, A: flatbuffers::Allocator + 'a> BookEventBuilder<'a, 'b, A> {
+  #[inline]
+  pub fn add_venue(&mut self, venue: flatbuffers::WIPOffset<&'b  str>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(BookEvent::VT_VENUE, venue);
+  }
+  #[inline]
+  pub fn add_symbol(&mut self, symbol: flatbuffers::WIPOffset<&'b  str>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(BookEvent::VT_SYMBOL, symbol);
+  }
+  #[inline]
+  pub fn add_last_update_id(&mut self, last_update_id: u64) {
+    self.fbb_.push_slot::<u64>(BookEvent::VT_LAST_UPDATE_ID, last_update_id, 0);
+  }
+  #[inline]
+  pub fn add_local_time(&mut self, local_time: u64) {
+    self.fbb_.push_slot::<u64>(BookEvent::VT_LOCAL_TIME, local_time, 0);
+  }
+  #[inline]
+  pub fn add_bids(&mut self, bids: flatbuffers::WIPOffset<flatbuffers::Vector<'b , Level>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(BookEvent::VT_BIDS, bids);
+  }
+  #[inline]
+  pub fn add_asks(&mut self, asks: flatbuffers::WIPOffset<flatbuffers::Vector<'b , Level>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(BookEvent::VT_ASKS, asks);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a, A>) -> BookEventBuilder<'a, 'b, A> {
+    let start = _fbb.start_table();
+    BookEventBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<BookEvent<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl core::fmt::Debug for BookEvent<'_> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    let mut ds = f.debug_struct("BookEvent");
+      ds.field("venue", &self.venue());
+      ds.field("symbol", &self.symbol());
+      ds.field("last_update_id", &self.last_update_id());
+      ds.field("local_time", &self.local_time());
+      ds.field("bids", &self.bids());
+      ds.field("asks", &self.asks());
+      ds.finish()
+  }
+}
+#[inline]
+/// Verifies that a buffer of bytes contains a `BookEvent`
+/// and returns it.
+/// Note that verification is still experimental and may not
+/// catch every error, or be maximally performant. For the
+/// previous, unchecked, behavior use
+/// `root_as_book_event_unchecked`.
+pub fn root_as_book_event(buf: &[u8]) -> Result<BookEvent, flatbuffers::InvalidFlatbuffer> {
+  flatbuffers::root::<BookEvent>(buf)
+}
+#[inline]
+/// Verifies that a buffer of bytes contains a size prefixed
+/// `BookEvent` and returns it.
+/// Note that verification is still experimental and may not
+/// catch every error, or be maximally performant. For the
+/// previous, unchecked, behavior use
+/// `size_prefixed_root_as_book_event_unchecked`.
+pub fn size_prefixed_root_as_book_event(buf: &[u8]) -> Result<BookEvent, flatbuffers::InvalidFlatbuffer> {
+  flatbuffers::size_prefixed_root::<BookEvent>(buf)
+}
+#[inline]
+/// Verifies, with the given options, that a buffer of bytes
+/// contains a `BookEvent` and returns it.
+/// Note that verification is still experimental and may not
+/// catch every error, or be maximally performant. For the
+/// previous, unchecked, behavior use
+/// `root_as_book_event_unchecked`.
+pub fn root_as_book_event_with_opts<'b, 'o>(
+  opts: &'o flatbuffers::VerifierOptions,
+  buf: &'b [u8],
+) -> Result<BookEvent<'b>, flatbuffers::InvalidFlatbuffer> {
+  flatbuffers::root_with_opts::<BookEvent<'b>>(opts, buf)
+}
+#[inline]
+/// Verifies, with the given verifier options, that a buffer of
+/// bytes contains a size prefixed `BookEvent` and returns
+/// it. Note that verification is still experimental and may not
+/// catch every error, or be maximally performant. For the
+/// previous, unchecked, behavior use
+/// `root_as_book_event_unchecked`.
+pub fn size_prefixed_root_as_book_event_with_opts<'b, 'o>(
+  opts: &'o flatbuffers::VerifierOptions,
+  buf: &'b [u8],
+) -> Result<BookEvent<'b>, flatbuffers::InvalidFlatbuffer> {
+  flatbuffers::size_prefixed_root_with_opts::<BookEvent<'b>>(opts, buf)
+}
+#[inline]
+/// Assumes, without verification, that a buffer of bytes contains a BookEvent and returns it.
+/// # Safety
+/// Callers must trust the given bytes do indeed contain a valid `BookEvent`.
+pub unsafe fn root_as_book_event_unchecked(buf: &[u8]) -> BookEvent {
+  flatbuffers::root_unchecked::<BookEvent>(buf)
+}
+#[inline]
+/// Assumes, without verification, that a buffer of bytes contains a size prefixed BookEvent and returns it.
+/// # Safety
+/// Callers must trust the given bytes do indeed contain a valid size prefixed `BookEvent`.
+pub unsafe fn size_prefixed_root_as_book_event_unchecked(buf: &[u8]) -> BookEvent {
+  flatbuffers::size_prefixed_root_unchecked::<BookEvent>(buf)
+}
+pub const BOOK_EVENT_IDENTIFIER: &str = "OBEV";
+
+#[inline]
+pub fn book_event_buffer_has_identifier(buf: &[u8]) -> bool {
+  flatbuffers::buffer_has_identifier(buf, BOOK_EVENT_IDENTIFIER, false)
+}
+
+#[inline]
+pub fn book_event_size_prefixed_buffer_has_identifier(buf: &[u8]) -> bool {
+  flatbuffers::buffer_has_identifier(buf, BOOK_EVENT_IDENTIFIER, true)
+}
+
+#[inline]
+pub fn finish_book_event_buffer<'a, 'b, A: flatbuffers::Allocator + 'a>(
+    fbb: &'b mut flatbuffers::FlatBufferBuilder<'a, A>,
+    root: flatbuffers::WIPOffset<BookEvent<'a>>) {
+  fbb.finish(root, Some(BOOK_EVENT_IDENTIFIER));
+}
+
+#[inline]
+pub fn finish_size_prefixed_book_event_buffer<'a, 'b, A: flatbuffers::Allocator + 'a>(fbb: &'b mut flatbuffers::FlatBufferBuilder<'a, A>, root: flatbuffers::WIPOffset<BookEvent<'a>>) {
+  fbb.finish_size_prefixed(root, Some(BOOK_EVENT_IDENTIFIER));
+}
+}  // pub mod fb
+}  // pub mod order_book
+
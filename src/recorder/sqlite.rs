@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::Path;
+use rusqlite::{params, Connection};
+use tracing::error;
+
+use crate::exchange::DepthKind;
+use crate::order_book::{OrderBook, Side};
+use crate::recorder::columns::{side_name, to_f64};
+use crate::recorder::{Record, Recorder};
+
+/// 事务内的行数达到该值时提交
+const COMMIT_ROWS: usize = 10_000;
+/// 事务开始后超过该时间（毫秒）提交
+const COMMIT_MS: u64 = 1_000;
+/// 指标中不平衡度使用的档位数
+const IMBALANCE_LEVELS: usize = 5;
+
+/// 建表和索引语句，可重复执行
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS deltas (
+    recv_time INTEGER NOT NULL,
+    exchange_time INTEGER NOT NULL,
+    venue TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    sequence INTEGER,
+    side TEXT NOT NULL,
+    price REAL NOT NULL,
+    quantity REAL NOT NULL
+);
+CREATE INDEX IF NOT EXISTS deltas_symbol_time ON deltas (venue, symbol, recv_time);
+
+CREATE TABLE IF NOT EXISTS snapshots (
+    time INTEGER NOT NULL,
+    venue TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    sequence INTEGER NOT NULL,
+    side TEXT NOT NULL,
+    level INTEGER NOT NULL,
+    price REAL NOT NULL,
+    quantity REAL NOT NULL
+);
+CREATE INDEX IF NOT EXISTS snapshots_symbol_time ON snapshots (venue, symbol, time);
+
+CREATE TABLE IF NOT EXISTS metrics (
+    time INTEGER NOT NULL,
+    venue TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    best_bid REAL,
+    best_ask REAL,
+    mid REAL,
+    spread REAL,
+    microprice REAL,
+    imbalance REAL
+);
+CREATE INDEX IF NOT EXISTS metrics_symbol_time ON metrics (venue, symbol, time);
+";
+
+/// SQLite 行情记录器
+///
+/// 深度变动按档位写入 `deltas` 表；按固定间隔把前 N 档写入 `snapshots` 表，
+/// 同时把最优价、中间价、价差、微价格和前5档不平衡度写入 `metrics` 表。
+/// 时间均为毫秒时间戳，三张表都按 (venue, symbol, 时间) 建索引，写入在事务中批量提交
+pub struct SqliteRecorder {
+    conn: Connection,
+    snapshot_interval_ms: u64,
+    snapshot_depth: usize,
+    /// 当前事务开始时间，没有进行中的事务时为 None
+    transaction_start: Option<u64>,
+    pending_rows: usize,
+    /// (交易所, 交易对) -> 上次快照时间
+    last_snapshot: HashMap<(String, String), u64>,
+}
+
+impl std::fmt::Debug for SqliteRecorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SqliteRecorder")
+            .field("path", &self.conn.path())
+            .field("snapshot_interval_ms", &self.snapshot_interval_ms)
+            .field("snapshot_depth", &self.snapshot_depth)
+            .field("pending_rows", &self.pending_rows)
+            .finish()
+    }
+}
+
+impl SqliteRecorder {
+    /// 打开或创建数据库并建表
+    ///
+    /// # 参数
+    ///
+    /// * `path` - 数据库文件
+    /// * `snapshot_interval_ms` - 快照和指标的记录间隔（毫秒）
+    /// * `snapshot_depth` - 快照档位数
+    pub fn open(path: impl AsRef<Path>, snapshot_interval_ms: u64, snapshot_depth: usize) -> Result<Self, Box<dyn Error>> {
+        let conn = Connection::open(path)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(SqliteRecorder {
+            conn,
+            snapshot_interval_ms,
+            snapshot_depth,
+            transaction_start: None,
+            pending_rows: 0,
+            last_snapshot: HashMap::new(),
+        })
+    }
+
+    /// 数据库连接，可用于查询已记录的数据
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// 没有进行中的事务时开始事务
+    fn begin(&mut self, local_time: u64) -> Result<(), Box<dyn Error>> {
+        if self.transaction_start.is_none() {
+            self.conn.execute_batch("BEGIN")?;
+            self.transaction_start = Some(local_time);
+        }
+        Ok(())
+    }
+
+    /// 行数或时间到达阈值时提交
+    fn maybe_commit(&mut self, local_time: u64) -> Result<(), Box<dyn Error>> {
+        if self.pending_rows >= COMMIT_ROWS || self.transaction_start.is_some_and(|start| local_time >= start + COMMIT_MS) {
+            self.commit()?;
+        }
+        Ok(())
+    }
+
+    fn commit(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.transaction_start.take().is_some() {
+            self.conn.execute_batch("COMMIT")?;
+            self.pending_rows = 0;
+        }
+        Ok(())
+    }
+}
+
+impl Recorder for SqliteRecorder {
+    fn record(&mut self, record: &Record) -> Result<(), Box<dyn Error>> {
+        let Record::Update { recv_time, venue, message } = record else {
+            return Ok(());
+        };
+        self.begin(*recv_time)?;
+        let kind = match message.kind {
+            DepthKind::Snapshot => "snapshot",
+            DepthKind::Delta => "delta",
+        };
+        let sequence = message.continuity.sequence().map(|sequence| sequence as i64);
+        let mut insert = self.conn.prepare_cached(
+            "INSERT INTO deltas (recv_time, exchange_time, venue, symbol, kind, sequence, side, price, quantity)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        )?;
+        let levels = message.bids.iter().map(|level| (Side::Bid, level))
+            .chain(message.asks.iter().map(|level| (Side::Ask, level)));
+        for (side, (price, quantity)) in levels {
+            insert.execute(params![
+                *recv_time as i64,
+                message.timestamp as i64,
+                venue,
+                message.symbol,
+                kind,
+                sequence,
+                side_name(side),
+                to_f64(*price),
+                to_f64(*quantity),
+            ])?;
+            self.pending_rows += 1;
+        }
+        drop(insert);
+        self.maybe_commit(*recv_time)
+    }
+
+    fn flush(&mut self) -> Result<(), Box<dyn Error>> {
+        self.commit()
+    }
+
+    fn on_book(&mut self, venue: &str, symbol: &str, book: &OrderBook, local_time: u64) -> Result<(), Box<dyn Error>> {
+        let key = (venue.to_string(), symbol.to_string());
+        if self.last_snapshot.get(&key).is_some_and(|last| local_time < last + self.snapshot_interval_ms) {
+            return Ok(());
+        }
+        self.last_snapshot.insert(key, local_time);
+        self.begin(local_time)?;
+
+        let mut insert = self.conn.prepare_cached(
+            "INSERT INTO snapshots (time, venue, symbol, sequence, side, level, price, quantity)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )?;
+        for side in [Side::Bid, Side::Ask] {
+            for (level, (price, quantity)) in book.top_levels(side, self.snapshot_depth).into_iter().enumerate() {
+                insert.execute(params![
+                    local_time as i64,
+                    venue,
+                    symbol,
+                    book.last_update_id as i64,
+                    side_name(side),
+                    level as i64,
+                    to_f64(price),
+                    to_f64(quantity),
+                ])?;
+                self.pending_rows += 1;
+            }
+        }
+        drop(insert);
+
+        self.conn.prepare_cached(
+            "INSERT INTO metrics (time, venue, symbol, best_bid, best_ask, mid, spread, microprice, imbalance)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        )?.execute(params![
+            local_time as i64,
+            venue,
+            symbol,
+            book.best_bid().map(|(price, _)| to_f64(price)),
+            book.best_ask().map(|(price, _)| to_f64(price)),
+            book.mid_price().map(to_f64),
+            book.spread().map(to_f64),
+            book.microprice().map(to_f64),
+            book.imbalance(IMBALANCE_LEVELS).map(to_f64),
+        ])?;
+        self.pending_rows += 1;
+        self.maybe_commit(local_time)
+    }
+}
+
+impl Drop for SqliteRecorder {
+    fn drop(&mut self) {
+        if let Err(e) = self.commit() {
+            error!(error = %e, "提交 SQLite 事务失败");
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use flate2::read::GzDecoder;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+use crate::binance::{is_partial_depth_stream, AggTradeEvent, DepthSnapshot, DepthUpdate, StreamMessage};
+use crate::exchange::{parse_rfc3339_millis, Continuity, DepthKind, DepthMessage};
+use crate::manager::BINANCE_VENUE;
+use crate::recorder::Record;
+use crate::trade::Trade;
+
+/// 读取 Tardis.dev 历史数据文件，`.gz` 结尾时先解压
+///
+/// 支持两种格式，按第一行自动识别：
+///
+/// * 标准化 CSV，表头以 `exchange,symbol,timestamp,local_timestamp` 开头，
+///   `incremental_book_L2` 转为深度更新，`trades` 转为成交；交易所名称使用 Tardis 的交易所 ID
+/// * 原始消息，每行为 `ISO 8601 接收时间 + 空格 + 币安组合流 JSON`，包括 Tardis 生成的
+///   `@depthSnapshot` 快照、`@depth` 增量更新和 `@trade`/`@aggTrade` 成交，没有接收时间时使用事件时间
+pub fn read_records(path: impl AsRef<Path>) -> Result<Vec<Record>, Box<dyn Error>> {
+    let path = path.as_ref();
+    let file = File::open(path)?;
+    let reader: Box<dyn Read> = if path.extension().is_some_and(|extension| extension == "gz") {
+        Box::new(GzDecoder::new(file))
+    } else {
+        Box::new(file)
+    };
+    let mut lines = BufReader::new(reader).lines();
+    let Some(first) = lines.next().transpose()? else {
+        return Ok(Vec::new());
+    };
+    if first.starts_with("exchange,") {
+        read_csv(&first, lines)
+    } else {
+        let mut records = Vec::new();
+        for line in std::iter::once(Ok(first)).chain(lines) {
+            let line = line?;
+            if !line.trim().is_empty() {
+                records.extend(parse_raw_line(&line)?);
+            }
+        }
+        Ok(records)
+    }
+}
+
+/// 标准化 CSV 中一行对应的数据
+#[derive(Debug)]
+enum CsvRow {
+    /// 深度变动，同一时间、同一类型的连续行合并为一条深度消息
+    Level {
+        is_snapshot: bool,
+        is_bid: bool,
+        price: Decimal,
+        amount: Decimal,
+    },
+    Trade(Trade),
+}
+
+/// 读取标准化 CSV，表头已在 `header` 中
+fn read_csv(header: &str, lines: impl Iterator<Item = std::io::Result<String>>) -> Result<Vec<Record>, Box<dyn Error>> {
+    let columns: Vec<&str> = header.split(',').collect();
+    let column = |name: &str| columns.iter().position(|column| *column == name)
+        .ok_or_else(|| format!("Tardis CSV 缺少列 {}", name));
+    let (exchange, symbol, timestamp, local_timestamp) = (column("exchange")?, column("symbol")?, column("timestamp")?, column("local_timestamp")?);
+    let (side, price, amount) = (column("side")?, column("price")?, column("amount")?);
+    // 有 is_snapshot 列的是深度数据，否则是成交
+    let is_snapshot = column("is_snapshot").ok();
+
+    let mut records = Vec::new();
+    // 正在合并的深度消息 (交易所, 接收时间)
+    let mut pending: Option<(String, u64, DepthMessage)> = None;
+    for line in lines {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').collect();
+        let field = |index: usize| fields.get(index).copied().ok_or_else(|| format!("Tardis CSV 行的列数不足: {}", line));
+        // 时间为微秒
+        let event_time = field(timestamp)?.parse::<u64>()? / 1000;
+        let recv_time = field(local_timestamp)?.parse::<u64>()? / 1000;
+        let venue = field(exchange)?;
+        let symbol = field(symbol)?;
+        let row = match is_snapshot {
+            Some(is_snapshot) => CsvRow::Level {
+                is_snapshot: field(is_snapshot)? == "true",
+                is_bid: field(side)? == "bid",
+                price: field(price)?.parse()?,
+                amount: field(amount)?.parse()?,
+            },
+            None => CsvRow::Trade(Trade {
+                price: field(price)?.parse()?,
+                quantity: field(amount)?.parse()?,
+                buyer_is_maker: field(side)? == "sell",
+                trade_time: event_time,
+            }),
+        };
+        match row {
+            CsvRow::Level { is_snapshot, is_bid, price, amount } => {
+                let kind = if is_snapshot { DepthKind::Snapshot } else { DepthKind::Delta };
+                let continues = pending.as_ref().is_some_and(|(pending_venue, pending_time, message)| {
+                    pending_venue == venue && *pending_time == recv_time && message.symbol == symbol && message.kind == kind
+                });
+                if !continues {
+                    records.extend(pending.take().map(|(venue, recv_time, message)| Record::Update { recv_time, venue, message }));
+                    pending = Some((venue.to_string(), recv_time, DepthMessage {
+                        symbol: symbol.to_string(),
+                        kind,
+                        bids: Vec::new(),
+                        asks: Vec::new(),
+                        continuity: Continuity::None,
+                        checksum: None,
+                        max_depth: None,
+                        timestamp: event_time,
+                    }));
+                }
+                if let Some((_, _, message)) = pending.as_mut() {
+                    if is_bid {
+                        message.bids.push((price, amount));
+                    } else {
+                        message.asks.push((price, amount));
+                    }
+                }
+            }
+            CsvRow::Trade(trade) => records.push(Record::trade(venue, symbol, &trade, recv_time)),
+        }
+    }
+    records.extend(pending.map(|(venue, recv_time, message)| Record::Update { recv_time, venue, message }));
+    Ok(records)
+}
+
+/// 币安 `@trade` 逐笔成交推送
+#[derive(Debug, Deserialize)]
+struct TradeEvent {
+    #[serde(rename = "p")]
+    price: Decimal,
+    #[serde(rename = "q")]
+    quantity: Decimal,
+    #[serde(rename = "m")]
+    buyer_is_maker: bool,
+    #[serde(rename = "T")]
+    trade_time: u64,
+}
+
+/// 解析一行原始消息，不认识的流返回空
+fn parse_raw_line(line: &str) -> Result<Option<Record>, Box<dyn Error>> {
+    let (recv_time, json) = match line.split_once(' ') {
+        Some((time, json)) if !time.starts_with('{') => (Some(parse_rfc3339_millis(time).ok_or_else(|| format!("无效的时间: {}", time))?), json),
+        _ => (None, line),
+    };
+    let message: StreamMessage = serde_json::from_str(json)?;
+    let Some((stream_symbol, stream)) = message.stream.split_once('@') else {
+        return Ok(None);
+    };
+    let symbol = stream_symbol.to_uppercase();
+    let data = message.data.get();
+    let record = if stream == "depthSnapshot" {
+        let snapshot: DepthSnapshot = serde_json::from_str(data)?;
+        let parse = |levels: &[[String; 2]]| {
+            levels.iter()
+                .map(|[price, quantity]| Ok((price.parse::<Decimal>()?, quantity.parse::<Decimal>()?)))
+                .collect::<Result<Vec<(Decimal, Decimal)>, Box<dyn Error>>>()
+        };
+        let time = recv_time.unwrap_or_default();
+        Record::Update {
+            recv_time: time,
+            venue: BINANCE_VENUE.to_string(),
+            message: DepthMessage {
+                symbol,
+                kind: DepthKind::Snapshot,
+                bids: parse(&snapshot.bids)?,
+                asks: parse(&snapshot.asks)?,
+                continuity: Continuity::Range { first: snapshot.lastUpdateId, last: snapshot.lastUpdateId },
+                checksum: None,
+                max_depth: None,
+                timestamp: time,
+            },
+        }
+    } else if stream.starts_with("depth") && !is_partial_depth_stream(&message.stream) {
+        let update: DepthUpdate = serde_json::from_str(data)?;
+        Record::update(BINANCE_VENUE, &update.to_depth_message()?, recv_time.unwrap_or(update.E))
+    } else if stream == "aggTrade" {
+        let event: AggTradeEvent = serde_json::from_str(data)?;
+        Record::trade(BINANCE_VENUE, &symbol, &Trade::from_agg_trade(&event)?, recv_time.unwrap_or(event.E))
+    } else if stream == "trade" {
+        let event: TradeEvent = serde_json::from_str(data)?;
+        let trade = Trade {
+            price: event.price,
+            quantity: event.quantity,
+            buyer_is_maker: event.buyer_is_maker,
+            trade_time: event.trade_time,
+        };
+        Record::trade(BINANCE_VENUE, &symbol, &trade, recv_time.unwrap_or(event.trade_time))
+    } else {
+        return Ok(None);
+    };
+    Ok(Some(record))
+}
@@ -0,0 +1,289 @@
+use std::error::Error;
+use std::path::Path;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::binance::Market;
+use crate::exchange::DepthKind;
+use crate::manager::BookManager;
+use crate::order_book::OrderBook;
+use crate::recorder::{read_file, Record};
+
+/// 进度输出间隔
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 回放速度
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplaySpeed {
+    /// 按记录时间间隔的倍数回放，1 为实时
+    Multiple(f64),
+    /// 不等待，尽快回放
+    Max,
+}
+
+impl ReplaySpeed {
+    /// 解析 `1x`、`10x`、`0.5x`、`realtime` 或 `max`
+    pub fn parse(spec: &str) -> Option<Self> {
+        match spec.to_ascii_lowercase().as_str() {
+            "max" => Some(ReplaySpeed::Max),
+            "realtime" => Some(ReplaySpeed::Multiple(1.0)),
+            spec => {
+                let multiple = spec.strip_suffix('x').unwrap_or(spec).parse::<f64>().ok()?;
+                (multiple.is_finite() && multiple > 0.0).then_some(ReplaySpeed::Multiple(multiple))
+            }
+        }
+    }
+}
+
+/// 回放控制命令
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplayCommand {
+    /// 暂停或继续
+    TogglePause,
+    /// 跳转到指定时间（毫秒）
+    SeekTo(u64),
+    /// 相对当前时间跳转（毫秒），负数向前
+    SeekBy(i64),
+    /// 修改速度
+    Speed(ReplaySpeed),
+    /// 结束回放
+    Quit,
+}
+
+impl ReplayCommand {
+    /// 解析交互命令
+    ///
+    /// * 空行或 `p` - 暂停/继续
+    /// * `s 时间戳` - 跳转到毫秒时间戳，`s +30` / `s -30` 相对跳转秒数
+    /// * `x 10x` - 修改速度
+    /// * `q` - 结束
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut parts = line.split_whitespace();
+        match (parts.next(), parts.next()) {
+            (None, _) | (Some("p"), None) => Some(ReplayCommand::TogglePause),
+            (Some("q"), None) => Some(ReplayCommand::Quit),
+            (Some("x"), Some(speed)) => ReplaySpeed::parse(speed).map(ReplayCommand::Speed),
+            (Some("s"), Some(time)) if time.starts_with(['+', '-']) => {
+                time.parse::<i64>().ok().map(|secs| ReplayCommand::SeekBy(secs * 1000))
+            }
+            (Some("s"), Some(time)) => time.parse::<u64>().ok().map(ReplayCommand::SeekTo),
+            _ => None,
+        }
+    }
+}
+
+/// 记录是否为 `venue` 的 `symbol` 的关键帧或完整快照，可以作为重建订单薄的起点
+pub fn is_book_start(record: &Record, venue: &str, symbol: &str) -> bool {
+    match record {
+        Record::Keyframe { venue: record_venue, message, .. } => record_venue == venue && message.symbol == symbol,
+        Record::Update { venue: record_venue, message, .. } => {
+            record_venue == venue && message.symbol == symbol && message.kind == DepthKind::Snapshot
+        }
+        Record::Raw { .. } | Record::Binary { .. } | Record::Trade { .. } => false,
+    }
+}
+
+/// 用按时间排序的记录重建 `time` 时刻的订单薄
+///
+/// 从不晚于该时刻的最后一个关键帧或快照开始，依次应用之后的增量更新，
+/// 与实时行情使用相同的序号检查；没有起点或重建时序号断开时返回 None
+pub fn book_at(records: &[Record], venue: &str, symbol: &str, time: u64) -> Option<OrderBook> {
+    let start = records.iter().rposition(|record| record.recv_time() <= time && is_book_start(record, venue, symbol))?;
+    let mut manager = BookManager::new(Market::Spot, &[]);
+    for record in &records[start..] {
+        let matches = match record {
+            Record::Update { venue: record_venue, message, .. } | Record::Keyframe { venue: record_venue, message, .. } => {
+                record_venue == venue && message.symbol == symbol
+            }
+            Record::Raw { .. } | Record::Binary { .. } | Record::Trade { .. } => false,
+        };
+        if !matches || record.recv_time() > time {
+            continue;
+        }
+        if let Err(e) = manager.replay_record(record.clone()) {
+            warn!(error = %e, "回放记录失败");
+        }
+    }
+    manager.venue_book(venue, symbol).cloned()
+}
+
+/// 回放输出
+#[derive(Debug, Clone)]
+pub enum ReplayEvent {
+    /// 下一条记录
+    Record(Record),
+    /// 向前跳转，之后从第一条记录重新开始，接收方应清空订单薄
+    Reset,
+}
+
+/// 记录回放引擎
+///
+/// 按接收时间顺序输出记录，支持实时、倍速和不等待三种速度，回放中可以暂停、
+/// 修改速度和跳转。向后跳转时快速输出中间的记录以保持订单薄状态正确；
+/// 向前跳转时先输出 [`ReplayEvent::Reset`]，再从头快速输出到目标时间
+#[derive(Debug)]
+pub struct Replayer {
+    records: Vec<Record>,
+    /// 下一条要输出的记录
+    position: usize,
+    speed: ReplaySpeed,
+    paused: bool,
+}
+
+impl Replayer {
+    /// 按接收时间排序记录，时间相同的保持原顺序
+    pub fn new(mut records: Vec<Record>, speed: ReplaySpeed) -> Self {
+        records.sort_by_key(|record| record.recv_time());
+        Replayer {
+            records,
+            position: 0,
+            speed,
+            paused: false,
+        }
+    }
+
+    /// 读取 NDJSON、压缩记录或 Tardis.dev 历史数据文件
+    pub fn open(path: impl AsRef<Path>, speed: ReplaySpeed) -> Result<Self, Box<dyn Error>> {
+        Ok(Replayer::new(read_file(path)?, speed))
+    }
+
+    /// 记录总数
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// 已输出的记录数
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// 第一条和最后一条记录的接收时间
+    pub fn time_range(&self) -> Option<(u64, u64)> {
+        Some((self.records.first()?.recv_time(), self.records.last()?.recv_time()))
+    }
+
+    /// 当前回放时间，即最近输出的记录的接收时间，尚未输出时为第一条记录的时间
+    pub fn current_time(&self) -> u64 {
+        let index = self.position.saturating_sub(1);
+        self.records.get(index).map_or(0, |record| record.recv_time())
+    }
+
+    /// 跳转到 `time`，输出之间的记录，`emit` 返回 false 时停止
+    pub fn seek(&mut self, time: u64, emit: &mut impl FnMut(ReplayEvent) -> bool) -> bool {
+        if self.position > 0 && time < self.current_time() {
+            if !emit(ReplayEvent::Reset) {
+                return false;
+            }
+            self.position = 0;
+        }
+        while let Some(record) = self.records.get(self.position)
+            && record.recv_time() < time
+        {
+            if !emit(ReplayEvent::Record(record.clone())) {
+                return false;
+            }
+            self.position += 1;
+        }
+        true
+    }
+
+    /// 回放剩余的记录直到结束、收到 Quit 或 `emit` 返回 false
+    pub fn run(&mut self, commands: &Receiver<ReplayCommand>, mut emit: impl FnMut(ReplayEvent) -> bool) {
+        // (墙上时间, 回放时间) 锚点，速度、暂停或位置变化后重新设置
+        let mut anchor: Option<(Instant, u64)> = None;
+        let mut commands_open = true;
+        let mut last_progress = Instant::now();
+        while let Some(next_time) = self.records.get(self.position).map(|record| record.recv_time()) {
+            let command = if self.paused && commands_open {
+                commands.recv().ok()
+            } else {
+                let wait = match (self.speed, anchor) {
+                    (ReplaySpeed::Max, _) | (_, None) => Duration::ZERO,
+                    (ReplaySpeed::Multiple(multiple), Some((wall, time))) => {
+                        let elapsed = Duration::from_secs_f64(next_time.saturating_sub(time) as f64 / 1000.0 / multiple);
+                        (wall + elapsed).saturating_duration_since(Instant::now())
+                    }
+                };
+                match (commands_open, wait.is_zero()) {
+                    (true, true) => match commands.try_recv() {
+                        Ok(command) => Some(command),
+                        Err(TryRecvError::Empty) => None,
+                        Err(TryRecvError::Disconnected) => {
+                            commands_open = false;
+                            None
+                        }
+                    },
+                    (true, false) => match commands.recv_timeout(wait) {
+                        Ok(command) => Some(command),
+                        Err(RecvTimeoutError::Timeout) => None,
+                        Err(RecvTimeoutError::Disconnected) => {
+                            commands_open = false;
+                            thread::sleep(wait);
+                            None
+                        }
+                    },
+                    (false, _) => {
+                        thread::sleep(wait);
+                        None
+                    }
+                }
+            };
+            match command {
+                Some(ReplayCommand::TogglePause) => {
+                    self.paused = !self.paused;
+                    anchor = None;
+                    info!(position = self.position, total = self.records.len(), time = self.current_time(),
+                          "回放{}", if self.paused { "暂停" } else { "继续" });
+                    continue;
+                }
+                Some(ReplayCommand::SeekTo(time)) => {
+                    if !self.seek(time, &mut emit) {
+                        return;
+                    }
+                    anchor = None;
+                    info!(time, position = self.position, total = self.records.len(), "跳转");
+                    continue;
+                }
+                Some(ReplayCommand::SeekBy(offset)) => {
+                    let time = self.current_time().saturating_add_signed(offset);
+                    if !self.seek(time, &mut emit) {
+                        return;
+                    }
+                    anchor = None;
+                    info!(time, position = self.position, total = self.records.len(), "跳转");
+                    continue;
+                }
+                Some(ReplayCommand::Speed(speed)) => {
+                    self.speed = speed;
+                    anchor = None;
+                    info!(speed = ?speed, "调整回放速度");
+                    continue;
+                }
+                Some(ReplayCommand::Quit) => return,
+                None => {}
+            }
+            // 暂停且命令通道已关闭时无法继续，直接结束
+            if self.paused {
+                return;
+            }
+            if anchor.is_none() {
+                anchor = Some((Instant::now(), next_time));
+            }
+            if !emit(ReplayEvent::Record(self.records[self.position].clone())) {
+                return;
+            }
+            self.position += 1;
+            if last_progress.elapsed() >= PROGRESS_INTERVAL {
+                info!(position = self.position, total = self.records.len(), time = self.current_time(), "回放进度");
+                last_progress = Instant::now();
+            }
+        }
+        info!(total = self.records.len(), "回放结束");
+    }
+}
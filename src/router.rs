@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use rust_decimal::Decimal;
+
+use crate::consolidated::ConsolidatedBook;
+use crate::fees::FeeSchedule;
+use crate::order_book::Side;
+
+/// 路由计划中的一笔成交
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteFill {
+    /// 交易所名称
+    pub venue: String,
+    /// 成交价格
+    pub price: Decimal,
+    /// 成交数量
+    pub quantity: Decimal,
+    /// 手续费（计价资产）
+    pub fee: Decimal,
+}
+
+/// 模拟拆单的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoutePlan {
+    /// 吃的盘口方向，买入为 Ask，卖出为 Bid
+    pub side: Side,
+    /// 目标数量
+    pub requested: Decimal,
+    /// 按成本从优到劣排列的成交
+    pub fills: Vec<RouteFill>,
+}
+
+impl RoutePlan {
+    /// 已成交数量
+    pub fn filled(&self) -> Decimal {
+        self.fills.iter().map(|fill| fill.quantity).sum()
+    }
+
+    /// 深度不足而未成交的数量
+    pub fn unfilled(&self) -> Decimal {
+        self.requested - self.filled()
+    }
+
+    /// 成交金额（不含手续费）
+    pub fn notional(&self) -> Decimal {
+        self.fills.iter().map(|fill| fill.price * fill.quantity).sum()
+    }
+
+    /// 总手续费
+    pub fn total_fee(&self) -> Decimal {
+        self.fills.iter().map(|fill| fill.fee).sum()
+    }
+
+    /// 成交均价（不含手续费）
+    pub fn average_price(&self) -> Option<Decimal> {
+        let filled = self.filled();
+        if filled.is_zero() {
+            return None;
+        }
+        Some(self.notional() / filled)
+    }
+
+    /// 含手续费的实际均价，买入加上手续费，卖出扣除手续费
+    pub fn effective_price(&self) -> Option<Decimal> {
+        let filled = self.filled();
+        if filled.is_zero() {
+            return None;
+        }
+        let cost = match self.side {
+            Side::Ask => self.notional() + self.total_fee(),
+            Side::Bid => self.notional() - self.total_fee(),
+        };
+        Some(cost / filled)
+    }
+
+    /// 各交易所的成交数量
+    pub fn venue_quantities(&self) -> HashMap<String, Decimal> {
+        let mut quantities = HashMap::new();
+        for fill in &self.fills {
+            *quantities.entry(fill.venue.clone()).or_insert(Decimal::ZERO) += fill.quantity;
+        }
+        quantities
+    }
+}
+
+/// 智能拆单模拟器
+///
+/// 把合并订单薄中每个交易所的每个档位按含手续费的实际价格排序，从最优开始逐档成交，
+/// 得到总成本最低的拆单方案
+#[derive(Debug, Clone)]
+pub struct OrderRouter {
+    /// 手续费表，使用吃单手续费
+    fees: FeeSchedule,
+}
+
+impl Default for OrderRouter {
+    fn default() -> Self {
+        OrderRouter::new()
+    }
+}
+
+impl OrderRouter {
+    /// 创建模拟器，使用默认手续费表
+    pub fn new() -> Self {
+        OrderRouter { fees: FeeSchedule::new() }
+    }
+
+    /// 设置手续费表
+    pub fn set_fee_schedule(&mut self, fees: FeeSchedule) {
+        self.fees = fees;
+    }
+
+    /// 模拟拆单
+    ///
+    /// # 参数
+    ///
+    /// * `book` - 合并订单薄
+    /// * `side` - 吃的盘口方向，买入为 Side::Ask，卖出为 Side::Bid
+    /// * `quantity` - 目标数量
+    pub fn route(&self, book: &ConsolidatedBook, side: Side, quantity: Decimal) -> RoutePlan {
+        // (含手续费价格, 交易所, 价格, 数量, 手续费率)
+        let mut candidates: Vec<(Decimal, String, Decimal, Decimal, Decimal)> = Vec::new();
+        for level in book.levels(side, usize::MAX) {
+            for venue in level.venues {
+                let fee_rate = self.fees.taker_rate(&venue.venue);
+                let effective = match side {
+                    Side::Ask => level.price * (Decimal::ONE + fee_rate),
+                    Side::Bid => level.price * (Decimal::ONE - fee_rate),
+                };
+                candidates.push((effective, venue.venue, level.price, venue.quantity, fee_rate));
+            }
+        }
+        match side {
+            Side::Ask => candidates.sort_by_key(|candidate| candidate.0),
+            Side::Bid => candidates.sort_by_key(|candidate| std::cmp::Reverse(candidate.0)),
+        }
+
+        let mut remaining = quantity;
+        let mut fills = Vec::new();
+        for (_, venue, price, level_quantity, fee_rate) in candidates {
+            if remaining <= Decimal::ZERO {
+                break;
+            }
+            let fill = level_quantity.min(remaining);
+            remaining -= fill;
+            fills.push(RouteFill {
+                venue,
+                price,
+                quantity: fill,
+                fee: price * fill * fee_rate,
+            });
+        }
+        RoutePlan { side, requested: quantity, fills }
+    }
+}
@@ -0,0 +1,454 @@
+//! SBE（Simple Binary Encoding）格式的行情消息，定义见 `schema/order_book_sbe.xml`
+//!
+//! 定长字段按固定偏移小端写入，读取方不需要解析即可按偏移取值，适合已经使用 FIX/SBE 工具链的低延迟下游。
+//! 每条消息为 8 字节消息头、定长块、重复组（档位），最后是变长的交易所和交易对名称
+
+use std::error::Error;
+use rust_decimal::Decimal;
+
+use crate::exchange::{Continuity, DepthKind, DepthMessage};
+use crate::order_book::OrderBook;
+use crate::recorder::Record;
+use crate::trade::Trade;
+
+/// 模式编号和版本，与 `schema/order_book_sbe.xml` 一致
+pub const SCHEMA_ID: u16 = 1;
+pub const SCHEMA_VERSION: u16 = 0;
+/// 消息模板编号
+pub const BOOK_UPDATE_TEMPLATE: u16 = 1;
+pub const TRADE_TEMPLATE: u16 = 2;
+pub const TOP_OF_BOOK_TEMPLATE: u16 = 3;
+
+const BOOK_UPDATE_BLOCK: u16 = 38;
+const TRADE_BLOCK: u16 = 35;
+const TOP_OF_BOOK_BLOCK: u16 = 52;
+/// 重复组中每个档位的大小：价格和数量各 9 字节
+const LEVEL_BLOCK: u16 = 18;
+/// 可缺省十进制数的空值
+const NULL_MANTISSA: i64 = i64::MIN;
+
+/// 解码后的消息
+#[derive(Debug, Clone)]
+pub enum SbeMessage {
+    /// 深度更新、快照或关键帧，校验和不编码
+    Record(Record),
+    /// 最优买卖价
+    TopOfBook {
+        venue: String,
+        symbol: String,
+        recv_time: u64,
+        last_update_id: u64,
+        bid: Option<(Decimal, Decimal)>,
+        ask: Option<(Decimal, Decimal)>,
+    },
+}
+
+/// 编码一条记录，原始消息帧没有 SBE 格式，返回 None
+pub fn encode_record(record: &Record) -> Result<Option<Vec<u8>>, Box<dyn Error>> {
+    let mut writer = Writer::default();
+    match record {
+        Record::Raw { .. } | Record::Binary { .. } => return Ok(None),
+        Record::Update { recv_time, venue, message } | Record::Keyframe { recv_time, venue, message } => {
+            let kind = match (record, message.kind) {
+                (Record::Keyframe { .. }, _) => 2,
+                (_, DepthKind::Snapshot) => 0,
+                (_, DepthKind::Delta) => 1,
+            };
+            let (continuity, first, last) = match message.continuity {
+                Continuity::None => (0, 0, 0),
+                Continuity::Prev { prev, sequence } => (1, prev, sequence),
+                Continuity::Range { first, last } => (2, first, last),
+                Continuity::Monotonic(timestamp) => (3, timestamp, 0),
+            };
+            writer.header(BOOK_UPDATE_BLOCK, BOOK_UPDATE_TEMPLATE);
+            writer.u64(*recv_time);
+            writer.u64(message.timestamp);
+            writer.u64(first);
+            writer.u64(last);
+            writer.u32(message.max_depth.unwrap_or_default() as u32);
+            writer.u8(kind);
+            writer.u8(continuity);
+            writer.levels(&message.bids)?;
+            writer.levels(&message.asks)?;
+            writer.string(venue)?;
+            writer.string(&message.symbol)?;
+        }
+        Record::Trade { recv_time, venue, symbol, trade } => {
+            writer.header(TRADE_BLOCK, TRADE_TEMPLATE);
+            writer.u64(*recv_time);
+            writer.u64(trade.trade_time);
+            writer.decimal(Some(trade.price))?;
+            writer.decimal(Some(trade.quantity))?;
+            writer.u8(trade.buyer_is_maker as u8);
+            writer.string(venue)?;
+            writer.string(symbol)?;
+        }
+    }
+    Ok(Some(writer.bytes))
+}
+
+/// 编码订单薄的最优买卖价
+pub fn encode_top_of_book(venue: &str, symbol: &str, book: &OrderBook, local_time: u64) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut writer = Writer::default();
+    writer.header(TOP_OF_BOOK_BLOCK, TOP_OF_BOOK_TEMPLATE);
+    writer.u64(local_time);
+    writer.u64(book.last_update_id);
+    for level in [book.best_bid(), book.best_ask()] {
+        writer.decimal(level.map(|(price, _)| price))?;
+        writer.decimal(level.map(|(_, quantity)| quantity))?;
+    }
+    writer.string(venue)?;
+    writer.string(symbol)?;
+    Ok(writer.bytes)
+}
+
+/// 解码一条消息，模板编号未知或内容不完整时返回错误
+pub fn decode(data: &[u8]) -> Result<SbeMessage, Box<dyn Error>> {
+    let mut reader = Reader { data, offset: 0 };
+    let block_length = reader.u16()? as usize;
+    let template = reader.u16()?;
+    let schema = reader.u16()?;
+    let _version = reader.u16()?;
+    if schema != SCHEMA_ID {
+        return Err(format!("未知的 SBE 模式: {}", schema).into());
+    }
+    // 较新版本可能在定长块末尾增加字段，按消息头中的长度跳过
+    let block = reader.take(block_length)?;
+    let mut fields = Reader { data: block, offset: 0 };
+    match template {
+        BOOK_UPDATE_TEMPLATE => {
+            let recv_time = fields.u64()?;
+            let timestamp = fields.u64()?;
+            let first = fields.u64()?;
+            let last = fields.u64()?;
+            let max_depth = fields.u32()?;
+            let kind = fields.u8()?;
+            let continuity = match fields.u8()? {
+                0 => Continuity::None,
+                1 => Continuity::Prev { prev: first, sequence: last },
+                2 => Continuity::Range { first, last },
+                3 => Continuity::Monotonic(first),
+                other => return Err(format!("未知的序号类型: {}", other).into()),
+            };
+            let bids = reader.levels()?;
+            let asks = reader.levels()?;
+            let venue = reader.string()?;
+            let symbol = reader.string()?;
+            let message = DepthMessage {
+                symbol,
+                kind: if kind == 1 { DepthKind::Delta } else { DepthKind::Snapshot },
+                bids,
+                asks,
+                continuity,
+                checksum: None,
+                max_depth: (max_depth > 0).then_some(max_depth as usize),
+                timestamp,
+            };
+            Ok(SbeMessage::Record(match kind {
+                0 | 1 => Record::Update { recv_time, venue, message },
+                2 => Record::Keyframe { recv_time, venue, message },
+                other => return Err(format!("未知的更新类型: {}", other).into()),
+            }))
+        }
+        TRADE_TEMPLATE => {
+            let recv_time = fields.u64()?;
+            let trade_time = fields.u64()?;
+            let price = fields.decimal()?.ok_or("成交价格为空")?;
+            let quantity = fields.decimal()?.ok_or("成交数量为空")?;
+            let buyer_is_maker = fields.u8()? != 0;
+            let venue = reader.string()?;
+            let symbol = reader.string()?;
+            Ok(SbeMessage::Record(Record::Trade {
+                recv_time,
+                venue,
+                symbol,
+                trade: Trade { price, quantity, buyer_is_maker, trade_time },
+            }))
+        }
+        TOP_OF_BOOK_TEMPLATE => {
+            let recv_time = fields.u64()?;
+            let last_update_id = fields.u64()?;
+            let mut level = || -> Result<Option<(Decimal, Decimal)>, Box<dyn Error>> {
+                Ok(fields.decimal()?.zip(fields.decimal()?))
+            };
+            let bid = level()?;
+            let ask = level()?;
+            Ok(SbeMessage::TopOfBook {
+                venue: reader.string()?,
+                symbol: reader.string()?,
+                recv_time,
+                last_update_id,
+                bid,
+                ask,
+            })
+        }
+        other => Err(format!("未知的 SBE 消息: {}", other).into()),
+    }
+}
+
+/// 按小端顺序写入字段
+#[derive(Default)]
+struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    fn header(&mut self, block_length: u16, template: u16) {
+        for value in [block_length, template, SCHEMA_ID, SCHEMA_VERSION] {
+            self.u16(value);
+        }
+    }
+
+    fn u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    fn u16(&mut self, value: u16) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    /// 十进制数写为 64 位尾数和 8 位指数，保留原有小数位数；尾数超出范围时去掉末尾的 0，仍超出时返回错误
+    fn decimal(&mut self, value: Option<Decimal>) -> Result<(), Box<dyn Error>> {
+        let (mantissa, exponent) = match value {
+            Some(value) => {
+                let value = if i64::try_from(value.mantissa()).is_ok() { value } else { value.normalize() };
+                let mantissa = i64::try_from(value.mantissa()).map_err(|_| format!("数值超出 SBE 范围: {}", value))?;
+                (mantissa, -(value.scale() as i8))
+            }
+            None => (NULL_MANTISSA, 0),
+        };
+        self.bytes.extend_from_slice(&mantissa.to_le_bytes());
+        self.bytes.push(exponent as u8);
+        Ok(())
+    }
+
+    fn levels(&mut self, levels: &[(Decimal, Decimal)]) -> Result<(), Box<dyn Error>> {
+        let count = u16::try_from(levels.len()).map_err(|_| format!("档位数超出 SBE 重复组上限: {}", levels.len()))?;
+        self.u16(LEVEL_BLOCK);
+        self.u16(count);
+        for (price, quantity) in levels {
+            self.decimal(Some(*price))?;
+            self.decimal(Some(*quantity))?;
+        }
+        Ok(())
+    }
+
+    fn string(&mut self, value: &str) -> Result<(), Box<dyn Error>> {
+        let len = u16::try_from(value.len()).map_err(|_| "字符串过长")?;
+        self.u16(len);
+        self.bytes.extend_from_slice(value.as_bytes());
+        Ok(())
+    }
+}
+
+/// 按小端顺序读取字段，越界时返回错误
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Box<dyn Error>> {
+        let bytes = self.data.get(self.offset..self.offset + len).ok_or("SBE 消息不完整")?;
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, Box<dyn Error>> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, Box<dyn Error>> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, Box<dyn Error>> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, Box<dyn Error>> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into()?))
+    }
+
+    fn decimal(&mut self) -> Result<Option<Decimal>, Box<dyn Error>> {
+        let mantissa = i64::from_le_bytes(self.take(8)?.try_into()?);
+        let exponent = self.u8()? as i8;
+        if mantissa == NULL_MANTISSA {
+            return Ok(None);
+        }
+        let value = if exponent <= 0 {
+            Decimal::try_from_i128_with_scale(mantissa as i128, exponent.unsigned_abs() as u32)?
+        } else {
+            10u64.checked_pow(exponent as u32)
+                .and_then(|scale| Decimal::from(mantissa).checked_mul(Decimal::from(scale)))
+                .ok_or("数值超出范围")?
+        };
+        Ok(Some(value))
+    }
+
+    fn levels(&mut self) -> Result<Vec<(Decimal, Decimal)>, Box<dyn Error>> {
+        let block_length = self.u16()? as usize;
+        let count = self.u16()? as usize;
+        let mut levels = Vec::with_capacity(count);
+        for _ in 0..count {
+            let mut entry = Reader { data: self.take(block_length)?, offset: 0 };
+            let price = entry.decimal()?.ok_or("档位价格为空")?;
+            let quantity = entry.decimal()?.ok_or("档位数量为空")?;
+            levels.push((price, quantity));
+        }
+        Ok(levels)
+    }
+
+    fn string(&mut self) -> Result<String, Box<dyn Error>> {
+        let len = self.u16()? as usize;
+        Ok(String::from_utf8(self.take(len)?.to_vec())?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    fn depth(kind: DepthKind, continuity: Continuity, bids: Vec<(Decimal, Decimal)>, asks: Vec<(Decimal, Decimal)>) -> DepthMessage {
+        DepthMessage {
+            symbol: "BTC-USDT".to_string(),
+            kind,
+            bids,
+            asks,
+            continuity,
+            checksum: None,
+            max_depth: None,
+            timestamp: 1_700_000_000_123,
+        }
+    }
+
+    fn round_trip(record: Record) {
+        let bytes = encode_record(&record).unwrap().unwrap();
+        match decode(&bytes).unwrap() {
+            SbeMessage::Record(decoded) => assert_eq!(decoded, record),
+            other => panic!("解码结果不是记录: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn depth_update_round_trip() {
+        let mut message = depth(
+            DepthKind::Delta,
+            Continuity::Range { first: 101, last: 105 },
+            vec![(dec!(65000.10), dec!(0.5)), (dec!(64999.9), dec!(0))],
+            vec![(dec!(65001), dec!(1.25000000))],
+        );
+        message.max_depth = Some(400);
+        round_trip(Record::Update { recv_time: 1_700_000_000_456, venue: "okx".to_string(), message: message.clone() });
+
+        for continuity in [Continuity::None, Continuity::Prev { prev: 7, sequence: 8 }, Continuity::Monotonic(1_700_000_000_000)] {
+            message.continuity = continuity;
+            round_trip(Record::Update { recv_time: 1, venue: "okx".to_string(), message: message.clone() });
+        }
+    }
+
+    #[test]
+    fn decimal_scale_preserved() {
+        let message = depth(DepthKind::Delta, Continuity::None, vec![(dec!(1.500), dec!(2.0))], Vec::new());
+        let bytes = encode_record(&Record::Update { recv_time: 1, venue: "binance".to_string(), message }).unwrap().unwrap();
+        let SbeMessage::Record(Record::Update { message, .. }) = decode(&bytes).unwrap() else {
+            panic!("解码结果不是深度更新");
+        };
+        assert_eq!(message.bids[0].0.to_string(), "1.500");
+        assert_eq!(message.bids[0].1.to_string(), "2.0");
+    }
+
+    #[test]
+    fn snapshot_and_keyframe_round_trip() {
+        let snapshot = depth(DepthKind::Snapshot, Continuity::Range { first: 42, last: 42 }, vec![(dec!(1), dec!(2))], vec![(dec!(3), dec!(4))]);
+        round_trip(Record::Update { recv_time: 2, venue: "binance".to_string(), message: snapshot.clone() });
+        round_trip(Record::Keyframe { recv_time: 3, venue: "binance".to_string(), message: snapshot });
+    }
+
+    #[test]
+    fn empty_levels_and_non_ascii_symbol() {
+        let mut message = depth(DepthKind::Snapshot, Continuity::Prev { prev: 0, sequence: 0 }, Vec::new(), Vec::new());
+        message.symbol = "比特币/泰达币".to_string();
+        round_trip(Record::Update { recv_time: 4, venue: "交易所".to_string(), message });
+    }
+
+    #[test]
+    fn trade_round_trip() {
+        for buyer_is_maker in [false, true] {
+            round_trip(Record::Trade {
+                recv_time: 5,
+                venue: "kraken".to_string(),
+                symbol: "XBT/USD".to_string(),
+                trade: Trade { price: dec!(64123.5), quantity: dec!(0.00012), buyer_is_maker, trade_time: 1_700_000_000_789 },
+            });
+        }
+    }
+
+    #[test]
+    fn raw_frames_not_encoded() {
+        let record = Record::Raw { recv_time: 1, venue: "binance".to_string(), frame: "{}".to_string() };
+        assert!(encode_record(&record).unwrap().is_none());
+    }
+
+    #[test]
+    fn top_of_book_round_trip() {
+        let book = OrderBook::from_levels(77, &[(dec!(99.5), dec!(3)), (dec!(99), dec!(1))], &[(dec!(100.25), dec!(2))]);
+        let bytes = encode_top_of_book("bybit", "БТК-USDT", &book, 9).unwrap();
+        let SbeMessage::TopOfBook { venue, symbol, recv_time, last_update_id, bid, ask } = decode(&bytes).unwrap() else {
+            panic!("解码结果不是最优价");
+        };
+        assert_eq!((venue.as_str(), symbol.as_str(), recv_time, last_update_id), ("bybit", "БТК-USDT", 9, 77));
+        assert_eq!(bid, Some((dec!(99.5), dec!(3))));
+        assert_eq!(ask, Some((dec!(100.25), dec!(2))));
+    }
+
+    #[test]
+    fn top_of_book_empty_sides() {
+        let book = OrderBook::from_levels(1, &[], &[(dec!(10), dec!(1))]);
+        let SbeMessage::TopOfBook { bid, ask, .. } = decode(&encode_top_of_book("a", "b", &book, 0).unwrap()).unwrap() else {
+            panic!("解码结果不是最优价");
+        };
+        assert_eq!(bid, None);
+        assert_eq!(ask, Some((dec!(10), dec!(1))));
+
+        let empty = OrderBook::from_levels(1, &[], &[]);
+        let SbeMessage::TopOfBook { bid, ask, .. } = decode(&encode_top_of_book("a", "b", &empty, 0).unwrap()).unwrap() else {
+            panic!("解码结果不是最优价");
+        };
+        assert_eq!((bid, ask), (None, None));
+    }
+
+    #[test]
+    fn truncated_buffers_rejected() {
+        let message = depth(DepthKind::Delta, Continuity::Range { first: 1, last: 2 }, vec![(dec!(1), dec!(1))], vec![(dec!(2), dec!(1))]);
+        let bytes = encode_record(&Record::Update { recv_time: 1, venue: "okx".to_string(), message }).unwrap().unwrap();
+        for len in 0..bytes.len() {
+            assert!(decode(&bytes[..len]).is_err(), "截断到 {} 字节仍能解码", len);
+        }
+        let book = OrderBook::from_levels(1, &[(dec!(1), dec!(1))], &[]);
+        let bytes = encode_top_of_book("okx", "BTC-USDT", &book, 1).unwrap();
+        assert!(decode(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn unknown_template_and_schema_rejected() {
+        let trade = Trade { price: dec!(1), quantity: dec!(1), buyer_is_maker: false, trade_time: 0 };
+        let mut bytes = encode_record(&Record::Trade { recv_time: 0, venue: "a".to_string(), symbol: "b".to_string(), trade }).unwrap().unwrap();
+        bytes[2..4].copy_from_slice(&99u16.to_le_bytes());
+        assert!(decode(&bytes).is_err());
+
+        bytes[2..4].copy_from_slice(&TRADE_TEMPLATE.to_le_bytes());
+        bytes[4..6].copy_from_slice(&(SCHEMA_ID + 1).to_le_bytes());
+        assert!(decode(&bytes).is_err());
+    }
+}
@@ -0,0 +1,31 @@
+use std::error::Error;
+use std::net::SocketAddr;
+use std::thread;
+use axum::response::Html;
+use axum::routing::get;
+use axum::Router;
+use tracing::error;
+
+/// 网页，其中的 `__WS_PORT__` 在启动时替换为 WebSocket 服务的端口
+const PAGE: &str = include_str!("dashboard.html");
+
+/// 在新线程中启动网页看板，监听失败时返回错误
+///
+/// `/` 返回一个单页网页，浏览器连接同一主机 `ws_port` 端口上的 WebSocket 服务（`--ws-server`），
+/// 显示选中订单薄的前若干档深度、最近 5 分钟的价差曲线和前若干档不平衡度。
+/// 页面参数 `book=交易所:交易对`、`rows=20`（深度档位数）、`levels=5`（不平衡度档位数）可省略
+pub fn spawn_dashboard_server(addr: SocketAddr, ws_port: u16) -> Result<(), Box<dyn Error>> {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()?;
+    let listener = runtime.block_on(tokio::net::TcpListener::bind(addr))?;
+    let page = PAGE.replace("__WS_PORT__", &ws_port.to_string());
+    let app = Router::new().route("/", get(move || std::future::ready(Html(page.clone()))));
+    thread::spawn(move || {
+        if let Err(e) = runtime.block_on(async { axum::serve(listener, app).await }) {
+            error!(error = %e, "网页看板服务退出");
+        }
+    });
+    Ok(())
+}
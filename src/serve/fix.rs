@@ -0,0 +1,229 @@
+use std::error::Error;
+use std::io::{BufWriter, Read};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+
+use crate::fix::{self, FixMessage, FixReader, FixWriter};
+use crate::serve::{BookHub, BookView, Subscription};
+
+/// 对方登录消息没有心跳间隔时使用的默认值
+const DEFAULT_HEARTBEAT: Duration = Duration::from_secs(30);
+/// 等待消息的超时，用于定期检查是否需要发送心跳
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 会话线程收到的事件
+#[derive(Debug)]
+enum SessionEvent {
+    /// 对方发来的消息
+    Inbound(FixMessage),
+    /// 广播的订单薄
+    Update(Arc<BookView>),
+    /// 落后于广播，需要重新发送快照
+    Lagged,
+    /// 连接断开或无法解析
+    Closed(String),
+}
+
+/// 一个行情请求中的一个交易对
+#[derive(Debug)]
+struct MdSubscription {
+    md_req_id: String,
+    subscription: Subscription,
+}
+
+/// 启动 FIX 4.4 行情服务（接受方），每个连接一个会话线程
+///
+/// 客户端登录后发送 MarketDataRequest (V)：SubscriptionRequestType 为 0 时只返回快照，为 1 时先返回
+/// MarketDataSnapshotFullRefresh (W)，之后以 MarketDataIncrementalRefresh (X) 推送变化的档位，为 2 时退订。
+/// 交易对可以用 SecurityExchange (207) 限定交易所，省略时订阅所有交易所的同名交易对；MarketDepth 为 0 时
+/// 使用 `--serve-depth` 档。不保存已发送的消息，ResendRequest 以 SequenceReset-Reset 回复，客户端收到后重新订阅即可
+pub fn spawn_fix_server(addr: SocketAddr, hub: Arc<BookHub>, comp_id: &str) -> Result<(), Box<dyn Error>> {
+    let listener = TcpListener::bind(addr)?;
+    let comp_id = comp_id.to_string();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let hub = hub.clone();
+                    let comp_id = comp_id.clone();
+                    thread::spawn(move || {
+                        let peer = stream.peer_addr().map(|addr| addr.to_string()).unwrap_or_default();
+                        if let Err(e) = serve_client(stream, &hub, &comp_id) {
+                            info!(peer, error = %e, "FIX 客户端断开");
+                        }
+                    });
+                }
+                Err(e) => warn!(error = %e, "接受 FIX 连接失败"),
+            }
+        }
+    });
+    Ok(())
+}
+
+/// 运行一个会话，直到对方登出或连接断开
+fn serve_client(stream: TcpStream, hub: &Arc<BookHub>, comp_id: &str) -> Result<(), Box<dyn Error>> {
+    let (events_tx, events_rx) = mpsc::channel();
+    spawn_reader(stream.try_clone()?, events_tx.clone());
+
+    let mut writer = FixWriter::new(BufWriter::new(stream), comp_id, "");
+    let mut heartbeat = None;
+    let mut subscriptions: Vec<MdSubscription> = Vec::new();
+    loop {
+        let event = match events_rx.recv_timeout(POLL_INTERVAL) {
+            Ok(event) => event,
+            Err(RecvTimeoutError::Timeout) => {
+                if let Some(interval) = heartbeat {
+                    writer.heartbeat_if_idle(interval)?;
+                }
+                continue;
+            }
+            Err(RecvTimeoutError::Disconnected) => return Ok(()),
+        };
+        match event {
+            SessionEvent::Inbound(message) if heartbeat.is_none() => {
+                // 登录之前只接受登录消息
+                if message.msg_type != fix::LOGON {
+                    return Err(format!("登录前收到消息 {}", message.msg_type).into());
+                }
+                let interval = message.get_u64(fix::HEART_BT_INT)
+                    .filter(|secs| *secs > 0)
+                    .map_or(DEFAULT_HEARTBEAT, Duration::from_secs);
+                writer.set_target(message.get(fix::SENDER_COMP_ID).unwrap_or_default());
+                writer.send(&fix::logon(interval))?;
+                heartbeat = Some(interval);
+                spawn_forwarder(hub, events_tx.clone());
+            }
+            SessionEvent::Inbound(message) => match message.msg_type.as_str() {
+                fix::TEST_REQUEST => writer.send(&fix::heartbeat_reply(&message))?,
+                fix::RESEND_REQUEST => {
+                    // 不重发历史行情，直接跳到下一个序号
+                    let mut reset = FixMessage::new(fix::SEQUENCE_RESET);
+                    reset.push(fix::GAP_FILL_FLAG, "N").push(fix::NEW_SEQ_NO, writer.next_seq() + 1);
+                    writer.send(&reset)?;
+                }
+                fix::LOGOUT => {
+                    writer.send(&FixMessage::new(fix::LOGOUT))?;
+                    return Ok(());
+                }
+                fix::MARKET_DATA_REQUEST => {
+                    for reply in market_data_request(&message, hub, &mut subscriptions) {
+                        writer.send(&reply)?;
+                    }
+                }
+                _ => {}
+            },
+            SessionEvent::Update(view) => {
+                for subscription in &mut subscriptions {
+                    let previous = subscription.subscription.sent(&view.venue, &view.symbol).cloned();
+                    let Some(update) = subscription.subscription.next(&view) else {
+                        continue;
+                    };
+                    let reply = match previous {
+                        Some(previous) if !update.snapshot => fix::incremental_refresh(&subscription.md_req_id, &update.book, &previous),
+                        _ => fix::snapshot_full_refresh(&subscription.md_req_id, &update.book),
+                    };
+                    writer.send(&reply)?;
+                }
+            }
+            SessionEvent::Lagged => {
+                for subscription in &mut subscriptions {
+                    subscription.subscription.reset();
+                    for update in subscription.subscription.snapshots(hub) {
+                        writer.send(&fix::snapshot_full_refresh(&subscription.md_req_id, &update.book))?;
+                    }
+                }
+            }
+            SessionEvent::Closed(reason) => return Err(reason.into()),
+        }
+    }
+}
+
+/// 处理行情请求，返回需要发送的快照或拒绝消息
+fn market_data_request(message: &FixMessage, hub: &BookHub, subscriptions: &mut Vec<MdSubscription>) -> Vec<FixMessage> {
+    let md_req_id = message.get(fix::MD_REQ_ID).unwrap_or_default().to_string();
+    let request_type = message.get(fix::SUBSCRIPTION_REQUEST_TYPE).unwrap_or(fix::SNAPSHOT);
+    if request_type == fix::UNSUBSCRIBE {
+        subscriptions.retain(|subscription| subscription.md_req_id != md_req_id);
+        return Vec::new();
+    }
+    let depth = match message.get_u64(fix::MARKET_DEPTH) {
+        None | Some(0) => hub.depth(),
+        Some(depth) => (depth as usize).min(hub.depth()),
+    };
+    let mut replies = Vec::new();
+    for group in message.groups(fix::SYMBOL) {
+        let value = |tag: u32| group.iter().find(|(t, _)| *t == tag).map(|(_, value)| value.as_str());
+        let (Some(symbol), venue) = (value(fix::SYMBOL), value(fix::SECURITY_EXCHANGE)) else {
+            continue;
+        };
+        let mut subscription = Subscription::new(venue, Some(symbol), depth);
+        let snapshots = subscription.snapshots(hub);
+        if snapshots.is_empty() {
+            replies.push(fix::market_data_reject(&md_req_id, 0, &format!("未知的交易对: {}", symbol)));
+            continue;
+        }
+        replies.extend(snapshots.iter().map(|update| fix::snapshot_full_refresh(&md_req_id, &update.book)));
+        if request_type == fix::SNAPSHOT_AND_UPDATES {
+            subscriptions.push(MdSubscription { md_req_id: md_req_id.clone(), subscription });
+        }
+    }
+    replies
+}
+
+/// 在新线程中读取并解析对方的消息
+fn spawn_reader(mut stream: TcpStream, events: Sender<SessionEvent>) {
+    thread::spawn(move || {
+        let mut reader = FixReader::default();
+        let mut buffer = [0u8; 4096];
+        loop {
+            let read = match stream.read(&mut buffer) {
+                Ok(0) => {
+                    let _ = events.send(SessionEvent::Closed("连接已关闭".to_string()));
+                    return;
+                }
+                Ok(read) => read,
+                Err(e) => {
+                    let _ = events.send(SessionEvent::Closed(e.to_string()));
+                    return;
+                }
+            };
+            reader.push(&buffer[..read]);
+            loop {
+                match reader.next_message() {
+                    Ok(Some(message)) => {
+                        if events.send(SessionEvent::Inbound(message)).is_err() {
+                            return;
+                        }
+                    }
+                    Ok(None) => break,
+                    Err(e) => {
+                        let _ = events.send(SessionEvent::Closed(e.to_string()));
+                        return;
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// 在新线程中把广播的订单薄转发给会话线程，会话结束后在下一次广播时退出
+fn spawn_forwarder(hub: &BookHub, events: Sender<SessionEvent>) {
+    let mut updates = hub.subscribe();
+    thread::spawn(move || {
+        loop {
+            let event = match updates.blocking_recv() {
+                Ok(view) => SessionEvent::Update(view),
+                Err(broadcast::error::RecvError::Lagged(_)) => SessionEvent::Lagged,
+                Err(broadcast::error::RecvError::Closed) => return,
+            };
+            if events.send(event).is_err() {
+                return;
+            }
+        }
+    });
+}
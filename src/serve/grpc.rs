@@ -0,0 +1,111 @@
+use std::error::Error;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::thread;
+use rust_decimal::Decimal;
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status};
+use tracing::error;
+
+use crate::proto::book_service_server::{BookService, BookServiceServer};
+use crate::proto::{Book, BookRequest, BookUpdate, Level, SubscribeRequest};
+use crate::serve::{BookHub, BookView, Subscription};
+
+/// 每个订阅的发送队列长度，客户端读取过慢时服务端等待
+const SUBSCRIBER_QUEUE: usize = 1024;
+
+/// gRPC 订单薄服务，定义见 `proto/order_book.proto`
+#[derive(Debug)]
+pub struct GrpcService {
+    hub: Arc<BookHub>,
+}
+
+impl GrpcService {
+    pub fn new(hub: Arc<BookHub>) -> Self {
+        GrpcService { hub }
+    }
+
+    /// 请求的档位数，0 或超过共享订单薄的档位数时使用后者
+    fn depth(&self, depth: u32) -> usize {
+        match depth as usize {
+            0 => self.hub.depth(),
+            depth => depth.min(self.hub.depth()),
+        }
+    }
+}
+
+/// 转为消息中的订单薄
+fn to_book(view: &BookView) -> Book {
+    let levels = |levels: &[(Decimal, Decimal)]| levels.iter().map(Level::from).collect();
+    Book {
+        venue: view.venue.clone(),
+        symbol: view.symbol.clone(),
+        last_update_id: view.last_update_id,
+        time: view.time,
+        bids: levels(&view.bids),
+        asks: levels(&view.asks),
+    }
+}
+
+#[tonic::async_trait]
+impl BookService for GrpcService {
+    type SubscribeStream = ReceiverStream<Result<BookUpdate, Status>>;
+
+    async fn subscribe(&self, request: Request<SubscribeRequest>) -> Result<Response<Self::SubscribeStream>, Status> {
+        let request = request.into_inner();
+        let depth = self.depth(request.depth);
+        // 先订阅再读取当前状态，不会漏掉两者之间的更新
+        let mut updates = self.hub.subscribe();
+        let (tx, rx) = mpsc::channel(SUBSCRIBER_QUEUE);
+        let hub = self.hub.clone();
+        tokio::spawn(async move {
+            let mut subscription = Subscription::new(Some(&request.venue), Some(&request.symbol), depth);
+            let mut pending = subscription.snapshots(&hub);
+            loop {
+                for update in pending.drain(..) {
+                    let update = BookUpdate { snapshot: update.snapshot, book: Some(to_book(&update.book)) };
+                    if tx.send(Ok(update)).await.is_err() {
+                        return;
+                    }
+                }
+                match updates.recv().await {
+                    Ok(view) => pending.extend(subscription.next(&view)),
+                    Err(broadcast::error::RecvError::Lagged(_)) => {
+                        subscription.reset();
+                        pending = subscription.snapshots(&hub);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return,
+                }
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn get_book(&self, request: Request<BookRequest>) -> Result<Response<Book>, Status> {
+        let request = request.into_inner();
+        let view = self.hub.book(&request.venue, &request.symbol)
+            .ok_or_else(|| Status::not_found(format!("没有 {} {} 的订单薄", request.venue, request.symbol)))?;
+        let top = view.top(self.depth(request.depth));
+        Ok(Response::new(to_book(&top)))
+    }
+}
+
+/// 在新线程中启动 gRPC 服务，监听失败时返回错误
+pub fn spawn_grpc_server(addr: SocketAddr, hub: Arc<BookHub>) -> Result<(), Box<dyn Error>> {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()?;
+    let listener = runtime.block_on(tokio::net::TcpListener::bind(addr))?;
+    thread::spawn(move || {
+        let incoming = tokio_stream::wrappers::TcpListenerStream::new(listener);
+        let served = runtime.block_on(tonic::transport::Server::builder()
+            .add_service(BookServiceServer::new(GrpcService::new(hub)))
+            .serve_with_incoming(incoming));
+        if let Err(e) = served {
+            error!(error = %e, "gRPC 服务退出");
+        }
+    });
+    Ok(())
+}
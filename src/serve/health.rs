@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use serde_json::json;
+
+/// 订单薄超过该时间没有更新即视为过期
+pub const DEFAULT_MAX_AGE: Duration = Duration::from_secs(30);
+
+/// 单个订单薄的同步状态
+#[derive(Debug, Clone, Copy)]
+struct BookStatus {
+    /// 本地订单薄是否存在（已由快照建立且没有因缺口丢弃）
+    synced: bool,
+    /// 最近一次更新的本地时间（毫秒）
+    updated: u64,
+}
+
+/// 单个订单薄的就绪状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookReadiness {
+    /// 还没有收到任何更新
+    Waiting,
+    /// 本地订单薄不存在，正在等待快照重新同步
+    Unsynced,
+    /// 超过最长间隔没有更新
+    Stale,
+    Ready,
+}
+
+impl BookReadiness {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BookReadiness::Waiting => "waiting",
+            BookReadiness::Unsynced => "unsynced",
+            BookReadiness::Stale => "stale",
+            BookReadiness::Ready => "ready",
+        }
+    }
+}
+
+/// 配置的订单薄是否都已同步且在持续更新，在主循环和 `/readyz` 之间共享
+///
+/// 只跟踪启动时登记的 (交易所, 交易对)，没有登记任何订单薄时总是就绪
+#[derive(Debug)]
+pub struct Readiness {
+    max_age_ms: u64,
+    books: Mutex<HashMap<(String, String), Option<BookStatus>>>,
+}
+
+impl Readiness {
+    /// `max_age` 为订单薄两次更新之间允许的最长间隔，例如 [`DEFAULT_MAX_AGE`]
+    pub fn new(max_age: Duration) -> Self {
+        Readiness {
+            max_age_ms: max_age.as_millis() as u64,
+            books: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 登记需要同步的订单薄
+    pub fn expect(&self, venue: &str, symbol: &str) {
+        self.lock().entry((venue.to_string(), symbol.to_string())).or_insert(None);
+    }
+
+    /// 订单薄处理了一条消息，`synced` 为本地订单薄是否存在；未登记的订单薄忽略
+    pub fn book_updated(&self, venue: &str, symbol: &str, synced: bool, local_time: u64) {
+        if let Some(status) = self.lock().get_mut(&(venue.to_string(), symbol.to_string())) {
+            *status = Some(BookStatus { synced, updated: local_time });
+        }
+    }
+
+    /// 本地订单薄因缺口或校验和不一致被丢弃
+    pub fn book_lost(&self, venue: &str, symbol: &str) {
+        if let Some(Some(status)) = self.lock().get_mut(&(venue.to_string(), symbol.to_string())) {
+            status.synced = false;
+        }
+    }
+
+    /// 各订单薄在本地时间 `now`（毫秒）的状态，按交易所和交易对排序
+    pub fn books(&self, now: u64) -> Vec<(String, String, BookReadiness)> {
+        let mut books: Vec<_> = self.lock().iter()
+            .map(|((venue, symbol), status)| {
+                let readiness = match status {
+                    None => BookReadiness::Waiting,
+                    Some(status) if !status.synced => BookReadiness::Unsynced,
+                    Some(status) if now.saturating_sub(status.updated) > self.max_age_ms => BookReadiness::Stale,
+                    Some(_) => BookReadiness::Ready,
+                };
+                (venue.clone(), symbol.clone(), readiness)
+            })
+            .collect();
+        books.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+        books
+    }
+
+    /// 所有登记的订单薄是否都已就绪，以及 JSON 格式的各订单薄状态
+    pub fn check(&self, now: u64) -> (bool, serde_json::Value) {
+        let books = self.books(now);
+        let ready = books.iter().all(|(_, _, readiness)| *readiness == BookReadiness::Ready);
+        let books: Vec<_> = books.iter()
+            .map(|(venue, symbol, readiness)| json!({ "venue": venue, "symbol": symbol, "status": readiness.name() }))
+            .collect();
+        (ready, json!({ "status": if ready { "ready" } else { "not ready" }, "books": books }))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<(String, String), Option<BookStatus>>> {
+        // 持锁的代码不会 panic，锁中毒时数据仍然可用
+        self.books.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -0,0 +1,105 @@
+use std::error::Error;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use axum::extract::Query;
+use axum::http::{header, StatusCode};
+use axum::routing::get;
+use axum::{Json, Router};
+use metrics_exporter_prometheus::PrometheusHandle;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::error;
+
+use crate::analytics::churn::ChurnMonitor;
+use crate::latency::{now_millis, FeedLatency};
+use crate::profile;
+use crate::serve::health::Readiness;
+
+/// Prometheus 文本格式的内容类型
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+/// 没有抓取时也定期整理直方图样本，避免内存增长
+const UPKEEP_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 按交易所和交易对过滤
+#[derive(Debug, Deserialize)]
+struct SymbolQuery {
+    venue: Option<String>,
+    symbol: Option<String>,
+}
+
+impl SymbolQuery {
+    fn matches(&self, venue: &str, symbol: &str) -> bool {
+        self.venue.as_deref().is_none_or(|v| v == venue) && self.symbol.as_deref().is_none_or(|s| s == symbol)
+    }
+}
+
+/// 延迟分位数列表
+fn latency(latency: &FeedLatency, query: SymbolQuery) -> Json<Value> {
+    let summaries = latency.summaries().iter()
+        .filter(|summary| query.matches(&summary.venue, &summary.symbol))
+        .map(|summary| summary.to_json())
+        .collect();
+    Json(Value::Array(summaries))
+}
+
+/// 更新频率和档位变化统计列表
+fn churn(churn: &ChurnMonitor, query: SymbolQuery) -> Json<Value> {
+    let summaries = churn.summaries().iter()
+        .filter(|summary| query.matches(&summary.venue, &summary.symbol))
+        .map(|summary| summary.to_json())
+        .collect();
+    Json(Value::Array(summaries))
+}
+
+/// 在新线程中启动指标服务，`GET /metrics` 返回 Prometheus 文本格式的运行指标，监听失败时返回错误
+///
+/// 指标见 [`crate::metrics`]，按交易所和交易对打标签；直方图在每次抓取时更新。
+/// `GET /latency?venue=&symbol=` 以 JSON 返回各交易对行情延迟的样本数、p50、p99 和最大值（毫秒），
+/// `GET /churn?venue=&symbol=` 返回最近区间和累计的每秒更新数、档位新增/删除/修改数和消息档位数。
+/// `GET /healthz` 在进程运行时总是返回 200；`GET /readyz` 在所有配置的订单薄都已同步且没有过期时返回 200，
+/// 否则返回 503，响应中列出各订单薄的状态。`GET /profile` 返回最近一个采样窗口内热路径各阶段（读取、解析、
+/// Decimal 转换、订单薄更新、输出）的次数和耗时，未开启 `--profile` 或第一个窗口尚未结束时返回 404
+pub fn spawn_metrics_server(
+    addr: SocketAddr,
+    handle: PrometheusHandle,
+    feed_latency: Arc<FeedLatency>,
+    churn_monitor: Arc<ChurnMonitor>,
+    readiness: Arc<Readiness>,
+) -> Result<(), Box<dyn Error>> {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()?;
+    let listener = runtime.block_on(tokio::net::TcpListener::bind(addr))?;
+    let upkeep = handle.clone();
+    runtime.spawn(async move {
+        loop {
+            tokio::time::sleep(UPKEEP_INTERVAL).await;
+            upkeep.run_upkeep();
+        }
+    });
+    let app = Router::new()
+        .route("/metrics", get(move || async move { ([(header::CONTENT_TYPE, CONTENT_TYPE)], handle.render()) }))
+        .route("/latency", get(move |Query(query): Query<SymbolQuery>| async move { latency(&feed_latency, query) }))
+        .route("/churn", get(move |Query(query): Query<SymbolQuery>| async move { churn(&churn_monitor, query) }))
+        .route("/profile", get(|| async {
+            match profile::report() {
+                Some(report) => (StatusCode::OK, Json(report.to_json())),
+                None => (StatusCode::NOT_FOUND, Json(json!({ "error": "没有采样数据，需要 --profile 开启" }))),
+            }
+        }))
+        .route("/healthz", get(|| async { Json(json!({ "status": "ok" })) }))
+        .route("/readyz", get(move || async move {
+            let (ready, body) = readiness.check(now_millis());
+            let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+            (status, Json(body))
+        }));
+    thread::spawn(move || {
+        if let Err(e) = runtime.block_on(async { axum::serve(listener, app).await }) {
+            error!(error = %e, "指标服务退出");
+        }
+    });
+    Ok(())
+}
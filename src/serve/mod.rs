@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+use rust_decimal::Decimal;
+use serde_json::json;
+use tokio::sync::broadcast;
+
+use crate::order_book::{OrderBook, Side};
+use crate::recorder::{Record, Recorder};
+
+pub mod dashboard;
+pub mod fix;
+pub mod grpc;
+pub mod health;
+pub mod metrics;
+pub mod redis;
+pub mod rest;
+#[cfg(unix)]
+pub mod uds;
+pub mod websocket;
+pub mod zmq;
+
+/// 默认每侧保存的档位数
+pub const DEFAULT_DEPTH: usize = 100;
+/// 更新广播队列长度，订阅方落后超过该数量时重新发送快照
+const BROADCAST_CAPACITY: usize = 4096;
+
+type Levels = Vec<(Decimal, Decimal)>;
+
+/// 服务端看到的订单薄，只包含前若干档
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookView {
+    pub venue: String,
+    pub symbol: String,
+    pub last_update_id: u64,
+    /// 本地更新时间（毫秒）
+    pub time: u64,
+    /// 买单，价格降序
+    pub bids: Levels,
+    /// 卖单，价格升序
+    pub asks: Levels,
+}
+
+impl BookView {
+    /// 由订单薄创建，每侧保留 `depth` 档
+    pub fn from_book(venue: &str, symbol: &str, book: &OrderBook, depth: usize, time: u64) -> Self {
+        BookView {
+            venue: venue.to_string(),
+            symbol: symbol.to_string(),
+            last_update_id: book.last_update_id,
+            time,
+            bids: book.top_levels(Side::Bid, depth),
+            asks: book.top_levels(Side::Ask, depth),
+        }
+    }
+
+    /// 每侧只保留 `depth` 档的副本
+    pub fn top(&self, depth: usize) -> Self {
+        BookView {
+            bids: self.bids.iter().take(depth).copied().collect(),
+            asks: self.asks.iter().take(depth).copied().collect(),
+            ..self.clone()
+        }
+    }
+
+    /// 从 `previous` 到当前的前 `depth` 档变化 (买单, 卖单)，删除的档位数量为 0
+    pub fn diff(&self, previous: &BookView, depth: usize) -> (Levels, Levels) {
+        let side = |old: &[(Decimal, Decimal)], new: &[(Decimal, Decimal)]| {
+            let (old, new) = (&old[..old.len().min(depth)], &new[..new.len().min(depth)]);
+            let removed = old.iter()
+                .filter(|(price, _)| !new.iter().any(|(new_price, _)| new_price == price))
+                .map(|(price, _)| (*price, Decimal::ZERO));
+            let changed = new.iter()
+                .filter(|level| !old.contains(level))
+                .copied();
+            removed.chain(changed).collect()
+        };
+        (side(&previous.bids, &self.bids), side(&previous.asks, &self.asks))
+    }
+}
+
+/// 发送给订阅方的一条订单薄消息
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewUpdate {
+    /// 为 true 时 `book` 是完整的前若干档，订阅方替换本地副本；否则只包含变化的档位，数量为 0 表示删除
+    pub snapshot: bool,
+    pub book: BookView,
+}
+
+impl ViewUpdate {
+    /// JSON 格式：`{"type":"snapshot"|"update","venue","symbol","lastUpdateId","time","bids":[[价格,数量]],"asks"}`，
+    /// 价格和数量为字符串
+    pub fn to_json(&self) -> String {
+        self.to_value().to_string()
+    }
+
+    /// MessagePack 格式，结构与 [`to_json`](Self::to_json) 相同
+    pub fn to_msgpack(&self) -> Result<Vec<u8>, rmp_serde::encode::Error> {
+        rmp_serde::to_vec(&self.to_value())
+    }
+
+    fn to_value(&self) -> serde_json::Value {
+        let levels = |levels: &Levels| levels.iter()
+            .map(|(price, quantity)| [price.to_string(), quantity.to_string()])
+            .collect::<Vec<_>>();
+        json!({
+            "type": if self.snapshot { "snapshot" } else { "update" },
+            "venue": self.book.venue,
+            "symbol": self.book.symbol,
+            "lastUpdateId": self.book.last_update_id,
+            "time": self.book.time,
+            "bids": levels(&self.book.bids),
+            "asks": levels(&self.book.asks),
+        })
+    }
+}
+
+/// 单个订阅方的订阅状态
+///
+/// 记录已发送给订阅方的前若干档，把广播的订单薄转为首次的快照和之后的变化档位
+#[derive(Debug)]
+pub struct Subscription {
+    venue: Option<String>,
+    symbol: Option<String>,
+    depth: usize,
+    sent: HashMap<(String, String), BookView>,
+}
+
+impl Subscription {
+    /// # 参数
+    ///
+    /// * `venue` - 交易所，None 时订阅所有交易所
+    /// * `symbol` - 交易对，None 时订阅所有交易对
+    /// * `depth` - 每侧档位数
+    pub fn new(venue: Option<&str>, symbol: Option<&str>, depth: usize) -> Self {
+        Subscription {
+            venue: venue.map(|venue| venue.to_string()),
+            symbol: symbol.map(|symbol| symbol.to_string()),
+            depth: depth.max(1),
+            sent: HashMap::new(),
+        }
+    }
+
+    /// 是否订阅了该订单薄
+    pub fn matches(&self, venue: &str, symbol: &str) -> bool {
+        self.venue.as_deref().is_none_or(|v| v == venue) && self.symbol.as_deref().is_none_or(|s| s == symbol)
+    }
+
+    /// 清除已发送的状态，之后每个订单薄重新从快照开始；订阅方落后于广播时使用
+    pub fn reset(&mut self) {
+        self.sent.clear();
+    }
+
+    /// 上次发送给订阅方的前若干档，尚未发送时返回 None
+    pub fn sent(&self, venue: &str, symbol: &str) -> Option<&BookView> {
+        self.sent.get(&(venue.to_string(), symbol.to_string()))
+    }
+
+    /// 订阅的所有订单薄当前的快照，连接建立或 [`reset`](Self::reset) 之后发送
+    pub fn snapshots(&mut self, hub: &BookHub) -> Vec<ViewUpdate> {
+        let views: Vec<_> = hub.keys().into_iter()
+            .filter(|(venue, symbol)| self.matches(venue, symbol))
+            .filter_map(|(venue, symbol)| hub.book(&venue, &symbol))
+            .collect();
+        views.iter().filter_map(|view| self.next(view)).collect()
+    }
+
+    /// 处理一条广播的订单薄，返回需要发送的消息；未订阅或前若干档没有变化时返回 None
+    pub fn next(&mut self, view: &BookView) -> Option<ViewUpdate> {
+        if !self.matches(&view.venue, &view.symbol) {
+            return None;
+        }
+        let top = view.top(self.depth);
+        let key = (view.venue.clone(), view.symbol.clone());
+        let update = match self.sent.get(&key) {
+            Some(previous) => {
+                let (bids, asks) = top.diff(previous, self.depth);
+                if bids.is_empty() && asks.is_empty() {
+                    return None;
+                }
+                ViewUpdate { snapshot: false, book: BookView { bids, asks, ..top.clone() } }
+            }
+            None => ViewUpdate { snapshot: true, book: top.clone() },
+        };
+        self.sent.insert(key, top);
+        Some(update)
+    }
+}
+
+/// 在行情线程和服务线程之间共享的订单薄
+///
+/// 行情线程通过 [`HubRecorder`] 在每次订单薄更新后写入最新的前若干档并广播，
+/// 各个服务读取最新状态或订阅广播，服务数量不影响行情线程
+#[derive(Debug)]
+pub struct BookHub {
+    depth: usize,
+    books: Mutex<HashMap<(String, String), Arc<BookView>>>,
+    updates: broadcast::Sender<Arc<BookView>>,
+}
+
+impl BookHub {
+    /// 创建共享订单薄，每侧保存 `depth` 档，例如 [`DEFAULT_DEPTH`]
+    pub fn new(depth: usize) -> Arc<Self> {
+        Arc::new(BookHub {
+            depth: depth.max(1),
+            books: Mutex::new(HashMap::new()),
+            updates: broadcast::channel(BROADCAST_CAPACITY).0,
+        })
+    }
+
+    /// 每侧保存的档位数
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// 最新的订单薄
+    pub fn book(&self, venue: &str, symbol: &str) -> Option<Arc<BookView>> {
+        self.lock().get(&(venue.to_string(), symbol.to_string())).cloned()
+    }
+
+    /// 所有订单薄的 (交易所, 交易对)
+    pub fn keys(&self) -> Vec<(String, String)> {
+        let mut keys: Vec<_> = self.lock().keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+
+    /// 订阅之后的所有更新
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<BookView>> {
+        self.updates.subscribe()
+    }
+
+    /// 保存并广播订单薄
+    pub fn publish(&self, view: BookView) {
+        let view = Arc::new(view);
+        self.lock().insert((view.venue.clone(), view.symbol.clone()), view.clone());
+        // 没有订阅方时发送失败，忽略
+        let _ = self.updates.send(view);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<(String, String), Arc<BookView>>> {
+        // 持锁的代码不会 panic，锁中毒时数据仍然可用
+        self.books.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// 把订单薄更新写入 [`BookHub`] 的记录器
+#[derive(Debug)]
+pub struct HubRecorder {
+    hub: Arc<BookHub>,
+}
+
+impl HubRecorder {
+    pub fn new(hub: Arc<BookHub>) -> Self {
+        HubRecorder { hub }
+    }
+}
+
+impl Recorder for HubRecorder {
+    fn record(&mut self, _record: &Record) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn on_book(&mut self, venue: &str, symbol: &str, book: &OrderBook, local_time: u64) -> Result<(), Box<dyn Error>> {
+        self.hub.publish(BookView::from_book(venue, symbol, book, self.hub.depth(), local_time));
+        Ok(())
+    }
+}
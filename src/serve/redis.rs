@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use serde_json::json;
+use tokio::sync::broadcast;
+use tracing::warn;
+
+use crate::recorder::columns::to_f64;
+use crate::serve::{BookHub, BookView, Levels};
+
+/// 默认键前缀
+pub const DEFAULT_PREFIX: &str = "book";
+/// 默认每侧档位数
+pub const DEFAULT_REDIS_DEPTH: usize = 20;
+/// 写入失败后重新连接的间隔
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Redis 订单薄镜像
+///
+/// 每个订单薄写入：
+///
+/// * `前缀:交易所:交易对:bids`、`前缀:交易所:交易对:asks` - 有序集合，分数为价格，成员为 `价格:数量`，
+///   例如 `ZREVRANGE book:binance:BTCUSDT:bids 0 4` 读取前 5 档买单
+/// * `前缀:交易所:交易对` - 哈希，字段 `lastUpdateId`、`time`
+///
+/// 每次写入在一个事务中完成，之后在频道 `前缀:交易所:交易对` 发布 `{"venue","symbol","lastUpdateId","time"}`，
+/// 订阅方收到通知后读取。写入在单独的线程进行，Redis 较慢时只写入每个订单薄最新的状态
+#[derive(Debug)]
+struct RedisMirror {
+    prefix: String,
+    depth: usize,
+    /// (交易所, 交易对) -> 已写入的前若干档
+    written: HashMap<(String, String), BookView>,
+}
+
+impl RedisMirror {
+    /// 写入一个订单薄，前若干档没有变化时跳过
+    fn write(&mut self, connection: &mut redis::Connection, view: &BookView) -> redis::RedisResult<()> {
+        let top = view.top(self.depth);
+        let key = (view.venue.clone(), view.symbol.clone());
+        if self.written.get(&key).is_some_and(|written| written.bids == top.bids && written.asks == top.asks) {
+            return Ok(());
+        }
+        let base = format!("{}:{}:{}", self.prefix, view.venue, view.symbol);
+        let members = |levels: &Levels| levels.iter()
+            .map(|(price, quantity)| (to_f64(*price), format!("{}:{}", price, quantity)))
+            .collect::<Vec<_>>();
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        for (side, levels) in [("bids", members(&top.bids)), ("asks", members(&top.asks))] {
+            let side_key = format!("{}:{}", base, side);
+            pipe.del(&side_key).ignore();
+            if !levels.is_empty() {
+                pipe.zadd_multiple(&side_key, &levels).ignore();
+            }
+        }
+        pipe.hset_multiple(&base, &[("lastUpdateId", top.last_update_id), ("time", top.time)]).ignore();
+        let notification = json!({
+            "venue": top.venue,
+            "symbol": top.symbol,
+            "lastUpdateId": top.last_update_id,
+            "time": top.time,
+        });
+        pipe.publish(&base, notification.to_string()).ignore();
+        pipe.query::<()>(connection)?;
+        self.written.insert(key, top);
+        Ok(())
+    }
+}
+
+/// 启动 Redis 镜像线程，地址无效或无法连接时返回错误
+///
+/// # 参数
+///
+/// * `url` - 例如 `redis://127.0.0.1:6379/0`
+/// * `prefix` - 键和频道的前缀，例如 [`DEFAULT_PREFIX`]
+/// * `depth` - 每侧档位数，不超过共享订单薄的档位数
+pub fn spawn_redis_mirror(url: &str, prefix: &str, depth: usize, hub: Arc<BookHub>) -> Result<(), Box<dyn Error>> {
+    let client = redis::Client::open(url)?;
+    let mut connection = Some(client.get_connection()?);
+    let mut mirror = RedisMirror {
+        prefix: prefix.to_string(),
+        depth: depth.clamp(1, hub.depth()),
+        written: HashMap::new(),
+    };
+    // 先订阅再读取当前状态，不会漏掉两者之间的更新
+    let mut updates = hub.subscribe();
+    thread::spawn(move || {
+        // 所有订单薄的当前状态，启动时和落后于广播时使用
+        let current = || -> HashMap<(String, String), Arc<BookView>> {
+            hub.keys().into_iter()
+                .filter_map(|(venue, symbol)| hub.book(&venue, &symbol).map(|view| ((venue, symbol), view)))
+                .collect()
+        };
+        let mut pending = current();
+        loop {
+            if pending.is_empty() {
+                match updates.blocking_recv() {
+                    Ok(view) => {
+                        pending.insert((view.venue.clone(), view.symbol.clone()), view);
+                    }
+                    Err(broadcast::error::RecvError::Lagged(_)) => pending = current(),
+                    Err(broadcast::error::RecvError::Closed) => return,
+                }
+            }
+            // 合并已到达的更新，每个订单薄只写入最新状态
+            loop {
+                match updates.try_recv() {
+                    Ok(view) => {
+                        pending.insert((view.venue.clone(), view.symbol.clone()), view);
+                    }
+                    Err(broadcast::error::TryRecvError::Lagged(_)) => pending = current(),
+                    Err(_) => break,
+                }
+            }
+            let Some(active) = connection.as_mut() else {
+                thread::sleep(RECONNECT_INTERVAL);
+                match client.get_connection() {
+                    Ok(reconnected) => {
+                        // 断开期间的状态未知，全部重新写入
+                        mirror.written.clear();
+                        connection = Some(reconnected);
+                    }
+                    Err(e) => warn!(error = %e, "重新连接 Redis 失败"),
+                }
+                continue;
+            };
+            for view in pending.values() {
+                if let Err(e) = mirror.write(active, view) {
+                    warn!(error = %e, "写入 Redis 失败");
+                    connection = None;
+                    break;
+                }
+            }
+            if connection.is_some() {
+                pending.clear();
+            }
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use std::error::Error;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::error;
+
+use crate::manager::BINANCE_VENUE;
+use crate::serve::{BookHub, Levels};
+
+/// 与币安相同的默认和最大档位数
+const DEFAULT_LIMIT: usize = 100;
+const MAX_LIMIT: usize = 5000;
+
+#[derive(Debug, Deserialize)]
+struct DepthQuery {
+    symbol: Option<String>,
+    limit: Option<String>,
+    /// 扩展参数，默认币安
+    venue: Option<String>,
+}
+
+/// 币安格式的错误响应
+fn error(code: i32, msg: &str) -> Response {
+    (StatusCode::BAD_REQUEST, Json(json!({ "code": code, "msg": msg }))).into_response()
+}
+
+/// 深度查询，`futures` 时与合约接口一样附带事件时间 `E` 和撮合时间 `T`
+async fn depth(hub: Arc<BookHub>, query: DepthQuery, futures: bool) -> Response {
+    let Some(symbol) = query.symbol.filter(|symbol| !symbol.is_empty()) else {
+        return error(-1102, "Mandatory parameter 'symbol' was not sent, was empty/null, or malformed.");
+    };
+    let limit = match query.limit.as_deref().map(str::parse::<usize>) {
+        None => DEFAULT_LIMIT,
+        Some(Ok(limit)) if (1..=MAX_LIMIT).contains(&limit) => limit,
+        Some(_) => return error(-1100, "Illegal characters found in parameter 'limit'; legal range is '1-5000'."),
+    };
+    let venue = query.venue.as_deref().unwrap_or(BINANCE_VENUE);
+    // 币安交易对为大写，其他交易所按原样查找
+    let symbol = if venue == BINANCE_VENUE { symbol.to_uppercase() } else { symbol };
+    let Some(view) = hub.book(venue, &symbol) else {
+        return error(-1121, "Invalid symbol.");
+    };
+    let levels = |levels: &Levels| levels.iter()
+        .take(limit)
+        .map(|(price, quantity)| [price.to_string(), quantity.to_string()])
+        .collect::<Vec<_>>();
+    let mut body = json!({
+        "lastUpdateId": view.last_update_id,
+        "bids": levels(&view.bids),
+        "asks": levels(&view.asks),
+    });
+    if futures {
+        body["E"] = json!(view.time);
+        body["T"] = json!(view.time);
+    }
+    Json(body).into_response()
+}
+
+/// 在新线程中启动与币安深度接口兼容的 HTTP 服务，监听失败时返回错误
+///
+/// 提供 `/depth`、`/api/v3/depth`（现货格式）和 `/fapi/v1/depth`、`/dapi/v1/depth`（合约格式），
+/// 参数 `symbol`、`limit` 与币安相同，另外可以用 `venue` 查询其他交易所的订单薄。
+/// 数据来自本地维护的订单薄，档位数不超过 `--serve-depth`，已有的工具把接口地址指向本服务即可。
+/// 提供 `dump` 时另有 `POST /admin/dump`，设置标志请求主循环转储所有订单薄的完整档位
+pub fn spawn_rest_server(addr: SocketAddr, hub: Arc<BookHub>, dump: Option<Arc<AtomicBool>>) -> Result<(), Box<dyn Error>> {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()?;
+    let listener = runtime.block_on(tokio::net::TcpListener::bind(addr))?;
+    let spot = get(|State(hub): State<Arc<BookHub>>, Query(query): Query<DepthQuery>| depth(hub, query, false));
+    let futures = get(|State(hub): State<Arc<BookHub>>, Query(query): Query<DepthQuery>| depth(hub, query, true));
+    let mut app = Router::new()
+        .route("/depth", spot.clone())
+        .route("/api/v3/depth", spot)
+        .route("/fapi/v1/depth", futures.clone())
+        .route("/dapi/v1/depth", futures);
+    if let Some(dump) = dump {
+        // 转储在主循环处理下一条消息时进行，这里只返回已受理
+        app = app.route("/admin/dump", post(move || async move {
+            dump.store(true, Ordering::Relaxed);
+            (StatusCode::ACCEPTED, Json(json!({ "status": "accepted" })))
+        }));
+    }
+    let app = app.with_state(hub);
+    thread::spawn(move || {
+        if let Err(e) = runtime.block_on(async { axum::serve(listener, app).await }) {
+            error!(error = %e, "HTTP 服务退出");
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use std::error::Error;
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::Arc;
+use std::thread;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+
+use crate::serve::{BookHub, Subscription};
+
+/// 启动 Unix 域套接字服务，每个连接一个线程
+///
+/// 连接后持续收到长度前缀的帧：4 字节大端长度后接 [`ViewUpdate::to_json`](crate::serve::ViewUpdate::to_json)
+/// 格式的 JSON，先是每个订单薄的快照，之后是变化的档位。套接字文件已存在时先删除
+pub fn spawn_uds_server(path: impl AsRef<Path>, hub: Arc<BookHub>) -> Result<(), Box<dyn Error>> {
+    let path = path.as_ref();
+    // 只删除上次运行遗留的套接字，不删除普通文件
+    if fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket()) {
+        fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let hub = hub.clone();
+                    thread::spawn(move || {
+                        if let Err(e) = serve_client(stream, &hub) {
+                            info!(error = %e, "Unix 套接字客户端断开");
+                        }
+                    });
+                }
+                Err(e) => warn!(error = %e, "接受 Unix 套接字连接失败"),
+            }
+        }
+    });
+    Ok(())
+}
+
+/// 向一个客户端推送订单薄，直到连接断开
+fn serve_client(stream: UnixStream, hub: &BookHub) -> Result<(), Box<dyn Error>> {
+    let mut writer = BufWriter::new(stream);
+    let mut subscription = Subscription::new(None, None, hub.depth());
+    // 先订阅再读取当前状态，不会漏掉两者之间的更新
+    let mut updates = hub.subscribe();
+    let mut pending = subscription.snapshots(hub);
+    loop {
+        for update in pending.drain(..) {
+            let frame = update.to_json();
+            writer.write_all(&(frame.len() as u32).to_be_bytes())?;
+            writer.write_all(frame.as_bytes())?;
+        }
+        writer.flush()?;
+        match updates.blocking_recv() {
+            Ok(view) => pending.extend(subscription.next(&view)),
+            Err(broadcast::error::RecvError::Lagged(_)) => {
+                subscription.reset();
+                pending = subscription.snapshots(hub);
+            }
+            Err(broadcast::error::RecvError::Closed) => return Ok(()),
+        }
+    }
+}
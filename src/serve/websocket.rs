@@ -0,0 +1,83 @@
+use std::error::Error;
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+use tokio::sync::broadcast;
+use tungstenite::handshake::server::{Request, Response};
+use tungstenite::{Message, Utf8Bytes};
+use tracing::{info, warn};
+
+use crate::serve::{BookHub, Subscription};
+
+/// 启动 WebSocket 服务，每个连接一个线程
+///
+/// 客户端连接 `ws://地址/?venue=交易所&symbol=交易对&depth=档位&format=json|msgpack`，参数都可省略，省略时订阅
+/// 全部订单薄和最大档位数。连接后先收到每个订单薄的快照，之后只收到变化的档位，格式见
+/// [`ViewUpdate::to_json`](crate::serve::ViewUpdate::to_json)；`format=msgpack` 时以二进制消息发送相同结构的
+/// MessagePack，流量比 JSON 小，客户端不需要解析文本。所有客户端共享同一个交易所连接
+pub fn spawn_websocket_server(addr: SocketAddr, hub: Arc<BookHub>) -> Result<(), Box<dyn Error>> {
+    let listener = TcpListener::bind(addr)?;
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let hub = hub.clone();
+                    thread::spawn(move || {
+                        let peer = stream.peer_addr().map(|addr| addr.to_string()).unwrap_or_default();
+                        if let Err(e) = serve_client(stream, &hub) {
+                            info!(peer, error = %e, "WebSocket 客户端断开");
+                        }
+                    });
+                }
+                Err(e) => warn!(error = %e, "接受 WebSocket 连接失败"),
+            }
+        }
+    });
+    Ok(())
+}
+
+/// 向一个客户端推送订单薄，直到连接断开
+// 握手回调的错误类型由 tungstenite 决定
+#[allow(clippy::result_large_err)]
+fn serve_client(stream: TcpStream, hub: &BookHub) -> Result<(), Box<dyn Error>> {
+    let mut query = String::new();
+    let mut socket = tungstenite::accept_hdr(stream, |request: &Request, response: Response| {
+        query = request.uri().query().unwrap_or_default().to_string();
+        Ok(response)
+    }).map_err(|e| format!("握手失败: {}", e))?;
+    let param = |name: &str| query.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find_map(|(key, value)| (key == name && !value.is_empty()).then_some(value));
+    let depth = param("depth")
+        .and_then(|depth| depth.parse::<usize>().ok())
+        .map_or(hub.depth(), |depth| depth.min(hub.depth()));
+    let msgpack = match param("format") {
+        None | Some("json") => false,
+        Some("msgpack") => true,
+        Some(format) => return Err(format!("未知的消息格式: {}", format).into()),
+    };
+    let mut subscription = Subscription::new(param("venue"), param("symbol"), depth);
+
+    // 先订阅再读取当前状态，不会漏掉两者之间的更新
+    let mut updates = hub.subscribe();
+    let mut pending = subscription.snapshots(hub);
+    loop {
+        for update in pending.drain(..) {
+            let message = if msgpack {
+                Message::Binary(update.to_msgpack()?.into())
+            } else {
+                Message::Text(Utf8Bytes::from(update.to_json()))
+            };
+            socket.send(message)?;
+        }
+        match updates.blocking_recv() {
+            Ok(view) => pending.extend(subscription.next(&view)),
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                warn!(skipped, "WebSocket 客户端落后，重新发送快照");
+                subscription.reset();
+                pending = subscription.snapshots(hub);
+            }
+            Err(broadcast::error::RecvError::Closed) => return Ok(()),
+        }
+    }
+}
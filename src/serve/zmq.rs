@@ -0,0 +1,66 @@
+use std::error::Error;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use zeromq::{PubSocket, Socket, SocketSend, ZmqMessage};
+use tracing::warn;
+
+use crate::serve::{BookHub, Subscription, ViewUpdate};
+
+/// 默认快照间隔
+pub const DEFAULT_SNAPSHOT_SECS: u64 = 10;
+
+/// 消息主题 `交易所.交易对.snapshot|update`，订阅方按前缀过滤，例如 `binance.BTCUSDT.`
+fn topic(update: &ViewUpdate) -> String {
+    let kind = if update.snapshot { "snapshot" } else { "update" };
+    format!("{}.{}.{}", update.book.venue, update.book.symbol, kind)
+}
+
+/// 启动 ZeroMQ PUB 发布线程，绑定失败时返回错误
+///
+/// 每条消息有两帧：主题和 [`ViewUpdate::to_json`] 格式的订单薄。每个订单薄首次出现时发送快照，
+/// 之后发送变化的档位，并每隔 `snapshot_interval` 重新发送所有订单薄的快照，晚连接的订阅方在一个间隔内
+/// 即可建立完整的订单薄。PUB 套接字在订阅方读取过慢时丢弃消息，不会阻塞
+///
+/// # 参数
+///
+/// * `endpoint` - 绑定地址，例如 `tcp://*:5556`、`ipc:///tmp/book.ipc`
+/// * `snapshot_interval` - 快照间隔
+pub fn spawn_zmq_publisher(endpoint: &str, hub: Arc<BookHub>, snapshot_interval: Duration) -> Result<(), Box<dyn Error>> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    let mut socket = PubSocket::new();
+    runtime.block_on(socket.bind(endpoint))?;
+    thread::spawn(move || runtime.block_on(async move {
+        // 先订阅再读取当前状态，不会漏掉两者之间的更新
+        let mut updates = hub.subscribe();
+        let mut subscription = Subscription::new(None, None, hub.depth());
+        let mut snapshots = tokio::time::interval(snapshot_interval);
+        loop {
+            let pending = tokio::select! {
+                _ = snapshots.tick() => {
+                    subscription.reset();
+                    subscription.snapshots(&hub)
+                }
+                update = updates.recv() => match update {
+                    Ok(view) => subscription.next(&view).into_iter().collect(),
+                    Err(broadcast::error::RecvError::Lagged(_)) => {
+                        subscription.reset();
+                        subscription.snapshots(&hub)
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return,
+                },
+            };
+            for update in pending {
+                let mut message = ZmqMessage::from(topic(&update));
+                message.push_back(update.to_json().into());
+                if let Err(e) = socket.send(message).await {
+                    warn!(error = %e, "ZeroMQ 发送失败");
+                }
+            }
+        }
+    }));
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use std::error::Error;
+use std::fs::OpenOptions;
+use std::io::{BufWriter, Write};
+use rust_decimal::Decimal;
+
+use crate::order_book::OrderBook;
+
+/// CSV 文件的表头
+const CSV_HEADER: &str = "timestamp,venue_a,symbol_a,venue_b,symbol_b,bid_a,ask_a,bid_b,ask_b,bid_diff,ask_diff,mid_diff,mid_diff_bps";
+
+/// 两个交易所同一品种的一次价差采样，差值均为 A - B
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpreadSample {
+    /// 本地时间（毫秒）
+    pub timestamp: u64,
+    pub bid_a: Decimal,
+    pub ask_a: Decimal,
+    pub bid_b: Decimal,
+    pub ask_b: Decimal,
+    /// 买一价差
+    pub bid_diff: Decimal,
+    /// 卖一价差
+    pub ask_diff: Decimal,
+    /// 中间价差
+    pub mid_diff: Decimal,
+    /// 中间价差占 B 中间价的基点数（溢价为正）
+    pub mid_diff_bps: Decimal,
+}
+
+/// 跨交易所价差记录器
+///
+/// 按固定间隔采样两个交易所同一品种的买一、卖一和中间价差，以 CSV 行写入输出，
+/// 用于事后分析交易所之间的持续溢价
+pub struct SpreadRecorder {
+    leg_a: (String, String),
+    leg_b: (String, String),
+    /// 最小采样间隔（毫秒）
+    interval_ms: u64,
+    last_timestamp: u64,
+    writer: Box<dyn Write + Send>,
+}
+
+impl std::fmt::Debug for SpreadRecorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SpreadRecorder")
+            .field("leg_a", &self.leg_a)
+            .field("leg_b", &self.leg_b)
+            .field("interval_ms", &self.interval_ms)
+            .finish()
+    }
+}
+
+impl SpreadRecorder {
+    /// 创建记录器，先写入 CSV 表头
+    ///
+    /// # 参数
+    ///
+    /// * `leg_a` - (交易所, 交易对)
+    /// * `leg_b` - (交易所, 交易对)，作为计算溢价的基准
+    /// * `interval_ms` - 最小采样间隔（毫秒）
+    /// * `writer` - 输出，例如文件或标准输出
+    pub fn new(leg_a: (String, String), leg_b: (String, String), interval_ms: u64, mut writer: Box<dyn Write + Send>) -> Result<Self, Box<dyn Error>> {
+        writeln!(writer, "{}", CSV_HEADER)?;
+        Ok(SpreadRecorder::with_writer(leg_a, leg_b, interval_ms, writer))
+    }
+
+    /// 追加写入 CSV 文件，文件为空时先写入表头
+    pub fn to_file(leg_a: (String, String), leg_b: (String, String), interval_ms: u64, path: &str) -> Result<Self, Box<dyn Error>> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        if file.metadata()?.len() == 0 {
+            SpreadRecorder::new(leg_a, leg_b, interval_ms, Box::new(BufWriter::new(file)))
+        } else {
+            Ok(SpreadRecorder::with_writer(leg_a, leg_b, interval_ms, Box::new(BufWriter::new(file))))
+        }
+    }
+
+    fn with_writer(leg_a: (String, String), leg_b: (String, String), interval_ms: u64, writer: Box<dyn Write + Send>) -> Self {
+        SpreadRecorder {
+            leg_a,
+            leg_b,
+            interval_ms,
+            last_timestamp: 0,
+            writer,
+        }
+    }
+
+    /// 判断交易所的交易对是否为其中一条腿
+    pub fn contains(&self, venue: &str, symbol: &str) -> bool {
+        [&self.leg_a, &self.leg_b].iter().any(|(leg_venue, leg_symbol)| leg_venue == venue && leg_symbol == symbol)
+    }
+
+    /// 按两边的订单薄计算价差，任意一边没有买一或卖一时返回 None
+    pub fn sample<'a>(&self, books: impl Fn(&str, &str) -> Option<&'a OrderBook>, timestamp: u64) -> Option<SpreadSample> {
+        let book_a = books(&self.leg_a.0, &self.leg_a.1)?;
+        let book_b = books(&self.leg_b.0, &self.leg_b.1)?;
+        let (bid_a, _) = book_a.best_bid()?;
+        let (ask_a, _) = book_a.best_ask()?;
+        let (bid_b, _) = book_b.best_bid()?;
+        let (ask_b, _) = book_b.best_ask()?;
+        let mid_a = book_a.mid_price()?;
+        let mid_b = book_b.mid_price()?;
+        let mid_diff = mid_a - mid_b;
+        let mid_diff_bps = if mid_b.is_zero() { Decimal::ZERO } else { mid_diff / mid_b * Decimal::from(10_000) };
+        Some(SpreadSample {
+            timestamp,
+            bid_a,
+            ask_a,
+            bid_b,
+            ask_b,
+            bid_diff: bid_a - bid_b,
+            ask_diff: ask_a - ask_b,
+            mid_diff,
+            mid_diff_bps,
+        })
+    }
+
+    /// 距上次记录超过采样间隔时采样并写入一行
+    pub fn record<'a>(&mut self, books: impl Fn(&str, &str) -> Option<&'a OrderBook>, timestamp: u64) -> Result<Option<SpreadSample>, Box<dyn Error>> {
+        if timestamp < self.last_timestamp + self.interval_ms {
+            return Ok(None);
+        }
+        let Some(sample) = self.sample(books, timestamp) else {
+            return Ok(None);
+        };
+        writeln!(self.writer, "{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                 sample.timestamp, self.leg_a.0, self.leg_a.1, self.leg_b.0, self.leg_b.1,
+                 sample.bid_a, sample.ask_a, sample.bid_b, sample.ask_b,
+                 sample.bid_diff, sample.ask_diff, sample.mid_diff, sample.mid_diff_bps.round_dp(4))?;
+        self.writer.flush()?;
+        self.last_timestamp = timestamp;
+        Ok(Some(sample))
+    }
+}
@@ -0,0 +1,99 @@
+use rust_decimal::Decimal;
+
+use crate::order_book::OrderBook;
+use crate::strategy::{Strategy, StrategyContext};
+
+/// 默认统计的档位数
+pub const DEFAULT_LEVELS: usize = 5;
+/// 默认两次下单之间的最短间隔（毫秒）
+pub const DEFAULT_COOLDOWN_MS: u64 = 1_000;
+
+/// 示例策略：按盘口不平衡度追单
+///
+/// 前若干档买盘明显厚于卖盘时买入，卖盘明显厚于买盘时卖出，持仓绝对值不超过上限，
+/// 两次下单之间至少间隔冷却时间
+#[derive(Debug, Clone)]
+pub struct ImbalanceStrategy {
+    name: String,
+    venue: String,
+    symbol: String,
+    levels: usize,
+    /// 不平衡度阈值，取值 0 到 1
+    threshold: Decimal,
+    /// 每次下单数量
+    size: Decimal,
+    /// 持仓上限
+    max_position: Decimal,
+    cooldown_ms: u64,
+    last_order: Option<u64>,
+}
+
+impl ImbalanceStrategy {
+    pub fn new(venue: &str, symbol: &str, threshold: Decimal, size: Decimal, max_position: Decimal) -> Self {
+        ImbalanceStrategy {
+            name: format!("imbalance {} {}", venue, symbol),
+            venue: venue.to_string(),
+            symbol: symbol.to_string(),
+            levels: DEFAULT_LEVELS,
+            threshold,
+            size,
+            max_position,
+            cooldown_ms: DEFAULT_COOLDOWN_MS,
+            last_order: None,
+        }
+    }
+
+    /// 解析 `交易所:交易对:阈值:数量:持仓上限`，例如 `binance:BTCUSDT:0.6:0.01:0.05`
+    pub fn parse(spec: &str) -> Option<Self> {
+        let parts: Vec<&str> = spec.split(':').collect();
+        match parts.as_slice() {
+            [venue, symbol, threshold, size, max_position] => Some(ImbalanceStrategy::new(
+                venue,
+                symbol,
+                threshold.parse().ok()?,
+                size.parse().ok()?,
+                max_position.parse().ok()?,
+            )),
+            _ => None,
+        }
+    }
+
+    /// 设置统计的档位数
+    pub fn set_levels(&mut self, levels: usize) {
+        self.levels = levels;
+    }
+
+    /// 设置两次下单之间的最短间隔（毫秒）
+    pub fn set_cooldown(&mut self, cooldown_ms: u64) {
+        self.cooldown_ms = cooldown_ms;
+    }
+}
+
+impl Strategy for ImbalanceStrategy {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn on_book_update(&mut self, ctx: &mut StrategyContext, venue: &str, symbol: &str, book: &OrderBook) {
+        if venue != self.venue || symbol != self.symbol {
+            return;
+        }
+        if self.last_order.is_some_and(|time| ctx.now() < time + self.cooldown_ms) {
+            return;
+        }
+        let Some(imbalance) = book.imbalance(self.levels) else {
+            return;
+        };
+        let position = ctx.position(venue, symbol);
+        let fill = if imbalance >= self.threshold && position + self.size <= self.max_position {
+            ctx.buy(venue, symbol, self.size)
+        } else if imbalance <= -self.threshold && position - self.size >= -self.max_position {
+            ctx.sell(venue, symbol, self.size)
+        } else {
+            None
+        };
+        if fill.is_some() {
+            self.last_order = Some(ctx.now());
+        }
+    }
+}
@@ -0,0 +1,325 @@
+use std::collections::HashMap;
+use std::error::Error;
+use rust_decimal::Decimal;
+use tracing::warn;
+
+use crate::binance::Market;
+use crate::fees::FeeSchedule;
+use crate::manager::BookManager;
+use crate::order_book::{OrderBook, Side};
+use crate::recorder::Record;
+use crate::strategy::imbalance::ImbalanceStrategy;
+use crate::trade::Trade;
+
+pub mod imbalance;
+
+/// 权益曲线的采样间隔（毫秒）
+const EQUITY_SAMPLE_MS: u64 = 1_000;
+
+/// 交易策略
+///
+/// 由 [`BookManager`] 在订单薄更新、收到成交和定时器到期时调用。实时运行时以实时行情
+/// 模拟成交，回测时按记录时间推进模拟时钟，两种情况下策略代码完全相同
+pub trait Strategy: Send + std::fmt::Debug {
+    /// 策略名称
+    fn name(&self) -> &str;
+
+    /// 订单薄更新后调用
+    fn on_book_update(&mut self, _ctx: &mut StrategyContext, _venue: &str, _symbol: &str, _book: &OrderBook) {}
+
+    /// 收到成交后调用
+    fn on_trade(&mut self, _ctx: &mut StrategyContext, _venue: &str, _symbol: &str, _trade: &Trade) {}
+
+    /// 定时器到期时调用
+    fn on_timer(&mut self, _ctx: &mut StrategyContext) {}
+
+    /// 定时器间隔（毫秒），为 None 时不调用 on_timer；定时器在处理行情时按当前时钟检查
+    fn timer_interval_ms(&self) -> Option<u64> {
+        None
+    }
+}
+
+/// 一笔模拟成交
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fill {
+    /// 成交时间（毫秒）
+    pub time: u64,
+    pub venue: String,
+    pub symbol: String,
+    /// 吃的盘口方向，买入为 Ask，卖出为 Bid
+    pub side: Side,
+    pub quantity: Decimal,
+    pub average_price: Decimal,
+    /// 成交金额
+    pub notional: Decimal,
+    /// 吃单手续费（计价货币）
+    pub fee: Decimal,
+    /// 相对下单前最优价的滑点（基点）
+    pub slippage_bps: Option<Decimal>,
+}
+
+/// 策略的模拟账户
+#[derive(Debug, Clone, Default)]
+pub struct Account {
+    /// 计价货币余额变动，买入减少、卖出增加，已扣除手续费
+    pub cash: Decimal,
+    /// (交易所, 交易对) -> 持仓数量，负数为空头
+    pub positions: HashMap<(String, String), Decimal>,
+    /// 全部成交
+    pub fills: Vec<Fill>,
+    /// 权益曲线 (时间, 权益)
+    pub equity: Vec<(u64, Decimal)>,
+}
+
+impl Account {
+    /// 持仓数量
+    pub fn position(&self, venue: &str, symbol: &str) -> Decimal {
+        self.positions.get(&(venue.to_string(), symbol.to_string())).copied().unwrap_or_default()
+    }
+
+    /// 按中间价计算的权益，持仓对应的订单薄不存在时返回 None
+    pub fn mark_to_market(&self, manager: &BookManager) -> Option<Decimal> {
+        let mut equity = self.cash;
+        for ((venue, symbol), quantity) in &self.positions {
+            if quantity.is_zero() {
+                continue;
+            }
+            equity += quantity * manager.venue_book(venue, symbol)?.mid_price()?;
+        }
+        Some(equity)
+    }
+
+    fn apply(&mut self, fill: Fill) {
+        let position = self.positions.entry((fill.venue.clone(), fill.symbol.clone())).or_default();
+        match fill.side {
+            Side::Ask => {
+                *position += fill.quantity;
+                self.cash -= fill.notional + fill.fee;
+            }
+            Side::Bid => {
+                *position -= fill.quantity;
+                self.cash += fill.notional - fill.fee;
+            }
+        }
+        self.fills.push(fill);
+    }
+}
+
+/// 策略回调的上下文，提供当前时间、订单薄查询和模拟下单
+#[derive(Debug)]
+pub struct StrategyContext<'a> {
+    manager: &'a BookManager,
+    account: &'a mut Account,
+    fees: &'a FeeSchedule,
+    now: u64,
+}
+
+impl<'a> StrategyContext<'a> {
+    /// 当前时间（毫秒），回测时为记录的接收时间
+    pub fn now(&self) -> u64 {
+        self.now
+    }
+
+    /// 交易所订单薄
+    pub fn book(&self, venue: &str, symbol: &str) -> Option<&'a OrderBook> {
+        self.manager.venue_book(venue, symbol)
+    }
+
+    /// 模拟账户
+    pub fn account(&self) -> &Account {
+        self.account
+    }
+
+    /// 持仓数量
+    pub fn position(&self, venue: &str, symbol: &str) -> Decimal {
+        self.account.position(venue, symbol)
+    }
+
+    /// 市价买入，按当前订单薄逐档成交，深度不足时部分成交，没有成交时返回 None
+    pub fn buy(&mut self, venue: &str, symbol: &str, quantity: Decimal) -> Option<Fill> {
+        self.market_order(venue, symbol, Side::Ask, quantity)
+    }
+
+    /// 市价卖出，按当前订单薄逐档成交，深度不足时部分成交，没有成交时返回 None
+    pub fn sell(&mut self, venue: &str, symbol: &str, quantity: Decimal) -> Option<Fill> {
+        self.market_order(venue, symbol, Side::Bid, quantity)
+    }
+
+    fn market_order(&mut self, venue: &str, symbol: &str, side: Side, quantity: Decimal) -> Option<Fill> {
+        let book = self.book(venue, symbol)?;
+        let simulation = match side {
+            Side::Ask => book.simulate_market_buy(quantity),
+            Side::Bid => book.simulate_market_sell(quantity),
+        };
+        let average_price = simulation.average_price?;
+        let fill = Fill {
+            time: self.now,
+            venue: venue.to_string(),
+            symbol: symbol.to_string(),
+            side,
+            quantity: simulation.filled,
+            average_price,
+            notional: simulation.notional,
+            fee: simulation.notional * self.fees.taker_rate(venue),
+            slippage_bps: simulation.slippage_bps(),
+        };
+        self.account.apply(fill.clone());
+        Some(fill)
+    }
+}
+
+/// 策略运行结果
+#[derive(Debug, Clone, Default)]
+pub struct BacktestReport {
+    pub strategy: String,
+    /// 第一次和最后一次调用策略的时间
+    pub start_time: u64,
+    pub end_time: u64,
+    pub fills: usize,
+    /// 累计成交金额
+    pub volume: Decimal,
+    pub fees: Decimal,
+    /// 平均滑点（基点）
+    pub average_slippage_bps: Option<Decimal>,
+    /// 按结束时中间价计算的盈亏，持仓订单薄不存在时为 None
+    pub pnl: Option<Decimal>,
+    /// 权益曲线的最大回撤
+    pub max_drawdown: Decimal,
+    /// 结束时的非零持仓
+    pub positions: Vec<(String, String, Decimal)>,
+}
+
+impl BacktestReport {
+    /// 打印报告
+    pub fn print(&self) {
+        println!("策略 {} 运行 {} - {}（{} 秒）", self.strategy, self.start_time, self.end_time,
+                 self.end_time.saturating_sub(self.start_time) / 1000);
+        println!("成交 {} 笔，成交金额: {}，手续费: {}，平均滑点: {:?} bps",
+                 self.fills, self.volume.round_dp(8), self.fees.round_dp(8), self.average_slippage_bps.map(|bps| bps.round_dp(2)));
+        println!("盈亏: {:?}，最大回撤: {}", self.pnl.map(|pnl| pnl.round_dp(8)), self.max_drawdown.round_dp(8));
+        for (venue, symbol, quantity) in &self.positions {
+            println!("持仓 {} {}: {}", venue, symbol, quantity);
+        }
+    }
+}
+
+/// 管理器中运行的一个策略及其账户
+#[derive(Debug)]
+pub(crate) struct StrategyRunner {
+    strategy: Box<dyn Strategy>,
+    account: Account,
+    fees: FeeSchedule,
+    next_timer: Option<u64>,
+    /// (第一次, 最近一次) 调用时间
+    active: Option<(u64, u64)>,
+}
+
+impl StrategyRunner {
+    pub(crate) fn new(strategy: Box<dyn Strategy>, fees: FeeSchedule) -> Self {
+        StrategyRunner {
+            strategy,
+            account: Account::default(),
+            fees,
+            next_timer: None,
+            active: None,
+        }
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        self.strategy.name()
+    }
+
+    /// 订单薄更新，返回本次产生的成交
+    pub(crate) fn on_book_update(&mut self, manager: &BookManager, venue: &str, symbol: &str, now: u64) -> Vec<Fill> {
+        let Some(book) = manager.venue_book(venue, symbol) else {
+            return Vec::new();
+        };
+        self.dispatch(manager, now, |strategy, ctx| strategy.on_book_update(ctx, venue, symbol, book))
+    }
+
+    /// 成交，返回本次产生的成交
+    pub(crate) fn on_trade(&mut self, manager: &BookManager, venue: &str, symbol: &str, trade: &Trade, now: u64) -> Vec<Fill> {
+        self.dispatch(manager, now, |strategy, ctx| strategy.on_trade(ctx, venue, symbol, trade))
+    }
+
+    /// 调用策略后检查定时器并采样权益
+    fn dispatch(&mut self, manager: &BookManager, now: u64, call: impl FnOnce(&mut dyn Strategy, &mut StrategyContext)) -> Vec<Fill> {
+        let before = self.account.fills.len();
+        let mut ctx = StrategyContext { manager, account: &mut self.account, fees: &self.fees, now };
+        call(self.strategy.as_mut(), &mut ctx);
+        if let Some(interval) = self.strategy.timer_interval_ms() {
+            let next = *self.next_timer.get_or_insert(now + interval);
+            if now >= next {
+                self.strategy.on_timer(&mut ctx);
+                self.next_timer = Some(now + interval);
+            }
+        }
+
+        let first = self.active.map_or(now, |(first, _)| first);
+        self.active = Some((first, now));
+        if self.account.equity.last().is_none_or(|(time, _)| now >= time + EQUITY_SAMPLE_MS)
+            && let Some(equity) = self.account.mark_to_market(manager)
+        {
+            self.account.equity.push((now, equity));
+        }
+        self.account.fills[before..].to_vec()
+    }
+
+    /// 生成报告，盈亏按 `manager` 当前的订单薄计算
+    pub(crate) fn report(&self, manager: &BookManager) -> BacktestReport {
+        let fills = &self.account.fills;
+        let slippages: Vec<Decimal> = fills.iter().filter_map(|fill| fill.slippage_bps).collect();
+        let mut peak = Decimal::MIN;
+        let mut max_drawdown = Decimal::ZERO;
+        for (_, equity) in &self.account.equity {
+            peak = peak.max(*equity);
+            max_drawdown = max_drawdown.max(peak - equity);
+        }
+        let mut positions: Vec<(String, String, Decimal)> = self.account.positions.iter()
+            .filter(|(_, quantity)| !quantity.is_zero())
+            .map(|((venue, symbol), quantity)| (venue.clone(), symbol.clone(), *quantity))
+            .collect();
+        positions.sort();
+        let (start_time, end_time) = self.active.unwrap_or_default();
+        BacktestReport {
+            strategy: self.strategy.name().to_string(),
+            start_time,
+            end_time,
+            fills: fills.len(),
+            volume: fills.iter().map(|fill| fill.notional).sum(),
+            fees: fills.iter().map(|fill| fill.fee).sum(),
+            average_slippage_bps: (!slippages.is_empty())
+                .then(|| slippages.iter().sum::<Decimal>() / Decimal::from(slippages.len())),
+            pnl: self.account.mark_to_market(manager),
+            max_drawdown,
+            positions,
+        }
+    }
+}
+
+/// 解析命令行的策略参数 `名称:参数`
+///
+/// * `imbalance:交易所:交易对:阈值:数量:持仓上限` - [`ImbalanceStrategy`]，例如 `imbalance:binance:BTCUSDT:0.6:0.01:0.05`
+pub fn parse_strategy(spec: &str) -> Option<Box<dyn Strategy>> {
+    let (name, params) = spec.split_once(':')?;
+    match name {
+        "imbalance" => ImbalanceStrategy::parse(params).map(|strategy| Box::new(strategy) as Box<dyn Strategy>),
+        _ => None,
+    }
+}
+
+/// 在按时间排序的记录上回测策略
+///
+/// 记录经过与实时行情相同的订单薄流程，模拟时钟取记录的接收时间，
+/// 市价单按当时的订单薄逐档成交并按 `fees` 扣除吃单手续费
+pub fn backtest(records: &[Record], strategy: Box<dyn Strategy>, fees: FeeSchedule) -> Result<BacktestReport, Box<dyn Error>> {
+    let mut manager = BookManager::new(Market::Spot, &[]);
+    manager.add_strategy(strategy, fees);
+    for record in records {
+        if let Err(e) = manager.replay_record(record.clone()) {
+            warn!(error = %e, "回放记录失败");
+        }
+        manager.poll_events();
+    }
+    manager.strategy_reports().into_iter().next().ok_or_else(|| "策略未运行".into())
+}
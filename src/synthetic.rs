@@ -0,0 +1,121 @@
+use std::cmp::Ordering;
+use std::error::Error;
+use rust_decimal::Decimal;
+
+use crate::order_book::OrderBook;
+
+/// 按从优到劣排列的档位 (价格, 数量)
+type Levels = Vec<(Decimal, Decimal)>;
+
+/// 合成交易对的一条腿
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntheticLeg {
+    /// 交易所名称
+    pub venue: String,
+    /// 交易对
+    pub symbol: String,
+    /// 是否取倒数，例如用 EURUSDT 得到 USDT/EUR
+    pub inverted: bool,
+}
+
+/// 由两条腿相乘得到的合成交易对，例如 BNB/EUR = BNBUSDT × (1 / EURUSDT)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntheticPair {
+    /// 合成交易对名称，例如 BNBEUR
+    pub name: String,
+    /// 第一条腿：基础资产/中间资产
+    pub first: SyntheticLeg,
+    /// 第二条腿：中间资产/计价资产
+    pub second: SyntheticLeg,
+}
+
+impl SyntheticPair {
+    /// 解析 `名称=腿1*腿2` 或 `名称=腿1/腿2`，`/` 表示第二条腿取倒数
+    ///
+    /// 例如 `BNBEUR=BNBUSDT/EURUSDT`、`BNBUSDT=BNBBTC*BTCUSDT`
+    pub fn parse(venue: &str, spec: &str) -> Result<Self, Box<dyn Error>> {
+        let (name, legs) = spec.split_once('=').ok_or_else(|| format!("合成交易对格式错误: {}", spec))?;
+        let (first, second, inverted) = match (legs.split_once('*'), legs.split_once('/')) {
+            (Some((first, second)), None) => (first, second, false),
+            (None, Some((first, second))) => (first, second, true),
+            _ => return Err(format!("合成交易对格式错误: {}", spec).into()),
+        };
+        Ok(SyntheticPair {
+            name: name.to_uppercase(),
+            first: SyntheticLeg { venue: venue.to_string(), symbol: first.to_uppercase(), inverted: false },
+            second: SyntheticLeg { venue: venue.to_string(), symbol: second.to_uppercase(), inverted },
+        })
+    }
+
+    /// 判断交易所的交易对是否为其中一条腿
+    pub fn contains(&self, venue: &str, symbol: &str) -> bool {
+        [&self.first, &self.second].iter().any(|leg| leg.venue == venue && leg.symbol == symbol)
+    }
+
+    /// 用两条腿的当前深度合成订单薄，任意一条腿尚未建立时返回 None
+    ///
+    /// # 参数
+    ///
+    /// * `books` - 查找订单薄的函数
+    /// * `depth` - 合成订单薄每侧最多的档位数
+    pub fn build<'a>(&self, books: impl Fn(&str, &str) -> Option<&'a OrderBook>, depth: usize) -> Option<OrderBook> {
+        let first = books(&self.first.venue, &self.first.symbol)?;
+        let second = books(&self.second.venue, &self.second.symbol)?;
+        let (first_bids, first_asks) = leg_levels(first, self.first.inverted);
+        let (second_bids, second_asks) = leg_levels(second, self.second.inverted);
+        let bids = multiply_levels(&first_bids, &second_bids, depth);
+        let asks = multiply_levels(&first_asks, &second_asks, depth);
+        Some(OrderBook::from_levels(0, &bids, &asks))
+    }
+}
+
+/// 取一条腿的买卖盘（均按从优到劣排列），取倒数时买卖盘互换
+///
+/// 倒数后的价格为 1/价格，数量换算为原计价资产数量（价格 × 数量）
+fn leg_levels(book: &OrderBook, inverted: bool) -> (Levels, Levels) {
+    if !inverted {
+        return (book.bids_list(), book.asks_list());
+    }
+    let invert = |(price, quantity): (Decimal, Decimal)| (Decimal::ONE / price, price * quantity);
+    let bids = book.asks_list().into_iter().filter(|(price, _)| !price.is_zero()).map(invert).collect();
+    let asks = book.bids_list().into_iter().filter(|(price, _)| !price.is_zero()).map(invert).collect();
+    (bids, asks)
+}
+
+/// 两条腿同一侧逐档相乘
+///
+/// 第一条腿每单位基础资产换得 `价格` 单位中间资产，第二条腿再按自身档位换出计价资产，
+/// 每个合成档位的数量取两条腿剩余流动性的较小值（以基础资产计）
+fn multiply_levels(first: &[(Decimal, Decimal)], second: &[(Decimal, Decimal)], depth: usize) -> Levels {
+    let mut levels: Levels = Vec::new();
+    let mut first = first.iter().copied();
+    let mut second = second.iter().copied();
+    let mut a = first.next();
+    let mut b = second.next();
+    while let (Some((first_price, first_quantity)), Some((second_price, second_quantity))) = (a, b) {
+        if first_price.is_zero() {
+            break;
+        }
+        // 第二条腿剩余的中间资产最多能承接的基础资产数量，每轮至少用完一条腿的一个档位
+        let second_capacity = second_quantity / first_price;
+        let (quantity, first_done, second_done) = match first_quantity.cmp(&second_capacity) {
+            Ordering::Less => (first_quantity, true, false),
+            Ordering::Greater => (second_capacity, false, true),
+            Ordering::Equal => (first_quantity, true, true),
+        };
+        let price = first_price * second_price;
+        match levels.last_mut() {
+            Some(last) if last.0 == price => last.1 += quantity,
+            _ => {
+                if levels.len() == depth {
+                    break;
+                }
+                levels.push((price, quantity));
+            }
+        }
+
+        a = if first_done { first.next() } else { Some((first_price, first_quantity - quantity)) };
+        b = if second_done { second.next() } else { Some((second_price, second_quantity - quantity * first_price)) };
+    }
+    levels
+}
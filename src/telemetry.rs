@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::UNIX_EPOCH;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::{global, KeyValue};
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::TracerProvider;
+use opentelemetry_sdk::{runtime, Resource};
+use tracing::Level;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+use crate::latency::now_millis;
+use crate::recorder::{utc_millis_label, HOUR_MS};
+
+/// 上报 OTLP 时的服务名
+const SERVICE_NAME: &str = "order_book";
+/// 默认保留的已轮转日志文件数
+pub const DEFAULT_LOG_KEEP: usize = 7;
+
+/// OTLP 导出的运行状态，丢弃时把缓冲的 span 发送出去
+#[derive(Debug)]
+pub struct OtlpGuard {
+    provider: TracerProvider,
+    /// 导出器在这个运行时中批量发送，需要与 provider 一起保留
+    _runtime: tokio::runtime::Runtime,
+}
+
+impl Drop for OtlpGuard {
+    fn drop(&mut self) {
+        let _ = self.provider.shutdown();
+    }
+}
+
+/// 日志和链路追踪的后台输出，在 main 中保留到退出，丢弃时写完缓冲的日志和 span
+#[derive(Debug)]
+pub struct TelemetryGuard {
+    _log_file: Option<WorkerGuard>,
+    _otlp: Option<OtlpGuard>,
+}
+
+/// 日志文件按时间轮转的周期（UTC）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogRotation {
+    Never,
+    Hourly,
+    Daily,
+}
+
+impl LogRotation {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "never" => Some(LogRotation::Never),
+            "hourly" => Some(LogRotation::Hourly),
+            "daily" => Some(LogRotation::Daily),
+            _ => None,
+        }
+    }
+
+    /// 时间 `millis` 所在周期的序号，不按时间轮转时为 0
+    fn period(&self, millis: u64) -> u64 {
+        match self {
+            LogRotation::Never => 0,
+            LogRotation::Hourly => millis / HOUR_MS,
+            LogRotation::Daily => millis / (24 * HOUR_MS),
+        }
+    }
+}
+
+/// JSON 日志文件的配置
+#[derive(Debug, Clone)]
+pub struct LogFile {
+    pub path: PathBuf,
+    pub rotation: LogRotation,
+    /// 文件超过该字节数时轮转，None 表示不限制
+    pub max_size: Option<u64>,
+    /// 保留的已轮转文件数，0 表示全部保留
+    pub keep: usize,
+}
+
+impl LogFile {
+    /// 每天轮转，不限制大小，保留 [`DEFAULT_LOG_KEEP`] 个已轮转文件
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        LogFile {
+            path: path.into(),
+            rotation: LogRotation::Daily,
+            max_size: None,
+            keep: DEFAULT_LOG_KEEP,
+        }
+    }
+}
+
+/// 按时间和大小轮转的文件
+///
+/// 轮转时把当前文件改名为 `文件名.UTC时间`（例如 `order_book.log.20240101-000000.125`），再创建新文件，
+/// 并删除超出保留数量的最旧文件
+#[derive(Debug)]
+struct RotatingFile {
+    config: LogFile,
+    file: File,
+    size: u64,
+    period: u64,
+}
+
+impl RotatingFile {
+    fn open(config: LogFile) -> io::Result<Self> {
+        if let Some(dir) = config.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&config.path)?;
+        let metadata = file.metadata()?;
+        // 续写已有文件时按其最后修改时间判断周期，重启后跨周期的文件在第一次写入时轮转
+        let modified = metadata.modified().ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .filter(|_| metadata.len() > 0)
+            .unwrap_or_else(now_millis);
+        Ok(RotatingFile { period: config.rotation.period(modified), size: metadata.len(), file, config })
+    }
+
+    fn rotate(&mut self, now: u64) -> io::Result<()> {
+        self.file.flush()?;
+        let Some(name) = self.config.path.file_name().and_then(|name| name.to_str()).map(str::to_string) else {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "日志文件名无效"));
+        };
+        fs::rename(&self.config.path, self.config.path.with_file_name(format!("{}.{}", name, utc_millis_label(now))))?;
+        self.file = OpenOptions::new().create(true).append(true).open(&self.config.path)?;
+        self.size = 0;
+        if self.config.keep > 0 {
+            self.prune(&name)?;
+        }
+        Ok(())
+    }
+
+    /// 删除超出保留数量的已轮转文件，文件名中的时间可以按字符串排序
+    fn prune(&self, name: &str) -> io::Result<()> {
+        let dir = match self.config.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            Some(dir) => dir.to_path_buf(),
+            None => PathBuf::from("."),
+        };
+        let prefix = format!("{}.", name);
+        let mut rotated: Vec<_> = fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_str().is_some_and(|file_name| file_name.starts_with(&prefix)))
+            .map(|entry| entry.path())
+            .collect();
+        rotated.sort();
+        let excess = rotated.len().saturating_sub(self.config.keep);
+        for path in &rotated[..excess] {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let now = now_millis();
+        let period = self.config.rotation.period(now);
+        let oversized = self.config.max_size.is_some_and(|max_size| self.size > 0 && self.size + buf.len() as u64 > max_size);
+        if period != self.period || oversized {
+            self.period = period;
+            self.rotate(now)?;
+        }
+        self.file.write_all(buf)?;
+        self.size += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// 日志级别由环境变量 `RUST_LOG` 控制，默认 info
+fn env_filter() -> EnvFilter {
+    EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"))
+}
+
+/// 创建 OTLP gRPC 导出器，导出器和批量发送任务需要在运行时中创建
+fn otlp_provider(endpoint: &str) -> Result<OtlpGuard, Box<dyn Error>> {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()?;
+    let provider = {
+        let _enter = runtime.enter();
+        let exporter = SpanExporter::builder()
+            .with_tonic()
+            .with_endpoint(endpoint)
+            .build()?;
+        TracerProvider::builder()
+            .with_batch_exporter(exporter, runtime::Tokio)
+            .with_resource(Resource::new([KeyValue::new("service.name", SERVICE_NAME)]))
+            .build()
+    };
+    Ok(OtlpGuard { provider, _runtime: runtime })
+}
+
+/// 初始化日志和链路追踪，整个进程调用一次
+///
+/// 日志级别由环境变量 `RUST_LOG` 控制，默认 info。提供 `log_file` 时日志以 JSON 行写入该文件（在后台线程写入，
+/// 按配置轮转），不再输出到标准输出，标准输出只留给订单薄、查询结果等数据输出；没有日志文件时，
+/// `console` 为 false 则丢弃日志（终端界面占用终端时）。
+/// 提供 `otlp_endpoint`（例如 `http://127.0.0.1:4317`）时，本 crate 的 span 通过 OTLP gRPC 发送，不受 `RUST_LOG` 限制。
+/// 每条行情消息是一个 `update` span，下分 `receive`（行情线程收到消息帧到交给主循环）、`parse`、`apply`（应用到订单薄）
+/// 和 `publish`（分析和各输出），在 Jaeger、Tempo 中可以看到消息在进程内各阶段的耗时和排队时间
+pub fn init(otlp_endpoint: Option<&str>, log_file: Option<LogFile>, console: bool) -> Result<TelemetryGuard, Box<dyn Error>> {
+    let (stdout, file, log_guard) = match log_file {
+        Some(log_file) => {
+            let (writer, guard) = tracing_appender::non_blocking(RotatingFile::open(log_file)?);
+            let layer = tracing_subscriber::fmt::layer()
+                .json()
+                .with_writer(writer)
+                .with_filter(env_filter());
+            (None, Some(layer), Some(guard))
+        }
+        None if console => (Some(tracing_subscriber::fmt::layer().with_filter(env_filter())), None, None),
+        None => (None, None, None),
+    };
+
+    let otlp = otlp_endpoint.map(otlp_provider).transpose()?;
+    // 只导出本 crate 的 span，导出器自身（tonic、hyper）的 span 不能再被导出
+    let otel = otlp.as_ref().map(|otlp| {
+        tracing_opentelemetry::layer()
+            .with_tracer(otlp.provider.tracer(SERVICE_NAME))
+            .with_filter(Targets::new().with_target(SERVICE_NAME, Level::DEBUG))
+    });
+    if otlp.is_some() {
+        global::set_text_map_propagator(TraceContextPropagator::new());
+    }
+    tracing_subscriber::registry().with(stdout).with(file).with(otel).try_init()?;
+    Ok(TelemetryGuard { _log_file: log_guard, _otlp: otlp })
+}
+
+/// 当前 span 的 W3C Trace Context（`traceparent`、`tracestate`），写入消息头后下游可以接续同一条链路；
+/// 没有启用 OTLP 时为空
+pub fn trace_headers() -> HashMap<String, String> {
+    let context = tracing::Span::current().context();
+    let mut headers = HashMap::new();
+    global::get_text_map_propagator(|propagator| propagator.inject_context(&context, &mut headers));
+    headers
+}
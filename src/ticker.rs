@@ -0,0 +1,100 @@
+use std::error::Error;
+use rust_decimal::Decimal;
+
+use crate::binance::{MiniTickerEvent, TickerEvent};
+
+/// 订阅的24小时行情流类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TickerStream {
+    /// 不订阅
+    None,
+    /// `@miniTicker`
+    Mini,
+    /// `@ticker`
+    Full,
+}
+
+impl TickerStream {
+    /// 从命令行参数解析
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "none" => Some(TickerStream::None),
+            "mini" => Some(TickerStream::Mini),
+            "full" => Some(TickerStream::Full),
+            _ => None,
+        }
+    }
+
+    /// 流名称后缀
+    pub fn stream_name(&self) -> Option<&'static str> {
+        match self {
+            TickerStream::None => None,
+            TickerStream::Mini => Some("miniTicker"),
+            TickerStream::Full => Some("ticker"),
+        }
+    }
+}
+
+/// 单个交易对的24小时统计
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ticker24h {
+    /// 事件时间（毫秒）
+    pub event_time: u64,
+    /// 最新成交价
+    pub last_price: Decimal,
+    /// 24小时开盘价
+    pub open: Decimal,
+    /// 24小时最高价
+    pub high: Decimal,
+    /// 24小时最低价
+    pub low: Decimal,
+    /// 24小时成交量（基础资产）
+    pub volume: Decimal,
+    /// 24小时成交额（计价资产）
+    pub quote_volume: Decimal,
+    /// 24小时成交笔数，仅完整行情提供
+    pub trades: Option<u64>,
+}
+
+impl Ticker24h {
+    /// 从完整行情推送创建
+    pub fn from_ticker(event: &TickerEvent) -> Result<Self, Box<dyn Error>> {
+        Ok(Ticker24h {
+            event_time: event.E,
+            last_price: event.c.parse::<Decimal>()?,
+            open: event.o.parse::<Decimal>()?,
+            high: event.h.parse::<Decimal>()?,
+            low: event.l.parse::<Decimal>()?,
+            volume: event.v.parse::<Decimal>()?,
+            quote_volume: event.q.parse::<Decimal>()?,
+            trades: Some(event.n),
+        })
+    }
+
+    /// 从精简行情推送创建
+    pub fn from_mini_ticker(event: &MiniTickerEvent) -> Result<Self, Box<dyn Error>> {
+        Ok(Ticker24h {
+            event_time: event.E,
+            last_price: event.c.parse::<Decimal>()?,
+            open: event.o.parse::<Decimal>()?,
+            high: event.h.parse::<Decimal>()?,
+            low: event.l.parse::<Decimal>()?,
+            volume: event.v.parse::<Decimal>()?,
+            quote_volume: event.q.parse::<Decimal>()?,
+            trades: None,
+        })
+    }
+
+    /// 24小时价格变化
+    pub fn price_change(&self) -> Decimal {
+        self.last_price - self.open
+    }
+
+    /// 24小时价格变化百分比
+    pub fn price_change_percent(&self) -> Option<Decimal> {
+        if self.open.is_zero() {
+            return None;
+        }
+        Some(self.price_change() / self.open * Decimal::ONE_HUNDRED)
+    }
+}
@@ -0,0 +1,35 @@
+use std::error::Error;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::binance::AggTradeEvent;
+
+/// 一笔成交
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Trade {
+    /// 成交价格
+    pub price: Decimal,
+    /// 成交数量
+    pub quantity: Decimal,
+    /// 买方是否为挂单方，是则为主动卖出
+    pub buyer_is_maker: bool,
+    /// 成交时间（毫秒）
+    pub trade_time: u64,
+}
+
+impl Trade {
+    /// 从归集成交推送创建
+    pub fn from_agg_trade(event: &AggTradeEvent) -> Result<Self, Box<dyn Error>> {
+        Ok(Trade {
+            price: event.p.parse::<Decimal>()?,
+            quantity: event.q.parse::<Decimal>()?,
+            buyer_is_maker: event.m,
+            trade_time: event.T,
+        })
+    }
+
+    /// 是否为主动买入
+    pub fn is_buy(&self) -> bool {
+        !self.buyer_is_maker
+    }
+}
@@ -0,0 +1,201 @@
+use rust_decimal::Decimal;
+
+use crate::fees::FeeSchedule;
+use crate::order_book::OrderBook;
+
+/// 三角套利方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TriangularDirection {
+    /// 计价资产 -> 中间资产 -> 基础资产 -> 计价资产，例如 USDT -> BTC -> BNB -> USDT
+    Forward,
+    /// 计价资产 -> 基础资产 -> 中间资产 -> 计价资产，例如 USDT -> BNB -> BTC -> USDT
+    Reverse,
+}
+
+/// 一次三角套利机会
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TriangularOpportunity {
+    /// 交易所名称
+    pub venue: String,
+    /// 套利方向
+    pub direction: TriangularDirection,
+    /// 依次成交的三个交易对
+    pub path: [String; 3],
+    /// 投入的计价资产数量
+    pub start_notional: Decimal,
+    /// 走完一圈后得到的计价资产数量（已扣手续费）
+    pub end_notional: Decimal,
+    /// 利润占投入的基点数
+    pub profit_bps: Decimal,
+}
+
+/// 同一交易所内的三角套利扫描器
+///
+/// 以三个相关交易对为例：`cross` = BNBBTC，`mid` = BTCUSDT，`base` = BNBUSDT，
+/// 按实际深度逐档模拟两个方向各走一圈，每一步都扣除吃单手续费
+#[derive(Debug, Clone)]
+pub struct TriangularScanner {
+    venue: String,
+    /// 基础资产/中间资产，例如 BNBBTC
+    cross_symbol: String,
+    /// 中间资产/计价资产，例如 BTCUSDT
+    mid_symbol: String,
+    /// 基础资产/计价资产，例如 BNBUSDT
+    base_symbol: String,
+    /// 每圈投入的计价资产数量
+    start_notional: Decimal,
+    /// 手续费表，使用该交易所的吃单手续费
+    fees: FeeSchedule,
+    /// 产生提醒的最小利润（基点）
+    threshold_bps: Decimal,
+    /// 上次输出的利润：[正向, 反向]
+    last_emitted: [Option<Decimal>; 2],
+}
+
+impl TriangularScanner {
+    /// 创建扫描器
+    ///
+    /// # 参数
+    ///
+    /// * `venue` - 交易所名称，例如 "binance"
+    /// * `cross_symbol` - 基础资产/中间资产交易对，例如 BNBBTC
+    /// * `mid_symbol` - 中间资产/计价资产交易对，例如 BTCUSDT
+    /// * `base_symbol` - 基础资产/计价资产交易对，例如 BNBUSDT
+    /// * `start_notional` - 每圈投入的计价资产数量
+    pub fn new(venue: &str, cross_symbol: &str, mid_symbol: &str, base_symbol: &str, start_notional: Decimal) -> Self {
+        TriangularScanner {
+            venue: venue.to_string(),
+            cross_symbol: cross_symbol.to_string(),
+            mid_symbol: mid_symbol.to_string(),
+            base_symbol: base_symbol.to_string(),
+            start_notional,
+            fees: FeeSchedule::new(),
+            threshold_bps: Decimal::ZERO,
+            last_emitted: [None, None],
+        }
+    }
+
+    /// 设置手续费表
+    pub fn set_fee_schedule(&mut self, fees: FeeSchedule) {
+        self.fees = fees;
+    }
+
+    /// 设置产生提醒的最小利润（基点）
+    pub fn set_threshold_bps(&mut self, threshold_bps: Decimal) {
+        self.threshold_bps = threshold_bps;
+    }
+
+    /// 交易所名称
+    pub fn venue(&self) -> &str {
+        &self.venue
+    }
+
+    /// 判断交易对是否属于该三角
+    pub fn contains(&self, venue: &str, symbol: &str) -> bool {
+        venue == self.venue && [&self.cross_symbol, &self.mid_symbol, &self.base_symbol].iter().any(|s| *s == symbol)
+    }
+
+    /// 按当前深度计算两个方向走一圈的结果，订单薄不全或深度不足时跳过该方向
+    pub fn scan<'a>(&self, books: impl Fn(&str, &str) -> Option<&'a OrderBook>) -> Vec<TriangularOpportunity> {
+        let (Some(cross), Some(mid), Some(base)) = (
+            books(&self.venue, &self.cross_symbol),
+            books(&self.venue, &self.mid_symbol),
+            books(&self.venue, &self.base_symbol),
+        ) else {
+            return Vec::new();
+        };
+        let keep = Decimal::ONE - self.fees.taker_rate(&self.venue);
+
+        let mut opportunities = Vec::new();
+        // 正向：买中间资产，用中间资产买基础资产，卖基础资产
+        let forward = buy_with_quote(mid, self.start_notional)
+            .and_then(|mid_amount| buy_with_quote(cross, mid_amount * keep))
+            .and_then(|base_amount| sell_base(base, base_amount * keep))
+            .map(|end| end * keep);
+        if let Some(end_notional) = forward {
+            opportunities.push(self.opportunity(TriangularDirection::Forward, end_notional));
+        }
+        // 反向：买基础资产，卖基础资产换中间资产，卖中间资产
+        let reverse = buy_with_quote(base, self.start_notional)
+            .and_then(|base_amount| sell_base(cross, base_amount * keep))
+            .and_then(|mid_amount| sell_base(mid, mid_amount * keep))
+            .map(|end| end * keep);
+        if let Some(end_notional) = reverse {
+            opportunities.push(self.opportunity(TriangularDirection::Reverse, end_notional));
+        }
+        opportunities
+    }
+
+    /// 扫描并返回利润超过阈值、且与上次输出相比出现或变化的机会
+    pub fn update<'a>(&mut self, books: impl Fn(&str, &str) -> Option<&'a OrderBook>) -> Vec<TriangularOpportunity> {
+        let mut alerts = Vec::new();
+        let mut emitted = [None, None];
+        for opportunity in self.scan(books) {
+            if opportunity.profit_bps <= self.threshold_bps {
+                continue;
+            }
+            let index = opportunity.direction as usize;
+            emitted[index] = Some(opportunity.profit_bps);
+            if self.last_emitted[index] != emitted[index] {
+                alerts.push(opportunity);
+            }
+        }
+        self.last_emitted = emitted;
+        alerts
+    }
+
+    fn opportunity(&self, direction: TriangularDirection, end_notional: Decimal) -> TriangularOpportunity {
+        let path = match direction {
+            TriangularDirection::Forward => [self.mid_symbol.clone(), self.cross_symbol.clone(), self.base_symbol.clone()],
+            TriangularDirection::Reverse => [self.base_symbol.clone(), self.cross_symbol.clone(), self.mid_symbol.clone()],
+        };
+        let profit_bps = if self.start_notional.is_zero() {
+            Decimal::ZERO
+        } else {
+            (end_notional - self.start_notional) / self.start_notional * Decimal::from(10_000)
+        };
+        TriangularOpportunity {
+            venue: self.venue.clone(),
+            direction,
+            path,
+            start_notional: self.start_notional,
+            end_notional,
+            profit_bps,
+        }
+    }
+}
+
+/// 用计价资产沿卖盘逐档买入，返回得到的基础资产数量，深度不足时返回 None
+fn buy_with_quote(book: &OrderBook, notional: Decimal) -> Option<Decimal> {
+    let mut remaining = notional;
+    let mut bought = Decimal::ZERO;
+    for (price, quantity) in &book.asks {
+        if remaining.is_zero() {
+            break;
+        }
+        let level_notional = *price * *quantity;
+        if level_notional >= remaining {
+            bought += remaining / *price;
+            remaining = Decimal::ZERO;
+        } else {
+            bought += *quantity;
+            remaining -= level_notional;
+        }
+    }
+    remaining.is_zero().then_some(bought)
+}
+
+/// 沿买盘逐档卖出基础资产，返回得到的计价资产数量，深度不足时返回 None
+fn sell_base(book: &OrderBook, quantity: Decimal) -> Option<Decimal> {
+    let mut remaining = quantity;
+    let mut received = Decimal::ZERO;
+    for (price, level_quantity) in book.bids.iter().rev() {
+        if remaining.is_zero() {
+            break;
+        }
+        let fill = remaining.min(*level_quantity);
+        received += *price * fill;
+        remaining -= fill;
+    }
+    remaining.is_zero().then_some(received)
+}
@@ -0,0 +1,249 @@
+use std::io;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Alignment, Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, Tabs};
+use ratatui::{DefaultTerminal, Frame};
+use rust_decimal::Decimal;
+use tracing::error;
+
+use crate::latency::now_millis;
+use crate::order_book::Side;
+use crate::serve::health::{BookReadiness, Readiness};
+use crate::serve::{BookHub, BookView};
+
+/// 界面刷新间隔，也是等待按键的最长时间
+const REFRESH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 运行中的终端界面，丢弃时关闭界面并恢复终端
+#[derive(Debug)]
+pub struct Tui {
+    /// 用户退出或界面出错后为 true，置为 true 时界面线程也会结束
+    closed: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Tui {
+    /// 用户是否已退出界面，主循环据此结束
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for Tui {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// 在新线程中运行终端界面，显示 [`BookHub`] 中订单薄的深度阶梯
+///
+/// 界面占用整个终端（备用屏幕、原始模式）：顶部为各订单薄及其同步状态，下方为选中订单薄的最优价、中间价、价差、
+/// 最后更新 ID 和以中间价为中心的价格阶梯（卖单在上，买单在下）。按键：←/→ 或 Tab 切换订单薄，↑/↓、PgUp/PgDn 滚动，
+/// 空格回到中间价，q、Esc 或 Ctrl-C 退出。同步状态来自 `readiness`，没有登记的订单薄显示为未跟踪
+pub fn spawn_tui(hub: Arc<BookHub>, readiness: Arc<Readiness>) -> io::Result<Tui> {
+    let terminal = ratatui::try_init()?;
+    let closed = Arc::new(AtomicBool::new(false));
+    let thread = {
+        let closed = closed.clone();
+        thread::spawn(move || {
+            let result = run(terminal, App::new(hub, readiness), &closed);
+            ratatui::restore();
+            closed.store(true, Ordering::Relaxed);
+            if let Err(e) = result {
+                error!(error = %e, "终端界面出错");
+            }
+        })
+    };
+    Ok(Tui { closed, thread: Some(thread) })
+}
+
+fn run(mut terminal: DefaultTerminal, mut app: App, closed: &AtomicBool) -> io::Result<()> {
+    while !closed.load(Ordering::Relaxed) {
+        terminal.draw(|frame| app.draw(frame))?;
+        if event::poll(REFRESH_INTERVAL)?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+            && !app.handle_key(key)
+        {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// 界面状态
+struct App {
+    hub: Arc<BookHub>,
+    readiness: Arc<Readiness>,
+    /// 选中的订单薄序号
+    selected: usize,
+    /// 阶梯相对中间价滚动的行数，正数向高价方向
+    offset: isize,
+    /// 上一次绘制时阶梯的行数，翻页时使用
+    page: usize,
+}
+
+impl App {
+    fn new(hub: Arc<BookHub>, readiness: Arc<Readiness>) -> Self {
+        App { hub, readiness, selected: 0, offset: 0, page: 0 }
+    }
+
+    /// 登记的订单薄和已经收到更新的订单薄及其同步状态，按交易所和交易对排序
+    fn books(&self) -> Vec<(String, String, Option<BookReadiness>)> {
+        let mut books: Vec<_> = self.readiness.books(now_millis()).into_iter()
+            .map(|(venue, symbol, readiness)| (venue, symbol, Some(readiness)))
+            .collect();
+        for (venue, symbol) in self.hub.keys() {
+            if !books.iter().any(|(v, s, _)| *v == venue && *s == symbol) {
+                books.push((venue, symbol, None));
+            }
+        }
+        books.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+        books
+    }
+
+    /// 处理按键，返回 false 表示退出
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let books = self.books().len().max(1);
+        let page = (self.page / 2).max(1) as isize;
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+            KeyCode::Right | KeyCode::Tab | KeyCode::Char('l') => {
+                self.selected = (self.selected + 1) % books;
+                self.offset = 0;
+            }
+            KeyCode::Left | KeyCode::BackTab | KeyCode::Char('h') => {
+                self.selected = (self.selected + books - 1) % books;
+                self.offset = 0;
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.offset += 1,
+            KeyCode::Down | KeyCode::Char('j') => self.offset -= 1,
+            KeyCode::PageUp => self.offset += page,
+            KeyCode::PageDown => self.offset -= page,
+            KeyCode::Char(' ') | KeyCode::Home => self.offset = 0,
+            _ => {}
+        }
+        // 超出两侧档位后继续滚动没有意义
+        let limit = self.hub.depth() as isize;
+        self.offset = self.offset.clamp(-limit, limit);
+        true
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [tabs_area, header_area, ladder_area, help_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(4),
+            Constraint::Min(3),
+            Constraint::Length(1),
+        ]).areas(frame.area());
+
+        let books = self.books();
+        self.selected = self.selected.min(books.len().saturating_sub(1));
+        let titles: Vec<_> = books.iter()
+            .map(|(venue, symbol, readiness)| Line::styled(format!("{}:{}", venue, symbol), Style::new().fg(status(*readiness).1)))
+            .collect();
+        frame.render_widget(Tabs::new(titles).select(self.selected).highlight_style(Modifier::REVERSED), tabs_area);
+        frame.render_widget(
+            Paragraph::new("←/→ 切换订单薄  ↑/↓ PgUp/PgDn 滚动  空格 回到中间价  q 退出").style(Style::new().fg(Color::DarkGray)),
+            help_area,
+        );
+
+        let Some((venue, symbol, readiness)) = books.get(self.selected) else {
+            frame.render_widget(Paragraph::new("等待订单薄...").block(Block::bordered()), header_area);
+            return;
+        };
+        let view = self.hub.book(venue, symbol);
+        frame.render_widget(header(venue, symbol, *readiness, view.as_deref()), header_area);
+        match view {
+            Some(view) => self.draw_ladder(frame, &view, ladder_area),
+            None => frame.render_widget(Paragraph::new("等待订单薄...").block(Block::bordered().title("深度")), ladder_area),
+        }
+    }
+
+    fn draw_ladder(&mut self, frame: &mut Frame, view: &BookView, area: Rect) {
+        // 去掉边框和表头
+        self.page = area.height.saturating_sub(3) as usize;
+        let best_bid = view.bids.first().map(|(price, _)| *price);
+        let best_ask = view.asks.first().map(|(price, _)| *price);
+        let rows = ladder(view, self.page, self.offset).into_iter().map(|(side, price, quantity)| {
+            let best = Some(price) == best_bid || Some(price) == best_ask;
+            let style = match side {
+                Side::Bid => Style::new().fg(Color::Green),
+                Side::Ask => Style::new().fg(Color::Red),
+            };
+            let style = if best { style.add_modifier(Modifier::BOLD) } else { style };
+            let quantity = Cell::from(Line::from(quantity.to_string()).alignment(Alignment::Right));
+            let (bid, ask) = match side {
+                Side::Bid => (quantity, Cell::default()),
+                Side::Ask => (Cell::default(), quantity),
+            };
+            Row::new([bid, Cell::from(Line::from(price.to_string()).alignment(Alignment::Center)), ask]).style(style)
+        });
+        let head = Row::new(["买量", "价格", "卖量"].map(|title| Cell::from(Line::from(title).alignment(Alignment::Center))))
+            .style(Style::new().add_modifier(Modifier::BOLD));
+        let table = Table::new(rows, [Constraint::Ratio(1, 3); 3])
+            .header(head)
+            .block(Block::bordered().title("深度"));
+        frame.render_widget(table, area);
+    }
+}
+
+/// 同步状态的显示文字和颜色，None 表示没有登记
+fn status(readiness: Option<BookReadiness>) -> (&'static str, Color) {
+    match readiness {
+        Some(BookReadiness::Waiting) => ("等待快照", Color::Yellow),
+        Some(BookReadiness::Unsynced) => ("重新同步", Color::Red),
+        Some(BookReadiness::Stale) => ("无更新", Color::Yellow),
+        Some(BookReadiness::Ready) => ("已同步", Color::Green),
+        None => ("未跟踪", Color::Gray),
+    }
+}
+
+/// 选中订单薄的状态、最优价、中间价和价差
+fn header<'a>(venue: &str, symbol: &str, readiness: Option<BookReadiness>, view: Option<&BookView>) -> Paragraph<'a> {
+    let (status, color) = status(readiness);
+    let mut first = vec![
+        Span::styled(format!("{} {}", venue, symbol), Style::new().add_modifier(Modifier::BOLD)),
+        Span::raw("  状态: "),
+        Span::styled(status, Style::new().fg(color)),
+    ];
+    let mut lines = Vec::new();
+    if let Some(view) = view {
+        first.push(Span::raw(format!("  最后更新 ID: {}  {} ms 前", view.last_update_id, now_millis().saturating_sub(view.time))));
+        let bbo = |levels: &[(Decimal, Decimal)]| levels.first()
+            .map(|(price, quantity)| format!("{} ({})", price, quantity))
+            .unwrap_or_else(|| "-".to_string());
+        let mut prices = format!("买一: {}  卖一: {}", bbo(&view.bids), bbo(&view.asks));
+        if let (Some((bid, _)), Some((ask, _))) = (view.bids.first(), view.asks.first()) {
+            let mid = (bid + ask) / Decimal::TWO;
+            let spread = ask - bid;
+            let bps = if mid.is_zero() { Decimal::ZERO } else { spread / mid * Decimal::from(10_000) };
+            prices.push_str(&format!("  中间价: {}  价差: {} ({} bps)", mid.normalize(), spread, bps.round_dp(2)));
+        }
+        lines.push(Line::from(prices));
+    }
+    lines.insert(0, Line::from(first));
+    Paragraph::new(lines).block(Block::new().borders(Borders::TOP | Borders::BOTTOM))
+}
+
+/// 以中间价为中心的 `height` 行价格阶梯 (方向, 价格, 数量)，卖单在上、买单在下，价格均为降序；
+/// `offset` 为正时向高价方向滚动，到达保存的档位末端后不再移动
+fn ladder(view: &BookView, height: usize, offset: isize) -> Vec<(Side, Decimal, Decimal)> {
+    let rows: Vec<_> = view.asks.iter().rev().map(|(price, quantity)| (Side::Ask, *price, *quantity))
+        .chain(view.bids.iter().map(|(price, quantity)| (Side::Bid, *price, *quantity)))
+        .collect();
+    // 买一所在的行
+    let center = view.asks.len() as isize;
+    let start = (center - height as isize / 2 - offset).clamp(0, rows.len().saturating_sub(height) as isize) as usize;
+    rows.into_iter().skip(start).take(height).collect()
+}
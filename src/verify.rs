@@ -0,0 +1,163 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::Path;
+
+use crate::binance::Market;
+use crate::exchange::DepthKind;
+use crate::fixture::book_hash;
+use crate::manager::BookManager;
+use crate::order_book::OrderBook;
+use crate::recorder::capture::scan_capture;
+use crate::recorder::{ndjson, read_file, Record};
+
+/// 每个订单薄最多打印的序号断开次数
+const MAX_PRINTED_GAPS: usize = 10;
+
+/// 单个订单薄的覆盖统计
+#[derive(Debug, Clone, Default)]
+pub struct StreamStats {
+    /// 增量更新条数
+    pub deltas: usize,
+    /// 交易所快照条数
+    pub snapshots: usize,
+    pub keyframes: usize,
+    pub trades: usize,
+    /// 第一条和最后一条记录的接收时间
+    pub first_time: u64,
+    pub last_time: u64,
+    /// 序号断开或校验和不一致 (接收时间, 说明)
+    pub gaps: Vec<(u64, String)>,
+    /// 断开之后到下一个快照或关键帧之间没有有效订单薄的时间（毫秒）
+    pub uncovered_ms: u64,
+    /// 关键帧与重建的订单薄不一致的次数
+    pub keyframe_mismatches: usize,
+    /// 订单薄断开的时间，重新建立后清除
+    broken_since: Option<u64>,
+}
+
+/// 记录文件的检查结果
+#[derive(Debug, Default)]
+pub struct VerifyReport {
+    /// 能够解压和解码的块数，NDJSON 等没有块的格式为 None
+    pub blocks: Option<usize>,
+    pub records: usize,
+    /// 接收时间早于前一条记录的记录数
+    pub out_of_order: usize,
+    /// 文件结构问题：截断、损坏的块或行、索引不一致
+    pub problems: Vec<String>,
+    /// (交易所, 交易对) -> 覆盖统计
+    pub streams: BTreeMap<(String, String), StreamStats>,
+}
+
+impl VerifyReport {
+    /// 文件结构完整，且所有订单薄的序号连续、关键帧一致
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+            && self.streams.values().all(|stats| stats.gaps.is_empty() && stats.keyframe_mismatches == 0)
+    }
+
+    /// 打印检查结果
+    pub fn print(&self) {
+        match self.blocks {
+            Some(blocks) => println!("{} 个块，{} 条记录，{} 条乱序", blocks, self.records, self.out_of_order),
+            None => println!("{} 条记录，{} 条乱序", self.records, self.out_of_order),
+        }
+        for problem in &self.problems {
+            println!("  问题: {}", problem);
+        }
+        for ((venue, symbol), stats) in &self.streams {
+            println!("{} {}: 时间 {} - {}（{:.1} 秒），增量 {}，快照 {}，关键帧 {}，成交 {}",
+                     venue, symbol, stats.first_time, stats.last_time,
+                     stats.last_time.saturating_sub(stats.first_time) as f64 / 1000.0,
+                     stats.deltas, stats.snapshots, stats.keyframes, stats.trades);
+            if !stats.gaps.is_empty() || stats.keyframe_mismatches > 0 {
+                println!("  断开 {} 次，无有效订单薄 {:.1} 秒，关键帧不一致 {} 次",
+                         stats.gaps.len(), stats.uncovered_ms as f64 / 1000.0, stats.keyframe_mismatches);
+            }
+            for (time, message) in stats.gaps.iter().take(MAX_PRINTED_GAPS) {
+                println!("  {} {}", time, message);
+            }
+            if stats.gaps.len() > MAX_PRINTED_GAPS {
+                println!("  ……另有 {} 次断开", stats.gaps.len() - MAX_PRINTED_GAPS);
+            }
+        }
+        println!("{}", if self.is_ok() { "检查通过" } else { "检查未通过" });
+    }
+}
+
+/// 检查记录文件
+///
+/// 压缩记录逐块检查截断、损坏和索引一致性，NDJSON 逐行检查，其他格式只检查内容。
+/// 然后按文件中的顺序用与实时行情相同的订单薄逻辑重放所有深度记录，统计每个订单薄的
+/// 序号断开、断开后没有有效订单薄的时间，以及关键帧与重建结果是否一致
+pub fn verify_file(path: impl AsRef<Path>) -> Result<VerifyReport, Box<dyn Error>> {
+    let path = path.as_ref();
+    let mut report = VerifyReport::default();
+    let records = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("zcap") => {
+            let scan = scan_capture(path)?;
+            report.blocks = Some(scan.blocks);
+            report.problems = scan.problems;
+            scan.records
+        }
+        Some("ndjson") => {
+            let (records, problems) = ndjson::scan_records(path)?;
+            report.problems = problems;
+            records
+        }
+        _ => read_file(path)?,
+    };
+    report.records = records.len();
+
+    let mut manager = BookManager::new(Market::Spot, &[]);
+    let mut last_time = 0;
+    for record in records {
+        let time = record.recv_time();
+        if time < last_time {
+            report.out_of_order += 1;
+        }
+        last_time = last_time.max(time);
+
+        let (venue, symbol) = match &record {
+            Record::Update { venue, message, .. } | Record::Keyframe { venue, message, .. } => (venue.clone(), message.symbol.clone()),
+            Record::Trade { venue, symbol, .. } => (venue.clone(), symbol.clone()),
+            Record::Raw { .. } | Record::Binary { .. } => continue,
+        };
+        let stats = report.streams.entry((venue.clone(), symbol.clone())).or_default();
+        if stats.first_time == 0 {
+            stats.first_time = time;
+        }
+        stats.last_time = stats.last_time.max(time);
+        match &record {
+            Record::Update { message, .. } if message.kind == DepthKind::Snapshot => stats.snapshots += 1,
+            Record::Update { .. } => stats.deltas += 1,
+            Record::Keyframe { message, .. } => {
+                stats.keyframes += 1;
+                let sequence = message.continuity.sequence().unwrap_or_default();
+                let keyframe = OrderBook::from_levels(sequence, &message.bids, &message.asks);
+                if manager.venue_book(&venue, &symbol).is_some_and(|book| book_hash(book) != book_hash(&keyframe)) {
+                    stats.keyframe_mismatches += 1;
+                }
+            }
+            Record::Trade { .. } => stats.trades += 1,
+            Record::Raw { .. } | Record::Binary { .. } => {}
+        }
+
+        if let Err(e) = manager.replay_record(record) {
+            stats.gaps.push((time, e.to_string()));
+            stats.broken_since.get_or_insert(time);
+        }
+        if let Some(since) = stats.broken_since
+            && manager.venue_book(&venue, &symbol).is_some()
+        {
+            stats.uncovered_ms += time.saturating_sub(since);
+            stats.broken_since = None;
+        }
+    }
+    for stats in report.streams.values_mut() {
+        if let Some(since) = stats.broken_since.take() {
+            stats.uncovered_ms += stats.last_time.saturating_sub(since);
+        }
+    }
+    Ok(report)
+}
@@ -0,0 +1,333 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+use serde::Deserialize;
+use serde_json::json;
+use tungstenite::http::Uri;
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{client_tls, Message, Utf8Bytes, WebSocket};
+use tracing::{info, warn};
+
+use crate::binance::{get_depth_snapshot, DepthSnapshot, Market};
+use crate::exchange::{is_timeout, set_read_timeout};
+
+/// 建立连接和等待响应的超时时间，超时后返回错误，由调用方改用 REST 接口
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+/// 等待响应时最多跳过的其他请求的响应条数
+const MAX_SKIPPED_RESPONSES: usize = 16;
+/// 获取快照最多使用每分钟请求权重上限的比例，其余留给发现交易对、对时等请求
+const SNAPSHOT_WEIGHT_SHARE: f64 = 0.5;
+
+/// WebSocket API 响应
+#[derive(Debug, Deserialize)]
+struct WsApiResponse {
+    id: Option<String>,
+    status: u16,
+    result: Option<DepthSnapshot>,
+    error: Option<WsApiError>,
+}
+
+/// WebSocket API 错误信息
+#[derive(Debug, Deserialize)]
+struct WsApiError {
+    code: i64,
+    msg: String,
+}
+
+/// 币安 WebSocket API 客户端
+///
+/// 保持一条长连接，按请求-响应方式获取深度快照，省去每次 REST 请求的建连开销
+pub struct WsApiClient {
+    market: Market,
+    socket: Option<WebSocket<MaybeTlsStream<TcpStream>>>,
+    next_id: u64,
+}
+
+impl WsApiClient {
+    /// 创建客户端，首次请求时才建立连接
+    pub fn new(market: Market) -> Self {
+        WsApiClient { market, socket: None, next_id: 1 }
+    }
+
+    /// 请求深度快照
+    ///
+    /// 连接断开、出错或超过 [`REQUEST_TIMEOUT`] 没有响应时丢弃连接，下次请求重新连接
+    pub fn depth(&mut self, symbol: &str, limit: u32) -> Result<DepthSnapshot, Box<dyn Error>> {
+        let result = self.request_depth(symbol, limit);
+        if result.is_err() {
+            self.socket = None;
+        }
+        result
+    }
+
+    fn request_depth(&mut self, symbol: &str, limit: u32) -> Result<DepthSnapshot, Box<dyn Error>> {
+        let id = format!("depth-{}", self.next_id);
+        self.next_id += 1;
+        let request = json!({
+            "id": id,
+            "method": "depth",
+            "params": {
+                "symbol": symbol,
+                "limit": limit.min(self.market.max_depth_limit()),
+            }
+        }).to_string();
+
+        let socket = match self.socket {
+            Some(ref mut socket) => socket,
+            None => {
+                info!(url = self.market.ws_api_url(), "正在连接 WebSocket API");
+                self.socket.insert(connect_with_timeout(self.market.ws_api_url())?)
+            }
+        };
+
+        socket.send(Message::Text(Utf8Bytes::from(request)))?;
+        let deadline = Instant::now() + REQUEST_TIMEOUT;
+        let mut skipped = 0;
+        loop {
+            // 每次读取只等待到截止时间，超时后改用 REST 接口
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err("WebSocket API 响应超时".into());
+            }
+            set_read_timeout(socket, remaining)?;
+            let message = match socket.read() {
+                Ok(message) => message,
+                Err(tungstenite::Error::Io(e)) if is_timeout(&e) => return Err("WebSocket API 响应超时".into()),
+                Err(e) => return Err(e.into()),
+            };
+            match message {
+                Message::Text(msg) => {
+                    let response: WsApiResponse = serde_json::from_str(&msg)?;
+                    if response.id.as_deref() != Some(id.as_str()) {
+                        skipped += 1;
+                        if skipped > MAX_SKIPPED_RESPONSES {
+                            return Err(format!("WebSocket API 连续 {} 条响应都不是请求 {} 的响应", skipped, id).into());
+                        }
+                        continue;
+                    }
+                    if let Some(error) = response.error {
+                        return Err(format!("WebSocket API 请求失败: {} {} {}", response.status, error.code, error.msg).into());
+                    }
+                    return response.result.ok_or_else(|| "WebSocket API 响应缺少结果".into());
+                }
+                Message::Close(_) => return Err("WebSocket API 连接已关闭".into()),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// 建立 WebSocket 连接，TCP 连接、握手和之后的读写都有超时
+fn connect_with_timeout(url: &str) -> Result<WebSocket<MaybeTlsStream<TcpStream>>, Box<dyn Error>> {
+    let uri: Uri = url.parse()?;
+    let host = uri.host().ok_or_else(|| format!("地址缺少主机名: {}", url))?;
+    let port = uri.port_u16().unwrap_or(if uri.scheme_str() == Some("ws") { 80 } else { 443 });
+    let addr = (host, port).to_socket_addrs()?.next().ok_or_else(|| format!("无法解析主机名: {}", host))?;
+    let stream = TcpStream::connect_timeout(&addr, REQUEST_TIMEOUT)?;
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+    let (socket, _) = client_tls(url, stream).map_err(|e| format!("WebSocket API 握手失败: {}", e))?;
+    Ok(socket)
+}
+
+/// 深度快照来源
+pub enum SnapshotSource {
+    /// REST 接口
+    Rest,
+    /// WebSocket API 长连接
+    WsApi(Box<WsApiClient>),
+    /// 固定的快照，每次请求都返回同一份，用于离线重放实时同步流程
+    Fixed(DepthSnapshot),
+}
+
+impl SnapshotSource {
+    /// 从命令行参数解析
+    pub fn parse(market: Market, name: &str) -> Option<Self> {
+        match name {
+            "rest" => Some(SnapshotSource::Rest),
+            "ws" | "ws-api" => Some(SnapshotSource::WsApi(Box::new(WsApiClient::new(market)))),
+            _ => None,
+        }
+    }
+
+    /// 获取深度快照，阻塞到取回为止，事件循环通过 [`SnapshotFetcher`] 在后台线程中调用
+    ///
+    /// WebSocket API 请求失败或超时时改用 REST 接口
+    pub fn fetch(&mut self, market: Market, symbol: &str, limit: u32) -> Result<DepthSnapshot, Box<dyn Error>> {
+        match self {
+            SnapshotSource::Rest => get_depth_snapshot(market, symbol, limit),
+            SnapshotSource::WsApi(client) => client.depth(symbol, limit).or_else(|e| {
+                warn!(symbol, error = %e, "WebSocket API 获取深度快照失败，改用 REST 接口");
+                get_depth_snapshot(market, symbol, limit)
+            }),
+            SnapshotSource::Fixed(snapshot) => Ok(snapshot.clone()),
+        }
+    }
+}
+
+/// 取回的深度快照：交易对和快照，失败时为错误信息
+pub type SnapshotResult = (String, Result<DepthSnapshot, String>);
+
+/// 在后台线程中获取深度快照，事件循环只提交请求和取回结果，不等待网络
+///
+/// REST 和 WebSocket API（包括失败后改用的 REST）都在后台线程中按请求顺序执行，第一次请求时才启动线程；
+/// 固定快照直接作为结果返回，离线重放时结果与请求顺序一致
+pub struct SnapshotFetcher {
+    market: Market,
+    /// 后台线程启动前保存快照来源
+    source: Option<SnapshotSource>,
+    /// 发往后台线程的请求 (交易对, 档位数)
+    requests: Option<Sender<(String, u32)>>,
+    results_tx: Sender<SnapshotResult>,
+    results: Receiver<SnapshotResult>,
+    /// 已请求、结果尚未取回的交易对
+    pending: HashSet<String>,
+}
+
+impl SnapshotFetcher {
+    pub fn new(market: Market, source: SnapshotSource) -> Self {
+        let (results_tx, results) = mpsc::channel();
+        SnapshotFetcher { market, source: Some(source), requests: None, results_tx, results, pending: HashSet::new() }
+    }
+
+    /// 交易对的快照是否已请求、结果尚未取回
+    pub fn is_pending(&self, symbol: &str) -> bool {
+        self.pending.contains(symbol)
+    }
+
+    /// 请求交易对的深度快照，已有未完成的请求时不重复请求
+    pub fn request(&mut self, symbol: &str, limit: u32) {
+        if !self.pending.insert(symbol.to_string()) {
+            return;
+        }
+        if let Some(SnapshotSource::Fixed(snapshot)) = &self.source {
+            let _ = self.results_tx.send((symbol.to_string(), Ok(snapshot.clone())));
+            return;
+        }
+        let requests = match self.requests.as_ref() {
+            Some(requests) => requests,
+            None => {
+                let source = self.source.take().unwrap_or(SnapshotSource::Rest);
+                self.requests.insert(spawn_fetcher(self.market, source, self.results_tx.clone()))
+            }
+        };
+        let _ = requests.send((symbol.to_string(), limit));
+    }
+
+    /// 取出已完成的请求结果，不等待
+    pub fn poll(&mut self) -> Vec<SnapshotResult> {
+        let results: Vec<SnapshotResult> = self.results.try_iter().collect();
+        for (symbol, _) in &results {
+            self.pending.remove(symbol);
+        }
+        results
+    }
+}
+
+impl std::fmt::Debug for SnapshotFetcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SnapshotFetcher")
+            .field("source", &self.source)
+            .field("started", &self.requests.is_some())
+            .field("pending", &self.pending)
+            .finish()
+    }
+}
+
+/// 请求权重预算，按每分钟上限匀速恢复
+///
+/// 大量交易对同时启动或重连时，快照请求按权重排队发出，避免超过上限被封禁 IP
+#[derive(Debug)]
+struct WeightBudget {
+    /// 每分钟可用的权重
+    capacity: f64,
+    available: f64,
+    refilled_at: Instant,
+}
+
+impl WeightBudget {
+    fn new(capacity: f64) -> Self {
+        WeightBudget { capacity, available: capacity, refilled_at: Instant::now() }
+    }
+
+    /// 按经过的时间恢复权重
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.refilled_at).as_secs_f64();
+        self.available = (self.available + elapsed * self.capacity / 60.0).min(self.capacity);
+        self.refilled_at = now;
+    }
+
+    /// 扣除权重，不足时返回需要等待的时间
+    fn try_acquire(&mut self, weight: u32, now: Instant) -> Option<Duration> {
+        self.refill(now);
+        // 单个请求的权重超过预算时，等预算恢复满即可发出
+        let weight = (weight as f64).min(self.capacity);
+        if self.available >= weight {
+            self.available -= weight;
+            return None;
+        }
+        Some(Duration::from_secs_f64((weight - self.available) * 60.0 / self.capacity))
+    }
+
+    /// 扣除权重，不足时阻塞到恢复足够的权重
+    fn acquire(&mut self, weight: u32) {
+        while let Some(wait) = self.try_acquire(weight, Instant::now()) {
+            thread::sleep(wait);
+        }
+    }
+}
+
+/// 在新线程中依次处理快照请求，事件循环丢弃请求发送端后退出
+///
+/// 每个请求发出前按档位数扣除 [`WeightBudget`]，预算不足时等待
+fn spawn_fetcher(market: Market, mut source: SnapshotSource, results: Sender<SnapshotResult>) -> Sender<(String, u32)> {
+    let (requests_tx, requests) = mpsc::channel::<(String, u32)>();
+    thread::spawn(move || {
+        let mut budget = WeightBudget::new(market.request_weight_limit() as f64 * SNAPSHOT_WEIGHT_SHARE);
+        for (symbol, limit) in requests {
+            let limit = limit.min(market.max_depth_limit());
+            let weight = market.depth_weight(limit);
+            if budget.try_acquire(weight, Instant::now()).is_some() {
+                info!(symbol, weight, "快照请求权重预算不足，等待恢复");
+                budget.acquire(weight);
+            }
+            let snapshot = source.fetch(market, &symbol, limit).map_err(|e| e.to_string());
+            if results.send((symbol, snapshot)).is_err() {
+                return;
+            }
+        }
+    });
+    requests_tx
+}
+
+impl std::fmt::Debug for SnapshotSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotSource::Rest => write!(f, "Rest"),
+            SnapshotSource::WsApi(client) => write!(f, "WsApi({})", client.market.ws_api_url()),
+            SnapshotSource::Fixed(snapshot) => write!(f, "Fixed({})", snapshot.lastUpdateId),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weight_budget_waits_for_refill() {
+        let start = Instant::now();
+        let mut budget = WeightBudget::new(600.0);
+        budget.refilled_at = start;
+        assert_eq!(budget.try_acquire(250, start), None);
+        assert_eq!(budget.try_acquire(250, start), None);
+        // 剩余 100，每秒恢复 10
+        assert_eq!(budget.try_acquire(250, start), Some(Duration::from_secs(15)));
+        assert_eq!(budget.try_acquire(250, start + Duration::from_secs(15)), None);
+        // 超过预算的请求等预算恢复满后发出
+        assert_eq!(budget.try_acquire(1000, start + Duration::from_secs(15)), Some(Duration::from_secs(60)));
+    }
+}